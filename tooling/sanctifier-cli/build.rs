@@ -1,5 +1,8 @@
 use regex::Regex;
+use sha2::{Digest, Sha256};
 use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
 use vergen::EmitBuilder;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -22,5 +25,39 @@ fn main() -> Result<(), Box<dyn Error>> {
         .all_sysinfo()
         .emit()?;
 
+    // 3. Fingerprint the sources, so the analysis cache misses when the
+    //    passes run here change (see `SANCTIFIER_CLI_FINGERPRINT`)
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut files = Vec::new();
+    collect_files(&src, &mut files);
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in &files {
+        let name = file.strip_prefix(&src).unwrap_or(file);
+        hasher.update([0]);
+        hasher.update(name.to_string_lossy().replace('\\', "/"));
+        hasher.update([0]);
+        hasher.update(fs::read(file)?);
+    }
+    println!("cargo:rerun-if-changed=src");
+    println!(
+        "cargo:rustc-env=SANCTIFIER_CLI_FINGERPRINT={:x}",
+        hasher.finalize()
+    );
+
     Ok(())
 }
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
@@ -1,24 +1,30 @@
+use crate::commands::cache::DiskCache;
+use crate::commands::changed_lines::ChangedLines;
 use crate::commands::color as c;
+use crate::commands::fix::package_dir;
+use crate::commands::smt::{self, SmtOptions, Solver};
 use crate::telemetry::{self, AnalysisTelemetry};
 use crate::vulndb::{VulnDatabase, VulnMatch};
 use clap::Args;
 use colored::*;
 #[allow(unused_imports)]
 use rayon::prelude::*;
-use sanctifier_core::finding_codes;
+use sanctifier_core::finding_codes::{self, FindingSeverity};
 use sanctifier_core::rules::RuleRegistry;
+use sanctifier_core::suppression::{self, SuppressedViolation, SuppressionMap};
 use sanctifier_core::{Analyzer, SanctifyConfig};
-use sha2::{Digest, Sha256};
 #[allow(unused_imports)]
 use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::warn;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 pub enum SeverityLevel {
+    Info,
     Low,
     Medium,
     High,
@@ -41,6 +47,7 @@ impl std::str::FromStr for SeverityLevel {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
+            "info" => Ok(Self::Info),
             "low" => Ok(Self::Low),
             "medium" => Ok(Self::Medium),
             "high" => Ok(Self::High),
@@ -50,6 +57,18 @@ impl std::str::FromStr for SeverityLevel {
     }
 }
 
+impl From<FindingSeverity> for SeverityLevel {
+    fn from(severity: FindingSeverity) -> Self {
+        match severity {
+            FindingSeverity::Critical => Self::Critical,
+            FindingSeverity::High => Self::High,
+            FindingSeverity::Medium => Self::Medium,
+            FindingSeverity::Low => Self::Low,
+            FindingSeverity::Info => Self::Info,
+        }
+    }
+}
+
 impl AnalysisProfile {
     pub fn as_str(self) -> &'static str {
         match self {
@@ -77,6 +96,10 @@ pub struct AnalyzeArgs {
 
     #[arg(short, long, default_value = "text")]
     pub format: String,
+    /// Add the catalogue of every finding code to --format json output; each
+    /// finding carries its own code either way
+    #[arg(long)]
+    pub include_error_codes: bool,
     /// Limit for ledger entry size in bytes
     #[arg(short, long, default_value = "64000")]
     pub limit: usize,
@@ -95,21 +118,56 @@ pub struct AnalyzeArgs {
     /// Return non-zero exit code when findings meet or exceed severity threshold
     #[arg(long)]
     pub exit_code: bool,
-    /// Minimum severity threshold for --exit-code (critical|high|medium|low)
+    /// Minimum severity threshold for --exit-code (critical|high|medium|low|info)
     #[arg(long, value_enum, default_value_t = SeverityLevel::High)]
     pub min_severity: SeverityLevel,
-    /// Disable incremental analysis cache
+    /// Analyze every file instead of reusing results from target/sanctifier-cache
     #[arg(short = 'n', long)]
     pub no_cache: bool,
     /// Analysis profile preset — overrides --exit-code and --min-severity when set
     #[arg(long, value_enum)]
     pub profile: Option<AnalysisProfile>,
+    /// Only report findings on lines changed since this git ref, or in
+    /// functions containing them; untracked files count as changed throughout
+    #[arg(long, value_name = "REF", conflicts_with_all = ["changed_lines", "staged"])]
+    pub changed_since: Option<String>,
+    /// Only report findings on uncommitted changes; same as --changed-since HEAD
+    #[arg(long, conflicts_with = "staged")]
+    pub changed_lines: bool,
+    /// Only report findings on changes staged for the next commit (git diff --cached)
+    #[arg(long)]
+    pub staged: bool,
+    /// Solver for the S011 proofs. Z3 runs in-process in builds with the
+    /// `smt` feature; otherwise the solver binary must be on PATH
+    #[arg(long, value_enum, default_value_t = Solver::Z3)]
+    pub solver: Solver,
+    /// Also write every S011 proof obligation to DIR as an SMT-LIB2 script
+    #[arg(long, value_name = "DIR")]
+    pub emit_smt2: Option<PathBuf>,
+}
+
+impl AnalyzeArgs {
+    /// Git ref selected by `--changed-since` or `--changed-lines`.
+    fn changed_ref(&self) -> Option<&str> {
+        match (&self.changed_since, self.changed_lines) {
+            (Some(git_ref), _) => Some(git_ref),
+            (None, true) => Some("HEAD"),
+            (None, false) => None,
+        }
+    }
+
+    fn smt_options(&self) -> SmtOptions {
+        SmtOptions {
+            solver: self.solver,
+            emit_smt2: self.emit_smt2.clone(),
+        }
+    }
 }
 
 // ── Per-file result container ────────────────────────────────────────────────
 
 /// All findings produced by analysing a single `.rs` file.
-#[derive(Default, serde::Serialize, Clone, Debug)]
+#[derive(Default, serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub(crate) struct FileAnalysisResult {
     pub(crate) file_path: String,
     pub(crate) collisions: Vec<sanctifier_core::StorageCollisionIssue>,
@@ -128,40 +186,54 @@ pub(crate) struct FileAnalysisResult {
     pub(crate) sep41_checked_contracts: Vec<String>,
     pub(crate) sep41_issues: Vec<sanctifier_core::Sep41Issue>,
     pub(crate) variable_shadowing_violations: Vec<sanctifier_core::RuleViolation>,
+    pub(crate) suppressed: Vec<SuppressedFinding>,
     pub(crate) timed_out: bool,
 }
 
+/// A finding removed from a [`FileAnalysisResult`] by a `[suppressions]` entry.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct SuppressedFinding {
+    pub(crate) code: String,
+    pub(crate) location: String,
+    pub(crate) reason: String,
+}
+
 // ── Entry point ──────────────────────────────────────────────────────────────
 
 pub fn exec(args: AnalyzeArgs) -> anyhow::Result<()> {
     let profile = args.profile;
     let exit_code_flag = args.exit_code;
-    let found_issues = run_analysis(args)?;
-    if resolve_exit(profile, exit_code_flag, found_issues) {
+    let min_severity = args.min_severity;
+    let highest = run_analysis(args)?;
+    if resolve_exit(profile, exit_code_flag, min_severity, highest) {
         std::process::exit(crate::exit_codes::FINDINGS_FOUND);
     }
     Ok(())
 }
 
-/// Decide whether to exit with a non-zero code based on the active profile and
-/// the `--exit-code` flag.  Profile overrides the flag when both are supplied.
+/// Decide whether to exit with a non-zero code based on the active profile,
+/// the `--exit-code` flag and the highest severity found.  Profile overrides
+/// the flag and `--min-severity` when both are supplied.
 fn resolve_exit(
     profile: Option<AnalysisProfile>,
     exit_code_flag: bool,
-    found_issues: bool,
+    min_severity: SeverityLevel,
+    highest: Option<SeverityLevel>,
 ) -> bool {
+    let at_least = |threshold: SeverityLevel| highest.is_some_and(|h| h >= threshold);
     match profile {
-        Some(AnalysisProfile::Strict) => found_issues,
+        Some(AnalysisProfile::Strict) => highest.is_some(),
         Some(AnalysisProfile::Lenient) | Some(AnalysisProfile::Audit) => false,
-        Some(AnalysisProfile::Ci) => found_issues,
-        None => exit_code_flag && found_issues,
+        Some(AnalysisProfile::Ci) => at_least(SeverityLevel::High),
+        None => exit_code_flag && at_least(min_severity),
     }
 }
 
 const VALID_FORMATS: &[&str] = &["text", "json", "ndjson", "sarif"];
 
 /// Run the full analysis and dispatch to the appropriate output format.
-pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
+/// Returns the highest severity among the reported findings, if any.
+pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<Option<SeverityLevel>> {
     if !VALID_FORMATS.contains(&args.format.as_str()) {
         anyhow::bail!(
             "unknown output format {:?}; valid values are: {}",
@@ -179,7 +251,7 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
     }
     if !is_soroban_project(&path) {
         eprintln!("No Soroban project found at {:?}", path);
-        return Ok(false);
+        return Ok(None);
     }
 
     let start = Instant::now();
@@ -187,35 +259,67 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
     let telemetry_enabled = config.telemetry;
 
     // When a single file is given, scan only that file — not its parent directory.
-    let rs_files: Vec<PathBuf> = if path.is_file() {
+    let mut rs_files: Vec<PathBuf> = if path.is_file() {
         vec![path.clone()]
     } else {
         collect_rs_files(&path, &config.ignore_paths)
     };
+    let changed = changed_lines(&args, &path, &mut rs_files)?;
+    let crates = CrateSources::read(&rs_files, &config.ignore_paths, changed.as_ref());
 
-    let registry = RuleRegistry::with_default_rules();
+    let registry = RuleRegistry::from_config(&config)?;
+    let cache = (!args.no_cache).then(|| DiskCache::open(&path, &config, &registry));
     let analyzer = Analyzer::new(config.clone());
+    let smt_options = args.smt_options();
 
     let mut all_violations: Vec<(String, sanctifier_core::RuleViolation)> = Vec::new();
+    let mut all_suppressed: Vec<SuppressedViolation> = Vec::new();
     let mut size_warnings_total: usize = 0;
     let mut collision_total: usize = 0;
 
     for file_path in &rs_files {
-        let content = match fs::read_to_string(file_path) {
+        let content = match read_source(changed.as_ref(), file_path) {
             Ok(c) => c,
             Err(_) => continue,
         };
         let file_str = file_path.display().to_string();
         eprintln!("Analyzing {}", file_str);
         tracing::debug!(target: "sanctifier", "Scanning Rust source file: {}", file_str);
-        for v in registry.run_all(&content) {
+        let (mut kept, mut suppressed) = run_rules(
+            &registry,
+            cache.as_ref(),
+            &config.suppressions,
+            &content,
+            &crates.of(file_path),
+            &file_str,
+        );
+        let (smt_kept, smt_suppressed) = smt::run(
+            &smt_options,
+            cache.as_ref(),
+            &config.suppressions,
+            &content,
+            &file_str,
+        );
+        kept.extend(smt_kept);
+        suppressed.extend(smt_suppressed);
+        keep_changed(
+            changed.as_ref(),
+            file_path,
+            &content,
+            &mut kept,
+            &mut suppressed,
+        );
+        for v in kept {
             all_violations.push((file_str.clone(), v));
         }
+        all_suppressed.extend(suppressed);
         size_warnings_total += analyzer.analyze_ledger_size(&content).len();
         collision_total += analyzer.scan_storage_collisions(&content).len();
     }
+    report_cache_hits(cache.as_ref(), rs_files.len());
 
     let total = all_violations.len();
+    let highest = highest_severity(all_violations.iter().map(|(_, v)| v));
     let duration_ms = start.elapsed().as_millis() as u64;
     if telemetry_enabled {
         let rule_ids = all_violations
@@ -251,10 +355,10 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
                 total_findings: total,
                 has_critical: all_violations
                     .iter()
-                    .any(|(_, v)| matches!(v.severity, sanctifier_core::Severity::Error)),
+                    .any(|(_, v)| v.finding_severity == FindingSeverity::Critical),
                 has_high: all_violations
                     .iter()
-                    .any(|(_, v)| matches!(v.severity, sanctifier_core::Severity::Warning)),
+                    .any(|(_, v)| v.finding_severity == FindingSeverity::High),
             },
         };
         let webhook_cfg = WebhookConfig {
@@ -267,57 +371,51 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
     if args.format == "json" {
         let rule_violations: Vec<serde_json::Value> = all_violations
             .into_iter()
-            .map(|(file, v)| {
+            .map(|(file, v)| violation_json(&file, &v))
+            .collect();
+        let suppressed: Vec<serde_json::Value> = all_suppressed
+            .iter()
+            .map(|s| {
                 serde_json::json!({
-                    "file": file,
-                    "rule_name": v.rule_name,
-                    "severity": format!("{:?}", v.severity),
-                    "message": v.message,
-                    "location": v.location,
-                    "suggestion": v.suggestion,
+                    "file": s.file,
+                    "code": s.code,
+                    "rule_name": s.violation.rule_name,
+                    "location": s.violation.location,
+                    "span": s.violation.span,
+                    "message": s.violation.message,
+                    "reason": s.reason,
                 })
             })
             .collect();
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&serde_json::json!({
-                "schema_version": "1.0.0",
-                "rule_violations": rule_violations,
-                "error_codes": finding_codes::all_finding_codes(),
-                "summary": {
-                    "total_findings": total,
-                    "duration_ms": duration_ms,
-                    "version": env!("CARGO_PKG_VERSION"),
-                },
-            }))?
-        );
+        let mut report = serde_json::json!({
+            "schema_version": "1.0.0",
+            "rule_violations": rule_violations,
+            "suppressed": suppressed,
+            "summary": {
+                "total_findings": total,
+                "suppressed_findings": all_suppressed.len(),
+                "duration_ms": duration_ms,
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        });
+        if args.include_error_codes {
+            report["error_codes"] = serde_json::json!(finding_codes::all_finding_codes());
+        }
+        println!("{}", serde_json::to_string_pretty(&report)?);
     } else if args.format == "sarif" {
+        // Suppressed findings stay in the log, marked with a SARIF
+        // `suppressions` entry, so viewers can show them as reviewed.
         let results: Vec<serde_json::Value> = all_violations
             .iter()
-            .map(|(file, v)| {
-                let level = match format!("{:?}", v.severity).as_str() {
-                    "Error" => "error",
-                    "Warning" => "warning",
-                    _ => "note",
-                };
-                let msg = match &v.suggestion {
-                    Some(s) => format!("{} — {}", v.message, s),
-                    None => v.message.clone(),
-                };
-                serde_json::json!({
-                    "ruleId": v.rule_name,
-                    "level": level,
-                    "message": { "text": msg },
-                    "locations": [{
-                        "physicalLocation": {
-                            "artifactLocation": {
-                                "uri": file,
-                                "uriBaseId": "%SRCROOT%"
-                            }
-                        }
-                    }]
-                })
-            })
+            .map(|(file, v)| sarif_result(file, v))
+            .chain(all_suppressed.iter().map(|s| {
+                let mut result = sarif_result(&s.file, &s.violation);
+                result["suppressions"] = serde_json::json!([{
+                    "kind": "external",
+                    "justification": s.reason,
+                }]);
+                result
+            }))
             .collect();
         let sarif = crate::commands::sarif::build_sarif_log(
             "sanctifier",
@@ -355,11 +453,17 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
         if !all_violations.is_empty() {
             println!("\n{} Found {} issue(s):", "⚠️".yellow(), total);
             for (file, v) in &all_violations {
+                let at = match &v.span {
+                    Some(span) => format!("{}:{}:{}", file, span.start_line, span.start_column + 1),
+                    None => file.clone(),
+                };
                 println!(
-                    "   {} [{}] {} — {}",
+                    "   {} [{}] {} ({}) {} — {}",
                     "->".red(),
+                    v.code,
                     v.rule_name.bold(),
-                    file,
+                    v.finding_severity.as_str(),
+                    at,
                     v.message
                 );
                 if let Some(s) = &v.suggestion {
@@ -367,6 +471,26 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
                 }
             }
         }
+        if !all_suppressed.is_empty() {
+            println!(
+                "\n{} {} finding(s) suppressed by .sanctify.toml:",
+                c::blue("ℹ"),
+                all_suppressed.len()
+            );
+            for s in &all_suppressed {
+                println!(
+                    "   [{}] {} {} — {}",
+                    s.code,
+                    s.file,
+                    s.violation.location,
+                    if s.reason.is_empty() {
+                        "no reason given"
+                    } else {
+                        &s.reason
+                    }
+                );
+            }
+        }
         if size_warnings_total == 0 {
             println!("No ledger size issues found.");
         }
@@ -376,7 +500,186 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
         println!("\nStatic analysis complete.");
     }
 
-    Ok(total > 0)
+    Ok(highest)
+}
+
+/// Lines changed since the ref of `--changed-since` / `--changed-lines`, or
+/// staged for `--staged`, with `rs_files` narrowed to the files they touch.
+/// `None` when none of them is given.
+fn changed_lines(
+    args: &AnalyzeArgs,
+    path: &Path,
+    rs_files: &mut Vec<PathBuf>,
+) -> anyhow::Result<Option<ChangedLines>> {
+    let (changed, changes) = if args.staged {
+        (
+            ChangedLines::staged(path)?,
+            "with staged changes".to_string(),
+        )
+    } else if let Some(git_ref) = args.changed_ref() {
+        (
+            ChangedLines::since(path, git_ref)?,
+            format!("changed since {}", git_ref),
+        )
+    } else {
+        return Ok(None);
+    };
+    rs_files.retain(|file| changed.contains(file));
+    eprintln!("Checking {} file(s) {}", rs_files.len(), changes);
+    Ok(Some(changed))
+}
+
+/// Text of `file` to analyze; under `--staged`, the copy in the index.
+fn read_source(changed: Option<&ChangedLines>, file: &Path) -> anyhow::Result<String> {
+    match changed {
+        Some(changed) => changed.read(file),
+        None => Ok(fs::read_to_string(file)?),
+    }
+}
+
+/// Source text of the crate each analyzed file belongs to, read once per
+/// crate, so rules can follow calls into the crate's other files.  A file in
+/// a package belongs with every `.rs` file under the package's `src`; files
+/// outside any package belong with each other.
+#[derive(Default)]
+pub(crate) struct CrateSources {
+    by_file: HashMap<PathBuf, Arc<Vec<String>>>,
+}
+
+impl CrateSources {
+    /// Read the crates of `files`, through `changed` so that under
+    /// `--staged` the whole crate is taken from the index.
+    pub(crate) fn read(
+        files: &[PathBuf],
+        ignore_paths: &[String],
+        changed: Option<&ChangedLines>,
+    ) -> Self {
+        let mut crates: HashMap<Option<PathBuf>, Arc<Vec<String>>> = HashMap::new();
+        let mut by_file = HashMap::new();
+        for file in files {
+            let package = package_dir(file);
+            let sources = crates.entry(package.clone()).or_insert_with(|| {
+                let members = match &package {
+                    Some(dir) => collect_rs_files(&dir.join("src"), ignore_paths),
+                    None => files
+                        .iter()
+                        .filter(|f| package_dir(f).is_none())
+                        .cloned()
+                        .collect(),
+                };
+                let sources = members.iter().filter_map(|f| read_source(changed, f).ok());
+                Arc::new(sources.collect())
+            });
+            by_file.insert(file.clone(), Arc::clone(sources));
+        }
+        Self { by_file }
+    }
+
+    /// Sources of the crate `file` belongs to; none for a file not read.
+    pub(crate) fn of(&self, file: &Path) -> Arc<Vec<String>> {
+        self.by_file.get(file).cloned().unwrap_or_default()
+    }
+}
+
+/// Drop the findings of `file` that lie outside its changed lines and the
+/// functions containing them.
+fn keep_changed(
+    changed: Option<&ChangedLines>,
+    file: &Path,
+    content: &str,
+    kept: &mut Vec<sanctifier_core::RuleViolation>,
+    suppressed: &mut Vec<SuppressedViolation>,
+) {
+    let Some(changes) = changed.and_then(|c| c.in_file(file, content)) else {
+        return;
+    };
+    kept.retain(|v| changes.keeps(v));
+    suppressed.retain(|s| changes.keeps(&s.violation));
+}
+
+/// Tell how many of `files` were answered from `cache`.
+pub(crate) fn report_cache_hits(cache: Option<&DiskCache>, files: usize) {
+    if let Some(hits) = cache.map(DiskCache::hits).filter(|&hits| hits > 0) {
+        eprintln!("Reused cached results for {} of {} file(s)", hits, files);
+    }
+}
+
+/// Highest five-level severity among `violations`, or `None` if there are none.
+pub(crate) fn highest_severity<'a>(
+    violations: impl IntoIterator<Item = &'a sanctifier_core::RuleViolation>,
+) -> Option<SeverityLevel> {
+    violations
+        .into_iter()
+        .map(|v| SeverityLevel::from(v.finding_severity))
+        .max()
+}
+
+/// JSON object for one entry of the `rule_violations` array.
+pub(crate) fn violation_json(file: &str, v: &sanctifier_core::RuleViolation) -> serde_json::Value {
+    serde_json::json!({
+        "file": file,
+        "code": v.code,
+        "rule_name": v.rule_name,
+        "severity": format!("{:?}", v.severity),
+        "finding_severity": v.finding_severity.as_str(),
+        "message": v.message,
+        "location": v.location,
+        "span": v.span,
+        "suggestion": v.suggestion,
+    })
+}
+
+/// Build a single SARIF `result` object for a rule violation.
+fn sarif_result(file: &str, v: &sanctifier_core::RuleViolation) -> serde_json::Value {
+    let level = match format!("{:?}", v.severity).as_str() {
+        "Error" => "error",
+        "Warning" => "warning",
+        _ => "note",
+    };
+    let msg = match &v.suggestion {
+        Some(s) => format!("{} — {}", v.message, s),
+        None => v.message.clone(),
+    };
+    let mut location = serde_json::json!({
+        "physicalLocation": {
+            "artifactLocation": {
+                "uri": file,
+                "uriBaseId": "%SRCROOT%"
+            }
+        }
+    });
+    if let Some(span) = &v.span {
+        // SARIF columns are 1-based; spans use proc-macro2's 0-based columns.
+        location["physicalLocation"]["region"] = serde_json::json!({
+            "startLine": span.start_line,
+            "startColumn": span.start_column + 1,
+            "endLine": span.end_line,
+            "endColumn": span.end_column + 1,
+        });
+        let name = match (&span.contract, &span.function) {
+            (Some(c), Some(f)) => Some(format!("{}::{}", c, f)),
+            (None, Some(f)) => Some(f.clone()),
+            (Some(c), None) => Some(c.clone()),
+            (None, None) => None,
+        };
+        if let Some(name) = name {
+            let kind = if span.function.is_some() { "function" } else { "type" };
+            location["logicalLocations"] = serde_json::json!([{
+                "fullyQualifiedName": name,
+                "kind": kind,
+            }]);
+        }
+    }
+    serde_json::json!({
+        "ruleId": v.rule_name,
+        "level": level,
+        "message": { "text": msg },
+        "locations": [location],
+        "properties": {
+            "code": v.code,
+            "severity": v.finding_severity.as_str(),
+        }
+    })
 }
 
 /// Stream one NDJSON line per finding immediately after each file is analysed.
@@ -385,17 +688,17 @@ pub(crate) fn run_analysis(args: AnalyzeArgs) -> anyhow::Result<bool> {
 ///
 /// Each finding line:
 /// ```json
-/// {"event":"finding","file":"src/lib.rs","rule":"arithmetic_overflow","severity":"Warning","message":"...","location":"fn:5","suggestion":"..."}
+/// {"event":"finding","file":"src/lib.rs","code":"S003","rule":"arithmetic_overflow","severity":"Warning","finding_severity":"medium","message":"...","location":"fn:5","span":{"start_line":5,...},"suggestion":"..."}
 /// ```
 /// Terminal line:
 /// ```json
-/// {"event":"done","total_findings":12,"duration_ms":843}
+/// {"event":"done","total_findings":12,"suppressed_findings":1,"duration_ms":843}
 /// ```
-fn stream_ndjson(args: &AnalyzeArgs) -> anyhow::Result<bool> {
+fn stream_ndjson(args: &AnalyzeArgs) -> anyhow::Result<Option<SeverityLevel>> {
     let path = &args.path;
     if !is_soroban_project(path) {
         eprintln!("No Soroban project found at {:?}", path);
-        return Ok(false);
+        return Ok(None);
     }
 
     let start = Instant::now();
@@ -405,36 +708,71 @@ fn stream_ndjson(args: &AnalyzeArgs) -> anyhow::Result<bool> {
     } else {
         path.clone()
     };
-    let rs_files = collect_rs_files(&scan_root, &config.ignore_paths);
-    let registry = RuleRegistry::with_default_rules();
+    let mut rs_files = collect_rs_files(&scan_root, &config.ignore_paths);
+    let changed = changed_lines(args, path, &mut rs_files)?;
+    let crates = CrateSources::read(&rs_files, &config.ignore_paths, changed.as_ref());
+    let registry = RuleRegistry::from_config(&config)?;
+    let cache = (!args.no_cache).then(|| DiskCache::open(path, &config, &registry));
+    let smt_options = args.smt_options();
     let stdout = std::io::stdout();
     let mut total = 0usize;
+    let mut suppressed_total = 0usize;
+    let mut highest: Option<SeverityLevel> = None;
 
     for file_path in &rs_files {
-        let content = match fs::read_to_string(file_path) {
+        let content = match read_source(changed.as_ref(), file_path) {
             Ok(c) => c,
             Err(_) => continue,
         };
         let file_str = file_path.display().to_string();
-        let violations = registry.run_all(&content);
+        let (mut violations, mut suppressed) = run_rules(
+            &registry,
+            cache.as_ref(),
+            &config.suppressions,
+            &content,
+            &crates.of(file_path),
+            &file_str,
+        );
+        let (smt_kept, smt_suppressed) = smt::run(
+            &smt_options,
+            cache.as_ref(),
+            &config.suppressions,
+            &content,
+            &file_str,
+        );
+        violations.extend(smt_kept);
+        suppressed.extend(smt_suppressed);
+        keep_changed(
+            changed.as_ref(),
+            file_path,
+            &content,
+            &mut violations,
+            &mut suppressed,
+        );
+        suppressed_total += suppressed.len();
 
         // Lock stdout once per file so all findings from this file are contiguous.
         let mut out = stdout.lock();
+        highest = highest.max(highest_severity(&violations));
         for v in violations {
             total += 1;
             let line = serde_json::json!({
                 "event": "finding",
                 "file": file_str,
+                "code": v.code,
                 "rule": v.rule_name,
                 "severity": format!("{:?}", v.severity),
+                "finding_severity": v.finding_severity.as_str(),
                 "message": v.message,
                 "location": v.location,
+                "span": v.span,
                 "suggestion": v.suggestion,
             });
             writeln!(out, "{}", line)?;
         }
         out.flush()?;
     }
+    report_cache_hits(cache.as_ref(), rs_files.len());
 
     let duration_ms = start.elapsed().as_millis() as u64;
     let mut out = stdout.lock();
@@ -444,12 +782,13 @@ fn stream_ndjson(args: &AnalyzeArgs) -> anyhow::Result<bool> {
         serde_json::json!({
             "event": "done",
             "total_findings": total,
+            "suppressed_findings": suppressed_total,
             "duration_ms": duration_ms,
         })
     )?;
     out.flush()?;
 
-    Ok(total > 0)
+    Ok(highest)
 }
 
 #[allow(dead_code)]
@@ -479,6 +818,28 @@ fn walk_dir(
 
 // ── Analyse one file ─────────────────────────────────────────────────────────
 
+/// Run every rule in `registry` over `content`, a file of the crate made up
+/// of `crate_sources`, or read its results from `cache`, tag the violation
+/// spans with `file_name` and split off those covered by `[suppressions]`.
+pub(crate) fn run_rules(
+    registry: &RuleRegistry,
+    cache: Option<&DiskCache>,
+    suppressions: &SuppressionMap,
+    content: &str,
+    crate_sources: &[String],
+    file_name: &str,
+) -> (Vec<sanctifier_core::RuleViolation>, Vec<SuppressedViolation>) {
+    let violations = match cache {
+        Some(cache) => cache.run_all(registry, content, crate_sources),
+        None => registry.run_all_in_crate(content, crate_sources),
+    };
+    let violations = violations
+        .into_iter()
+        .map(|v| v.in_file(file_name))
+        .collect();
+    suppression::partition_violations(suppressions, file_name, violations)
+}
+
 pub(crate) fn analyze_single_file(
     analyzer: &Analyzer,
     vuln_db: &VulnDatabase,
@@ -569,8 +930,17 @@ pub(crate) fn analyze_single_file(
     }
     res.upgrade_reports.push(up);
 
-    // SMT invariant verification requires the z3 feature; leave empty when not available.
-    res.smt_issues = vec![];
+    res.smt_issues = smt::violations(Solver::default(), None, content)
+        .into_iter()
+        .map(|v| sanctifier_core::SmtInvariantIssue {
+            function_name: v
+                .span
+                .and_then(|span| span.function)
+                .unwrap_or_else(|| v.location.clone()),
+            description: v.message,
+            location: format!("{}:{}", file_name, v.location),
+        })
+        .collect();
 
     let sep41_report = analyzer.verify_sep41_interface(content);
     if sep41_report.candidate {
@@ -581,9 +951,66 @@ pub(crate) fn analyze_single_file(
         }
     }
 
+    apply_suppressions(&mut res, &analyzer.config.suppressions);
     res
 }
 
+/// Drop every finding in `res` matched by `suppressions`, recording each one
+/// in `res.suppressed`.  Findings without a line number are always kept.
+fn apply_suppressions(res: &mut FileAnalysisResult, suppressions: &SuppressionMap) {
+    use finding_codes::*;
+    use suppression::line_from_location as line_of;
+
+    if suppressions.is_empty() {
+        return;
+    }
+    let file = res.file_path.clone();
+    let mut suppressed = Vec::new();
+    let mut keep = |code: &'static str, location: &str, line: Option<usize>| {
+        match suppression::find_suppression(suppressions, code, &file, line) {
+            Some(s) => {
+                suppressed.push(SuppressedFinding {
+                    code: code.to_string(),
+                    location: location.to_string(),
+                    reason: s.reason.clone(),
+                });
+                false
+            }
+            None => true,
+        }
+    };
+
+    res.collisions
+        .retain(|i| keep(STORAGE_COLLISION, &i.location, line_of(&i.location)));
+    res.unsafe_patterns
+        .retain(|i| keep(UNSAFE_PATTERN, &i.snippet, Some(i.line)));
+    res.panic_issues
+        .retain(|i| keep(PANIC_USAGE, &i.location, line_of(&i.location)));
+    res.arithmetic_issues
+        .retain(|i| keep(ARITHMETIC_OVERFLOW, &i.location, line_of(&i.location)));
+    res.truncation_bounds_issues
+        .retain(|i| keep(TRUNCATION_BOUNDS, &i.location, line_of(&i.location)));
+    res.custom_matches
+        .retain(|i| keep(CUSTOM_RULE_MATCH, &i.snippet, Some(i.line)));
+    res.event_issues
+        .retain(|i| keep(EVENT_INCONSISTENCY, &i.location, line_of(&i.location)));
+    res.unhandled_results
+        .retain(|i| keep(UNHANDLED_RESULT, &i.location, line_of(&i.location)));
+    res.variable_shadowing_violations
+        .retain(|v| keep(UNSAFE_PATTERN, &v.location, line_of(&v.location)));
+    for report in &mut res.upgrade_reports {
+        report
+            .findings
+            .retain(|f| keep(UPGRADE_RISK, &f.location, line_of(&f.location)));
+    }
+    res.sep41_issues
+        .retain(|i| keep(SEP41_INTERFACE_DEVIATION, &i.location, line_of(&i.location)));
+    res.smt_issues
+        .retain(|i| keep(SMT_INVARIANT_VIOLATION, &i.location, line_of(&i.location)));
+
+    res.suppressed = suppressed;
+}
+
 // ── Timeout wrapper ──────────────────────────────────────────────────────────
 
 pub(crate) fn run_with_timeout<F, R>(timeout: Option<Duration>, f: F) -> Option<R>
@@ -680,15 +1107,6 @@ pub(crate) fn is_soroban_project(path: &Path) -> bool {
     cargo_toml_path.exists()
 }
 
-// ── Cache ────────────────────────────────────────────────────────────────────
-
-#[allow(dead_code)]
-fn sha256_hex(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    format!("{:x}", hasher.finalize())
-}
-
 // ── Path normalization ────────────────────────────────────────────────────────
 
 /// Normalize a CLI path argument for the current OS.
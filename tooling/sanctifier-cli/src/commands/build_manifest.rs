@@ -0,0 +1,273 @@
+//! Reproducible-build manifests.
+//!
+//! `deploy` and `verify` record how a contract WASM was produced — compiler,
+//! target, lockfile, soroban-sdk version, release profile and flags — in a
+//! JSON manifest written next to the artifact (`contract.wasm` →
+//! `contract.manifest.json`).  When two builds of the same source hash
+//! differently, comparing their manifests points at the input that changed.
+
+use anyhow::Context;
+use sanctifier_core::sdk_version::detect_sdk_version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Target every Soroban contract is built for.
+pub const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// Inputs and output of one contract build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub schema_version: String,
+    /// Sanctifier version that recorded the manifest.
+    pub recorded_by: String,
+    /// First line of `rustc -vV`, e.g. `rustc 1.79.0 (129f3b996 2024-06-10)`.
+    pub rustc: Option<String>,
+    pub target: String,
+    /// sha256 of the `Cargo.lock` the build resolved dependencies from.
+    pub cargo_lock_sha256: Option<String>,
+    /// soroban-sdk version requirement declared in the contract's Cargo.toml.
+    pub soroban_sdk: Option<String>,
+    /// `[profile.release]` settings of the workspace root, as TOML values.
+    pub profile: BTreeMap<String, String>,
+    /// `RUSTFLAGS` in effect for the build.
+    pub rustflags: Option<String>,
+    pub wasm_sha256: String,
+}
+
+/// One build input that differs between two manifests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDifference {
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+    /// What the difference most likely changed in the build.
+    pub cause: &'static str,
+}
+
+impl BuildManifest {
+    /// Record the build environment of the contract at `contract_path`, whose
+    /// build produced `wasm`.
+    pub fn capture(contract_path: &Path, wasm: &[u8]) -> Self {
+        let root = workspace_root(contract_path);
+        Self {
+            schema_version: "1.0.0".to_string(),
+            recorded_by: format!("sanctifier {}", env!("CARGO_PKG_VERSION")),
+            rustc: rustc_version(contract_path),
+            target: WASM_TARGET.to_string(),
+            cargo_lock_sha256: fs::read(root.join("Cargo.lock"))
+                .ok()
+                .map(|lock| sha256_hex(&lock)),
+            soroban_sdk: detect_sdk_version(&contract_path.join("Cargo.toml")).version,
+            profile: release_profile(&root.join("Cargo.toml")),
+            rustflags: std::env::var("RUSTFLAGS").ok().filter(|f| !f.is_empty()),
+            wasm_sha256: sha256_hex(wasm),
+        }
+    }
+
+    /// Read the manifest at `path`, if there is one.
+    pub fn read(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read build manifest {}", path.display()))?;
+        let manifest = serde_json::from_str(&content)
+            .with_context(|| format!("invalid build manifest {}", path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("failed to write build manifest {}", path.display()))
+    }
+
+    /// Build inputs that differ from `expected`, most decisive first.  The
+    /// WASM hash itself is not reported; it is what the inputs explain.
+    pub fn diff(&self, expected: &BuildManifest) -> Vec<ManifestDifference> {
+        let mut differences = Vec::new();
+        let mut compare = |field, expected: String, actual: String, cause| {
+            if expected != actual {
+                differences.push(ManifestDifference {
+                    field,
+                    expected,
+                    actual,
+                    cause,
+                });
+            }
+        };
+
+        compare(
+            "rustc",
+            display(&expected.rustc),
+            display(&self.rustc),
+            "a different Rust compiler generates different code",
+        );
+        compare(
+            "target",
+            expected.target.clone(),
+            self.target.clone(),
+            "the contract was built for a different target",
+        );
+        compare(
+            "soroban_sdk",
+            display(&expected.soroban_sdk),
+            display(&self.soroban_sdk),
+            "a different soroban-sdk version changes the contract's host bindings",
+        );
+        compare(
+            "cargo_lock_sha256",
+            display(&expected.cargo_lock_sha256),
+            display(&self.cargo_lock_sha256),
+            "Cargo.lock changed, so dependencies may have resolved to other versions",
+        );
+        compare(
+            "profile",
+            profile_display(&expected.profile),
+            profile_display(&self.profile),
+            "release profile settings (opt-level, lto, ...) changed",
+        );
+        compare(
+            "rustflags",
+            display(&expected.rustflags),
+            display(&self.rustflags),
+            "different RUSTFLAGS were passed to the compiler",
+        );
+        differences
+    }
+}
+
+/// Where the manifest for the artifact at `wasm_path` lives.
+pub fn manifest_path(wasm_path: &Path) -> PathBuf {
+    wasm_path.with_extension("manifest.json")
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Profiles only take effect at the workspace root, so walk up from the
+/// contract to the nearest Cargo.toml declaring `[workspace]`.
+fn workspace_root(contract_path: &Path) -> PathBuf {
+    let mut current = Some(contract_path);
+    while let Some(dir) = current {
+        let is_workspace = fs::read_to_string(dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok())
+            .is_some_and(|table| table.contains_key("workspace"));
+        if is_workspace {
+            return dir.to_path_buf();
+        }
+        current = dir.parent();
+    }
+    contract_path.to_path_buf()
+}
+
+fn release_profile(cargo_toml: &Path) -> BTreeMap<String, String> {
+    let table = fs::read_to_string(cargo_toml)
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok());
+    table
+        .as_ref()
+        .and_then(|t| t.get("profile")?.get("release")?.as_table())
+        .map(|release| {
+            release
+                .iter()
+                .map(|(key, value)| (key.clone(), value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Run in the contract directory so a `rust-toolchain.toml` there is honoured.
+fn rustc_version(contract_path: &Path) -> Option<String> {
+    let output = Command::new("rustc")
+        .arg("-vV")
+        .current_dir(contract_path)
+        .output()
+        .ok()
+        .filter(|out| out.status.success())?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(str::to_string)
+}
+
+fn display(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "(unknown)".to_string())
+}
+
+fn profile_display(profile: &BTreeMap<String, String>) -> String {
+    if profile.is_empty() {
+        return "(defaults)".to_string();
+    }
+    profile
+        .iter()
+        .map(|(key, value)| format!("{} = {}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn capture_reads_lockfile_sdk_and_workspace_profile() {
+        let dir = tempdir().unwrap();
+        let contract = dir.path().join("contracts/token");
+        fs::create_dir_all(&contract).unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"contracts/*\"]\n\n[profile.release]\nopt-level = \"z\"\nlto = true\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Cargo.lock"), "version = 3\n").unwrap();
+        fs::write(
+            contract.join("Cargo.toml"),
+            "[package]\nname = \"token\"\n\n[dependencies]\nsoroban-sdk = \"21.7.6\"\n",
+        )
+        .unwrap();
+
+        let manifest = BuildManifest::capture(&contract, b"\0asm");
+        assert_eq!(manifest.soroban_sdk.as_deref(), Some("21.7.6"));
+        assert_eq!(
+            manifest.cargo_lock_sha256.as_deref(),
+            Some(sha256_hex(b"version = 3\n").as_str())
+        );
+        assert_eq!(manifest.profile["opt-level"], "\"z\"");
+        assert_eq!(manifest.profile["lto"], "true");
+        assert_eq!(manifest.wasm_sha256, sha256_hex(b"\0asm"));
+    }
+
+    #[test]
+    fn diff_names_changed_inputs_and_their_causes() {
+        let dir = tempdir().unwrap();
+        let expected = BuildManifest::capture(dir.path(), b"a");
+        let mut actual = expected.clone();
+        actual.wasm_sha256 = sha256_hex(b"b");
+        assert!(actual.diff(&expected).is_empty());
+
+        actual.rustc = Some("rustc 1.81.0".to_string());
+        actual.cargo_lock_sha256 = Some("0".repeat(64));
+        let differences = actual.diff(&expected);
+        let fields: Vec<_> = differences.iter().map(|d| d.field).collect();
+        assert_eq!(fields, ["rustc", "cargo_lock_sha256"]);
+        assert_eq!(differences[0].actual, "rustc 1.81.0");
+    }
+
+    #[test]
+    fn manifest_sits_next_to_the_artifact() {
+        assert_eq!(
+            manifest_path(Path::new("target/release/token.wasm")),
+            PathBuf::from("target/release/token.manifest.json")
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! On-disk analysis cache and the `sanctifier cache` command.
+//!
+//! `analyze`, `workspace`, `diff`, `watch` and `serve` store the results of
+//! every file they analyze under `target/sanctifier-cache` (or
+//! `$CARGO_TARGET_DIR/sanctifier-cache`).  Entries are content-addressed: an
+//! entry's name is the SHA-256 of the file's contents, the registered rules,
+//! the configuration and fingerprints of the analysis code itself, so a
+//! change to any of them simply misses the cache, even between builds that
+//! share a version number.  Nothing is ever rewritten in place; stale
+//! entries stay until `sanctifier cache clean`.
+
+use crate::commands::analyze::{analyze_single_file, FileAnalysisResult};
+use crate::commands::color as c;
+use crate::vulndb::VulnDatabase;
+use clap::{Args, Subcommand};
+use sanctifier_core::{Analyzer, RuleRegistry, RuleViolation, SanctifyConfig};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Directory under the cargo target directory holding the cache.
+const CACHE_DIR: &str = "sanctifier-cache";
+
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Delete every cached analysis result
+    Clean {
+        /// Project whose cache to delete
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Show where the cache is and how much it holds
+    Stats {
+        /// Project whose cache to inspect
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+pub fn exec(args: CacheArgs) -> anyhow::Result<()> {
+    match args.action {
+        CacheAction::Clean { path } => {
+            let dir = cache_dir(&path);
+            let (entries, _) = usage(&dir)?;
+            if dir.exists() {
+                fs::remove_dir_all(&dir)?;
+            }
+            println!(
+                "{} Removed {} cached result(s) from {}",
+                c::green_check(),
+                entries,
+                dir.display()
+            );
+        }
+        CacheAction::Stats { path } => {
+            let dir = cache_dir(&path);
+            let (entries, bytes) = usage(&dir)?;
+            println!("{} {}", c::bold("Cache:"), dir.display());
+            println!("   entries: {}", entries);
+            println!("   size:    {:.1} KiB", bytes as f64 / 1024.0);
+        }
+    }
+    Ok(())
+}
+
+/// Rule violations of previously analyzed file contents, stored on disk.
+pub(crate) struct DiskCache {
+    dir: PathBuf,
+    /// Digest of the inputs shared by every entry: the registered rules, the
+    /// configuration and the code that produced the results.
+    context: String,
+    hits: AtomicUsize,
+}
+
+impl DiskCache {
+    /// The cache of the project containing `path`, for results of `registry`
+    /// configured by `config`.
+    pub(crate) fn open(path: &Path, config: &SanctifyConfig, registry: &RuleRegistry) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(sanctifier_core::ENGINE_FINGERPRINT);
+        hasher.update(env!("SANCTIFIER_CLI_FINGERPRINT"));
+        hasher.update([cfg!(feature = "smt") as u8]);
+        for rule in registry.available_rules() {
+            hasher.update([0]);
+            hasher.update(rule);
+        }
+        hasher.update([0]);
+        hasher.update(serde_json::to_vec(config).unwrap_or_default());
+        Self {
+            dir: cache_dir(path),
+            context: format!("{:x}", hasher.finalize()),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Violations of `registry` in `content`, a file of the crate made up of
+    /// `crate_sources`, from the cache when the file and its crate were
+    /// analyzed before.
+    pub(crate) fn run_all(
+        &self,
+        registry: &RuleRegistry,
+        content: &str,
+        crate_sources: &[String],
+    ) -> Vec<RuleViolation> {
+        let key: Vec<&str> = std::iter::once(content)
+            .chain(crate_sources.iter().map(String::as_str))
+            .collect();
+        self.get_or_insert(&key, || registry.run_all_in_crate(content, crate_sources))
+    }
+
+    /// [`analyze_single_file`] of `content`, named `file_name`, from the cache
+    /// when the same file was analyzed before against the same vulnerability
+    /// database.
+    pub(crate) fn analyze_file(
+        &self,
+        analyzer: &Analyzer,
+        vuln_db: &VulnDatabase,
+        content: &str,
+        file_name: &str,
+    ) -> FileAnalysisResult {
+        let vulnerabilities = serde_json::to_string(&vuln_db.vulnerabilities).unwrap_or_default();
+        self.get_or_insert(&["file", &vulnerabilities, file_name, content], || {
+            analyze_single_file(analyzer, vuln_db, content, file_name)
+        })
+    }
+
+    /// The result stored under `key`, or `analyze()` stored under it.  Write
+    /// failures only cost the next run a miss.
+    pub(crate) fn get_or_insert<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &[&str],
+        analyze: impl FnOnce() -> T,
+    ) -> T {
+        self.lookup(key, analyze, true)
+    }
+
+    /// Like [`DiskCache::get_or_insert`] for results that supplement another
+    /// lookup of the same file, so a hit is not counted twice.
+    pub(crate) fn get_or_insert_extra<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &[&str],
+        analyze: impl FnOnce() -> T,
+    ) -> T {
+        self.lookup(key, analyze, false)
+    }
+
+    fn lookup<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &[&str],
+        analyze: impl FnOnce() -> T,
+        count_hit: bool,
+    ) -> T {
+        let entry = self.entry(key);
+        let cached = fs::read(&entry)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        if let Some(result) = cached {
+            if count_hit {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+            }
+            return result;
+        }
+        let result = analyze();
+        if let Err(err) = self.store(&entry, &result) {
+            tracing::debug!(target: "sanctifier", error = %err, "Failed to write analysis cache entry");
+        }
+        result
+    }
+
+    /// Number of lookups answered from the cache.
+    pub(crate) fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    fn entry(&self, key: &[&str]) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.context);
+        for part in key {
+            hasher.update([0]);
+            hasher.update(part);
+        }
+        self.dir.join(format!("{:x}.json", hasher.finalize()))
+    }
+
+    /// Write through a temporary file so concurrent runs never read a
+    /// partial entry.
+    fn store<T: Serialize>(&self, entry: &Path, result: &T) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(&self.dir)?;
+        file.write_all(&serde_json::to_vec(result)?)?;
+        file.persist(entry)?;
+        Ok(())
+    }
+}
+
+/// `sanctifier-cache` in the cargo target directory of the project containing
+/// `path`: the enclosing workspace root if there is one, otherwise the nearest
+/// directory with a `Cargo.toml`.
+fn cache_dir(path: &Path) -> PathBuf {
+    if let Some(target) = std::env::var_os("CARGO_TARGET_DIR") {
+        return PathBuf::from(target).join(CACHE_DIR);
+    }
+    let start = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
+    };
+    let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+    let mut root = None;
+    for dir in start.ancestors() {
+        let Ok(manifest) = fs::read_to_string(dir.join("Cargo.toml")) else {
+            continue;
+        };
+        if manifest.contains("[workspace]") {
+            root = Some(dir);
+            break;
+        }
+        root = root.or(Some(dir));
+    }
+    root.unwrap_or(&start).join("target").join(CACHE_DIR)
+}
+
+/// Number of entries in the cache at `dir` and their total size in bytes.
+fn usage(dir: &Path) -> anyhow::Result<(usize, u64)> {
+    if !dir.exists() {
+        return Ok((0, 0));
+    }
+    let mut entries = 0;
+    let mut bytes = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.path().extension().is_some_and(|ext| ext == "json") {
+            entries += 1;
+            bytes += entry.metadata()?.len();
+        }
+    }
+    Ok((entries, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = r#"
+        use soroban_sdk::{contract, contractimpl, Env};
+        #[contract] pub struct Counter;
+        #[contractimpl] impl Counter {
+            pub fn add(_env: Env, a: u64, b: u64) -> u64 { a + b }
+        }
+    "#;
+
+    fn cache(dir: &Path, config: &SanctifyConfig, registry: &RuleRegistry) -> DiskCache {
+        DiskCache {
+            dir: dir.to_path_buf(),
+            ..DiskCache::open(dir, config, registry)
+        }
+    }
+
+    #[test]
+    fn second_run_reads_violations_back_from_disk() {
+        let temp = TempDir::new().unwrap();
+        let config = SanctifyConfig::default();
+        let registry = RuleRegistry::from_config(&config).unwrap();
+
+        let fresh = cache(temp.path(), &config, &registry).run_all(&registry, SOURCE, &[]);
+        assert!(!fresh.is_empty());
+        assert_eq!(usage(temp.path()).unwrap().0, 1);
+
+        let again = cache(temp.path(), &config, &registry);
+        let cached = again.run_all(&registry, SOURCE, &[]);
+        assert_eq!(again.hits(), 1);
+        assert_eq!(
+            serde_json::to_value(&cached).unwrap(),
+            serde_json::to_value(&fresh).unwrap()
+        );
+
+        again.run_all(&registry, &SOURCE.replace("a + b", "a - b"), &[]);
+        assert_eq!(again.hits(), 1);
+        assert_eq!(usage(temp.path()).unwrap().0, 2);
+
+        // Another file of the crate changing invalidates the entry too.
+        again.run_all(&registry, SOURCE, &["fn helper() {}".to_string()]);
+        assert_eq!(again.hits(), 1);
+        assert_eq!(usage(temp.path()).unwrap().0, 3);
+    }
+
+    #[test]
+    fn configuration_is_part_of_the_key() {
+        let temp = TempDir::new().unwrap();
+        let config = SanctifyConfig::default();
+        let registry = RuleRegistry::from_config(&config).unwrap();
+        cache(temp.path(), &config, &registry).run_all(&registry, SOURCE, &[]);
+
+        let mut limited = config.clone();
+        limited.ledger_limit /= 2;
+        let other = cache(temp.path(), &limited, &registry);
+        other.run_all(&registry, SOURCE, &[]);
+        assert_eq!(other.hits(), 0);
+    }
+
+    #[test]
+    fn full_file_results_are_read_back_from_disk() {
+        let temp = TempDir::new().unwrap();
+        let config = SanctifyConfig::default();
+        let registry = RuleRegistry::from_config(&config).unwrap();
+        let analyzer = Analyzer::new(config.clone());
+        let vuln_db = VulnDatabase::load_default();
+
+        let fresh = cache(temp.path(), &config, &registry).analyze_file(
+            &analyzer,
+            &vuln_db,
+            SOURCE,
+            "src/lib.rs",
+        );
+        assert_eq!(fresh.file_path, "src/lib.rs");
+
+        let again = cache(temp.path(), &config, &registry);
+        let cached = again.analyze_file(&analyzer, &vuln_db, SOURCE, "src/lib.rs");
+        assert_eq!(again.hits(), 1);
+        assert_eq!(
+            serde_json::to_value(&cached).unwrap(),
+            serde_json::to_value(&fresh).unwrap()
+        );
+
+        again.analyze_file(&analyzer, &vuln_db, SOURCE, "src/other.rs");
+        assert_eq!(again.hits(), 1);
+    }
+
+    #[test]
+    fn cache_dir_is_under_the_workspace_target() {
+        let temp = TempDir::new().unwrap();
+        let member = temp.path().join("contracts/token");
+        fs::create_dir_all(member.join("src")).unwrap();
+        fs::write(temp.path().join("Cargo.toml"), "[workspace]\n").unwrap();
+        fs::write(member.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(member.join("src/lib.rs"), "").unwrap();
+
+        let root = fs::canonicalize(temp.path()).unwrap();
+        if std::env::var_os("CARGO_TARGET_DIR").is_none() {
+            assert_eq!(
+                cache_dir(&member.join("src/lib.rs")),
+                root.join("target").join(CACHE_DIR)
+            );
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Changed-lines filtering for `analyze --changed-since` / `--changed-lines`
+//! / `--staged`.
+//!
+//! `git diff` hunks give the lines of each file that changed since a ref, or
+//! that are staged for the next commit; untracked files count as changed
+//! throughout.  Changed files are still analyzed whole so rules see their
+//! full context, as found in the working tree or, for `--staged`, in the
+//! index, which is what the hunks' line numbers refer to.  Afterwards a
+//! violation is kept when its span touches a changed line or lies in a
+//! function that does.  Violations without a span describe the whole file and
+//! are always kept.
+
+use crate::commands::git::git;
+use anyhow::Context;
+use sanctifier_core::RuleViolation;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// Inclusive range of 1-based line numbers.
+type LineRange = (usize, usize);
+
+/// Lines changed in each file of a git working tree since a ref.
+#[derive(Debug, Default)]
+pub(crate) struct ChangedLines {
+    /// Keyed by canonical path.
+    files: HashMap<PathBuf, Vec<LineRange>>,
+    /// Root of the working tree whose index holds the text to analyze, for
+    /// `--staged`.
+    index: Option<PathBuf>,
+}
+
+impl ChangedLines {
+    /// Lines that differ between `git_ref` and the working tree of the
+    /// repository containing `path`.  Untracked files, other than ignored
+    /// ones, are changed throughout.
+    pub(crate) fn since(path: &Path, git_ref: &str) -> anyhow::Result<Self> {
+        let toplevel = toplevel(path)?;
+        let diff = unified_diff(&toplevel, git_ref)
+            .with_context(|| format!("failed to diff against {}", git_ref))?;
+        let untracked = git(
+            &toplevel,
+            &["ls-files", "-z", "--others", "--exclude-standard"],
+        )
+        .context("failed to list untracked files")?;
+        let mut changed = Self::from_diff(&toplevel, &diff);
+        for file in untracked.split('\0').filter(|file| !file.is_empty()) {
+            changed
+                .files
+                .insert(toplevel.join(file), vec![(1, usize::MAX)]);
+        }
+        Ok(changed)
+    }
+
+    /// Lines staged for the next commit in the repository containing `path`,
+    /// i.e. the difference between `HEAD` and the index.  Unstaged edits are
+    /// not included, and [`Self::read`] returns the staged text.
+    pub(crate) fn staged(path: &Path) -> anyhow::Result<Self> {
+        let toplevel = toplevel(path)?;
+        let diff =
+            unified_diff(&toplevel, "--cached").context("failed to diff the staged changes")?;
+        Ok(Self {
+            index: Some(toplevel.clone()),
+            ..Self::from_diff(&toplevel, &diff)
+        })
+    }
+
+    fn from_diff(toplevel: &Path, diff: &str) -> Self {
+        let files = parse_diff(diff)
+            .into_iter()
+            .map(|(file, ranges)| (toplevel.join(file), ranges))
+            .collect();
+        Self { files, index: None }
+    }
+
+    /// Text of `file` that the changed lines refer to: its staged copy for
+    /// `--staged`, otherwise the working tree's.
+    pub(crate) fn read(&self, file: &Path) -> anyhow::Result<String> {
+        let Some(toplevel) = &self.index else {
+            return Ok(fs::read_to_string(file)?);
+        };
+        let file = fs::canonicalize(file)?;
+        let relative = file.strip_prefix(toplevel)?.to_string_lossy();
+        git(
+            toplevel,
+            &["show", &format!(":{}", relative.replace('\\', "/"))],
+        )
+    }
+
+    /// Whether `file` has changed lines.
+    pub(crate) fn contains(&self, file: &Path) -> bool {
+        fs::canonicalize(file).is_ok_and(|file| self.files.contains_key(&file))
+    }
+
+    /// Filter for the violations of `file`, whose text is `source`; `None`
+    /// when the file has not changed.
+    pub(crate) fn in_file(&self, file: &Path, source: &str) -> Option<FileChanges<'_>> {
+        let changed = self.files.get(&fs::canonicalize(file).ok()?)?;
+        Some(FileChanges {
+            changed,
+            functions: function_ranges(source),
+        })
+    }
+}
+
+/// Root of the git working tree containing `path`.
+fn toplevel(path: &Path) -> anyhow::Result<PathBuf> {
+    let dir = match path.parent() {
+        Some(parent) if path.is_file() && !parent.as_os_str().is_empty() => parent,
+        _ if path.is_file() => Path::new("."),
+        _ => path,
+    };
+    let toplevel = git(dir, &["rev-parse", "--show-toplevel"])
+        .with_context(|| format!("{} is not inside a git repository", path.display()))?;
+    Ok(fs::canonicalize(toplevel.trim())?)
+}
+
+/// `git diff --unified=0` of the whole repository at `toplevel`; `selection`
+/// is a ref or `--cached`.
+fn unified_diff(toplevel: &Path, selection: &str) -> anyhow::Result<String> {
+    git(
+        toplevel,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            selection,
+            "--",
+        ],
+    )
+}
+
+/// Changed lines of one file and the functions it declares.
+pub(crate) struct FileChanges<'a> {
+    changed: &'a [LineRange],
+    functions: Vec<LineRange>,
+}
+
+impl FileChanges<'_> {
+    /// Whether `violation` touches a changed line or sits in a function that
+    /// does.
+    pub(crate) fn keeps(&self, violation: &RuleViolation) -> bool {
+        let Some(span) = &violation.span else {
+            return true;
+        };
+        let touched = |(start, end): LineRange| {
+            self.changed
+                .iter()
+                .any(|&(from, to)| from <= end && start <= to)
+        };
+        touched((span.start_line, span.end_line))
+            || self
+                .functions
+                .iter()
+                .filter(|&&(start, end)| start <= span.start_line && span.start_line <= end)
+                .any(|&function| touched(function))
+    }
+}
+
+/// New-side line ranges per file of a `git diff --unified=0`.  A deletion
+/// touches the lines on either side of it.
+fn parse_diff(diff: &str) -> HashMap<PathBuf, Vec<LineRange>> {
+    let mut files: HashMap<PathBuf, Vec<LineRange>> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+    for line in diff.lines() {
+        if let Some(target) = line.strip_prefix("+++ ") {
+            current = target.strip_prefix("b/").map(PathBuf::from);
+            continue;
+        }
+        let (Some(file), Some(hunk)) = (&current, line.strip_prefix("@@ ")) else {
+            continue;
+        };
+        let Some(new_side) = hunk.split_whitespace().find_map(|t| t.strip_prefix('+')) else {
+            continue;
+        };
+        let (start, count) = match new_side.split_once(',') {
+            Some((start, count)) => (start.parse::<usize>(), count.parse::<usize>()),
+            None => (new_side.parse(), Ok(1)),
+        };
+        let (Ok(start), Ok(count)) = (start, count) else {
+            continue;
+        };
+        let range = if count == 0 {
+            (start.max(1), start + 1)
+        } else {
+            (start, start + count - 1)
+        };
+        files.entry(file.clone()).or_default().push(range);
+    }
+    files
+}
+
+/// Line ranges of every function, method and trait method in `source`.
+fn function_ranges(source: &str) -> Vec<LineRange> {
+    #[derive(Default)]
+    struct Functions(Vec<LineRange>);
+
+    impl Functions {
+        fn push(&mut self, span: proc_macro2::Span) {
+            self.0.push((span.start().line, span.end().line));
+        }
+    }
+
+    impl<'ast> Visit<'ast> for Functions {
+        fn visit_item_fn(&mut self, f: &'ast syn::ItemFn) {
+            self.push(f.span());
+            syn::visit::visit_item_fn(self, f);
+        }
+
+        fn visit_impl_item_fn(&mut self, f: &'ast syn::ImplItemFn) {
+            self.push(f.span());
+            syn::visit::visit_impl_item_fn(self, f);
+        }
+
+        fn visit_trait_item_fn(&mut self, f: &'ast syn::TraitItemFn) {
+            self.push(f.span());
+            syn::visit::visit_trait_item_fn(self, f);
+        }
+    }
+
+    let Ok(file) = syn::parse_file(source) else {
+        return Vec::new();
+    };
+    let mut functions = Functions::default();
+    functions.visit_file(&file);
+    functions.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sanctifier_core::rules::{RuleViolation, Severity, SourceSpan};
+
+    #[test]
+    fn parse_diff_reads_new_side_hunks() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3 +3 @@ fn a() {
+@@ -10,0 +11,2 @@ fn b() {
+@@ -20,2 +22,0 @@ fn c() {
+diff --git a/src/old.rs b/src/old.rs
+--- a/src/old.rs
++++ /dev/null
+@@ -1,4 +0,0 @@
+";
+        let files = parse_diff(diff);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[Path::new("src/lib.rs")], [(3, 3), (11, 12), (22, 23)]);
+    }
+
+    #[test]
+    fn keeps_violations_on_changed_lines_or_in_changed_functions() {
+        let source = "\
+fn changed() {
+    let a = 1;
+    let b = 2;
+}
+
+fn untouched() {
+    let c = 3;
+}
+";
+        let changed = [(2, 2)];
+        let changes = FileChanges {
+            changed: &changed,
+            functions: function_ranges(source),
+        };
+        let at = |line: usize| {
+            RuleViolation::new("test", Severity::Warning, "finding".into(), String::new())
+                .with_span(SourceSpan {
+                    file: None,
+                    start_line: line,
+                    start_column: 4,
+                    end_line: line,
+                    end_column: 10,
+                    function: None,
+                    contract: None,
+                })
+        };
+        assert!(changes.keeps(&at(2)));
+        assert!(changes.keeps(&at(3)));
+        assert!(!changes.keeps(&at(7)));
+        assert!(changes.keeps(&RuleViolation::new(
+            "test",
+            Severity::Info,
+            "whole file".into(),
+            String::new()
+        )));
+    }
+}
@@ -1,5 +1,4 @@
-#![allow(dead_code)]
-
+use crate::commands::build_manifest::{manifest_path, BuildManifest};
 use crate::commands::color as c;
 use crate::errors::SanctifierError;
 use clap::Args;
@@ -19,7 +18,8 @@ pub struct DeployArgs {
     #[arg(short, long, default_value = "testnet")]
     pub network: String,
 
-    /// Soroban secret key (or set SOROBAN_SECRET_KEY env var)
+    /// Soroban secret key. Prefer the SOROBAN_SECRET_KEY env var, which stays
+    /// out of shell history and the process list
     #[arg(short, long)]
     pub secret_key: Option<String>,
 
@@ -81,8 +81,23 @@ pub fn exec(args: DeployArgs) -> anyhow::Result<()> {
         std::process::exit(1);
     }
 
+    let wasm_path = wasm_path.unwrap();
+
+    // Record the build next to the artifact so `verify` can explain a later
+    // hash mismatch.
+    let wasm = fs::read(&wasm_path)?;
+    let manifest = manifest_path(&wasm_path);
+    BuildManifest::capture(&args.contract_path, &wasm).write(&manifest)?;
+    if !is_json {
+        println!(
+            "{} Build manifest written to {}",
+            c::green("✓"),
+            manifest.display()
+        );
+    }
+
     let config = DeploymentConfig {
-        wasm_path: wasm_path.unwrap(),
+        wasm_path,
         network: args.network.clone(),
         secret_key: secret_key.clone(),
         account_id: args.account_id,
@@ -93,8 +108,12 @@ pub fn exec(args: DeployArgs) -> anyhow::Result<()> {
         Ok(contract_id) => {
             if is_json {
                 println!(
-                    "{{\"status\":\"success\",\"contract_id\":\"{}\",\"network\":\"{}\"}}",
-                    contract_id, args.network
+                    "{}",
+                    serde_json::json!({
+                        "status": "success",
+                        "contract_id": contract_id,
+                        "network": args.network,
+                    })
                 );
             } else {
                 println!("{} Contract deployed successfully!", c::green("✅"));
@@ -114,7 +133,7 @@ pub fn exec(args: DeployArgs) -> anyhow::Result<()> {
         }
         Err(e) => {
             if is_json {
-                println!("{{\"status\":\"error\",\"message\":\"{}\"}} ", e);
+                println!("{}", serde_json::json!({ "status": "error", "message": e }));
             } else {
                 eprintln!("{} Deployment failed: {}", c::red("❌"), e);
             }
@@ -166,23 +185,64 @@ fn build_contract(contract_path: &PathBuf, is_json: bool) -> bool {
     }
 }
 
+/// The release artifact of the package at `contract_path`. Cargo reports the
+/// target directory, so workspace members resolve to the shared
+/// `<workspace>/target` rather than a directory of their own.
 fn find_wasm_file(contract_path: &Path) -> Option<PathBuf> {
-    let target_dir = contract_path.join("target/wasm32-unknown-unknown/release");
-
-    if target_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&target_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(ext) = path.extension() {
-                    if ext == "wasm" {
-                        return Some(path);
-                    }
-                }
-            }
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .arg("--manifest-path")
+        .arg(contract_path.join("Cargo.toml"))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        eprintln!(
+            "{} Failed to read cargo metadata:\n{}",
+            c::red("❌"),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return None;
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let path = wasm_artifact(&metadata, &fs::canonicalize(contract_path).ok()?);
+    match path {
+        Some(path) if path.exists() => Some(path),
+        Some(path) => {
+            eprintln!("{} No WASM found at {}", c::red("❌"), path.display());
+            None
+        }
+        None => {
+            eprintln!(
+                "{} No cdylib target found for {}",
+                c::red("❌"),
+                contract_path.display()
+            );
+            None
         }
     }
+}
 
-    None
+/// Where `cargo build --release --target wasm32-unknown-unknown` puts the
+/// cdylib of the package in `contract_dir`, according to `cargo metadata`.
+fn wasm_artifact(metadata: &serde_json::Value, contract_dir: &Path) -> Option<PathBuf> {
+    let target_dir = metadata["target_directory"].as_str()?;
+    let package = metadata["packages"].as_array()?.iter().find(|package| {
+        package["manifest_path"]
+            .as_str()
+            .and_then(|manifest| Path::new(manifest).parent())
+            .is_some_and(|dir| dir == contract_dir)
+    })?;
+    let lib = package["targets"].as_array()?.iter().find(|target| {
+        target["kind"]
+            .as_array()
+            .is_some_and(|kinds| kinds.iter().any(|kind| kind == "cdylib"))
+    })?;
+    let name = lib["name"].as_str()?.replace('-', "_");
+    Some(
+        Path::new(target_dir)
+            .join("wasm32-unknown-unknown/release")
+            .join(format!("{name}.wasm")),
+    )
 }
 
 fn deploy_contract(config: &DeploymentConfig, is_json: bool) -> Result<String, String> {
@@ -194,17 +254,13 @@ fn deploy_contract(config: &DeploymentConfig, is_json: bool) -> Result<String, S
         );
     }
 
+    // The key goes through the environment rather than `--source` so it
+    // never shows up in the process list.
     let output = Command::new("soroban")
-        .args([
-            "contract",
-            "deploy",
-            "--wasm",
-            config.wasm_path.to_str().unwrap(),
-            "--source",
-            &config.secret_key,
-            "--network",
-            &config.network,
-        ])
+        .args(["contract", "deploy", "--network", &config.network])
+        .arg("--wasm")
+        .arg(&config.wasm_path)
+        .env("SOROBAN_ACCOUNT", &config.secret_key)
         .output()
         .map_err(|e| format!("Failed to deploy: {}", e))?;
 
@@ -253,3 +309,35 @@ fn validate_deployment(contract_id: &str, network: &str, is_json: bool) -> anyho
         Err(anyhow::anyhow!("Validation failed: {}", error))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_member_artifact_is_under_the_shared_target_dir() {
+        let metadata = serde_json::json!({
+            "target_directory": "/ws/target",
+            "packages": [
+                {
+                    "manifest_path": "/ws/contracts/helper/Cargo.toml",
+                    "targets": [{ "name": "helper", "kind": ["lib"] }]
+                },
+                {
+                    "manifest_path": "/ws/contracts/amm-pool/Cargo.toml",
+                    "targets": [{ "name": "amm-pool", "kind": ["cdylib", "rlib"] }]
+                }
+            ]
+        });
+        assert_eq!(
+            wasm_artifact(&metadata, Path::new("/ws/contracts/amm-pool")),
+            Some(PathBuf::from(
+                "/ws/target/wasm32-unknown-unknown/release/amm_pool.wasm"
+            ))
+        );
+        assert_eq!(
+            wasm_artifact(&metadata, Path::new("/ws/contracts/helper")),
+            None
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use crate::commands::analyze::{
-    analyze_single_file, collect_rs_files, is_soroban_project, load_config, run_with_timeout,
-    FileAnalysisResult, SeverityLevel,
+    analyze_single_file, collect_rs_files, is_soroban_project, load_config, report_cache_hits,
+    run_rules, run_with_timeout, violation_json, CrateSources, FileAnalysisResult, SeverityLevel,
 };
+use crate::commands::cache::DiskCache;
 use crate::commands::color as c;
+use crate::commands::git::git;
+use crate::commands::pr_comment::{build_diff_summary, render_pr_comment};
 use crate::vulndb::{VulnDatabase, VulnMatch};
+use anyhow::Context;
 use clap::Args;
 use rayon::prelude::*;
-use sanctifier_core::{Analyzer, SanctifyConfig};
+use sanctifier_core::finding_codes;
+use sanctifier_core::{Analyzer, RuleRegistry, RuleViolation, SanctifyConfig};
 use serde_json::Value;
 use std::collections::HashSet;
 use std::fs;
@@ -25,15 +30,24 @@ pub struct DiffArgs {
     pub path: PathBuf,
 
     /// Path to the baseline JSON file (output of `sanctifier analyze --format json`)
-    #[arg(long)]
-    pub baseline: PathBuf,
+    #[arg(long, required_unless_present = "base", conflicts_with = "base")]
+    pub baseline: Option<PathBuf>,
+
+    /// Git ref to compare against instead of a baseline file; it is checked
+    /// out into a temporary worktree and analyzed with the same config
+    #[arg(long, value_name = "REF")]
+    pub base: Option<String>,
 
-    /// Output format (text, json)
+    /// Output format (text, json, markdown); markdown is the PR comment body
     #[arg(short, long, default_value = "text")]
     pub format: String,
 
+    /// Link to the full report, added to the markdown PR comment
+    #[arg(long, value_name = "URL")]
+    pub report_url: Option<String>,
+
     /// Overwrite the baseline file with current results
-    #[arg(long)]
+    #[arg(long, requires = "baseline")]
     pub update_baseline: bool,
 
     /// Return non-zero exit code when new findings meet or exceed severity threshold
@@ -55,6 +69,10 @@ pub struct DiffArgs {
     /// Limit for ledger entry size in bytes
     #[arg(short, long, default_value = "64000")]
     pub limit: usize,
+
+    /// Analyze every file instead of reusing results from target/sanctifier-cache
+    #[arg(short = 'n', long)]
+    pub no_cache: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -75,43 +93,9 @@ fn fingerprint_finding(category: &str, detail: &str) -> String {
 fn extract_fingerprints_from_json(report: &Value) -> HashSet<String> {
     let mut fps = HashSet::new();
 
-    // auth_gaps – function_name contains "file:fn_name"
-    if let Some(arr) = report.get("auth_gaps").and_then(|v| v.as_array()) {
-        for item in arr {
-            if let Some(name) = item.get("function_name").and_then(|v| v.as_str()) {
-                fps.insert(fingerprint_finding("auth_gap", name));
-            }
-        }
-    }
-
-    // arithmetic_issues – keyed on operation + location
-    if let Some(arr) = report.get("arithmetic_issues").and_then(|v| v.as_array()) {
-        for item in arr {
-            let op = item.get("operation").and_then(|v| v.as_str()).unwrap_or("");
-            let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-            // Use file + function from location (strip line number)
-            let stable_loc = strip_line_number(loc);
-            fps.insert(fingerprint_finding(
-                "arithmetic_overflow",
-                &format!("{}::{}", stable_loc, op),
-            ));
-        }
-    }
-
-    // panic_issues
-    if let Some(arr) = report.get("panic_issues").and_then(|v| v.as_array()) {
-        for item in arr {
-            let issue_type = item
-                .get("issue_type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-            let stable_loc = strip_line_number(loc);
-            fps.insert(fingerprint_finding(
-                "panic_usage",
-                &format!("{}::{}", stable_loc, issue_type),
-            ));
-        }
+    // rule_violations – keyed on rule + enclosing item, never on line
+    if let Some(arr) = report.get("rule_violations").and_then(|v| v.as_array()) {
+        fps.extend(arr.iter().map(violation_fingerprint));
     }
 
     // storage_collisions
@@ -135,20 +119,6 @@ fn extract_fingerprints_from_json(report: &Value) -> HashSet<String> {
         }
     }
 
-    // ledger_size_warnings
-    if let Some(arr) = report
-        .get("ledger_size_warnings")
-        .and_then(|v| v.as_array())
-    {
-        for item in arr {
-            let name = item
-                .get("struct_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            fps.insert(fingerprint_finding("ledger_size_risk", name));
-        }
-    }
-
     // event_issues
     if let Some(arr) = report.get("event_issues").and_then(|v| v.as_array()) {
         for item in arr {
@@ -165,26 +135,6 @@ fn extract_fingerprints_from_json(report: &Value) -> HashSet<String> {
         }
     }
 
-    // unhandled_results
-    if let Some(arr) = report.get("unhandled_results").and_then(|v| v.as_array()) {
-        for item in arr {
-            let fname = item
-                .get("function_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let call = item
-                .get("call_expression")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-            let stable_loc = strip_line_number(loc);
-            fps.insert(fingerprint_finding(
-                "unhandled_result",
-                &format!("{}::{}::{}", stable_loc, fname, call),
-            ));
-        }
-    }
-
     // upgrade_reports
     if let Some(arr) = report.get("upgrade_reports").and_then(|v| v.as_array()) {
         for rpt in arr {
@@ -280,6 +230,21 @@ fn strip_line_number(loc: &str) -> &str {
     loc
 }
 
+/// Fingerprint an entry of `rule_violations`.  Its `span` names the enclosing
+/// contract and function, so the fingerprint survives code moving around.
+/// Entries without a span (older reports) fall back to the location string.
+fn violation_fingerprint(item: &Value) -> String {
+    let field = |v: &Value, key: &str| v.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let rule = field(item, "rule_name");
+    let file = field(item, "file");
+    let message = field(item, "message");
+    let scope = match item.get("span").filter(|s| !s.is_null()) {
+        Some(span) => format!("{}::{}", field(span, "contract"), field(span, "function")),
+        None => strip_line_number(&field(item, "location")).to_string(),
+    };
+    fingerprint_finding(&rule, &format!("{}::{}::{}", file, scope, message))
+}
+
 // ---------------------------------------------------------------------------
 // Collect new findings as JSON items for reporting
 // ---------------------------------------------------------------------------
@@ -312,52 +277,11 @@ fn collect_new_findings(current: &Value, baseline_fps: &HashSet<String>) -> Valu
     }
 
     new_report.insert(
-        "auth_gaps".into(),
+        "rule_violations".into(),
         serde_json::json!(filter_array(
             current,
-            "auth_gaps",
-            |item| {
-                item.get("function_name")
-                    .and_then(|v| v.as_str())
-                    .map(|n| fingerprint_finding("auth_gap", n))
-            },
-            baseline_fps
-        )),
-    );
-
-    new_report.insert(
-        "arithmetic_issues".into(),
-        serde_json::json!(filter_array(
-            current,
-            "arithmetic_issues",
-            |item| {
-                let op = item.get("operation").and_then(|v| v.as_str()).unwrap_or("");
-                let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-                Some(fingerprint_finding(
-                    "arithmetic_overflow",
-                    &format!("{}::{}", strip_line_number(loc), op),
-                ))
-            },
-            baseline_fps
-        )),
-    );
-
-    new_report.insert(
-        "panic_issues".into(),
-        serde_json::json!(filter_array(
-            current,
-            "panic_issues",
-            |item| {
-                let it = item
-                    .get("issue_type")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-                Some(fingerprint_finding(
-                    "panic_usage",
-                    &format!("{}::{}", strip_line_number(loc), it),
-                ))
-            },
+            "rule_violations",
+            |item| Some(violation_fingerprint(item)),
             baseline_fps
         )),
     );
@@ -393,20 +317,6 @@ fn collect_new_findings(current: &Value, baseline_fps: &HashSet<String>) -> Valu
         )),
     );
 
-    new_report.insert(
-        "ledger_size_warnings".into(),
-        serde_json::json!(filter_array(
-            current,
-            "ledger_size_warnings",
-            |item| {
-                item.get("struct_name")
-                    .and_then(|v| v.as_str())
-                    .map(|n| fingerprint_finding("ledger_size_risk", n))
-            },
-            baseline_fps
-        )),
-    );
-
     new_report.insert(
         "event_issues".into(),
         serde_json::json!(filter_array(
@@ -427,30 +337,6 @@ fn collect_new_findings(current: &Value, baseline_fps: &HashSet<String>) -> Valu
         )),
     );
 
-    new_report.insert(
-        "unhandled_results".into(),
-        serde_json::json!(filter_array(
-            current,
-            "unhandled_results",
-            |item| {
-                let fname = item
-                    .get("function_name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let call = item
-                    .get("call_expression")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-                Some(fingerprint_finding(
-                    "unhandled_result",
-                    &format!("{}::{}::{}", strip_line_number(loc), fname, call),
-                ))
-            },
-            baseline_fps
-        )),
-    );
-
     new_report.insert("upgrade_reports".into(), {
         let mut new_reports = Vec::new();
         if let Some(arr) = current.get("upgrade_reports").and_then(|v| v.as_array()) {
@@ -565,14 +451,10 @@ fn collect_new_findings(current: &Value, baseline_fps: &HashSet<String>) -> Valu
 
 fn count_new_findings(new: &Value) -> usize {
     let simple_keys = [
-        "auth_gaps",
-        "arithmetic_issues",
-        "panic_issues",
+        "rule_violations",
         "storage_collisions",
         "unsafe_patterns",
-        "ledger_size_warnings",
         "event_issues",
-        "unhandled_results",
         "smt_issues",
         "sep41_issues",
         "vulnerability_db_matches",
@@ -604,14 +486,22 @@ fn highest_severity_in_new(new: &Value) -> Option<SeverityLevel> {
         });
     };
 
-    // auth_gaps -> Critical
-    if new
-        .get("auth_gaps")
-        .and_then(|v| v.as_array())
-        .map(|a| !a.is_empty())
-        .unwrap_or(false)
-    {
-        consider(SeverityLevel::Critical);
+    // rule_violations -> their five-level severity
+    if let Some(arr) = new.get("rule_violations").and_then(|v| v.as_array()) {
+        for item in arr {
+            let tagged = item
+                .get("finding_severity")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse::<SeverityLevel>().ok());
+            // Baselines written before findings were tagged only carry the rule name.
+            let severity = tagged.unwrap_or_else(|| {
+                let rule = item.get("rule_name").and_then(|v| v.as_str()).unwrap_or("");
+                finding_codes::code_for_rule(rule)
+                    .and_then(finding_codes::lookup_finding_code)
+                    .map_or(SeverityLevel::Medium, |c| c.severity.into())
+            });
+            consider(severity);
+        }
     }
     // smt_issues -> Critical
     if new
@@ -622,24 +512,6 @@ fn highest_severity_in_new(new: &Value) -> Option<SeverityLevel> {
     {
         consider(SeverityLevel::Critical);
     }
-    // arithmetic_issues -> High
-    if new
-        .get("arithmetic_issues")
-        .and_then(|v| v.as_array())
-        .map(|a| !a.is_empty())
-        .unwrap_or(false)
-    {
-        consider(SeverityLevel::High);
-    }
-    // panic_issues -> High
-    if new
-        .get("panic_issues")
-        .and_then(|v| v.as_array())
-        .map(|a| !a.is_empty())
-        .unwrap_or(false)
-    {
-        consider(SeverityLevel::High);
-    }
     // unsafe_patterns -> High
     if new
         .get("unsafe_patterns")
@@ -658,15 +530,6 @@ fn highest_severity_in_new(new: &Value) -> Option<SeverityLevel> {
     {
         consider(SeverityLevel::High);
     }
-    // unhandled_results -> High
-    if new
-        .get("unhandled_results")
-        .and_then(|v| v.as_array())
-        .map(|a| !a.is_empty())
-        .unwrap_or(false)
-    {
-        consider(SeverityLevel::High);
-    }
     // storage_collisions -> Medium
     if new
         .get("storage_collisions")
@@ -676,15 +539,6 @@ fn highest_severity_in_new(new: &Value) -> Option<SeverityLevel> {
     {
         consider(SeverityLevel::Medium);
     }
-    // ledger_size_warnings -> Medium
-    if new
-        .get("ledger_size_warnings")
-        .and_then(|v| v.as_array())
-        .map(|a| !a.is_empty())
-        .unwrap_or(false)
-    {
-        consider(SeverityLevel::Medium);
-    }
     // sep41_issues -> Medium
     if new
         .get("sep41_issues")
@@ -724,12 +578,17 @@ fn highest_severity_in_new(new: &Value) -> Option<SeverityLevel> {
 // Build current JSON report (same format as `sanctifier analyze --format json`)
 // ---------------------------------------------------------------------------
 
+/// Analyze `path`, naming its files as if it were checked out at `label` so
+/// reports of two checkouts of one tree share fingerprints.  Rule results
+/// are cached in the project at `label` unless `use_cache` is false.
 fn build_current_report(
     path: &Path,
+    label: &Path,
     config: &SanctifyConfig,
     analyzer: &Arc<Analyzer>,
     vuln_db: &Arc<VulnDatabase>,
     timeout_secs: u64,
+    use_cache: bool,
 ) -> anyhow::Result<Value> {
     let rs_files = if path.is_dir() {
         collect_rs_files(path, &config.ignore_paths)
@@ -739,6 +598,9 @@ fn build_current_report(
         vec![]
     };
 
+    let registry = Arc::new(RuleRegistry::from_config(config)?);
+    let cache = use_cache.then(|| Arc::new(DiskCache::open(label, config, &registry)));
+    let crates = CrateSources::read(&rs_files, &config.ignore_paths, None);
     let total_files = rs_files.len();
     let counter = Arc::new(AtomicUsize::new(0));
     let timeout_dur = if timeout_secs == 0 {
@@ -747,78 +609,81 @@ fn build_current_report(
         Some(Duration::from_secs(timeout_secs))
     };
 
-    let mut results: Vec<FileAnalysisResult> = rs_files
+    let mut results: Vec<(FileAnalysisResult, Vec<RuleViolation>)> = rs_files
         .par_iter()
         .map(|file_path| {
             let idx = counter.fetch_add(1, Ordering::Relaxed) + 1;
-            let file_name = file_path.display().to_string();
+            let file_name = report_name(path, label, file_path);
             eprintln!("[{}/{}] Analyzing {}", idx, total_files, file_name);
             let content = match fs::read_to_string(file_path) {
                 Ok(c) => c,
-                Err(_) => return FileAnalysisResult::default(),
+                Err(_) => return Default::default(),
             };
             let analyzer = Arc::clone(analyzer);
+            let registry = Arc::clone(&registry);
+            let cache = cache.clone();
             let vuln_db = Arc::clone(vuln_db);
+            let crate_sources = crates.of(file_path);
             let file_name_clone = file_name.clone();
             match run_with_timeout(timeout_dur, move || {
-                analyze_single_file(&analyzer, &vuln_db, &content, &file_name_clone)
+                let res = analyze_single_file(&analyzer, &vuln_db, &content, &file_name_clone);
+                let (violations, _) = run_rules(
+                    &registry,
+                    cache.as_deref(),
+                    &analyzer.config.suppressions,
+                    &content,
+                    &crate_sources,
+                    &file_name_clone,
+                );
+                (res, violations)
             }) {
                 Some(res) => res,
                 None => {
                     warn!(target: "sanctifier", file = %file_name, timeout_secs = timeout_secs, "Analysis timed out");
-                    FileAnalysisResult { file_path: file_name, timed_out: true, ..Default::default() }
+                    (FileAnalysisResult { file_path: file_name, timed_out: true, ..Default::default() }, vec![])
                 }
             }
         })
         .collect();
 
-    results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    report_cache_hits(cache.as_deref(), total_files);
+    results.sort_by(|a, b| a.0.file_path.cmp(&b.0.file_path));
 
+    let mut rule_violations = Vec::new();
     let mut collisions = Vec::new();
-    let mut size_warnings = Vec::new();
     let mut unsafe_patterns = Vec::new();
-    let mut auth_gaps = Vec::new();
-    let mut panic_issues = Vec::new();
-    let mut arithmetic_issues = Vec::new();
     let mut custom_matches = Vec::new();
     let mut vuln_matches: Vec<VulnMatch> = Vec::new();
     let mut event_issues = Vec::new();
-    let mut unhandled_results = Vec::new();
     let mut upgrade_reports = Vec::new();
     let mut smt_issues = Vec::new();
     let mut sep41_checked_contracts = Vec::new();
     let mut sep41_issues = Vec::new();
     let mut timed_out_files: Vec<String> = Vec::new();
+    let mut suppressed = Vec::new();
 
-    for r in results {
+    for (r, violations) in results {
+        rule_violations.extend(violations.iter().map(|v| violation_json(&r.file_path, v)));
         collisions.extend(r.collisions);
-        size_warnings.extend(r.size_warnings);
         unsafe_patterns.extend(r.unsafe_patterns);
-        auth_gaps.extend(r.auth_gaps);
-        panic_issues.extend(r.panic_issues);
-        arithmetic_issues.extend(r.arithmetic_issues);
         custom_matches.extend(r.custom_matches);
         vuln_matches.extend(r.vuln_matches);
         event_issues.extend(r.event_issues);
-        unhandled_results.extend(r.unhandled_results);
         upgrade_reports.extend(r.upgrade_reports);
         smt_issues.extend(r.smt_issues);
         sep41_checked_contracts.extend(r.sep41_checked_contracts);
         sep41_issues.extend(r.sep41_issues);
+        suppressed.extend(r.suppressed);
         if r.timed_out {
             timed_out_files.push(r.file_path);
         }
     }
 
-    let total_findings = collisions.len()
-        + size_warnings.len()
+    let total_findings = rule_violations.len()
+        + collisions.len()
         + unsafe_patterns.len()
-        + auth_gaps.len()
-        + panic_issues.len()
-        + arithmetic_issues.len()
         + custom_matches.len()
         + event_issues.len()
-        + unhandled_results.len()
         + upgrade_reports
             .iter()
             .map(|r| r.findings.len())
@@ -829,15 +694,11 @@ fn build_current_report(
 
     let report = serde_json::json!({
         "schema_version": "1.0.0",
+        "rule_violations": rule_violations,
         "storage_collisions": collisions,
-        "ledger_size_warnings": size_warnings,
         "unsafe_patterns": unsafe_patterns,
-        "auth_gaps": auth_gaps,
-        "panic_issues": panic_issues,
-        "arithmetic_issues": arithmetic_issues,
         "custom_rules": custom_matches,
         "event_issues": event_issues,
-        "unhandled_results": unhandled_results,
         "upgrade_reports": upgrade_reports,
         "smt_issues": smt_issues,
         "sep41_checked_contracts": sep41_checked_contracts,
@@ -845,20 +706,124 @@ fn build_current_report(
         "vulnerability_db_matches": vuln_matches,
         "vulnerability_db_version": vuln_db.version,
         "timed_out_files": timed_out_files,
+        "suppressed": suppressed,
         "metadata": {
             "version": env!("CARGO_PKG_VERSION"),
-            "project_path": path.display().to_string(),
+            "project_path": label.display().to_string(),
             "format": "sanctifier-ci-v1",
             "timeout_secs": timeout_secs,
         },
         "summary": {
             "total_findings": total_findings,
+            "suppressed_findings": suppressed.len(),
         },
     });
 
     Ok(report)
 }
 
+/// `file` under `path`, renamed to the same place under `label`.
+fn report_name(path: &Path, label: &Path, file: &Path) -> String {
+    match file.strip_prefix(path) {
+        Ok(relative) if !relative.as_os_str().is_empty() => label.join(relative),
+        _ => label.to_path_buf(),
+    }
+    .display()
+    .to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Base revision
+// ---------------------------------------------------------------------------
+
+/// A git ref checked out into a temporary worktree, removed again on drop.
+struct BaseWorktree {
+    repo: PathBuf,
+    dir: tempfile::TempDir,
+}
+
+impl BaseWorktree {
+    fn checkout(repo: &Path, git_ref: &str) -> anyhow::Result<Self> {
+        let dir = tempfile::tempdir().context("failed to create a temporary directory")?;
+        let root = dir.path().join("base");
+        git(
+            repo,
+            &[
+                "worktree",
+                "add",
+                "--detach",
+                "--quiet",
+                &root.to_string_lossy(),
+                git_ref,
+            ],
+        )
+        .with_context(|| format!("failed to check out {}", git_ref))?;
+        Ok(Self {
+            repo: repo.to_path_buf(),
+            dir,
+        })
+    }
+
+    fn root(&self) -> PathBuf {
+        self.dir.path().join("base")
+    }
+}
+
+impl Drop for BaseWorktree {
+    fn drop(&mut self) {
+        let root = self.root();
+        let _ = git(
+            &self.repo,
+            &["worktree", "remove", "--force", &root.to_string_lossy()],
+        );
+    }
+}
+
+/// Analyze `path` as committed at `git_ref`, with `config`.  Files are named
+/// as in the working tree; a path that does not exist at `git_ref` yields an
+/// empty report, so everything in it counts as new.
+fn build_base_report(
+    git_ref: &str,
+    path: &Path,
+    config: &SanctifyConfig,
+    analyzer: &Arc<Analyzer>,
+    vuln_db: &Arc<VulnDatabase>,
+    timeout_secs: u64,
+    use_cache: bool,
+) -> anyhow::Result<Value> {
+    let dir = match path.parent() {
+        Some(parent) if path.is_file() && !parent.as_os_str().is_empty() => parent,
+        _ if path.is_file() => Path::new("."),
+        _ => path,
+    };
+    let toplevel = PathBuf::from(
+        git(dir, &["rev-parse", "--show-toplevel"])
+            .with_context(|| format!("{} is not inside a git repository", path.display()))?
+            .trim(),
+    );
+    let absolute =
+        fs::canonicalize(path).with_context(|| format!("failed to resolve {}", path.display()))?;
+    let relative = absolute
+        .strip_prefix(fs::canonicalize(&toplevel)?)
+        .with_context(|| format!("{} is outside the git repository", path.display()))?
+        .to_path_buf();
+
+    let worktree = BaseWorktree::checkout(&toplevel, git_ref)?;
+    let base_path = worktree.root().join(relative);
+    if !base_path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    build_current_report(
+        &base_path,
+        path,
+        config,
+        analyzer,
+        vuln_db,
+        timeout_secs,
+        use_cache,
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Entry point
 // ---------------------------------------------------------------------------
@@ -918,64 +883,97 @@ pub fn exec(args: DiffArgs) -> anyhow::Result<()> {
     });
 
     // 1. Build current report
-    let current_report = build_current_report(&path, &config, &analyzer, &vuln_db, args.timeout)?;
-
-    // 2. Handle --update-baseline: write current results and potentially also diff
-    if args.update_baseline {
-        let json_str = serde_json::to_string_pretty(&current_report)?;
-        fs::write(&args.baseline, &json_str)?;
-        if !is_json {
-            println!(
-                "{} Baseline updated: {}",
-                c::green("✅"),
-                args.baseline.display()
-            );
+    let current_report = build_current_report(
+        &path,
+        &path,
+        &config,
+        &analyzer,
+        &vuln_db,
+        args.timeout,
+        !args.no_cache,
+    )?;
+
+    // 2. Build the base report, or load the baseline file
+    let (baseline_report, baseline_name) = match (&args.base, &args.baseline) {
+        (Some(git_ref), _) => {
+            info!(target: "sanctifier", git_ref = %git_ref, "Analyzing base revision");
+            let report = build_base_report(
+                git_ref,
+                &path,
+                &config,
+                &analyzer,
+                &vuln_db,
+                args.timeout,
+                !args.no_cache,
+            )?;
+            (report, git_ref.clone())
         }
-    }
+        (None, Some(baseline)) => {
+            // --update-baseline: write current results and potentially also diff
+            if args.update_baseline {
+                let json_str = serde_json::to_string_pretty(&current_report)?;
+                fs::write(baseline, &json_str)?;
+                if !is_json {
+                    println!(
+                        "{} Baseline updated: {}",
+                        c::green("✅"),
+                        baseline.display()
+                    );
+                }
+            }
 
-    // 3. Load baseline
-    let baseline_report: Value = if args.baseline.exists() {
-        let baseline_str = fs::read_to_string(&args.baseline)?;
-        serde_json::from_str(&baseline_str)?
-    } else {
-        if !args.update_baseline {
-            if is_json {
-                let err = serde_json::json!({
-                    "error": format!("Baseline file not found: {}", args.baseline.display()),
-                    "success": false,
-                });
-                println!("{}", serde_json::to_string_pretty(&err)?);
+            let report: Value = if baseline.exists() {
+                let baseline_str = fs::read_to_string(baseline)?;
+                serde_json::from_str(&baseline_str)?
             } else {
-                error!(
-                    target: "sanctifier",
-                    path = %args.baseline.display(),
-                    "Baseline file not found. Run with --update-baseline to create one."
-                );
-            }
-            std::process::exit(2);
+                if !args.update_baseline {
+                    if is_json {
+                        let err = serde_json::json!({
+                            "error": format!("Baseline file not found: {}", baseline.display()),
+                            "success": false,
+                        });
+                        println!("{}", serde_json::to_string_pretty(&err)?);
+                    } else {
+                        error!(
+                            target: "sanctifier",
+                            path = %baseline.display(),
+                            "Baseline file not found. Run with --update-baseline to create one."
+                        );
+                    }
+                    std::process::exit(2);
+                }
+                // If we just wrote the baseline, there are no new findings by definition
+                current_report.clone()
+            };
+            (report, baseline.display().to_string())
         }
-        // If we just wrote the baseline, there are no new findings by definition
-        current_report.clone()
+        (None, None) => unreachable!("clap requires --baseline unless --base is given"),
     };
 
-    // 4. Compare
+    // 3. Compare; resolved findings are the baseline's findings missing now
     let baseline_fps = extract_fingerprints_from_json(&baseline_report);
     let new_findings = collect_new_findings(&current_report, &baseline_fps);
     let new_count = count_new_findings(&new_findings);
+    let current_fps = extract_fingerprints_from_json(&current_report);
+    let resolved_findings = collect_new_findings(&baseline_report, &current_fps);
+    let resolved_count = count_new_findings(&resolved_findings);
 
     let duration_ms = start.elapsed().as_millis() as u64;
 
-    // 5. Determine exit code
+    // 4. Determine exit code
     let new_highest = highest_severity_in_new(&new_findings);
     let should_exit_with_1 =
         args.exit_code && new_highest.map(|h| h >= args.min_severity).unwrap_or(false);
 
-    // 6. Output
+    // 5. Output
     if is_json {
         let diff_report = serde_json::json!({
             "new_findings": new_findings,
             "new_findings_count": new_count,
-            "baseline_path": args.baseline.display().to_string(),
+            "resolved_findings": resolved_findings,
+            "resolved_findings_count": resolved_count,
+            "baseline_path": args.baseline.as_ref().map(|p| p.display().to_string()),
+            "base_ref": args.base,
             "metadata": {
                 "version": env!("CARGO_PKG_VERSION"),
                 "duration_ms": duration_ms,
@@ -983,13 +981,19 @@ pub fn exec(args: DiffArgs) -> anyhow::Result<()> {
             },
         });
         println!("{}", serde_json::to_string_pretty(&diff_report)?);
+    } else if args.format == "markdown" {
+        let summary = build_diff_summary(&pr_comment_json(&new_findings, resolved_count));
+        print!(
+            "{}",
+            render_pr_comment(&summary, args.report_url.as_deref())
+        );
     } else {
         println!(
             "\n{} Diff analysis complete. ({} ms)",
             c::green("✨"),
             duration_ms
         );
-        println!("   Baseline: {}", args.baseline.display());
+        println!("   Baseline: {}", baseline_name);
 
         if new_count == 0 {
             println!(
@@ -1005,6 +1009,13 @@ pub fn exec(args: DiffArgs) -> anyhow::Result<()> {
 
             print_new_text_findings(&new_findings);
         }
+        if resolved_count > 0 {
+            println!(
+                "   {} {} finding(s) resolved since baseline.",
+                c::green("✅"),
+                resolved_count
+            );
+        }
     }
 
     if should_exit_with_1 {
@@ -1013,50 +1024,178 @@ pub fn exec(args: DiffArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn print_new_text_findings(new: &Value) {
-    use sanctifier_core::finding_codes;
+// ---------------------------------------------------------------------------
+// PR comment
+// ---------------------------------------------------------------------------
 
-    if let Some(arr) = new.get("auth_gaps").and_then(|v| v.as_array()) {
-        for item in arr {
-            if let Some(name) = item.get("function_name").and_then(|v| v.as_str()) {
-                println!(
-                    "   {} [{}] Auth gap: {}",
-                    c::red("->"),
-                    c::bold(finding_codes::AUTH_GAP),
-                    c::bold(name)
-                );
-            }
+/// Regroup new findings by file into the diff JSON that
+/// `pr_comment::build_diff_summary` reads.
+fn pr_comment_json(new: &Value, resolved_count: usize) -> Value {
+    let mut by_file = serde_json::Map::new();
+    for (file, finding) in comment_findings(new) {
+        let entry = by_file
+            .entry(file.clone())
+            .or_insert_with(|| serde_json::json!({ "file": file, "findings": [] }));
+        if let Some(findings) = entry["findings"].as_array_mut() {
+            findings.push(finding);
         }
     }
-    if let Some(arr) = new.get("arithmetic_issues").and_then(|v| v.as_array()) {
-        for item in arr {
-            let op = item.get("operation").and_then(|v| v.as_str()).unwrap_or("");
-            let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-            println!(
-                "   {} [{}] Arithmetic: {} at {}",
-                c::red("->"),
-                c::bold(finding_codes::ARITHMETIC_OVERFLOW),
-                c::bold(op),
-                loc
-            );
+    serde_json::json!({
+        "new_findings": by_file,
+        "resolved_findings_count": resolved_count,
+    })
+}
+
+/// `(file, finding)` for every new finding, with the rule, severity and
+/// message the PR comment shows.
+fn comment_findings(new: &Value) -> Vec<(String, Value)> {
+    let text = |item: &Value, key: &str| {
+        item.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    let catalogued = |code: &str| {
+        finding_codes::lookup_finding_code(code).map_or("medium", |c| c.severity.as_str())
+    };
+    let finding = |rule_id: &str, severity: &str, message: String| {
+        serde_json::json!({ "rule_id": rule_id, "severity": severity, "message": message })
+    };
+    let mut out = Vec::new();
+
+    for item in items(new, "rule_violations") {
+        let rule = text(item, "rule_name");
+        let code = item
+            .get("code")
+            .and_then(|v| v.as_str())
+            .or_else(|| finding_codes::code_for_rule(&rule))
+            .unwrap_or(&rule);
+        let severity = item
+            .get("finding_severity")
+            .and_then(|v| v.as_str())
+            .unwrap_or_else(|| catalogued(code));
+        out.push((
+            text(item, "file"),
+            finding(code, severity, text(item, "message")),
+        ));
+    }
+
+    // (category, code, field led by the file name, message field)
+    let located = [
+        (
+            "storage_collisions",
+            finding_codes::STORAGE_COLLISION,
+            "location",
+            "message",
+        ),
+        (
+            "unsafe_patterns",
+            finding_codes::UNSAFE_PATTERN,
+            "snippet",
+            "snippet",
+        ),
+        (
+            "event_issues",
+            finding_codes::EVENT_INCONSISTENCY,
+            "location",
+            "message",
+        ),
+        (
+            "smt_issues",
+            finding_codes::SMT_INVARIANT_VIOLATION,
+            "location",
+            "description",
+        ),
+        (
+            "sep41_issues",
+            finding_codes::SEP41_INTERFACE_DEVIATION,
+            "location",
+            "message",
+        ),
+        (
+            "custom_rules",
+            finding_codes::CUSTOM_RULE_MATCH,
+            "snippet",
+            "rule_name",
+        ),
+    ];
+    for (key, code, location, message) in located {
+        for item in items(new, key) {
+            let location = text(item, location);
+            let message = match text(item, message) {
+                m if m == location => detail_of(&m).to_string(),
+                m => m,
+            };
+            out.push((file_of(&location), finding(code, catalogued(code), message)));
         }
     }
-    if let Some(arr) = new.get("panic_issues").and_then(|v| v.as_array()) {
+
+    for report in items(new, "upgrade_reports") {
+        for item in items(report, "findings") {
+            let severity = text(item, "severity").to_lowercase();
+            out.push((
+                file_of(&text(item, "location")),
+                finding(
+                    finding_codes::UPGRADE_RISK,
+                    &severity,
+                    text(item, "message"),
+                ),
+            ));
+        }
+    }
+
+    for item in items(new, "vulnerability_db_matches") {
+        out.push((
+            text(item, "file"),
+            finding(
+                &text(item, "vuln_id"),
+                &text(item, "severity"),
+                text(item, "name"),
+            ),
+        ));
+    }
+    out
+}
+
+fn items<'v>(value: &'v Value, key: &str) -> impl Iterator<Item = &'v Value> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+}
+
+/// File name leading a `file:detail` location.
+fn file_of(location: &str) -> String {
+    location.split(':').next().unwrap_or("").to_string()
+}
+
+/// The `detail` of a `file:detail` location.
+fn detail_of(location: &str) -> &str {
+    location
+        .split_once(':')
+        .map_or(location, |(_, detail)| detail)
+}
+
+fn print_new_text_findings(new: &Value) {
+    if let Some(arr) = new.get("rule_violations").and_then(|v| v.as_array()) {
         for item in arr {
-            let it = item
-                .get("issue_type")
+            let rule = item.get("rule_name").and_then(|v| v.as_str()).unwrap_or("");
+            let code = item
+                .get("code")
                 .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let loc = item.get("location").and_then(|v| v.as_str()).unwrap_or("");
-            println!(
-                "   {} [{}] Panic: {} at {}",
-                c::red("->"),
-                c::bold(finding_codes::PANIC_USAGE),
-                c::bold(it),
-                loc
-            );
+                .or_else(|| finding_codes::code_for_rule(rule))
+                .unwrap_or(rule);
+            let msg = item.get("message").and_then(|v| v.as_str()).unwrap_or("");
+            let file = item.get("file").and_then(|v| v.as_str()).unwrap_or("");
+            let at = match item.pointer("/span/start_line").and_then(|v| v.as_u64()) {
+                Some(line) => format!("{}:{}", file, line),
+                None => file.to_string(),
+            };
+            println!("   {} [{}] {} at {}", c::red("->"), c::bold(code), msg, at);
         }
     }
+
     if let Some(arr) = new.get("storage_collisions").and_then(|v| v.as_array()) {
         for item in arr {
             let key = item.get("key_value").and_then(|v| v.as_str()).unwrap_or("");
@@ -1079,20 +1218,6 @@ fn print_new_text_findings(new: &Value) {
             );
         }
     }
-    if let Some(arr) = new.get("ledger_size_warnings").and_then(|v| v.as_array()) {
-        for item in arr {
-            let name = item
-                .get("struct_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            println!(
-                "   {} [{}] Size: {}",
-                c::red("->"),
-                c::bold(finding_codes::LEDGER_SIZE_RISK),
-                c::bold(name)
-            );
-        }
-    }
     if let Some(arr) = new.get("smt_issues").and_then(|v| v.as_array()) {
         for item in arr {
             let fname = item
@@ -1135,20 +1260,6 @@ fn print_new_text_findings(new: &Value) {
             );
         }
     }
-    if let Some(arr) = new.get("unhandled_results").and_then(|v| v.as_array()) {
-        for item in arr {
-            let fname = item
-                .get("function_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            println!(
-                "   {} [{}] Unhandled result: {}",
-                c::red("->"),
-                c::bold(finding_codes::UNHANDLED_RESULT),
-                c::bold(fname)
-            );
-        }
-    }
     if let Some(arr) = new.get("upgrade_reports").and_then(|v| v.as_array()) {
         for rpt in arr {
             if let Some(findings) = rpt.get("findings").and_then(|v| v.as_array()) {
@@ -1181,3 +1292,62 @@ fn print_new_text_findings(new: &Value) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn violation_fingerprint_ignores_line_shifts() {
+        let at = |line: u64| {
+            serde_json::json!({
+                "file": "src/lib.rs",
+                "rule_name": "auth_gap",
+                "message": "missing require_auth",
+                "location": format!("set_admin:{}", line),
+                "span": {
+                    "start_line": line, "start_column": 4, "end_line": line, "end_column": 20,
+                    "function": "set_admin", "contract": "Token",
+                },
+            })
+        };
+        assert_eq!(violation_fingerprint(&at(10)), violation_fingerprint(&at(42)));
+
+        let mut elsewhere = at(10);
+        elsewhere["span"]["function"] = "mint".into();
+        assert_ne!(violation_fingerprint(&at(10)), violation_fingerprint(&elsewhere));
+    }
+
+    #[test]
+    fn report_name_maps_base_checkout_onto_working_tree() {
+        let base = Path::new("/tmp/wt/base/contracts/token");
+        let label = Path::new("contracts/token");
+        assert_eq!(
+            report_name(base, label, &base.join("src/lib.rs")),
+            "contracts/token/src/lib.rs"
+        );
+        let file = Path::new("/tmp/wt/base/lib.rs");
+        assert_eq!(report_name(file, Path::new("lib.rs"), file), "lib.rs");
+    }
+
+    #[test]
+    fn pr_comment_json_groups_new_findings_by_file() {
+        let new = serde_json::json!({
+            "rule_violations": [{
+                "file": "src/lib.rs", "code": "S001", "rule_name": "auth_gap",
+                "finding_severity": "critical", "message": "missing require_auth",
+            }],
+            "storage_collisions": [{
+                "key_value": "ADMIN", "location": "src/storage.rs:admin:12", "message": "collides",
+            }],
+        });
+        let comment = pr_comment_json(&new, 3);
+        let summary = build_diff_summary(&comment);
+        assert_eq!(summary.new_count, 2);
+        assert_eq!(summary.resolved_count, 3);
+        assert_eq!(summary.by_severity.get("critical"), Some(&1));
+        assert_eq!(summary.top_new[0].rule, "S001");
+        assert_eq!(summary.top_new[1].file, "src/storage.rs");
+        assert_eq!(summary.top_new[1].rule, finding_codes::STORAGE_COLLISION);
+    }
+}
@@ -1,11 +1,13 @@
 use crate::commands::analyze::{
-    analyze_single_file, collect_rs_files, load_config, run_with_timeout,
+    analyze_single_file, collect_rs_files, load_config, run_rules, run_with_timeout, CrateSources,
+    FileAnalysisResult,
 };
 use crate::commands::color as c;
 use crate::vulndb::VulnDatabase;
 use clap::{Args, ValueEnum};
 use rayon::prelude::*;
-use sanctifier_core::Analyzer;
+use sanctifier_core::finding_codes;
+use sanctifier_core::{Analyzer, RuleRegistry, RuleViolation, SourceSpan};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{
@@ -70,6 +72,7 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
     });
 
     let config = load_config(&args.path);
+    let registry = Arc::new(RuleRegistry::from_config(&config)?);
     let analyzer = Arc::new(Analyzer::new(config));
 
     let vuln_db = Arc::new(match &args.vuln_db {
@@ -94,6 +97,7 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
         return Ok(());
     }
 
+    let crates = CrateSources::read(&rs_files, &analyzer.config.ignore_paths, None);
     let total = rs_files.len();
     let counter = Arc::new(AtomicUsize::new(0));
     let timeout_dur = if args.timeout == 0 {
@@ -113,10 +117,25 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
             };
             let file_name = file_path.display().to_string();
             let analyzer = Arc::clone(&analyzer);
+            let registry = Arc::clone(&registry);
             let vuln_db = Arc::clone(&vuln_db);
+            let crate_sources = crates.of(file_path);
             let file_name_clone = file_name.clone();
             run_with_timeout(timeout_dur, move || {
-                analyze_single_file(&analyzer, &vuln_db, &content, &file_name_clone)
+                let legacy = analyze_single_file(&analyzer, &vuln_db, &content, &file_name_clone);
+                let (violations, suppressed) = run_rules(
+                    &registry,
+                    None,
+                    &analyzer.config.suppressions,
+                    &content,
+                    &crate_sources,
+                    &file_name_clone,
+                );
+                FileExport {
+                    legacy,
+                    violations,
+                    suppressed: suppressed.len(),
+                }
             })
             .unwrap_or_default()
         })
@@ -136,51 +155,35 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
         "title",
         "location",
         "suggestion",
+        "file",
+        "start_line",
+        "start_column",
+        "end_line",
+        "end_column",
+        "function",
+        "contract",
     ])?;
 
-    for r in &results {
-        for item in &r.auth_gaps {
-            wtr.write_record([
-                "critical",
-                "S001",
-                "auth_gap",
-                "Missing require_auth()",
-                &item.function_name,
-                "Add <addr>.require_auth() at the start of the function",
-            ])?;
-        }
-        for item in &r.panic_issues {
-            wtr.write_record([
-                "high",
-                "S002",
-                "panic_handling",
-                &format!("Explicit {} usage", item.issue_type),
-                &item.location,
-                "Replace with Result-returning error handling",
-            ])?;
-        }
-        for item in &r.arithmetic_issues {
-            wtr.write_record([
-                "high",
-                "S003",
-                "arithmetic",
-                &format!("Unchecked {} operation", item.operation),
-                &item.location,
-                "Use checked_add / checked_mul or enable overflow-checks = true",
-            ])?;
-        }
-        for item in &r.size_warnings {
-            wtr.write_record([
-                "medium",
-                "S004",
-                "storage_limits",
-                &format!("Ledger size risk: {}", item.struct_name),
-                &item.struct_name,
-                "Reduce struct size or split storage across multiple entries",
-            ])?;
+    for export in &results {
+        for v in &export.violations {
+            let info = finding_codes::lookup_finding_code(&v.code);
+            let category = info.as_ref().map_or(v.rule_name.as_str(), |i| i.category);
+            let mut row = vec![
+                v.finding_severity.as_str().to_string(),
+                v.code.clone(),
+                category.to_string(),
+                v.message.clone(),
+                v.location.clone(),
+                v.suggestion.clone().unwrap_or_default(),
+            ];
+            row.extend(span_columns(v.span.as_ref()));
+            wtr.write_record(&row)?;
         }
+
+        // Findings not (yet) produced by a registered rule carry no span.
+        let r = &export.legacy;
         for item in &r.collisions {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 "medium",
                 "S005",
                 "storage_keys",
@@ -190,7 +193,7 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
             ])?;
         }
         for item in &r.unsafe_patterns {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 "high",
                 "S006",
                 "unsafe_patterns",
@@ -200,7 +203,7 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
             ])?;
         }
         for item in &r.custom_matches {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 "medium",
                 "S007",
                 "custom_rules",
@@ -210,7 +213,7 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
             ])?;
         }
         for item in &r.event_issues {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 "low",
                 "S008",
                 "event_consistency",
@@ -219,19 +222,9 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
                 &item.message,
             ])?;
         }
-        for item in &r.unhandled_results {
-            wtr.write_record([
-                "high",
-                "S009",
-                "error_handling",
-                "Unhandled Result value",
-                &item.location,
-                "Propagate or explicitly handle the Result",
-            ])?;
-        }
         for report in &r.upgrade_reports {
             for finding in &report.findings {
-                wtr.write_record([
+                write_unspanned(&mut wtr, [
                     "high",
                     "S010",
                     "upgrade_safety",
@@ -241,18 +234,8 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
                 ])?;
             }
         }
-        for item in &r.smt_issues {
-            wtr.write_record([
-                "critical",
-                "S011",
-                "smt_verification",
-                "SMT invariant violation",
-                &item.location,
-                &item.description,
-            ])?;
-        }
         for item in &r.sep41_issues {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 "medium",
                 "S012",
                 "token_interface",
@@ -261,18 +244,8 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
                 &item.message,
             ])?;
         }
-        for item in &r.truncation_bounds_issues {
-            wtr.write_record([
-                "high",
-                "S016",
-                "bounds_checking",
-                &format!("Truncation/bounds risk: {}", item.kind),
-                &item.location,
-                &item.suggestion,
-            ])?;
-        }
         for item in &r.vuln_matches {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 &item.severity,
                 "S014",
                 "vuln_db",
@@ -282,7 +255,7 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
             ])?;
         }
         if r.timed_out {
-            wtr.write_record([
+            write_unspanned(&mut wtr, [
                 "low",
                 "S000",
                 "analysis_timeout",
@@ -295,33 +268,85 @@ pub fn exec(args: ExportArgs) -> anyhow::Result<()> {
 
     wtr.flush()?;
 
-    let total_rows = results.iter().map(count_findings).sum::<usize>();
+    let total_rows = results.iter().map(FileExport::count).sum::<usize>();
     println!(
         "{} Exported {} finding(s) to {:?}",
         c::green_check(),
         total_rows,
         output_path
     );
+    let suppressed = results.iter().map(FileExport::suppressed).sum::<usize>();
+    if suppressed > 0 {
+        println!(
+            "{} {} finding(s) omitted by [suppressions] in .sanctify.toml",
+            c::blue_info(),
+            suppressed
+        );
+    }
     Ok(())
 }
 
-fn count_findings(r: &crate::commands::analyze::FileAnalysisResult) -> usize {
-    r.auth_gaps.len()
-        + r.panic_issues.len()
-        + r.arithmetic_issues.len()
-        + r.size_warnings.len()
-        + r.collisions.len()
-        + r.unsafe_patterns.len()
-        + r.custom_matches.len()
-        + r.event_issues.len()
-        + r.unhandled_results.len()
-        + r.upgrade_reports
-            .iter()
-            .map(|u| u.findings.len())
-            .sum::<usize>()
-        + r.smt_issues.len()
-        + r.sep41_issues.len()
-        + r.truncation_bounds_issues.len()
-        + r.vuln_matches.len()
-        + r.timed_out as usize
+/// Everything exported for one file: rule violations (with spans) plus the
+/// findings that only the legacy per-file scan produces.
+#[derive(Default)]
+struct FileExport {
+    legacy: FileAnalysisResult,
+    violations: Vec<RuleViolation>,
+    suppressed: usize,
+}
+
+/// Finding codes exported from [`FileAnalysisResult`] rather than the rule
+/// registry.
+const LEGACY_CODES: &[&str] = &["S005", "S006", "S007", "S008", "S010", "S012"];
+
+impl FileExport {
+    fn count(&self) -> usize {
+        let r = &self.legacy;
+        self.violations.len()
+            + r.collisions.len()
+            + r.unsafe_patterns.len()
+            + r.custom_matches.len()
+            + r.event_issues.len()
+            + r.upgrade_reports
+                .iter()
+                .map(|u| u.findings.len())
+                .sum::<usize>()
+            + r.sep41_issues.len()
+            + r.vuln_matches.len()
+            + r.timed_out as usize
+    }
+
+    fn suppressed(&self) -> usize {
+        self.suppressed
+            + self
+                .legacy
+                .suppressed
+                .iter()
+                .filter(|s| LEGACY_CODES.contains(&s.code.as_str()))
+                .count()
+    }
+}
+
+/// The trailing `file`..`contract` columns for a violation span.
+fn span_columns(span: Option<&SourceSpan>) -> [String; 7] {
+    let Some(span) = span else {
+        return Default::default();
+    };
+    [
+        span.file.clone().unwrap_or_default(),
+        span.start_line.to_string(),
+        span.start_column.to_string(),
+        span.end_line.to_string(),
+        span.end_column.to_string(),
+        span.function.clone().unwrap_or_default(),
+        span.contract.clone().unwrap_or_default(),
+    ]
+}
+
+/// Write a row that has no source span, leaving the span columns empty.
+fn write_unspanned<W: std::io::Write>(
+    wtr: &mut csv::Writer<W>,
+    fields: [&str; 6],
+) -> csv::Result<()> {
+    wtr.write_record(fields.iter().copied().chain([""; 7]))
 }
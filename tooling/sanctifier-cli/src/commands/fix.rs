@@ -1,9 +1,16 @@
 use crate::commands::color as c;
+use anyhow::Context;
 use clap::Args;
+use sanctifier_core::fix_context::contract_errors;
+use sanctifier_core::storage_layout::LayoutType;
 use sanctifier_core::{patcher::Patcher, rules::Patch, RuleRegistry};
+use serde_json::Value;
+use similar::TextDiff;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 #[derive(Args, Debug)]
 pub struct FixArgs {
@@ -13,15 +20,37 @@ pub struct FixArgs {
     /// Interactively review each patch before applying
     #[arg(long)]
     pub interactive: bool,
+    /// Print the fixes as a unified diff instead of applying them
+    #[arg(long, conflicts_with = "output")]
+    pub diff: bool,
+    /// Write the fixes to a .patch file instead of applying them
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// A file whose selected patches applied cleanly and still parse.
+struct PatchedFile {
+    path: PathBuf,
+    original: String,
+    patched: String,
+    patches: usize,
 }
 
 pub fn exec(args: FixArgs) -> anyhow::Result<()> {
     let registry = RuleRegistry::with_default_rules();
     let files = collect_rs_files(&args.path);
+    let in_place = !args.diff && args.output.is_none();
+    // Keep stdout for the diff itself when one is requested.
+    let mut out: Box<dyn Write> = if in_place {
+        Box::new(io::stdout())
+    } else {
+        Box::new(io::stderr())
+    };
 
-    let mut total_applied = 0usize;
     let mut total_skipped = 0usize;
     let mut apply_all = false;
+    let mut patched_files = Vec::new();
+    let mut crate_errors: HashMap<Option<PathBuf>, Vec<LayoutType>> = HashMap::new();
 
     for file_path in &files {
         let source = match fs::read_to_string(file_path) {
@@ -29,34 +58,40 @@ pub fn exec(args: FixArgs) -> anyhow::Result<()> {
             Err(_) => continue,
         };
 
-        let violations = registry.run_all(&source);
-        let all_patches: Vec<Patch> = violations
-            .iter()
-            .flat_map(|v| v.patches.iter().cloned())
-            .collect();
+        let package = package_dir(file_path);
+        let errors = crate_errors
+            .entry(package.clone())
+            .or_insert_with(|| error_enums(package.as_deref(), &files));
+        let all_patches = registry.fix_all_in_crate(&source, errors);
 
         if all_patches.is_empty() {
             continue;
         }
 
-        println!("\n{} {}", c::blue("📄"), file_path.display());
+        writeln!(out, "\n{} {}", c::blue("📄"), file_path.display())?;
 
         let selected: Vec<Patch> = if !args.interactive || apply_all {
-            all_patches.clone()
+            all_patches.into_iter().map(|(_, patch)| patch).collect()
         } else {
             let mut chosen = Vec::new();
-            for patch in &all_patches {
+            for (rule, patch) in &all_patches {
                 if apply_all {
                     chosen.push(patch.clone());
                     continue;
                 }
 
-                println!("\n  {} {}", c::yellow("→"), patch.description);
-                print_diff(&source, patch);
+                writeln!(
+                    out,
+                    "\n  {} {} {}",
+                    c::yellow("→"),
+                    patch.description,
+                    c::dimmed(&format!("[{}]", rule))
+                )?;
+                print_diff(&mut out, &source, patch)?;
 
                 loop {
-                    print!("  Apply? [y/n/a/d/?] ");
-                    io::stdout().flush()?;
+                    write!(out, "  Apply? [y/n/a/d/?] ")?;
+                    out.flush()?;
                     let mut line = String::new();
                     io::stdin().lock().read_line(&mut line)?;
                     match line.trim() {
@@ -74,12 +109,13 @@ pub fn exec(args: FixArgs) -> anyhow::Result<()> {
                             break;
                         }
                         "d" | "D" => {
-                            print_diff(&source, patch);
+                            print_diff(&mut out, &source, patch)?;
                         }
                         _ => {
-                            println!(
+                            writeln!(
+                                out,
                                 "  y=apply  n=skip  a=apply-all-remaining  d=show-diff  ?=help"
-                            );
+                            )?;
                         }
                     }
                 }
@@ -91,37 +127,386 @@ pub fn exec(args: FixArgs) -> anyhow::Result<()> {
             continue;
         }
 
-        let patched = Patcher::apply_patches(&source, &selected);
-        fs::write(file_path, patched)?;
-        total_applied += selected.len();
-        println!(
-            "  {} Applied {} patch(es)",
-            c::green_check(),
-            selected.len()
-        );
+        let outcome = Patcher::apply(&source, &selected);
+        for conflict in &outcome.conflicts {
+            writeln!(
+                out,
+                "  {} Skipped: {} (overlaps: {})",
+                c::yellow_warning(),
+                selected[conflict.patch].description,
+                selected[conflict.with].description
+            )?;
+        }
+        for &index in &outcome.unlocated {
+            writeln!(
+                out,
+                "  {} Skipped: {} (range lies outside the file)",
+                c::yellow_warning(),
+                selected[index].description
+            )?;
+        }
+        total_skipped += outcome.conflicts.len() + outcome.unlocated.len();
+        if outcome.applied.is_empty() {
+            continue;
+        }
+
+        if let Err(err) = syn::parse_file(&outcome.source) {
+            writeln!(
+                out,
+                "  {} Patched file no longer parses ({}); left unchanged",
+                c::red_cross(),
+                err
+            )?;
+            total_skipped += outcome.applied.len();
+            continue;
+        }
+
+        let edition = edition(package.as_deref());
+        patched_files.push(PatchedFile {
+            path: file_path.clone(),
+            patched: format_source(file_path, &source, outcome.source, &edition),
+            original: source,
+            patches: outcome.applied.len(),
+        });
+    }
+
+    if !in_place {
+        let root = if args.path.is_file() {
+            args.path.parent().unwrap_or(&args.path)
+        } else {
+            &args.path
+        };
+        let diff: String = patched_files
+            .iter()
+            .map(|file| unified_diff(root, file))
+            .collect();
+        let proposed: usize = patched_files.iter().map(|f| f.patches).sum();
+        match &args.output {
+            Some(path) => {
+                fs::write(path, &diff)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                writeln!(out, "\n{} Wrote {}", c::green_check(), path.display())?;
+            }
+            None => io::stdout().write_all(diff.as_bytes())?,
+        }
+        writeln!(
+            out,
+            "\n{} Done: {} proposed, {} skipped",
+            c::green("✨"),
+            proposed,
+            total_skipped
+        )?;
+        return Ok(());
+    }
+
+    let packages = check_packages(&mut out, &patched_files)?;
+    for file in &patched_files {
+        fs::write(&file.path, &file.patched)
+            .with_context(|| format!("failed to write {}", file.path.display()))?;
     }
+    let rolled_back = verify_compiles(&mut out, &patched_files, packages)?;
 
-    println!(
+    let mut total_applied = 0usize;
+    writeln!(out)?;
+    for (i, file) in patched_files.iter().enumerate() {
+        if rolled_back.contains(&i) {
+            total_skipped += file.patches;
+            writeln!(
+                out,
+                "{} {}: no longer compiles; rolled back {} patch(es)",
+                c::red_cross(),
+                file.path.display(),
+                file.patches
+            )?;
+        } else {
+            total_applied += file.patches;
+            writeln!(
+                out,
+                "{} {}: applied {} patch(es)",
+                c::green_check(),
+                file.path.display(),
+                file.patches
+            )?;
+        }
+    }
+
+    writeln!(
+        out,
         "\n{} Done: {} applied, {} skipped",
         c::green("✨"),
         total_applied,
         total_skipped
-    );
+    )?;
     Ok(())
 }
 
-fn print_diff(source: &str, patch: &Patch) {
+/// Show the lines `patch` touches before and after applying it.
+fn print_diff(out: &mut dyn Write, source: &str, patch: &Patch) -> io::Result<()> {
+    let patched = Patcher::apply_patches(source, std::slice::from_ref(patch));
     let lines: Vec<&str> = source.lines().collect();
-    let start = patch.start_line.saturating_sub(1);
-    let end = patch.end_line.min(lines.len());
-    println!("  {}", c::dimmed("──────"));
+    let new_lines: Vec<&str> = patched.lines().collect();
+    let start = patch.start_line.saturating_sub(1).min(lines.len());
+    let end = patch.end_line.clamp(start, lines.len());
+    let new_end = (end + new_lines.len())
+        .saturating_sub(lines.len())
+        .clamp(start, new_lines.len());
+    writeln!(out, "  {}", c::dimmed("──────"))?;
     for (i, line) in lines[start..end].iter().enumerate() {
-        println!("  {:<6} {}", c::red(&format!("-{}", start + i + 1)), line);
+        writeln!(
+            out,
+            "  {:<6} {}",
+            c::red(&format!("-{}", start + i + 1)),
+            line
+        )?;
+    }
+    for new_line in &new_lines[start..new_end] {
+        writeln!(out, "  {:<6} {}", c::green("+"), new_line)?;
+    }
+    writeln!(out, "  {}", c::dimmed("──────"))
+}
+
+/// Unified diff of one file, with `a/` and `b/` prefixes for `git apply`.
+/// The path is relative to the current directory or, for files outside it,
+/// to `root`, the directory being fixed.
+fn unified_diff(root: &Path, file: &PatchedFile) -> String {
+    let path = fs::canonicalize(&file.path).unwrap_or_else(|_| file.path.clone());
+    let path = [std::env::current_dir().ok(), Some(root.to_path_buf())]
+        .into_iter()
+        .flatten()
+        .filter_map(|base| fs::canonicalize(base).ok())
+        .find_map(|base| path.strip_prefix(base).ok().map(Path::to_path_buf))
+        .unwrap_or(path);
+    TextDiff::from_lines(&file.original, &file.patched)
+        .unified_diff()
+        .context_radius(3)
+        .header(
+            &format!("a/{}", path.display()),
+            &format!("b/{}", path.display()),
+        )
+        .to_string()
+}
+
+/// Run rustfmt over `patched` if `original` was already formatted, so fixes
+/// follow the file's style without reformatting code nobody touched.
+fn format_source(path: &Path, original: &str, patched: String, edition: &str) -> String {
+    match rustfmt(path, original, edition) {
+        Some(formatted) if formatted == original => {
+            rustfmt(path, &patched, edition).unwrap_or(patched)
+        }
+        _ => patched,
+    }
+}
+
+/// Format `source` through rustfmt's stdin, picking up the `rustfmt.toml`
+/// next to `path`.  `None` when rustfmt is missing or rejects the input.
+fn rustfmt(path: &Path, source: &str, edition: &str) -> Option<String> {
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+    let mut command = Command::new("rustfmt");
+    command
+        .args(["--edition", edition])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let mut child = command.spawn().ok()?;
+    let mut stdin = child.stdin.take()?;
+    let input = source.to_string();
+    // Feed stdin from a thread so a large file cannot fill both pipes.
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().ok()?;
+    writer.join().ok()?.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// A compile error: the file of its primary span and its message.  Lines are
+/// left out so an error a patch merely moved still matches.
+type CompileError = (PathBuf, String);
+
+/// A package containing patched files, and the errors it had before they
+/// were written.
+struct Package {
+    dir: PathBuf,
+    files: Vec<usize>,
+    baseline: Vec<CompileError>,
+}
+
+/// Run `cargo check` on every package containing one of `files` before they
+/// are patched, so [`verify_compiles`] only blames the fixes for errors that
+/// are new.  Packages cargo cannot check are left with the syntax check done
+/// while patching.
+fn check_packages(out: &mut dyn Write, files: &[PatchedFile]) -> anyhow::Result<Vec<Package>> {
+    let mut by_dir: BTreeMap<PathBuf, Vec<usize>> = BTreeMap::new();
+    for (i, file) in files.iter().enumerate() {
+        if let Some(dir) = package_dir(&file.path) {
+            by_dir.entry(dir).or_default().push(i);
+        }
+    }
+
+    let mut packages = Vec::new();
+    for (dir, files) in by_dir {
+        writeln!(out, "\n  Checking {} before patching …", dir.display())?;
+        match cargo_check(&dir) {
+            Ok(baseline) => packages.push(Package {
+                dir,
+                files,
+                baseline,
+            }),
+            Err(reason) => writeln!(
+                out,
+                "  {} {}; kept the syntax check only",
+                c::yellow_warning(),
+                reason
+            )?,
+        }
+    }
+    Ok(packages)
+}
+
+/// Check `packages` again now that `files` are patched and restore the files
+/// that new errors are reported in, or every patched file of the package
+/// when the new errors lie in files no fix touched.  Checks again after each
+/// rollback, and returns the indices of the files restored.
+fn verify_compiles(
+    out: &mut dyn Write,
+    files: &[PatchedFile],
+    packages: Vec<Package>,
+) -> anyhow::Result<Vec<usize>> {
+    let mut rolled_back = Vec::new();
+    for Package {
+        dir,
+        files: mut patched,
+        baseline,
+    } in packages
+    {
+        while !patched.is_empty() {
+            writeln!(out, "\n  Checking {} …", dir.display())?;
+            let errors = match cargo_check(&dir) {
+                Ok(errors) => new_errors(&baseline, errors),
+                Err(reason) => {
+                    writeln!(
+                        out,
+                        "  {} {}; kept the syntax check only",
+                        c::yellow_warning(),
+                        reason
+                    )?;
+                    break;
+                }
+            };
+            if errors.is_empty() {
+                break;
+            }
+            let mut blamed: Vec<usize> = patched
+                .iter()
+                .copied()
+                .filter(|&i| {
+                    fs::canonicalize(&files[i].path)
+                        .is_ok_and(|path| errors.iter().any(|(file, _)| path.ends_with(file)))
+                })
+                .collect();
+            if blamed.is_empty() {
+                blamed = patched.clone();
+            }
+            for &i in &blamed {
+                fs::write(&files[i].path, &files[i].original)
+                    .with_context(|| format!("failed to restore {}", files[i].path.display()))?;
+            }
+            patched.retain(|i| !blamed.contains(i));
+            rolled_back.extend(blamed);
+        }
+    }
+    Ok(rolled_back)
+}
+
+/// The errors of `errors` that `baseline` does not account for, counting
+/// repeated errors.
+fn new_errors(baseline: &[CompileError], mut errors: Vec<CompileError>) -> Vec<CompileError> {
+    for known in baseline {
+        if let Some(i) = errors.iter().position(|error| error == known) {
+            errors.swap_remove(i);
+        }
     }
-    for new_line in patch.replacement.lines() {
-        println!("  {:<6} {}", c::green("+"), new_line);
+    errors
+}
+
+/// Compile errors of package `dir`, or why cargo could not tell.
+fn cargo_check(dir: &Path) -> Result<Vec<CompileError>, String> {
+    let output = Command::new("cargo")
+        .args(["check", "--quiet", "--message-format=json"])
+        .current_dir(dir)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("could not run cargo: {}", e))?;
+    let errors = compile_errors(&String::from_utf8_lossy(&output.stdout));
+    if !output.status.success() && errors.is_empty() {
+        return Err(format!("cargo check failed in {}", dir.display()));
     }
-    println!("  {}", c::dimmed("──────"));
+    Ok(errors)
+}
+
+/// Error-level messages in cargo's JSON output, by the file of their primary
+/// span.
+fn compile_errors(json_lines: &str) -> Vec<CompileError> {
+    json_lines
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-message" && msg["message"]["level"] == "error")
+        .filter_map(|msg| {
+            let message = &msg["message"];
+            let file = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|span| span["is_primary"] == true)?["file_name"]
+                .as_str()?;
+            Some((
+                PathBuf::from(file),
+                message["message"].as_str().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// `#[contracterror]` enums of every source file in `package`, so fixes can
+/// report errors declared in another file of the crate.  Files outside any
+/// package only see the other files being fixed.
+fn error_enums(package: Option<&Path>, files: &[PathBuf]) -> Vec<LayoutType> {
+    let sources = match package {
+        Some(dir) => collect_rs_files(&dir.join("src")),
+        None => files.to_vec(),
+    };
+    sources
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|source| contract_errors(&source))
+        .collect()
+}
+
+/// Directory of the nearest Cargo.toml declaring a `[package]`.
+pub(crate) fn package_dir(file: &Path) -> Option<PathBuf> {
+    let file = fs::canonicalize(file).ok()?;
+    file.ancestors()
+        .skip(1)
+        .find(|dir| manifest(dir).is_some_and(|table| table.contains_key("package")))
+        .map(Path::to_path_buf)
+}
+
+fn manifest(dir: &Path) -> Option<toml::Table> {
+    fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()?
+        .parse::<toml::Table>()
+        .ok()
+}
+
+/// The package's Rust edition, for rustfmt; 2021 when it cannot be read.
+fn edition(package: Option<&Path>) -> String {
+    package
+        .and_then(manifest)
+        .and_then(|table| Some(table.get("package")?.get("edition")?.as_str()?.to_string()))
+        .unwrap_or_else(|| "2021".to_string())
 }
 
 fn collect_rs_files(path: &Path) -> Vec<PathBuf> {
@@ -149,3 +534,42 @@ fn walk_dir(dir: &Path, out: &mut Vec<PathBuf>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_errors_keeps_primary_spans_of_errors() {
+        let json = [
+            r#"{"reason":"compiler-artifact","target":{"name":"dep"}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"warning","spans":[{"file_name":"src/warn.rs","is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","spans":[{"file_name":"src/lib.rs","is_primary":false},{"file_name":"src/bad.rs","is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"aborting due to 1 previous error","spans":[]}}"#,
+            r#"{"reason":"build-finished","success":false}"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            compile_errors(&json),
+            [(PathBuf::from("src/bad.rs"), "mismatched types".to_string())]
+        );
+    }
+
+    #[test]
+    fn new_errors_discounts_the_baseline_once_per_error() {
+        let error = |file: &str, message: &str| (PathBuf::from(file), message.to_string());
+        let baseline = [error("src/a.rs", "mismatched types")];
+        let errors = vec![
+            error("src/a.rs", "mismatched types"),
+            error("src/a.rs", "mismatched types"),
+            error("src/b.rs", "cannot find value"),
+        ];
+        assert_eq!(
+            new_errors(&baseline, errors),
+            [
+                error("src/b.rs", "cannot find value"),
+                error("src/a.rs", "mismatched types"),
+            ]
+        );
+    }
+}
@@ -1,8 +1,10 @@
+use anyhow::Context;
 use clap::{Args, ValueEnum};
 use sanctifier_core::gas_estimator::GasEstimator;
 use sanctifier_core::gas_report::{
     detect_unbounded_loop_warnings, render_json_report, render_text_report, GasReport,
 };
+use sanctifier_core::wasm_cost::estimate_wasm;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -21,6 +23,10 @@ pub struct GasArgs {
     /// Output format
     #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
     pub format: OutputFormat,
+
+    /// Compiled contract (.wasm) to profile per export alongside the source estimates
+    #[arg(long, value_name = "WASM")]
+    pub wasm: Option<PathBuf>,
 }
 
 pub fn exec(args: GasArgs) -> anyhow::Result<()> {
@@ -36,7 +42,14 @@ pub fn exec(args: GasArgs) -> anyhow::Result<()> {
         warnings.extend(detect_unbounded_loop_warnings(&source));
     }
 
-    let report = GasReport::from_estimates(function_reports);
+    let mut report = GasReport::from_estimates(function_reports);
+    if let Some(wasm_path) = &args.wasm {
+        let bytes = fs::read(wasm_path)
+            .with_context(|| format!("failed to read {}", wasm_path.display()))?;
+        let wasm = estimate_wasm(&bytes)
+            .with_context(|| format!("failed to profile {}", wasm_path.display()))?;
+        report = report.with_wasm(wasm);
+    }
 
     match args.format {
         OutputFormat::Json => println!("{}", render_json_report(&report)),
@@ -0,0 +1,24 @@
+//! Running `git` for the commands that read history or the index.
+
+use anyhow::{bail, Context};
+use std::path::Path;
+use std::process::Command;
+
+/// Stdout of `git -C dir <args>`; fails with git's stderr when it exits
+/// unsuccessfully.
+pub(crate) fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -47,7 +47,7 @@ impl ConfigGenerator {
             ledger_limit: 64000,
             telemetry: false,
             strict_mode: false,
-            rules: vec![
+            custom_rules: vec![
                 CustomRule {
                     name: "no_unsafe_block".to_string(),
                     pattern: "unsafe\\s*\\{".to_string(),
@@ -61,7 +61,10 @@ impl ConfigGenerator {
                     severity: sanctifier_core::RuleSeverity::High,
                 },
             ],
+            custom_rules_yaml: None,
             approaching_threshold: 0.8,
+            rules: Default::default(),
+            suppressions: Default::default(),
         }
     }
 }
@@ -210,13 +213,13 @@ mod tests {
         assert_eq!(config.approaching_threshold, 0.8);
 
         // Verify custom_rules
-        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.custom_rules.len(), 2);
 
-        let rule1 = &config.rules[0];
+        let rule1 = &config.custom_rules[0];
         assert_eq!(rule1.name, "no_unsafe_block");
         assert_eq!(rule1.pattern, "unsafe\\s*\\{");
 
-        let rule2 = &config.rules[1];
+        let rule2 = &config.custom_rules[1];
         assert_eq!(rule2.name, "no_mem_forget");
         assert_eq!(rule2.pattern, "std::mem::forget");
     }
@@ -245,7 +248,7 @@ mod tests {
     fn test_custom_rules_have_valid_patterns() {
         let config = ConfigGenerator::generate_default_config();
 
-        for rule in &config.rules {
+        for rule in &config.custom_rules {
             assert!(
                 !rule.name.is_empty(),
                 "Custom rule name should not be empty"
@@ -15,30 +15,32 @@ pub struct InstallHooksArgs {
     pub husky: bool,
 }
 
+// Pre-commit hooks only report findings in the staged lines being committed;
+// pre-push hooks scan the whole project.
 const PRE_COMMIT_HOOK: &str = r#"#!/bin/sh
 # Sanctifier pre-commit hook
 # Auto-generated by sanctifier install-hooks
 
-sanctifier analyze --quiet --severity high
+sanctifier analyze --staged --exit-code --min-severity high
 "#;
 
 const PRE_PUSH_HOOK: &str = r#"#!/bin/sh
 # Sanctifier pre-push hook
 # Auto-generated by sanctifier install-hooks
 
-sanctifier analyze --quiet --severity high
+sanctifier analyze --exit-code --min-severity high
 "#;
 
 const HUSKY_PRE_COMMIT: &str = r#"#!/usr/bin/env sh
 . "$(dirname -- "$0")/_/husky.sh"
 
-sanctifier analyze --quiet --severity high
+sanctifier analyze --staged --exit-code --min-severity high
 "#;
 
 const HUSKY_PRE_PUSH: &str = r#"#!/usr/bin/env sh
 . "$(dirname -- "$0")/_/husky.sh"
 
-sanctifier analyze --quiet --severity high
+sanctifier analyze --exit-code --min-severity high
 "#;
 
 pub struct HookInstaller;
@@ -363,6 +365,37 @@ mod tests {
         assert_eq!(permissions.mode() & 0o111, 0o111);
     }
 
+    #[test]
+    fn test_hook_commands_parse_as_analyze_args() {
+        use crate::commands::analyze::AnalyzeArgs;
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            analyze: AnalyzeArgs,
+        }
+
+        for hook in [
+            PRE_COMMIT_HOOK,
+            PRE_PUSH_HOOK,
+            HUSKY_PRE_COMMIT,
+            HUSKY_PRE_PUSH,
+        ] {
+            let command = hook.lines().last().unwrap();
+            let args = command
+                .strip_prefix("sanctifier analyze ")
+                .unwrap()
+                .split_whitespace();
+            let cli = Cli::try_parse_from(std::iter::once("analyze").chain(args)).unwrap();
+            assert!(cli.analyze.exit_code);
+            assert_eq!(
+                cli.analyze.staged,
+                hook == PRE_COMMIT_HOOK || hook == HUSKY_PRE_COMMIT
+            );
+        }
+    }
+
     #[test]
     fn test_get_hooks_dir_without_husky() {
         let _guard = CWD_LOCK.lock().unwrap();
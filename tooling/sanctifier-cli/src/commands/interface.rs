@@ -0,0 +1,78 @@
+use crate::commands::analyze::{collect_rs_files, load_config};
+use anyhow::{bail, Context};
+use clap::Args;
+use sanctifier_core::contract_interface::{extract_interface, ContractInterface};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct InterfaceArgs {
+    /// Path to a Rust source file or contract directory
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Write the JSON interface to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn exec(args: InterfaceArgs) -> anyhow::Result<()> {
+    let interface = interface_from_path(&args.path)?;
+    if interface.contracts.is_empty() {
+        bail!(
+            "no #[contractimpl] blocks found under {}",
+            args.path.display()
+        );
+    }
+
+    let mut json = serde_json::to_value(&interface)?;
+    json["schema_version"] = serde_json::json!("1.0.0");
+    let rendered = serde_json::to_string_pretty(&json)?;
+
+    match &args.output {
+        Some(output) => {
+            fs::write(output, rendered + "\n")
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            eprintln!("Wrote contract interface to {}", output.display());
+        }
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
+
+fn interface_from_path(path: &Path) -> anyhow::Result<ContractInterface> {
+    let root = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
+    };
+    let mut interface = ContractInterface::default();
+    for file in collect_sources(path)? {
+        let source = fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let name = file
+            .strip_prefix(root)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        let extracted =
+            extract_interface(&source).with_context(|| format!("failed to parse {}", name))?;
+        interface.merge(extracted.in_file(&name));
+    }
+    Ok(interface)
+}
+
+fn collect_sources(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    if !path.is_dir() {
+        bail!("{} is not a valid Rust file or directory", path.display());
+    }
+
+    let config = load_config(path);
+    let mut files = collect_rs_files(path, &config.ignore_paths);
+    files.sort();
+    Ok(files)
+}
@@ -1,9 +1,14 @@
 use clap::Args;
+use sanctifier_core::custom_yaml_rules::{load_yaml_rules, YamlRuleWrapper};
+use sanctifier_core::finding_codes::{self, FindingSeverity};
+use sanctifier_core::rules::{Patch, RuleRegistry, RuleViolation};
+use sanctifier_core::suppression;
 use sanctifier_core::{Analyzer, SanctifyConfig};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::io::{self, BufRead, Read, Write};
-use std::sync::{Arc, Mutex};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
 
 #[derive(Args, Debug)]
 pub struct LspArgs {
@@ -13,20 +18,34 @@ pub struct LspArgs {
 }
 
 pub fn exec(args: LspArgs) -> anyhow::Result<()> {
-    let server = SanctifierLanguageServer::new(args.debug);
-    server.run()
+    let mut server = SanctifierLanguageServer::new(args.debug);
+    server.run(io::stdin().lock(), io::stdout())
+}
+
+/// LSP `TextDocumentSyncKind.Incremental`.
+const SYNC_INCREMENTAL: u8 = 2;
+/// JSON-RPC error code for requests the server does not implement.
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// An open editor buffer and the diagnostics last published for it.
+struct Document {
+    text: String,
+    version: Option<i64>,
+    diagnostics: Vec<Value>,
 }
 
 struct SanctifierLanguageServer {
     debug: bool,
-    documents: Arc<Mutex<HashMap<String, String>>>,
+    documents: HashMap<String, Document>,
+    exited: bool,
 }
 
 impl SanctifierLanguageServer {
     fn new(debug: bool) -> Self {
         Self {
             debug,
-            documents: Arc::new(Mutex::new(HashMap::new())),
+            documents: HashMap::new(),
+            exited: false,
         }
     }
 
@@ -36,400 +55,477 @@ impl SanctifierLanguageServer {
         }
     }
 
-    fn run(&self) -> anyhow::Result<()> {
+    /// Serve JSON-RPC messages from `reader` until `exit` or end of input.
+    fn run(&mut self, mut reader: impl BufRead, mut writer: impl Write) -> anyhow::Result<()> {
         self.log("LSP server starting");
 
-        let stdin = io::stdin();
-        let mut stdout = io::stdout();
-
-        let mut reader = stdin.lock();
-        let mut line_buffer = String::new();
-
-        // Main server loop
-        loop {
-            line_buffer.clear();
-            if reader.read_line(&mut line_buffer)? == 0 {
-                break;
-            }
-
-            if line_buffer.is_empty() || line_buffer.starts_with('\n') {
-                continue;
-            }
-
-            if line_buffer.starts_with("Content-Length:") {
-                let content_length = line_buffer
-                    .strip_prefix("Content-Length:")
-                    .unwrap_or("")
-                    .trim()
-                    .parse::<usize>()
-                    .unwrap_or(0);
-
-                // Read the empty line
-                line_buffer.clear();
-                let _ = reader.read_line(&mut line_buffer);
-
-                // Read the message
-                let mut message = vec![0; content_length];
-                reader.read_exact(&mut message)?;
-                let message_str = String::from_utf8(message)?;
+        while let Some(body) = read_message(&mut reader)? {
+            let msg = match serde_json::from_slice::<Value>(&body) {
+                Ok(msg) => msg,
+                Err(e) => {
+                    self.log(&format!("Failed to parse message: {}", e));
+                    continue;
+                }
+            };
 
+            for out in self.handle_message(&msg) {
+                let out = out.to_string();
+                write!(writer, "Content-Length: {}\r\n\r\n{}", out.len(), out)?;
+                writer.flush()?;
                 self.log(&format!(
-                    "Received message: {}",
-                    message_str.chars().take(100).collect::<String>()
+                    "Sent: {}",
+                    out.chars().take(100).collect::<String>()
                 ));
-
-                match serde_json::from_str::<Value>(&message_str) {
-                    Ok(msg) => {
-                        if let Some(result) = self.handle_message(&msg) {
-                            let response = result.to_string();
-                            let response_bytes = response.as_bytes();
-                            write!(
-                                stdout,
-                                "Content-Length: {}\r\n\r\n{}",
-                                response_bytes.len(),
-                                response
-                            )?;
-                            stdout.flush()?;
-                            self.log(&format!(
-                                "Sent response: {}",
-                                response.chars().take(100).collect::<String>()
-                            ));
-                        }
-                    }
-                    Err(e) => {
-                        self.log(&format!("Failed to parse message: {}", e));
-                    }
-                }
+            }
+            if self.exited {
+                break;
             }
         }
 
         Ok(())
     }
 
-    fn handle_message(&self, msg: &Value) -> Option<Value> {
-        let id = msg.get("id")?;
-
-        match msg.get("method").and_then(|m| m.as_str()) {
-            Some("initialize") => {
-                self.log("Handling initialize request");
-                Some(json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": {
-                        "capabilities": {
-                            "textDocumentSync": 1,
-                            "diagnosticProvider": {
-                                "interFileDependencies": false,
-                                "workspaceDiagnostics": false
-                            },
-                            "codeActionProvider": true,
-                            "hoverProvider": true,
-                        }
-                    }
-                }))
-            }
-            Some("shutdown") => {
-                self.log("Handling shutdown request");
-                Some(json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": null
-                }))
-            }
-            Some("textDocument/codeAction") => {
-                self.log("Handling codeAction request");
-                let params = msg.get("params")?;
-                let uri = params
-                    .get("textDocument")
-                    .and_then(|t| t.get("uri"))
-                    .and_then(|u| u.as_str())?;
-
-                let docs = self.documents.lock().unwrap();
-                if let Some(text) = docs.get(uri) {
-                    let actions = self.get_code_actions(text);
-                    return Some(json!({
+    /// Handle one incoming message and return the messages to send back:
+    /// the response to a request, or notifications such as
+    /// `textDocument/publishDiagnostics`.
+    fn handle_message(&mut self, msg: &Value) -> Vec<Value> {
+        let Some(method) = msg.get("method").and_then(|m| m.as_str()) else {
+            // A response to a request of ours; the server sends none.
+            return vec![];
+        };
+        let params = msg.get("params").unwrap_or(&Value::Null);
+        self.log(&format!("Received {}", method));
+
+        match msg.get("id") {
+            Some(id) => {
+                let response = match self.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({
                         "jsonrpc": "2.0",
                         "id": id,
-                        "result": actions
-                    }));
-                }
-
-                Some(json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": []
-                }))
-            }
-            Some("initialized") | Some("exit") => {
-                self.log(&format!(
-                    "Ignoring notification: {}",
-                    msg.get("method").and_then(|m| m.as_str()).unwrap_or("?")
-                ));
-                None
-            }
-            _ => {
-                self.log(&format!("Unknown method: {:?}", msg.get("method")));
-                None
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+                vec![response]
             }
+            None => self.handle_notification(method, params),
         }
     }
 
-    #[allow(dead_code)]
-    fn handle_notification(&self, notif: &Value) -> anyhow::Result<Option<Vec<Value>>> {
-        let method = notif.get("method").and_then(|m| m.as_str()).unwrap_or("");
-        self.log(&format!("Handling notification: {}", method));
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i32, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        "change": SYNC_INCREMENTAL,
+                    },
+                    "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+                    "hoverProvider": true,
+                },
+                "serverInfo": {
+                    "name": "sanctifier",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/codeAction" => Ok(self.code_actions(params)),
+            "textDocument/hover" => Ok(self.hover(params)),
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method: {}", method))),
+        }
+    }
 
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
         match method {
             "textDocument/didOpen" => {
-                let params = notif
-                    .get("params")
-                    .ok_or_else(|| anyhow::anyhow!("Missing params"))?;
-                let uri = params
-                    .get("textDocument")
-                    .and_then(|t| t.get("uri"))
-                    .and_then(|u| u.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Missing uri"))?;
-                let text = params
-                    .get("textDocument")
-                    .and_then(|t| t.get("text"))
-                    .and_then(|t| t.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Missing text"))?;
-
-                let mut docs = self.documents.lock().unwrap();
-                docs.insert(uri.to_string(), text.to_string());
-
-                let diagnostics = self.analyze_document(text);
-                Ok(Some(diagnostics))
+                let document = &params["textDocument"];
+                self.documents.insert(
+                    uri.to_string(),
+                    Document {
+                        text: document["text"].as_str().unwrap_or_default().to_string(),
+                        version: document["version"].as_i64(),
+                        diagnostics: Vec::new(),
+                    },
+                );
+                vec![self.publish_diagnostics(uri)]
             }
             "textDocument/didChange" => {
-                let params = notif
-                    .get("params")
-                    .ok_or_else(|| anyhow::anyhow!("Missing params"))?;
-                let uri = params
-                    .get("textDocument")
-                    .and_then(|t| t.get("uri"))
-                    .and_then(|u| u.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Missing uri"))?;
-                let text = params
-                    .get("contentChanges")
-                    .and_then(|c| c.as_array())
-                    .and_then(|a| a.first())
-                    .and_then(|c| c.get("text"))
-                    .and_then(|t| t.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Missing text"))?;
-
-                let mut docs = self.documents.lock().unwrap();
-                docs.insert(uri.to_string(), text.to_string());
-
-                let diagnostics = self.analyze_document(text);
-                Ok(Some(diagnostics))
+                let Some(document) = self.documents.get_mut(uri) else {
+                    self.log(&format!("didChange for unopened document {}", uri));
+                    return vec![];
+                };
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    apply_change(&mut document.text, change);
+                }
+                document.version = params["textDocument"]["version"].as_i64();
+                vec![self.publish_diagnostics(uri)]
             }
             "textDocument/didClose" => {
-                let params = notif
-                    .get("params")
-                    .ok_or_else(|| anyhow::anyhow!("Missing params"))?;
-                let uri = params
-                    .get("textDocument")
-                    .and_then(|t| t.get("uri"))
-                    .and_then(|u| u.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Missing uri"))?;
-
-                let mut docs = self.documents.lock().unwrap();
-                docs.remove(uri);
-                Ok(None)
+                self.documents.remove(uri);
+                vec![publish(uri, None, Vec::new())]
+            }
+            "exit" => {
+                self.exited = true;
+                vec![]
             }
-            _ => Ok(None),
+            _ => vec![],
         }
     }
 
-    #[allow(dead_code)]
-    fn analyze_document(&self, text: &str) -> Vec<Value> {
-        let config = SanctifyConfig::default();
-        let analyzer = Analyzer::new(config);
-        let mut diagnostics = Vec::new();
-
-        // Scan auth gaps
-        let auth_gaps = analyzer.scan_auth_gaps(text);
-        for gap in auth_gaps {
-            if let Some(line_num) = self.find_function_line(&gap, text) {
-                diagnostics.push(json!({
-                    "range": {
-                        "start": { "line": line_num, "character": 0 },
-                        "end": { "line": line_num, "character": 100 }
-                    },
-                    "severity": 2,
-                    "code": "S001",
-                    "source": "sanctifier",
-                    "message": format!(
-                        "Function '{}' modifies state without authorization check. Add require_auth or require_auth_for_args.",
-                        gap
-                    ),
-                }));
-            }
+    /// Re-analyse the open document `uri` and build the notification that
+    /// replaces its diagnostics in the editor.
+    fn publish_diagnostics(&mut self, uri: &str) -> Value {
+        let Some(document) = self.documents.get(uri) else {
+            return publish(uri, None, Vec::new());
+        };
+        let diagnostics = self.analyze_document(uri, &document.text);
+        let document = self.documents.get_mut(uri).expect("document is open");
+        document.diagnostics = diagnostics.clone();
+        publish(uri, document.version, diagnostics)
+    }
+
+    /// Run every configured rule over `text`: the built-in registry with the
+    /// `[rules.<name>]` tables applied, YAML rules named by
+    /// `custom_rules_yaml`, and `[[custom_rules]]` regexes.
+    fn analyze_document(&self, uri: &str, text: &str) -> Vec<Value> {
+        let file = uri_to_path(uri);
+        let (config, config_dir) = file.as_deref().map(config_for_file).unwrap_or_default();
+        let registry = self.registry(&config, config_dir.as_deref());
+
+        let mut diagnostics: Vec<Value> = registry
+            .run_all(text)
+            .iter()
+            .map(|v| diagnostic(text, v))
+            .collect();
+
+        for custom_match in Analyzer::new(config.clone()).analyze_custom_rules(text) {
+            diagnostics.push(json!({
+                "range": line_range(text, custom_match.line),
+                "severity": lsp_severity(custom_match.severity),
+                "code": finding_codes::CUSTOM_RULE_MATCH,
+                "source": "sanctifier",
+                "message": format!("Custom rule '{}': {}", custom_match.rule_name, custom_match.snippet),
+                "data": { "rule": custom_match.rule_name },
+            }));
         }
 
-        // Scan panics
-        let panics = analyzer.scan_panics(text);
-        for panic in panics {
-            if let Some(line_num) = self.find_function_line(&panic.function_name, text) {
-                diagnostics.push(json!({
-                    "range": {
-                        "start": { "line": line_num, "character": 0 },
-                        "end": { "line": line_num, "character": 100 }
-                    },
-                    "severity": 2,
-                    "code": "S002",
-                    "source": "sanctifier",
-                    "message": format!(
-                        "Found {} in function '{}'. Consider using Result or Option instead.",
-                        panic.issue_type, panic.function_name
-                    ),
-                }));
+        // Drop anything accepted via `[suppressions]` in .sanctify.toml.
+        if let Some(file) = file {
+            let file = file.display().to_string();
+            diagnostics.retain(|d| {
+                let code = d["code"].as_str().unwrap_or_default();
+                let line = d["range"]["start"]["line"].as_u64().map(|l| l as usize + 1);
+                suppression::find_suppression(&config.suppressions, code, &file, line).is_none()
+            });
+        }
+
+        diagnostics
+    }
+
+    /// The rule registry for a document governed by `config`.  Like the config
+    /// itself, an invalid `[rules.<name>]` table or YAML rules file falls
+    /// back to the defaults rather than taking the server down.
+    fn registry(&self, config: &SanctifyConfig, config_dir: Option<&Path>) -> RuleRegistry {
+        let mut registry = RuleRegistry::from_config(config).unwrap_or_else(|e| {
+            self.log(&format!("Ignoring invalid rule configuration: {}", e));
+            RuleRegistry::default()
+        });
+        if let (Some(yaml), Some(dir)) = (&config.custom_rules_yaml, config_dir) {
+            match load_yaml_rules(&dir.join(yaml)) {
+                Ok(rules) => {
+                    for rule in rules {
+                        registry.register(YamlRuleWrapper::new(rule));
+                    }
+                }
+                Err(e) => self.log(&format!("Ignoring {}: {}", yaml, e)),
             }
         }
+        registry
+    }
 
-        // Scan arithmetic overflow
-        let arithmetic_issues = analyzer.scan_arithmetic_overflow(text);
-        for issue in arithmetic_issues {
-            if let Some(line_num) = self.find_function_line(&issue.function_name, text) {
-                diagnostics.push(json!({
-                    "range": {
-                        "start": { "line": line_num, "character": 0 },
-                        "end": { "line": line_num, "character": 100 }
+    /// Quick fixes for the diagnostics in the requested range, built from the
+    /// patches of the rules that reported them.
+    fn code_actions(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(document) = self.documents.get(uri) else {
+            return json!([]);
+        };
+        let (config, config_dir) = uri_to_path(uri)
+            .as_deref()
+            .map(config_for_file)
+            .unwrap_or_default();
+        let fixes = self
+            .registry(&config, config_dir.as_deref())
+            .fix_all(&document.text);
+
+        let start = position(&params["range"]["start"]);
+        let end = position(&params["range"]["end"]);
+        let mut actions = Vec::new();
+        for (index, d) in document.diagnostics.iter().enumerate() {
+            if position(&d["range"]["start"]) > end || position(&d["range"]["end"]) < start {
+                continue;
+            }
+            let rule = d["data"]["rule"].as_str().unwrap_or_default();
+            let patches: Vec<&Patch> = fixes
+                .iter()
+                .filter(|(name, patch)| {
+                    name == rule
+                        && patch_owner(&document.text, &document.diagnostics, rule, patch)
+                            == Some(index)
+                })
+                .map(|(_, patch)| patch)
+                .collect();
+            for patch in &patches {
+                actions.push(json!({
+                    "title": patch.description,
+                    "kind": "quickfix",
+                    "diagnostics": [d],
+                    "isPreferred": patches.len() == 1,
+                    "edit": {
+                        "changes": {
+                            uri: [{
+                                "range": lsp_range(
+                                    &document.text,
+                                    (patch.start_line, patch.start_column),
+                                    (patch.end_line, patch.end_column),
+                                ),
+                                "newText": patch.replacement,
+                            }],
+                        },
                     },
-                    "severity": 2,
-                    "code": "S003",
-                    "source": "sanctifier",
-                    "message": format!(
-                        "Unchecked arithmetic operation '{}' in function '{}'. {}",
-                        issue.operation, issue.function_name, issue.suggestion
-                    ),
                 }));
             }
         }
+        Value::Array(actions)
+    }
 
-        // Scan unsafe patterns
-        let unsafe_patterns = analyzer.analyze_unsafe_patterns(text);
-        for pattern in unsafe_patterns {
-            let severity = match pattern.pattern_type {
-                sanctifier_core::PatternType::Panic => 1,
-                sanctifier_core::PatternType::Unwrap => 2,
-                sanctifier_core::PatternType::Expect => 2,
-            };
-            diagnostics.push(json!({
-                "range": {
-                    "start": { "line": pattern.line as i32 - 1, "character": 0 },
-                    "end": { "line": pattern.line as i32 - 1, "character": 100 }
-                },
-                "severity": severity,
-                "code": "S006",
-                "source": "sanctifier",
-                "message": format!("Unsafe pattern: {}. Consider safer alternatives.", pattern.snippet),
-            }));
-        }
+    /// Catalogue explanation of the findings under the cursor.
+    fn hover(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(document) = self.documents.get(uri) else {
+            return Value::Null;
+        };
+        let at = position(&params["position"]);
+        let hits: Vec<&Value> = document
+            .diagnostics
+            .iter()
+            .filter(|d| position(&d["range"]["start"]) <= at && at <= position(&d["range"]["end"]))
+            .collect();
+        let Some(first) = hits.first() else {
+            return Value::Null;
+        };
 
-        // Analyze ledger size
-        let size_warnings = analyzer.analyze_ledger_size(text);
-        for warning in size_warnings {
-            if let Some(line_num) = self.find_struct_line(&warning.struct_name, text) {
-                let (severity, message) = match warning.level {
-                    sanctifier_core::SizeWarningLevel::ExceedsLimit => (
-                        1,
-                        format!(
-                            "Struct '{}' exceeds ledger entry limit ({} > {} bytes)",
-                            warning.struct_name, warning.estimated_size, warning.limit
-                        ),
+        let sections: Vec<String> = hits
+            .iter()
+            .map(|d| {
+                let code = d["code"].as_str().unwrap_or_default();
+                let message = d["message"].as_str().unwrap_or_default();
+                match finding_codes::lookup_finding_code(code) {
+                    Some(entry) => format!(
+                        "**{} {}** ({})\n\n{}\n\n{}\n\n**Remediation:** {}\n\n[Documentation]({})",
+                        entry.code,
+                        entry.title,
+                        entry.severity.as_str(),
+                        message,
+                        entry.description,
+                        entry.remediation,
+                        entry.doc_url
                     ),
-                    sanctifier_core::SizeWarningLevel::ApproachingLimit => (
-                        2,
-                        format!(
-                            "Struct '{}' approaching ledger entry limit ({} / {} bytes)",
-                            warning.struct_name, warning.estimated_size, warning.limit
-                        ),
-                    ),
-                };
-                diagnostics.push(json!({
-                    "range": {
-                        "start": { "line": line_num, "character": 0 },
-                        "end": { "line": line_num, "character": 100 }
-                    },
-                    "severity": severity,
-                    "code": "S004",
-                    "source": "sanctifier",
-                    "message": message,
-                }));
+                    None => format!("**{}**\n\n{}", code, message),
+                }
+            })
+            .collect();
+        json!({
+            "contents": { "kind": "markdown", "value": sections.join("\n\n---\n\n") },
+            "range": first["range"],
+        })
+    }
+}
+
+/// Read one `Content-Length`-framed message body, or `None` at end of input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
             }
+            continue;
         }
-
-        // Analyze custom rules
-        let custom_matches = analyzer.analyze_custom_rules(text);
-        for custom_match in custom_matches {
-            diagnostics.push(json!({
-                "range": {
-                    "start": { "line": custom_match.line as i32 - 1, "character": 0 },
-                    "end": { "line": custom_match.line as i32 - 1, "character": 100 }
-                },
-                "severity": 3,
-                "code": "S007",
-                "source": "sanctifier",
-                "message": format!("Custom rule '{}': {}", custom_match.rule_name, custom_match.snippet),
-            }));
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse::<usize>().ok();
         }
-
-        diagnostics
     }
 
-    fn get_code_actions(&self, text: &str) -> Vec<Value> {
-        let config = SanctifyConfig::default();
-        let analyzer = Analyzer::new(config);
-        let mut actions = Vec::new();
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
 
-        // Auth gap code actions
-        let auth_gaps = analyzer.scan_auth_gaps(text);
-        for gap in auth_gaps {
-            actions.push(json!({
-                "title": format!("Add require_auth to function '{}'", gap),
-                "kind": "quickfix",
-                "isPreferred": true,
-            }));
-        }
+fn publish(uri: &str, version: Option<i64>, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "version": version, "diagnostics": diagnostics },
+    })
+}
 
-        // Arithmetic overflow code actions
-        let arithmetic_issues = analyzer.scan_arithmetic_overflow(text);
-        for issue in arithmetic_issues {
-            actions.push(json!({
-                "title": format!("Use checked_{} instead of '{}'", issue.operation.trim_end_matches('='), issue.operation),
-                "kind": "quickfix",
-                "isPreferred": true,
-            }));
+/// Apply one `TextDocumentContentChangeEvent`: a ranged edit, or the whole
+/// new text when the event has no range.
+fn apply_change(text: &mut String, change: &Value) {
+    let Some(new_text) = change["text"].as_str() else {
+        return;
+    };
+    match change.get("range") {
+        Some(range) => {
+            let start = byte_offset(text, &range["start"]);
+            let end = byte_offset(text, &range["end"]).max(start);
+            text.replace_range(start..end, new_text);
         }
-
-        actions
+        None => *text = new_text.to_string(),
     }
+}
 
-    #[allow(dead_code)]
-    fn find_function_line(&self, fn_name: &str, text: &str) -> Option<usize> {
-        let pattern = format!(r"pub\s+fn\s+{}\s*\(", fn_name);
-        let re = regex::Regex::new(&pattern).ok()?;
-        for (line_num, line) in text.lines().enumerate() {
-            if re.is_match(line) {
-                return Some(line_num);
-            }
+/// Byte offset of an LSP position (0-based line, UTF-16 column), clamped to
+/// the end of its line and of the text.
+fn byte_offset(text: &str, at: &Value) -> usize {
+    let (line, character) = position(at);
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return text.len(),
         }
-        None
     }
-
-    #[allow(dead_code)]
-    fn find_struct_line(&self, struct_name: &str, text: &str) -> Option<usize> {
-        let pattern = format!(r"(?:pub\s+)?struct\s+{}\s*(?:\{{|<)", struct_name);
-        let re = regex::Regex::new(&pattern).ok()?;
-        for (line_num, line) in text.lines().enumerate() {
-            if re.is_match(line) {
-                return Some(line_num);
-            }
+    let rest = &text[line_start..];
+    let line_text = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    let mut units = 0;
+    for (offset, c) in line_text.char_indices() {
+        if units >= character {
+            return line_start + offset;
         }
-        None
+        units += c.len_utf16();
     }
+    line_start + line_text.len()
+}
+
+/// `(line, character)` of an LSP position, ordered as in the document.
+fn position(position: &Value) -> (usize, usize) {
+    (
+        position["line"].as_u64().unwrap_or(0) as usize,
+        position["character"].as_u64().unwrap_or(0) as usize,
+    )
+}
+
+/// LSP position of a 1-based line and a column counted in chars, as syn
+/// reports them.
+fn lsp_position(text: &str, line: usize, column: usize) -> Value {
+    let line = line.saturating_sub(1);
+    let character: usize = match text.lines().nth(line) {
+        Some(line_text) => line_text.chars().take(column).map(char::len_utf16).sum(),
+        None => column,
+    };
+    json!({ "line": line, "character": character })
+}
+
+/// LSP range between two `(1-based line, char column)` points.
+fn lsp_range(text: &str, start: (usize, usize), end: (usize, usize)) -> Value {
+    json!({
+        "start": lsp_position(text, start.0, start.1),
+        "end": lsp_position(text, end.0, end.1),
+    })
+}
+
+/// LSP range covering the whole 1-based `line`.
+fn line_range(text: &str, line: usize) -> Value {
+    let len = text
+        .lines()
+        .nth(line.saturating_sub(1))
+        .map_or(0, |l| l.chars().count());
+    lsp_range(text, (line, 0), (line, len))
+}
+
+fn diagnostic(text: &str, v: &RuleViolation) -> Value {
+    let range = match &v.span {
+        Some(span) => lsp_range(
+            text,
+            (span.start_line, span.start_column),
+            (span.end_line, span.end_column),
+        ),
+        None => line_range(text, v.line().unwrap_or(1)),
+    };
+    let message = match &v.suggestion {
+        Some(s) => format!("{}. {}", v.message, s),
+        None => v.message.clone(),
+    };
+    json!({
+        "range": range,
+        "severity": lsp_severity(v.finding_severity),
+        "code": v.code,
+        "source": "sanctifier",
+        "message": message,
+        "data": { "rule": v.rule_name },
+    })
+}
+
+/// Index of the diagnostic of `rule` that `patch` fixes.  Rules produce
+/// patches separately from findings, so a patch is attributed to the last
+/// finding of its rule that starts at or before it — an inserted
+/// `require_auth` belongs to the function flagged above it — or to the
+/// rule's first finding when it precedes them all.
+fn patch_owner(text: &str, diagnostics: &[Value], rule: &str, patch: &Patch) -> Option<usize> {
+    let at = position(&lsp_position(text, patch.start_line, patch.start_column));
+    let of_rule = || {
+        diagnostics
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d["data"]["rule"] == rule)
+    };
+    of_rule()
+        .rfind(|(_, d)| position(&d["range"]["start"]) <= at)
+        .or_else(|| of_rule().next())
+        .map(|(index, _)| index)
+}
+
+/// LSP `DiagnosticSeverity` for a finding severity.
+fn lsp_severity(severity: FindingSeverity) -> u8 {
+    match severity {
+        FindingSeverity::Critical | FindingSeverity::High => 1,
+        FindingSeverity::Medium => 2,
+        FindingSeverity::Low => 3,
+        FindingSeverity::Info => 4,
+    }
+}
+
+/// Convert a `file://` URI to a filesystem path, decoding escapes such as
+/// `%20`.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+/// Load the nearest `.sanctify.toml` above `file`, with the directory it was
+/// found in.  Unlike the batch commands an invalid config must not take the
+/// server down, so errors fall back to the defaults.
+fn config_for_file(file: &Path) -> (SanctifyConfig, Option<PathBuf>) {
+    let Some(path) = file
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join(".sanctify.toml"))
+        .find(|p| p.exists())
+    else {
+        return Default::default();
+    };
+    let config = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default();
+    (config, path.parent().map(Path::to_path_buf))
 }
 
 #[cfg(test)]
@@ -573,4 +669,269 @@ mod tests {
         let arithmetic = analyzer.scan_arithmetic_overflow(source);
         assert_eq!(arithmetic.len(), 0);
     }
+
+    #[test]
+    fn test_lsp_diagnostics_honor_config_suppressions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".sanctify.toml"),
+            "[suppressions]\nS001 = [{ file = \"lib.rs\", line = 4, reason = \"admin only\" }]\n",
+        )
+        .unwrap();
+        let file = dir.path().join("lib.rs");
+        let uri = format!("file://{}", file.display());
+        let source = r#"
+            #[contractimpl]
+            impl MyContract {
+                pub fn set_data(env: Env, val: u32) {
+                    env.storage().instance().set(&DataKey::Val, &val);
+                }
+            }
+        "#;
+
+        let server = SanctifierLanguageServer::new(false);
+        let diagnostics = server.analyze_document(&uri, source);
+        assert!(diagnostics.iter().all(|d| d["code"] != "S001"));
+    }
+
+    #[test]
+    fn test_lsp_loads_config_for_uri_with_escaped_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("my contract");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(
+            project.join(".sanctify.toml"),
+            "[suppressions]\nS001 = [{ file = \"lib.rs\", line = 4, reason = \"admin only\" }]\n",
+        )
+        .unwrap();
+        let file = project.join("lib.rs");
+        let uri = Url::from_file_path(&file).unwrap().to_string();
+        assert!(uri.contains("my%20contract"), "{uri}");
+        assert_eq!(uri_to_path(&uri), Some(file));
+
+        let source = r#"
+            #[contractimpl]
+            impl MyContract {
+                pub fn set_data(env: Env, val: u32) {
+                    env.storage().instance().set(&DataKey::Val, &val);
+                }
+            }
+        "#;
+        let server = SanctifierLanguageServer::new(false);
+        let diagnostics = server.analyze_document(&uri, source);
+        assert!(diagnostics.iter().all(|d| d["code"] != "S001"));
+    }
+
+    #[test]
+    fn test_lsp_diagnostic_ranges_come_from_rule_spans() {
+        let source = "impl MyContract {\n    pub fn add(env: Env, a: u64, b: u64) -> u64 {\n        a + b\n    }\n}\n";
+
+        let server = SanctifierLanguageServer::new(false);
+        let diagnostics = server.analyze_document("untitled:lib.rs", source);
+        let arith = diagnostics
+            .iter()
+            .find(|d| d["code"] == "S003")
+            .expect("arithmetic diagnostic");
+        assert_eq!(arith["range"]["start"], json!({ "line": 2, "character": 8 }));
+        assert_eq!(arith["range"]["end"], json!({ "line": 2, "character": 13 }));
+    }
+
+    const AUTH_GAP_SOURCE: &str = "#[contractimpl]\nimpl Vault {\n    pub fn set_admin(env: Env, admin: Address) {\n        env.storage().instance().set(&DataKey::Admin, &admin);\n    }\n}\n";
+
+    fn open(server: &mut SanctifierLanguageServer, uri: &str, text: &str) -> Value {
+        let mut out = server.handle_message(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text }
+            },
+        }));
+        out.remove(0)
+    }
+
+    #[test]
+    fn test_lsp_incremental_changes_use_utf16_positions() {
+        let mut text = "let s = \"🦀\";\nlet x = a + b;\n".to_string();
+        // Replace `a` on the second line, then the crab (two UTF-16 units).
+        apply_change(
+            &mut text,
+            &json!({
+                "range": { "start": { "line": 1, "character": 8 }, "end": { "line": 1, "character": 9 } },
+                "text": "left",
+            }),
+        );
+        apply_change(
+            &mut text,
+            &json!({
+                "range": { "start": { "line": 0, "character": 9 }, "end": { "line": 0, "character": 11 } },
+                "text": "ok",
+            }),
+        );
+        assert_eq!(text, "let s = \"ok\";\nlet x = left + b;\n");
+
+        apply_change(&mut text, &json!({ "text": "fn main() {}" }));
+        assert_eq!(text, "fn main() {}");
+    }
+
+    #[test]
+    fn test_lsp_publishes_diagnostics_on_open_and_change() {
+        let mut server = SanctifierLanguageServer::new(false);
+        let published = open(&mut server, "untitled:vault.rs", AUTH_GAP_SOURCE);
+        assert_eq!(published["method"], "textDocument/publishDiagnostics");
+        assert_eq!(published["params"]["version"], 1);
+        let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+        let auth = diagnostics
+            .iter()
+            .find(|d| d["code"] == "S001")
+            .expect("auth gap diagnostic");
+        assert_eq!(auth["data"]["rule"], "auth_gap");
+        assert_eq!(auth["range"]["start"]["line"], 2);
+
+        // Inserting the missing check clears the finding.
+        let out = server.handle_message(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "untitled:vault.rs", "version": 2 },
+                "contentChanges": [{
+                    "range": { "start": { "line": 3, "character": 0 }, "end": { "line": 3, "character": 0 } },
+                    "text": "        admin.require_auth();\n",
+                }],
+            },
+        }));
+        assert_eq!(out[0]["params"]["version"], 2);
+        assert!(out[0]["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|d| d["code"] != "S001"));
+    }
+
+    #[test]
+    fn test_lsp_quick_fix_applies_rule_patch() {
+        let mut server = SanctifierLanguageServer::new(false);
+        let published = open(&mut server, "untitled:vault.rs", AUTH_GAP_SOURCE);
+        let auth = published["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["code"] == "S001")
+            .unwrap()
+            .clone();
+
+        let out = server.handle_message(&json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": "untitled:vault.rs" },
+                "range": auth["range"],
+                "context": { "diagnostics": [auth] },
+            },
+        }));
+        let actions = out[0]["result"].as_array().unwrap();
+        let fix = actions
+            .iter()
+            .find(|a| a["diagnostics"][0]["code"] == "S001")
+            .expect("auth gap quick fix");
+        assert_eq!(fix["kind"], "quickfix");
+        let edit = &fix["edit"]["changes"]["untitled:vault.rs"][0];
+        assert!(edit["newText"].as_str().unwrap().contains("require_auth"));
+        assert_eq!(edit["range"]["start"]["line"], 3);
+    }
+
+    #[test]
+    fn test_lsp_hover_explains_finding_code() {
+        let mut server = SanctifierLanguageServer::new(false);
+        open(&mut server, "untitled:vault.rs", AUTH_GAP_SOURCE);
+        let out = server.handle_message(&json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": "untitled:vault.rs" },
+                "position": { "line": 2, "character": 12 },
+            },
+        }));
+        let entry = finding_codes::lookup_finding_code("S001").unwrap();
+        let value = out[0]["result"]["contents"]["value"].as_str().unwrap();
+        assert!(value.starts_with("**S001 "), "{value}");
+        assert!(value.contains(entry.remediation), "{value}");
+
+        let out = server.handle_message(&json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": "untitled:vault.rs" },
+                "position": { "line": 0, "character": 0 },
+            },
+        }));
+        assert_eq!(out[0]["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_lsp_runs_yaml_rules_and_rule_tables_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".sanctify.toml"),
+            "custom_rules_yaml = \"rules.yaml\"\n\n[rules.auth_gap]\nenabled = false\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("rules.yaml"),
+            "- id: no_admin_key\n  name: No admin key\n  description: Admin key stored in instance storage\n  severity: warning\n  matcher:\n    type: regex\n    pattern: \"DataKey::Admin\"\n",
+        )
+        .unwrap();
+        let uri = format!("file://{}", dir.path().join("vault.rs").display());
+
+        let diagnostics =
+            SanctifierLanguageServer::new(false).analyze_document(&uri, AUTH_GAP_SOURCE);
+        assert!(diagnostics.iter().all(|d| d["code"] != "S001"));
+        let custom = diagnostics
+            .iter()
+            .find(|d| d["data"]["rule"] == "no_admin_key")
+            .expect("YAML rule diagnostic");
+        assert_eq!(custom["code"], "S007");
+        assert_eq!(custom["range"]["start"]["line"], 3);
+    }
+
+    #[test]
+    fn test_lsp_session_over_framed_stdio() {
+        let messages = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ];
+        let mut input = Vec::new();
+        for message in &messages {
+            let body = message.to_string();
+            write!(
+                input,
+                "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+
+        let mut output = Vec::new();
+        SanctifierLanguageServer::new(false)
+            .run(io::Cursor::new(input), &mut output)
+            .unwrap();
+
+        let mut reader = io::Cursor::new(output);
+        let mut responses = Vec::new();
+        while let Some(body) = read_message(&mut reader).unwrap() {
+            responses.push(serde_json::from_slice::<Value>(&body).unwrap());
+        }
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["textDocumentSync"]["change"],
+            SYNC_INCREMENTAL
+        );
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[2]["result"], Value::Null);
+    }
 }
@@ -1,7 +1,10 @@
 pub mod analyze;
 pub mod badge;
 pub mod benchmark;
+pub mod build_manifest;
+pub mod cache;
 pub mod callgraph;
+pub mod changed_lines;
 pub mod color;
 pub mod complexity;
 pub mod deploy;
@@ -11,8 +14,10 @@ pub mod explain;
 pub mod export;
 pub mod fix;
 pub mod gas;
+pub mod git;
 pub mod init;
 pub mod install_hooks;
+pub mod interface;
 pub mod lsp;
 pub mod pr_comment;
 pub mod reentrancy;
@@ -20,10 +25,12 @@ pub mod report;
 pub mod report_templates;
 pub mod sarif;
 pub mod serve;
+pub mod smt;
 pub mod storage;
 pub mod suppress;
 pub mod update;
 pub mod upgrade;
+pub mod upgrade_check;
 pub mod verify;
 pub mod verify_deployment;
 pub mod watch;
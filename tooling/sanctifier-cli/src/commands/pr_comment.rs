@@ -1,4 +1,3 @@
-#![allow(dead_code)]
 //! # GitHub PR Comment Formatter
 //!
 //! Renders a structured "delta vs base branch" summary for posting as a
@@ -18,7 +17,7 @@ pub struct DiffSummary {
     pub resolved_count: usize,
     /// new findings grouped by severity label
     pub by_severity: HashMap<String, usize>,
-    /// top new findings (up to 10) for inline display
+    /// top new findings (up to 10, most severe first) for inline display
     pub top_new: Vec<FindingSummary>,
 }
 
@@ -50,30 +49,33 @@ pub fn build_diff_summary(diff_json: &Value) -> DiffSummary {
                         .unwrap_or("unknown")
                         .to_string();
                     *summary.by_severity.entry(sev.clone()).or_insert(0) += 1;
-                    if summary.top_new.len() < 10 {
-                        summary.top_new.push(FindingSummary {
-                            rule: f
-                                .get("rule_id")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string(),
-                            severity: sev,
-                            file: file_val
-                                .get("file")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("?")
-                                .to_string(),
-                            message: f
-                                .get("message")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
-                        });
-                    }
+                    summary.top_new.push(FindingSummary {
+                        rule: f
+                            .get("rule_id")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string(),
+                        severity: sev,
+                        file: file_val
+                            .get("file")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("?")
+                            .to_string(),
+                        message: f
+                            .get("message")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string(),
+                    });
                 }
             }
         }
     }
+    // Show the most severe findings first.
+    summary
+        .top_new
+        .sort_by_key(|f| std::cmp::Reverse(severity_rank(&f.severity)));
+    summary.top_new.truncate(10);
 
     // Resolved findings (present in baseline but not in current)
     if let Some(resolved) = diff_json
@@ -259,6 +261,23 @@ mod tests {
         assert!(md.contains("No new findings"));
     }
 
+    #[test]
+    fn test_top_new_lists_most_severe_first() {
+        let diff = json!({
+            "new_findings": {
+                "src/a.rs": { "file": "src/a.rs", "findings": [
+                    { "rule_id": "S008", "severity": "low", "message": "event" },
+                ]},
+                "src/b.rs": { "file": "src/b.rs", "findings": [
+                    { "rule_id": "S001", "severity": "critical", "message": "auth" },
+                ]},
+            },
+        });
+        let s = build_diff_summary(&diff);
+        assert_eq!(s.top_new[0].rule, "S001");
+        assert_eq!(s.top_new[1].file, "src/a.rs");
+    }
+
     #[test]
     fn test_severity_rank_order() {
         assert!(severity_rank("critical") > severity_rank("high"));
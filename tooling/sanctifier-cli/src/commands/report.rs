@@ -10,7 +10,7 @@
 
 use crate::commands::analyze::{
     analyze_single_file, collect_rs_files, is_soroban_project, load_config, run_with_timeout,
-    FileAnalysisResult,
+    FileAnalysisResult, SuppressedFinding,
 };
 use crate::vulndb::{VulnDatabase, VulnMatch};
 use clap::Args;
@@ -174,6 +174,7 @@ struct ReportData {
     sep41_issues: Vec<sanctifier_core::Sep41Issue>,
     vuln_matches: Vec<VulnMatch>,
     timed_out_files: Vec<String>,
+    suppressed: Vec<SuppressedFinding>,
     has_critical: bool,
     has_high: bool,
 }
@@ -193,6 +194,7 @@ fn merge_results(results: Vec<FileAnalysisResult>) -> ReportData {
         sep41_issues: vec![],
         vuln_matches: vec![],
         timed_out_files: vec![],
+        suppressed: vec![],
         has_critical: false,
         has_high: false,
     };
@@ -215,6 +217,7 @@ fn merge_results(results: Vec<FileAnalysisResult>) -> ReportData {
         data.smt_issues.extend(r.smt_issues);
         data.sep41_issues.extend(r.sep41_issues);
         data.vuln_matches.extend(r.vuln_matches);
+        data.suppressed.extend(r.suppressed);
     }
 
     data.has_critical =
@@ -528,6 +531,21 @@ fn render_markdown(data: &ReportData, path: &Path, vuln_db_version: &str) -> Str
         md.push_str("_No findings — contract passed all checks._ 🎉\n\n");
     }
 
+    if !data.suppressed.is_empty() {
+        md.push_str("### Suppressed Findings\n\n");
+        md.push_str("> Accepted via `[suppressions]` in `.sanctify.toml`; not counted above.\n\n");
+        md.push_str("| Code | Location | Reason |\n|---|---|---|\n");
+        for s in &data.suppressed {
+            md.push_str(&format!(
+                "| `{}` | `{}` | {} |\n",
+                s.code,
+                s.location,
+                s.reason.replace('|', "\\|")
+            ));
+        }
+        md.push('\n');
+    }
+
     // ── Footer ──
     md.push_str("---\n\n");
     md.push_str(&format!(
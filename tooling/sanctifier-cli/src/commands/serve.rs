@@ -1,10 +1,12 @@
+use crate::commands::analyze::load_config;
+use crate::commands::cache::DiskCache;
 use anyhow::{Context, Result};
 use clap::Args;
-use sanctifier_core::analysis_cache::AnalysisCache;
 use sanctifier_core::rules::RuleRegistry;
-use sanctifier_core::{Analyzer, SanctifyConfig};
+use sanctifier_core::Analyzer;
 use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::Arc;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use warp::Filter;
@@ -22,10 +24,9 @@ pub struct ServeArgs {
 
 #[derive(Clone)]
 struct AppState {
-    #[allow(dead_code)]
     registry: Arc<RuleRegistry>,
     analyzer: Arc<Analyzer>,
-    cache: Arc<Mutex<AnalysisCache<serde_json::Value>>>,
+    cache: Arc<DiskCache>,
 }
 
 pub fn exec(args: ServeArgs) -> Result<()> {
@@ -34,10 +35,12 @@ pub fn exec(args: ServeArgs) -> Result<()> {
 }
 
 async fn serve_async(args: ServeArgs) -> Result<()> {
-    let registry = Arc::new(RuleRegistry::with_default_rules());
-    let config = SanctifyConfig::default();
+    // The server analyzes request bodies, not files on disk, so it applies the
+    // `.sanctify.toml` of the directory it was started from.
+    let config = load_config(Path::new("."));
+    let registry = Arc::new(RuleRegistry::from_config(&config)?);
+    let cache = Arc::new(DiskCache::open(Path::new("."), &config, &registry));
     let analyzer = Arc::new(Analyzer::new(config));
-    let cache = Arc::new(Mutex::new(AnalysisCache::new(100)));
 
     let state = AppState {
         registry,
@@ -94,46 +97,49 @@ async fn handle_analyze(
     file.flush().await.map_err(|_| warp::reject::reject())?;
 
     // Check cache or analyze
-    let cache_key = format!("{:x}", md5::compute(source));
     let analyzer = &state.analyzer;
-    let findings = {
-        let mut cache = state.cache.lock().unwrap();
-        cache.get_or_analyze(&cache_key, source, || {
-            let mut results = serde_json::Map::new();
-
-            let collisions = analyzer.scan_storage_collisions(source);
-            results.insert(
-                "storage_collisions".into(),
-                serde_json::to_value(collisions).unwrap_or_default(),
-            );
-
-            let size_warnings = analyzer.analyze_ledger_size(source);
-            results.insert(
-                "ledger_size_warnings".into(),
-                serde_json::to_value(size_warnings).unwrap_or_default(),
-            );
-
-            let unsafe_patterns = analyzer.analyze_unsafe_patterns(source);
-            results.insert(
-                "unsafe_patterns".into(),
-                serde_json::to_value(unsafe_patterns).unwrap_or_default(),
-            );
-
-            let auth_gaps = analyzer.scan_auth_gaps(source);
-            results.insert(
-                "auth_gaps".into(),
-                serde_json::to_value(auth_gaps).unwrap_or_default(),
-            );
-
-            let panic_issues = analyzer.scan_panics(source);
-            results.insert(
-                "panic_issues".into(),
-                serde_json::to_value(panic_issues).unwrap_or_default(),
-            );
-
-            serde_json::Value::Object(results)
-        })
-    };
+    let registry = &state.registry;
+    let findings = state.cache.get_or_insert(&["serve", source], || {
+        let mut results = serde_json::Map::new();
+
+        let collisions = analyzer.scan_storage_collisions(source);
+        results.insert(
+            "storage_collisions".into(),
+            serde_json::to_value(collisions).unwrap_or_default(),
+        );
+
+        let size_warnings = analyzer.analyze_ledger_size(source);
+        results.insert(
+            "ledger_size_warnings".into(),
+            serde_json::to_value(size_warnings).unwrap_or_default(),
+        );
+
+        let unsafe_patterns = analyzer.analyze_unsafe_patterns(source);
+        results.insert(
+            "unsafe_patterns".into(),
+            serde_json::to_value(unsafe_patterns).unwrap_or_default(),
+        );
+
+        let auth_gaps = analyzer.scan_auth_gaps(source);
+        results.insert(
+            "auth_gaps".into(),
+            serde_json::to_value(auth_gaps).unwrap_or_default(),
+        );
+
+        let panic_issues = analyzer.scan_panics(source);
+        results.insert(
+            "panic_issues".into(),
+            serde_json::to_value(panic_issues).unwrap_or_default(),
+        );
+
+        let rule_violations = registry.run_all(source);
+        results.insert(
+            "rule_violations".into(),
+            serde_json::to_value(rule_violations).unwrap_or_default(),
+        );
+
+        serde_json::Value::Object(results)
+    });
 
     Ok(warp::reply::json(&findings))
 }
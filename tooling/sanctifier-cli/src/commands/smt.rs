@@ -0,0 +1,320 @@
+//! The S011 pass of `analyze`: SMT proofs that contract functions preserve
+//! their `#[invariant]`s and that fixed-point `a * b / d` calculations cannot
+//! overflow.
+//!
+//! Findings are reported as `smt_invariant` violations spanning the function
+//! they were proved in, so `[suppressions]`, inline suppressions and
+//! `--changed-since` treat them like those of any other rule.
+//!
+//! Z3 runs in-process when the `smt` feature links it.  Otherwise, and for
+//! `--solver cvc5|bitwuzla`, the proofs are piped as SMT-LIB2 to the solver
+//! binary on `PATH`; invariant preservation needs the in-process encoding
+//! and is skipped there.
+
+use crate::commands::cache::DiskCache;
+use sanctifier_core::rules::{Severity, SourceSpan};
+use sanctifier_core::smt::{
+    discover_fixed_point_mul_divs, fixed_point_query, invariant_queries, FixedPointProofError,
+    SmtBackend, SmtFinding, SmtFindingKind, SmtLibError,
+};
+use sanctifier_core::suppression::{self, InlineSuppressions, SuppressedViolation, SuppressionMap};
+use sanctifier_core::RuleViolation;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use syn::spanned::Spanned;
+use syn::{ImplItem, Item};
+
+/// Rule name the S011 findings are reported under.
+pub(crate) const RULE_NAME: &str = "smt_invariant";
+
+/// Solver for the S011 proofs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Solver {
+    #[default]
+    Z3,
+    Cvc5,
+    Bitwuzla,
+}
+
+impl Solver {
+    fn backend(self) -> SmtBackend {
+        match self {
+            Self::Z3 => SmtBackend::Z3,
+            Self::Cvc5 => SmtBackend::Cvc5,
+            Self::Bitwuzla => SmtBackend::Bitwuzla,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Z3 => "z3",
+            Self::Cvc5 => "cvc5",
+            Self::Bitwuzla => "bitwuzla",
+        }
+    }
+}
+
+/// How `analyze` runs the S011 pass: `--solver` and `--emit-smt2`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SmtOptions {
+    pub(crate) solver: Solver,
+    /// Directory every proof obligation is written to as a `.smt2` script.
+    pub(crate) emit_smt2: Option<PathBuf>,
+}
+
+/// The S011 violations of `content`, tagged with `file_name` and split, like
+/// [`crate::commands::analyze::run_rules`], into those that remain and those
+/// covered by `[suppressions]`.
+pub(crate) fn run(
+    options: &SmtOptions,
+    cache: Option<&DiskCache>,
+    suppressions: &SuppressionMap,
+    content: &str,
+    file_name: &str,
+) -> (Vec<RuleViolation>, Vec<SuppressedViolation>) {
+    if let Some(dir) = &options.emit_smt2 {
+        emit_smt2(dir, content, file_name);
+    }
+    let violations = violations(options.solver, cache, content)
+        .into_iter()
+        .map(|v| v.in_file(file_name))
+        .collect();
+    suppression::partition_violations(suppressions, file_name, violations)
+}
+
+/// The S011 violations of `content`, proved again unless `cache` holds them,
+/// without those silenced by inline suppressions.
+pub(crate) fn violations(
+    solver: Solver,
+    cache: Option<&DiskCache>,
+    content: &str,
+) -> Vec<RuleViolation> {
+    let findings = match cache {
+        Some(cache) => cache.get_or_insert_extra(&[RULE_NAME, solver.name(), content], || {
+            findings(solver, content)
+        }),
+        None => findings(solver, content),
+    };
+    let spans = function_spans(content);
+    let violations = findings
+        .iter()
+        .map(|finding| violation(finding, spans.get(&finding.location)))
+        .collect();
+    InlineSuppressions::parse(content).filter(violations).0
+}
+
+/// The findings of `solver` over `content`.  A calculation the solver fails
+/// on is reported and skipped; checks it could not decide in time are logged
+/// and skipped.
+fn findings(solver: Solver, content: &str) -> Vec<SmtFinding> {
+    let mut findings = Vec::new();
+    #[cfg(feature = "smt")]
+    if solver == Solver::Z3 {
+        use sanctifier_core::smt::{verify_invariant_preservation, SmtConfig};
+        findings = verify_invariant_preservation(content, &SmtConfig::default());
+    }
+    for candidate in discover_fixed_point_mul_divs(content) {
+        match candidate.verify(solver.backend()) {
+            Ok(finding) => findings.extend(finding),
+            Err(FixedPointProofError::SmtLib(err @ SmtLibError::Spawn { .. })) => {
+                warn_solver_missing(&err);
+                break;
+            }
+            Err(err) => tracing::warn!(
+                target: "sanctifier",
+                function = %candidate.spec.function_name,
+                error = %err,
+                "Could not prove `{}`",
+                candidate.expression
+            ),
+        }
+    }
+    findings.retain(|finding| {
+        if finding.is_timeout {
+            tracing::debug!(
+                target: "sanctifier",
+                "{} timed out on `{}` in {}",
+                solver.name(),
+                finding.invariant_name,
+                finding.location
+            );
+        }
+        !finding.is_timeout
+    });
+    findings
+}
+
+/// Say once per run that the solver binary could not be started, instead of
+/// once per calculation.
+fn warn_solver_missing(err: &SmtLibError) {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| {
+        tracing::warn!(target: "sanctifier", error = %err, "S011 proofs skipped");
+    });
+}
+
+/// Write every proof obligation of `content` to `dir` as SMT-LIB2, named
+/// after `file_name` so the scripts of different files do not collide.
+fn emit_smt2(dir: &Path, content: &str, file_name: &str) {
+    let prefix = file_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let prefix = prefix.trim_matches('_');
+    let fixed_point = discover_fixed_point_mul_divs(content)
+        .into_iter()
+        .filter_map(|candidate| fixed_point_query(&candidate.spec).ok())
+        .enumerate()
+        .map(|(index, mut query)| {
+            query.name = format!("{}_{index}", query.name);
+            query
+        });
+    for mut query in fixed_point.chain(invariant_queries(content)) {
+        query.name = format!("{prefix}__{}", query.name);
+        if let Err(err) = query.write_to(dir) {
+            tracing::warn!(
+                target: "sanctifier",
+                error = %err,
+                "Failed to write {}.smt2 to {}",
+                query.name,
+                dir.display()
+            );
+        }
+    }
+}
+
+/// `finding` as a violation spanning its function, when that is known.
+fn violation(finding: &SmtFinding, span: Option<&SourceSpan>) -> RuleViolation {
+    let location = match span {
+        Some(span) => format!("{}:{}", finding.location, span.start_line),
+        None => finding.location.clone(),
+    };
+    let violation = RuleViolation::new(RULE_NAME, Severity::Error, description(finding), location);
+    match span {
+        Some(span) => violation.with_span(span.clone()),
+        None => violation,
+    }
+}
+
+/// One-line description of `finding`: what was proved, the counterexample,
+/// the violating inputs and, for invariants, the path taken.
+pub(crate) fn description(finding: &SmtFinding) -> String {
+    let mut description = match finding.kind {
+        SmtFindingKind::FixedPointOverflow => {
+            format!("`{}` can overflow", finding.invariant_name)
+        }
+        SmtFindingKind::InvariantNotPreserved => format!(
+            "`{}` does not preserve invariant `{}`",
+            finding.location, finding.invariant_name
+        ),
+        _ => format!("Invariant `{}` can be violated", finding.invariant_name),
+    };
+    if let Some(counterexample) = &finding.counterexample {
+        description.push_str(": ");
+        description.push_str(counterexample);
+    }
+    if !finding.witness.is_empty() {
+        let inputs = finding
+            .witness
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect::<Vec<_>>();
+        description.push_str(&format!(" [inputs: {}]", inputs.join(", ")));
+    }
+    if !finding.path.is_empty() {
+        description.push_str(&format!(" [path: {}]", finding.path.join(" → ")));
+    }
+    description
+}
+
+/// Span of every free function and method in `content`, by name; the first
+/// one wins when a name is declared twice.
+fn function_spans(content: &str) -> HashMap<String, SourceSpan> {
+    let Ok(file) = syn::parse_file(content) else {
+        return HashMap::new();
+    };
+    let mut spans = HashMap::new();
+    for item in &file.items {
+        match item {
+            Item::Fn(f) => {
+                let name = f.sig.ident.to_string();
+                let span = SourceSpan::from_span(f.span()).in_function(&name);
+                spans.entry(name).or_insert(span);
+            }
+            Item::Impl(imp) => {
+                for item in &imp.items {
+                    if let ImplItem::Fn(f) = item {
+                        let name = f.sig.ident.to_string();
+                        let span = SourceSpan::from_span(f.span())
+                            .in_function(&name)
+                            .in_impl(imp);
+                        spans.entry(name).or_insert(span);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    const SOURCE: &str = "\
+#[contractimpl]
+impl Token {
+    #[allow(sanctifier::smt_invariant)]
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        move_balance(&env, &from, &to, amount);
+    }
+
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        add_balance(&env, &to, amount);
+    }
+}
+";
+
+    fn finding(function: &str) -> SmtFinding {
+        SmtFinding {
+            kind: SmtFindingKind::InvariantNotPreserved,
+            invariant_name: "supply == sum(balance)".into(),
+            location: function.into(),
+            counterexample: Some("mint(amount = 1) takes supply = 0 to supply = 0".into()),
+            witness: BTreeMap::from([("amount".into(), "1".into())]),
+            path: vec!["add_balance(&env, &to, amount)".into()],
+            is_timeout: false,
+        }
+    }
+
+    #[test]
+    fn findings_span_their_function_and_honor_inline_suppressions() {
+        let spans = function_spans(SOURCE);
+        let violations = ["transfer", "mint"]
+            .iter()
+            .map(|f| violation(&finding(f), spans.get(*f)))
+            .collect();
+        let (kept, _) = InlineSuppressions::parse(SOURCE).filter(violations);
+
+        assert_eq!(kept.len(), 1);
+        let mint = &kept[0];
+        assert_eq!(mint.code, "S011");
+        assert_eq!(mint.location, "mint:8");
+        let span = mint.span.as_ref().unwrap();
+        assert_eq!((span.start_line, span.end_line), (8, 10));
+        assert_eq!(span.contract.as_deref(), Some("Token"));
+    }
+
+    #[test]
+    fn description_reports_inputs_and_path() {
+        assert_eq!(
+            description(&finding("mint")),
+            "`mint` does not preserve invariant `supply == sum(balance)`: \
+             mint(amount = 1) takes supply = 0 to supply = 0 \
+             [inputs: amount = 1] [path: add_balance(&env, &to, amount)]"
+        );
+    }
+}
@@ -36,17 +36,15 @@ pub fn exec(args: StorageArgs) -> anyhow::Result<()> {
         ));
     }
 
-    let mut collisions = Vec::new();
+    // Scan every file together so keys shared across modules and crates are
+    // cross-checked.
+    let mut sources = Vec::new();
     for file_path in rust_files {
         let source = fs::read_to_string(&file_path)
             .with_context(|| format!("failed to read {}", file_path.display()))?;
-        let file_label = file_path.display().to_string();
-
-        for mut collision in analyzer.scan_storage_collisions(&source) {
-            collision.location = qualify_location(&file_label, &collision.location);
-            collisions.push(collision);
-        }
+        sources.push((file_path.display().to_string(), source));
     }
+    let mut collisions = analyzer.scan_storage_collisions_in_files(&sources);
 
     collisions.sort_by(|left, right| {
         left.location
@@ -103,16 +101,6 @@ fn normalize_scan_path(path: &Path) -> anyhow::Result<PathBuf> {
     ))
 }
 
-fn qualify_location(file_label: &str, location: &str) -> String {
-    if let Some((_, line)) = location.rsplit_once(':') {
-        if line.parse::<usize>().is_ok() {
-            return format!("{}:{}", file_label, line);
-        }
-    }
-
-    format!("{}:{}", file_label, location)
-}
-
 fn collect_rust_files(path: &Path, ignore_paths: &[String]) -> Vec<PathBuf> {
     if path.is_file() {
         return vec![path.to_path_buf()];
@@ -185,14 +173,6 @@ fn load_config(path: &Path) -> anyhow::Result<SanctifyConfig> {
 mod tests {
     use super::*;
 
-    #[test]
-    fn qualify_location_prefers_file_and_line() {
-        assert_eq!(
-            qualify_location("contracts/sample.rs", "storage-op:42"),
-            "contracts/sample.rs:42"
-        );
-    }
-
     #[test]
     fn normalize_scan_path_accepts_cargo_toml() {
         let path = PathBuf::from("/tmp/demo/Cargo.toml");
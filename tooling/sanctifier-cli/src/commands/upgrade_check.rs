@@ -0,0 +1,200 @@
+use crate::commands::analyze::{collect_rs_files, load_config};
+use crate::commands::color as c;
+use crate::commands::git::git;
+use anyhow::{bail, Context};
+use clap::{Args, ValueEnum};
+use sanctifier_core::storage_layout::{
+    diff_storage_layouts, extract_storage_layout, StorageLayout, UpgradeRiskReport,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct UpgradeCheckArgs {
+    /// Version being upgraded from: a source file, a contract directory, or a
+    /// git ref whose copy of --new is compared
+    #[arg(long, value_name = "GIT_REF|PATH")]
+    pub old: String,
+
+    /// Version being upgraded to: a source file or contract directory
+    #[arg(long, value_name = "PATH", default_value = ".")]
+    pub new: PathBuf,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Exit with code 1 when the upgrade changes the storage layout incompatibly
+    #[arg(long)]
+    pub exit_code: bool,
+}
+
+pub fn exec(args: UpgradeCheckArgs) -> anyhow::Result<()> {
+    let new_layout = layout_from_path(&args.new)?;
+    let old_path = Path::new(&args.old);
+    let old_layout = if old_path.exists() {
+        layout_from_path(old_path)?
+    } else {
+        layout_from_git(&args.old, &args.new)?
+    };
+
+    let report = diff_storage_layouts(&old_layout, &new_layout);
+    match args.format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "upgrade_risk": report }))?
+        ),
+        OutputFormat::Text => print_text(&args, &report),
+    }
+
+    if args.exit_code && !report.compatible {
+        std::process::exit(crate::exit_codes::FINDINGS_FOUND);
+    }
+    Ok(())
+}
+
+fn print_text(args: &UpgradeCheckArgs, report: &UpgradeRiskReport) {
+    println!(
+        "{}",
+        c::bold(&format!(
+            "Storage layout upgrade check: {} -> {}",
+            args.old,
+            args.new.display()
+        ))
+    );
+    println!(
+        "   {} type(s), {} key(s) before; {} type(s), {} key(s) after",
+        report.old_layout.types.len(),
+        report.old_layout.keys.len(),
+        report.new_layout.types.len(),
+        report.new_layout.keys.len()
+    );
+
+    if report.compatible {
+        println!(
+            "\n{} No storage layout changes break existing entries.",
+            c::green_check()
+        );
+        return;
+    }
+
+    println!(
+        "\n{} [{}] {} upgrade risk(s) found:",
+        c::yellow_warning(),
+        report.code,
+        report.changes.len()
+    );
+    for change in &report.changes {
+        println!(
+            "   [{}] {}: {}",
+            c::red(change.severity.as_str()),
+            c::bold(&change.item),
+            change.message
+        );
+        println!("      at {}", c::dimmed(&change.location));
+    }
+}
+
+/// Layout of a source file, or of the Rust files under a directory that
+/// `ignore_paths` does not exclude; files that do not parse are skipped.
+fn layout_from_path(path: &Path) -> anyhow::Result<StorageLayout> {
+    if path.is_file() {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let name = path.file_name().unwrap_or(path.as_os_str());
+        return parse_layout(&source, &name.to_string_lossy());
+    }
+    if !path.is_dir() {
+        bail!("{} is not a valid Rust file or directory", path.display());
+    }
+
+    let mut files = collect_rs_files(path, &load_config(path).ignore_paths);
+    files.sort();
+    let mut layout = StorageLayout::default();
+    for file in files {
+        let source = fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let name = file
+            .strip_prefix(path)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        match parse_layout(&source, &name) {
+            Ok(file_layout) => layout.merge(file_layout),
+            Err(err) => warn!(target: "sanctifier", "{:#}; skipping it", err),
+        }
+    }
+    Ok(layout)
+}
+
+/// Layout of `path` as committed at `git_ref`.
+fn layout_from_git(git_ref: &str, path: &Path) -> anyhow::Result<StorageLayout> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let toplevel = git(dir, &["rev-parse", "--show-toplevel"])
+        .with_context(|| format!("{} is neither a path nor a git ref", git_ref))?;
+    let toplevel = PathBuf::from(toplevel.trim());
+    let absolute =
+        fs::canonicalize(path).with_context(|| format!("failed to resolve {}", path.display()))?;
+    let relative = absolute
+        .strip_prefix(fs::canonicalize(&toplevel)?)
+        .with_context(|| format!("{} is outside the git repository", path.display()))?;
+    let spec = relative.to_string_lossy().replace('\\', "/");
+    let spec = if spec.is_empty() {
+        ".".to_string()
+    } else {
+        spec
+    };
+
+    let listing = git(
+        &toplevel,
+        &["ls-tree", "-r", "--name-only", git_ref, "--", &spec],
+    )
+    .with_context(|| format!("{} is neither a path nor a git ref", git_ref))?;
+    let prefix = if path.is_file() {
+        Path::new(&spec).parent().map(Path::to_path_buf)
+    } else {
+        Some(PathBuf::from(&spec))
+    };
+
+    let mut layout = StorageLayout::default();
+    let mut found = false;
+    for file in listing.lines().filter(|f| f.ends_with(".rs")) {
+        if file.split('/').any(|part| part == "target") {
+            continue;
+        }
+        found = true;
+        let source = git(&toplevel, &["show", &format!("{}:{}", git_ref, file)])?;
+        let name = prefix
+            .as_deref()
+            .filter(|p| !p.as_os_str().is_empty() && *p != Path::new("."))
+            .and_then(|p| Path::new(file).strip_prefix(p).ok())
+            .map_or_else(|| file.to_string(), |p| p.display().to_string());
+        layout.merge(parse_layout(&source, &name)?);
+    }
+    if !found {
+        bail!("no Rust sources under {} at {}", spec, git_ref);
+    }
+    Ok(layout)
+}
+
+fn parse_layout(source: &str, name: &str) -> anyhow::Result<StorageLayout> {
+    let layout =
+        extract_storage_layout(source).with_context(|| format!("failed to parse {}", name))?;
+    Ok(layout.in_file(name))
+}
@@ -1,7 +1,11 @@
 #![allow(dead_code)]
+use crate::commands::analyze::{collect_rs_files, load_config};
+use crate::commands::build_manifest::{manifest_path, BuildManifest};
 use crate::commands::color as c;
 use anyhow::{bail, Context};
 use clap::Args;
+use sanctifier_core::contract_interface::{extract_interface, ContractInterface};
+use sanctifier_core::contract_spec::{diff_interfaces, interface_from_wasm, SpecChange};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use tracing::info;
@@ -13,8 +17,8 @@ pub struct VerifyArgs {
     pub path: PathBuf,
 
     /// On-chain contract ID to fetch the deployed WASM from
-    #[arg(long)]
-    pub contract_id: String,
+    #[arg(long, required_unless_present = "wasm_file")]
+    pub contract_id: Option<String>,
 
     /// Stellar/Soroban network passphrase or alias (e.g. "testnet", "mainnet")
     #[arg(long, default_value = "testnet")]
@@ -23,20 +27,39 @@ pub struct VerifyArgs {
     /// Path to a pre-fetched on-chain WASM file (skips network fetch)
     #[arg(long)]
     pub wasm_file: Option<PathBuf>,
+
+    /// Path to a locally built WASM file (skips the local build)
+    #[arg(long)]
+    pub local_wasm: Option<PathBuf>,
+
+    /// Compare the embedded contract specs (functions, types, error enums)
+    /// instead of the bytecode hashes. Without --local-wasm the on-chain spec
+    /// is compared against the contract source at PATH.
+    #[arg(long)]
+    pub spec: bool,
+
+    /// Build manifest recorded when the on-chain WASM was built; defaults to
+    /// the manifest next to --wasm-file. Used to explain hash mismatches.
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
 }
 
 pub fn exec(args: VerifyArgs) -> anyhow::Result<()> {
+    if args.spec {
+        return exec_spec(&args);
+    }
+
     println!("{}", c::bold("sanctifier verify — bytecode verification"));
     println!();
 
     // Step 1: Build local WASM
-    let local_wasm = build_local_wasm(&args.path)?;
+    let (local_wasm, built) = match &args.local_wasm {
+        Some(p) => (p.clone(), false),
+        None => (build_local_wasm(&args.path)?, true),
+    };
 
     // Step 2: Obtain on-chain WASM (from file or network)
-    let remote_wasm_path = match &args.wasm_file {
-        Some(p) => p.clone(),
-        None => fetch_onchain_wasm(&args.contract_id, &args.network)?,
-    };
+    let remote_wasm_path = remote_wasm(&args)?;
 
     // Step 3: Read and hash both files
     let local_bytes = std::fs::read(&local_wasm)
@@ -51,6 +74,17 @@ pub fn exec(args: VerifyArgs) -> anyhow::Result<()> {
     let local_hash = sha256_hex(&local_bytes);
     let remote_hash = sha256_hex(&remote_bytes);
 
+    // Record how the local WASM was built. A WASM passed with --local-wasm
+    // keeps its own manifest, or is assumed to come from this environment.
+    let local_manifest_path = manifest_path(&local_wasm);
+    let local_manifest = match BuildManifest::read(&local_manifest_path)? {
+        Some(manifest) if !built => manifest,
+        _ => BuildManifest::capture(&args.path, &local_bytes),
+    };
+    if built {
+        local_manifest.write(&local_manifest_path)?;
+    }
+
     info!(target: "sanctifier", local_hash = %local_hash, remote_hash = %remote_hash, "bytecode hashes");
 
     println!(
@@ -79,10 +113,174 @@ pub fn exec(args: VerifyArgs) -> anyhow::Result<()> {
         );
         println!("  Local  sha256: {}", local_hash);
         println!("  Remote sha256: {}", remote_hash);
+        explain_mismatch(&args, &local_manifest, &remote_hash)?;
+        println!(
+            "  {}",
+            c::dimmed("Re-run with --spec to see which functions or types differ.")
+        );
         bail!(
             "bytecode mismatch detected for contract {}",
-            args.contract_id
+            contract_label(&args)
+        );
+    }
+}
+
+/// Compare the build manifest of the on-chain WASM, when one was recorded,
+/// with the local one and print the build inputs that differ.
+fn explain_mismatch(
+    args: &VerifyArgs,
+    local: &BuildManifest,
+    remote_hash: &str,
+) -> anyhow::Result<()> {
+    let path = match (&args.manifest, &args.wasm_file) {
+        (Some(p), _) => p.clone(),
+        (None, Some(wasm)) => manifest_path(wasm),
+        (None, None) => {
+            println!(
+                "  {}",
+                c::dimmed("Pass --manifest with the build manifest of the deployed WASM to find the cause.")
+            );
+            return Ok(());
+        }
+    };
+    let Some(remote) = BuildManifest::read(&path)? else {
+        if args.manifest.is_some() {
+            bail!("build manifest not found: {}", path.display());
+        }
+        return Ok(());
+    };
+
+    println!();
+    if remote.wasm_sha256 != remote_hash {
+        println!(
+            "  {} {} describes a different WASM (sha256={}); its inputs may not apply.",
+            c::yellow_warning(),
+            path.display(),
+            &remote.wasm_sha256[..remote.wasm_sha256.len().min(16)]
+        );
+    }
+    let differences = local.diff(&remote);
+    if differences.is_empty() {
+        println!(
+            "  Build inputs match {}; the source itself has likely changed.",
+            path.display()
+        );
+        return Ok(());
+    }
+    println!("  Likely cause(s), from {}:", path.display());
+    for difference in &differences {
+        println!("  - {}: {}", c::bold(difference.field), difference.cause);
+        println!("      deployed: {}", difference.expected);
+        println!("      local   : {}", difference.actual);
+    }
+    Ok(())
+}
+
+/// Compare the contract spec embedded in the on-chain WASM against a local
+/// WASM or, failing that, against the contract source.
+fn exec_spec(args: &VerifyArgs) -> anyhow::Result<()> {
+    println!(
+        "{}",
+        c::bold("sanctifier verify — contract spec verification")
+    );
+    println!();
+
+    let (expected, expected_label) = match &args.local_wasm {
+        Some(p) => (spec_from_wasm(p)?, format!("local WASM {}", p.display())),
+        None => (
+            interface_from_source(&args.path)?,
+            format!("source at {}", args.path.display()),
+        ),
+    };
+    let remote_wasm_path = remote_wasm(args)?;
+    let actual = spec_from_wasm(&remote_wasm_path)?;
+
+    let count =
+        |i: &ContractInterface| i.contracts.iter().map(|c| c.functions.len()).sum::<usize>();
+    println!(
+        "  Expected : {} ({} functions)",
+        expected_label,
+        count(&expected)
+    );
+    println!(
+        "  On-chain : {} ({} functions)",
+        remote_wasm_path.display(),
+        count(&actual)
+    );
+    println!();
+
+    let changes = diff_interfaces(&expected, &actual);
+    if changes.is_empty() {
+        println!(
+            "{} Contract spec matches the on-chain deployment.",
+            c::green_bold("✓")
         );
+        return Ok(());
+    }
+
+    println!(
+        "{} MISMATCH — {} difference(s) in the contract spec:",
+        c::red_bold("✗"),
+        changes.len()
+    );
+    print_spec_changes(&changes);
+    bail!(
+        "contract spec mismatch detected for contract {}",
+        contract_label(args)
+    );
+}
+
+fn print_spec_changes(changes: &[SpecChange]) {
+    for change in changes {
+        println!("  - {}", change.message);
+    }
+}
+
+fn spec_from_wasm(path: &Path) -> anyhow::Result<ContractInterface> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read WASM: {}", path.display()))?;
+    interface_from_wasm(&bytes)
+        .with_context(|| format!("failed to decode contract spec from {}", path.display()))
+}
+
+/// The interface declared by every `.rs` file under `path`.
+fn interface_from_source(path: &Path) -> anyhow::Result<ContractInterface> {
+    let files = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        let mut files = collect_rs_files(path, &load_config(path).ignore_paths);
+        files.sort();
+        files
+    };
+
+    let mut interface = ContractInterface::default();
+    for file in files {
+        let source = std::fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let extracted = extract_interface(&source)
+            .with_context(|| format!("failed to parse {}", file.display()))?;
+        interface.merge(extracted);
+    }
+    if interface.contracts.is_empty() {
+        bail!("no #[contractimpl] blocks found under {}", path.display());
+    }
+    Ok(interface)
+}
+
+/// The on-chain WASM, from `--wasm-file` or fetched by contract ID.
+fn remote_wasm(args: &VerifyArgs) -> anyhow::Result<PathBuf> {
+    match (&args.wasm_file, &args.contract_id) {
+        (Some(p), _) => Ok(p.clone()),
+        (None, Some(id)) => fetch_onchain_wasm(id, &args.network),
+        (None, None) => bail!("either --contract-id or --wasm-file is required"),
+    }
+}
+
+fn contract_label(args: &VerifyArgs) -> String {
+    match (&args.contract_id, &args.wasm_file) {
+        (Some(id), _) => id.clone(),
+        (None, Some(p)) => p.display().to_string(),
+        (None, None) => "<unknown>".to_string(),
     }
 }
 
@@ -1,10 +1,14 @@
 #![allow(dead_code)]
-use crate::commands::analyze::{analyze_single_file, collect_rs_files, run_with_timeout};
+use crate::commands::analyze::{
+    analyze_single_file, collect_rs_files, report_cache_hits, run_with_timeout,
+};
+use crate::commands::cache::DiskCache;
 use crate::commands::color as c;
 use crate::vulndb::VulnDatabase;
+use anyhow::Context;
 use clap::Args;
 use rayon::prelude::*;
-use sanctifier_core::{Analyzer, SanctifyConfig};
+use sanctifier_core::{Analyzer, RuleRegistry, SanctifyConfig};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -35,6 +39,10 @@ pub struct WorkspaceArgs {
     /// Output format (text, json)
     #[arg(short, long, default_value = "text")]
     pub format: String,
+
+    /// Analyze every file instead of reusing results from target/sanctifier-cache
+    #[arg(short = 'n', long)]
+    pub no_cache: bool,
 }
 
 // ── Minimal Cargo.toml deserialisation ───────────────────────────────────────
@@ -243,6 +251,12 @@ pub fn exec(args: WorkspaceArgs) -> anyhow::Result<()> {
 
     for contract in &contracts {
         let config = load_config_for(&contract.path);
+        // Registry rules run through `analyze_single_file`; reject a bad
+        // `[rules.<name>]` table up front instead of silently using defaults.
+        let registry = RuleRegistry::from_config(&config)
+            .with_context(|| format!("invalid rule configuration for {}", contract.name))?;
+        let cache = (!args.no_cache)
+            .then(|| Arc::new(DiskCache::open(&workspace_root, &config, &registry)));
         let analyzer = Arc::new(Analyzer::new(config));
 
         // Collect contract source files + shared lib source files.
@@ -267,14 +281,21 @@ pub fn exec(args: WorkspaceArgs) -> anyhow::Result<()> {
                 };
                 let file_name = file_path.display().to_string();
                 let analyzer = Arc::clone(&analyzer);
+                let cache = cache.clone();
                 let vuln_db = Arc::clone(&vuln_db);
                 let file_name_clone = file_name.clone();
-                run_with_timeout(timeout_dur, move || {
-                    analyze_single_file(&analyzer, &vuln_db, &content, &file_name_clone)
+                run_with_timeout(timeout_dur, move || match cache {
+                    Some(cache) => {
+                        cache.analyze_file(&analyzer, &vuln_db, &content, &file_name_clone)
+                    }
+                    None => analyze_single_file(&analyzer, &vuln_db, &content, &file_name_clone),
                 })
                 .unwrap_or_default()
             })
             .collect();
+        if !is_json {
+            report_cache_hits(cache.as_deref(), total_files);
+        }
 
         let finding_count: usize = results.iter().map(count_findings).sum();
         grand_total += finding_count;
@@ -49,13 +49,21 @@ pub enum Commands {
     /// Explain a finding code (e.g. S001, S003) with details and remediation
     Explain(commands::explain::ExplainArgs),
     /// Check for and download the latest Sanctifier binary
-    Update,
+    Update {
+        /// Report the available version without installing it
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Self-update with checksum verification via GitHub Releases
     Upgrade(commands::upgrade::UpgradeArgs),
+    /// Compare storage layouts of two contract versions for upgrade-breaking changes
+    UpgradeCheck(commands::upgrade_check::UpgradeCheckArgs),
     /// Detect reentrancy vulnerabilities (state mutation before external call)
     Reentrancy(commands::reentrancy::ReentrancyArgs),
     /// Verify local source against on-chain bytecode
     Verify(commands::verify::VerifyArgs),
+    /// Build and deploy a contract, recording a build manifest for `verify`
+    Deploy(commands::deploy::DeployArgs),
     /// Verify an on-chain deployment matches expected local source or a pinned hash
     VerifyDeployment(commands::verify_deployment::VerifyDeploymentArgs),
     /// Analyze an entire Cargo workspace (multiple contracts/libs)
@@ -84,6 +92,10 @@ pub enum Commands {
     Badge(commands::badge::BadgeArgs),
     /// Compare two scan results and show new/resolved findings
     Diff(commands::diff::DiffArgs),
+    /// Describe each contract's functions, types, errors, events and auth as JSON
+    Interface(commands::interface::InterfaceArgs),
+    /// Show or clean the on-disk analysis cache (target/sanctifier-cache)
+    Cache(commands::cache::CacheArgs),
 }
 
 fn main() {
@@ -122,10 +134,12 @@ fn run() -> anyhow::Result<()> {
         Commands::Complexity(args) => commands::complexity::exec(args),
         Commands::Fix(args) => commands::fix::exec(args),
         Commands::Explain(args) => commands::explain::exec(args),
-        Commands::Update => commands::update::exec(),
+        Commands::Update { dry_run } => commands::update::exec(dry_run),
         Commands::Upgrade(args) => commands::upgrade::exec(args),
+        Commands::UpgradeCheck(args) => commands::upgrade_check::exec(args),
         Commands::Reentrancy(args) => commands::reentrancy::exec(args),
         Commands::Verify(args) => commands::verify::exec(args),
+        Commands::Deploy(args) => commands::deploy::exec(args),
         Commands::VerifyDeployment(args) => commands::verify_deployment::exec(args),
         Commands::Workspace(args) => commands::workspace::exec(args),
         Commands::Watch(args) => commands::watch::exec(args),
@@ -141,5 +155,7 @@ fn run() -> anyhow::Result<()> {
         Commands::Export(args) => commands::export::exec(args),
         Commands::Badge(args) => commands::badge::exec(args),
         Commands::Diff(args) => commands::diff::exec(args),
+        Commands::Interface(args) => commands::interface::exec(args),
+        Commands::Cache(args) => commands::cache::exec(args),
     }
 }
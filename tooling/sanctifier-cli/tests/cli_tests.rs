@@ -287,6 +287,63 @@ fn test_storage_directory_scan_aggregates_rust_files() {
     }));
 }
 
+#[test]
+fn test_storage_directory_scan_reports_collisions_across_files() {
+    let temp_dir = tempdir().unwrap();
+    let storage = temp_dir.path().join("storage.rs");
+    let lib = temp_dir.path().join("lib.rs");
+
+    fs::write(
+        &storage,
+        r#"
+            pub fn balance_key(user: Address) -> DataKey {
+                DataKey::Balance(user)
+            }
+
+            pub fn record_stake(env: &Env, user: Address, amount: i128) {
+                env.storage().persistent().set(&DataKey::Balance(user), &amount);
+            }
+        "#,
+    )
+    .unwrap();
+    fs::write(
+        &lib,
+        r#"
+            #[contractimpl]
+            impl Vault {
+                pub fn deposit(env: Env, user: Address, amount: i128) {
+                    env.storage().persistent().set(&storage::balance_key(user), &amount);
+                }
+            }
+        "#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("storage")
+        .arg(temp_dir.path())
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: Value = serde_json::from_slice(&output).unwrap();
+    let collisions = json.as_array().unwrap();
+    assert_eq!(collisions.len(), 2);
+    let lib_finding = collisions
+        .iter()
+        .find(|c| c["location"] == format!("{}:5", lib.display()))
+        .expect("finding located in lib.rs");
+    assert!(lib_finding["message"]
+        .as_str()
+        .unwrap()
+        .contains(&format!("{}:7", storage.display())));
+}
+
 #[test]
 fn test_update_help() {
     let mut cmd = Command::cargo_bin("sanctifier").unwrap();
@@ -620,164 +677,662 @@ fn test_gas_text_output_warns_on_unbounded_loop() {
 }
 
 #[test]
-fn test_analyze_json_includes_call_graph_edges() {
+fn test_gas_wasm_profiles_exports_alongside_source_estimates() {
     let temp_dir = tempdir().unwrap();
-    let contract_path = temp_dir.path().join("router.rs");
+    let contract_path = temp_dir.path().join("balance_contract.rs");
+    let wasm_path = temp_dir.path().join("balance_contract.wasm");
 
     fs::write(
         &contract_path,
         r#"
-            use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
-
-            #[contract]
-            pub struct Router;
+            use soroban_sdk::{contractimpl, Env};
 
             #[contractimpl]
-            impl Router {
-                pub fn forward(env: Env, target: Address, to: Address, amount: i128) {
-                    let fn_name = Symbol::new(&env, "transfer");
-                    env.invoke_contract::<()>(target, &fn_name, (&to, &amount));
+            impl BalanceContract {
+                pub fn balance(env: Env) -> i128 {
+                    env.storage().persistent().get(&0u32).unwrap_or(0)
                 }
             }
         "#,
     )
     .unwrap();
 
+    // `balance` calls the `l.1` (get_contract_data) host import.
+    #[rustfmt::skip]
+    let wasm: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+        0x02, 0x07, 0x01, 0x01, b'l', 0x01, b'1', 0x00, 0x00,
+        0x03, 0x02, 0x01, 0x00,
+        0x07, 0x0b, 0x01, 0x07, b'b', b'a', b'l', b'a', b'n', b'c', b'e', 0x00, 0x01,
+        0x0a, 0x06, 0x01, 0x04, 0x00, 0x10, 0x00, 0x0b,
+    ];
+    fs::write(&wasm_path, wasm).unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("gas")
+        .arg(&contract_path)
+        .arg("--wasm")
+        .arg(&wasm_path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Export                   | Source estimate",
+        ))
+        .stdout(predicates::str::contains(
+            "host calls: get_contract_data x1",
+        ));
+
     let output = Command::cargo_bin("sanctifier")
         .unwrap()
-        .arg("analyze")
+        .arg("gas")
         .arg(&contract_path)
+        .arg("--wasm")
+        .arg(&wasm_path)
         .arg("--format")
         .arg("json")
-        .env_remove("RUST_LOG")
-        .output()
-        .expect("sanctifier should run");
-
-    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
-    let payload: serde_json::Value = serde_json::from_str(&stdout).expect("stdout should be JSON");
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
 
-    // The current JSON output doesn't include call_graph at the top level
-    // Just verify the JSON is valid and contains expected structure
-    assert!(payload.is_object(), "JSON output should be an object");
-    assert!(
-        payload["error_codes"].is_array(),
-        "JSON should contain error_codes"
-    );
+    let json: Value = serde_json::from_slice(&output).unwrap();
+    let export = &json["wasm"]["exports"][0];
+    assert_eq!(export["name"], "balance");
+    assert_eq!(export["host_calls"]["get_contract_data"], 1);
+    assert_eq!(export["estimate"]["ledger_read_bytes"], 256);
 }
-/// Verifies that `sanctifier analyze --format json` output conforms to the
-/// published JSON Schema at `schemas/analysis-output.json`.
+
+const UPGRADE_V1: &str = r#"
+    use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+    #[contracttype]
+    pub enum DataKey {
+        Admin,
+        Balance(Address),
+    }
+
+    #[contractimpl]
+    impl Token {
+        pub fn init(env: Env, admin: Address) {
+            env.storage().instance().set(&DataKey::Admin, &admin);
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().persistent().get(&DataKey::Balance(id)).unwrap_or(0)
+        }
+    }
+"#;
+
 #[test]
-fn test_json_output_validates_against_schema() {
-    // Locate the schema relative to the workspace root (two levels up from
-    // this package's Cargo.toml directory).
-    let schema_path =
-        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../schemas/analysis-output.json");
-    let schema_text = fs::read_to_string(&schema_path)
-        .expect("schemas/analysis-output.json should exist at the workspace root");
-    let schema_value: serde_json::Value =
-        serde_json::from_str(&schema_text).expect("schema file should be valid JSON");
-    let compiled =
-        JSONSchema::compile(&schema_value).expect("schema should compile without errors");
+fn test_upgrade_check_reports_breaking_layout_changes_as_json() {
+    let temp_dir = tempdir().unwrap();
+    let old_path = temp_dir.path().join("v1.rs");
+    let new_path = temp_dir.path().join("v2.rs");
+    fs::write(&old_path, UPGRADE_V1).unwrap();
+    fs::write(
+        &new_path,
+        UPGRADE_V1
+            .replace(".instance()", ".persistent()")
+            .replace("-> i128", "-> u64"),
+    )
+    .unwrap();
 
-    let fixture_path = env::current_dir()
+    Command::cargo_bin("sanctifier")
         .unwrap()
-        .join("tests/fixtures/vulnerable_contract.rs");
+        .arg("upgrade-check")
+        .arg("--old")
+        .arg(&old_path)
+        .arg("--new")
+        .arg(&old_path)
+        .arg("--exit-code")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No storage layout changes"));
 
     let output = Command::cargo_bin("sanctifier")
         .unwrap()
-        .arg("analyze")
-        .arg(fixture_path)
+        .arg("upgrade-check")
+        .arg("--old")
+        .arg(&old_path)
+        .arg("--new")
+        .arg(&new_path)
         .arg("--format")
         .arg("json")
-        .env_remove("RUST_LOG")
-        .output()
-        .expect("sanctifier should run");
-
-    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
-    let instance: serde_json::Value =
-        serde_json::from_str(&stdout).expect("JSON output should parse");
+        .arg("--exit-code")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
 
-    let result = compiled.validate(&instance);
-    if let Err(errors) = result {
-        let messages: Vec<String> = errors.map(|e| e.to_string()).collect();
-        panic!(
-            "JSON output does not conform to schemas/analysis-output.json:\n{}",
-            messages.join("\n")
-        );
-    }
+    let json: Value = serde_json::from_slice(&output).unwrap();
+    let report = &json["upgrade_risk"];
+    assert_eq!(report["code"], "S010");
+    assert_eq!(report["compatible"], false);
+    let kinds: Vec<_> = report["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| (c["kind"].as_str().unwrap(), c["item"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("key_tier_changed", "DataKey::Admin"),
+            ("value_type_changed", "DataKey::Balance"),
+        ]
+    );
 }
 
-// ── NDJSON streaming tests ─────────────────────────────────────────────────────
-
-/// `--format ndjson` emits one JSON object per line and ends with `{"event":"done"}`.
+/// Directories are walked like `analyze` walks them: `ignore_paths` is
+/// honored and files that do not parse are skipped with a warning.
 #[test]
-fn test_ndjson_output_structure() {
-    let dir = tempdir().unwrap();
-    let contract = dir.path().join("contract.rs");
+fn test_upgrade_check_skips_ignored_and_unparseable_files() {
+    let temp_dir = tempdir().unwrap();
+    let old_dir = temp_dir.path().join("old");
+    let new_dir = temp_dir.path().join("new");
+    fs::create_dir_all(&old_dir).unwrap();
+    fs::create_dir_all(new_dir.join("generated")).unwrap();
+    fs::write(old_dir.join("lib.rs"), UPGRADE_V1).unwrap();
+    fs::write(new_dir.join("lib.rs"), UPGRADE_V1).unwrap();
+    fs::write(new_dir.join("scratch.rs"), "pub fn unfinished( {").unwrap();
     fs::write(
-        &contract,
-        r#"fn transfer() { let a = 1u64; let b = 2u64; let c = a + b; }"#,
+        new_dir.join("generated/lib.rs"),
+        UPGRADE_V1.replace("-> i128", "-> u64"),
+    )
+    .unwrap();
+    fs::write(
+        new_dir.join(".sanctify.toml"),
+        "ignore_paths = [\"target\", \"generated\"]\n",
     )
     .unwrap();
 
-    let output = Command::cargo_bin("sanctifier")
+    Command::cargo_bin("sanctifier")
         .unwrap()
-        .args(["analyze", "--format", "ndjson"])
-        .arg(&contract)
-        .output()
-        .unwrap();
+        .arg("upgrade-check")
+        .arg("--old")
+        .arg(&old_dir)
+        .arg("--new")
+        .arg(&new_dir)
+        .arg("--exit-code")
+        .env_remove("RUST_LOG")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No storage layout changes"))
+        .stderr(predicates::str::contains("failed to parse scratch.rs"));
+}
 
-    assert!(output.status.success(), "exit code must be 0");
+#[test]
+fn test_upgrade_check_reads_old_version_from_git_ref() {
+    let temp_dir = tempdir().unwrap();
+    let contract_path = temp_dir.path().join("lib.rs");
+    fs::write(&contract_path, UPGRADE_V1).unwrap();
+
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "lib.rs"]);
+    git(&["commit", "-q", "-m", "v1"]);
+
+    fs::write(&contract_path, UPGRADE_V1.replace("Balance(", "Balances(")).unwrap();
 
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    let lines: Vec<&str> = stdout.lines().collect();
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("upgrade-check")
+        .arg("--old")
+        .arg("HEAD")
+        .arg("--new")
+        .arg(&contract_path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "DataKey::Balance was renamed to DataKey::Balances",
+        ))
+        .stdout(predicates::str::contains("lib.rs:"));
+}
 
-    assert!(!lines.is_empty(), "must emit at least one line");
+#[test]
+fn test_interface_describes_functions_errors_and_events_as_json() {
+    let temp_dir = tempdir().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(
+        src.join("lib.rs"),
+        r#"
+            #[contracterror]
+            #[repr(u32)]
+            pub enum Error {
+                Unauthorized = 1,
+            }
 
-    // Every line must be valid JSON
-    for line in &lines {
-        serde_json::from_str::<Value>(line)
-            .unwrap_or_else(|_| panic!("line is not valid JSON: {}", line));
-    }
+            #[contract]
+            pub struct Vault;
 
-    // Last line must be the done event
-    let last: Value = serde_json::from_str(lines.last().unwrap()).unwrap();
-    assert_eq!(last["event"], "done", "last line must have event=done");
-    assert!(
-        last["total_findings"].is_number(),
-        "done line must have numeric total_findings"
+            #[contractimpl]
+            impl Vault {
+                pub fn deposit(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+                    from.require_auth();
+                    env.events().publish((symbol_short!("deposit"), from), amount);
+                    Ok(())
+                }
+            }
+        "#,
+    )
+    .unwrap();
+    fs::write(
+        src.join("admin.rs"),
+        r#"
+            #[contractimpl]
+            impl Vault {
+                pub fn pause(env: Env, admin: Address) {
+                    admin.require_auth();
+                }
+            }
+        "#,
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("interface.json");
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("interface")
+        .arg(temp_dir.path())
+        .arg("--output")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    let json: Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    assert_eq!(json["schema_version"], "1.0.0");
+    let vault = &json["contracts"][0];
+    assert_eq!(vault["name"], "Vault");
+    let functions = vault["functions"].as_array().unwrap();
+    assert_eq!(functions.len(), 2);
+    let deposit = functions.iter().find(|f| f["name"] == "deposit").unwrap();
+    assert_eq!(
+        deposit["inputs"],
+        serde_json::json!([
+            {"name": "from", "type": "Address"},
+            {"name": "amount", "type": "i128"},
+        ])
     );
-    assert!(
-        last["duration_ms"].is_number(),
-        "done line must have numeric duration_ms"
+    assert_eq!(deposit["output"], "Result<(),Error>");
+    assert_eq!(deposit["auth"], serde_json::json!(["from"]));
+    assert_eq!(
+        deposit["events"][0]["topics"][0],
+        serde_json::json!({"kind": "symbol", "value": "deposit"})
+    );
+    assert_eq!(deposit["file"], "src/lib.rs");
+    assert_eq!(
+        json["errors"][0]["cases"],
+        serde_json::json!([{"name": "Unauthorized", "code": 1}])
     );
 }
 
-/// Finding lines carry the expected fields.
 #[test]
-fn test_ndjson_finding_fields() {
-    let dir = tempdir().unwrap();
-    let contract = dir.path().join("contract.rs");
-    fs::write(&contract, r#"fn add(a: u64, b: u64) -> u64 { a + b }"#).unwrap();
+fn test_interface_skips_ignore_paths_from_config() {
+    let temp_dir = tempdir().unwrap();
+    let vendor = temp_dir.path().join("vendor");
+    fs::create_dir_all(&vendor).unwrap();
+    fs::write(
+        temp_dir.path().join(".sanctify.toml"),
+        "ignore_paths = [\"target\", \"vendor\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        "#[contractimpl]\nimpl Vault { pub fn pause(env: Env) {} }\n",
+    )
+    .unwrap();
+    fs::write(
+        vendor.join("lib.rs"),
+        "#[contractimpl]\nimpl Vendored { pub fn call(env: Env) {} }\n",
+    )
+    .unwrap();
 
     let output = Command::cargo_bin("sanctifier")
         .unwrap()
-        .args(["analyze", "--format", "ndjson"])
-        .arg(&contract)
+        .arg("interface")
+        .arg(temp_dir.path())
         .output()
         .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let contracts = json["contracts"].as_array().unwrap();
+    assert_eq!(contracts.len(), 1, "got: {contracts:?}");
+    assert_eq!(contracts[0]["name"], "Vault");
+}
 
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    let finding_lines: Vec<Value> = stdout
-        .lines()
-        .map(|l| serde_json::from_str(l).unwrap())
-        .filter(|v: &Value| v["event"] == "finding")
-        .collect();
+#[test]
+fn test_interface_fails_without_contracts() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("lib.rs");
+    fs::write(&path, "pub fn helper() -> u32 { 1 }").unwrap();
 
-    assert!(
-        !finding_lines.is_empty(),
-        "must emit at least one finding for unchecked arithmetic"
-    );
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("interface")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("no #[contractimpl] blocks"));
+}
+
+/// A wasm module whose `contractspecv0` section declares
+/// `balance(id: Address) -> i128`.
+#[rustfmt::skip]
+const BALANCE_SPEC_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+    0x00, 0x3f, 0x0e,
+    b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b's', b'p', b'e', b'c', b'v', b'0',
+    // ScSpecEntry::FunctionV0 { doc: "", name: "balance", .. }
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x07, b'b', b'a', b'l', b'a', b'n', b'c', b'e', 0x00,
+    // inputs: [{ doc: "", name: "id", type: Address }]
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x02, b'i', b'd', 0x00, 0x00, 0x00, 0x00, 0x00, 0x13,
+    // outputs: [I128]
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0b,
+];
+
+#[test]
+fn test_verify_spec_matches_source_and_local_wasm() {
+    let temp_dir = tempdir().unwrap();
+    let wasm_path = temp_dir.path().join("onchain.wasm");
+    fs::write(&wasm_path, BALANCE_SPEC_WASM).unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        r#"
+            #[contractimpl]
+            impl Token {
+                pub fn balance(env: Env, id: soroban_sdk::Address) -> i128 {
+                    0
+                }
+            }
+        "#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["verify", "--spec", "--wasm-file"])
+        .arg(&wasm_path)
+        .arg(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Contract spec matches"));
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["verify", "--spec", "--wasm-file"])
+        .arg(&wasm_path)
+        .arg("--local-wasm")
+        .arg(&wasm_path)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_verify_spec_reports_changed_and_missing_functions() {
+    let temp_dir = tempdir().unwrap();
+    let wasm_path = temp_dir.path().join("onchain.wasm");
+    fs::write(&wasm_path, BALANCE_SPEC_WASM).unwrap();
+    fs::write(
+        temp_dir.path().join("lib.rs"),
+        r#"
+            #[contractimpl]
+            impl Token {
+                pub fn balance(env: Env, id: Address) -> u64 {
+                    0
+                }
+
+                pub fn mint(env: Env, to: Address, amount: i128) {}
+            }
+        "#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["verify", "--spec", "--wasm-file"])
+        .arg(&wasm_path)
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(
+            "Function 'balance' differs: expected (id: Address) -> u64, found (id: Address) -> i128",
+        ))
+        .stdout(predicates::str::contains(
+            "Function 'mint' is missing from the wasm",
+        ))
+        .stderr(predicates::str::contains("contract spec mismatch"));
+}
+
+#[test]
+fn test_verify_hash_mismatch_reports_build_manifest_differences() {
+    use sanctifier_cli::commands::build_manifest::{sha256_hex, BuildManifest};
+
+    let temp_dir = tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"token\"\n\n[dependencies]\nsoroban-sdk = \"21.7.6\"\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("Cargo.lock"), "version = 3\n").unwrap();
+    let local_wasm = temp_dir.path().join("local.wasm");
+    let remote_wasm = temp_dir.path().join("onchain.wasm");
+    fs::write(&local_wasm, b"\0asm\x01\0\0\0").unwrap();
+    fs::write(&remote_wasm, BALANCE_SPEC_WASM).unwrap();
+
+    // The deployed build used an older compiler and another lockfile.
+    let mut deployed = BuildManifest::capture(temp_dir.path(), BALANCE_SPEC_WASM);
+    deployed.rustc = Some("rustc 1.74.0 (79e9716c9 2023-11-13)".to_string());
+    deployed.cargo_lock_sha256 = Some(sha256_hex(b"version = 2\n"));
+    deployed
+        .write(&temp_dir.path().join("onchain.manifest.json"))
+        .unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("verify")
+        .arg(temp_dir.path())
+        .arg("--local-wasm")
+        .arg(&local_wasm)
+        .arg("--wasm-file")
+        .arg(&remote_wasm)
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("Likely cause(s)"))
+        .stdout(predicates::str::contains(
+            "deployed: rustc 1.74.0 (79e9716c9 2023-11-13)",
+        ))
+        .stdout(predicates::str::contains("Cargo.lock changed"));
+}
+
+#[test]
+fn test_analyze_json_includes_call_graph_edges() {
+    let temp_dir = tempdir().unwrap();
+    let contract_path = temp_dir.path().join("router.rs");
+
+    fs::write(
+        &contract_path,
+        r#"
+            use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
+
+            #[contract]
+            pub struct Router;
+
+            #[contractimpl]
+            impl Router {
+                pub fn forward(env: Env, target: Address, to: Address, amount: i128) {
+                    let fn_name = Symbol::new(&env, "transfer");
+                    env.invoke_contract::<()>(target, &fn_name, (&to, &amount));
+                }
+            }
+        "#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(&contract_path)
+        .arg("--format")
+        .arg("json")
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("sanctifier should run");
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    let payload: serde_json::Value = serde_json::from_str(&stdout).expect("stdout should be JSON");
+
+    // The current JSON output doesn't include call_graph at the top level
+    // Just verify the JSON is valid and contains expected structure
+    assert!(payload.is_object(), "JSON output should be an object");
+    assert!(
+        payload.get("error_codes").is_none(),
+        "error_codes should only be emitted with --include-error-codes"
+    );
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(&contract_path)
+        .args(["--format", "json", "--include-error-codes"])
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("sanctifier should run");
+    let payload: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+    assert!(
+        payload["error_codes"].is_array(),
+        "JSON should contain error_codes"
+    );
+}
+/// Verifies that `sanctifier analyze --format json` output conforms to the
+/// published JSON Schema at `schemas/analysis-output.json`.
+#[test]
+fn test_json_output_validates_against_schema() {
+    // Locate the schema relative to the workspace root (two levels up from
+    // this package's Cargo.toml directory).
+    let schema_path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../schemas/analysis-output.json");
+    let schema_text = fs::read_to_string(&schema_path)
+        .expect("schemas/analysis-output.json should exist at the workspace root");
+    let schema_value: serde_json::Value =
+        serde_json::from_str(&schema_text).expect("schema file should be valid JSON");
+    let compiled =
+        JSONSchema::compile(&schema_value).expect("schema should compile without errors");
+
+    let fixture_path = env::current_dir()
+        .unwrap()
+        .join("tests/fixtures/vulnerable_contract.rs");
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(fixture_path)
+        .arg("--format")
+        .arg("json")
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("sanctifier should run");
+
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    let instance: serde_json::Value =
+        serde_json::from_str(&stdout).expect("JSON output should parse");
+
+    let result = compiled.validate(&instance);
+    if let Err(errors) = result {
+        let messages: Vec<String> = errors.map(|e| e.to_string()).collect();
+        panic!(
+            "JSON output does not conform to schemas/analysis-output.json:\n{}",
+            messages.join("\n")
+        );
+    }
+}
+
+// ── NDJSON streaming tests ─────────────────────────────────────────────────────
+
+/// `--format ndjson` emits one JSON object per line and ends with `{"event":"done"}`.
+#[test]
+fn test_ndjson_output_structure() {
+    let dir = tempdir().unwrap();
+    let contract = dir.path().join("contract.rs");
+    fs::write(
+        &contract,
+        r#"fn transfer() { let a = 1u64; let b = 2u64; let c = a + b; }"#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "ndjson"])
+        .arg(&contract)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "exit code must be 0");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    assert!(!lines.is_empty(), "must emit at least one line");
+
+    // Every line must be valid JSON
+    for line in &lines {
+        serde_json::from_str::<Value>(line)
+            .unwrap_or_else(|_| panic!("line is not valid JSON: {}", line));
+    }
+
+    // Last line must be the done event
+    let last: Value = serde_json::from_str(lines.last().unwrap()).unwrap();
+    assert_eq!(last["event"], "done", "last line must have event=done");
+    assert!(
+        last["total_findings"].is_number(),
+        "done line must have numeric total_findings"
+    );
+    assert!(
+        last["duration_ms"].is_number(),
+        "done line must have numeric duration_ms"
+    );
+}
+
+/// Finding lines carry the expected fields.
+#[test]
+fn test_ndjson_finding_fields() {
+    let dir = tempdir().unwrap();
+    let contract = dir.path().join("contract.rs");
+    fs::write(&contract, r#"fn add(a: u64, b: u64) -> u64 { a + b }"#).unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "ndjson"])
+        .arg(&contract)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let finding_lines: Vec<Value> = stdout
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .filter(|v: &Value| v["event"] == "finding")
+        .collect();
+
+    assert!(
+        !finding_lines.is_empty(),
+        "must emit at least one finding for unchecked arithmetic"
+    );
 
     for finding in &finding_lines {
         assert!(finding["file"].is_string(), "finding must have file");
@@ -1094,10 +1649,416 @@ fn test_diff_help_is_accessible() {
 }
 
 #[test]
-fn test_report_help_mentions_output_flag() {
+fn test_diff_base_ref_renders_pr_comment() {
+    let temp_dir = tempdir().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"counter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = \"21\"\n",
+    )
+    .unwrap();
+    let base = r#"#![no_std]
+use soroban_sdk::{contract, contractimpl, Env};
+
+#[contract]
+pub struct Counter;
+
+#[contractimpl]
+impl Counter {
+    pub fn add(_env: Env, a: u64, b: u64) -> u64 {
+        a + b
+    }
+}
+"#;
+    fs::write(src.join("lib.rs"), base).unwrap();
+
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "base"]);
+
+    // Swap the unchecked addition for an unwrap: one finding resolved, new ones added.
+    fs::write(
+        src.join("lib.rs"),
+        base.replace("a + b", "a.checked_add(b).unwrap()"),
+    )
+    .unwrap();
+
     Command::cargo_bin("sanctifier")
         .unwrap()
-        .args(["report", "--help"])
+        .arg("diff")
+        .arg(temp_dir.path())
+        .args(["--base", "HEAD", "--format", "markdown"])
+        .args(["--report-url", "https://ci.example.com/report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("<!-- sanctifier-pr-comment -->"))
+        .stdout(predicates::str::contains("`S002`"))
+        .stdout(predicates::str::contains("src/lib.rs"))
+        .stdout(predicates::str::is_match(r"`-[1-9]\d*` resolved").unwrap())
+        .stdout(predicates::str::contains("https://ci.example.com/report"));
+
+    let worktrees = std::process::Command::new("git")
+        .arg("-C")
+        .arg(temp_dir.path())
+        .args(["worktree", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&worktrees.stdout).lines().count(), 1);
+}
+
+#[test]
+fn test_analyze_follows_helpers_in_other_files_of_the_crate() {
+    let temp_dir = tempdir().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"vault\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = \"21\"\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("lib.rs"),
+        r#"#![no_std]
+use soroban_sdk::{contract, contractimpl, Address, Env};
+
+mod storage;
+
+#[contract]
+pub struct Vault;
+
+#[contractimpl]
+impl Vault {
+    pub fn deposit(env: Env, from: Address, amount: i128) {
+        storage::write_balance(&env, &from, amount);
+    }
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        src.join("storage.rs"),
+        r#"use soroban_sdk::{Address, Env};
+
+pub fn write_balance(env: &Env, id: &Address, amount: i128) {
+    env.storage().persistent().set(id, &amount);
+}
+"#,
+    )
+    .unwrap();
+
+    // Only lib.rs is analyzed; the write happens in its sibling storage.rs.
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(src.join("lib.rs"))
+        .args(["--format", "json"])
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let payload: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rules: Vec<&str> = payload["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|v| v["location"].as_str().unwrap().starts_with("deposit:"))
+        .map(|v| v["rule_name"].as_str().unwrap())
+        .collect();
+    assert!(rules.contains(&"auth_gap"), "{rules:?}");
+    assert!(rules.contains(&"taint_propagation"), "{rules:?}");
+}
+
+#[test]
+fn test_analyze_changed_lines_reports_only_changed_functions() {
+    let temp_dir = tempdir().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"store\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = \"21\"\n",
+    )
+    .unwrap();
+    let base = r#"#![no_std]
+use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+#[contract]
+pub struct Store;
+
+#[contractimpl]
+impl Store {
+    pub fn first(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("a")).unwrap()
+    }
+
+    pub fn second(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("b")).unwrap()
+    }
+}
+"#;
+    fs::write(src.join("lib.rs"), base).unwrap();
+
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "base"]);
+
+    // Touch a line of `second` other than the one with the unwrap.
+    fs::write(
+        src.join("lib.rs"),
+        base.replace(
+            "pub fn second(env: Env) -> u32 {",
+            "pub fn second(env: Env) -> u32 {\n        // read the counter",
+        ),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(temp_dir.path())
+        .args(["--changed-lines", "--format", "json"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Checking 1 file(s) changed since HEAD"))
+        .get_output()
+        .stdout
+        .clone();
+    let json: Value = serde_json::from_slice(&output).unwrap();
+    let panics: Vec<_> = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|v| v["rule_name"] == "panic_detection")
+        .collect();
+    assert_eq!(panics.len(), 1, "{:#?}", panics);
+    assert_eq!(panics[0]["span"]["start_line"], 15);
+
+    // A file git does not track yet has changed throughout.
+    fs::write(
+        src.join("extra.rs"),
+        r#"use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+#[contract]
+pub struct Extra;
+
+#[contractimpl]
+impl Extra {
+    pub fn read(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("c")).unwrap()
+    }
+}
+"#,
+    )
+    .unwrap();
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(temp_dir.path())
+        .args(["--changed-since", "HEAD", "--format", "json"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Checking 2 file(s) changed since HEAD",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let json: Value = serde_json::from_slice(&output).unwrap();
+    assert!(json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v["rule_name"] == "panic_detection"
+            && v["span"]["file"].as_str().unwrap().ends_with("extra.rs")));
+}
+
+#[test]
+fn test_analyze_staged_ignores_unstaged_edits() {
+    let temp_dir = tempdir().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"store\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = \"21\"\n",
+    )
+    .unwrap();
+    let base = r#"#![no_std]
+use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+#[contract]
+pub struct Store;
+
+#[contractimpl]
+impl Store {
+    pub fn first(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("a")).unwrap()
+    }
+
+    pub fn second(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("b")).unwrap()
+    }
+}
+"#;
+    fs::write(src.join("lib.rs"), base).unwrap();
+
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "base"]);
+
+    // Stage an edit to `second`, then leave an unstaged edit in `first`.
+    let staged = base.replace(
+        "pub fn second(env: Env) -> u32 {",
+        "pub fn second(env: Env) -> u32 {\n        // read the counter",
+    );
+    fs::write(src.join("lib.rs"), &staged).unwrap();
+    git(&["add", "src/lib.rs"]);
+    fs::write(
+        src.join("lib.rs"),
+        staged.replace(
+            "pub fn first(env: Env) -> u32 {",
+            "pub fn first(env: Env) -> u32 {\n        // work in progress",
+        ),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(temp_dir.path())
+        .args(["--staged", "--format", "json"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Checking 1 file(s) with staged changes",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let json: Value = serde_json::from_slice(&output).unwrap();
+    let panics: Vec<_> = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|v| v["rule_name"] == "panic_detection")
+        .collect();
+    assert_eq!(panics.len(), 1, "{:#?}", panics);
+    assert_eq!(
+        panics[0]["location"].as_str().unwrap().split(':').next(),
+        Some("second")
+    );
+    // Lines are numbered as in the index, not the working tree.
+    assert_eq!(panics[0]["span"]["start_line"], 15);
+}
+
+#[test]
+fn test_analyze_reuses_cached_results_until_cleaned() {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let temp_dir = tempdir().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"counter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = \"21\"\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("lib.rs"),
+        r#"#![no_std]
+use soroban_sdk::{contract, contractimpl, Env};
+
+#[contract]
+pub struct Counter;
+
+#[contractimpl]
+impl Counter {
+    pub fn add(_env: Env, a: u64, b: u64) -> u64 {
+        a + b
+    }
+}
+"#,
+    )
+    .unwrap();
+    let sanctifier = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("sanctifier").unwrap();
+        cmd.env_remove("CARGO_TARGET_DIR")
+            .args(args)
+            .arg(temp_dir.path());
+        cmd
+    };
+    let reused = predicates::str::contains("Reused cached results for 1 of 1 file(s)");
+
+    let first = sanctifier(&["analyze", "--format", "json"])
+        .assert()
+        .success()
+        .stderr(reused.clone().not())
+        .get_output()
+        .stdout
+        .clone();
+    let second = sanctifier(&["analyze", "--format", "json"])
+        .assert()
+        .success()
+        .stderr(reused.clone())
+        .get_output()
+        .stdout
+        .clone();
+    let violations = |output: &[u8]| {
+        let json: Value = serde_json::from_slice(output).unwrap();
+        json["rule_violations"].clone()
+    };
+    assert_eq!(violations(&first), violations(&second));
+
+    sanctifier(&["analyze", "--no-cache"])
+        .assert()
+        .success()
+        .stderr(reused.clone().not());
+    sanctifier(&["cache", "stats"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("sanctifier-cache"))
+        .stdout(predicates::str::contains("entries: 2"));
+    sanctifier(&["cache", "clean"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Removed 2 cached result(s)"));
+    assert!(!temp_dir.path().join("target/sanctifier-cache").exists());
+    sanctifier(&["analyze"]).assert().success().stderr(reused.not());
+}
+
+#[test]
+fn test_report_help_mentions_output_flag() {
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["report", "--help"])
         .assert()
         .success()
         .stdout(predicates::str::contains("--output"));
@@ -1153,6 +2114,263 @@ fn test_fix_help_is_accessible() {
         .stdout(predicates::str::contains("patch"));
 }
 
+#[test]
+fn test_fix_interactive_applies_type_aware_patches() {
+    let dir = tempdir().unwrap();
+    let contract = dir.path().join("lib.rs");
+    fs::write(
+        &contract,
+        r#"#![no_std]
+use soroban_sdk::{contract, contracterror, contractimpl, Address, Env};
+
+#[contracterror]
+pub enum Error {
+    NotInitialized = 1,
+    Overflow = 2,
+}
+
+#[contract]
+pub struct Token;
+
+#[contractimpl]
+impl Token {
+    pub fn deposit(env: Env, user: Address, amount: i128) -> Result<i128, Error> {
+        user.require_auth();
+        let balance: i128 = env.storage().persistent().get(&user).unwrap();
+        let updated = balance + amount;
+        env.storage().persistent().set(&user, &updated);
+        Ok(updated)
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["fix", "--interactive"])
+        .arg(&contract)
+        .write_stdin("y\ny\nn\n")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("[arithmetic_overflow]"))
+        .stdout(predicates::str::contains("2 applied, 1 skipped"));
+
+    let fixed = fs::read_to_string(&contract).unwrap();
+    assert!(fixed.contains(".ok_or(Error::NotInitialized)?;"));
+    assert!(fixed.contains("let updated = balance.checked_add(amount).ok_or(Error::Overflow)?;"));
+    assert!(!fixed.contains("extend_ttl"));
+}
+
+const FIX_DIFF_CONTRACT: &str = r#"#![no_std]
+use soroban_sdk::{contract, contracterror, contractimpl, Address, Env};
+
+#[contracterror]
+pub enum Error {
+    Overflow = 1,
+}
+
+#[contract]
+pub struct Counter;
+
+#[contractimpl]
+impl Counter {
+    pub fn add(env: Env, user: Address, a: u64, b: u64) -> Result<u64, Error> {
+        user.require_auth();
+        Ok(a + b)
+    }
+}
+"#;
+
+#[test]
+fn test_fix_diff_prints_patch_and_leaves_file_unchanged() {
+    let dir = tempdir().unwrap();
+    let contract = dir.path().join("lib.rs");
+    fs::write(&contract, FIX_DIFF_CONTRACT).unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["fix", "--diff"])
+        .arg(&contract)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("--- a/lib.rs\n+++ b/lib.rs"))
+        .stdout(predicates::str::contains("-        Ok(a + b)"))
+        .stdout(predicates::str::contains(
+            "+        Ok(a.checked_add(b).ok_or(Error::Overflow)?)",
+        ))
+        .stderr(predicates::str::contains("1 proposed"));
+
+    assert_eq!(fs::read_to_string(&contract).unwrap(), FIX_DIFF_CONTRACT);
+}
+
+#[test]
+fn test_fix_output_writes_patch_file() {
+    let dir = tempdir().unwrap();
+    let contract = dir.path().join("lib.rs");
+    let patch_file = dir.path().join("fixes.patch");
+    fs::write(&contract, FIX_DIFF_CONTRACT).unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["fix", "--output"])
+        .arg(&patch_file)
+        .arg(&contract)
+        .assert()
+        .success()
+        .stdout(predicates::str::is_empty());
+
+    let patch = fs::read_to_string(&patch_file).unwrap();
+    assert!(patch.starts_with("--- a/"));
+    assert!(patch.contains("@@ "));
+    assert!(patch.contains("+        Ok(a.checked_add(b).ok_or(Error::Overflow)?)"));
+    assert_eq!(fs::read_to_string(&contract).unwrap(), FIX_DIFF_CONTRACT);
+}
+
+#[test]
+fn test_fix_uses_error_enums_declared_elsewhere_in_the_crate() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"counter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("src/errors.rs"),
+        "use soroban_sdk::contracterror;\n\n#[contracterror]\npub enum Error {\n    Overflow = 1,\n}\n",
+    )
+    .unwrap();
+    let contract = dir.path().join("src/lib.rs");
+    fs::write(
+        &contract,
+        FIX_DIFF_CONTRACT.replace(
+            "#[contracterror]\npub enum Error {\n    Overflow = 1,\n}\n",
+            "mod errors;\nuse errors::Error;\n",
+        ),
+    )
+    .unwrap();
+
+    // Only lib.rs is being fixed; the enum comes from its sibling errors.rs.
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["fix", "--diff"])
+        .arg(&contract)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "+        Ok(a.checked_add(b).ok_or(Error::Overflow)?)",
+        ))
+        .stderr(predicates::str::contains("1 proposed"));
+}
+
+#[test]
+fn test_fix_rolls_back_files_that_no_longer_compile() {
+    let dir = tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"fix-check\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+    )
+    .unwrap();
+    // `good` writes through storage that can extend TTLs, `bad` through
+    // storage that cannot, so only the fix in `good` compiles.  The error
+    // `good` already had is not the fix's fault.
+    fs::write(
+        src.join("lib.rs"),
+        r#"pub mod bad;
+pub mod good;
+
+pub struct Env;
+pub struct Storage;
+pub struct Persistent;
+
+impl Env {
+    pub fn require_auth(&self) {}
+    pub fn storage(&self) -> Storage {
+        Storage
+    }
+}
+
+impl Storage {
+    pub fn persistent(&self) -> Persistent {
+        Persistent
+    }
+}
+
+impl Persistent {
+    pub fn set(&self, _key: &u32, _val: &u32) {}
+    pub fn extend_ttl(&self, _key: &u32, _threshold: u32, _extend_to: u32) {}
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        src.join("good.rs"),
+        r#"use crate::Env;
+
+pub struct Good;
+
+impl Good {
+    pub fn store(env: Env, key: u32, val: u32) {
+        env.storage().persistent().set(&key, &val);
+    }
+}
+
+pub fn unfinished() -> u32 {
+    "todo"
+}
+"#,
+    )
+    .unwrap();
+    let bad = r#"pub struct Env;
+pub struct Storage;
+pub struct Persistent;
+
+impl Env {
+    pub fn require_auth(&self) {}
+    pub fn storage(&self) -> Storage {
+        Storage
+    }
+}
+
+impl Storage {
+    pub fn persistent(&self) -> Persistent {
+        Persistent
+    }
+}
+
+impl Persistent {
+    pub fn set(&self, _key: &u32, _val: &u32) {}
+}
+
+pub struct Bad;
+
+impl Bad {
+    pub fn store(env: Env, key: u32, val: u32) {
+        env.storage().persistent().set(&key, &val);
+    }
+}
+"#;
+    fs::write(src.join("bad.rs"), bad).unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["fix"])
+        .arg(&src)
+        .env("CARGO_TARGET_DIR", dir.path().join("target"))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("no longer compiles; rolled back"))
+        .stdout(predicates::str::contains("2 applied, 2 skipped"));
+
+    assert_eq!(fs::read_to_string(src.join("bad.rs")).unwrap(), bad);
+    assert!(fs::read_to_string(src.join("good.rs"))
+        .unwrap()
+        .contains(".extend_ttl(&key, 120_960, 518_400);"));
+}
+
 #[test]
 fn test_completions_bash_outputs_script() {
     Command::cargo_bin("sanctifier")
@@ -1358,6 +2576,58 @@ fn test_analyze_sarif_findings_are_in_results_array() {
     );
 }
 
+/// SARIF results carry a `region` and logical location taken from the
+/// violation span; the JSON report exposes the same span.
+#[test]
+fn test_analyze_sarif_region_comes_from_violation_span() {
+    let dir = tempdir().unwrap();
+    let contract = dir.path().join("lib.rs");
+    fs::write(
+        &contract,
+        "impl Vault {\n    pub fn add(env: Env, a: u64, b: u64) -> u64 {\n        a + b\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = |format: &str| -> Value {
+        let output = Command::cargo_bin("sanctifier")
+            .unwrap()
+            .arg("analyze")
+            .arg(&contract)
+            .arg("--format")
+            .arg(format)
+            .env_remove("RUST_LOG")
+            .output()
+            .unwrap();
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let sarif = run("sarif");
+    let result = sarif["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["ruleId"] == "arithmetic_overflow")
+        .expect("arithmetic_overflow result");
+    let location = &result["locations"][0];
+    assert_eq!(location["physicalLocation"]["region"]["startLine"], 3);
+    assert_eq!(location["physicalLocation"]["region"]["startColumn"], 9);
+    assert_eq!(
+        location["logicalLocations"][0]["fullyQualifiedName"],
+        "Vault::add"
+    );
+
+    let json = run("json");
+    let violation = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["rule_name"] == "arithmetic_overflow")
+        .expect("arithmetic_overflow violation");
+    assert_eq!(violation["span"]["start_line"], 3);
+    assert_eq!(violation["span"]["function"], "add");
+    assert_eq!(violation["span"]["contract"], "Vault");
+}
+
 // ── #519: Analysis profile exit codes ────────────────────────────────────────
 
 /// `--profile strict` exits 1 whenever any finding is detected.
@@ -1425,3 +2695,342 @@ fn test_no_profile_no_exit_code_exits_zero_with_findings() {
         .assert()
         .success();
 }
+
+// ── [suppressions] in .sanctify.toml ──────────────────────────────────────────
+
+fn write_suppressed_project() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(".sanctify.toml"),
+        r#"
+[suppressions]
+S003 = [{ file = "contract.rs", line = 1, reason = "inputs are bounded by the caller" }]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("contract.rs"),
+        "fn add(a: u64, b: u64) -> u64 { a + b }\n",
+    )
+    .unwrap();
+    dir
+}
+
+/// A matching suppression removes the finding and reports it separately.
+#[test]
+fn test_suppressed_finding_is_reported_separately_in_json() {
+    let dir = write_suppressed_project();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json"])
+        .arg(dir.path().join("contract.rs"))
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let violations = json["rule_violations"].as_array().unwrap();
+    assert!(violations
+        .iter()
+        .all(|v| v["rule_name"] != "arithmetic_overflow"));
+    assert_eq!(json["summary"]["suppressed_findings"], 1);
+    assert_eq!(json["suppressed"][0]["code"], "S003");
+    assert_eq!(
+        json["suppressed"][0]["reason"],
+        "inputs are bounded by the caller"
+    );
+}
+
+/// SARIF keeps suppressed results but marks them with a `suppressions` entry.
+#[test]
+fn test_suppressed_finding_carries_sarif_suppression() {
+    let dir = write_suppressed_project();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "sarif"])
+        .arg(dir.path().join("contract.rs"))
+        .output()
+        .unwrap();
+    let sarif: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    let suppressed: Vec<&Value> = results
+        .iter()
+        .filter(|r| r.get("suppressions").is_some())
+        .collect();
+    assert_eq!(suppressed.len(), 1);
+    assert_eq!(suppressed[0]["ruleId"], "arithmetic_overflow");
+    assert_eq!(suppressed[0]["suppressions"][0]["kind"], "external");
+    assert_eq!(
+        suppressed[0]["suppressions"][0]["justification"],
+        "inputs are bounded by the caller"
+    );
+}
+
+// ── Finding codes and five-level severity ─────────────────────────────────────
+
+fn write_overflow_only_project() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("contract.rs"),
+        "fn add(a: u64, b: u64) -> u64 { a + b }\n",
+    )
+    .unwrap();
+    dir
+}
+
+/// Every rule violation is tagged with its finding code and catalogue severity.
+#[test]
+fn test_json_violations_carry_code_and_finding_severity() {
+    let dir = write_overflow_only_project();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json"])
+        .arg(dir.path().join("contract.rs"))
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let violation = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["rule_name"] == "arithmetic_overflow")
+        .expect("arithmetic_overflow must fire");
+    assert_eq!(violation["code"], "S003");
+    assert_eq!(violation["finding_severity"], "medium");
+}
+
+/// `--min-severity` is compared against the finding's five-level severity.
+#[test]
+fn test_min_severity_threshold_uses_finding_severity() {
+    let dir = write_overflow_only_project();
+    let contract = dir.path().join("contract.rs");
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--exit-code", "--min-severity", "high"])
+        .arg(&contract)
+        .assert()
+        .success();
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--exit-code", "--min-severity", "medium"])
+        .arg(&contract)
+        .assert()
+        .code(1);
+}
+
+// ── Per-rule configuration ───────────────────────────────────────────────────
+
+/// `[rules.<name>]` can raise a rule's severity or switch it off entirely.
+#[test]
+fn test_rules_table_overrides_severity_and_disables_rules() {
+    let dir = write_overflow_only_project();
+    let contract = dir.path().join("contract.rs");
+    let config = dir.path().join(".sanctify.toml");
+
+    fs::write(&config, "[rules.arithmetic_overflow]\nseverity = \"critical\"\n").unwrap();
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--exit-code", "--min-severity", "critical"])
+        .arg(&contract)
+        .assert()
+        .code(1);
+
+    fs::write(&config, "[rules.arithmetic_overflow]\nenabled = false\n").unwrap();
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json"])
+        .arg(&contract)
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|v| v["rule_name"] != "arithmetic_overflow"));
+}
+
+/// A table naming a rule that does not exist is reported, not ignored.
+#[test]
+fn test_rules_table_with_unknown_rule_fails() {
+    let dir = write_overflow_only_project();
+    fs::write(
+        dir.path().join(".sanctify.toml"),
+        "[rules.arithmetic]\nenabled = false\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("sanctifier")
+        .unwrap()
+        .arg("analyze")
+        .arg(dir.path().join("contract.rs"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("[rules.arithmetic] unknown rule"));
+}
+
+/// The fixed-point proofs run during `analyze`: the vesting schedule's
+/// unchecked `total_amount * elapsed / duration` can overflow i128.
+#[test]
+#[cfg(feature = "smt")]
+fn test_analyze_reports_fixed_point_overflow_in_vesting_contract() {
+    let contract = env::current_dir()
+        .unwrap()
+        .join("../../contracts/vesting/src/lib.rs")
+        .canonicalize()
+        .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json", "--no-cache"])
+        .arg(&contract)
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let overflow = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["code"] == "S011" && v["span"]["function"] == "vested_amount")
+        .expect("no S011 finding for vested_amount");
+    assert!(overflow["message"]
+        .as_str()
+        .unwrap()
+        .contains("can overflow"));
+}
+
+/// Without Z3 linked in, `--solver cvc5` pipes the fixed-point proofs to the
+/// `cvc5` binary on `PATH`, and `--emit-smt2` keeps the scripts it was sent.
+#[test]
+#[cfg(unix)]
+fn test_analyze_proves_fixed_point_bounds_with_solver_binary() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let contract = env::current_dir()
+        .unwrap()
+        .join("../../contracts/vesting/src/lib.rs")
+        .canonicalize()
+        .unwrap();
+    let dir = tempdir().unwrap();
+    let bin = dir.path().join("bin");
+    let scripts = dir.path().join("smt2");
+    fs::create_dir(&bin).unwrap();
+    let solver = bin.join("cvc5");
+    fs::write(
+        &solver,
+        "#!/bin/sh\ncat > /dev/null\nprintf 'sat\\n((multiplicand (_ bv170141183460469231731687303715884105727 256)) \
+         (multiplier (_ bv2 256)) (divisor (_ bv1 256)) \
+         (product (_ bv340282366920938463463374607431768211454 256)) \
+         (quotient (_ bv340282366920938463463374607431768211454 256)))\\n'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&solver, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), env::var("PATH").unwrap_or_default());
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json", "--no-cache"])
+        .args(["--solver", "cvc5", "--emit-smt2"])
+        .arg(&scripts)
+        .arg(&contract)
+        .env("PATH", path)
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let overflow = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["code"] == "S011" && v["span"]["function"] == "vested_amount")
+        .expect("no S011 finding for vested_amount");
+    assert!(overflow["message"]
+        .as_str()
+        .unwrap()
+        .contains("can overflow"));
+
+    let emitted = fs::read_dir(&scripts)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "smt2"))
+        .collect::<Vec<_>>();
+    assert!(!emitted.is_empty());
+    assert!(emitted
+        .iter()
+        .all(|path| fs::read_to_string(path).unwrap().contains("(check-sat)")));
+}
+
+/// Invariant preservation runs during `analyze`: a transfer that reads both
+/// balances before writing them mints tokens on a self-transfer.  The
+/// finding spans the function, so a `[suppressions]` entry can silence it.
+#[test]
+#[cfg(feature = "smt")]
+fn test_analyze_reports_function_that_breaks_a_contract_invariant() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("token.rs"),
+        r#"
+        #[invariant = "DataKey::TotalSupply == sum(DataKey::Balance)"]
+        impl Token {
+            pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+                let from_balance: i128 = env.storage().persistent().get(&DataKey::Balance(from.clone())).unwrap_or(0);
+                let to_balance: i128 = env.storage().persistent().get(&DataKey::Balance(to.clone())).unwrap_or(0);
+                if from_balance < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+                env.storage().persistent().set(&DataKey::Balance(from), &(from_balance - amount));
+                env.storage().persistent().set(&DataKey::Balance(to), &(to_balance + amount));
+                Ok(())
+            }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json", "--no-cache"])
+        .arg(dir.path().join("token.rs"))
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let violation = json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["code"] == "S011" && v["span"]["function"] == "transfer")
+        .expect("no S011 finding for transfer");
+    let message = violation["message"].as_str().unwrap();
+    assert!(message.starts_with("`transfer` does not preserve invariant"));
+    assert!(message.contains("[path: "), "{message}");
+
+    fs::write(
+        dir.path().join(".sanctify.toml"),
+        format!(
+            "[suppressions]\nS011 = [{{ file = \"token.rs\", line = {}, reason = \"self-transfers are rejected upstream\" }}]\n",
+            violation["span"]["start_line"]
+        ),
+    )
+    .unwrap();
+    let output = Command::cargo_bin("sanctifier")
+        .unwrap()
+        .args(["analyze", "--format", "json", "--no-cache"])
+        .arg(dir.path().join("token.rs"))
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(!json["rule_violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v["code"] == "S011"));
+    assert_eq!(json["suppressed"][0]["code"], "S011");
+}
@@ -0,0 +1,44 @@
+//! Fingerprints the crate's sources for `ENGINE_FINGERPRINT`, so caches of
+//! analysis results can tell builds with different rules apart even when
+//! the version number is the same.
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+fn main() {
+    let src = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+    let mut files = Vec::new();
+    collect_files(&src, &mut files);
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(env::var("CARGO_PKG_VERSION").unwrap());
+    for file in &files {
+        let name = file.strip_prefix(&src).unwrap_or(file);
+        hasher.update([0]);
+        hasher.update(name.to_string_lossy().replace('\\', "/"));
+        hasher.update([0]);
+        hasher.update(fs::read(file).unwrap_or_default());
+    }
+
+    println!("cargo:rerun-if-changed=src");
+    println!(
+        "cargo:rustc-env=SANCTIFIER_ENGINE_FINGERPRINT={:x}",
+        hasher.finalize()
+    );
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
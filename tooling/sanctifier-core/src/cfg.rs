@@ -15,32 +15,53 @@
 //! header, `if`/`else` branches join at a common successor, and `match` arms
 //! all flow into the block following the match.
 //!
+//! Every way out of the function ends in a dedicated [`Cfg::exit`] block:
+//! falling off the end of the body, `return`, a `?` that propagates an
+//! error, and panicking macros (`panic!`, `panic_with_error!`,
+//! `unreachable!`, ...; `assert!` and friends may or may not exit).
+//! `break` and `continue` jump to the exit or header of the loop they name,
+//! honouring labels, and a `loop` is only left through a `break`.
+//!
+//! Branching expressions on the right-hand side of a `let` or an assignment
+//! are lowered like their statement forms, with the binding repeated at the
+//! end of each branch: `let x = if c { a } else { b };` becomes
+//! `let x = a;` on one path and `let x = b;` on the other.  `match` arms and
+//! `if let` / `while let` conditions bind their patterns from the scrutinee
+//! ([`BlockStmt::MatchBinding`]).
+//!
 //! # Scope and limitations
 //!
 //! This is intentionally a lightweight, intra-procedural CFG tailored to the
 //! subset of Rust that appears in Soroban contracts, not a general-purpose
 //! Rust control-flow analyzer:
 //!
-//! * `let x = if/match { .. };` is **not** split into branch blocks — the
-//!   whole initializer is kept as one opaque statement in the current block.
-//!   Only `if`/`while`/`for`/`loop`/`match` that appear as a *statement*
-//!   (not as a sub-expression) get their own blocks/edges.
-//! * `break`/`continue`/`?` do not carry precise jump targets; they end the
-//!   current block but are not wired back to a specific loop-exit block.
-//!   This is a conservative simplification — it can never cause a dataflow
-//!   fact to be lost, only (rarely) computed slightly more permissively.
+//! * Branching is lowered for statements and for the whole right-hand side
+//!   of a `let` or assignment.  Branches nested deeper inside an expression
+//!   (`f(if c { a } else { b })`) stay part of one opaque statement.
+//! * A `?` or `assert!` exits after the statement containing it, so the
+//!   edge to [`Cfg::exit`] carries the effects of the whole statement.
+//! * Panicking method calls (`unwrap`, `expect`) and closures are not
+//!   modelled as exits.
 //! * Function calls are not inlined or resolved — this CFG is strictly
-//!   intra-procedural.
+//!   intra-procedural.  Calls between functions of a file are handled by
+//!   summaries instead (see [`crate::interprocedural`]).
 
-use syn::{Block, Expr, ExprForLoop, ExprIf, ExprLoop, ExprMatch, ExprWhile, Local, Pat, Stmt};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{
+    Block, Expr, ExprAssign, ExprForLoop, ExprIf, ExprLoop, ExprMatch, ExprWhile, Local, Pat, Stmt,
+};
 
 /// A single statement kept inside a [`BasicBlock`].
 ///
 /// Control-flow-introducing statements (`if`, `while`, `for`, `loop`,
-/// `match` used as a *statement*) are lowered into separate blocks/edges by
-/// [`Cfg::build`] and never appear here. Everything else — `let` bindings,
-/// plain expression statements, assignments — is kept verbatim so that
-/// dataflow passes can inspect it.
+/// `match` used as a *statement* or as the right-hand side of a `let` or
+/// assignment) are lowered into separate blocks/edges by [`Cfg::build`] and
+/// never appear here. Everything else — `let` bindings, plain expression
+/// statements, assignments — is kept verbatim so that dataflow passes can
+/// inspect it.
 #[derive(Debug, Clone)]
 pub enum BlockStmt {
     /// A `let` binding, e.g. `let x = expr;`.
@@ -51,6 +72,67 @@ pub enum BlockStmt {
     /// Kept separate from `Local` because the binding's taintedness depends
     /// on `iter_expr`, not on a normal initializer.
     ForBinding { pat: Pat, iter_expr: Expr },
+    /// The bindings of a `match` arm or an `if let` / `while let` pattern,
+    /// taken from the matched value.
+    MatchBinding { pat: Pat, scrutinee: Expr },
+}
+
+impl BlockStmt {
+    /// Expressions evaluated by the statement.  A [`BlockStmt::MatchBinding`]
+    /// evaluates nothing: its scrutinee is evaluated before the branch.
+    pub fn exprs(&self) -> Vec<&Expr> {
+        match self {
+            BlockStmt::Local(local) => local.init.iter().map(|init| &*init.expr).collect(),
+            BlockStmt::Expr(expr) => vec![expr],
+            BlockStmt::ForBinding { iter_expr, .. } => vec![iter_expr],
+            BlockStmt::MatchBinding { .. } => Vec::new(),
+        }
+    }
+
+    /// Whether the statement is a panicking macro (`panic!`,
+    /// `panic_with_error!`, ...), which aborts the invocation and rolls back
+    /// its effects.
+    pub fn panics(&self) -> bool {
+        matches!(self, BlockStmt::Expr(Expr::Macro(m)) if is_panic_macro(&m.mac))
+    }
+
+    /// Whether the statement is a `return Err(..)`.
+    pub fn returns_error(&self) -> bool {
+        let BlockStmt::Expr(Expr::Return(ret)) = self else {
+            return false;
+        };
+        let Some(Expr::Call(call)) = ret.expr.as_deref() else {
+            return false;
+        };
+        let Expr::Path(func) = &*call.func else {
+            return false;
+        };
+        func.path.segments.last().is_some_and(|s| s.ident == "Err")
+    }
+
+    /// Whether the statement contains a `?` that may return an error from
+    /// the function.
+    pub fn propagates_error(&self) -> bool {
+        self.exprs().into_iter().any(contains_try)
+    }
+
+    /// The statement as (token-spaced) source text.
+    pub fn to_source(&self) -> String {
+        match self {
+            BlockStmt::Local(local) => quote::quote!(#local).to_string(),
+            BlockStmt::Expr(expr) => quote::quote!(#expr).to_string(),
+            BlockStmt::ForBinding { pat, iter_expr } => {
+                format!(
+                    "for {} in {}",
+                    quote::quote!(#pat),
+                    quote::quote!(#iter_expr)
+                )
+            }
+            BlockStmt::MatchBinding { pat, scrutinee } => {
+                format!("{} = {}", quote::quote!(#pat), quote::quote!(#scrutinee))
+            }
+        }
+    }
 }
 
 /// A single node in the control-flow graph: a maximal run of statements with
@@ -61,6 +143,15 @@ pub struct BasicBlock {
     pub stmts: Vec<BlockStmt>,
 }
 
+/// The condition under which control takes an edge.
+#[derive(Debug, Clone)]
+pub struct EdgeCondition {
+    /// The `if` / `while` / `assert!` condition.
+    pub expr: Expr,
+    /// The value `expr` has when the edge is taken.
+    pub holds: bool,
+}
+
 /// An intra-procedural control-flow graph for one function body.
 #[derive(Debug, Clone)]
 pub struct Cfg {
@@ -68,6 +159,12 @@ pub struct Cfg {
     /// `successors[b]` lists every block reachable in one step from block `b`.
     pub successors: Vec<Vec<usize>>,
     pub entry: usize,
+    /// Empty block every path out of the function ends in.
+    pub exit: usize,
+    /// Conditions of the edges out of a boolean `if` / `while` test or an
+    /// `assert!`, keyed by `(from, to)`.  Other edges (`if let`, `match`
+    /// arms, `for` headers) carry no condition the graph can express.
+    pub conditions: HashMap<(usize, usize), EdgeCondition>,
 }
 
 impl Cfg {
@@ -75,23 +172,75 @@ impl Cfg {
     pub fn build(block: &Block) -> Cfg {
         let mut builder = CfgBuilder::default();
         let entry = builder.new_block();
-        builder.process_stmts(&block.stmts, entry);
+        builder.exit = builder.new_block();
+        let end = builder.process_stmts(&block.stmts, entry);
+        builder.add_edge(end, builder.exit);
         Cfg {
             blocks: builder.blocks,
             successors: builder.successors,
             entry,
+            exit: builder.exit,
+            conditions: builder.conditions,
         }
     }
 
     pub fn block_count(&self) -> usize {
         self.blocks.len()
     }
+
+    /// Renders the graph in Graphviz DOT format, one box per block listing
+    /// its statements.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n", escape_dot(name));
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in &self.blocks {
+            let mut label = if block.id == self.entry {
+                format!("B{} (entry)", block.id)
+            } else if block.id == self.exit {
+                format!("B{} (exit)", block.id)
+            } else {
+                format!("B{}", block.id)
+            };
+            label.push_str("\\l");
+            for stmt in &block.stmts {
+                label.push_str(&escape_dot(&stmt.to_source()));
+                label.push_str("\\l");
+            }
+            let _ = writeln!(dot, "    b{} [label=\"{}\"];", block.id, label);
+        }
+        for (from, succs) in self.successors.iter().enumerate() {
+            for to in succs {
+                let _ = writeln!(dot, "    b{} -> b{};", from, to);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Builds the statement that binds a branch's value, e.g. `let x = <value>;`.
+type Bind<'a> = &'a dyn Fn(Expr) -> BlockStmt;
+
+/// Jump targets of an enclosing loop or labelled block.
+struct JumpTargets {
+    label: Option<String>,
+    /// Loop header; `None` for a labelled block, which `continue` cannot
+    /// target and an unlabelled `break` skips.
+    continue_to: Option<usize>,
+    break_to: usize,
 }
 
 #[derive(Default)]
 struct CfgBuilder {
     blocks: Vec<BasicBlock>,
     successors: Vec<Vec<usize>>,
+    exit: usize,
+    jumps: Vec<JumpTargets>,
+    conditions: HashMap<(usize, usize), EdgeCondition>,
 }
 
 impl CfgBuilder {
@@ -109,10 +258,43 @@ impl CfgBuilder {
         self.successors[from].push(to);
     }
 
+    /// Adds the edge taken when `cond` evaluates to `holds`.
+    fn add_branch(&mut self, from: usize, to: usize, cond: &Expr, holds: bool) {
+        self.add_edge(from, to);
+        if !matches!(cond, Expr::Let(_)) {
+            self.conditions.insert(
+                (from, to),
+                EdgeCondition {
+                    expr: cond.clone(),
+                    holds,
+                },
+            );
+        }
+    }
+
     fn push_stmt(&mut self, block: usize, stmt: BlockStmt) {
         self.blocks[block].stmts.push(stmt);
     }
 
+    /// Ends `current` with a jump to `target` (if any). Anything textually
+    /// after this point in the same scope is unreachable along this path; it
+    /// gets a fresh, edge-less block so it doesn't pollute `current`'s flow.
+    fn jump(&mut self, current: usize, target: Option<usize>) -> usize {
+        if let Some(target) = target {
+            self.add_edge(current, target);
+        }
+        self.new_block()
+    }
+
+    /// Continues after a statement that may also leave the function (`?`,
+    /// `assert!`): `current` gets an edge to the exit and to a new block.
+    fn may_exit(&mut self, current: usize) -> usize {
+        self.add_edge(current, self.exit);
+        let next = self.new_block();
+        self.add_edge(current, next);
+        next
+    }
+
     /// Processes a sequence of statements starting at `current`, returning
     /// the block that subsequent (fallthrough) code should attach to.
     fn process_stmts(&mut self, stmts: &[Stmt], mut current: usize) -> usize {
@@ -124,46 +306,192 @@ impl CfgBuilder {
 
     fn process_stmt(&mut self, stmt: &Stmt, current: usize) -> usize {
         match stmt {
-            Stmt::Local(local) => {
-                self.push_stmt(current, BlockStmt::Local(local.clone()));
-                current
+            Stmt::Local(local) => self.process_local(local, current),
+            Stmt::Expr(expr, _) => self.process_expr(expr, current, None),
+            Stmt::Macro(m) => {
+                let expr = Expr::Macro(syn::ExprMacro {
+                    attrs: m.attrs.clone(),
+                    mac: m.mac.clone(),
+                });
+                self.process_expr(&expr, current, None)
             }
-            Stmt::Expr(expr, _) => self.process_expr_stmt(expr, current),
-            _ => current,
+            Stmt::Item(_) => current,
         }
     }
 
-    /// Processes an expression appearing directly as a statement. Only here
-    /// do `if`/`while`/`for`/`loop`/`match` get split into their own blocks —
-    /// see the module-level "Scope and limitations" note.
-    fn process_expr_stmt(&mut self, expr: &Expr, current: usize) -> usize {
+    fn process_local(&mut self, local: &Local, current: usize) -> usize {
+        let Some(init) = &local.init else {
+            self.push_stmt(current, BlockStmt::Local(local.clone()));
+            return current;
+        };
+
+        if let Some((_, diverge)) = &init.diverge {
+            // `let pat = expr else { .. };` — the else block must diverge.
+            self.push_stmt(current, BlockStmt::Local(local.clone()));
+            let else_entry = self.new_block();
+            self.add_edge(current, else_entry);
+            self.process_expr(diverge, else_entry, None);
+            return self.after_fallible(&init.expr, current);
+        }
+
+        if is_branching(&init.expr) {
+            let bind = |value: Expr| {
+                let mut local = local.clone();
+                if let Some(init) = &mut local.init {
+                    *init.expr = value;
+                }
+                BlockStmt::Local(local)
+            };
+            return self.process_expr(&init.expr, current, Some(&bind));
+        }
+
+        self.push_stmt(current, BlockStmt::Local(local.clone()));
+        self.after_fallible(&init.expr, current)
+    }
+
+    fn after_fallible(&mut self, expr: &Expr, current: usize) -> usize {
+        if contains_try(expr) {
+            self.may_exit(current)
+        } else {
+            current
+        }
+    }
+
+    /// Processes an expression used as a statement (`bind` is `None`) or
+    /// whose value is bound by `bind`.  Branching expressions get their own
+    /// blocks/edges — see the module-level "Scope and limitations" note.
+    fn process_expr(&mut self, expr: &Expr, current: usize, bind: Option<Bind<'_>>) -> usize {
         match expr {
-            Expr::If(e) => self.process_if(e, current),
-            Expr::While(e) => self.process_while(e, current),
-            Expr::ForLoop(e) => self.process_for(e, current),
-            Expr::Loop(e) => self.process_loop(e, current),
-            Expr::Match(e) => self.process_match(e, current),
-            Expr::Block(e) => self.process_stmts(&e.block.stmts, current),
-            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) => {
+            Expr::If(e) => self.process_if(e, current, bind),
+            Expr::Match(e) => self.process_match(e, current, bind),
+            Expr::Block(e) => match &e.label {
+                Some(label) => {
+                    let after = self.new_block();
+                    self.jumps.push(JumpTargets {
+                        label: Some(label.name.ident.to_string()),
+                        continue_to: None,
+                        break_to: after,
+                    });
+                    let end = self.process_block(&e.block, current, bind);
+                    self.jumps.pop();
+                    self.add_edge(end, after);
+                    after
+                }
+                None => self.process_block(&e.block, current, bind),
+            },
+            Expr::While(e) if bind.is_none() => self.process_while(e, current),
+            Expr::ForLoop(e) if bind.is_none() => self.process_for(e, current),
+            Expr::Loop(e) if bind.is_none() => self.process_loop(e, current),
+            Expr::Assign(a) if bind.is_none() && is_branching(&a.right) => {
+                let bind = |value: Expr| {
+                    BlockStmt::Expr(Expr::Assign(ExprAssign {
+                        attrs: a.attrs.clone(),
+                        left: a.left.clone(),
+                        eq_token: a.eq_token,
+                        right: Box::new(value),
+                    }))
+                };
+                self.process_expr(&a.right, current, Some(&bind))
+            }
+            Expr::Return(_) => {
                 self.push_stmt(current, BlockStmt::Expr(expr.clone()));
-                // Anything textually after this point in the same scope is
-                // unreachable along this path; give it a fresh, edge-less
-                // block so it doesn't pollute `current`'s flow.
-                self.new_block()
+                let exit = self.exit;
+                self.jump(current, Some(exit))
             }
-            _ => {
+            Expr::Break(b) => {
                 self.push_stmt(current, BlockStmt::Expr(expr.clone()));
+                let target = self.jump_target(b.label.as_ref(), false);
+                self.jump(current, target)
+            }
+            Expr::Continue(c) => {
+                self.push_stmt(current, BlockStmt::Expr(expr.clone()));
+                let target = self.jump_target(c.label.as_ref(), true);
+                self.jump(current, target)
+            }
+            Expr::Macro(m) if is_panic_macro(&m.mac) => {
+                self.push_stmt(current, BlockStmt::Expr(expr.clone()));
+                let exit = self.exit;
+                self.jump(current, Some(exit))
+            }
+            _ => {
+                let stmt = match bind {
+                    Some(bind) => bind(expr.clone()),
+                    None => BlockStmt::Expr(expr.clone()),
+                };
+                self.push_stmt(current, stmt);
+                match expr {
+                    Expr::Macro(m) if is_assert_macro(&m.mac) => match assert_condition(&m.mac) {
+                        Some(cond) => {
+                            let exit = self.exit;
+                            self.add_branch(current, exit, &cond, false);
+                            let next = self.new_block();
+                            self.add_branch(current, next, &cond, true);
+                            next
+                        }
+                        None => self.may_exit(current),
+                    },
+                    _ => self.after_fallible(expr, current),
+                }
+            }
+        }
+    }
+
+    /// Processes a `{ .. }` block; with `bind`, its tail expression is the
+    /// value being bound.
+    fn process_block(&mut self, block: &Block, current: usize, bind: Option<Bind<'_>>) -> usize {
+        let Some(bind) = bind else {
+            return self.process_stmts(&block.stmts, current);
+        };
+        match block.stmts.split_last() {
+            Some((Stmt::Expr(tail, None), init)) => {
+                let current = self.process_stmts(init, current);
+                self.process_expr(tail, current, Some(bind))
+            }
+            _ => {
+                let current = self.process_stmts(&block.stmts, current);
+                self.push_stmt(current, bind(syn::parse_quote!(())));
                 current
             }
         }
     }
 
-    fn process_if(&mut self, e: &ExprIf, current: usize) -> usize {
-        self.push_stmt(current, BlockStmt::Expr((*e.cond).clone()));
+    /// Block a `break` (or, with `is_continue`, a `continue`) jumps to.
+    fn jump_target(&self, label: Option<&syn::Lifetime>, is_continue: bool) -> Option<usize> {
+        let label = label.map(|l| l.ident.to_string());
+        let frame = self.jumps.iter().rev().find(|frame| match &label {
+            Some(label) => frame.label.as_ref() == Some(label),
+            None => frame.continue_to.is_some(),
+        })?;
+        if is_continue {
+            frame.continue_to
+        } else {
+            Some(frame.break_to)
+        }
+    }
 
+    /// Pushes the evaluation of an `if`/`while` condition into `current`, and
+    /// the bindings of an `if let`/`while let` pattern into `body_entry`.
+    fn push_condition(&mut self, cond: &Expr, current: usize, body_entry: usize) {
+        match cond {
+            Expr::Let(l) => {
+                self.push_stmt(current, BlockStmt::Expr((*l.expr).clone()));
+                self.push_stmt(
+                    body_entry,
+                    BlockStmt::MatchBinding {
+                        pat: (*l.pat).clone(),
+                        scrutinee: (*l.expr).clone(),
+                    },
+                );
+            }
+            _ => self.push_stmt(current, BlockStmt::Expr(cond.clone())),
+        }
+    }
+
+    fn process_if(&mut self, e: &ExprIf, current: usize, bind: Option<Bind<'_>>) -> usize {
         let then_entry = self.new_block();
-        self.add_edge(current, then_entry);
-        let then_exit = self.process_stmts(&e.then_branch.stmts, then_entry);
+        self.push_condition(&e.cond, current, then_entry);
+        self.add_branch(current, then_entry, &e.cond, true);
+        let then_exit = self.process_block(&e.then_branch, then_entry, bind);
 
         let join = self.new_block();
         self.add_edge(then_exit, join);
@@ -171,13 +499,13 @@ impl CfgBuilder {
         match &e.else_branch {
             Some((_, else_expr)) => {
                 let else_entry = self.new_block();
-                self.add_edge(current, else_entry);
-                let else_exit = self.process_expr_stmt(else_expr, else_entry);
+                self.add_branch(current, else_entry, &e.cond, false);
+                let else_exit = self.process_expr(else_expr, else_entry, bind);
                 self.add_edge(else_exit, join);
             }
             None => {
                 // No else: the false branch falls straight through to join.
-                self.add_edge(current, join);
+                self.add_branch(current, join, &e.cond, false);
             }
         }
         join
@@ -186,15 +514,15 @@ impl CfgBuilder {
     fn process_while(&mut self, e: &ExprWhile, current: usize) -> usize {
         let header = self.new_block();
         self.add_edge(current, header);
-        self.push_stmt(header, BlockStmt::Expr((*e.cond).clone()));
 
         let body_entry = self.new_block();
-        self.add_edge(header, body_entry);
-        let body_exit = self.process_stmts(&e.body.stmts, body_entry);
+        self.push_condition(&e.cond, header, body_entry);
+        self.add_branch(header, body_entry, &e.cond, true);
+        let after = self.new_block();
+        let body_exit = self.process_loop_body(&e.body, &e.label, body_entry, header, after);
         self.add_edge(body_exit, header); // back edge
 
-        let after = self.new_block();
-        self.add_edge(header, after);
+        self.add_branch(header, after, &e.cond, false);
         after
     }
 
@@ -211,10 +539,10 @@ impl CfgBuilder {
 
         let body_entry = self.new_block();
         self.add_edge(header, body_entry);
-        let body_exit = self.process_stmts(&e.body.stmts, body_entry);
+        let after = self.new_block();
+        let body_exit = self.process_loop_body(&e.body, &e.label, body_entry, header, after);
         self.add_edge(body_exit, header); // back edge
 
-        let after = self.new_block();
         self.add_edge(header, after);
         after
     }
@@ -222,31 +550,132 @@ impl CfgBuilder {
     fn process_loop(&mut self, e: &ExprLoop, current: usize) -> usize {
         let header = self.new_block();
         self.add_edge(current, header);
-        let body_exit = self.process_stmts(&e.body.stmts, header);
-        self.add_edge(body_exit, header); // back edge
-
-        // `loop` has no implicit condition; it only exits via `break`. We
-        // still add a reachable exit block so dataflow facts that hold at
-        // loop entry are conservatively available after it too.
+        // `loop` has no condition: the block after it is only reached
+        // through a `break`.
         let after = self.new_block();
-        self.add_edge(header, after);
+        let body_exit = self.process_loop_body(&e.body, &e.label, header, header, after);
+        self.add_edge(body_exit, header); // back edge
         after
     }
 
-    fn process_match(&mut self, e: &ExprMatch, current: usize) -> usize {
+    fn process_loop_body(
+        &mut self,
+        body: &Block,
+        label: &Option<syn::Label>,
+        entry: usize,
+        header: usize,
+        after: usize,
+    ) -> usize {
+        self.jumps.push(JumpTargets {
+            label: label.as_ref().map(|l| l.name.ident.to_string()),
+            continue_to: Some(header),
+            break_to: after,
+        });
+        let body_exit = self.process_stmts(&body.stmts, entry);
+        self.jumps.pop();
+        body_exit
+    }
+
+    fn process_match(&mut self, e: &ExprMatch, current: usize, bind: Option<Bind<'_>>) -> usize {
         self.push_stmt(current, BlockStmt::Expr((*e.expr).clone()));
+        let current = self.after_fallible(&e.expr, current);
 
         let join = self.new_block();
         for arm in &e.arms {
             let arm_entry = self.new_block();
             self.add_edge(current, arm_entry);
-            let arm_exit = self.process_expr_stmt(&arm.body, arm_entry);
+            self.push_stmt(
+                arm_entry,
+                BlockStmt::MatchBinding {
+                    pat: arm.pat.clone(),
+                    scrutinee: (*e.expr).clone(),
+                },
+            );
+            if let Some((_, guard)) = &arm.guard {
+                self.push_stmt(arm_entry, BlockStmt::Expr((**guard).clone()));
+            }
+            let arm_exit = self.process_expr(&arm.body, arm_entry, bind);
             self.add_edge(arm_exit, join);
         }
         join
     }
 }
 
+/// Whether `expr` is lowered into blocks when it is the whole right-hand side
+/// of a `let` or assignment.
+fn is_branching(expr: &Expr) -> bool {
+    match expr {
+        Expr::If(e) => e.else_branch.is_some(),
+        Expr::Match(_) => true,
+        Expr::Block(e) => e.label.is_none(),
+        _ => false,
+    }
+}
+
+fn macro_name(mac: &syn::Macro) -> String {
+    mac.path
+        .segments
+        .last()
+        .map(|s| s.ident.to_string())
+        .unwrap_or_default()
+}
+
+fn is_panic_macro(mac: &syn::Macro) -> bool {
+    matches!(
+        macro_name(mac).as_str(),
+        "panic" | "panic_with_error" | "unreachable" | "unimplemented" | "todo"
+    )
+}
+
+fn is_assert_macro(mac: &syn::Macro) -> bool {
+    matches!(
+        macro_name(mac).as_str(),
+        "assert" | "assert_eq" | "assert_ne"
+    )
+}
+
+/// The condition an `assert!` / `assert_eq!` / `assert_ne!` checks; `None`
+/// for any other macro.
+pub(crate) fn assert_condition(mac: &syn::Macro) -> Option<Expr> {
+    if !is_assert_macro(mac) {
+        return None;
+    }
+    let args = mac
+        .parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated)
+        .ok()?;
+    let mut args = args.into_iter();
+    match macro_name(mac).as_str() {
+        "assert" => args.next(),
+        name => {
+            let (lhs, rhs) = (args.next()?, args.next()?);
+            Some(if name == "assert_eq" {
+                syn::parse_quote!((#lhs) == (#rhs))
+            } else {
+                syn::parse_quote!((#lhs) != (#rhs))
+            })
+        }
+    }
+}
+
+/// Whether `expr` contains a `?` that returns from the enclosing function
+/// (closures and async blocks have their own).
+fn contains_try(expr: &Expr) -> bool {
+    struct TryFinder(bool);
+
+    impl<'ast> Visit<'ast> for TryFinder {
+        fn visit_expr_try(&mut self, _: &'ast syn::ExprTry) {
+            self.0 = true;
+        }
+        fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+        fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+        fn visit_item(&mut self, _: &'ast syn::Item) {}
+    }
+
+    let mut finder = TryFinder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,4 +755,112 @@ mod tests {
         let branching = build("if cond { let a = 1; } else { let b = 2; }");
         assert!(branching.block_count() > straight.block_count());
     }
+
+    /// Blocks reachable from the entry.
+    fn reachable(cfg: &Cfg) -> Vec<bool> {
+        let mut seen = vec![false; cfg.block_count()];
+        let mut stack = vec![cfg.entry];
+        while let Some(b) = stack.pop() {
+            if !std::mem::replace(&mut seen[b], true) {
+                stack.extend(&cfg.successors[b]);
+            }
+        }
+        seen
+    }
+
+    /// The block holding a statement whose source text contains `needle`.
+    fn block_with(cfg: &Cfg, needle: &str) -> usize {
+        cfg.blocks
+            .iter()
+            .find(|b| b.stmts.iter().any(|s| s.to_source().contains(needle)))
+            .map(|b| b.id)
+            .unwrap_or_else(|| panic!("no block contains `{needle}`"))
+    }
+
+    #[test]
+    fn return_and_panic_edge_to_exit_and_cut_the_path() {
+        let cfg = build("if a { return; } if b { panic!(\"no\"); } done();");
+        assert!(cfg.successors[block_with(&cfg, "return")].contains(&cfg.exit));
+        assert!(cfg.successors[block_with(&cfg, "panic")].contains(&cfg.exit));
+        assert!(cfg.successors[block_with(&cfg, "done")].contains(&cfg.exit));
+        assert!(cfg.blocks[cfg.exit].stmts.is_empty());
+
+        let cfg = build("return; after();");
+        assert!(!reachable(&cfg)[block_with(&cfg, "after")]);
+    }
+
+    #[test]
+    fn try_operator_may_exit() {
+        let cfg = build("let v = load(key)?; use_it(v);");
+        let load = block_with(&cfg, "load");
+        assert!(cfg.successors[load].contains(&cfg.exit));
+        assert_eq!(cfg.successors[load].len(), 2);
+        // A `?` inside a closure returns from the closure, not the function.
+        let cfg = build("let f = |k| load(k)?; use_it(f);");
+        assert_eq!(cfg.successors[cfg.entry], vec![cfg.exit]);
+    }
+
+    #[test]
+    fn break_and_continue_target_the_labelled_loop() {
+        let cfg = build(
+            "'outer: for x in xs { while c { if d { break 'outer; } if e { continue 'outer; } inner(); } } after();",
+        );
+        let header = block_with(&cfg, "for x in xs");
+        let after = block_with(&cfg, "after");
+        assert!(cfg.successors[block_with(&cfg, "break")].contains(&after));
+        assert!(cfg.successors[block_with(&cfg, "continue")].contains(&header));
+    }
+
+    #[test]
+    fn loop_is_only_left_through_break() {
+        let cfg = build("loop { spin(); } after();");
+        assert!(!reachable(&cfg)[block_with(&cfg, "after")]);
+        let cfg = build("loop { if done { break; } } after();");
+        assert!(reachable(&cfg)[block_with(&cfg, "after")]);
+    }
+
+    #[test]
+    fn branching_let_initializer_is_lowered_per_branch() {
+        let cfg = build("let x = if c { a } else { b }; use_it(x);");
+        assert_eq!(cfg.successors[cfg.entry].len(), 2);
+        let then_block = block_with(&cfg, "let x = a");
+        let else_block = block_with(&cfg, "let x = b");
+        assert_ne!(then_block, else_block);
+
+        let cfg = build("y = match v { Some(n) => n, None => 0 };");
+        assert_eq!(cfg.successors[cfg.entry].len(), 2);
+        let arm = block_with(&cfg, "y = n");
+        assert!(matches!(
+            cfg.blocks[arm].stmts[0],
+            BlockStmt::MatchBinding { .. }
+        ));
+    }
+
+    #[test]
+    fn branch_and_assert_edges_carry_their_condition() {
+        let cfg = build("if a > b { x(); } else { y(); } assert!(ok); done();");
+        let then_edge = &cfg.conditions[&(cfg.entry, block_with(&cfg, "x ()"))];
+        assert!(then_edge.holds);
+        assert!(matches!(then_edge.expr, Expr::Binary(_)));
+        assert!(!cfg.conditions[&(cfg.entry, block_with(&cfg, "y ()"))].holds);
+
+        let check = block_with(&cfg, "assert");
+        assert!(!cfg.conditions[&(check, cfg.exit)].holds);
+        let next = block_with(&cfg, "done");
+        assert!(cfg.conditions[&(check, next)].holds);
+
+        let cfg = build("if let Some(v) = opt { x(); }");
+        assert!(cfg.conditions.is_empty());
+    }
+
+    #[test]
+    fn dot_export_lists_blocks_and_edges() {
+        let cfg = build("if c { sink(\"x\"); }");
+        let dot = cfg.to_dot("f");
+        assert!(dot.starts_with("digraph \"f\" {"));
+        assert!(dot.contains("(entry)") && dot.contains("(exit)"));
+        assert!(dot.contains("sink (\\\"x\\\")"));
+        let edges = cfg.successors.iter().map(Vec::len).sum::<usize>();
+        assert_eq!(dot.matches(" -> ").count(), edges);
+    }
 }
@@ -99,7 +99,7 @@ impl DiscoveredContract {
 /// Soroban-defined reserved function names that are not user-callable.
 const RESERVED_ENTRYPOINTS: &[&str] = &["__constructor", "__check_auth"];
 
-fn has_attr_named(attrs: &[syn::Attribute], name: &str) -> bool {
+pub(crate) fn has_attr_named(attrs: &[syn::Attribute], name: &str) -> bool {
     attrs.iter().any(|attr| {
         if let Meta::Path(path) = &attr.meta {
             path.is_ident(name) || path.segments.iter().any(|s| s.ident == name)
@@ -214,6 +214,36 @@ pub fn discover_contracts(file: &File) -> Vec<DiscoveredContract> {
     by_name.into_values().collect()
 }
 
+/// The `impl` block and declaration of the public function `name` of
+/// `contract`, as listed by [`discover_contracts`].
+pub(crate) fn entry_point<'a>(
+    file: &'a File,
+    contract: &str,
+    name: &str,
+) -> Option<(&'a syn::ItemImpl, &'a syn::ImplItemFn)> {
+    file.items.iter().find_map(|item| {
+        let Item::Impl(impl_block) = item else {
+            return None;
+        };
+        if !has_attr_named(&impl_block.attrs, "contractimpl")
+            || type_to_name(&impl_block.self_ty).as_deref() != Some(contract)
+        {
+            return None;
+        }
+        impl_block
+            .items
+            .iter()
+            .find_map(|impl_item| match impl_item {
+                ImplItem::Fn(f)
+                    if f.sig.ident == name && matches!(f.vis, syn::Visibility::Public(_)) =>
+                {
+                    Some((impl_block, f))
+                }
+                _ => None,
+            })
+    })
+}
+
 // ── Unit tests ────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
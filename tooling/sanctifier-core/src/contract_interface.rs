@@ -0,0 +1,601 @@
+//! Contract interface extraction.
+//!
+//! [`extract_interface`] describes what a contract exposes to callers: every
+//! public function of a `#[contractimpl]` block found by
+//! [`discover_contracts`] with its typed parameters and return type, the
+//! addresses it calls `require_auth` on and the events it publishes, together
+//! with the `#[contracttype]` and `#[contracterror]` definitions of the file.
+//! Auth checks and events in helper functions the entry point calls are
+//! attributed to the entry point.
+//!
+//! The `env: Env` parameter is left out of the inputs, as in the contract
+//! spec the SDK embeds in the compiled wasm.  Types are written as in the
+//! source with whitespace and references removed, e.g. `Vec<Address>`.
+
+use crate::contract_discovery::{discover_contracts, entry_point};
+use crate::storage_layout::{layout_type, type_name, LayoutType};
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use syn::visit::Visit;
+use syn::{parse_str, Expr, File, ImplItem, Item, Lit, Pat, Signature};
+
+/// Interface of the contracts in one source file or a whole crate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractInterface {
+    /// Contracts, ordered by name.
+    pub contracts: Vec<InterfaceContract>,
+    /// `#[contracttype]` definitions, in declaration order.
+    pub types: Vec<LayoutType>,
+    /// `#[contracterror]` enums, in declaration order.
+    pub errors: Vec<ContractErrorEnum>,
+}
+
+/// A contract and its callable functions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceContract {
+    pub name: String,
+    /// Public functions, in declaration order.
+    pub functions: Vec<InterfaceFunction>,
+}
+
+/// A public contract function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceFunction {
+    pub name: String,
+    /// Parameters after `env`.
+    pub inputs: Vec<InterfaceParam>,
+    /// Return type; `None` for `()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Addresses the function requires authorization from, as written.
+    pub auth: Vec<String>,
+    /// Events the function publishes.
+    pub events: Vec<InterfaceEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+/// A function parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// An `env.events().publish(topics, data)` call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceEvent {
+    pub topics: Vec<EventValue>,
+    pub data: EventValue,
+}
+
+/// A topic or the data of an event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventValue {
+    /// A constant symbol such as `symbol_short!("transfer")`.
+    Symbol { value: String },
+    /// A value computed at run time; `ty` is known for parameters.
+    Value {
+        expr: String,
+        #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+        ty: Option<String>,
+    },
+}
+
+/// A `#[contracterror]` enum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractErrorEnum {
+    pub name: String,
+    pub cases: Vec<ErrorCase>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+/// One error case and the code it is reported with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCase {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<i64>,
+}
+
+impl ContractInterface {
+    /// Record `file` as the source of every function, type and error.
+    pub fn in_file(mut self, file: &str) -> Self {
+        for function in self.contracts.iter_mut().flat_map(|c| &mut c.functions) {
+            function.file = Some(file.to_string());
+        }
+        for ty in &mut self.types {
+            ty.file = Some(file.to_string());
+        }
+        for error in &mut self.errors {
+            error.file = Some(file.to_string());
+        }
+        self
+    }
+
+    /// Add the interface of another source file of the same crate.  A
+    /// contract implemented across files gets the functions of both; a type
+    /// or function declared twice keeps its first declaration.
+    pub fn merge(&mut self, other: ContractInterface) {
+        for contract in other.contracts {
+            match self.contracts.iter_mut().find(|c| c.name == contract.name) {
+                Some(existing) => {
+                    for function in contract.functions {
+                        if !existing.functions.iter().any(|f| f.name == function.name) {
+                            existing.functions.push(function);
+                        }
+                    }
+                }
+                None => self.contracts.push(contract),
+            }
+        }
+        self.contracts.sort_by(|a, b| a.name.cmp(&b.name));
+        for ty in other.types {
+            if !self.types.iter().any(|t| t.name == ty.name) {
+                self.types.push(ty);
+            }
+        }
+        for error in other.errors {
+            if !self.errors.iter().any(|e| e.name == error.name) {
+                self.errors.push(error);
+            }
+        }
+    }
+}
+
+/// Extract the interface of the contracts in one source file.
+pub fn extract_interface(source: &str) -> Result<ContractInterface, syn::Error> {
+    let file = parse_str::<File>(source)?;
+    let scope = FileScope::new(&file);
+
+    let contracts = discover_contracts(&file)
+        .into_iter()
+        .filter(|contract| contract.has_contractimpl)
+        .map(|contract| InterfaceContract {
+            functions: contract
+                .all_public_functions()
+                .iter()
+                .filter_map(|func| entry_point(&file, &contract.struct_name, &func.name))
+                .map(|(_, f)| scope.function(&f.sig, &f.block))
+                .collect(),
+            name: contract.struct_name,
+        })
+        .collect();
+
+    let types = file
+        .items
+        .iter()
+        .filter_map(|item| layout_type(item, "contracttype"))
+        .collect();
+    let errors = file
+        .items
+        .iter()
+        .filter_map(|item| layout_type(item, "contracterror"))
+        .map(|error| ContractErrorEnum {
+            name: error.name,
+            cases: error
+                .members
+                .into_iter()
+                .map(|case| ErrorCase {
+                    name: case.name,
+                    code: case.discriminant,
+                })
+                .collect(),
+            file: None,
+            line: error.line,
+        })
+        .collect();
+
+    Ok(ContractInterface {
+        contracts,
+        types,
+        errors,
+    })
+}
+
+/// Functions and constants of a file, to follow helper calls and resolve
+/// constant event topics.
+struct FileScope<'a> {
+    functions: HashMap<String, &'a syn::Block>,
+    consts: HashMap<String, &'a Expr>,
+}
+
+impl<'a> FileScope<'a> {
+    fn new(file: &'a File) -> Self {
+        let mut scope = Self {
+            functions: HashMap::new(),
+            consts: HashMap::new(),
+        };
+        for item in &file.items {
+            match item {
+                Item::Fn(f) => {
+                    scope.functions.insert(f.sig.ident.to_string(), &f.block);
+                }
+                Item::Const(c) => {
+                    scope.consts.insert(c.ident.to_string(), &c.expr);
+                }
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        match impl_item {
+                            ImplItem::Fn(f) => {
+                                scope
+                                    .functions
+                                    .entry(f.sig.ident.to_string())
+                                    .or_insert(&f.block);
+                            }
+                            ImplItem::Const(c) => {
+                                scope.consts.insert(c.ident.to_string(), &c.expr);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        scope
+    }
+
+    fn function(&self, sig: &Signature, block: &syn::Block) -> InterfaceFunction {
+        let inputs: Vec<InterfaceParam> = sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(pt) => Some(InterfaceParam {
+                    name: match pt.pat.as_ref() {
+                        Pat::Ident(pi) => pi.ident.to_string(),
+                        pat => expr_text(pat),
+                    },
+                    ty: type_name(&pt.ty),
+                }),
+                syn::FnArg::Receiver(_) => None,
+            })
+            .filter(|param| !is_env_type(&param.ty))
+            .collect();
+
+        let mut collector = EffectCollector {
+            scope: self,
+            params: inputs
+                .iter()
+                .map(|p| (p.name.clone(), p.ty.clone()))
+                .collect(),
+            visited: HashSet::from([sig.ident.to_string()]),
+            auth: Vec::new(),
+            events: Vec::new(),
+        };
+        collector.visit_block(block);
+
+        InterfaceFunction {
+            name: sig.ident.to_string(),
+            output: match &sig.output {
+                syn::ReturnType::Type(_, ty) => Some(type_name(ty)),
+                syn::ReturnType::Default => None,
+            },
+            inputs,
+            auth: collector.auth,
+            events: collector.events,
+            file: None,
+            line: sig.ident.span().start().line,
+        }
+    }
+}
+
+fn is_env_type(ty: &str) -> bool {
+    ty == "Env" || ty.ends_with("::Env")
+}
+
+/// Collects the auth checks and events of a function and the helpers it
+/// calls.
+struct EffectCollector<'s, 'a> {
+    scope: &'s FileScope<'a>,
+    /// Parameter types of the entry point.
+    params: HashMap<String, String>,
+    /// Functions already walked, to stop at recursion.
+    visited: HashSet<String>,
+    auth: Vec<String>,
+    events: Vec<InterfaceEvent>,
+}
+
+impl EffectCollector<'_, '_> {
+    fn follow(&mut self, name: &str) {
+        if let Some(block) = self.scope.functions.get(name).copied() {
+            if self.visited.insert(name.to_string()) {
+                self.visit_block(block);
+            }
+        }
+    }
+
+    fn event_value(&self, expr: &Expr, depth: usize) -> EventValue {
+        if let Some(symbol) = self.symbol(expr, depth) {
+            return EventValue::Symbol { value: symbol };
+        }
+        let ty = match strip(expr) {
+            Expr::Path(p) => p
+                .path
+                .get_ident()
+                .and_then(|ident| self.params.get(&ident.to_string()))
+                .cloned(),
+            _ => None,
+        };
+        EventValue::Value {
+            expr: expr_text(strip(expr)),
+            ty,
+        }
+    }
+
+    /// Value of a constant symbol expression.
+    fn symbol(&self, expr: &Expr, depth: usize) -> Option<String> {
+        match strip(expr) {
+            Expr::Macro(m) if m.mac.path.is_ident("symbol_short") => {
+                let lit = m.mac.parse_body::<syn::LitStr>().ok()?;
+                Some(lit.value())
+            }
+            Expr::Call(call) => {
+                let Expr::Path(func) = call.func.as_ref() else {
+                    return None;
+                };
+                let is_symbol_new = func.path.segments.len() >= 2
+                    && func.path.segments.iter().rev().nth(1)?.ident == "Symbol"
+                    && func.path.segments.last()?.ident == "new";
+                match (is_symbol_new, call.args.iter().nth(1)) {
+                    (true, Some(Expr::Lit(lit))) => match &lit.lit {
+                        Lit::Str(s) => Some(s.value()),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Expr::Path(p) if depth < 4 => {
+                let name = p.path.segments.last()?.ident.to_string();
+                let value = self.scope.consts.get(&name)?;
+                self.symbol(value, depth + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for EffectCollector<'_, '_> {
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        if method == "require_auth" || method == "require_auth_for_args" {
+            let who = expr_text(strip(&call.receiver));
+            if !self.auth.contains(&who) {
+                self.auth.push(who);
+            }
+        } else if method == "publish"
+            && matches!(call.receiver.as_ref(), Expr::MethodCall(inner) if inner.method == "events")
+            && call.args.len() == 2
+        {
+            let topics = match strip(&call.args[0]) {
+                Expr::Tuple(tuple) => tuple
+                    .elems
+                    .iter()
+                    .map(|topic| self.event_value(topic, 0))
+                    .collect(),
+                topic => vec![self.event_value(topic, 0)],
+            };
+            let data = self.event_value(&call.args[1], 0);
+            self.events.push(InterfaceEvent { topics, data });
+        }
+        syn::visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(func) = call.func.as_ref() {
+            if let Some(last) = func.path.segments.last() {
+                self.follow(&last.ident.to_string());
+            }
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+
+    // Nested items are only reached through calls.
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
+
+fn strip(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Reference(r) => strip(&r.expr),
+        Expr::Paren(p) => strip(&p.expr),
+        Expr::MethodCall(call) if call.method == "clone" && call.args.is_empty() => {
+            strip(&call.receiver)
+        }
+        _ => expr,
+    }
+}
+
+fn expr_text<T: ToTokens>(tokens: &T) -> String {
+    tokens
+        .to_token_stream()
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage_layout::LayoutTypeKind;
+
+    const TOKEN: &str = r#"
+        const TRANSFER: Symbol = symbol_short!("transfer");
+
+        #[contracterror]
+        #[repr(u32)]
+        pub enum Error {
+            NotAdmin = 1,
+            InsufficientBalance = 2,
+        }
+
+        #[contracttype]
+        pub enum DataKey {
+            Admin,
+            Balance(Address),
+        }
+
+        #[contract]
+        pub struct Token;
+
+        fn check_admin(env: &Env) {
+            let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+            admin.require_auth();
+        }
+
+        #[contractimpl]
+        impl Token {
+            pub fn mint(env: Env, to: Address, amount: i128) {
+                check_admin(&env);
+                env.events().publish((symbol_short!("mint"), to.clone()), amount);
+            }
+
+            pub fn transfer(env: Env, from: Address, to: &Address, amount: i128) -> Result<(), Error> {
+                from.require_auth();
+                env.events().publish((TRANSFER, from, to.clone()), amount);
+                Ok(())
+            }
+
+            pub fn balance(env: Env, id: Address) -> i128 {
+                0
+            }
+
+            fn internal(env: Env) {}
+        }
+    "#;
+
+    fn function<'a>(interface: &'a ContractInterface, name: &str) -> &'a InterfaceFunction {
+        interface.contracts[0]
+            .functions
+            .iter()
+            .find(|f| f.name == name)
+            .unwrap()
+    }
+
+    fn symbol(value: &str) -> EventValue {
+        EventValue::Symbol {
+            value: value.to_string(),
+        }
+    }
+
+    fn value(expr: &str, ty: Option<&str>) -> EventValue {
+        EventValue::Value {
+            expr: expr.to_string(),
+            ty: ty.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn lists_public_functions_with_typed_inputs_and_outputs() {
+        let interface = extract_interface(TOKEN).unwrap();
+        assert_eq!(interface.contracts.len(), 1);
+        assert_eq!(interface.contracts[0].name, "Token");
+        let names: Vec<_> = interface.contracts[0]
+            .functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, ["mint", "transfer", "balance"]);
+
+        let transfer = function(&interface, "transfer");
+        let inputs: Vec<_> = transfer
+            .inputs
+            .iter()
+            .map(|p| (p.name.as_str(), p.ty.as_str()))
+            .collect();
+        assert_eq!(
+            inputs,
+            [("from", "Address"), ("to", "Address"), ("amount", "i128")]
+        );
+        assert_eq!(transfer.output.as_deref(), Some("Result<(),Error>"));
+        assert_eq!(function(&interface, "mint").output, None);
+    }
+
+    #[test]
+    fn records_auth_including_helpers() {
+        let interface = extract_interface(TOKEN).unwrap();
+        assert_eq!(function(&interface, "transfer").auth, ["from"]);
+        assert_eq!(function(&interface, "mint").auth, ["admin"]);
+        assert!(function(&interface, "balance").auth.is_empty());
+    }
+
+    #[test]
+    fn records_event_topics_and_data() {
+        let interface = extract_interface(TOKEN).unwrap();
+        let transfer = &function(&interface, "transfer").events;
+        assert_eq!(transfer.len(), 1);
+        assert_eq!(
+            transfer[0].topics,
+            [
+                symbol("transfer"),
+                value("from", Some("Address")),
+                value("to", Some("Address")),
+            ]
+        );
+        assert_eq!(transfer[0].data, value("amount", Some("i128")));
+        assert_eq!(
+            function(&interface, "mint").events[0].topics[0],
+            symbol("mint")
+        );
+    }
+
+    #[test]
+    fn records_errors_and_types() {
+        let interface = extract_interface(TOKEN).unwrap();
+        let error = &interface.errors[0];
+        assert_eq!(error.name, "Error");
+        let cases: Vec<_> = error
+            .cases
+            .iter()
+            .map(|c| (c.name.as_str(), c.code))
+            .collect();
+        assert_eq!(
+            cases,
+            [("NotAdmin", Some(1)), ("InsufficientBalance", Some(2))]
+        );
+        assert_eq!(interface.types.len(), 1);
+        assert_eq!(interface.types[0].name, "DataKey");
+        assert_eq!(interface.types[0].kind, LayoutTypeKind::Enum);
+    }
+
+    #[test]
+    fn merge_joins_contracts_split_across_files() {
+        let mut merged = extract_interface(TOKEN).unwrap().in_file("src/lib.rs");
+        merged.merge(
+            extract_interface(
+                r#"
+                #[contractimpl]
+                impl Token {
+                    pub fn burn(env: Env, from: Address, amount: i128) {
+                        from.require_auth();
+                    }
+                }
+                "#,
+            )
+            .unwrap()
+            .in_file("src/burn.rs"),
+        );
+        assert_eq!(merged.contracts.len(), 1);
+        let burn = function(&merged, "burn");
+        assert_eq!(burn.file.as_deref(), Some("src/burn.rs"));
+        assert_eq!(
+            function(&merged, "mint").file.as_deref(),
+            Some("src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn serializes_event_values_with_their_kind() {
+        let json = serde_json::to_value(value("to", Some("Address"))).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "value", "expr": "to", "type": "Address"})
+        );
+    }
+}
@@ -0,0 +1,668 @@
+//! Contract spec decoding and interface comparison.
+//!
+//! The Soroban SDK embeds a description of every contract function and
+//! `#[contracttype]` / `#[contracterror]` definition in the `contractspecv0`
+//! custom section of the compiled wasm, as a stream of XDR `ScSpecEntry`
+//! values.  [`interface_from_wasm`] decodes that section into the same
+//! [`ContractInterface`] model [`extract_interface`] builds from source, and
+//! [`diff_interfaces`] reports how two interfaces differ function by
+//! function, so two builds — or a build and its source — can be compared
+//! even when their bytes differ.
+//!
+//! The spec carries no auth or event information, so only function
+//! signatures, types and error enums are compared.  Type names are compared
+//! without their module path (`soroban_sdk::Address` equals `Address`), and
+//! struct fields regardless of order, since the spec lists them sorted.
+//!
+//! [`extract_interface`]: crate::contract_interface::extract_interface
+
+use crate::contract_interface::{
+    ContractErrorEnum, ContractInterface, ErrorCase, InterfaceContract, InterfaceFunction,
+    InterfaceParam,
+};
+use crate::storage_layout::{LayoutMember, LayoutType, LayoutTypeKind};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use stellar_xdr::curr::{
+    Limited, Limits, ReadXdr, ScSpecEntry, ScSpecTypeDef, ScSpecUdtUnionCaseV0,
+};
+use thiserror::Error;
+use wasmparser::{Parser, Payload};
+
+/// Name of the custom section holding the contract spec.
+pub const SPEC_SECTION: &str = "contractspecv0";
+
+/// A wasm module whose contract spec could not be read.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ContractSpecError {
+    /// The bytes are not a well-formed wasm module.
+    #[error("invalid wasm module: {0}")]
+    Parse(String),
+    /// The module has no `contractspecv0` section.
+    #[error("wasm module has no {SPEC_SECTION} section; was it built with the Soroban SDK?")]
+    Missing,
+    /// The section is not a valid stream of spec entries.
+    #[error("invalid {SPEC_SECTION} section: {0}")]
+    Xdr(String),
+}
+
+/// Decode the contract spec embedded in the wasm module `bytes`.
+///
+/// The result holds one contract named `contract`, since the spec does not
+/// record the contract's name.
+pub fn interface_from_wasm(bytes: &[u8]) -> Result<ContractInterface, ContractSpecError> {
+    let mut section = None;
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload.map_err(|e| ContractSpecError::Parse(e.to_string()))? {
+            Payload::CustomSection(custom) if custom.name() == SPEC_SECTION => {
+                section = Some(custom.data().to_vec());
+            }
+            _ => {}
+        }
+    }
+    let section = section.ok_or(ContractSpecError::Missing)?;
+
+    let entries = ScSpecEntry::read_xdr_iter(&mut Limited::new(
+        Cursor::new(section.as_slice()),
+        Limits {
+            depth: 500,
+            len: section.len(),
+        },
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| ContractSpecError::Xdr(e.to_string()))?;
+
+    let mut interface = ContractInterface::default();
+    let mut functions = Vec::new();
+    for entry in entries {
+        match entry {
+            ScSpecEntry::FunctionV0(f) => functions.push(InterfaceFunction {
+                name: f.name.0.to_utf8_string_lossy(),
+                inputs: f
+                    .inputs
+                    .iter()
+                    .map(|input| InterfaceParam {
+                        name: input.name.to_utf8_string_lossy(),
+                        ty: spec_type(&input.type_),
+                    })
+                    .collect(),
+                output: f
+                    .outputs
+                    .first()
+                    .filter(|ty| !matches!(ty, ScSpecTypeDef::Void))
+                    .map(spec_type),
+                auth: Vec::new(),
+                events: Vec::new(),
+                file: None,
+                line: 0,
+            }),
+            ScSpecEntry::UdtStructV0(s) => interface.types.push(LayoutType {
+                name: s.name.to_utf8_string_lossy(),
+                kind: LayoutTypeKind::Struct,
+                members: s
+                    .fields
+                    .iter()
+                    .map(|field| LayoutMember {
+                        name: field.name.to_utf8_string_lossy(),
+                        types: vec![spec_type(&field.type_)],
+                        discriminant: None,
+                    })
+                    .collect(),
+                file: None,
+                line: 0,
+            }),
+            ScSpecEntry::UdtUnionV0(u) => interface.types.push(LayoutType {
+                name: u.name.to_utf8_string_lossy(),
+                kind: LayoutTypeKind::Enum,
+                members: u
+                    .cases
+                    .iter()
+                    .map(|case| match case {
+                        ScSpecUdtUnionCaseV0::VoidV0(v) => LayoutMember {
+                            name: v.name.to_utf8_string_lossy(),
+                            types: Vec::new(),
+                            discriminant: None,
+                        },
+                        ScSpecUdtUnionCaseV0::TupleV0(t) => LayoutMember {
+                            name: t.name.to_utf8_string_lossy(),
+                            types: t.type_.iter().map(spec_type).collect(),
+                            discriminant: None,
+                        },
+                    })
+                    .collect(),
+                file: None,
+                line: 0,
+            }),
+            ScSpecEntry::UdtEnumV0(e) => interface.types.push(LayoutType {
+                name: e.name.to_utf8_string_lossy(),
+                kind: LayoutTypeKind::Enum,
+                members: e
+                    .cases
+                    .iter()
+                    .map(|case| LayoutMember {
+                        name: case.name.to_utf8_string_lossy(),
+                        types: Vec::new(),
+                        discriminant: Some(i64::from(case.value)),
+                    })
+                    .collect(),
+                file: None,
+                line: 0,
+            }),
+            ScSpecEntry::UdtErrorEnumV0(e) => interface.errors.push(ContractErrorEnum {
+                name: e.name.to_utf8_string_lossy(),
+                cases: e
+                    .cases
+                    .iter()
+                    .map(|case| ErrorCase {
+                        name: case.name.to_utf8_string_lossy(),
+                        code: Some(i64::from(case.value)),
+                    })
+                    .collect(),
+                file: None,
+                line: 0,
+            }),
+        }
+    }
+    interface.contracts.push(InterfaceContract {
+        name: "contract".to_string(),
+        functions,
+    });
+    Ok(interface)
+}
+
+/// Rust spelling of a spec type, as [`extract_interface`] writes it.
+///
+/// [`extract_interface`]: crate::contract_interface::extract_interface
+fn spec_type(ty: &ScSpecTypeDef) -> String {
+    match ty {
+        ScSpecTypeDef::Val => "Val".to_string(),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Void => "()".to_string(),
+        ScSpecTypeDef::Error => "Error".to_string(),
+        ScSpecTypeDef::U32 => "u32".to_string(),
+        ScSpecTypeDef::I32 => "i32".to_string(),
+        ScSpecTypeDef::U64 => "u64".to_string(),
+        ScSpecTypeDef::I64 => "i64".to_string(),
+        ScSpecTypeDef::Timepoint => "Timepoint".to_string(),
+        ScSpecTypeDef::Duration => "Duration".to_string(),
+        ScSpecTypeDef::U128 => "u128".to_string(),
+        ScSpecTypeDef::I128 => "i128".to_string(),
+        ScSpecTypeDef::U256 => "U256".to_string(),
+        ScSpecTypeDef::I256 => "I256".to_string(),
+        ScSpecTypeDef::Bytes => "Bytes".to_string(),
+        ScSpecTypeDef::String => "String".to_string(),
+        ScSpecTypeDef::Symbol => "Symbol".to_string(),
+        ScSpecTypeDef::Address => "Address".to_string(),
+        ScSpecTypeDef::Option(o) => format!("Option<{}>", spec_type(&o.value_type)),
+        ScSpecTypeDef::Result(r) => format!(
+            "Result<{},{}>",
+            spec_type(&r.ok_type),
+            spec_type(&r.error_type)
+        ),
+        ScSpecTypeDef::Vec(v) => format!("Vec<{}>", spec_type(&v.element_type)),
+        ScSpecTypeDef::Map(m) => format!(
+            "Map<{},{}>",
+            spec_type(&m.key_type),
+            spec_type(&m.value_type)
+        ),
+        ScSpecTypeDef::Tuple(t) => format!(
+            "({})",
+            t.value_types
+                .iter()
+                .map(spec_type)
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        ScSpecTypeDef::BytesN(b) => format!("BytesN<{}>", b.n),
+        ScSpecTypeDef::Udt(u) => u.name.to_utf8_string_lossy(),
+    }
+}
+
+/// `Vec<Address>` for `soroban_sdk::Vec<soroban_sdk::Address>`.
+fn canonical_type(ty: &str) -> String {
+    let mut out = String::with_capacity(ty.len());
+    let mut segment = String::new();
+    let mut chars = ty.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            segment.clear();
+        } else if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else {
+            out.push_str(&segment);
+            segment.clear();
+            out.push(c);
+        }
+    }
+    out.push_str(&segment);
+    out
+}
+
+// ── Diffing ───────────────────────────────────────────────────────────────────
+
+/// Kind of a difference between two contract interfaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecChangeKind {
+    FunctionAdded,
+    FunctionRemoved,
+    SignatureChanged,
+    TypeAdded,
+    TypeRemoved,
+    TypeChanged,
+    ErrorEnumAdded,
+    ErrorEnumRemoved,
+    ErrorEnumChanged,
+}
+
+/// One difference between the expected and the actual interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpecChange {
+    pub kind: SpecChangeKind,
+    /// Function, type or error enum the change applies to.
+    pub item: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    pub message: String,
+}
+
+/// Compare the interface a build is expected to have with the one it has.
+/// Functions of all contracts are compared together, as one wasm module
+/// exports one contract.  Changes are ordered functions first, then types,
+/// then error enums, each by name.
+pub fn diff_interfaces(
+    expected: &ContractInterface,
+    actual: &ContractInterface,
+) -> Vec<SpecChange> {
+    let mut changes = Vec::new();
+
+    diff_named(
+        &functions(expected),
+        &functions(actual),
+        "Function",
+        [
+            SpecChangeKind::FunctionAdded,
+            SpecChangeKind::FunctionRemoved,
+            SpecChangeKind::SignatureChanged,
+        ],
+        &mut changes,
+    );
+    diff_named(
+        &expected
+            .types
+            .iter()
+            .map(|t| (t.name.clone(), type_shape(t)))
+            .collect(),
+        &actual
+            .types
+            .iter()
+            .map(|t| (t.name.clone(), type_shape(t)))
+            .collect(),
+        "Type",
+        [
+            SpecChangeKind::TypeAdded,
+            SpecChangeKind::TypeRemoved,
+            SpecChangeKind::TypeChanged,
+        ],
+        &mut changes,
+    );
+    diff_named(
+        &expected
+            .errors
+            .iter()
+            .map(|e| (e.name.clone(), error_shape(e)))
+            .collect(),
+        &actual
+            .errors
+            .iter()
+            .map(|e| (e.name.clone(), error_shape(e)))
+            .collect(),
+        "Error enum",
+        [
+            SpecChangeKind::ErrorEnumAdded,
+            SpecChangeKind::ErrorEnumRemoved,
+            SpecChangeKind::ErrorEnumChanged,
+        ],
+        &mut changes,
+    );
+
+    changes
+}
+
+/// Report items present on one side only, and items whose shape differs.
+fn diff_named(
+    expected: &BTreeMap<String, String>,
+    actual: &BTreeMap<String, String>,
+    noun: &str,
+    [added, removed, changed]: [SpecChangeKind; 3],
+    changes: &mut Vec<SpecChange>,
+) {
+    for (name, shape) in expected {
+        match actual.get(name) {
+            None => changes.push(SpecChange {
+                kind: removed,
+                item: name.clone(),
+                expected: Some(shape.clone()),
+                actual: None,
+                message: format!("{} '{}' is missing from the wasm", noun, name),
+            }),
+            Some(found) if found != shape => changes.push(SpecChange {
+                kind: changed,
+                item: name.clone(),
+                expected: Some(shape.clone()),
+                actual: Some(found.clone()),
+                message: format!(
+                    "{} '{}' differs: expected {}, found {}",
+                    noun, name, shape, found
+                ),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, shape) in actual {
+        if !expected.contains_key(name) {
+            changes.push(SpecChange {
+                kind: added,
+                item: name.clone(),
+                expected: None,
+                actual: Some(shape.clone()),
+                message: format!("{} '{}' is only in the wasm", noun, name),
+            });
+        }
+    }
+}
+
+/// Signatures by function name, e.g. `(to: Address, amount: i128) -> ()`.
+fn functions(interface: &ContractInterface) -> BTreeMap<String, String> {
+    interface
+        .contracts
+        .iter()
+        .flat_map(|c| &c.functions)
+        .map(|f| {
+            let inputs: Vec<String> = f
+                .inputs
+                .iter()
+                .map(|p| format!("{}: {}", p.name, canonical_type(&p.ty)))
+                .collect();
+            let output = f
+                .output
+                .as_deref()
+                .map_or_else(|| "()".to_string(), canonical_type);
+            (
+                f.name.clone(),
+                format!("({}) -> {}", inputs.join(", "), output),
+            )
+        })
+        .collect()
+}
+
+/// `enum { A, B(Address), C = 3 }` or `struct { a: u32, b: i128 }`.
+fn type_shape(ty: &LayoutType) -> String {
+    let mut members: Vec<String> = ty
+        .members
+        .iter()
+        .map(|m| {
+            let types: Vec<String> = m.types.iter().map(|t| canonical_type(t)).collect();
+            match (ty.kind, m.discriminant) {
+                (LayoutTypeKind::Struct, _) => format!("{}: {}", m.name, types.join(", ")),
+                (LayoutTypeKind::Enum, Some(value)) => format!("{} = {}", m.name, value),
+                (LayoutTypeKind::Enum, None) if types.is_empty() => m.name.clone(),
+                (LayoutTypeKind::Enum, None) => format!("{}({})", m.name, types.join(", ")),
+            }
+        })
+        .collect();
+    let keyword = match ty.kind {
+        LayoutTypeKind::Struct => {
+            members.sort();
+            "struct"
+        }
+        LayoutTypeKind::Enum => "enum",
+    };
+    format!("{} {{ {} }}", keyword, members.join(", "))
+}
+
+/// `{ NotFound = 1, Unauthorized = 2 }`.
+fn error_shape(error: &ContractErrorEnum) -> String {
+    let cases: Vec<String> = error
+        .cases
+        .iter()
+        .map(|case| match case.code {
+            Some(code) => format!("{} = {}", case.name, code),
+            None => case.name.clone(),
+        })
+        .collect();
+    format!("{{ {} }}", cases.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_interface::extract_interface;
+    use stellar_xdr::curr::{
+        ScSpecFunctionInputV0, ScSpecFunctionV0, ScSpecTypeResult, ScSpecTypeUdt,
+        ScSpecUdtErrorEnumCaseV0, ScSpecUdtErrorEnumV0, ScSpecUdtStructFieldV0, ScSpecUdtStructV0,
+        ScSymbol, WriteXdr,
+    };
+
+    const SOURCE: &str = r#"
+        #[contracterror]
+        #[repr(u32)]
+        pub enum Error {
+            Unauthorized = 1,
+        }
+
+        #[contracttype]
+        pub struct Config {
+            pub fee: u32,
+            pub admin: soroban_sdk::Address,
+        }
+
+        #[contract]
+        pub struct Vault;
+
+        #[contractimpl]
+        impl Vault {
+            pub fn deposit(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+                from.require_auth();
+                Ok(())
+            }
+
+            pub fn config(env: Env) -> Config {
+                todo!()
+            }
+        }
+    "#;
+
+    fn function(
+        name: &str,
+        inputs: &[(&str, ScSpecTypeDef)],
+        output: Option<ScSpecTypeDef>,
+    ) -> ScSpecEntry {
+        ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+            doc: Default::default(),
+            name: ScSymbol(name.try_into().unwrap()),
+            inputs: inputs
+                .iter()
+                .map(|(name, ty)| ScSpecFunctionInputV0 {
+                    doc: Default::default(),
+                    name: (*name).try_into().unwrap(),
+                    type_: ty.clone(),
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            outputs: output.into_iter().collect::<Vec<_>>().try_into().unwrap(),
+        })
+    }
+
+    fn udt(name: &str) -> ScSpecTypeDef {
+        ScSpecTypeDef::Udt(ScSpecTypeUdt {
+            name: name.try_into().unwrap(),
+        })
+    }
+
+    /// Entries the SDK would embed for `SOURCE`.
+    fn entries() -> Vec<ScSpecEntry> {
+        vec![
+            ScSpecEntry::UdtErrorEnumV0(ScSpecUdtErrorEnumV0 {
+                doc: Default::default(),
+                lib: Default::default(),
+                name: "Error".try_into().unwrap(),
+                cases: vec![ScSpecUdtErrorEnumCaseV0 {
+                    doc: Default::default(),
+                    name: "Unauthorized".try_into().unwrap(),
+                    value: 1,
+                }]
+                .try_into()
+                .unwrap(),
+            }),
+            ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+                doc: Default::default(),
+                lib: Default::default(),
+                name: "Config".try_into().unwrap(),
+                // Sorted by name, unlike the source.
+                fields: [
+                    ("admin", ScSpecTypeDef::Address),
+                    ("fee", ScSpecTypeDef::U32),
+                ]
+                .into_iter()
+                .map(|(name, type_)| ScSpecUdtStructFieldV0 {
+                    doc: Default::default(),
+                    name: name.try_into().unwrap(),
+                    type_,
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            }),
+            function(
+                "deposit",
+                &[
+                    ("from", ScSpecTypeDef::Address),
+                    ("amount", ScSpecTypeDef::I128),
+                ],
+                Some(ScSpecTypeDef::Result(Box::new(ScSpecTypeResult {
+                    ok_type: Box::new(ScSpecTypeDef::Void),
+                    error_type: Box::new(udt("Error")),
+                }))),
+            ),
+            function("config", &[], Some(udt("Config"))),
+        ]
+    }
+
+    /// A minimal wasm module holding `entries` in its spec section.
+    fn wasm(entries: &[ScSpecEntry]) -> Vec<u8> {
+        let mut spec = Vec::new();
+        for entry in entries {
+            spec.extend(entry.to_xdr(Limits::none()).unwrap());
+        }
+        let mut payload = vec![SPEC_SECTION.len() as u8];
+        payload.extend(SPEC_SECTION.as_bytes());
+        payload.extend(spec);
+
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        module.push(0);
+        let mut len = payload.len();
+        loop {
+            let byte = (len & 0x7f) as u8;
+            len >>= 7;
+            if len == 0 {
+                module.push(byte);
+                break;
+            }
+            module.push(byte | 0x80);
+        }
+        module.extend(payload);
+        module
+    }
+
+    #[test]
+    fn decodes_functions_types_and_errors() {
+        let interface = interface_from_wasm(&wasm(&entries())).unwrap();
+        let functions = &interface.contracts[0].functions;
+        assert_eq!(functions[0].name, "deposit");
+        assert_eq!(functions[0].inputs[1].ty, "i128");
+        assert_eq!(functions[0].output.as_deref(), Some("Result<(),Error>"));
+        assert_eq!(functions[1].output.as_deref(), Some("Config"));
+        assert_eq!(interface.types[0].members[0].name, "admin");
+        assert_eq!(interface.errors[0].cases[0].code, Some(1));
+    }
+
+    #[test]
+    fn spec_matching_the_source_has_no_changes() {
+        let source = extract_interface(SOURCE).unwrap();
+        let built = interface_from_wasm(&wasm(&entries())).unwrap();
+        assert_eq!(diff_interfaces(&source, &built), []);
+    }
+
+    #[test]
+    fn reports_function_type_and_error_differences() {
+        let mut changed = entries();
+        // `deposit` takes a u64, `config` is gone, `withdraw` is new, and
+        // `Unauthorized` has a new code.
+        changed[2] = function(
+            "deposit",
+            &[
+                ("from", ScSpecTypeDef::Address),
+                ("amount", ScSpecTypeDef::U64),
+            ],
+            None,
+        );
+        changed[3] = function("withdraw", &[("to", ScSpecTypeDef::Address)], None);
+        if let ScSpecEntry::UdtErrorEnumV0(error) = &mut changed[0] {
+            let mut cases = error.cases.to_vec();
+            cases[0].value = 2;
+            error.cases = cases.try_into().unwrap();
+        }
+
+        let source = extract_interface(SOURCE).unwrap();
+        let built = interface_from_wasm(&wasm(&changed)).unwrap();
+        let changes = diff_interfaces(&source, &built);
+        let kinds: Vec<_> = changes.iter().map(|c| (c.kind, c.item.as_str())).collect();
+        assert_eq!(
+            kinds,
+            [
+                (SpecChangeKind::FunctionRemoved, "config"),
+                (SpecChangeKind::SignatureChanged, "deposit"),
+                (SpecChangeKind::FunctionAdded, "withdraw"),
+                (SpecChangeKind::ErrorEnumChanged, "Error"),
+            ]
+        );
+        assert_eq!(
+            changes[1].expected.as_deref(),
+            Some("(from: Address, amount: i128) -> Result<(),Error>")
+        );
+        assert_eq!(
+            changes[1].actual.as_deref(),
+            Some("(from: Address, amount: u64) -> ()")
+        );
+    }
+
+    #[test]
+    fn missing_section_and_bad_bytes_are_errors() {
+        assert_eq!(
+            interface_from_wasm(b"\0asm\x01\0\0\0"),
+            Err(ContractSpecError::Missing)
+        );
+        assert!(matches!(
+            interface_from_wasm(b"not wasm"),
+            Err(ContractSpecError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn canonical_types_drop_module_paths() {
+        assert_eq!(
+            canonical_type("soroban_sdk::Vec<soroban_sdk::Address>"),
+            "Vec<Address>"
+        );
+        assert_eq!(
+            canonical_type("Map<Symbol,(u32,i128)>"),
+            "Map<Symbol,(u32,i128)>"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::finding_codes::FindingSeverity;
 use crate::rules::{Patch, Rule, RuleViolation, Severity};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -100,6 +101,16 @@ impl YamlRuleWrapper {
     }
 }
 
+impl From<YamlSeverity> for FindingSeverity {
+    fn from(s: YamlSeverity) -> Self {
+        match s {
+            YamlSeverity::Error => FindingSeverity::High,
+            YamlSeverity::Warning => FindingSeverity::Medium,
+            YamlSeverity::Info => FindingSeverity::Info,
+        }
+    }
+}
+
 impl Rule for YamlRuleWrapper {
     #[allow(clippy::misnamed_getters)]
     fn name(&self) -> &str {
@@ -110,6 +121,10 @@ impl Rule for YamlRuleWrapper {
         &self.rule.description
     }
 
+    fn default_severity(&self) -> FindingSeverity {
+        self.rule.severity.clone().into()
+    }
+
     fn check(&self, source: &str) -> Vec<RuleViolation> {
         match &self.rule.matcher {
             AstMatcher::FunctionCall { name, .. } => check_function_calls(source, name, &self.rule),
@@ -0,0 +1,600 @@
+//! Generic lattice-based dataflow over [`crate::cfg::Cfg`].
+//!
+//! An [`Analysis`] supplies a fact type, a direction, a join and a
+//! per-statement transfer function; [`solve`] runs a worklist to a fixed
+//! point and returns the facts at the boundaries of every block, from which
+//! [`DataflowResults::stmt_facts`] recovers the fact at every statement.
+//!
+//! Most analyses are sets of items with a gen/kill transfer function.  Those
+//! implement [`GenKill`] instead and get [`Analysis`] for free.  Three ship
+//! with the crate:
+//!
+//! * [`ReachingDefinitions`] — which assignments may reach a point
+//!   (forward, union).
+//! * [`Liveness`] — which variables may be read later (backward, union).
+//! * [`MustHappen`] — which events happened on *every* path before a point
+//!   ([`MustHappen::before`]) or will happen on every path after it
+//!   ([`MustHappen::after`]) (intersection).
+//!
+//! Blocks not reachable from the analysis' starting point (the entry for
+//! forward analyses, the exit for backward ones) have no facts.  In
+//! particular a must-analysis never has to assume "everything happened" for
+//! a block it has not seen yet: the join only ranges over neighbours that
+//! have been visited, so loops converge to the greatest fixed point.
+//!
+//! Variable names are matched by identifier: shadowing, closures and fields
+//! are not distinguished.  Macro arguments are read when they parse as a
+//! comma-separated list of expressions.
+
+use crate::cfg::{BlockStmt, Cfg};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{BinOp, Expr, Token};
+
+/// Which way facts flow along CFG edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the entry towards the exit.
+    Forward,
+    /// From the exit back towards the entry.
+    Backward,
+}
+
+/// How facts from several neighbours are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Meet {
+    /// A fact holds if it holds on *some* path ("may" analyses).
+    Union,
+    /// A fact holds only if it holds on *every* path ("must" analyses).
+    Intersection,
+}
+
+/// Position of a statement: `blocks[block].stmts[index]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    pub block: usize,
+    pub index: usize,
+}
+
+/// A dataflow problem over a [`Cfg`].
+pub trait Analysis {
+    /// Lattice element describing the program state at one point.
+    type Fact: Clone + Eq;
+
+    fn direction(&self) -> Direction;
+
+    /// Fact at the starting boundary: the entry for forward analyses, the
+    /// exit for backward ones.
+    fn boundary(&self) -> Self::Fact;
+
+    /// Combine `other` into `into` where control flow merges.
+    fn join(&self, into: &mut Self::Fact, other: &Self::Fact);
+
+    /// Apply one statement to `fact`, in the analysis' direction.
+    fn transfer(&self, loc: Location, stmt: &BlockStmt, fact: &mut Self::Fact);
+}
+
+/// A set-valued analysis whose transfer function is `(fact - kill) ∪ gen`.
+pub trait GenKill {
+    type Item: Ord + Clone;
+
+    fn direction(&self) -> Direction;
+
+    fn meet(&self) -> Meet;
+
+    /// Items holding at the starting boundary; empty by default.
+    fn boundary(&self) -> BTreeSet<Self::Item> {
+        BTreeSet::new()
+    }
+
+    /// Fill in what `stmt` generates and kills.
+    fn gen_kill(
+        &self,
+        loc: Location,
+        stmt: &BlockStmt,
+        gen: &mut BTreeSet<Self::Item>,
+        kill: &mut BTreeSet<Self::Item>,
+    );
+}
+
+impl<T: GenKill> Analysis for T {
+    type Fact = BTreeSet<T::Item>;
+
+    fn direction(&self) -> Direction {
+        GenKill::direction(self)
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        GenKill::boundary(self)
+    }
+
+    fn join(&self, into: &mut Self::Fact, other: &Self::Fact) {
+        match self.meet() {
+            Meet::Union => into.extend(other.iter().cloned()),
+            Meet::Intersection => into.retain(|item| other.contains(item)),
+        }
+    }
+
+    fn transfer(&self, loc: Location, stmt: &BlockStmt, fact: &mut Self::Fact) {
+        let mut gen = BTreeSet::new();
+        let mut kill = BTreeSet::new();
+        self.gen_kill(loc, stmt, &mut gen, &mut kill);
+        fact.retain(|item| !kill.contains(item));
+        fact.extend(gen);
+    }
+}
+
+/// Fixed-point facts at the boundaries of every block, in program order.
+#[derive(Debug, Clone)]
+pub struct DataflowResults<F> {
+    direction: Direction,
+    /// Fact before each block's first statement.
+    entry: Vec<Option<F>>,
+    /// Fact after each block's last statement.
+    exit: Vec<Option<F>>,
+}
+
+impl<F: Clone> DataflowResults<F> {
+    /// Fact before the first statement of `block`, if the block was reached.
+    pub fn block_entry(&self, block: usize) -> Option<&F> {
+        self.entry[block].as_ref()
+    }
+
+    /// Fact after the last statement of `block`, if the block was reached.
+    pub fn block_exit(&self, block: usize) -> Option<&F> {
+        self.exit[block].as_ref()
+    }
+
+    /// Facts at every point of `block` in program order: element `i` holds
+    /// before statement `i`, the last element after the final statement.
+    pub fn stmt_facts<A>(&self, cfg: &Cfg, analysis: &A, block: usize) -> Option<Vec<F>>
+    where
+        A: Analysis<Fact = F>,
+    {
+        let stmts = &cfg.blocks[block].stmts;
+        let mut points = Vec::with_capacity(stmts.len() + 1);
+        match self.direction {
+            Direction::Forward => {
+                let mut fact = self.entry[block].clone()?;
+                points.push(fact.clone());
+                for (index, stmt) in stmts.iter().enumerate() {
+                    analysis.transfer(Location { block, index }, stmt, &mut fact);
+                    points.push(fact.clone());
+                }
+            }
+            Direction::Backward => {
+                let mut fact = self.exit[block].clone()?;
+                points.push(fact.clone());
+                for (index, stmt) in stmts.iter().enumerate().rev() {
+                    analysis.transfer(Location { block, index }, stmt, &mut fact);
+                    points.push(fact.clone());
+                }
+                points.reverse();
+            }
+        }
+        Some(points)
+    }
+}
+
+/// Run `analysis` over `cfg` to a fixed point.
+pub fn solve<A: Analysis>(cfg: &Cfg, analysis: &A) -> DataflowResults<A::Fact> {
+    let n = cfg.block_count();
+    let mut predecessors = vec![Vec::new(); n];
+    for (from, succs) in cfg.successors.iter().enumerate() {
+        for &to in succs {
+            predecessors[to].push(from);
+        }
+    }
+
+    let direction = analysis.direction();
+    let (start, upstream, downstream) = match direction {
+        Direction::Forward => (cfg.entry, &predecessors, &cfg.successors),
+        Direction::Backward => (cfg.exit, &cfg.successors, &predecessors),
+    };
+
+    // `input`/`output` are in the analysis' direction: for a backward
+    // analysis `input` is the fact after the block's last statement.
+    let mut input: Vec<Option<A::Fact>> = vec![None; n];
+    let mut output: Vec<Option<A::Fact>> = vec![None; n];
+    let mut queued = vec![false; n];
+    let mut worklist = VecDeque::from([start]);
+    queued[start] = true;
+
+    while let Some(b) = worklist.pop_front() {
+        queued[b] = false;
+
+        let mut fact = (b == start).then(|| analysis.boundary());
+        for &up in &upstream[b] {
+            if let Some(out) = &output[up] {
+                match &mut fact {
+                    Some(fact) => analysis.join(fact, out),
+                    None => fact = Some(out.clone()),
+                }
+            }
+        }
+        let Some(fact) = fact else {
+            continue;
+        };
+
+        let mut out = fact.clone();
+        let stmts = &cfg.blocks[b].stmts;
+        let mut apply = |index: usize| {
+            analysis.transfer(Location { block: b, index }, &stmts[index], &mut out);
+        };
+        match direction {
+            Direction::Forward => (0..stmts.len()).for_each(&mut apply),
+            Direction::Backward => (0..stmts.len()).rev().for_each(&mut apply),
+        }
+        input[b] = Some(fact);
+
+        if output[b].as_ref() != Some(&out) {
+            output[b] = Some(out);
+            for &down in &downstream[b] {
+                if !queued[down] {
+                    queued[down] = true;
+                    worklist.push_back(down);
+                }
+            }
+        }
+    }
+
+    let (entry, exit) = match direction {
+        Direction::Forward => (input, output),
+        Direction::Backward => (output, input),
+    };
+    DataflowResults {
+        direction,
+        entry,
+        exit,
+    }
+}
+
+// ── Shipped analyses ──────────────────────────────────────────────────────────
+
+/// An assignment to `var` at `location`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Definition {
+    pub var: String,
+    pub location: Location,
+}
+
+/// Which definitions may reach each point.  Parameters are not definitions.
+pub struct ReachingDefinitions {
+    by_var: BTreeMap<String, BTreeSet<Definition>>,
+}
+
+impl ReachingDefinitions {
+    pub fn new(cfg: &Cfg) -> Self {
+        let mut by_var: BTreeMap<String, BTreeSet<Definition>> = BTreeMap::new();
+        for block in &cfg.blocks {
+            for (index, stmt) in block.stmts.iter().enumerate() {
+                let location = Location {
+                    block: block.id,
+                    index,
+                };
+                for var in defined_vars(stmt) {
+                    by_var
+                        .entry(var.clone())
+                        .or_default()
+                        .insert(Definition { var, location });
+                }
+            }
+        }
+        Self { by_var }
+    }
+}
+
+impl GenKill for ReachingDefinitions {
+    type Item = Definition;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn meet(&self) -> Meet {
+        Meet::Union
+    }
+
+    fn gen_kill(
+        &self,
+        loc: Location,
+        stmt: &BlockStmt,
+        gen: &mut BTreeSet<Definition>,
+        kill: &mut BTreeSet<Definition>,
+    ) {
+        for var in defined_vars(stmt) {
+            if let Some(defs) = self.by_var.get(&var) {
+                kill.extend(defs.iter().cloned());
+            }
+            gen.insert(Definition { var, location: loc });
+        }
+    }
+}
+
+/// Which variables may still be read after each point.
+pub struct Liveness;
+
+impl GenKill for Liveness {
+    type Item = String;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn meet(&self) -> Meet {
+        Meet::Union
+    }
+
+    fn gen_kill(
+        &self,
+        _loc: Location,
+        stmt: &BlockStmt,
+        gen: &mut BTreeSet<String>,
+        kill: &mut BTreeSet<String>,
+    ) {
+        kill.extend(defined_vars(stmt));
+        gen.extend(used_vars(stmt));
+    }
+}
+
+/// Events that happen on every path before (or after) each point.
+///
+/// `events` names what a statement does, e.g. `["require_auth"]` for a
+/// statement calling `require_auth`.  With [`MustHappen::after`], the fact
+/// before a storage write tells whether every path from the write to the
+/// exit goes on to do something — an `extend_ttl`, say.
+pub struct MustHappen<F> {
+    direction: Direction,
+    events: F,
+}
+
+impl<F: Fn(&BlockStmt) -> Vec<String>> MustHappen<F> {
+    /// Events that happened on every path from the entry to a point.
+    pub fn before(events: F) -> Self {
+        Self {
+            direction: Direction::Forward,
+            events,
+        }
+    }
+
+    /// Events that happen on every path from a point to the exit.
+    pub fn after(events: F) -> Self {
+        Self {
+            direction: Direction::Backward,
+            events,
+        }
+    }
+}
+
+impl<F: Fn(&BlockStmt) -> Vec<String>> GenKill for MustHappen<F> {
+    type Item = String;
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn meet(&self) -> Meet {
+        Meet::Intersection
+    }
+
+    fn gen_kill(
+        &self,
+        _loc: Location,
+        stmt: &BlockStmt,
+        gen: &mut BTreeSet<String>,
+        _kill: &mut BTreeSet<String>,
+    ) {
+        gen.extend((self.events)(stmt));
+    }
+}
+
+// ── Definitions and uses ──────────────────────────────────────────────────────
+
+/// Variables a statement assigns: `let` patterns, `x = ..`, `x += ..`, and
+/// `for`/`match` bindings.
+pub fn defined_vars(stmt: &BlockStmt) -> BTreeSet<String> {
+    let mut vars = BTreeSet::new();
+    match stmt {
+        BlockStmt::Local(local) => collect_pat_vars(&local.pat, &mut vars),
+        BlockStmt::Expr(expr) => {
+            if let Some(target) = assignment_target(expr) {
+                vars.extend(plain_var(target));
+            }
+        }
+        BlockStmt::ForBinding { pat, .. } | BlockStmt::MatchBinding { pat, .. } => {
+            collect_pat_vars(pat, &mut vars)
+        }
+    }
+    vars
+}
+
+/// Variables a statement reads.  The left-hand side of a plain assignment
+/// to a variable is not a read; that of a compound assignment is.
+pub fn used_vars(stmt: &BlockStmt) -> BTreeSet<String> {
+    let mut uses = UseCollector::default();
+    match stmt {
+        BlockStmt::Expr(Expr::Assign(a)) if plain_var(&a.left).is_some() => {
+            uses.visit_expr(&a.right)
+        }
+        // The scrutinee is read where it is evaluated, before the branch.
+        BlockStmt::MatchBinding { .. } => {}
+        _ => stmt.exprs().into_iter().for_each(|e| uses.visit_expr(e)),
+    }
+    uses.vars
+}
+
+fn assignment_target(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Assign(a) => Some(&a.left),
+        Expr::Binary(b) if is_compound_assign(&b.op) => Some(&b.left),
+        _ => None,
+    }
+}
+
+fn is_compound_assign(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+fn plain_var(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(p) if p.qself.is_none() && p.path.segments.len() == 1 => {
+            Some(p.path.segments[0].ident.to_string())
+        }
+        Expr::Paren(p) => plain_var(&p.expr),
+        _ => None,
+    }
+}
+
+fn collect_pat_vars(pat: &syn::Pat, out: &mut BTreeSet<String>) {
+    let mut names = std::collections::HashSet::new();
+    crate::taint_engine::collect_pat_idents(pat, &mut names);
+    out.extend(names);
+}
+
+#[derive(Default)]
+struct UseCollector {
+    vars: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for UseCollector {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr {
+            Expr::Path(_) => self.vars.extend(plain_var(expr)),
+            // `helper(x)` reads `x`, not a variable called `helper`.
+            Expr::Call(call) if plain_var(&call.func).is_some() => {
+                call.args.iter().for_each(|arg| self.visit_expr(arg))
+            }
+            _ => syn::visit::visit_expr(self, expr),
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            args.iter().for_each(|arg| self.visit_expr(arg));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_str;
+
+    fn build(src: &str) -> Cfg {
+        let block: syn::Block = parse_str(&format!("{{ {src} }}")).unwrap();
+        Cfg::build(&block)
+    }
+
+    /// Location of the first statement whose source contains `needle`.
+    fn find(cfg: &Cfg, needle: &str) -> Location {
+        cfg.blocks
+            .iter()
+            .flat_map(|b| {
+                b.stmts
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, s)| (Location { block: b.id, index }, s))
+            })
+            .find(|(_, s)| s.to_source().contains(needle))
+            .map(|(loc, _)| loc)
+            .unwrap_or_else(|| panic!("no statement contains `{needle}`"))
+    }
+
+    /// Fact just before the statement at `loc`.
+    fn before<A: Analysis>(cfg: &Cfg, analysis: &A, loc: Location) -> A::Fact {
+        let results = solve(cfg, analysis);
+        results.stmt_facts(cfg, analysis, loc.block).unwrap()[loc.index].clone()
+    }
+
+    #[test]
+    fn reaching_definitions_merge_at_joins_and_are_killed_by_reassignment() {
+        let cfg = build("let mut x = 1; if c { x = 2; } use_it(x); x = 3; last(x);");
+        let rd = ReachingDefinitions::new(&cfg);
+
+        let at_use: Vec<_> = before(&cfg, &rd, find(&cfg, "use_it"))
+            .into_iter()
+            .map(|d| d.location)
+            .collect();
+        assert_eq!(at_use, vec![find(&cfg, "let mut x"), find(&cfg, "x = 2")]);
+
+        let at_last = before(&cfg, &rd, find(&cfg, "last"));
+        assert_eq!(at_last.len(), 1);
+        assert_eq!(at_last.first().unwrap().location, find(&cfg, "x = 3"));
+    }
+
+    #[test]
+    fn liveness_follows_reads_backwards_through_loops() {
+        let cfg = build("let mut i = 0; let unused = 5; while i < n { i += 1; } done();");
+        let live = before(&cfg, &Liveness, find(&cfg, "let unused"));
+        assert!(live.contains("i"), "i is read by the loop condition");
+        assert!(!live.contains("unused"));
+        assert!(before(&cfg, &Liveness, find(&cfg, "done")).is_empty());
+    }
+
+    #[test]
+    fn liveness_reads_macro_arguments() {
+        let cfg = build("let amount = 5; log!(&env, \"amount\", amount);");
+        let live = before(&cfg, &Liveness, find(&cfg, "log"));
+        assert!(live.contains("amount"));
+    }
+
+    fn calls(method: &'static str) -> impl Fn(&BlockStmt) -> Vec<String> {
+        move |stmt| {
+            if stmt.to_source().contains(&format!(". {} (", method)) {
+                vec![method.to_string()]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    #[test]
+    fn must_happen_before_requires_every_path() {
+        let cfg = build("if c { a.require_auth(); } write(); a.require_auth(); again();");
+        let analysis = MustHappen::before(calls("require_auth"));
+        assert!(before(&cfg, &analysis, find(&cfg, "write")).is_empty());
+        assert!(before(&cfg, &analysis, find(&cfg, "again")).contains("require_auth"));
+    }
+
+    #[test]
+    fn must_happen_after_sees_missing_branch() {
+        let cfg = build(
+            "s.set(&k, &v); if c { s.extend_ttl(&k, 1, 2); } t.set(&k, &v); s.extend_ttl(&k, 1, 2);",
+        );
+        let analysis = MustHappen::after(calls("extend_ttl"));
+        let results = solve(&cfg, &analysis);
+        // Every path after both writes reaches the final extend_ttl.
+        let first = find(&cfg, "s . set");
+        let facts = results.stmt_facts(&cfg, &analysis, first.block).unwrap();
+        assert!(facts[first.index + 1].contains("extend_ttl"));
+
+        let cfg = build("s.set(&k, &v); if c { s.extend_ttl(&k, 1, 2); }");
+        let loc = find(&cfg, "s . set");
+        let facts = solve(&cfg, &analysis)
+            .stmt_facts(&cfg, &analysis, loc.block)
+            .unwrap();
+        assert!(facts[loc.index + 1].is_empty());
+    }
+
+    #[test]
+    fn blocks_unreachable_from_the_start_have_no_facts() {
+        let cfg = build("loop { spin(); } after();");
+        let results = solve(&cfg, &Liveness);
+        assert!(results.block_entry(find(&cfg, "spin").block).is_none());
+        let results = solve(&cfg, &ReachingDefinitions::new(&cfg));
+        assert!(results.block_entry(find(&cfg, "after").block).is_none());
+    }
+}
@@ -1,26 +1,60 @@
 //! Canonical finding codes emitted by Sanctifier analysis passes.
 //!
-//! Each constant (`S000` – `S016`) maps to a single diagnostic category.
-//! Call `all_finding_codes()` to retrieve the full catalogue at runtime.
+//! Each constant (`S000` – `S033`) maps to a single diagnostic category.
+//! Call `all_finding_codes()` to retrieve the full catalogue at runtime, and
+//! `code_for_rule()` to find the code a built-in rule reports under.
 
 use serde::{Deserialize, Serialize};
 
 /// Severity level for findings.
+///
+/// Deserialization also accepts the lowercase names used in `.sanctify.toml`
+/// and the rule-level `"error"` / `"warning"` spellings.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum FindingSeverity {
     /// Critical severity - immediate security risk
+    #[serde(alias = "critical")]
     Critical,
     /// High severity - significant security concern
+    #[serde(alias = "high", alias = "error")]
     High,
     /// Medium severity - potential issue
     #[default]
+    #[serde(alias = "medium", alias = "warning")]
     Medium,
     /// Low severity - minor concern
+    #[serde(alias = "low")]
     Low,
     /// Informational - no immediate risk
+    #[serde(alias = "info")]
     Info,
 }
 
+impl FindingSeverity {
+    /// Position on the severity scale, from `0` (Info) to `4` (Critical).
+    /// Use it to compare severities against a threshold.
+    pub fn rank(self) -> u8 {
+        match self {
+            Self::Critical => 4,
+            Self::High => 3,
+            Self::Medium => 2,
+            Self::Low => 1,
+            Self::Info => 0,
+        }
+    }
+
+    /// Lower-case label used in reports (`"critical"`, `"high"`, …).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::High => "high",
+            Self::Medium => "medium",
+            Self::Low => "low",
+            Self::Info => "info",
+        }
+    }
+}
+
 /// Analysis timed out for a file (see `--timeout`).
 pub const ANALYSIS_TIMEOUT: &str = "S000";
 /// Missing authentication guard in a privileged function.
@@ -85,6 +119,10 @@ pub const TIMESTAMP_RANDOMNESS: &str = "S029";
 pub const REQUIRE_AUTH_FOR_ARGS: &str = "S030";
 /// Loop bound or iteration count derives from an unbounded user-controlled parameter, risking out-of-gas reverts.
 pub const GAS_EXHAUSTION_RISK: &str = "S031";
+/// Inline `sanctifier-ignore` comment or `#[allow(sanctifier::…)]` attribute that suppresses nothing.
+pub const UNUSED_SUPPRESSION: &str = "S032";
+/// Entry point whose storage footprint, written bytes or events can exceed Soroban's per-transaction resource limits.
+pub const RESOURCE_LIMIT_RISK: &str = "S033";
 
 /// A single finding-code entry with machine-readable code, category, and
 /// human-readable description.
@@ -95,6 +133,8 @@ pub struct FindingCode {
     pub code: &'static str,
     /// Broad category (e.g. `"authentication"`).
     pub category: &'static str,
+    /// Closest CWE weakness id, if one applies (e.g. `862` for CWE-862).
+    pub cwe: Option<u32>,
     /// One-line description of the finding.
     pub description: &'static str,
     /// Short human-readable title.
@@ -110,9 +150,19 @@ pub struct FindingCode {
 impl FindingCode {
     /// Format a short, self-contained diagnostic message suitable for terminal or log output.
     pub fn format_diagnostic(&self) -> String {
+        let cwe = self
+            .cwe
+            .map(|id| format!(" CWE-{}", id))
+            .unwrap_or_default();
         format!(
-            "[{}] {} ({:?})\n  {}\n  Remediation: {}\n  More info: {}",
-            self.code, self.title, self.severity, self.description, self.remediation, self.doc_url,
+            "[{}] {} ({:?}){}\n  {}\n  Remediation: {}\n  More info: {}",
+            self.code,
+            self.title,
+            self.severity,
+            cwe,
+            self.description,
+            self.remediation,
+            self.doc_url,
         )
     }
 }
@@ -123,12 +173,53 @@ pub fn lookup_finding_code(code: &str) -> Option<FindingCode> {
     all_finding_codes().into_iter().find(|c| c.code == code)
 }
 
+/// Map a built-in rule name (as returned by `Rule::name`) to its finding code.
+/// Returns `None` for rule names this version does not know about.
+///
+/// This is the single source of truth for the rule → code mapping; the
+/// default [`Rule::code`](crate::rules::Rule::code) consults it.
+pub fn code_for_rule(rule_name: &str) -> Option<&'static str> {
+    let code = match rule_name {
+        "auth_gap" => AUTH_GAP,
+        "panic_detection" => PANIC_USAGE,
+        "arithmetic_overflow" => ARITHMETIC_OVERFLOW,
+        "ledger_size" => LEDGER_SIZE_RISK,
+        "unused_variable"
+        | "variable_shadowing"
+        | "storage_update_state_check"
+        | "xdr_raw_construction" => UNSAFE_PATTERN,
+        "unhandled_result" => UNHANDLED_RESULT,
+        "reentrancy" => REENTRANCY,
+        "truncation_bounds" => TRUNCATION_BOUNDS,
+        "unsafe_prng" => UNSAFE_PRNG,
+        "unchecked_external_call" => UNCHECKED_EXTERNAL_CALL,
+        "missing_state_event" | "shadow_storage" => MISSING_STATE_EVENT,
+        "instance_storage_misuse" => INSTANCE_STORAGE_MISUSE,
+        "raw_invoke_contract" => RAW_INVOKE_CONTRACT,
+        "shallow_test" => SHALLOW_TEST,
+        "transfer_from_no_allowance" => TRANSFER_FROM_NO_ALLOWANCE,
+        "missing_ttl_bump" => MISSING_TTL_BUMP,
+        "taint_propagation" => TAINT_PROPAGATION,
+        "static_reentrancy" => STATIC_REENTRANCY,
+        "deprecated_sdk_usage" => DEPRECATED_SDK_USAGE,
+        "timestamp_randomness" => TIMESTAMP_RANDOMNESS,
+        "require_auth_for_args" => REQUIRE_AUTH_FOR_ARGS,
+        "gas_exhaustion_risk" => GAS_EXHAUSTION_RISK,
+        "resource_limits" => RESOURCE_LIMIT_RISK,
+        "smt_invariant" => SMT_INVARIANT_VIOLATION,
+        crate::suppression::UNUSED_SUPPRESSION_RULE => UNUSED_SUPPRESSION,
+        _ => return None,
+    };
+    Some(code)
+}
+
 /// Returns every finding code known to this version of Sanctifier.
 pub fn all_finding_codes() -> Vec<FindingCode> {
     vec![
         FindingCode {
             code: ANALYSIS_TIMEOUT,
             category: "timeout",
+            cwe: None,
             description: "Analysis of a file was aborted because it exceeded the per-file timeout",
             title: "Analysis Timeout",
             severity: FindingSeverity::Info,
@@ -138,6 +229,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: AUTH_GAP,
             category: "authentication",
+            cwe: Some(862),
             description: "Missing authentication guard in a privileged state-changing or external-call function",
             title: "Missing Authorization Guard",
             severity: FindingSeverity::Critical,
@@ -147,6 +239,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: PANIC_USAGE,
             category: "panic_handling",
+            cwe: Some(248),
             description: "panic!/unwrap/expect usage that may cause runtime aborts",
             title: "Panic Usage",
             severity: FindingSeverity::Medium,
@@ -156,6 +249,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: ARITHMETIC_OVERFLOW,
             category: "arithmetic",
+            cwe: Some(190),
             description: "Unchecked arithmetic operation with overflow/underflow risk",
             title: "Unchecked Arithmetic",
             severity: FindingSeverity::Medium,
@@ -165,6 +259,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: LEDGER_SIZE_RISK,
             category: "storage_limits",
+            cwe: Some(400),
             description: "Ledger entry size is exceeding or approaching configured threshold",
             title: "Ledger Entry Size Risk",
             severity: FindingSeverity::Medium,
@@ -174,6 +269,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: STORAGE_COLLISION,
             category: "storage_keys",
+            cwe: Some(694),
             description: "Potential storage key collision across contract data paths",
             title: "Storage Key Collision",
             severity: FindingSeverity::Medium,
@@ -183,6 +279,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: UNSAFE_PATTERN,
             category: "unsafe_patterns",
+            cwe: Some(676),
             description: "Potentially unsafe language/runtime pattern was detected",
             title: "Unsafe Pattern",
             severity: FindingSeverity::Medium,
@@ -192,6 +289,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: CUSTOM_RULE_MATCH,
             category: "custom_rule",
+            cwe: None,
             description: "User-defined rule matched contract source",
             title: "Custom Rule Match",
             severity: FindingSeverity::Info,
@@ -201,6 +299,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: EVENT_INCONSISTENCY,
             category: "events",
+            cwe: Some(778),
             description: "Inconsistent topic counts or sub-optimal gas patterns in events",
             title: "Event Inconsistency",
             severity: FindingSeverity::Low,
@@ -210,6 +309,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: UNHANDLED_RESULT,
             category: "logic",
+            cwe: Some(252),
             description: "A function call returns a Result that is not consumed or handled",
             title: "Unhandled Result",
             severity: FindingSeverity::Medium,
@@ -219,6 +319,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: UPGRADE_RISK,
             category: "upgrades",
+            cwe: Some(284),
             description: "Potential security risk in contract upgrade or admin mechanisms",
             title: "Upgrade Risk",
             severity: FindingSeverity::Medium,
@@ -228,6 +329,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: SMT_INVARIANT_VIOLATION,
             category: "formal_verification",
+            cwe: Some(682),
             description: "Formal verification (Z3) proved a mathematical violation of an invariant",
             title: "SMT Invariant Violation",
             severity: FindingSeverity::High,
@@ -237,6 +339,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: SEP41_INTERFACE_DEVIATION,
             category: "token_interface",
+            cwe: None,
             description: "SEP-41 token interface compatibility or authorization deviation",
             title: "SEP-41 Interface Deviation",
             severity: FindingSeverity::Medium,
@@ -246,6 +349,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: REENTRANCY,
             category: "reentrancy",
+            cwe: Some(841),
             description: "State mutation before external call without a reentrancy guard",
             title: "Reentrancy",
             severity: FindingSeverity::Critical,
@@ -255,6 +359,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: ADMIN_TRUST_RISK,
             category: "centralization",
+            cwe: Some(269),
             description: "Excessive administrative control or insecure credential management",
             title: "Admin Trust Risk",
             severity: FindingSeverity::Medium,
@@ -264,6 +369,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: HARDCODED_SECRET_KEY,
             category: "secrets",
+            cwe: Some(798),
             description: "Hardcoded secret key or sensitive mnemonic in contract source",
             title: "Hardcoded Secret Key",
             severity: FindingSeverity::Critical,
@@ -273,6 +379,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: TRUNCATION_BOUNDS,
             category: "truncation_bounds",
+            cwe: Some(197),
             description: "Integer truncation cast or unchecked array/slice indexing",
             title: "Truncation / Bounds Risk",
             severity: FindingSeverity::Medium,
@@ -282,6 +389,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: CONTRACTIMPORT_MISMATCH,
             category: "integration",
+            cwe: None,
             description: "contractimport signature does not match actual implemented workspace source",
             title: "ContractImport Mismatch",
             severity: FindingSeverity::High,
@@ -291,6 +399,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: UNSAFE_PRNG,
             category: "randomness",
+            cwe: Some(338),
             description: "Use of PRNG without proper seeding in state-critical code that could lead to predictable randomness",
             title: "Unsafe PRNG Usage",
             severity: FindingSeverity::Medium,
@@ -300,6 +409,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: UNCHECKED_EXTERNAL_CALL,
             category: "external_calls",
+            cwe: Some(252),
             description: "Result from cross-contract call is not checked, which may leave state inconsistent",
             title: "Unchecked External Call",
             severity: FindingSeverity::Medium,
@@ -309,6 +419,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: MISSING_STATE_EVENT,
             category: "events",
+            cwe: Some(778),
             description: "Privileged state change (admin, pause, upgrade) without event emission breaks off-chain data integrity",
             title: "Missing State Event",
             severity: FindingSeverity::Medium,
@@ -318,6 +429,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: INSTANCE_STORAGE_MISUSE,
             category: "storage_type",
+            cwe: Some(400),
             description: "Per-user or large dataset stored in Instance storage instead of Persistent, causing ledger entry bloat",
             title: "Instance Storage Misuse",
             severity: FindingSeverity::Medium,
@@ -327,6 +439,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: RAW_INVOKE_CONTRACT,
             category: "error_handling",
+            cwe: Some(755),
             description: "Cross-contract call via `invoke_contract` panics on callee failure; use `try_invoke_contract` with explicit Result handling",
             title: "Raw Invoke Contract",
             severity: FindingSeverity::Medium,
@@ -336,6 +449,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: SHALLOW_TEST,
             category: "test_quality",
+            cwe: None,
             description: "#[test] function never references a ContractClient, bypassing serialization and auth paths exercised by the Soroban host-function boundary",
             title: "Shallow Test",
             severity: FindingSeverity::Low,
@@ -345,6 +459,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: TRANSFER_FROM_NO_ALLOWANCE,
             category: "token_safety",
+            cwe: Some(863),
             description: "transfer_from-style function moves 'from' balance without checking or decrementing the spender's allowance, allowing any caller to drain any account",
             title: "Transfer-From Without Allowance",
             severity: FindingSeverity::Critical,
@@ -354,6 +469,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: MISSING_TTL_BUMP,
             category: "storage_ttl",
+            cwe: Some(672),
             description: "Persistent or Temporary storage entry written without a corresponding extend_ttl call — entry may silently expire",
             title: "Missing TTL Bump",
             severity: FindingSeverity::Medium,
@@ -363,6 +479,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: TAINT_PROPAGATION,
             category: "taint_analysis",
+            cwe: Some(20),
             description: "User-controlled data (tainted source) reaches a sensitive sink without sanitisation, including through tuple/struct destructures",
             title: "Taint Propagation",
             severity: FindingSeverity::High,
@@ -372,6 +489,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: STATIC_REENTRANCY,
             category: "reentrancy",
+            cwe: Some(841),
             description: "External contract call precedes a storage mutation without a reentrancy guard — classic checks-effects-interactions violation",
             title: "Static Reentrancy",
             severity: FindingSeverity::Medium,
@@ -381,6 +499,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: DEPRECATED_SDK_USAGE,
             category: "sdk_migration",
+            cwe: Some(477),
             description: "Usage of a storage or deployment API removed or renamed in Soroban SDK v22 — bump(), RawVal, and deployer().deploy() must be migrated",
             title: "Deprecated SDK Usage",
             severity: FindingSeverity::High,
@@ -390,6 +509,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: TIMESTAMP_RANDOMNESS,
             category: "randomness",
+            cwe: Some(330),
             description: "Block timestamp (env.ledger().timestamp()) used as entropy for randomness — validators can manipulate timestamps within bounds",
             title: "Timestamp Used as Randomness",
             severity: FindingSeverity::High,
@@ -399,6 +519,7 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: REQUIRE_AUTH_FOR_ARGS,
             category: "authentication",
+            cwe: Some(294),
             description: "Function with multiple Address parameters uses require_auth instead of require_auth_for_args, enabling replay/scope-confusion attacks on multi-arg admin operations",
             title: "Missing require_auth_for_args",
             severity: FindingSeverity::High,
@@ -408,12 +529,33 @@ pub fn all_finding_codes() -> Vec<FindingCode> {
         FindingCode {
             code: GAS_EXHAUSTION_RISK,
             category: "gas_limits",
+            cwe: Some(834),
             description: "Loop bound or iteration count derives from an unbounded user-controlled parameter, which can exhaust the transaction's gas budget and cause an out-of-gas revert",
             title: "Gas Exhaustion Risk",
             severity: FindingSeverity::Medium,
             remediation: "Cap the iteration count with a fixed maximum (e.g. .take(MAX_ITEMS) or an explicit length check before the loop) so the gas cost cannot scale unbounded with caller-supplied input",
             doc_url: "https://github.com/HyperSafeD/Sanctifier/blob/main/docs/rules/gas-exhaustion-risk.md",
         },
+        FindingCode {
+            code: UNUSED_SUPPRESSION,
+            category: "suppression",
+            cwe: None,
+            description: "Inline sanctifier-ignore comment or #[allow(sanctifier::...)] attribute does not match any finding",
+            title: "Unused Suppression",
+            severity: FindingSeverity::Low,
+            remediation: "Remove the stale suppression so it cannot hide a future finding at the same location",
+            doc_url: "https://github.com/HyperSafeD/Sanctifier/blob/main/docs/error-codes.md",
+        },
+        FindingCode {
+            code: RESOURCE_LIMIT_RISK,
+            category: "resource_limits",
+            cwe: Some(770),
+            description: "An entry point can read or write more ledger entries or bytes, or emit more event bytes, than the network allows in one transaction, so the call is rejected however much fee is offered",
+            title: "Resource Limit Risk",
+            severity: FindingSeverity::Medium,
+            remediation: "Bound the number of distinct storage keys, callee contracts and events one call can touch, e.g. by capping loops with a constant maximum or paginating batch operations across transactions",
+            doc_url: "https://github.com/HyperSafeD/Sanctifier/blob/main/docs/rules/resource-limits.md",
+        },
     ]
 }
 
@@ -489,5 +631,41 @@ mod tests {
         assert!(codes.iter().any(|c| c.code == TIMESTAMP_RANDOMNESS));
         assert!(codes.iter().any(|c| c.code == REQUIRE_AUTH_FOR_ARGS));
         assert!(codes.iter().any(|c| c.code == GAS_EXHAUSTION_RISK));
+        assert!(codes.iter().any(|c| c.code == UNUSED_SUPPRESSION));
+        assert!(codes.iter().any(|c| c.code == RESOURCE_LIMIT_RISK));
+    }
+
+    #[test]
+    fn every_rule_code_is_in_the_catalogue() {
+        let registry = crate::rules::RuleRegistry::with_default_rules();
+        for name in registry.available_rules() {
+            let code = code_for_rule(name).expect("mapped above");
+            assert!(
+                lookup_finding_code(code).is_some(),
+                "rule '{}' maps to unknown code {}",
+                name,
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn severity_rank_orders_the_scale() {
+        assert!(FindingSeverity::Critical.rank() > FindingSeverity::High.rank());
+        assert!(FindingSeverity::High.rank() > FindingSeverity::Medium.rank());
+        assert!(FindingSeverity::Medium.rank() > FindingSeverity::Low.rank());
+        assert!(FindingSeverity::Low.rank() > FindingSeverity::Info.rank());
+    }
+
+    #[test]
+    fn every_default_rule_has_a_finding_code() {
+        let registry = crate::rules::RuleRegistry::with_default_rules();
+        for name in registry.available_rules() {
+            assert!(
+                code_for_rule(name).is_some(),
+                "rule '{}' has no finding code",
+                name
+            );
+        }
     }
 }
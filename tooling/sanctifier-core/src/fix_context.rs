@@ -0,0 +1,390 @@
+//! Context for type-aware auto-fixes.
+//!
+//! Rules that rewrite a panicking operation into error handling need to know
+//! how the enclosing function may fail.  A function returning `Result<_, E>`
+//! for a `#[contracterror]` enum `E` propagates `E::Variant` with `?`; any
+//! other function with an `Env` parameter aborts with `panic_with_error!`.
+//! [`FixContext`] collects the contract's error enums, including those
+//! declared in other files of the crate, and slices the source text that
+//! rewrites are spliced from, so patches keep the author's formatting.
+
+use crate::storage_layout::{layout_type, LayoutType};
+use syn::{File, FnArg, GenericArgument, Pat, PathArguments, ReturnType, Signature, Type};
+
+/// Error variants an overflowing `checked_*` call is reported as, most
+/// specific first; [`FnFailure::variant`] picks the first one declared.
+pub const OVERFLOW_ERRORS: &[&str] = &[
+    "Overflow",
+    "ArithmeticOverflow",
+    "MathOverflow",
+    "ArithmeticError",
+    "MathError",
+];
+pub const UNDERFLOW_ERRORS: &[&str] = &[
+    "Underflow",
+    "ArithmeticUnderflow",
+    "Overflow",
+    "ArithmeticOverflow",
+    "MathOverflow",
+    "ArithmeticError",
+    "MathError",
+];
+pub const DIVISION_ERRORS: &[&str] = &[
+    "DivisionByZero",
+    "DivideByZero",
+    "DivByZero",
+    "ZeroDivision",
+    "Overflow",
+    "ArithmeticError",
+    "MathError",
+];
+/// Error variants for a value that is absent, e.g. an unset storage key.
+pub const MISSING_ERRORS: &[&str] = &[
+    "NotFound",
+    "KeyNotFound",
+    "NotInitialized",
+    "Missing",
+    "NoValue",
+    "DoesNotExist",
+];
+/// Error variants for a failed conversion or fallible call.
+pub const CONVERSION_ERRORS: &[&str] = &[
+    "ConversionError",
+    "ConversionFailed",
+    "InvalidInput",
+    "InvalidArgument",
+    "InvalidValue",
+];
+
+/// Byte offsets of line starts, for converting `syn` line/column positions
+/// (1-based lines, 0-based char columns) into offsets into the source.
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// Byte offset of `column` on `line`; the end of the line's text when
+    /// `column` is one past its last character.  `None` when the position
+    /// lies outside the source.
+    pub fn offset(&self, source: &str, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(source.len(), |next| next - 1);
+        let text = &source[start..end];
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        match text.char_indices().nth(column) {
+            Some((i, _)) => Some(start + i),
+            None if column == text.chars().count() => Some(start + text.len()),
+            None => None,
+        }
+    }
+}
+
+/// Source text and contract error enums of one file.
+pub struct FixContext<'a> {
+    source: &'a str,
+    lines: LineIndex,
+    errors: Vec<LayoutType>,
+    /// `panic_with_error!` as it can be named in this file.
+    panic_macro: &'static str,
+}
+
+/// How a function reports an error `E::Variant`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureMode {
+    /// `return Err(..)` through `?`.
+    Propagate,
+    /// `panic_with_error!(env, ..)` with the function's `Env` parameter.
+    Abort { env: String },
+}
+
+/// How one function fails, and the error enums it can fail with.
+pub struct FnFailure<'c> {
+    pub mode: FailureMode,
+    errors: Vec<&'c LayoutType>,
+    panic_macro: &'static str,
+}
+
+/// The `#[contracterror]` enums declared in `source`; none when it does not
+/// parse.  Collect these over every file of a crate for
+/// [`FixContext::with_crate_errors`].
+pub fn contract_errors(source: &str) -> Vec<LayoutType> {
+    syn::parse_str::<File>(source)
+        .map(|file| file_errors(&file))
+        .unwrap_or_default()
+}
+
+fn file_errors(file: &File) -> Vec<LayoutType> {
+    file.items
+        .iter()
+        .filter_map(|item| layout_type(item, "contracterror"))
+        .collect()
+}
+
+/// Whether `name` occurs in `source` as a whole identifier.
+fn mentions(source: &str, name: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    source.match_indices(name).any(|(i, _)| {
+        !source[..i].ends_with(is_ident) && !source[i + name.len()..].starts_with(is_ident)
+    })
+}
+
+impl<'a> FixContext<'a> {
+    pub fn new(source: &'a str, file: &File) -> Self {
+        Self::with_crate_errors(source, file, &[])
+    }
+
+    /// Like [`FixContext::new`], also seeing `crate_errors`, the error enums
+    /// declared across the crate.  An enum from another file is only used
+    /// where `source` names it, through an import or a function's return
+    /// type, so fixes never refer to a type that is out of scope.  An enum
+    /// declared in `file` itself shadows one of the same name.
+    pub fn with_crate_errors(source: &'a str, file: &File, crate_errors: &[LayoutType]) -> Self {
+        let mut errors = file_errors(file);
+        for error in crate_errors {
+            if mentions(source, &error.name) && !errors.iter().any(|e| e.name == error.name) {
+                errors.push(error.clone());
+            }
+        }
+        // Without an import the macro has to be named by its full path.
+        let panic_macro = if source.contains("panic_with_error") {
+            "panic_with_error!"
+        } else {
+            "soroban_sdk::panic_with_error!"
+        };
+        Self {
+            source,
+            lines: LineIndex::new(source),
+            errors,
+            panic_macro,
+        }
+    }
+
+    /// Source text covered by `span`.
+    pub fn text(&self, span: proc_macro2::Span) -> Option<&'a str> {
+        let (start, end) = (span.start(), span.end());
+        let from = self.lines.offset(self.source, start.line, start.column)?;
+        let to = self.lines.offset(self.source, end.line, end.column)?;
+        self.source.get(from..to)
+    }
+
+    /// Leading whitespace of `line`.
+    pub fn indent(&self, line: usize) -> &'a str {
+        let Some(start) = self.lines.offset(self.source, line, 0) else {
+            return "";
+        };
+        let rest = &self.source[start..];
+        let width = rest
+            .find(|c: char| !c.is_whitespace() || c == '\n')
+            .unwrap_or(rest.len());
+        &rest[..width]
+    }
+
+    /// How the function with signature `sig` can fail with a contract error:
+    /// by propagation when it returns `Result<_, E>` for an error enum `E`,
+    /// otherwise by aborting through its `Env` parameter.  `None` when the
+    /// contract declares no error enum or the function has neither.
+    pub fn failure(&self, sig: &Signature) -> Option<FnFailure<'_>> {
+        if let Some(error) = result_error_type(sig) {
+            if let Some(e) = self.errors.iter().find(|e| e.name == error) {
+                return Some(FnFailure {
+                    mode: FailureMode::Propagate,
+                    errors: vec![e],
+                    panic_macro: self.panic_macro,
+                });
+            }
+        }
+        if self.errors.is_empty() {
+            return None;
+        }
+        Some(FnFailure {
+            mode: FailureMode::Abort {
+                env: env_param(sig)?,
+            },
+            errors: self.errors.iter().collect(),
+            panic_macro: self.panic_macro,
+        })
+    }
+}
+
+impl FnFailure<'_> {
+    /// `Enum::Variant` for the first of `candidates` an error enum declares.
+    pub fn variant(&self, candidates: &[&str]) -> Option<String> {
+        candidates.iter().find_map(|candidate| {
+            self.errors
+                .iter()
+                .find(|e| e.members.iter().any(|m| m.name == *candidate))
+                .map(|e| format!("{}::{}", e.name, candidate))
+        })
+    }
+
+    /// `option` with its `None` case turned into `error`.
+    pub fn on_none(&self, option: &str, error: &str) -> String {
+        match &self.mode {
+            FailureMode::Propagate => format!("{}.ok_or({})?", option, error),
+            FailureMode::Abort { env } => format!(
+                "{}.unwrap_or_else(|| {}({}, {}))",
+                option, self.panic_macro, env, error
+            ),
+        }
+    }
+
+    /// `result` with its `Err` case turned into `error`.
+    pub fn on_err(&self, result: &str, error: &str) -> String {
+        match &self.mode {
+            FailureMode::Propagate => format!("{}.map_err(|_| {})?", result, error),
+            FailureMode::Abort { env } => format!(
+                "{}.unwrap_or_else(|_| {}({}, {}))",
+                result, self.panic_macro, env, error
+            ),
+        }
+    }
+}
+
+/// `E` of a `-> Result<T, E>` return type.
+fn result_error_type(sig: &Signature) -> Option<String> {
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return None;
+    };
+    let Type::Path(path) = ty.as_ref() else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    if last.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    match args.args.iter().nth(1)? {
+        GenericArgument::Type(Type::Path(error)) => {
+            Some(error.path.segments.last()?.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Name of the `Env` / `&Env` parameter.
+fn env_param(sig: &Signature) -> Option<String> {
+    sig.inputs.iter().find_map(|input| {
+        let FnArg::Typed(arg) = input else {
+            return None;
+        };
+        let ty = match arg.ty.as_ref() {
+            Type::Reference(r) => r.elem.as_ref(),
+            ty => ty,
+        };
+        let is_env =
+            matches!(ty, Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Env"));
+        match arg.pat.as_ref() {
+            Pat::Ident(ident) if is_env => Some(ident.ident.to_string()),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+#[contracterror]
+pub enum Error {
+    NotFound = 1,
+    Overflow = 2,
+}
+
+impl Token {
+    pub fn balance(env: Env, id: Address) -> Result<i128, Error> {
+        read(&env, id)
+    }
+
+    pub fn mint(e: &Env, amount: i128) {
+        write(e, amount);
+    }
+
+    pub fn name() -> String {
+        String::new()
+    }
+}
+"#;
+
+    fn signatures(file: &File) -> Vec<&Signature> {
+        file.items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Impl(i) => Some(i),
+                _ => None,
+            })
+            .flat_map(|i| &i.items)
+            .filter_map(|item| match item {
+                syn::ImplItem::Fn(f) => Some(&f.sig),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn result_functions_propagate_and_others_abort_through_env() {
+        let file = syn::parse_str::<File>(SOURCE).unwrap();
+        let context = FixContext::new(SOURCE, &file);
+        let sigs = signatures(&file);
+
+        let balance = context.failure(sigs[0]).unwrap();
+        assert_eq!(balance.mode, FailureMode::Propagate);
+        let error = balance.variant(&["Missing", "NotFound"]).unwrap();
+        assert_eq!(error, "Error::NotFound");
+        assert_eq!(balance.on_none("x", &error), "x.ok_or(Error::NotFound)?");
+
+        let mint = context.failure(sigs[1]).unwrap();
+        assert_eq!(
+            mint.on_err("x", "Error::Overflow"),
+            "x.unwrap_or_else(|_| soroban_sdk::panic_with_error!(e, Error::Overflow))"
+        );
+        assert!(mint.variant(&["Underflow"]).is_none());
+
+        assert!(context.failure(sigs[2]).is_none());
+    }
+
+    #[test]
+    fn error_enums_of_other_files_are_used_where_named() {
+        let crate_errors = contract_errors(SOURCE);
+        let uses =
+            "use crate::errors::Error;\nimpl Token { pub fn mint(e: &Env, amount: i128) {} }";
+        let file = syn::parse_str::<File>(uses).unwrap();
+        let context = FixContext::with_crate_errors(uses, &file, &crate_errors);
+        let mint = context.failure(signatures(&file)[0]).unwrap();
+        assert_eq!(mint.variant(&["Overflow"]).unwrap(), "Error::Overflow");
+
+        let unnamed = "impl Token { pub fn mint(e: &Env, amount: i128) {} }";
+        let file = syn::parse_str::<File>(unnamed).unwrap();
+        let context = FixContext::with_crate_errors(unnamed, &file, &crate_errors);
+        assert!(context.failure(signatures(&file)[0]).is_none());
+
+        assert!(contract_errors("not rust {").is_empty());
+    }
+
+    #[test]
+    fn line_index_slices_by_char_columns() {
+        let source = "let é = a + b;\r\nlet c = d;";
+        let index = LineIndex::new(source);
+        let start = index.offset(source, 1, 8).unwrap();
+        let end = index.offset(source, 1, 13).unwrap();
+        assert_eq!(&source[start..end], "a + b");
+        assert_eq!(
+            index.offset(source, 1, 14),
+            Some(source.find('\r').unwrap())
+        );
+        assert_eq!(index.offset(source, 2, 10), Some(source.len()));
+        assert_eq!(index.offset(source, 3, 0), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! Gas report rendering helpers.
 
 use crate::gas_estimator::GasEstimationReport;
+use crate::wasm_cost::WasmCostReport;
 use serde::Serialize;
 use syn::spanned::Spanned;
 use syn::visit::{self, Visit};
@@ -12,6 +13,9 @@ pub struct GasReport {
     pub functions: Vec<GasEstimationReport>,
     /// Total estimated instruction count across all functions.
     pub total: usize,
+    /// Bytecode-level estimates, when a compiled module was supplied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<WasmCostReport>,
 }
 
 impl GasReport {
@@ -22,7 +26,17 @@ impl GasReport {
             .map(|function| function.estimated_instructions)
             .sum();
 
-        Self { functions, total }
+        Self {
+            functions,
+            total,
+            wasm: None,
+        }
+    }
+
+    /// Attach the bytecode-level estimates of the compiled contract.
+    pub fn with_wasm(mut self, wasm: WasmCostReport) -> Self {
+        self.wasm = Some(wasm);
+        self
     }
 }
 
@@ -51,6 +65,62 @@ pub fn render_text_report(report: &GasReport, warnings: &[String]) -> String {
     }
 
     out.push_str(&format!("Total                    | {}\n", report.total));
+
+    if let Some(wasm) = &report.wasm {
+        out.push_str(&render_wasm_table(report, wasm));
+    }
+    out
+}
+
+/// Per-export bytecode estimates, next to the source-level estimate of the
+/// function with the same name.
+fn render_wasm_table(report: &GasReport, wasm: &WasmCostReport) -> String {
+    let mut out = format!(
+        "\nWasm module: {} bytes, {} bytes of linear memory\n",
+        wasm.module_bytes, wasm.memory_bytes
+    );
+    out.push_str(
+        "Export                   | Source estimate | Wasm instructions | CPU instructions | Memory bytes | Ledger read bytes | Ledger write bytes\n",
+    );
+    out.push_str(
+        "-------------------------|-----------------|-------------------|------------------|--------------|-------------------|-------------------\n",
+    );
+
+    for export in &wasm.exports {
+        let source = report
+            .functions
+            .iter()
+            .find(|function| function.function_name == export.name)
+            .map_or_else(
+                || "-".to_string(),
+                |function| function.estimated_instructions.to_string(),
+            );
+        out.push_str(&format!(
+            "{:<25} | {:<15} | {:<17} | {:<16} | {:<12} | {:<17} | {}\n",
+            export.name,
+            source,
+            export.instructions,
+            export.estimate.cpu_instructions,
+            export.estimate.memory_bytes,
+            export.estimate.ledger_read_bytes,
+            export.estimate.ledger_write_bytes,
+        ));
+        if !export.host_calls.is_empty() {
+            let calls: Vec<String> = export
+                .host_calls
+                .iter()
+                .map(|(name, count)| format!("{name} x{count}"))
+                .collect();
+            out.push_str(&format!("  host calls: {}\n", calls.join(", ")));
+        }
+    }
+
+    if !wasm.unknown_imports.is_empty() {
+        out.push_str(&format!(
+            "[WARN] imports not in the Soroban host interface: {}\n",
+            wasm.unknown_imports.join(", ")
+        ));
+    }
     out
 }
 
@@ -0,0 +1,514 @@
+//! Inter-procedural summaries over the functions of a file or crate.
+//!
+//! [`crate::taint_engine`] and the `auth_gap` rule look at one function body
+//! at a time.  Contracts routinely split an entry point across private
+//! helpers, though: the entry point hands a user argument to a helper that
+//! does `env.storage().persistent().set(..)`, or delegates the
+//! `require_auth` check to an `ensure_admin(&env)` helper.  A single-body
+//! analysis loses the flow at the call in both directions.
+//!
+//! [`CallSummaries::from_files`] builds a call graph over every free function
+//! and `impl` method in the files of a crate and computes a
+//! [`FunctionSummary`] for each,
+//! iterating to a fixed point so call chains of any depth (and recursion)
+//! converge.  The body analyses then consult the summary of a callee instead
+//! of stopping at the call.
+//!
+//! Calls are resolved by path: `helper(..)` and `module::helper(..)` to a
+//! free function, `Self::helper(..)` to a method of the enclosing `impl`, and
+//! `Type::helper(..)` to a method of `impl Type`.  Method-call syntax
+//! (`value.helper()`) and trait dispatch are not resolved and keep their
+//! intra-procedural treatment.  Module paths are not tracked either, so when
+//! two files define a function under the same name, calls resolve to the
+//! one in the file listed first.
+
+use crate::rules::{auth_gap, impl_type_name};
+use crate::taint_engine::{self, TaintContext};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use syn::visit::Visit;
+use syn::{Expr, File, Item};
+
+/// What a function does with its parameters, including through its callees.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FunctionSummary {
+    /// Sink method names reached by each parameter (by position, not counting
+    /// a `self` receiver) without a preceding `require_auth`, whether the
+    /// sink call is in this function or in one of its callees.
+    pub param_sinks: BTreeMap<usize, BTreeSet<String>>,
+    /// Parameters the function calls `require_auth` / `require_auth_for_args`
+    /// on, directly or by passing them to a callee that does.
+    pub authorized_params: BTreeSet<usize>,
+    /// Whether the function authorizes anything at all.
+    pub requires_auth: bool,
+    /// Whether the function writes contract storage.
+    pub mutates_storage: bool,
+    /// Whether the function calls another contract.
+    pub calls_external: bool,
+}
+
+/// A call from one summarised function to another.
+#[derive(Debug, Clone)]
+struct CallSite {
+    callee: String,
+    /// The argument at each position when it is a plain identifier
+    /// (optionally borrowed), so callee facts can be mapped back to the
+    /// caller's parameters.
+    args: Vec<Option<String>>,
+}
+
+/// A function definition found in one of the files.
+struct FnDef<'a> {
+    key: String,
+    owner: Option<String>,
+    sig: &'a syn::Signature,
+    block: &'a syn::Block,
+}
+
+/// Call graph and per-function summaries for a file or crate.
+#[derive(Debug, Clone, Default)]
+pub struct CallSummaries {
+    summaries: BTreeMap<String, FunctionSummary>,
+    calls: BTreeMap<String, Vec<CallSite>>,
+}
+
+impl CallSummaries {
+    /// Summarise every function in `file`.  `extra_sinks` are method names
+    /// treated as sinks in addition to storage writes and external calls (see
+    /// [`TaintContext::extra_sinks`]).
+    pub fn from_file(file: &File, extra_sinks: &[String]) -> Self {
+        Self::from_files(&[file], extra_sinks)
+    }
+
+    /// Summarise every function in `files`, the files of one crate, so
+    /// calls into helpers defined in another file are followed.  List the
+    /// file being analyzed first: its definitions win over same-named ones
+    /// elsewhere.
+    pub fn from_files(files: &[&File], extra_sinks: &[String]) -> Self {
+        let mut defs: Vec<FnDef<'_>> = files.iter().flat_map(|file| collect_fns(file)).collect();
+        // Duplicate names (e.g. the same helper behind two `cfg`s, or in two
+        // modules) keep the first definition.
+        let mut seen = HashSet::new();
+        defs.retain(|def| seen.insert(def.key.clone()));
+        let keys: HashSet<&str> = defs.iter().map(|d| d.key.as_str()).collect();
+
+        let mut this = CallSummaries::default();
+        for def in &defs {
+            let mut calls = CallCollector {
+                owner: def.owner.as_deref(),
+                keys: &keys,
+                found: Vec::new(),
+            };
+            calls.visit_block(def.block);
+            this.calls.insert(def.key.clone(), calls.found);
+            this.summaries
+                .insert(def.key.clone(), local_summary(def.sig, def.block));
+        }
+
+        // Summaries only ever grow and are bounded by the number of
+        // parameters and sink names in the files, so this terminates.
+        loop {
+            let updates: Vec<(String, FunctionSummary)> = defs
+                .iter()
+                .map(|def| (def.key.clone(), this.step(def, extra_sinks)))
+                .collect();
+            let mut changed = false;
+            for (key, summary) in updates {
+                if this.summaries.get(&key) != Some(&summary) {
+                    this.summaries.insert(key, summary);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return this;
+            }
+        }
+    }
+
+    /// Summarise `file` together with `crate_sources`, the source text of
+    /// the other files of its crate.  Sources that fail to parse are
+    /// skipped.
+    pub fn from_sources(file: &File, crate_sources: &[String], extra_sinks: &[String]) -> Self {
+        let others: Vec<File> = crate_sources
+            .iter()
+            .filter_map(|source| syn::parse_str::<File>(source).ok())
+            .collect();
+        let files: Vec<&File> = std::iter::once(file).chain(&others).collect();
+        Self::from_files(&files, extra_sinks)
+    }
+
+    /// Summary of the function with qualified name `key` (see
+    /// [`qualified_name`]).
+    pub fn get(&self, key: &str) -> Option<&FunctionSummary> {
+        self.summaries.get(key)
+    }
+
+    /// Qualified names of the summarised functions `key` calls.
+    pub fn callees(&self, key: &str) -> BTreeSet<&str> {
+        self.calls
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|site| site.callee.as_str())
+            .collect()
+    }
+
+    /// Resolve the callee of a call expression made from inside `owner`
+    /// (the enclosing `impl` type, if any) to its summary.
+    pub fn resolve(&self, owner: Option<&str>, func: &Expr) -> Option<(&str, &FunctionSummary)> {
+        let key = resolve_call_path(owner, func)?;
+        self.summaries
+            .get_key_value(&key)
+            .map(|(key, summary)| (key.as_str(), summary))
+    }
+
+    /// Recompute one function's summary from the current callee summaries.
+    fn step(&self, def: &FnDef<'_>, extra_sinks: &[String]) -> FunctionSummary {
+        let mut summary = self.summaries[&def.key].clone();
+        let params = param_names(def.sig);
+
+        for site in &self.calls[&def.key] {
+            let callee = &self.summaries[&site.callee];
+            summary.requires_auth |= callee.requires_auth;
+            summary.mutates_storage |= callee.mutates_storage;
+            summary.calls_external |= callee.calls_external;
+            for &idx in &callee.authorized_params {
+                let Some(arg) = site.args.get(idx).and_then(Option::as_deref) else {
+                    continue;
+                };
+                if let Some(param) = params.iter().position(|p| p.as_deref() == Some(arg)) {
+                    summary.authorized_params.insert(param);
+                }
+            }
+        }
+
+        let ctx = TaintContext {
+            extra_sinks,
+            summaries: Some(self),
+            owner: def.owner.as_deref(),
+        };
+        for (idx, sources) in param_sources(def.sig) {
+            for finding in taint_engine::analyze_with(def.block, sources, ctx) {
+                summary
+                    .param_sinks
+                    .entry(idx)
+                    .or_default()
+                    .insert(finding.sink);
+            }
+        }
+        summary
+    }
+}
+
+/// Name a function is summarised under: `Type::name` for methods of
+/// `impl Type`, plain `name` for free functions.
+pub fn qualified_name(owner: Option<&str>, name: &str) -> String {
+    match owner {
+        Some(owner) => format!("{}::{}", owner, name),
+        None => name.to_string(),
+    }
+}
+
+/// Variables bound by each parameter that can carry caller-controlled data,
+/// by position.  `self` receivers and `Env` parameters are skipped.
+pub fn param_sources(sig: &syn::Signature) -> Vec<(usize, HashSet<String>)> {
+    typed_params(sig)
+        .filter(|(_, pt)| {
+            let ty = &pt.ty;
+            !quote::quote!(#ty).to_string().contains("Env")
+        })
+        .map(|(idx, pt)| {
+            let mut names = HashSet::new();
+            taint_engine::collect_pat_idents(&pt.pat, &mut names);
+            (idx, names)
+        })
+        .collect()
+}
+
+fn typed_params(sig: &syn::Signature) -> impl Iterator<Item = (usize, &syn::PatType)> {
+    sig.inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pt) => Some(pt),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .enumerate()
+}
+
+/// The identifier bound by each parameter, when it is a plain binding.
+fn param_names(sig: &syn::Signature) -> Vec<Option<String>> {
+    typed_params(sig)
+        .map(|(_, pt)| match pt.pat.as_ref() {
+            syn::Pat::Ident(pi) => Some(pi.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_fns(file: &File) -> Vec<FnDef<'_>> {
+    let mut defs = Vec::new();
+    for item in &file.items {
+        match item {
+            Item::Fn(f) => defs.push(FnDef {
+                key: f.sig.ident.to_string(),
+                owner: None,
+                sig: &f.sig,
+                block: &f.block,
+            }),
+            Item::Impl(i) => {
+                let owner = impl_type_name(i);
+                for impl_item in &i.items {
+                    if let syn::ImplItem::Fn(f) = impl_item {
+                        defs.push(FnDef {
+                            key: qualified_name(owner.as_deref(), &f.sig.ident.to_string()),
+                            owner: owner.clone(),
+                            sig: &f.sig,
+                            block: &f.block,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    defs
+}
+
+/// Facts visible in the function's own body, before callees are considered.
+fn local_summary(sig: &syn::Signature, block: &syn::Block) -> FunctionSummary {
+    let mut security = auth_gap::FunctionSecuritySummary::default();
+    auth_gap::check_fn_body(block, &mut security);
+
+    let params = param_names(sig);
+    let mut auth = AuthCollector {
+        params: &params,
+        found: BTreeSet::new(),
+    };
+    auth.visit_block(block);
+
+    FunctionSummary {
+        param_sinks: BTreeMap::new(),
+        authorized_params: auth.found,
+        requires_auth: security.has_auth,
+        mutates_storage: security.has_mutation,
+        calls_external: security.has_external_call,
+    }
+}
+
+pub(crate) fn resolve_call_path(owner: Option<&str>, func: &Expr) -> Option<String> {
+    let Expr::Path(p) = func else {
+        return None;
+    };
+    let segments: Vec<String> = p
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    match segments.as_slice() {
+        [name] => Some(name.clone()),
+        [ty, name] if ty == "Self" => owner.map(|owner| qualified_name(Some(owner), name)),
+        // `storage::write(..)`, `crate::auth::ensure(..)`: a free function in
+        // a module, told apart from `Type::method` by the case of the last
+        // qualifier.
+        [.., module, name] if module.starts_with(|c: char| c.is_lowercase()) => Some(name.clone()),
+        [.., ty, name] => Some(qualified_name(Some(ty), name)),
+        [] => None,
+    }
+}
+
+/// Strip borrows and parentheses down to a plain identifier.
+fn plain_ident(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(p) if p.path.segments.len() == 1 => Some(p.path.segments[0].ident.to_string()),
+        Expr::Reference(r) => plain_ident(&r.expr),
+        Expr::Paren(p) => plain_ident(&p.expr),
+        Expr::Group(g) => plain_ident(&g.expr),
+        _ => None,
+    }
+}
+
+struct CallCollector<'a> {
+    owner: Option<&'a str>,
+    keys: &'a HashSet<&'a str>,
+    found: Vec<CallSite>,
+}
+
+impl<'ast> Visit<'ast> for CallCollector<'_> {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Some(callee) = resolve_call_path(self.owner, &call.func) {
+            if self.keys.contains(callee.as_str()) {
+                self.found.push(CallSite {
+                    callee,
+                    args: call.args.iter().map(plain_ident).collect(),
+                });
+            }
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+}
+
+struct AuthCollector<'a> {
+    params: &'a [Option<String>],
+    found: BTreeSet<usize>,
+}
+
+impl<'ast> Visit<'ast> for AuthCollector<'_> {
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if call.method == "require_auth" || call.method == "require_auth_for_args" {
+            if let Some(name) = plain_ident(&call.receiver) {
+                if let Some(idx) = self
+                    .params
+                    .iter()
+                    .position(|p| p.as_deref() == Some(name.as_str()))
+                {
+                    self.found.insert(idx);
+                }
+            }
+        }
+        syn::visit::visit_expr_method_call(self, call);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summarise(src: &str) -> CallSummaries {
+        CallSummaries::from_file(&syn::parse_str(src).unwrap(), &[])
+    }
+
+    #[test]
+    fn param_reaching_sink_in_helper_is_summarised() {
+        let summaries = summarise(
+            r#"
+            impl Vault {
+                fn write(env: &Env, key: Symbol, amount: i128) {
+                    env.storage().persistent().set(&key, &amount);
+                }
+            }
+            "#,
+        );
+        let write = summaries.get("Vault::write").unwrap();
+        assert!(write.mutates_storage);
+        assert!(write.param_sinks[&1].contains("set"));
+        assert!(write.param_sinks[&2].contains("set"));
+        assert!(!write.param_sinks.contains_key(&0));
+    }
+
+    #[test]
+    fn facts_propagate_through_call_chains() {
+        let summaries = summarise(
+            r#"
+            fn store(env: &Env, value: i128) {
+                env.storage().instance().set(&KEY, &value);
+            }
+            fn forward(env: &Env, value: i128) {
+                store(env, value);
+            }
+            impl Vault {
+                pub fn deposit(env: Env, amount: i128) {
+                    forward(&env, amount);
+                }
+            }
+            "#,
+        );
+        let deposit = summaries.get("Vault::deposit").unwrap();
+        assert!(deposit.mutates_storage);
+        assert!(deposit.param_sinks[&1].contains("set"));
+        assert_eq!(
+            summaries
+                .callees("Vault::deposit")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["forward"]
+        );
+    }
+
+    #[test]
+    fn authorization_in_helper_is_attributed_to_caller_param() {
+        let summaries = summarise(
+            r#"
+            impl Vault {
+                fn ensure_owner(owner: &Address) {
+                    owner.require_auth();
+                }
+                pub fn withdraw(env: Env, owner: Address, amount: i128) {
+                    Self::ensure_owner(&owner);
+                    env.storage().persistent().set(&owner, &amount);
+                }
+            }
+            "#,
+        );
+        let helper = summaries.get("Vault::ensure_owner").unwrap();
+        assert_eq!(helper.authorized_params, BTreeSet::from([0]));
+        let withdraw = summaries.get("Vault::withdraw").unwrap();
+        assert!(withdraw.requires_auth);
+        assert_eq!(withdraw.authorized_params, BTreeSet::from([1]));
+        assert!(withdraw.param_sinks.is_empty());
+    }
+
+    #[test]
+    fn recursion_reaches_a_fixed_point() {
+        let summaries = summarise(
+            r#"
+            fn walk(env: &Env, n: u32) {
+                if n > 0 {
+                    walk(env, n - 1);
+                }
+                env.storage().temporary().set(&n, &true);
+            }
+            "#,
+        );
+        assert!(summaries.get("walk").unwrap().param_sinks[&1].contains("set"));
+    }
+
+    #[test]
+    fn self_calls_resolve_to_enclosing_impl() {
+        let summaries = summarise(
+            r#"
+            impl A { fn helper(x: u32) { env.storage().instance().set(&K, &x); } }
+            impl B { fn helper(x: u32) {} pub fn run(x: u32) { Self::helper(x); } }
+            "#,
+        );
+        assert!(summaries.get("B::run").unwrap().param_sinks.is_empty());
+        assert_eq!(
+            summaries.callees("B::run").into_iter().collect::<Vec<_>>(),
+            vec!["B::helper"]
+        );
+    }
+
+    #[test]
+    fn helpers_in_other_files_of_the_crate_are_followed() {
+        let entry: File = syn::parse_str(
+            r#"
+            fn helper(x: u32) {}
+            impl Vault {
+                pub fn deposit(env: Env, from: Address, amount: i128) {
+                    storage::write(&env, amount);
+                    auth::ensure(&from);
+                    helper(1);
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let crate_sources = [
+            "fn write(env: &Env, value: i128) { env.storage().persistent().set(&KEY, &value); }"
+                .to_string(),
+            "fn ensure(who: &Address) { who.require_auth(); }".to_string(),
+            "fn helper(x: u32) { env.storage().instance().set(&K, &x); }".to_string(),
+            "not rust {".to_string(),
+        ];
+
+        let deposit = CallSummaries::from_file(&entry, &[]);
+        assert!(!deposit.get("Vault::deposit").unwrap().mutates_storage);
+
+        let summaries = CallSummaries::from_sources(&entry, &crate_sources, &[]);
+        let deposit = summaries.get("Vault::deposit").unwrap();
+        assert!(deposit.mutates_storage);
+        assert!(deposit.param_sinks[&2].contains("set"));
+        assert_eq!(deposit.authorized_params, BTreeSet::from([1]));
+        // The entry file's own `helper` shadows the one in another file.
+        assert!(!summaries.get("helper").unwrap().mutates_storage);
+    }
+}
@@ -3,7 +3,7 @@
 //!
 //! This crate provides the [`Analyzer`] entry-point together with a
 //! [`RuleRegistry`] of pluggable rules.  Every finding is tagged with a
-//! canonical code from the [`finding_codes`] module (`S000` – `S027`).
+//! canonical code from the [`finding_codes`] module (`S000` – `S033`).
 //!
 //! # JSON output schema
 //!
@@ -54,41 +54,43 @@ pub mod cfg;
 pub mod complexity;
 pub mod constant_folding;
 pub mod contract_discovery;
+pub mod contract_interface;
+pub mod contract_spec;
+pub mod custom_yaml_rules;
+pub mod dataflow;
 pub mod finding_codes;
+pub mod fix_context;
 pub mod gas_estimator;
 pub mod gas_report;
 pub mod input_validation;
+pub mod interprocedural;
 pub mod parser;
 pub mod patcher;
 pub mod reentrancy;
+pub mod rule_config;
 pub mod rules;
 pub mod sdk_version;
 pub mod sep41;
-#[cfg(feature = "smt")]
 pub mod smt;
 pub mod soroban_v21;
 pub mod storage_collision;
+pub mod storage_layout;
+pub mod suppression;
 pub mod taint_engine;
+pub mod wasm_cost;
 
 // Re-export common types for easier CLI access
 pub use complexity::{ContractMetrics, FunctionMetrics};
 pub use finding_codes::FindingSeverity as RuleSeverity;
 pub use finding_codes::FindingSeverity;
 pub use reentrancy::ReentrancyEdge;
-pub use rules::{Patch, Rule, RuleRegistry, RuleViolation, Severity};
+pub use rule_config::{RuleConfig, RuleConfigError};
+pub use rules::{Patch, Rule, RuleRegistry, RuleViolation, Severity, SourceSpan};
 pub use sep41::{Sep41Issue, Sep41IssueKind, Sep41VerificationReport};
-#[cfg(feature = "smt")]
 pub use smt::SmtInvariantIssue;
 
-#[cfg(not(feature = "smt"))]
-#[derive(Debug, Serialize, Clone)]
-pub struct SmtInvariantIssue {
-    pub function_name: String,
-    pub description: String,
-    pub location: String,
-}
-
 pub use storage_collision::StorageCollisionIssue;
+pub use storage_layout::{StorageLayout, UpgradeRiskReport};
 
 // ── Panic Guard ───────────────────────────────────────────────────────────────
 
@@ -104,10 +106,15 @@ where
 
 // ── Configuration ─────────────────────────────────────────────────────────────
 
+/// SHA-256 of this crate's sources, taken at build time.  Caches of
+/// analysis results key on it, so a change to any rule invalidates them even
+/// when the version number stays the same.
+pub const ENGINE_FINGERPRINT: &str = env!("SANCTIFIER_ENGINE_FINGERPRINT");
+
 pub const DEFAULT_LEDGER_ENTRY_LIMIT: usize = 64 * 1024;
 pub const DEFAULT_APPROACHING_THRESHOLD: f64 = 0.8;
 
-/// User-defined regex-based rule. Defined in .sanctify.toml under [[rules]].
+/// User-defined regex-based rule. Defined in .sanctify.toml under [[custom_rules]].
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CustomRule {
     pub name: String,
@@ -118,23 +125,47 @@ pub struct CustomRule {
     pub severity: finding_codes::FindingSeverity,
 }
 
+/// Project-level configuration loaded from `.sanctify.toml`.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SanctifyConfig {
+    /// Paths to skip during directory walking.
     #[serde(default = "default_ignore_paths")]
     pub ignore_paths: Vec<String>,
-    #[serde(default = "default_enabled_rules")]
+    /// Legacy rule groups (see [`LEGACY_RULE_GROUPS`]); the rules of a group
+    /// left out do not run unless a `[rules.<name>]` table says otherwise.
+    #[serde(
+        default = "default_enabled_rules",
+        deserialize_with = "deserialize_enabled_rules"
+    )]
     pub enabled_rules: Vec<String>,
+    /// Ledger-entry size limit in bytes.
     #[serde(default = "default_ledger_limit")]
     pub ledger_limit: usize,
+    /// Fraction of `ledger_limit` at which an *approaching* warning fires.
     #[serde(default = "default_approaching_threshold")]
     pub approaching_threshold: f64,
+    /// Opt-in anonymous usage telemetry.
     #[serde(default = "default_telemetry_enabled")]
     pub telemetry: bool,
+    /// When `true`, use a tighter threshold for size warnings.
     #[serde(default)]
     pub strict_mode: bool,
-    /// Custom regex rules (field name "rules" in TOML).
-    #[serde(default, alias = "custom_rules")]
-    pub rules: Vec<CustomRule>,
+    /// Custom regex rules (`[[custom_rules]]`).
+    #[serde(default)]
+    pub custom_rules: Vec<CustomRule>,
+    /// YAML custom rules file, relative to the `.sanctify.toml` that names it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_rules_yaml: Option<String>,
+    /// Per-rule settings (`[rules.<name>]`), see [`rule_config`].
+    #[serde(
+        default,
+        deserialize_with = "rule_config::deserialize_rule_tables",
+        skip_serializing_if = "std::collections::BTreeMap::is_empty"
+    )]
+    pub rules: rule_config::RuleConfigMap,
+    /// Accepted findings keyed by code, written by `sanctifier suppress`.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub suppressions: suppression::SuppressionMap,
 }
 
 fn default_ignore_paths() -> Vec<String> {
@@ -148,6 +179,41 @@ fn default_enabled_rules() -> Vec<String> {
         "ledger_size".to_string(),
     ]
 }
+
+/// Group names accepted in the legacy `enabled_rules` list, with the rules
+/// each one switches.  Groups without rules of their own are accepted for
+/// older configs and always on.
+pub const LEGACY_RULE_GROUPS: &[(&str, &[&str])] = &[
+    ("auth_gaps", &["auth_gap"]),
+    ("panics", &["panic_detection"]),
+    ("arithmetic", &["arithmetic_overflow"]),
+    ("integer_overflow", &["arithmetic_overflow"]),
+    ("ledger_size", &["ledger_size"]),
+    ("unsafe_patterns", &[]),
+    ("reentrancy", &[]),
+];
+
+/// Deserialize `enabled_rules`, rejecting names that are not a legacy group
+/// so a typo cannot silently switch a rule off.
+fn deserialize_enabled_rules<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let groups = Vec::<String>::deserialize(deserializer)?;
+    if let Some(unknown) = groups
+        .iter()
+        .find(|group| !LEGACY_RULE_GROUPS.iter().any(|(name, _)| name == group))
+    {
+        let known: Vec<&str> = LEGACY_RULE_GROUPS.iter().map(|(name, _)| *name).collect();
+        return Err(serde::de::Error::custom(format!(
+            "unknown rule group `{}` in enabled_rules; expected one of: {} \
+             (use [rules.<name>] tables for individual rules)",
+            unknown,
+            known.join(", ")
+        )));
+    }
+    Ok(groups)
+}
 fn default_ledger_limit() -> usize {
     DEFAULT_LEDGER_ENTRY_LIMIT
 }
@@ -168,20 +234,45 @@ impl Default for SanctifyConfig {
             approaching_threshold: default_approaching_threshold(),
             telemetry: default_telemetry_enabled(),
             strict_mode: false,
-            rules: vec![],
+            custom_rules: vec![],
+            custom_rules_yaml: None,
+            rules: Default::default(),
+            suppressions: Default::default(),
+        }
+    }
+}
+
+impl SanctifyConfig {
+    /// Whether the rule called `name` should run.  Its `[rules.<name>]` table
+    /// decides when there is one; otherwise the rule runs unless it belongs to
+    /// a legacy group that a non-empty `enabled_rules` leaves out.
+    /// `strict_mode` keeps every rule enabled.
+    pub fn rule_enabled(&self, name: &str) -> bool {
+        if self.strict_mode {
+            return true;
         }
+        if let Some(rule) = self.rules.get(name) {
+            return rule.enabled;
+        }
+        let mut groups = LEGACY_RULE_GROUPS
+            .iter()
+            .filter(|(_, rules)| rules.contains(&name))
+            .peekable();
+        self.enabled_rules.is_empty()
+            || groups.peek().is_none()
+            || groups.any(|(group, _)| self.enabled_rules.iter().any(|g| g == group))
     }
 }
 
 // ── Finding types ─────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum SizeWarningLevel {
     ExceedsLimit,
     ApproachingLimit,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SizeWarning {
     pub struct_name: String,
     pub estimated_size: usize,
@@ -189,14 +280,14 @@ pub struct SizeWarning {
     pub level: SizeWarningLevel,
 }
 
-#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum PatternType {
     Panic,
     Unwrap,
     Expect,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UnsafePattern {
     pub pattern_type: PatternType,
     pub line: usize,
@@ -205,7 +296,7 @@ pub struct UnsafePattern {
 
 // ── Upgrade analysis types ────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpgradeFinding {
     pub category: UpgradeCategory,
     pub function_name: Option<String>,
@@ -215,7 +306,7 @@ pub struct UpgradeFinding {
     pub severity: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum UpgradeCategory {
     AdminControl,
@@ -226,7 +317,7 @@ pub enum UpgradeCategory {
 }
 
 /// Upgrade safety report.
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpgradeReport {
     pub findings: Vec<UpgradeFinding>,
     pub upgrade_mechanisms: Vec<String>,
@@ -281,7 +372,7 @@ fn is_init_fn(name: &str) -> bool {
 /// Represents a panic, unwrap, or expect usage detected in contract code.
 ///
 /// This is the structured finding type for S002 (Panic Usage).
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PanicIssue {
     pub function_name: String,
     pub issue_type: String,
@@ -342,7 +433,7 @@ pub struct PanicIssue {
 ///            // }
 /// }
 /// ```
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ArithmeticIssue {
     pub function_name: String,
     pub operation: String,
@@ -350,7 +441,7 @@ pub struct ArithmeticIssue {
     pub location: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TruncationBoundsIssue {
     pub function_name: String,
     pub kind: String,
@@ -359,21 +450,30 @@ pub struct TruncationBoundsIssue {
     pub location: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AuthGapIssue {
     pub function_name: String,
     pub location: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EventIssue {
+    pub function_name: String,
     pub event_name: String,
-    pub issue_type: String,
+    pub issue_type: EventIssueType,
     pub location: String,
     pub message: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum EventIssueType {
+    /// The same event name is published with different topic counts.
+    InconsistentSchema,
+    /// A string topic that could be a `symbol_short!`.
+    OptimizableTopic,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UnhandledResultIssue {
     pub function_name: String,
     pub call_expression: String,
@@ -382,7 +482,7 @@ pub struct UnhandledResultIssue {
 }
 
 /// A match from a custom regex rule.
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CustomRuleMatch {
     pub rule_name: String,
     pub line: usize,
@@ -410,33 +510,6 @@ impl std::fmt::Display for CustomRuleValidationError {
     }
 }
 
-/// Project-level configuration loaded from `.sanctify.toml`.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SanctifyConfig {
-    /// Paths to skip during directory walking.
-    #[serde(default = "default_ignore_paths")]
-    pub ignore_paths: Vec<String>,
-    /// Names of enabled built-in rules.
-    #[serde(default = "default_enabled_rules")]
-    pub enabled_rules: Vec<String>,
-    /// Ledger-entry size limit in bytes.
-    #[serde(default = "default_ledger_limit")]
-    pub ledger_limit: usize,
-    /// Fraction of `ledger_limit` at which an *approaching* warning fires.
-    #[serde(default = "default_approaching_threshold")]
-    pub approaching_threshold: f64,
-    /// When `true`, use a tighter threshold for size warnings.
-    #[serde(default)]
-    pub strict_mode: bool,
-    /// User-defined regex rules.
-    #[serde(default)]
-    pub custom_rules: Vec<CustomRule>,
-}
-
-fn default_ignore_paths() -> Vec<String> {
-    vec!["target".to_string(), ".git".to_string()]
-}
-
 #[derive(Debug, Serialize, Clone)]
 pub struct GasEstimation {
     pub function_name: String,
@@ -478,8 +551,11 @@ impl Analyzer {
     pub fn new(config: SanctifyConfig) -> Self {
         Self { config }
     }
+    /// Run a single registry rule, honoring its `[rules.<name>]` table.  An
+    /// invalid table falls back to the rule's defaults; callers that need to
+    /// report it build the registry with [`RuleRegistry::from_config`].
     pub fn run_rule(&self, source: &str, rule_name: &str) -> Vec<RuleViolation> {
-        let registry = rules::RuleRegistry::with_default_rules();
+        let registry = rules::RuleRegistry::from_config(&self.config).unwrap_or_default();
         registry.run_by_name(source, rule_name)
     }
 
@@ -504,9 +580,54 @@ impl Analyzer {
         reentrancy::scan_invoke_contract_calls(source)
     }
 
+    /// Validate custom rules before executing them (S007 UX improvement).
+    ///
+    /// Returns a list of [`CustomRuleValidationError`] for every rule whose
+    /// regex pattern fails to compile or whose name is empty.  An empty return
+    /// value means all rules are ready to run.  Call this once at startup so
+    /// broken configuration is surfaced to the user with a clear message rather
+    /// than silently dropped during analysis.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let errors = analyzer.validate_custom_rules(&config.custom_rules);
+    /// if !errors.is_empty() {
+    ///     for e in &errors { eprintln!("Config error: {e}"); }
+    ///     std::process::exit(1);
+    /// }
+    /// ```
+    pub fn validate_custom_rules(&self, rules: &[CustomRule]) -> Vec<CustomRuleValidationError> {
+        let mut errors = Vec::new();
+        for rule in rules {
+            if rule.name.trim().is_empty() {
+                errors.push(CustomRuleValidationError {
+                    rule_name: "<unnamed>".to_string(),
+                    message: "rule name must not be empty".to_string(),
+                });
+            }
+            if rule.pattern.is_empty() {
+                errors.push(CustomRuleValidationError {
+                    rule_name: rule.name.clone(),
+                    message: "pattern must not be empty — use a non-empty regex string".to_string(),
+                });
+            } else if let Err(e) = Regex::new(&rule.pattern) {
+                errors.push(CustomRuleValidationError {
+                    rule_name: rule.name.clone(),
+                    message: format!("invalid regex pattern '{}': {}", rule.pattern, e),
+                });
+            }
+        }
+        errors
+    }
+
+    /// Run regex-based custom rules from config. Returns matches with line and snippet.
+    ///
+    /// Rules with invalid regex patterns are skipped silently. For upfront
+    /// validation that surfaces configuration errors, call
+    /// [`Analyzer::validate_custom_rules`] first.
     pub fn analyze_custom_rules(&self, source: &str) -> Vec<CustomRuleMatch> {
         let mut matches = Vec::new();
-        for rule in &self.config.rules {
+        for rule in &self.config.custom_rules {
             let re = match Regex::new(&rule.pattern) {
                 Ok(r) => r,
                 Err(_) => continue,
@@ -517,6 +638,7 @@ impl Analyzer {
                         rule_name: rule.name.clone(),
                         line: line_no + 1,
                         snippet: line.trim().to_string(),
+                        severity: rule.severity,
                     });
                 }
             }
@@ -525,6 +647,9 @@ impl Analyzer {
     }
 
     pub fn scan_auth_gaps(&self, source: &str) -> Vec<String> {
+        if !self.config.rule_enabled("auth_gap") {
+            return vec![];
+        }
         with_panic_guard(|| self.scan_auth_gaps_impl(source))
     }
 
@@ -551,6 +676,9 @@ impl Analyzer {
     }
 
     pub fn scan_panics(&self, source: &str) -> Vec<PanicIssue> {
+        if !self.config.rule_enabled("panic_detection") {
+            return vec![];
+        }
         with_panic_guard(|| self.scan_panics_impl(source))
     }
 
@@ -718,14 +846,36 @@ impl Analyzer {
             Err(_) => return vec![],
         };
         let mut visitor = storage_collision::StorageVisitor::new();
+        visitor.collect_definitions(&file);
         syn::visit::visit_file(&mut visitor, &file);
         visitor.final_check();
         visitor.collisions
     }
 
-    pub fn scan_events(&self, _source: &str) -> Vec<EventIssue> {
-        // Event scanning is not implemented in the current core engine.
-        Vec::new()
+    /// Scan the `(path, source)` files of a crate or workspace together, so
+    /// keys that collide across files are reported with both locations.
+    /// Key constants and key-constructor functions resolve across files.
+    /// Files that fail to parse are skipped.
+    pub fn scan_storage_collisions_in_files(
+        &self,
+        sources: &[(String, String)],
+    ) -> Vec<StorageCollisionIssue> {
+        let files: Vec<(&str, File)> = sources
+            .iter()
+            .filter_map(|(path, source)| {
+                parse_str::<File>(source).ok().map(|file| (path.as_str(), file))
+            })
+            .collect();
+        let mut visitor = storage_collision::StorageVisitor::new();
+        for (_, file) in &files {
+            visitor.collect_definitions(file);
+        }
+        for (path, file) in &files {
+            visitor.set_file(path);
+            syn::visit::visit_file(&mut visitor, file);
+        }
+        visitor.final_check();
+        visitor.collisions
     }
 
     pub fn scan_unhandled_results(&self, source: &str) -> Vec<UnhandledResultIssue> {
@@ -743,6 +893,9 @@ impl Analyzer {
     }
 
     pub fn analyze_ledger_size(&self, source: &str) -> Vec<SizeWarning> {
+        if !self.config.rule_enabled("ledger_size") {
+            return vec![];
+        }
         with_panic_guard(|| self.analyze_ledger_size_impl(source))
     }
 
@@ -955,6 +1108,9 @@ impl Analyzer {
     }
 
     pub fn scan_arithmetic_overflow(&self, source: &str) -> Vec<ArithmeticIssue> {
+        if !self.config.rule_enabled("arithmetic_overflow") {
+            return vec![];
+        }
         with_panic_guard(|| self.scan_arithmetic_overflow_impl(source))
     }
 
@@ -1002,73 +1158,6 @@ impl Analyzer {
             suggestions: Vec::new(),
         };
 
-    /// Validate custom rules before executing them (S007 UX improvement).
-    ///
-    /// Returns a list of [`CustomRuleValidationError`] for every rule whose
-    /// regex pattern fails to compile or whose name is empty.  An empty return
-    /// value means all rules are ready to run.  Call this once at startup so
-    /// broken configuration is surfaced to the user with a clear message rather
-    /// than silently dropped during analysis.
-    ///
-    /// # Example
-    /// ```rust,ignore
-    /// let errors = analyzer.validate_custom_rules(&config.custom_rules);
-    /// if !errors.is_empty() {
-    ///     for e in &errors { eprintln!("Config error: {e}"); }
-    ///     std::process::exit(1);
-    /// }
-    /// ```
-    pub fn validate_custom_rules(
-        &self,
-        rules: &[CustomRule],
-    ) -> Vec<CustomRuleValidationError> {
-        use regex::Regex;
-        let mut errors = Vec::new();
-        for rule in rules {
-            if rule.name.trim().is_empty() {
-                errors.push(CustomRuleValidationError {
-                    rule_name: "<unnamed>".to_string(),
-                    message: "rule name must not be empty".to_string(),
-                });
-            }
-            if rule.pattern.is_empty() {
-                errors.push(CustomRuleValidationError {
-                    rule_name: rule.name.clone(),
-                    message: "pattern must not be empty — use a non-empty regex string".to_string(),
-                });
-            } else if let Err(e) = Regex::new(&rule.pattern) {
-                errors.push(CustomRuleValidationError {
-                    rule_name: rule.name.clone(),
-                    message: format!("invalid regex pattern '{}': {}", rule.pattern, e),
-                });
-            }
-        }
-        errors
-    }
-
-    /// Run regex-based custom rules from config. Returns matches with line and snippet.
-    ///
-    /// Rules with invalid regex patterns are skipped silently. For upfront
-    /// validation that surfaces configuration errors, call
-    /// [`Analyzer::validate_custom_rules`] first.
-    pub fn analyze_custom_rules(&self, source: &str, rules: &[CustomRule]) -> Vec<CustomRuleMatch> {
-        use regex::Regex;
-
-        let mut matches = Vec::new();
-        for rule in rules {
-            let re = match Regex::new(&rule.pattern) {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
-            for (line_no, line) in source.lines().enumerate() {
-                let line_num = line_no + 1;
-                if re.find(line).is_some() {
-                    matches.push(CustomRuleMatch {
-                        rule_name: rule.name.clone(),
-                        line: line_num,
-                        snippet: line.trim().to_string(),
-                        severity: rule.severity,
-                    });
         // Collect #[contracttype] storage types
         for item in &file.items {
             if let Item::Struct(s) = item {
@@ -2087,11 +2176,7 @@ fn is_string_literal(expr: &syn::Expr) -> bool {
 fn fn_has_reinit_guard(block: &syn::Block) -> bool {
     for stmt in &block.stmts {
         match stmt {
-            syn::Stmt::Expr(expr, _) => {
-                if expr_has_storage_guard(expr) {
-                    return true;
-                }
-            }
+            syn::Stmt::Expr(expr, _) if expr_has_storage_guard(expr) => return true,
             syn::Stmt::Local(local) => {
                 if let Some(init) = &local.init {
                     if expr_has_storage_guard(&init.expr) {
@@ -2141,11 +2226,7 @@ fn expr_has_storage_guard(expr: &syn::Expr) -> bool {
 fn block_has_early_exit(block: &syn::Block) -> bool {
     for stmt in &block.stmts {
         match stmt {
-            syn::Stmt::Expr(expr, _) => {
-                if expr_has_early_exit(expr) {
-                    return true;
-                }
-            }
+            syn::Stmt::Expr(expr, _) if expr_has_early_exit(expr) => return true,
             syn::Stmt::Macro(m) if m.mac.path.is_ident("panic") => {
                 return true;
             }
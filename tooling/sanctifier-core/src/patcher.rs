@@ -1,118 +1,208 @@
 //! Source-level auto-patching.
+//!
+//! Patches from different rules are applied together.  [`Patcher::apply`]
+//! resolves each patch's line/column range through a [`LineIndex`], merges
+//! exact duplicates, applies insertions at the same point in input order, and
+//! reports a patch whose range overlaps one accepted before it as a
+//! [`PatchConflict`] instead of corrupting the source.  Input order therefore
+//! decides which of two conflicting patches wins.
 
+use crate::fix_context::LineIndex;
 use crate::rules::Patch;
 
 /// Applies [`Patch`]es to source text.
 pub struct Patcher;
 
-impl Patcher {
-    /// Apply a set of non-overlapping patches to `source`.
-    pub fn apply_patches(source: &str, patches: &[Patch]) -> String {
-        if patches.is_empty() {
-            return source.to_string();
-        }
-
-        let mut sorted_patches = patches.to_vec();
-        // Sort by position in reverse order (bottom-up, right-to-left)
-        // to avoid shifting offsets of subsequent patches.
-        sorted_patches.sort_by(|a, b| {
-            if a.start_line != b.start_line {
-                b.start_line.cmp(&a.start_line)
-            } else {
-                b.start_column.cmp(&a.start_column)
-            }
-        });
+/// Outcome of [`Patcher::apply`].  Patches are referred to by their index in
+/// the input slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchOutcome {
+    /// The patched source.
+    pub source: String,
+    /// Patches applied, in input order.  A duplicate of an applied patch is
+    /// neither applied again nor reported.
+    pub applied: Vec<usize>,
+    /// Patches skipped because they overlap an applied patch.
+    pub conflicts: Vec<PatchConflict>,
+    /// Patches whose range lies outside the source.
+    pub unlocated: Vec<usize>,
+}
 
-        let mut result = source.to_string();
+/// A patch skipped because its range overlaps one applied before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatchConflict {
+    pub patch: usize,
+    /// The applied patch it overlaps.
+    pub with: usize,
+}
 
-        for patch in sorted_patches {
-            let (start_offset, end_offset) = match Self::calculate_offsets(&result, &patch) {
-                Some(offsets) => offsets,
-                None => continue,
-            };
+/// A patch resolved to a byte range of the source.
+struct Located<'p> {
+    index: usize,
+    start: usize,
+    end: usize,
+    patch: &'p Patch,
+}
 
-            result.replace_range(start_offset..end_offset, &patch.replacement);
+impl Located<'_> {
+    /// Whether the two edits touch the same text.  Ranges that merely meet
+    /// do not overlap, and neither do two insertions at the same point; an
+    /// insertion strictly inside a replaced range does.
+    fn overlaps(&self, other: &Located<'_>) -> bool {
+        if self.start == self.end && other.start == other.end {
+            return false;
         }
+        self.start.max(other.start) < self.end.min(other.end)
+            || (self.start == self.end && other.start < self.start && self.start < other.end)
+            || (other.start == other.end && self.start < other.start && other.start < self.end)
+    }
+}
 
-        result
+impl Patcher {
+    /// Apply `patches` to `source`, skipping any that cannot be applied.
+    /// Use [`Patcher::apply`] to learn which were skipped and why.
+    pub fn apply_patches(source: &str, patches: &[Patch]) -> String {
+        Self::apply(source, patches).source
     }
 
-    fn calculate_offsets(source: &str, patch: &Patch) -> Option<(usize, usize)> {
-        let mut start_offset = None;
-        let mut end_offset = None;
-        let mut current_offset = 0;
-        let mut current_line = 1;
-
-        // Simple but potentially slow off-set calculator.
-        // For CLI use, it should be fine.
-        for (i, c) in source.char_indices() {
-            if current_line == patch.start_line && start_offset.is_none() {
-                // syn columns are Unicode-aware char offsets on that line
-                // But we need to be careful about tab widths etc.
-                // For now assuming 1 char = 1 column.
-                let line_text = &source[current_offset..];
-                for (col_counter, (j, c2)) in line_text.char_indices().enumerate() {
-                    if col_counter == patch.start_column {
-                        start_offset = Some(current_offset + j);
-                        break;
-                    }
-                    if c2 == '\n' {
-                        break;
-                    }
-                }
-            }
+    /// Apply `patches` to `source`, leaving out patches that overlap an
+    /// earlier one or point outside the source.
+    pub fn apply(source: &str, patches: &[Patch]) -> PatchOutcome {
+        let lines = LineIndex::new(source);
+        let mut accepted: Vec<Located<'_>> = Vec::new();
+        let mut conflicts = Vec::new();
+        let mut unlocated = Vec::new();
 
-            if current_line == patch.end_line && end_offset.is_none() {
-                let line_text = &source[current_offset..];
-                for (col_counter, (j, c2)) in line_text.char_indices().enumerate() {
-                    if col_counter == patch.end_column {
-                        end_offset = Some(current_offset + j);
-                        break;
-                    }
-                    if c2 == '\n' {
-                        break;
-                    }
+        for (index, patch) in patches.iter().enumerate() {
+            let start = lines.offset(source, patch.start_line, patch.start_column);
+            let end = lines.offset(source, patch.end_line, patch.end_column);
+            let located = match (start, end) {
+                (Some(start), Some(end)) if start <= end => Located {
+                    index,
+                    start,
+                    end,
+                    patch,
+                },
+                _ => {
+                    unlocated.push(index);
+                    continue;
                 }
+            };
+            if accepted.iter().any(|a| {
+                a.start == located.start
+                    && a.end == located.end
+                    && a.patch.replacement == patch.replacement
+            }) {
+                continue;
             }
-
-            if c == '\n' {
-                current_line += 1;
-                current_offset = i + 1;
+            match accepted.iter().find(|a| a.overlaps(&located)) {
+                Some(earlier) => conflicts.push(PatchConflict {
+                    patch: index,
+                    with: earlier.index,
+                }),
+                None => accepted.push(located),
             }
         }
 
-        // Handle end of file cases
-        if start_offset.is_none() && patch.start_line == current_line {
-            // check if column is at the very end
-            let line_text = &source[current_offset..];
-            if patch.start_column <= line_text.chars().count() {
-                start_offset = Some(
-                    current_offset
-                        + line_text
-                            .char_indices()
-                            .nth(patch.start_column)
-                            .map(|(i, _)| i)
-                            .unwrap_or(line_text.len()),
-                );
-            }
+        let applied = accepted.iter().map(|a| a.index).collect();
+        // Stable sort: insertions at one point keep their input order, and
+        // come before a replacement starting there.
+        accepted.sort_by_key(|a| (a.start, a.end));
+        let mut result = String::with_capacity(source.len());
+        let mut cursor = 0;
+        for edit in &accepted {
+            result.push_str(&source[cursor..edit.start]);
+            result.push_str(&edit.patch.replacement);
+            cursor = edit.end;
         }
-        if end_offset.is_none() && patch.end_line == current_line {
-            let line_text = &source[current_offset..];
-            if patch.end_column <= line_text.chars().count() {
-                end_offset = Some(
-                    current_offset
-                        + line_text
-                            .char_indices()
-                            .nth(patch.end_column)
-                            .map(|(i, _)| i)
-                            .unwrap_or(line_text.len()),
-                );
-            }
+        result.push_str(&source[cursor..]);
+
+        PatchOutcome {
+            source: result,
+            applied,
+            conflicts,
+            unlocated,
         }
+    }
+}
 
-        match (start_offset, end_offset) {
-            (Some(s), Some(e)) => Some((s, e)),
-            _ => None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(start: (usize, usize), end: (usize, usize), replacement: &str) -> Patch {
+        Patch {
+            start_line: start.0,
+            start_column: start.1,
+            end_line: end.0,
+            end_column: end.1,
+            replacement: replacement.to_string(),
+            description: String::new(),
         }
     }
+
+    #[test]
+    fn applies_replacements_and_insertions_across_lines() {
+        let source = "let a = x + y;\nlet b = a;\n";
+        let patches = [
+            patch((1, 8), (1, 13), "x.checked_add(y)?"),
+            patch((2, 9), (2, 9), ".max(0)"),
+            patch((1, 0), (1, 0), "env.require_auth();\n"),
+        ];
+        let outcome = Patcher::apply(source, &patches);
+        assert_eq!(
+            outcome.source,
+            "env.require_auth();\nlet a = x.checked_add(y)?;\nlet b = a.max(0);\n"
+        );
+        assert_eq!(outcome.applied, [0, 1, 2]);
+        assert!(outcome.conflicts.is_empty());
+    }
+
+    #[test]
+    fn reports_overlaps_and_merges_duplicates() {
+        let source = "let total = a + b * c;\n";
+        let patches = [
+            patch((1, 12), (1, 21), "checked(a, b, c)"),
+            patch((1, 16), (1, 21), "b.checked_mul(c)"),
+            patch((1, 12), (1, 21), "checked(a, b, c)"),
+            patch((1, 14), (1, 14), "/* inside */"),
+            patch((1, 21), (1, 21), "?"),
+        ];
+        let outcome = Patcher::apply(source, &patches);
+        assert_eq!(outcome.source, "let total = checked(a, b, c)?;\n");
+        assert_eq!(outcome.applied, [0, 4]);
+        assert_eq!(
+            outcome.conflicts,
+            [
+                PatchConflict { patch: 1, with: 0 },
+                PatchConflict { patch: 3, with: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn insertions_at_one_point_keep_their_order() {
+        let source = "fn f() {}";
+        let patches = [
+            patch((1, 8), (1, 8), " a();"),
+            patch((1, 8), (1, 8), " b();"),
+        ];
+        assert_eq!(
+            Patcher::apply_patches(source, &patches),
+            "fn f() { a(); b();}"
+        );
+    }
+
+    #[test]
+    fn reports_patches_outside_the_source() {
+        let source = "é = 1;\r\nx";
+        let patches = [
+            patch((1, 6), (1, 6), " // one"),
+            patch((1, 7), (1, 7), "!"),
+            patch((3, 0), (3, 0), "!"),
+        ];
+        let outcome = Patcher::apply(source, &patches);
+        assert_eq!(outcome.source, "é = 1; // one\r\nx");
+        assert_eq!(outcome.unlocated, [1, 2]);
+    }
 }
@@ -0,0 +1,239 @@
+//! Per-rule configuration.
+//!
+//! Built-in rules are configured in `.sanctify.toml` with one table per rule,
+//! keyed by [`Rule::name`](crate::rules::Rule::name):
+//!
+//! ```toml
+//! [rules.arithmetic_overflow]
+//! severity = "critical"
+//!
+//! [rules.unused_variable]
+//! enabled = false
+//!
+//! [rules.gas_exhaustion_risk]
+//! max_loop_bound = 1000
+//!
+//! [rules.taint_propagation]
+//! extra_sinks = ["transfer", "burn"]
+//! ```
+//!
+//! `enabled` and `severity` are understood for every rule and applied by
+//! [`RuleRegistry::configure`](crate::rules::RuleRegistry::configure).  Every
+//! other key is a rule-specific option handed to
+//! [`Rule::configure`](crate::rules::Rule::configure).  Unknown rule names and
+//! options a rule does not understand are errors, so a typo cannot silently
+//! leave a rule running with its defaults.
+//!
+//! With `strict_mode = true`, rules cannot be disabled and severities can only
+//! be raised above the catalogue default; a table lowering one is an error.
+
+use crate::finding_codes::FindingSeverity;
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+/// Rule tables keyed by rule name (`[rules.<name>]`).
+pub type RuleConfigMap = BTreeMap<String, RuleConfig>;
+
+/// A single `[rules.<name>]` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleConfig {
+    /// Set to `false` to stop the rule from running.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Severity to report the rule's findings with, replacing the catalogue
+    /// default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<FindingSeverity>,
+    /// Rule-specific options: every other key in the table.
+    #[serde(flatten)]
+    pub options: BTreeMap<String, serde_json::Value>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            severity: None,
+            options: BTreeMap::new(),
+        }
+    }
+}
+
+impl RuleConfig {
+    /// Fail if the table sets any option outside `known`.
+    pub fn expect_options(&self, known: &[&str]) -> Result<(), String> {
+        match self.options.keys().find(|k| !known.contains(&k.as_str())) {
+            None => Ok(()),
+            Some(key) if known.is_empty() => Err(format!(
+                "unknown option `{}`; this rule takes no options",
+                key
+            )),
+            Some(key) => Err(format!(
+                "unknown option `{}`; expected one of: {}",
+                key,
+                known.join(", ")
+            )),
+        }
+    }
+
+    /// Read `key` as a non-negative integer.
+    pub fn u64_option(&self, key: &str) -> Result<Option<u64>, String> {
+        match self.options.get(key) {
+            None => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| format!("`{}` must be a non-negative integer", key)),
+        }
+    }
+
+    /// Read `key` as a string.
+    pub fn string_option(&self, key: &str) -> Result<Option<String>, String> {
+        match self.options.get(key) {
+            None => Ok(None),
+            Some(value) => value
+                .as_str()
+                .map(|s| Some(s.to_string()))
+                .ok_or_else(|| format!("`{}` must be a string", key)),
+        }
+    }
+
+    /// Read `key` as a list of strings.
+    pub fn string_list_option(&self, key: &str) -> Result<Option<Vec<String>>, String> {
+        let Some(value) = self.options.get(key) else {
+            return Ok(None);
+        };
+        let invalid = || format!("`{}` must be a list of strings", key);
+        value
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|item| item.as_str().map(str::to_string).ok_or_else(invalid))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+}
+
+/// A `[rules.<name>]` table that could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("[rules.{rule}] {message}")]
+pub struct RuleConfigError {
+    /// Name of the table the error was found in.
+    pub rule: String,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl RuleConfigError {
+    pub fn new(rule: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Deserialize the `rules` key of `.sanctify.toml`.
+///
+/// Older configs used `[[rules]]` for custom regex rules.  That array shape
+/// now fails with a pointer to `[[custom_rules]]` instead of a generic type
+/// error.
+pub fn deserialize_rule_tables<'de, D>(deserializer: D) -> Result<RuleConfigMap, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct RuleTables;
+
+    impl<'de> Visitor<'de> for RuleTables {
+        type Value = RuleConfigMap;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a table of `[rules.<name>]` entries")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut tables = RuleConfigMap::new();
+            while let Some((name, config)) = map.next_entry::<String, RuleConfig>()? {
+                tables.insert(name, config);
+            }
+            Ok(tables)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, _seq: A) -> Result<Self::Value, A::Error> {
+            Err(de::Error::custom(
+                "custom regex rules are declared with `[[custom_rules]]`; \
+                 `[rules.<name>]` configures built-in rules",
+            ))
+        }
+    }
+
+    deserializer.deserialize_any(RuleTables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: serde_json::Value) -> RuleConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn defaults_to_enabled_without_override() {
+        let config = parse(serde_json::json!({}));
+        assert_eq!(config, RuleConfig::default());
+    }
+
+    #[test]
+    fn accepts_lowercase_and_rule_level_severities() {
+        let config = parse(serde_json::json!({ "severity": "critical" }));
+        assert_eq!(config.severity, Some(FindingSeverity::Critical));
+        let config = parse(serde_json::json!({ "severity": "error" }));
+        assert_eq!(config.severity, Some(FindingSeverity::High));
+    }
+
+    #[test]
+    fn collects_unknown_keys_as_options() {
+        let config = parse(serde_json::json!({ "enabled": false, "max_loop_bound": 50 }));
+        assert!(!config.enabled);
+        assert_eq!(config.u64_option("max_loop_bound"), Ok(Some(50)));
+        assert!(config.expect_options(&["max_loop_bound"]).is_ok());
+        assert!(config.expect_options(&[]).is_err());
+    }
+
+    #[test]
+    fn rejects_mistyped_options() {
+        let config = parse(serde_json::json!({ "extra_sinks": "transfer" }));
+        assert!(config.string_list_option("extra_sinks").is_err());
+        let config = parse(serde_json::json!({ "max_loop_bound": -1 }));
+        assert!(config.u64_option("max_loop_bound").is_err());
+    }
+
+    #[test]
+    fn legacy_rule_array_points_at_custom_rules() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            #[serde(deserialize_with = "deserialize_rule_tables")]
+            rules: RuleConfigMap,
+        }
+        let err = serde_json::from_value::<Wrapper>(serde_json::json!({
+            "rules": [{ "name": "no_unsafe", "pattern": "unsafe" }]
+        }))
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("[[custom_rules]]"));
+
+        let ok: Wrapper = serde_json::from_value(serde_json::json!({
+            "rules": { "auth_gap": { "enabled": false } }
+        }))
+        .unwrap();
+        assert!(!ok.rules["auth_gap"].enabled);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::fix_context::{
+    FixContext, FnFailure, DIVISION_ERRORS, OVERFLOW_ERRORS, UNDERFLOW_ERRORS,
+};
+use crate::rules::{impl_type_name, Patch, Rule, RuleViolation, Severity, SourceSpan};
+use crate::storage_layout::LayoutType;
 use crate::ArithmeticIssue;
 use std::collections::HashSet;
 use syn::spanned::Spanned;
@@ -61,6 +65,15 @@ use syn::{parse_str, File};
 /// }
 /// ```
 ///
+/// # Auto-fix
+///
+/// [`Rule::fix`] rewrites `a + b` into `a.checked_add(b)` and reports the
+/// `None` case with the contract's `#[contracterror]` enum: propagated as
+/// `.ok_or(Error::Overflow)?` when the function returns `Result<_, Error>`,
+/// otherwise through `panic_with_error!` with its `Env` parameter.  Chains
+/// such as `a * b + c` become one patch.  Without an error enum declaring an
+/// overflow-like variant no patch is offered.
+///
 /// # References
 ///
 /// - [S003 Documentation](https://github.com/HyperSafeD/Sanctifier/blob/main/docs/rules/s003-arithmetic-overflow.md)
@@ -98,6 +111,7 @@ impl Rule for ArithmeticOverflowRule {
         let mut visitor = ArithVisitor {
             issues: Vec::new(),
             current_fn: None,
+            current_impl: None,
             seen: HashSet::new(),
             index_depth: 0,
             test_mod_depth: 0,
@@ -107,28 +121,53 @@ impl Rule for ArithmeticOverflowRule {
         visitor
             .issues
             .into_iter()
-            .map(|issue| {
+            .map(|(issue, span)| {
                 RuleViolation::new(
                     self.name(),
                     Severity::Warning,
                     format!("Unchecked '{}' operation could overflow", issue.operation),
                     issue.location,
                 )
+                .with_span(span)
                 .with_suggestion(issue.suggestion)
             })
             .collect()
     }
 
+    fn fix(&self, source: &str) -> Vec<Patch> {
+        self.fix_in_crate(source, &[])
+    }
+
+    fn fix_in_crate(&self, source: &str, crate_errors: &[LayoutType]) -> Vec<Patch> {
+        let file = match parse_str::<File>(source) {
+            Ok(f) => f,
+            Err(_) => return vec![],
+        };
+
+        let context = FixContext::with_crate_errors(source, &file, crate_errors);
+        let mut fixer = ArithFixer {
+            context: &context,
+            current: None,
+            patches: Vec::new(),
+            index_depth: 0,
+            test_mod_depth: 0,
+        };
+        fixer.visit_file(&file);
+        fixer.patches
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
 
 pub(crate) struct ArithVisitor {
-    /// Issues found during AST traversal.
-    pub(crate) issues: Vec<ArithmeticIssue>,
+    /// Issues found during AST traversal, with the span of the offending expression.
+    pub(crate) issues: Vec<(ArithmeticIssue, SourceSpan)>,
     /// Current function name context (None when outside any function).
     pub(crate) current_fn: Option<String>,
+    /// Self type of the enclosing `impl` block, if any.
+    pub(crate) current_impl: Option<String>,
     /// Deduplication set: (function_name, operation) pairs already reported.
     /// Prevents multiple findings for the same operator in one function.
     pub(crate) seen: HashSet<(String, String)>,
@@ -144,6 +183,12 @@ pub(crate) struct ArithVisitor {
 // Redundant ArithmeticIssue struct removed
 
 impl ArithVisitor {
+    fn span_of(&self, span: proc_macro2::Span, fn_name: &str) -> SourceSpan {
+        SourceSpan::from_span(span)
+            .in_function(fn_name)
+            .in_contract(self.current_impl.as_deref())
+    }
+
     /// Checks if an expression is a compile-time constant.
     ///
     /// Returns `true` for:
@@ -284,6 +329,13 @@ impl<'ast> Visit<'ast> for ArithVisitor {
         }
     }
 
+    /// Visit impl block - tracks the self type reported as the enclosing contract.
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        syn::visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     /// Visit impl item function - tracks current function context for findings.
     ///
     /// Skips functions with `#[test]` attribute or inside test modules.
@@ -351,12 +403,16 @@ impl<'ast> Visit<'ast> for ArithVisitor {
                         if !self.seen.contains(&key) {
                             self.seen.insert(key);
                             let line = node.left.span().start().line;
-                            self.issues.push(ArithmeticIssue {
-                                function_name: fn_name.clone(),
-                                operation: op_str.to_string(),
-                                suggestion: suggestion.to_string(),
-                                location: format!("{}:{}", fn_name, line),
-                            });
+                            let span = self.span_of(node.span(), &fn_name);
+                            self.issues.push((
+                                ArithmeticIssue {
+                                    function_name: fn_name.clone(),
+                                    operation: op_str.to_string(),
+                                    suggestion: suggestion.to_string(),
+                                    location: format!("{}:{}", fn_name, line),
+                                },
+                                span,
+                            ));
                         }
                     }
                 }
@@ -385,12 +441,16 @@ impl<'ast> Visit<'ast> for ArithVisitor {
                 if !self.seen.contains(&key) {
                     self.seen.insert(key);
                     let line = node.span().start().line;
-                    self.issues.push(ArithmeticIssue {
-                        function_name: fn_name.clone(),
-                        operation: method_name,
-                        suggestion,
-                        location: format!("{}:{}", fn_name, line),
-                    });
+                    let span = self.span_of(node.span(), &fn_name);
+                    self.issues.push((
+                        ArithmeticIssue {
+                            function_name: fn_name.clone(),
+                            operation: method_name,
+                            suggestion,
+                            location: format!("{}:{}", fn_name, line),
+                        },
+                        span,
+                    ));
                 }
             }
         }
@@ -415,12 +475,16 @@ impl<'ast> Visit<'ast> for ArithVisitor {
                         if !self.seen.contains(&key) {
                             self.seen.insert(key);
                             let line = node.span().start().line;
-                            self.issues.push(ArithmeticIssue {
-                                function_name: fn_name.clone(),
-                                operation: func_name,
-                                suggestion,
-                                location: format!("{}:{}", fn_name, line),
-                            });
+                            let span = self.span_of(node.span(), &fn_name);
+                            self.issues.push((
+                                ArithmeticIssue {
+                                    function_name: fn_name.clone(),
+                                    operation: func_name,
+                                    suggestion,
+                                    location: format!("{}:{}", fn_name, line),
+                                },
+                                span,
+                            ));
                         }
                     }
                 }
@@ -430,6 +494,185 @@ impl<'ast> Visit<'ast> for ArithVisitor {
     }
 }
 
+/// Collects [`Patch`]es rewriting unchecked arithmetic in the functions that
+/// can report a contract error.  Skips the same code [`ArithVisitor`] does,
+/// plus closures, where `?` would not return from the function.
+struct ArithFixer<'c> {
+    context: &'c FixContext<'c>,
+    /// Name of the function being visited and how it fails.
+    current: Option<(String, FnFailure<'c>)>,
+    patches: Vec<Patch>,
+    index_depth: u32,
+    test_mod_depth: u32,
+}
+
+impl<'c> ArithFixer<'c> {
+    fn visit_fn(&mut self, attrs: &[syn::Attribute], sig: &syn::Signature, block: &syn::Block) {
+        if self.test_mod_depth > 0 || has_test_attr(attrs) {
+            return;
+        }
+        let current = self
+            .context
+            .failure(sig)
+            .map(|failure| (sig.ident.to_string(), failure));
+        let prev = std::mem::replace(&mut self.current, current);
+        self.visit_block(block);
+        self.current = prev;
+    }
+}
+
+impl<'ast, 'c> Visit<'ast> for ArithFixer<'c> {
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        let is_test = is_cfg_test(&node.attrs);
+        self.test_mod_depth += u32::from(is_test);
+        syn::visit::visit_item_mod(self, node);
+        self.test_mod_depth -= u32::from(is_test);
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        self.visit_fn(&node.attrs, &node.sig, &node.block);
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        self.visit_fn(&node.attrs, &node.sig, &node.block);
+    }
+
+    fn visit_expr_closure(&mut self, _node: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_index(&mut self, node: &'ast syn::ExprIndex) {
+        self.visit_expr(&node.expr);
+        self.index_depth += 1;
+        self.visit_expr(&node.index);
+        self.index_depth -= 1;
+    }
+
+    fn visit_expr_binary(&mut self, node: &'ast syn::ExprBinary) {
+        if self.index_depth == 0 {
+            if let Some((fn_name, failure)) = &self.current {
+                if let Some(replacement) = checked_binary(self.context, failure, node) {
+                    let (op_str, _) = ArithVisitor::classify_op(&node.op).unwrap_or_default();
+                    let (method, _) = checked_method(&node.op).unwrap_or_default();
+                    let span = node.span();
+                    self.patches.push(Patch {
+                        start_line: span.start().line,
+                        start_column: span.start().column,
+                        end_line: span.end().line,
+                        end_column: span.end().column,
+                        replacement,
+                        description: format!(
+                            "Replace unchecked '{}' with {} in function '{}'",
+                            op_str, method, fn_name
+                        ),
+                    });
+                    return;
+                }
+            }
+        }
+        syn::visit::visit_expr_binary(self, node);
+    }
+}
+
+/// `checked_*` method replacing an arithmetic operator, with the error
+/// variants its `None` may be reported as.
+fn checked_method(op: &syn::BinOp) -> Option<(&'static str, &'static [&'static str])> {
+    match op {
+        syn::BinOp::Add(_) | syn::BinOp::AddAssign(_) => Some(("checked_add", OVERFLOW_ERRORS)),
+        syn::BinOp::Sub(_) | syn::BinOp::SubAssign(_) => Some(("checked_sub", UNDERFLOW_ERRORS)),
+        syn::BinOp::Mul(_) | syn::BinOp::MulAssign(_) => Some(("checked_mul", OVERFLOW_ERRORS)),
+        syn::BinOp::Div(_) | syn::BinOp::DivAssign(_) => Some(("checked_div", DIVISION_ERRORS)),
+        syn::BinOp::Rem(_) | syn::BinOp::RemAssign(_) => Some(("checked_rem", DIVISION_ERRORS)),
+        _ => None,
+    }
+}
+
+/// The checked form of `node`, e.g. `a.checked_add(b).ok_or(Error::Overflow)?`
+/// or, for `a += b`, `a = a.checked_add(b)...`.
+fn checked_binary(
+    context: &FixContext<'_>,
+    failure: &FnFailure<'_>,
+    node: &syn::ExprBinary,
+) -> Option<String> {
+    let (method, errors) = checked_method(&node.op)?;
+    if is_string_literal(&node.left)
+        || is_string_literal(&node.right)
+        || matches!(*node.right, syn::Expr::Reference(_))
+        || crate::constant_folding::is_foldable_constant(&syn::Expr::Binary(node.clone()))
+    {
+        return None;
+    }
+    let error = failure.variant(errors)?;
+    let compound = matches!(
+        node.op,
+        syn::BinOp::AddAssign(_)
+            | syn::BinOp::SubAssign(_)
+            | syn::BinOp::MulAssign(_)
+            | syn::BinOp::DivAssign(_)
+            | syn::BinOp::RemAssign(_)
+    );
+
+    // An unsuffixed literal has no type to call methods on: `2 * x` is
+    // rewritten as `x.checked_mul(2)`, `2 - x` is left alone.
+    let commutative = matches!(node.op, syn::BinOp::Add(_) | syn::BinOp::Mul(_));
+    let (left, right) = if commutative
+        && crate::constant_folding::is_foldable_constant(&node.left)
+        && !crate::constant_folding::is_foldable_constant(&node.right)
+    {
+        (&node.right, &node.left)
+    } else {
+        (&node.left, &node.right)
+    };
+    if crate::constant_folding::is_foldable_constant(left) {
+        return None;
+    }
+
+    let receiver = operand(context, failure, left, true)?;
+    let argument = operand(context, failure, right, false)?;
+    let checked = failure.on_none(&format!("{}.{}({})", receiver, method, argument), &error);
+    if compound {
+        Some(format!("{} = {}", context.text(node.left.span())?, checked))
+    } else {
+        Some(checked)
+    }
+}
+
+/// Source text of an operand, with nested arithmetic checked as well.  A
+/// method receiver that is not a postfix expression is parenthesized.
+fn operand(
+    context: &FixContext<'_>,
+    failure: &FnFailure<'_>,
+    expr: &syn::Expr,
+    receiver: bool,
+) -> Option<String> {
+    let nested = match expr {
+        syn::Expr::Paren(p) => p.expr.as_ref(),
+        _ => expr,
+    };
+    if let syn::Expr::Binary(b) = nested {
+        if let Some(checked) = checked_binary(context, failure, b) {
+            return Some(checked);
+        }
+    }
+    let text = context.text(expr.span())?;
+    let postfix = matches!(
+        expr,
+        syn::Expr::Path(_)
+            | syn::Expr::Field(_)
+            | syn::Expr::MethodCall(_)
+            | syn::Expr::Call(_)
+            | syn::Expr::Lit(_)
+            | syn::Expr::Paren(_)
+            | syn::Expr::Index(_)
+            | syn::Expr::Macro(_)
+            | syn::Expr::Try(_)
+            | syn::Expr::Tuple(_)
+    );
+    if receiver && !postfix {
+        Some(format!("({})", text))
+    } else {
+        Some(text.to_string())
+    }
+}
+
 /// Classifies custom math method calls that lack overflow protection.
 ///
 /// # Detected Patterns
@@ -688,4 +931,76 @@ mod tests {
             "arithmetic with a non-constant operand must still be flagged"
         );
     }
+
+    const ERRORS: &str = r#"
+        #[contracterror]
+        pub enum Error {
+            NotFound = 1,
+            Overflow = 2,
+            Underflow = 3,
+        }
+    "#;
+
+    fn fixed(source: &str) -> String {
+        let source = format!("{}{}", ERRORS, source);
+        let patches = ArithmeticOverflowRule::new().fix(&source);
+        crate::patcher::Patcher::apply_patches(&source, &patches)
+    }
+
+    #[test]
+    fn fix_propagates_contract_error_from_result_function() {
+        let fixed = fixed(
+            r#"
+            impl Token {
+                pub fn mint(env: Env, balance: i128, amount: i128, fee: i128) -> Result<i128, Error> {
+                    let total = balance * 2 + amount;
+                    let net = total - fee;
+                    Ok(net)
+                }
+            }
+        "#,
+        );
+        assert!(fixed.contains(
+            "let total = balance.checked_mul(2).ok_or(Error::Overflow)?.checked_add(amount).ok_or(Error::Overflow)?;"
+        ));
+        assert!(fixed.contains("let net = total.checked_sub(fee).ok_or(Error::Underflow)?;"));
+        assert!(ArithmeticOverflowRule::new().check(&fixed).is_empty());
+    }
+
+    #[test]
+    fn fix_aborts_with_panic_with_error_outside_result_functions() {
+        let fixed = fixed(
+            r#"
+            impl Token {
+                pub fn burn(env: Env, mut supply: i128, amount: i128) {
+                    supply -= amount;
+                    let doubled = 2 * (supply + amount);
+                }
+            }
+        "#,
+        );
+        assert!(fixed.contains(
+            "supply = supply.checked_sub(amount).unwrap_or_else(|| soroban_sdk::panic_with_error!(env, Error::Underflow));"
+        ));
+        assert!(fixed.contains(
+            "let doubled = supply.checked_add(amount).unwrap_or_else(|| soroban_sdk::panic_with_error!(env, Error::Overflow)).checked_mul(2)"
+        ));
+    }
+
+    #[test]
+    fn fix_needs_an_error_enum_and_a_way_to_fail() {
+        let rule = ArithmeticOverflowRule::new();
+        let without_errors = r#"
+            impl Token {
+                pub fn mint(env: Env, a: i128, b: i128) -> i128 { a + b }
+            }
+        "#;
+        assert!(rule.fix(without_errors).is_empty());
+
+        let without_env = format!(
+            "{}{}",
+            ERRORS, "impl Token { pub fn add(a: i128, b: i128) -> i128 { a + b } }"
+        );
+        assert!(rule.fix(&without_env).is_empty());
+    }
 }
@@ -1,22 +1,41 @@
 use crate::input_validation::{validate_no_null_bytes, validate_source_size};
-use crate::rules::{Patch, Rule, RuleViolation, Severity};
+use crate::interprocedural::{qualified_name, CallSummaries};
+use crate::rules::{impl_type_name, Patch, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
 /// Rule that flags public functions modifying state without auth.
+///
+/// Calls to other functions in the same file, or with
+/// [`Rule::check_in_crate`] anywhere in the crate, count through their
+/// [`CallSummaries`]: a helper that writes storage makes its caller
+/// privileged, and a helper that calls `require_auth` authorizes it.
 pub struct AuthGapRule;
 
+/// What a single function body does, ignoring its callees.
 #[derive(Default)]
-struct FunctionSecuritySummary {
-    has_mutation: bool,
-    has_auth: bool,
-    has_external_call: bool,
+pub(crate) struct FunctionSecuritySummary {
+    pub(crate) has_mutation: bool,
+    pub(crate) has_auth: bool,
+    pub(crate) has_external_call: bool,
 }
 
 impl FunctionSecuritySummary {
     fn has_sensitive_action(&self) -> bool {
         self.has_mutation || self.has_external_call
     }
+
+    /// Facts for `f` in `impl owner`, merged with everything its callees do.
+    fn for_fn(summaries: &CallSummaries, owner: Option<&str>, f: &syn::ImplItemFn) -> Self {
+        let mut summary = Self::default();
+        check_fn_body(&f.block, &mut summary);
+        if let Some(s) = summaries.get(&qualified_name(owner, &f.sig.ident.to_string())) {
+            summary.has_mutation |= s.mutates_storage;
+            summary.has_auth |= s.requires_auth;
+            summary.has_external_call |= s.calls_external;
+        }
+        summary
+    }
 }
 
 fn is_reserved_soroban_entrypoint(fn_name: &str) -> bool {
@@ -46,6 +65,10 @@ impl Rule for AuthGapRule {
     }
 
     fn check(&self, source: &str) -> Vec<RuleViolation> {
+        self.check_in_crate(source, &[])
+    }
+
+    fn check_in_crate(&self, source: &str, crate_sources: &[String]) -> Vec<RuleViolation> {
         // Guard: empty source has no findings (fast-path, no parse needed).
         if let Err(e) = validate_source_size(source) {
             if e.code == "EMPTY_SOURCE" {
@@ -82,9 +105,11 @@ impl Rule for AuthGapRule {
             Err(_) => return vec![],
         };
 
+        let summaries = CallSummaries::from_sources(&file, crate_sources, &[]);
         let mut gaps = Vec::new();
         for item in &file.items {
             if let Item::Impl(i) = item {
+                let owner = impl_type_name(i);
                 for impl_item in &i.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
                         if let syn::Visibility::Public(_) = f.vis {
@@ -93,15 +118,17 @@ impl Rule for AuthGapRule {
                                 continue;
                             }
                             let fn_line = f.sig.ident.span().start().line;
-                            let mut summary = FunctionSecuritySummary::default();
-                            check_fn_body(&f.block, &mut summary);
+                            let summary =
+                                FunctionSecuritySummary::for_fn(&summaries, owner.as_deref(), f);
                             if summary.has_sensitive_action() && !summary.has_auth {
                                 gaps.push(RuleViolation::new(
                                     self.name(),
                                     Severity::Warning,
                                     format!("Function '{}' performs a privileged operation without authentication", fn_name),
                                     format!("{}:{}", fn_name, fn_line),
-                                ).with_suggestion("Add require_auth() or require_auth_for_args() before storage operations or external contract calls".to_string()));
+                                )
+                                .with_span(SourceSpan::from_span(f.sig.span()).in_impl(i).in_function(&fn_name))
+                                .with_suggestion("Add require_auth() or require_auth_for_args() before storage operations or external contract calls".to_string()));
                             }
                         }
                     }
@@ -117,17 +144,19 @@ impl Rule for AuthGapRule {
             Err(_) => return vec![],
         };
 
+        let summaries = CallSummaries::from_file(&file, &[]);
         let mut patches = Vec::new();
         for item in &file.items {
             if let Item::Impl(i) = item {
+                let owner = impl_type_name(i);
                 for impl_item in &i.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
                         if let syn::Visibility::Public(_) = f.vis {
                             if is_reserved_soroban_entrypoint(&f.sig.ident.to_string()) {
                                 continue;
                             }
-                            let mut summary = FunctionSecuritySummary::default();
-                            check_fn_body(&f.block, &mut summary);
+                            let summary =
+                                FunctionSecuritySummary::for_fn(&summaries, owner.as_deref(), f);
                             if summary.has_sensitive_action() && !summary.has_auth {
                                 // Add require_auth() as the first statement in the function
                                 if let Some(first_stmt) = f.block.stmts.first() {
@@ -172,7 +201,7 @@ impl Rule for AuthGapRule {
     }
 }
 
-fn check_fn_body(block: &syn::Block, summary: &mut FunctionSecuritySummary) {
+pub(crate) fn check_fn_body(block: &syn::Block, summary: &mut FunctionSecuritySummary) {
     for stmt in &block.stmts {
         match stmt {
             syn::Stmt::Expr(expr, _) => check_expr(expr, summary),
@@ -392,6 +421,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn storage_write_in_private_helper_is_attributed_to_caller() {
+        let rule = AuthGapRule::new();
+        let source = r#"
+            impl MyContract {
+                pub fn set_admin(env: Env, new_admin: Address) {
+                    Self::write_admin(&env, &new_admin);
+                }
+                fn write_admin(env: &Env, admin: &Address) {
+                    env.storage().instance().set(&symbol_short!("admin"), admin);
+                }
+            }
+        "#;
+        let violations = rule.check(source);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("set_admin"));
+    }
+
+    #[test]
+    fn require_auth_in_helper_authorizes_caller() {
+        let rule = AuthGapRule::new();
+        let source = r#"
+            fn ensure_admin(env: &Env) {
+                read_admin(env).require_auth();
+            }
+            impl MyContract {
+                pub fn set_fee(env: Env, fee: u32) {
+                    ensure_admin(&env);
+                    env.storage().instance().set(&symbol_short!("fee"), &fee);
+                }
+            }
+        "#;
+        assert!(rule.check(source).is_empty());
+    }
+
     #[test]
     fn invalid_source_produces_no_panic() {
         let rule = AuthGapRule::new();
@@ -458,6 +522,19 @@ mod tests {
         assert!(line > 0, "line number must be positive");
     }
 
+    #[test]
+    fn violation_span_covers_signature_and_enclosing_items() {
+        let rule = AuthGapRule::new();
+        let source = "impl MyContract {\n    pub fn set_value(env: Env, v: u32) {\n        env.storage().persistent().set(&symbol_short!(\"V\"), &v);\n    }\n}\n";
+        let violations = rule.check(source);
+        let span = violations[0].span.as_ref().expect("span must be set");
+        assert_eq!((span.start_line, span.start_column), (2, 8));
+        assert_eq!(span.end_line, 2);
+        assert_eq!(span.function.as_deref(), Some("set_value"));
+        assert_eq!(span.contract.as_deref(), Some("MyContract"));
+        assert_eq!(span.file, None);
+    }
+
     #[test]
     fn whitespace_only_source_produces_no_findings() {
         let rule = AuthGapRule::new();
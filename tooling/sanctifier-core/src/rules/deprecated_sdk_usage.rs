@@ -27,7 +27,7 @@
 //! let rule = DeprecatedSdkUsageRule::with_sdk_major(major);
 //! ```
 
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::visit::Visit;
 use syn::{parse_str, File};
@@ -96,6 +96,7 @@ impl Rule for DeprecatedSdkUsageRule {
         let mut visitor = DeprecatedVisitor {
             violations: Vec::new(),
             current_fn: None,
+            current_impl: None,
             test_mod_depth: 0,
         };
         visitor.visit_file(&file);
@@ -105,6 +106,7 @@ impl Rule for DeprecatedSdkUsageRule {
             .into_iter()
             .map(|v| {
                 RuleViolation::new(self.name(), Severity::Warning, v.message, v.location)
+                    .with_span(v.span)
                     .with_suggestion(v.suggestion)
             })
             .collect()
@@ -120,15 +122,27 @@ impl Rule for DeprecatedSdkUsageRule {
 struct PendingViolation {
     message: String,
     location: String,
+    span: SourceSpan,
     suggestion: String,
 }
 
 struct DeprecatedVisitor {
     violations: Vec<PendingViolation>,
     current_fn: Option<String>,
+    current_impl: Option<String>,
     test_mod_depth: u32,
 }
 
+impl DeprecatedVisitor {
+    fn span_of(&self, span: proc_macro2::Span) -> SourceSpan {
+        let span = SourceSpan::from_span(span).in_contract(self.current_impl.as_deref());
+        match &self.current_fn {
+            Some(fn_name) => span.in_function(fn_name),
+            None => span,
+        }
+    }
+}
+
 impl<'ast> Visit<'ast> for DeprecatedVisitor {
     // ── Skip #[cfg(test)] modules ────────────────────────────────────────────
     fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
@@ -141,6 +155,12 @@ impl<'ast> Visit<'ast> for DeprecatedVisitor {
         }
     }
 
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        syn::visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
         if self.test_mod_depth > 0 || has_test_attr(&node.attrs) {
             return;
@@ -181,6 +201,7 @@ impl<'ast> Visit<'ast> for DeprecatedVisitor {
                              use `extend_ttl()` instead (in `{fn_name}` at line {line})"
                         ),
                         location: format!("{fn_name}:{line}"),
+                        span: self.span_of(node.span()),
                         suggestion: "Replace `.bump(ledgers)` with \
                             `.extend_ttl(min_ledgers_to_live, max_ledgers_to_live)`. \
                             Both the minimum and maximum ledger bounds must be provided."
@@ -201,6 +222,7 @@ impl<'ast> Visit<'ast> for DeprecatedVisitor {
                              (in `{fn_name}` at line {line})"
                         ),
                         location: format!("{fn_name}:{line}"),
+                        span: self.span_of(node.span()),
                         suggestion: "Replace `env.deployer().deploy(wasm_hash, salt)` with \
                             `env.deployer().with_address(contract_id, salt).deploy_v2(wasm_hash, ctor_args)` \
                             or `env.deployer().with_current_contract(salt).deploy_v2(wasm_hash, ctor_args)`."
@@ -231,6 +253,7 @@ impl<'ast> Visit<'ast> for DeprecatedVisitor {
                              (in `{fn_name}` at line {line})"
                         ),
                         location: format!("{fn_name}:{line}"),
+                        span: self.span_of(segment.ident.span()),
                         suggestion: "Replace `RawVal` with `Val` throughout. \
                             `Val` is the unified host value type in Soroban SDK v22+."
                             .to_string(),
@@ -254,6 +277,7 @@ impl<'ast> Visit<'ast> for DeprecatedVisitor {
                              (in `{fn_name}` at line {line})"
                         ),
                         location: format!("{fn_name}:{line}"),
+                        span: self.span_of(node.span()),
                         suggestion: "Replace `RawVal` with `Val` throughout. \
                             `Val` is the unified host value type in Soroban SDK v22+."
                             .to_string(),
@@ -10,16 +10,34 @@
 //! This rule flags `for`/`while` loops inside `impl` methods whose bound
 //! traces directly back to such a parameter, unless the bound expression
 //! itself is clamped via `.min(...)`/`.saturating_sub(...)`/`.take(...)`.
-
-use super::{Rule, RuleViolation, Severity};
+//!
+//! The `max_loop_bound` option (`[rules.gas_exhaustion_risk]`) caps what a
+//! clamp may allow: `.take(1_000_000)` does not count as a bound when the
+//! project sets `max_loop_bound = 1000`.  Clamp bounds given as integer
+//! literals or `const` items are checked; any other bound is trusted.
+
+use super::{Rule, RuleViolation, Severity, SourceSpan};
+use crate::constant_folding::fold_to_i128;
+use crate::rule_config::RuleConfig;
+use std::collections::HashMap;
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
-pub struct GasExhaustionRiskRule;
+pub struct GasExhaustionRiskRule {
+    max_loop_bound: Option<u64>,
+}
 
 impl GasExhaustionRiskRule {
     pub fn new() -> Self {
-        Self
+        Self {
+            max_loop_bound: None,
+        }
+    }
+
+    /// Only accept clamps whose bound is at most `max` iterations.
+    pub fn with_max_loop_bound(mut self, max: u64) -> Self {
+        self.max_loop_bound = Some(max);
+        self
     }
 }
 
@@ -38,12 +56,24 @@ impl Rule for GasExhaustionRiskRule {
         "Detects loops whose iteration count derives from an unbounded user-controlled parameter (S031)"
     }
 
+    fn configure(&mut self, config: &RuleConfig) -> Result<(), String> {
+        config.expect_options(&["max_loop_bound"])?;
+        if let Some(max) = config.u64_option("max_loop_bound")? {
+            self.max_loop_bound = Some(max);
+        }
+        Ok(())
+    }
+
     fn check(&self, source: &str) -> Vec<RuleViolation> {
         let file = match parse_str::<File>(source) {
             Ok(f) => f,
             Err(_) => return vec![],
         };
 
+        let limit = ClampLimit {
+            max: self.max_loop_bound,
+            consts: const_values(&file),
+        };
         let mut violations = Vec::new();
 
         for item in &file.items {
@@ -57,9 +87,9 @@ impl Rule for GasExhaustionRiskRule {
                         }
 
                         let mut findings = Vec::new();
-                        scan_block(&f.block, &fn_name, &unbounded_params, &mut findings);
+                        scan_block(&f.block, &fn_name, &unbounded_params, &limit, &mut findings);
 
-                        for (location, reason) in findings {
+                        for (location, span, reason) in findings {
                             violations.push(
                                 RuleViolation::new(
                                     self.name(),
@@ -70,6 +100,11 @@ impl Rule for GasExhaustionRiskRule {
                                     ),
                                     location,
                                 )
+                                .with_span(
+                                    SourceSpan::from_span(span)
+                                        .in_impl(impl_block)
+                                        .in_function(&fn_name),
+                                )
                                 .with_suggestion(
                                     "Cap the iteration count with a fixed maximum, e.g. `.iter().take(MAX_ITEMS)` \
                                     or an explicit `if param.len() > MAX_ITEMS { return Err(...) }` check before \
@@ -130,20 +165,69 @@ fn type_is_unbounded(ty: &syn::Type) -> bool {
     }
 }
 
+/// Integer values of the file's `const` items, so clamp bounds written as
+/// `MAX_ITEMS` can be compared against `max_loop_bound`.
+pub(super) fn const_values(file: &File) -> HashMap<String, i128> {
+    let mut consts = HashMap::new();
+    let mut record = |ident: &syn::Ident, expr: &syn::Expr| {
+        if let Some(value) = fold_to_i128(expr) {
+            consts.insert(ident.to_string(), value);
+        }
+    };
+    for item in &file.items {
+        match item {
+            Item::Const(c) => record(&c.ident, &c.expr),
+            Item::Impl(impl_block) => {
+                for impl_item in &impl_block.items {
+                    if let syn::ImplItem::Const(c) = impl_item {
+                        record(&c.ident, &c.expr);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    consts
+}
+
+/// The largest bound a clamp may allow and still count as bounding a loop.
+struct ClampLimit {
+    max: Option<u64>,
+    consts: HashMap<String, i128>,
+}
+
+impl ClampLimit {
+    fn accepts(&self, bound: &syn::Expr) -> bool {
+        let Some(max) = self.max else {
+            return true;
+        };
+        let value = match bound {
+            syn::Expr::Path(p) => p
+                .path
+                .segments
+                .last()
+                .and_then(|seg| self.consts.get(&seg.ident.to_string()).copied()),
+            _ => fold_to_i128(bound),
+        };
+        !matches!(value, Some(v) if v > i128::from(max))
+    }
+}
+
 /// Walk a block looking for `for`/`while` loops whose bound traces back to
 /// an unbounded parameter.
 fn scan_block(
     block: &syn::Block,
     fn_name: &str,
     unbounded_params: &[String],
-    findings: &mut Vec<(String, String)>,
+    limit: &ClampLimit,
+    findings: &mut Vec<(String, proc_macro2::Span, String)>,
 ) {
     for stmt in &block.stmts {
         match stmt {
-            syn::Stmt::Expr(expr, _) => scan_expr(expr, fn_name, unbounded_params, findings),
+            syn::Stmt::Expr(expr, _) => scan_expr(expr, fn_name, unbounded_params, limit, findings),
             syn::Stmt::Local(local) => {
                 if let Some(init) = &local.init {
-                    scan_expr(&init.expr, fn_name, unbounded_params, findings);
+                    scan_expr(&init.expr, fn_name, unbounded_params, limit, findings);
                 }
             }
             _ => {}
@@ -155,34 +239,35 @@ fn scan_expr(
     expr: &syn::Expr,
     fn_name: &str,
     unbounded_params: &[String],
-    findings: &mut Vec<(String, String)>,
+    limit: &ClampLimit,
+    findings: &mut Vec<(String, proc_macro2::Span, String)>,
 ) {
     match expr {
         syn::Expr::ForLoop(f) => {
-            if let Some(reason) = unbounded_iterator_reason(&f.expr, unbounded_params) {
+            if let Some(reason) = unbounded_iterator_reason(&f.expr, unbounded_params, limit) {
                 let line = f.span().start().line;
-                findings.push((format!("{fn_name}:line {line}"), reason));
+                findings.push((format!("{fn_name}:line {line}"), f.span(), reason));
             }
-            scan_block(&f.body, fn_name, unbounded_params, findings);
+            scan_block(&f.body, fn_name, unbounded_params, limit, findings);
         }
         syn::Expr::While(w) => {
-            if let Some(reason) = unbounded_condition_reason(&w.cond, unbounded_params) {
+            if let Some(reason) = unbounded_condition_reason(&w.cond, unbounded_params, limit) {
                 let line = w.span().start().line;
-                findings.push((format!("{fn_name}:line {line}"), reason));
+                findings.push((format!("{fn_name}:line {line}"), w.span(), reason));
             }
-            scan_block(&w.body, fn_name, unbounded_params, findings);
+            scan_block(&w.body, fn_name, unbounded_params, limit, findings);
         }
-        syn::Expr::Loop(l) => scan_block(&l.body, fn_name, unbounded_params, findings),
-        syn::Expr::Block(b) => scan_block(&b.block, fn_name, unbounded_params, findings),
+        syn::Expr::Loop(l) => scan_block(&l.body, fn_name, unbounded_params, limit, findings),
+        syn::Expr::Block(b) => scan_block(&b.block, fn_name, unbounded_params, limit, findings),
         syn::Expr::If(i) => {
-            scan_block(&i.then_branch, fn_name, unbounded_params, findings);
+            scan_block(&i.then_branch, fn_name, unbounded_params, limit, findings);
             if let Some((_, else_expr)) = &i.else_branch {
-                scan_expr(else_expr, fn_name, unbounded_params, findings);
+                scan_expr(else_expr, fn_name, unbounded_params, limit, findings);
             }
         }
         syn::Expr::Match(m) => {
             for arm in &m.arms {
-                scan_expr(&arm.body, fn_name, unbounded_params, findings);
+                scan_expr(&arm.body, fn_name, unbounded_params, limit, findings);
             }
         }
         _ => {}
@@ -192,8 +277,12 @@ fn scan_expr(
 /// If `iter_expr` (the expression after `in` in a `for x in <iter_expr>`)
 /// iterates the full length of an unbounded parameter without a clamp,
 /// returns a human-readable reason. Otherwise `None`.
-fn unbounded_iterator_reason(iter_expr: &syn::Expr, unbounded_params: &[String]) -> Option<String> {
-    if expr_contains_clamp(iter_expr) {
+fn unbounded_iterator_reason(
+    iter_expr: &syn::Expr,
+    unbounded_params: &[String],
+    limit: &ClampLimit,
+) -> Option<String> {
+    if expr_contains_clamp(iter_expr, limit) {
         return None;
     }
     match iter_expr {
@@ -202,10 +291,10 @@ fn unbounded_iterator_reason(iter_expr: &syn::Expr, unbounded_params: &[String])
             if let Some(param) = root_receiver_param(&m.receiver, unbounded_params) {
                 return Some(format!("iterates the full length of parameter '{param}'"));
             }
-            unbounded_iterator_reason(&m.receiver, unbounded_params)
+            unbounded_iterator_reason(&m.receiver, unbounded_params, limit)
         }
-        syn::Expr::Reference(r) => unbounded_iterator_reason(&r.expr, unbounded_params),
-        syn::Expr::Paren(p) => unbounded_iterator_reason(&p.expr, unbounded_params),
+        syn::Expr::Reference(r) => unbounded_iterator_reason(&r.expr, unbounded_params, limit),
+        syn::Expr::Paren(p) => unbounded_iterator_reason(&p.expr, unbounded_params, limit),
         // `0..param` or `0..param.len()`
         syn::Expr::Range(r) => {
             let end = r.end.as_deref()?;
@@ -218,8 +307,12 @@ fn unbounded_iterator_reason(iter_expr: &syn::Expr, unbounded_params: &[String])
 
 /// If `cond` (a `while` condition) compares a loop counter against an
 /// unbounded parameter (or its `.len()`) without a clamp, returns a reason.
-fn unbounded_condition_reason(cond: &syn::Expr, unbounded_params: &[String]) -> Option<String> {
-    if expr_contains_clamp(cond) {
+fn unbounded_condition_reason(
+    cond: &syn::Expr,
+    unbounded_params: &[String],
+    limit: &ClampLimit,
+) -> Option<String> {
+    if expr_contains_clamp(cond, limit) {
         return None;
     }
     let syn::Expr::Binary(b) = cond else {
@@ -268,22 +361,26 @@ fn root_receiver_param(expr: &syn::Expr, unbounded_params: &[String]) -> Option<
 
 /// Returns true if `expr` contains a clamp-style call (`.min(`, `.take(`,
 /// `.saturating_sub(`, `.truncate(`) anywhere in its method-call chain,
-/// signalling the author already bounded the iteration count.
-fn expr_contains_clamp(expr: &syn::Expr) -> bool {
+/// signalling the author already bounded the iteration count.  Clamps whose
+/// bound exceeds `limit` do not count.
+fn expr_contains_clamp(expr: &syn::Expr, limit: &ClampLimit) -> bool {
     match expr {
         syn::Expr::MethodCall(m) => {
-            let method = m.method.to_string();
-            matches!(
-                method.as_str(),
-                "min" | "take" | "saturating_sub" | "truncate" | "clamp"
-            ) || expr_contains_clamp(&m.receiver)
+            let bound = match m.method.to_string().as_str() {
+                "min" | "take" | "truncate" => m.args.first(),
+                "clamp" => m.args.iter().nth(1),
+                "saturating_sub" => None,
+                _ => return expr_contains_clamp(&m.receiver, limit),
+            };
+            !matches!(bound, Some(b) if !limit.accepts(b))
+                || expr_contains_clamp(&m.receiver, limit)
         }
-        syn::Expr::Reference(r) => expr_contains_clamp(&r.expr),
-        syn::Expr::Paren(p) => expr_contains_clamp(&p.expr),
+        syn::Expr::Reference(r) => expr_contains_clamp(&r.expr, limit),
+        syn::Expr::Paren(p) => expr_contains_clamp(&p.expr, limit),
         syn::Expr::Range(r) => r
             .end
             .as_deref()
-            .map(expr_contains_clamp)
+            .map(|end| expr_contains_clamp(end, limit))
             .unwrap_or(false),
         _ => false,
     }
@@ -425,6 +522,42 @@ mod tests {
         assert!(violations.is_empty());
     }
 
+    #[test]
+    fn max_loop_bound_rejects_clamps_above_the_limit() {
+        let rule = GasExhaustionRiskRule::new().with_max_loop_bound(100);
+        let source = r#"
+            const MAX_BATCH: u32 = 50;
+            impl Batch {
+                pub fn small(env: Env, recipients: Vec<Address>) {
+                    for r in recipients.iter().take(MAX_BATCH) {
+                        do_transfer(&env, r);
+                    }
+                }
+                pub fn huge(env: Env, recipients: Vec<Address>) {
+                    for r in recipients.iter().take(1_000_000) {
+                        do_transfer(&env, r);
+                    }
+                }
+            }
+        "#;
+        let violations = rule.check(source);
+        assert_eq!(violations.len(), 1, "{violations:?}");
+        assert!(violations[0].message.contains("huge"));
+    }
+
+    #[test]
+    fn configure_reads_max_loop_bound() {
+        let mut rule = GasExhaustionRiskRule::new();
+        let config: RuleConfig =
+            serde_json::from_value(serde_json::json!({ "max_loop_bound": 10 })).unwrap();
+        rule.configure(&config).unwrap();
+        assert_eq!(rule.max_loop_bound, Some(10));
+
+        let typo: RuleConfig =
+            serde_json::from_value(serde_json::json!({ "max_loop_bnd": 10 })).unwrap();
+        assert!(rule.configure(&typo).is_err());
+    }
+
     #[test]
     fn empty_source_produces_no_findings() {
         let rule = GasExhaustionRiskRule::new();
@@ -7,7 +7,8 @@
 //! `Persistent` (or `Temporary`) storage, where each key is its own ledger
 //! entry with independent rent.
 
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Rule, RuleViolation, Severity, SourceSpan};
+use syn::spanned::Spanned;
 use syn::{
     parse_str,
     visit::{self, Visit},
@@ -46,12 +47,14 @@ const WRITE_OPS: &[&str] = &["set", "update", "try_update"];
 struct InstanceMisuseVisitor {
     violations: Vec<InstanceMisuseViolation>,
     current_fn: String,
+    current_impl: Option<String>,
 }
 
 struct InstanceMisuseViolation {
     fn_name: String,
     key_hint: String,
     line: usize,
+    span: SourceSpan,
 }
 
 impl InstanceMisuseVisitor {
@@ -59,6 +62,7 @@ impl InstanceMisuseVisitor {
         Self {
             violations: Vec::new(),
             current_fn: String::new(),
+            current_impl: None,
         }
     }
 
@@ -81,7 +85,13 @@ impl InstanceMisuseVisitor {
 }
 
 impl<'ast> Visit<'ast> for InstanceMisuseVisitor {
-    // Track which function we are currently inside.
+    // Track which impl block and function we are currently inside.
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
         let prev = std::mem::replace(&mut self.current_fn, node.sig.ident.to_string());
         visit::visit_impl_item_fn(self, node);
@@ -104,10 +114,14 @@ impl<'ast> Visit<'ast> for InstanceMisuseVisitor {
             if let Some(key_arg) = node.args.first() {
                 if Self::is_per_user_key(key_arg) && is_instance_chain(&node.receiver) {
                     let line = line_of(&node.receiver);
+                    let span = SourceSpan::from_span(node.span())
+                        .in_function(&self.current_fn)
+                        .in_contract(self.current_impl.as_deref());
                     self.violations.push(InstanceMisuseViolation {
                         fn_name: self.current_fn.clone(),
                         key_hint: Self::key_label(key_arg),
                         line,
+                        span,
                     });
                 }
             }
@@ -136,7 +150,6 @@ fn is_instance_chain(expr: &Expr) -> bool {
 
 /// Best-effort line number from a span (falls back to 0).
 fn line_of(expr: &Expr) -> usize {
-    use syn::spanned::Spanned;
     expr.span().start().line
 }
 
@@ -193,6 +206,7 @@ impl Rule for InstanceStorageMisuseRule {
                     ),
                     format!("{}:{}", v.fn_name, v.line),
                 )
+                .with_span(v.span)
                 .with_suggestion(
                     "Move per-user / per-account data to `env.storage().persistent()` \
                      (or `temporary()` for short-lived state). Reserve `instance()` for \
@@ -1,4 +1,4 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
 use syn::{parse_str, Fields, File, Item, Meta, Type};
 
 /// Rule that estimates `#[contracttype]` storage sizes against ledger limits.
@@ -91,7 +91,8 @@ impl Rule for LedgerSizeRule {
                                 "{}:estimated {} bytes, limit {} bytes",
                                 s.ident, size, self.ledger_limit
                             ),
-                        ));
+                        )
+                        .with_span(SourceSpan::from_span(s.ident.span())));
                     }
                 }
                 Item::Enum(e) if has_contracttype(&e.attrs) => {
@@ -112,7 +113,8 @@ impl Rule for LedgerSizeRule {
                                 "{}:estimated {} bytes, limit {} bytes",
                                 e.ident, size, self.ledger_limit
                             ),
-                        ));
+                        )
+                        .with_span(SourceSpan::from_span(e.ident.span())));
                     }
                 }
                 _ => {}
@@ -138,7 +140,7 @@ impl LedgerSizeRule {
         }
     }
 
-    fn estimate_struct_size(&self, s: &syn::ItemStruct) -> usize {
+    pub(crate) fn estimate_struct_size(&self, s: &syn::ItemStruct) -> usize {
         let mut total = 0;
         match &s.fields {
             Fields::Named(fields) => {
@@ -156,7 +158,7 @@ impl LedgerSizeRule {
         total
     }
 
-    fn estimate_enum_size(&self, e: &syn::ItemEnum) -> usize {
+    pub(crate) fn estimate_enum_size(&self, e: &syn::ItemEnum) -> usize {
         const DISCRIMINANT_SIZE: usize = 4;
         let mut max_variant = 0usize;
         for v in &e.variants {
@@ -180,7 +182,7 @@ impl LedgerSizeRule {
     }
 
     #[allow(clippy::only_used_in_recursion)]
-    fn estimate_type_size(&self, ty: &Type) -> usize {
+    pub(crate) fn estimate_type_size(&self, ty: &Type) -> usize {
         match ty {
             Type::Path(tp) => {
                 if let Some(seg) = tp.path.segments.last() {
@@ -1,4 +1,4 @@
-use crate::rules::{Patch, Rule, RuleViolation, Severity};
+use crate::rules::{Patch, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
@@ -61,6 +61,7 @@ impl Rule for MissingStateEventRule {
                                         ),
                                         format!("line {}", span.start().line),
                                     )
+                                    .with_span(SourceSpan::from_span(span).in_impl(i).in_function(&fn_name))
                                     .with_suggestion(
                                         "Add env.events().publish() after state changes for off-chain observability".to_string()
                                     ),
@@ -3,9 +3,24 @@
 //! Soroban storage entries expire. A contract that writes to `persistent()` or
 //! `temporary()` storage but never calls `extend_ttl` on that entry will
 //! silently lose data once the ledger TTL elapses.
-
-use super::{Rule, RuleViolation, Severity};
+//!
+//! The check is path-sensitive: a write is flagged unless every path from it
+//! to the end of the function goes on to call `extend_ttl` (or the legacy
+//! `bump`), as computed by [`MustHappen::after`] over the function's CFG.  A
+//! path that panics or returns an error (`?`, `return Err(..)`) fails the
+//! invocation, which rolls the write back, so it needs no bump.
+//!
+//! The auto-fix inserts `<tier>.extend_ttl(<key>, threshold, extend_to)` right
+//! after the statement holding the write, in the innermost block around it,
+//! so the key expression is still in scope.  It uses the contract's own TTL
+//! constants when it declares them.
+
+use super::{Patch, Rule, RuleViolation, Severity, SourceSpan};
+use crate::cfg::{BlockStmt, Cfg};
+use crate::dataflow::{self, MustHappen};
+use crate::fix_context::FixContext;
 use syn::spanned::Spanned;
+use syn::visit::Visit;
 use syn::{parse_str, File, Item};
 
 pub struct MissingTtlBumpRule;
@@ -22,15 +37,13 @@ impl Default for MissingTtlBumpRule {
     }
 }
 
-// ── Internal state ─────────────────────────────────────────────────────────────
+/// Event recorded by statements that extend a TTL (or abort the invocation).
+const TTL_EXTENDED: &str = "extend_ttl";
 
-#[derive(Default)]
-struct FnTtlState {
-    /// Locations (fn_name, line) where persistent/temporary writes happen.
-    writes: Vec<(String, usize)>,
-    /// True if the function calls extend_ttl anywhere.
-    has_extend_ttl: bool,
-}
+/// Threshold and extend-to ledger counts of an inserted bump when the
+/// contract has no constants for them: about 7 and 30 days of 5s ledgers.
+const DEFAULT_TTL_THRESHOLD: &str = "120_960";
+const DEFAULT_TTL_EXTEND_TO: &str = "518_400";
 
 // ── Rule impl ──────────────────────────────────────────────────────────────────
 
@@ -57,31 +70,33 @@ impl Rule for MissingTtlBumpRule {
                 for impl_item in &impl_block.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
                         let fn_name = f.sig.ident.to_string();
-                        let mut state = FnTtlState::default();
-                        scan_block(&f.block, &fn_name, &mut state);
-
-                        if !state.has_extend_ttl {
-                            for (name, line) in &state.writes {
-                                violations.push(
-                                    RuleViolation::new(
-                                        self.name(),
-                                        Severity::Warning,
-                                        format!(
-                                            "Function '{}' writes to Persistent/Temporary storage \
-                                             but never calls extend_ttl — the entry may expire",
-                                            name
-                                        ),
-                                        format!("{}:{}", name, line),
-                                    )
-                                    .with_suggestion(
-                                        "Call env.storage().persistent().extend_ttl(&key, low, high) \
-                                         (or temporary()) after each write to prevent the entry \
-                                         from expiring. Example: \
-                                         env.storage().instance().extend_ttl(1000, 5000);"
-                                            .to_string(),
+                        for write in writes_without_later_bump(&f.block) {
+                            let span = write.span();
+                            violations.push(
+                                RuleViolation::new(
+                                    self.name(),
+                                    Severity::Warning,
+                                    format!(
+                                        "Function '{}' writes to Persistent/Temporary storage \
+                                         without calling extend_ttl afterwards on every path — \
+                                         the entry may expire",
+                                        fn_name
                                     ),
-                                );
-                            }
+                                    format!("{}:{}", fn_name, span.start().line),
+                                )
+                                .with_span(
+                                    SourceSpan::from_span(span)
+                                        .in_impl(impl_block)
+                                        .in_function(&fn_name),
+                                )
+                                .with_suggestion(
+                                    "Call env.storage().persistent().extend_ttl(&key, low, high) \
+                                     (or temporary()) after each write to prevent the entry \
+                                     from expiring. Example: \
+                                     env.storage().instance().extend_ttl(1000, 5000);"
+                                        .to_string(),
+                                ),
+                            );
                         }
                     }
                 }
@@ -91,70 +106,113 @@ impl Rule for MissingTtlBumpRule {
         violations
     }
 
+    fn fix(&self, source: &str) -> Vec<Patch> {
+        let file = match parse_str::<File>(source) {
+            Ok(f) => f,
+            Err(_) => return vec![],
+        };
+
+        let context = FixContext::new(source, &file);
+        let bounds = ttl_bounds(&file);
+        let mut patches = Vec::new();
+        for item in &file.items {
+            if let Item::Impl(impl_block) = item {
+                for impl_item in &impl_block.items {
+                    if let syn::ImplItem::Fn(f) = impl_item {
+                        for write in writes_without_later_bump(&f.block) {
+                            if let Some(patch) = bump_patch(&context, f, &write, &bounds) {
+                                // One statement may hold several writes of the same entry.
+                                if !patches.contains(&patch) {
+                                    patches.push(patch);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        patches
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
 
-// ── AST helpers ────────────────────────────────────────────────────────────────
+// ── Dataflow ───────────────────────────────────────────────────────────────────
 
-fn scan_block(block: &syn::Block, fn_name: &str, state: &mut FnTtlState) {
-    for stmt in &block.stmts {
-        match stmt {
-            syn::Stmt::Local(l) => {
-                if let Some(init) = &l.init {
-                    scan_expr(&init.expr, fn_name, state);
-                }
+/// Persistent/temporary writes in `body` that some path to the function
+/// exit leaves without a later TTL extension.
+fn writes_without_later_bump(body: &syn::Block) -> Vec<syn::ExprMethodCall> {
+    let cfg = without_error_exits(Cfg::build(body));
+    let analysis = MustHappen::after(ttl_events);
+    let results = dataflow::solve(&cfg, &analysis);
+
+    let mut writes = Vec::new();
+    for block in &cfg.blocks {
+        // No facts: the block never reaches the exit (e.g. an endless loop).
+        let Some(facts) = results.stmt_facts(&cfg, &analysis, block.id) else {
+            continue;
+        };
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            if !facts[index].contains(TTL_EXTENDED) {
+                writes.extend(storage_calls(stmt).writes);
             }
-            syn::Stmt::Expr(e, _) => scan_expr(e, fn_name, state),
-            _ => {}
         }
     }
+    writes
 }
 
-fn scan_expr(expr: &syn::Expr, fn_name: &str, state: &mut FnTtlState) {
-    match expr {
-        syn::Expr::MethodCall(mc) => {
-            let method = mc.method.to_string();
+/// `cfg` without its edges to the exit along which the function returns an
+/// error.  A `?` only leaves on one of its block's edges, so unlike a panic it
+/// cannot be handled as an event of its statement.
+fn without_error_exits(mut cfg: Cfg) -> Cfg {
+    let exit = cfg.exit;
+    for (block, successors) in cfg.blocks.iter().zip(&mut cfg.successors) {
+        let Some(last) = block.stmts.last() else {
+            continue;
+        };
+        if last.returns_error() || (successors.len() > 1 && last.propagates_error()) {
+            successors.retain(|&to| to != exit);
+        }
+    }
+    cfg
+}
 
-            if method == "extend_ttl" || method == "bump" {
-                state.has_extend_ttl = true;
-            }
+fn ttl_events(stmt: &BlockStmt) -> Vec<String> {
+    if stmt.panics() || storage_calls(stmt).extends_ttl {
+        vec![TTL_EXTENDED.to_string()]
+    } else {
+        Vec::new()
+    }
+}
 
-            if is_persistent_or_temporary_write(&method, &mc.receiver) {
-                let span = mc.span();
-                state.writes.push((fn_name.to_string(), span.start().line));
-            }
+#[derive(Default)]
+struct StorageCalls {
+    /// Persistent/temporary writes.
+    writes: Vec<syn::ExprMethodCall>,
+    /// Whether an `extend_ttl`/`bump` call is made.
+    extends_ttl: bool,
+}
 
-            scan_expr(&mc.receiver, fn_name, state);
-            for arg in &mc.args {
-                scan_expr(arg, fn_name, state);
-            }
-        }
-        syn::Expr::Block(b) => scan_block(&b.block, fn_name, state),
-        syn::Expr::If(i) => {
-            scan_expr(&i.cond, fn_name, state);
-            scan_block(&i.then_branch, fn_name, state);
-            if let Some((_, else_expr)) = &i.else_branch {
-                scan_expr(else_expr, fn_name, state);
-            }
-        }
-        syn::Expr::Match(m) => {
-            scan_expr(&m.expr, fn_name, state);
-            for arm in &m.arms {
-                scan_expr(&arm.body, fn_name, state);
-            }
-        }
-        syn::Expr::Assign(a) => {
-            scan_expr(&a.left, fn_name, state);
-            scan_expr(&a.right, fn_name, state);
+fn storage_calls(stmt: &BlockStmt) -> StorageCalls {
+    let mut calls = StorageCalls::default();
+    for expr in stmt.exprs() {
+        calls.visit_expr(expr);
+    }
+    calls
+}
+
+impl<'ast> Visit<'ast> for StorageCalls {
+    fn visit_expr_method_call(&mut self, mc: &'ast syn::ExprMethodCall) {
+        let method = mc.method.to_string();
+        if method == "extend_ttl" || method == "bump" {
+            self.extends_ttl = true;
         }
-        syn::Expr::Call(c) => {
-            for arg in &c.args {
-                scan_expr(arg, fn_name, state);
-            }
+        if is_persistent_or_temporary_write(&method, &mc.receiver) {
+            self.writes.push(mc.clone());
         }
-        _ => {}
+        syn::visit::visit_expr_method_call(self, mc);
     }
 }
 
@@ -169,6 +227,121 @@ fn is_persistent_or_temporary_write(method: &str, receiver: &syn::Expr) -> bool
     receiver_str.contains("persistent") || receiver_str.contains("temporary")
 }
 
+// ── Auto-fix ───────────────────────────────────────────────────────────────────
+
+/// `extend_ttl` arguments: the contract's non-instance `*THRESHOLD*` constant
+/// and the `*BUMP*`/`*EXTEND*` constant sharing its prefix (or the first one),
+/// e.g. `BALANCE_LIFETIME_THRESHOLD` and `BALANCE_BUMP_AMOUNT`.
+fn ttl_bounds(file: &File) -> (String, String) {
+    let consts: Vec<String> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Const(c) => Some(c.ident.to_string()),
+            _ => None,
+        })
+        .filter(|name| !name.contains("INSTANCE"))
+        .collect();
+    let threshold = consts.iter().find(|name| name.contains("THRESHOLD"));
+    let mut extend_to = consts
+        .iter()
+        .filter(|name| name.contains("BUMP") || name.contains("EXTEND"));
+    let prefix = |name: &str| name.split('_').next().unwrap_or_default().to_string();
+    match threshold {
+        Some(threshold) => {
+            let paired = extend_to
+                .clone()
+                .find(|name| prefix(name) == prefix(threshold));
+            match paired.or_else(|| extend_to.next()) {
+                Some(extend_to) => (threshold.clone(), extend_to.clone()),
+                None => default_ttl_bounds(),
+            }
+        }
+        None => default_ttl_bounds(),
+    }
+}
+
+fn default_ttl_bounds() -> (String, String) {
+    (
+        DEFAULT_TTL_THRESHOLD.to_string(),
+        DEFAULT_TTL_EXTEND_TO.to_string(),
+    )
+}
+
+/// Insertion of a bump for `write` after the statement holding it.  `None`
+/// for a `remove` (the entry is gone) and for writes whose statement is a
+/// value-producing expression, such as a match arm, where there is no place
+/// for another statement.
+fn bump_patch(
+    context: &FixContext<'_>,
+    f: &syn::ImplItemFn,
+    write: &syn::ExprMethodCall,
+    (threshold, extend_to): &(String, String),
+) -> Option<Patch> {
+    if write.method == "remove" {
+        return None;
+    }
+    let key = context.text(write.args.first()?.span())?;
+    let tier = context.text(write.receiver.span())?;
+
+    let mut enclosing = EnclosingStmt {
+        target: write.span().start(),
+        found: None,
+    };
+    enclosing.visit_block(&f.block);
+    let stmt = enclosing.found?;
+    let separator = match stmt {
+        // A `set` ending a block returns `()`, so it can take a semicolon.
+        syn::Stmt::Expr(syn::Expr::MethodCall(tail), None)
+            if write.method == "set" && tail == write =>
+        {
+            ";"
+        }
+        syn::Stmt::Expr(_, None) => return None,
+        _ => "",
+    };
+
+    let end = stmt.span().end();
+    Some(Patch {
+        start_line: end.line,
+        start_column: end.column,
+        end_line: end.line,
+        end_column: end.column,
+        replacement: format!(
+            "{}\n{}{}.extend_ttl({}, {}, {});",
+            separator,
+            context.indent(stmt.span().start().line),
+            tier,
+            key,
+            threshold,
+            extend_to
+        ),
+        description: format!(
+            "Extend the TTL of the entry written in function '{}'",
+            f.sig.ident
+        ),
+    })
+}
+
+/// Finds the innermost block statement containing `target`.
+struct EnclosingStmt<'ast> {
+    target: proc_macro2::LineColumn,
+    found: Option<&'ast syn::Stmt>,
+}
+
+impl<'ast> Visit<'ast> for EnclosingStmt<'ast> {
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        for stmt in &block.stmts {
+            let (start, end) = (stmt.span().start(), stmt.span().end());
+            let target = (self.target.line, self.target.column);
+            if (start.line, start.column) <= target && target < (end.line, end.column) {
+                self.found = Some(stmt);
+            }
+        }
+        syn::visit::visit_block(self, block);
+    }
+}
+
 // ── Unit tests ─────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -228,6 +401,87 @@ mod tests {
         assert!(v.is_empty(), "function with extend_ttl must not be flagged");
     }
 
+    #[test]
+    fn flags_write_when_extend_ttl_is_only_on_one_branch() {
+        let source = r#"
+            impl MyContract {
+                pub fn store(env: Env, key: Symbol, val: i128, bump: bool) {
+                    env.storage().persistent().set(&key, &val);
+                    if bump {
+                        env.storage().persistent().extend_ttl(&key, 1000, 5000);
+                    }
+                }
+            }
+        "#;
+        let v = rule().check(source);
+        assert_eq!(v.len(), 1, "the no-bump path must be flagged");
+    }
+
+    #[test]
+    fn flags_write_after_the_only_extend_ttl() {
+        let source = r#"
+            impl MyContract {
+                pub fn store(env: Env, key: Symbol, val: i128) {
+                    env.storage().persistent().extend_ttl(&key, 1000, 5000);
+                    env.storage().persistent().set(&key, &val);
+                }
+            }
+        "#;
+        assert_eq!(rule().check(source).len(), 1);
+    }
+
+    #[test]
+    fn no_violation_when_other_paths_panic_or_bump() {
+        let source = r#"
+            impl MyContract {
+                pub fn store(env: Env, key: Symbol, val: i128) {
+                    env.storage().temporary().set(&key, &val);
+                    match val {
+                        0 => panic!("zero"),
+                        _ => env.storage().temporary().extend_ttl(&key, 100, 500),
+                    }
+                }
+            }
+        "#;
+        assert!(rule().check(source).is_empty());
+    }
+
+    #[test]
+    fn no_violation_when_other_paths_return_an_error() {
+        let source = r#"
+            impl MyContract {
+                pub fn store(env: Env, key: Symbol, val: i128) -> Result<(), Error> {
+                    env.storage().persistent().set(&key, &val);
+                    let checked = check(val)?;
+                    if checked < 0 {
+                        return Err(Error::Negative);
+                    }
+                    env.storage().persistent().extend_ttl(&key, 1000, 5000);
+                    Ok(())
+                }
+            }
+        "#;
+        assert!(rule().check(source).is_empty());
+    }
+
+    #[test]
+    fn flags_write_when_the_success_path_after_a_try_skips_the_bump() {
+        let source = r#"
+            impl MyContract {
+                pub fn store(env: Env, key: Symbol, val: i128) -> Result<(), Error> {
+                    env.storage().persistent().set(&key, &val);
+                    let checked = check(val)?;
+                    if checked < 0 {
+                        return Ok(());
+                    }
+                    env.storage().persistent().extend_ttl(&key, 1000, 5000);
+                    Ok(())
+                }
+            }
+        "#;
+        assert_eq!(rule().check(source).len(), 1);
+    }
+
     #[test]
     fn no_violation_when_instance_write_only() {
         // Instance storage has its own TTL semantics and is excluded from this rule
@@ -264,4 +518,53 @@ mod tests {
     fn invalid_source_no_panic() {
         assert!(rule().check("not valid rust {{{").is_empty());
     }
+
+    fn fixed(source: &str) -> String {
+        crate::patcher::Patcher::apply_patches(source, &rule().fix(source))
+    }
+
+    #[test]
+    fn fix_inserts_bump_after_the_write_in_its_block() {
+        let source = r#"
+impl MyContract {
+    pub fn store(env: Env, key: Symbol, val: i128, keep: bool) {
+        if keep {
+            env.storage().persistent().set(&key, &val);
+        }
+        env.storage().temporary().set(&DataKey::Last, &val)
+    }
+}
+"#;
+        let fixed = fixed(source);
+        assert!(fixed.contains(
+            "            env.storage().persistent().set(&key, &val);\n            env.storage().persistent().extend_ttl(&key, 120_960, 518_400);\n        }"
+        ));
+        assert!(fixed.contains(
+            "env.storage().temporary().set(&DataKey::Last, &val);\n        env.storage().temporary().extend_ttl(&DataKey::Last, 120_960, 518_400);"
+        ));
+        assert!(rule().check(&fixed).is_empty());
+    }
+
+    #[test]
+    fn fix_uses_contract_ttl_constants_and_skips_removals() {
+        let source = r#"
+const INSTANCE_LIFETIME_THRESHOLD: u32 = 100;
+const INSTANCE_BUMP_AMOUNT: u32 = 200;
+const BALANCE_LIFETIME_THRESHOLD: u32 = 300;
+const BALANCE_BUMP_AMOUNT: u32 = 400;
+
+impl MyContract {
+    pub fn store(env: Env, key: Symbol, val: i128) {
+        env.storage().persistent().set(&key, &val);
+        env.storage().persistent().remove(&key);
+    }
+}
+"#;
+        let patches = rule().fix(source);
+        assert_eq!(patches.len(), 1);
+        assert!(patches[0].replacement.ends_with(
+            "env.storage().persistent().extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);"
+        ));
+        assert!(patches[0].description.contains("'store'"));
+    }
 }
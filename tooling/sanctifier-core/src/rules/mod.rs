@@ -19,6 +19,8 @@ pub mod missing_state_event;
 pub mod panic_detection;
 /// Reentrancy vulnerability detection and auto-fix.
 pub mod reentrancy;
+/// Entry points that can exceed Soroban's per-transaction resource limits (S033).
+pub mod resource_limits;
 /// Shadow storage pattern detection.
 pub mod shadow_storage;
 /// Detect usage of env.storage().instance().update() without state check.
@@ -56,8 +58,14 @@ pub mod timestamp_randomness;
 pub mod transfer_from_no_allowance;
 /// Variable shadowing in nested scopes.
 pub mod variable_shadowing;
+use crate::finding_codes::{self, FindingCode, FindingSeverity};
+use crate::rule_config::{RuleConfig, RuleConfigError};
+use crate::storage_layout::LayoutType;
+use crate::suppression::InlineSuppressions;
+use crate::SanctifyConfig;
 use serde::Serialize;
 use std::any::Any;
+use std::collections::HashMap;
 
 /// A single analysis rule.
 ///
@@ -68,12 +76,44 @@ pub trait Rule: Send + Sync + std::panic::UnwindSafe + std::panic::RefUnwindSafe
     fn name(&self) -> &str;
     /// Human-readable description.
     fn description(&self) -> &str;
+    /// Canonical finding code (e.g. `"S001"`).  Built-in rules are looked up
+    /// with [`finding_codes::code_for_rule`]; any other rule reports as
+    /// [`finding_codes::CUSTOM_RULE_MATCH`] unless it overrides this.
+    fn code(&self) -> &'static str {
+        finding_codes::code_for_rule(self.name()).unwrap_or(finding_codes::CUSTOM_RULE_MATCH)
+    }
+    /// Catalogue entry for [`Rule::code`]: title, category, CWE and remediation.
+    fn metadata(&self) -> Option<FindingCode> {
+        finding_codes::lookup_finding_code(self.code())
+    }
+    /// Severity given to every finding of this rule.
+    fn default_severity(&self) -> FindingSeverity {
+        self.metadata().map(|m| m.severity).unwrap_or_default()
+    }
+    /// Apply rule-specific options from the rule's `[rules.<name>]` table.
+    /// The default accepts none; rules with tunables override this and return
+    /// a message describing any option they cannot use.
+    fn configure(&mut self, config: &RuleConfig) -> Result<(), String> {
+        config.expect_options(&[])
+    }
     /// Run the check and return all violations.
     fn check(&self, source: &str) -> Vec<RuleViolation>;
+    /// [`Rule::check`] for one file of a crate, given the source text of the
+    /// crate's files.  Rules that follow calls into other files override
+    /// this; the rest only look at `source`.
+    fn check_in_crate(&self, source: &str, _crate_sources: &[String]) -> Vec<RuleViolation> {
+        self.check(source)
+    }
     /// Optionally produce auto-fix patches.
     fn fix(&self, _source: &str) -> Vec<Patch> {
         vec![]
     }
+    /// [`Rule::fix`] for one file of a crate whose `#[contracterror]` enums,
+    /// collected over all its files, are `crate_errors`.  Rules whose fixes
+    /// report contract errors override this; the rest ignore the enums.
+    fn fix_in_crate(&self, source: &str, _crate_errors: &[LayoutType]) -> Vec<Patch> {
+        self.fix(source)
+    }
     /// Down-cast helper.
     fn as_any(&self) -> &dyn Any;
 }
@@ -100,20 +140,97 @@ pub struct Patch {
 pub struct RuleViolation {
     /// Name of the rule that fired.
     pub rule_name: String,
-    /// Severity level.
+    /// Canonical finding code (e.g. `"S001"`).
+    #[serde(default)]
+    pub code: String,
+    /// Severity level as chosen by the rule.
     pub severity: Severity,
+    /// Severity on the shared five-level scale of [`finding_codes`].  This is
+    /// what `--min-severity` and exit codes are compared against.
+    #[serde(default)]
+    pub finding_severity: FindingSeverity,
     /// Human-readable message.
     pub message: String,
     /// Source location.
     pub location: String,
+    /// Exact source range, when the rule could attribute the finding to a
+    /// syntax node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
     /// Optional suggestion.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggestion: Option<String>,
     /// Optional auto-fix patches.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub patches: Vec<Patch>,
 }
 
+/// Source range of a [`RuleViolation`], with the items that enclose it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, serde::Deserialize)]
+pub struct SourceSpan {
+    /// File the violation was found in.  Rules only see source text, so this
+    /// is filled in by the caller through [`RuleViolation::in_file`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Start line (1-based).
+    pub start_line: usize,
+    /// Start column (0-based).
+    pub start_column: usize,
+    /// End line (1-based).
+    pub end_line: usize,
+    /// End column (0-based).
+    pub end_column: usize,
+    /// Name of the enclosing function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// Self type of the enclosing `impl` block — the contract struct for
+    /// `#[contractimpl]` blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+}
+
+impl SourceSpan {
+    /// Build a span from the location of a syntax node.
+    pub fn from_span(span: proc_macro2::Span) -> Self {
+        let (start, end) = (span.start(), span.end());
+        Self {
+            file: None,
+            start_line: start.line,
+            start_column: start.column,
+            end_line: end.line,
+            end_column: end.column,
+            function: None,
+            contract: None,
+        }
+    }
+
+    /// Record the enclosing function.
+    pub fn in_function(mut self, name: impl Into<String>) -> Self {
+        self.function = Some(name.into());
+        self
+    }
+
+    /// Record the enclosing contract, if known.
+    pub fn in_contract(mut self, name: Option<&str>) -> Self {
+        self.contract = name.map(str::to_string);
+        self
+    }
+
+    /// Record the self type of the enclosing `impl` block as the contract.
+    pub fn in_impl(self, item: &syn::ItemImpl) -> Self {
+        self.in_contract(impl_type_name(item).as_deref())
+    }
+}
+
+/// Last path segment of an `impl` block's self type (`Token` for
+/// `impl Token`, `impl token::Token` or `impl<'a> Token<'a>`).
+pub(crate) fn impl_type_name(item: &syn::ItemImpl) -> Option<String> {
+    match item.self_ty.as_ref() {
+        syn::Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
 /// Severity level of a rule violation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize)]
 #[non_exhaustive]
@@ -127,18 +244,58 @@ pub enum Severity {
 }
 
 impl RuleViolation {
-    /// Create a new violation.
+    /// Create a new violation, classified by the catalogue entry of
+    /// `rule_name` (see [`finding_codes::code_for_rule`]).
     pub fn new(rule_name: &str, severity: Severity, message: String, location: String) -> Self {
+        let code =
+            finding_codes::code_for_rule(rule_name).unwrap_or(finding_codes::CUSTOM_RULE_MATCH);
+        let finding_severity = finding_codes::lookup_finding_code(code)
+            .map(|c| c.severity)
+            .unwrap_or_default();
         Self {
             rule_name: rule_name.to_string(),
+            code: code.to_string(),
             severity,
+            finding_severity,
             message,
             location,
+            span: None,
             suggestion: None,
             patches: vec![],
         }
     }
 
+    /// Set the finding code and five-level severity.
+    pub fn classified(mut self, code: &str, severity: FindingSeverity) -> Self {
+        self.code = code.to_string();
+        self.finding_severity = severity;
+        self
+    }
+
+    /// Attach the exact source range of the finding.
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Record the file the finding was reported in.  Has no effect on
+    /// violations without a span.
+    pub fn in_file(mut self, file: &str) -> Self {
+        if let Some(span) = &mut self.span {
+            span.file = Some(file.to_string());
+        }
+        self
+    }
+
+    /// 1-based line of the finding: the span start when known, otherwise the
+    /// trailing line number of [`RuleViolation::location`].
+    pub fn line(&self) -> Option<usize> {
+        match &self.span {
+            Some(span) => Some(span.start_line),
+            None => crate::suppression::line_from_location(&self.location),
+        }
+    }
+
     /// Attach auto-fix patches.
     pub fn with_patches(mut self, patches: Vec<Patch>) -> Self {
         self.patches = patches;
@@ -154,9 +311,12 @@ impl RuleViolation {
 
 /// A registry of [`Rule`] implementations.
 ///
-/// Use [`RuleRegistry::with_default_rules`] to get the built-in set.
+/// Use [`RuleRegistry::with_default_rules`] to get the built-in set, or
+/// [`RuleRegistry::from_config`] to apply the `[rules.<name>]` tables of a
+/// `.sanctify.toml` on top of it.
 pub struct RuleRegistry {
     pub(crate) rules: Vec<Box<dyn Rule>>,
+    severity_overrides: HashMap<String, FindingSeverity>,
 }
 
 impl Default for RuleRegistry {
@@ -168,7 +328,10 @@ impl Default for RuleRegistry {
 impl RuleRegistry {
     /// Create an empty registry.
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            severity_overrides: HashMap::new(),
+        }
     }
 
     /// Register a rule.
@@ -177,20 +340,75 @@ impl RuleRegistry {
     }
 
     /// Run every registered rule against `source`.
+    ///
+    /// Findings covered by `#[allow(sanctifier::<rule>)]` or a
+    /// `// sanctifier-ignore-next-line` comment are dropped; inline
+    /// suppressions that cover nothing are reported as
+    /// [`UNUSED_SUPPRESSION_RULE`](crate::suppression::UNUSED_SUPPRESSION_RULE) warnings.
     pub fn run_all(&self, source: &str) -> Vec<RuleViolation> {
-        self.rules
+        self.run_all_in_crate(source, &[])
+    }
+
+    /// [`RuleRegistry::run_all`] for one file of a crate, with the source
+    /// text of the crate's files, so rules can follow calls to helpers
+    /// defined in another file.
+    pub fn run_all_in_crate(&self, source: &str, crate_sources: &[String]) -> Vec<RuleViolation> {
+        let violations = self
+            .rules
             .iter()
-            .flat_map(|rule| rule.check(source))
-            .collect()
+            .flat_map(|rule| self.run_rule(rule.as_ref(), source, crate_sources))
+            .collect();
+        let inline = InlineSuppressions::parse(source);
+        if inline.is_empty() {
+            return violations;
+        }
+        let (mut kept, used) = inline.filter(violations);
+        kept.extend(inline.unused(&used, &self.available_rules()));
+        kept
     }
 
-    /// Run a single rule by name.
+    /// Run a single rule by name.  Inline suppressions are honored, but
+    /// unused ones are only reported by [`RuleRegistry::run_all`].
     pub fn run_by_name(&self, source: &str, name: &str) -> Vec<RuleViolation> {
-        self.rules
+        let violations = self
+            .rules
             .iter()
             .filter(|rule| rule.name() == name)
-            .flat_map(|rule| rule.check(source))
-            .collect()
+            .flat_map(|rule| self.run_rule(rule.as_ref(), source, &[]))
+            .collect();
+        InlineSuppressions::parse(source).filter(violations).0
+    }
+
+    /// Auto-fix patches of every registered rule for `source`, each with the
+    /// name of the rule that produced it: the [`Rule::fix`] output followed
+    /// by the patches attached to the rule's violations.
+    pub fn fix_all(&self, source: &str) -> Vec<(String, Patch)> {
+        self.fix_all_in_crate(source, &[])
+    }
+
+    /// [`RuleRegistry::fix_all`] for one file of a crate, with the
+    /// `#[contracterror]` enums declared across the crate (see
+    /// [`crate::fix_context::contract_errors`]) available to every fix.
+    pub fn fix_all_in_crate(
+        &self,
+        source: &str,
+        crate_errors: &[LayoutType],
+    ) -> Vec<(String, Patch)> {
+        let mut fixes = Vec::new();
+        for rule in &self.rules {
+            let name = rule.name().to_string();
+            let attached = rule.check(source).into_iter().flat_map(|v| v.patches);
+            for patch in rule
+                .fix_in_crate(source, crate_errors)
+                .into_iter()
+                .chain(attached)
+            {
+                if !fixes.iter().any(|(n, p)| *n == name && *p == patch) {
+                    fixes.push((name.clone(), patch));
+                }
+            }
+        }
+        fixes
     }
 
     /// List the names of all registered rules.
@@ -198,6 +416,82 @@ impl RuleRegistry {
         self.rules.iter().map(|rule| rule.name()).collect()
     }
 
+    /// Severity the findings of rule `name` are reported with, including any
+    /// `[rules.<name>]` override; `None` if no such rule is registered.
+    pub fn severity_of(&self, name: &str) -> Option<FindingSeverity> {
+        let rule = self.rules.iter().find(|rule| rule.name() == name)?;
+        Some(self.severity(rule.as_ref()))
+    }
+
+    /// Apply the `[rules.<name>]` tables in `config` to the registered rules.
+    ///
+    /// Disabled rules are dropped, severity overrides replace the rule's
+    /// default, and any remaining keys go to [`Rule::configure`].  Under
+    /// `strict_mode` rules stay enabled and severities can only be raised;
+    /// lowering one is an error, as is a table naming a rule that is not
+    /// registered.
+    pub fn configure(&mut self, config: &SanctifyConfig) -> Result<(), RuleConfigError> {
+        for (name, rule_config) in &config.rules {
+            let Some(index) = self.rules.iter().position(|rule| rule.name() == name) else {
+                return Err(RuleConfigError::new(
+                    name,
+                    format!(
+                        "unknown rule; available rules: {}",
+                        self.available_rules().join(", ")
+                    ),
+                ));
+            };
+            let rule = &mut self.rules[index];
+            rule.configure(rule_config)
+                .map_err(|message| RuleConfigError::new(name, message))?;
+            if let Some(severity) = rule_config.severity {
+                let default = rule.default_severity();
+                if config.strict_mode && severity.rank() < default.rank() {
+                    return Err(RuleConfigError::new(
+                        name,
+                        format!(
+                            "severity `{}` is below the default `{}`, which strict_mode does not allow",
+                            severity.as_str(),
+                            default.as_str()
+                        ),
+                    ));
+                }
+                self.severity_overrides.insert(name.clone(), severity);
+            }
+        }
+        self.rules.retain(|rule| config.rule_enabled(rule.name()));
+        Ok(())
+    }
+
+    /// Create a registry with all built-in rules, configured from `config`.
+    pub fn from_config(config: &SanctifyConfig) -> Result<Self, RuleConfigError> {
+        let mut registry = Self::with_default_rules();
+        registry.configure(config)?;
+        Ok(registry)
+    }
+
+    /// Run `rule` and tag its findings with the rule's code and severity.
+    fn run_rule(
+        &self,
+        rule: &dyn Rule,
+        source: &str,
+        crate_sources: &[String],
+    ) -> Vec<RuleViolation> {
+        let code = rule.code();
+        let severity = self.severity(rule);
+        rule.check_in_crate(source, crate_sources)
+            .into_iter()
+            .map(|v| v.classified(code, severity))
+            .collect()
+    }
+
+    fn severity(&self, rule: &dyn Rule) -> FindingSeverity {
+        self.severity_overrides
+            .get(rule.name())
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+
     /// Create a registry pre-loaded with all built-in rules.
     pub fn with_default_rules() -> Self {
         let mut registry = Self::new();
@@ -227,6 +521,7 @@ impl RuleRegistry {
         registry.register(timestamp_randomness::TimestampRandomnessRule::new());
         registry.register(require_auth_for_args::RequireAuthForArgsRule::new());
         registry.register(gas_exhaustion::GasExhaustionRiskRule::new());
+        registry.register(resource_limits::ResourceLimitsRule::new());
         registry
     }
 }
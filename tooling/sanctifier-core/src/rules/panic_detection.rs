@@ -1,7 +1,20 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::fix_context::{
+    FixContext, FnFailure, CONVERSION_ERRORS, DIVISION_ERRORS, MISSING_ERRORS, OVERFLOW_ERRORS,
+    UNDERFLOW_ERRORS,
+};
+use crate::rules::{Patch, Rule, RuleViolation, Severity, SourceSpan};
+use crate::storage_layout::LayoutType;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
 use syn::{parse_str, File};
 
 /// Rule that detects `panic!`, `unwrap()`, and `expect()` calls.
+///
+/// [`Rule::fix`] replaces `unwrap()`/`expect()` with the contract's
+/// `#[contracterror]` enum when the receiver is recognisably an `Option`
+/// (`get`, `checked_*`, `first`, ...) or a `Result` (`try_*`, `parse`): the
+/// error is propagated with `?` from functions returning `Result<_, Error>`
+/// and raised with `panic_with_error!` elsewhere.
 pub struct PanicDetectionRule;
 
 impl PanicDetectionRule {
@@ -56,7 +69,12 @@ impl Rule for PanicDetectionRule {
                                 continue;
                             }
                             let fn_name = f.sig.ident.to_string();
-                            check_fn_panics(&f.block, &fn_name, &mut issues);
+                            let mut found = Vec::new();
+                            check_fn_panics(&f.block, &fn_name, &mut found);
+                            issues.extend(found.into_iter().map(|(issue, span)| {
+                                let span = SourceSpan::from_span(span).in_impl(i);
+                                (issue, span.in_function(&fn_name))
+                            }));
                         }
                     }
                 }
@@ -72,7 +90,11 @@ impl Rule for PanicDetectionRule {
                                     continue;
                                 }
                                 let fn_name = f.sig.ident.to_string();
-                                check_fn_panics(&f.block, &fn_name, &mut issues);
+                                let mut found = Vec::new();
+                                check_fn_panics(&f.block, &fn_name, &mut found);
+                                issues.extend(found.into_iter().map(|(issue, span)| {
+                                    (issue, SourceSpan::from_span(span).in_function(&fn_name))
+                                }));
                             }
                         }
                     }
@@ -83,7 +105,7 @@ impl Rule for PanicDetectionRule {
 
         issues
             .into_iter()
-            .map(|issue| {
+            .map(|(issue, span)| {
                 let severity = match issue.issue_type.as_str() {
                     "panic!" => Severity::Error,
                     _ => Severity::Warning,
@@ -94,17 +116,162 @@ impl Rule for PanicDetectionRule {
                     format!("Use of '{}' can cause contract failure", issue.issue_type),
                     issue.location,
                 )
+                .with_span(span)
                 .with_suggestion("Use Result types and proper error handling instead".to_string())
             })
             .collect()
     }
 
+    fn fix(&self, source: &str) -> Vec<Patch> {
+        self.fix_in_crate(source, &[])
+    }
+
+    fn fix_in_crate(&self, source: &str, crate_errors: &[LayoutType]) -> Vec<Patch> {
+        let file = match parse_str::<File>(source) {
+            Ok(f) => f,
+            Err(_) => return vec![],
+        };
+
+        let context = FixContext::with_crate_errors(source, &file, crate_errors);
+        let mut fns: Vec<(&syn::Signature, &syn::Block)> = Vec::new();
+        for item in &file.items {
+            match item {
+                syn::Item::Impl(i) if !is_cfg_test_item(&i.attrs) => {
+                    fns.extend(i.items.iter().filter_map(|impl_item| match impl_item {
+                        syn::ImplItem::Fn(f) if !has_test_attr(&f.attrs) => {
+                            Some((&f.sig, &f.block))
+                        }
+                        _ => None,
+                    }));
+                }
+                syn::Item::Mod(m) if !is_cfg_test_item(&m.attrs) => {
+                    if let Some((_, items)) = &m.content {
+                        fns.extend(items.iter().filter_map(|item| match item {
+                            syn::Item::Fn(f) if !has_test_attr(&f.attrs) => {
+                                Some((&f.sig, &*f.block))
+                            }
+                            _ => None,
+                        }));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut patches = Vec::new();
+        for (sig, block) in fns {
+            let Some(failure) = context.failure(sig) else {
+                continue;
+            };
+            let mut fixer = UnwrapFixer {
+                context: &context,
+                failure,
+                fn_name: sig.ident.to_string(),
+                patches: Vec::new(),
+            };
+            fixer.visit_block(block);
+            patches.extend(fixer.patches);
+        }
+        patches
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
 
-fn check_fn_panics(block: &syn::Block, fn_name: &str, issues: &mut Vec<PanicIssue>) {
+/// Rewrites the `unwrap()`/`expect()` calls of one function.  Closures are
+/// skipped, as `?` inside them would not return from the function.
+struct UnwrapFixer<'c> {
+    context: &'c FixContext<'c>,
+    failure: FnFailure<'c>,
+    fn_name: String,
+    patches: Vec<Patch>,
+}
+
+/// What an `unwrap()` receiver evaluates to, judged by the call producing it.
+enum Fallible {
+    Option(&'static [&'static str]),
+    Result(&'static [&'static str]),
+}
+
+fn fallible(expr: &syn::Expr) -> Option<Fallible> {
+    match expr {
+        syn::Expr::Paren(p) => fallible(&p.expr),
+        syn::Expr::MethodCall(m) => match m.method.to_string().as_str() {
+            "checked_add" | "checked_mul" | "checked_pow" | "checked_neg" => {
+                Some(Fallible::Option(OVERFLOW_ERRORS))
+            }
+            "checked_sub" => Some(Fallible::Option(UNDERFLOW_ERRORS)),
+            "checked_div" | "checked_rem" => Some(Fallible::Option(DIVISION_ERRORS)),
+            "get" | "first" | "last" | "pop_front" | "pop_back" | "next" | "find" | "position"
+            | "ok" => Some(Fallible::Option(MISSING_ERRORS)),
+            method if method.starts_with("try_") || method == "parse" => {
+                Some(Fallible::Result(CONVERSION_ERRORS))
+            }
+            _ => None,
+        },
+        syn::Expr::Call(c) => match &*c.func {
+            syn::Expr::Path(p)
+                if p.path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident.to_string().starts_with("try_")) =>
+            {
+                Some(Fallible::Result(CONVERSION_ERRORS))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl UnwrapFixer<'_> {
+    fn replacement(&self, call: &syn::ExprMethodCall) -> Option<(String, String)> {
+        let receiver = self.context.text(call.receiver.span())?;
+        match fallible(&call.receiver)? {
+            Fallible::Option(errors) => {
+                let error = self.failure.variant(errors)?;
+                Some((self.failure.on_none(receiver, &error), error))
+            }
+            Fallible::Result(errors) => {
+                let error = self.failure.variant(errors)?;
+                Some((self.failure.on_err(receiver, &error), error))
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UnwrapFixer<'_> {
+    fn visit_expr_closure(&mut self, _node: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        let before = self.patches.len();
+        syn::visit::visit_expr_method_call(self, node);
+        let method = node.method.to_string();
+        // A call containing a rewritten one is left for the next run, as
+        // the two patches would overlap.
+        if self.patches.len() > before || (method != "unwrap" && method != "expect") {
+            return;
+        }
+        if let Some((replacement, error)) = self.replacement(node) {
+            let span = node.span();
+            self.patches.push(Patch {
+                start_line: span.start().line,
+                start_column: span.start().column,
+                end_line: span.end().line,
+                end_column: span.end().column,
+                replacement,
+                description: format!(
+                    "Report {} instead of calling {}() in function '{}'",
+                    error, method, self.fn_name
+                ),
+            });
+        }
+    }
+}
+
+fn check_fn_panics(block: &syn::Block, fn_name: &str, issues: &mut Vec<(PanicIssue, proc_macro2::Span)>) {
     for stmt in &block.stmts {
         match stmt {
             syn::Stmt::Expr(expr, _) => check_expr_panics(expr, fn_name, issues),
@@ -114,34 +281,43 @@ fn check_fn_panics(block: &syn::Block, fn_name: &str, issues: &mut Vec<PanicIssu
                 }
             }
             syn::Stmt::Macro(m) if m.mac.path.is_ident("panic") => {
-                issues.push(PanicIssue {
-                    function_name: fn_name.to_string(),
-                    issue_type: "panic!".to_string(),
-                    location: fn_name.to_string(),
-                });
+                issues.push((
+                    PanicIssue {
+                        function_name: fn_name.to_string(),
+                        issue_type: "panic!".to_string(),
+                        location: fn_name.to_string(),
+                    },
+                    m.span(),
+                ));
             }
             _ => {}
         }
     }
 }
 
-fn check_expr_panics(expr: &syn::Expr, fn_name: &str, issues: &mut Vec<PanicIssue>) {
+fn check_expr_panics(expr: &syn::Expr, fn_name: &str, issues: &mut Vec<(PanicIssue, proc_macro2::Span)>) {
     match expr {
         syn::Expr::Macro(m) if m.mac.path.is_ident("panic") => {
-            issues.push(PanicIssue {
-                function_name: fn_name.to_string(),
-                issue_type: "panic!".to_string(),
-                location: fn_name.to_string(),
-            });
+            issues.push((
+                PanicIssue {
+                    function_name: fn_name.to_string(),
+                    issue_type: "panic!".to_string(),
+                    location: fn_name.to_string(),
+                },
+                m.span(),
+            ));
         }
         syn::Expr::MethodCall(m) => {
             let method_name = m.method.to_string();
             if method_name == "unwrap" || method_name == "expect" {
-                issues.push(PanicIssue {
-                    function_name: fn_name.to_string(),
-                    issue_type: method_name,
-                    location: fn_name.to_string(),
-                });
+                issues.push((
+                    PanicIssue {
+                        function_name: fn_name.to_string(),
+                        issue_type: method_name,
+                        location: fn_name.to_string(),
+                    },
+                    m.span(),
+                ));
             }
             check_expr_panics(&m.receiver, fn_name, issues);
             for arg in &m.args {
@@ -257,4 +433,54 @@ mod tests {
         let violations = rule.check(source);
         assert_eq!(violations.len(), 0, "#[cfg(test)] module must be skipped");
     }
+
+    #[test]
+    fn fix_propagates_contract_errors_for_option_and_result_unwraps() {
+        let source = r#"
+            #[contracterror]
+            pub enum Error {
+                NotInitialized = 1,
+                InvalidInput = 2,
+            }
+
+            impl Token {
+                pub fn admin(env: Env) -> Result<Address, Error> {
+                    let admin = env.storage().instance().get(&DataKey::Admin).unwrap();
+                    let amount: u32 = env.storage().instance().get(&DataKey::Limit).expect("limit").try_into().unwrap();
+                    Ok(admin)
+                }
+
+                pub fn decimals(env: Env) -> u32 {
+                    env.storage().instance().get(&DataKey::Decimals).unwrap()
+                }
+
+                pub fn name(env: Env) -> String {
+                    read_name(&env).unwrap()
+                }
+            }
+        "#;
+        let patches = PanicDetectionRule::new().fix(source);
+        let fixed = crate::patcher::Patcher::apply_patches(source, &patches);
+        assert!(fixed.contains(
+            "let admin = env.storage().instance().get(&DataKey::Admin).ok_or(Error::NotInitialized)?;"
+        ));
+        // The inner `expect` is fixed first; the outer `unwrap` on the next run.
+        assert!(fixed
+            .contains("get(&DataKey::Limit).ok_or(Error::NotInitialized)?.try_into().unwrap();"));
+        assert!(fixed.contains(
+            "env.storage().instance().get(&DataKey::Decimals).unwrap_or_else(|| soroban_sdk::panic_with_error!(env, Error::NotInitialized))"
+        ));
+        // Nothing says what `read_name` returns.
+        assert!(fixed.contains("read_name(&env).unwrap()"));
+
+        let second = PanicDetectionRule::new().fix(&fixed);
+        assert_eq!(second.len(), 1);
+        assert_eq!(
+            second[0]
+                .replacement
+                .matches("map_err(|_| Error::InvalidInput)?")
+                .count(),
+            1
+        );
+    }
 }
@@ -5,7 +5,7 @@
 //! `env.try_invoke_contract()`, which surfaces the callee result as a typed
 //! `Result`, and handle errors explicitly.
 
-use crate::rules::{Patch, Rule, RuleViolation, Severity};
+use crate::rules::{Patch, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
@@ -47,7 +47,15 @@ impl Rule for RawInvokeContractRule {
             if let Item::Impl(impl_block) = item {
                 for impl_item in &impl_block.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
+                        let fn_name = f.sig.ident.to_string();
+                        let found = violations.len();
                         scan_block(&f.block, &mut violations);
+                        for v in &mut violations[found..] {
+                            v.span = v
+                                .span
+                                .take()
+                                .map(|s| s.in_impl(impl_block).in_function(&fn_name));
+                        }
                     }
                 }
             }
@@ -95,6 +103,7 @@ fn scan_expr(expr: &syn::Expr, violations: &mut Vec<RuleViolation>) {
                         ),
                         format!("line {}", line),
                     )
+                    .with_span(SourceSpan::from_span(mc.span()))
                     .with_suggestion(
                         "Replace `env.invoke_contract::<T>(…)` with \
                          `env.try_invoke_contract::<T, E>(…)?` or match on the returned Result \
@@ -7,7 +7,7 @@
 //! Also provides [`ReentrancyRule::fix`] which emits a [`Patch`] that inserts
 //! a boolean instance-storage lock guard around the external call site.
 
-use super::{Patch, Rule, RuleViolation, Severity};
+use super::{Patch, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
@@ -49,6 +49,7 @@ struct FnReentrancyState {
 struct ReentrancyViolation {
     fn_name: String,
     line: usize,
+    span: proc_macro2::Span,
 }
 
 // ── Rule implementation ───────────────────────────────────────────────────────
@@ -91,6 +92,11 @@ impl Rule for ReentrancyRule {
                                     ),
                                     format!("{}:{}", v.fn_name, v.line),
                                 )
+                                .with_span(
+                                    SourceSpan::from_span(v.span)
+                                        .in_impl(impl_block)
+                                        .in_function(&v.fn_name),
+                                )
                                 .with_suggestion(
                                     "Wrap the external call with a boolean instance-storage \
                                      lock: set REENTRANCY_LOCK to true before the call and \
@@ -221,6 +227,7 @@ fn scan_expr(expr: &syn::Expr, fn_name: &str, state: &mut FnReentrancyState) {
                 state.violations.push(ReentrancyViolation {
                     fn_name: fn_name.to_string(),
                     line: span.start().line,
+                    span,
                 });
             }
 
@@ -242,6 +249,7 @@ fn scan_expr(expr: &syn::Expr, fn_name: &str, state: &mut FnReentrancyState) {
                         state.violations.push(ReentrancyViolation {
                             fn_name: fn_name.to_string(),
                             line: span.start().line,
+                            span,
                         });
                     }
                 }
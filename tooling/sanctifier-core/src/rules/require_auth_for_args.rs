@@ -1,4 +1,5 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
+use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
 /// Rule S030 — detects functions with multiple Address parameters that use
@@ -83,6 +84,11 @@ impl Rule for RequireAuthForArgsRule {
                                     ),
                                     fn_name.clone(),
                                 )
+                                .with_span(
+                                    SourceSpan::from_span(method.sig.span())
+                                        .in_impl(impl_block)
+                                        .in_function(&fn_name),
+                                )
                                 .with_suggestion(
                                     "Replace require_auth() with require_auth_for_args() to bind authorization to the exact call payload. \
                                      Example: address.require_auth_for_args((arg1, arg2, ...).into_val(&env)); \
@@ -0,0 +1,1411 @@
+//! Rule S033 — entry points that can exceed Soroban's per-transaction
+//! resource limits.
+//!
+//! A Soroban transaction declares its ledger footprint up front, and the
+//! network caps how many entries it may read and write, how many bytes those
+//! entries hold and how large the events it emits may be.  A call that needs
+//! more is rejected whatever fee the caller offers.
+//!
+//! For every public function found by [`discover_contracts`], this rule walks
+//! the body path by path — `if` and `match` arms fork a path, `return` and
+//! `?` end one, and a panic drops it since the call fails anyway — and counts
+//! on each path the distinct storage keys read and written, the contracts
+//! invoked and the bytes of every `events().publish(..)`.  Calls to other functions in the file are inlined,
+//! with their arguments substituted into the keys they touch.  An access in a
+//! loop whose key depends on the loop variable counts once per iteration: the
+//! bound of a literal or `const` range or `.take(n)`, else the
+//! `loop_iterations` option.  The worst path is compared against the network
+//! profile set under `[rules.resource_limits]`.
+//!
+//! Counts are estimates.  All `instance()` storage shares the contract
+//! instance entry, which is always in the footprint together with the
+//! contract code; an entry whose value type is unknown is assumed to hold
+//! `entry_bytes`.  Code entries count towards the entry limit but not towards
+//! read bytes, since their size is only known from the compiled wasm.
+
+use super::gas_exhaustion::const_values;
+use super::ledger_size::LedgerSizeRule;
+use super::{Rule, RuleViolation, Severity, SourceSpan};
+use crate::constant_folding::fold_to_i128;
+use crate::contract_discovery::{discover_contracts, entry_point};
+use crate::rule_config::RuleConfig;
+use crate::storage_collision::SorobanStorageType;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use syn::visit::Visit;
+use syn::{parse_str, Block, Expr, File, ImplItem, Item, Pat, Signature, Stmt, Type};
+
+/// Per-transaction resource limits of a Soroban network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkLimits {
+    /// Ledger entries in the footprint, read-only and read-write together.
+    pub read_entries: u64,
+    /// Ledger entries in the read-write footprint.
+    pub write_entries: u64,
+    /// Total size of the footprint entries.
+    pub read_bytes: u64,
+    /// Total size of the read-write entries after the call.
+    pub write_bytes: u64,
+    /// Total size of the contract events emitted.
+    pub events_bytes: u64,
+}
+
+impl NetworkLimits {
+    /// Stellar mainnet limits as of protocol 21.
+    pub const MAINNET: Self = Self {
+        read_entries: 40,
+        write_entries: 25,
+        read_bytes: 200_000,
+        write_bytes: 132_096,
+        events_bytes: 8_198,
+    };
+
+    /// Stellar testnet limits, which currently match mainnet.
+    pub const TESTNET: Self = Self::MAINNET;
+
+    /// Limits of a named profile: `"mainnet"` or `"testnet"`.
+    pub fn profile(name: &str) -> Option<Self> {
+        match name {
+            "mainnet" => Some(Self::MAINNET),
+            "testnet" => Some(Self::TESTNET),
+            _ => None,
+        }
+    }
+}
+
+/// Size assumed for a ledger entry whose value type is unknown.
+const DEFAULT_ENTRY_BYTES: u64 = 256;
+/// Iterations assumed for a loop without a constant bound.
+const DEFAULT_LOOP_ITERATIONS: u64 = 10;
+/// Paths kept per function before they are collapsed into their worst case.
+const MAX_PATHS: usize = 64;
+/// Event bytes beyond topics and data: contract id and XDR framing.
+const EVENT_OVERHEAD_BYTES: u64 = 40;
+/// Size assumed for an event topic or data value of unknown type.
+const UNKNOWN_VALUE_BYTES: u64 = 32;
+
+pub struct ResourceLimitsRule {
+    profile: String,
+    limits: NetworkLimits,
+    entry_bytes: u64,
+    loop_iterations: u64,
+}
+
+impl ResourceLimitsRule {
+    /// Check against the mainnet profile.
+    pub fn new() -> Self {
+        Self {
+            profile: "mainnet".to_string(),
+            limits: NetworkLimits::MAINNET,
+            entry_bytes: DEFAULT_ENTRY_BYTES,
+            loop_iterations: DEFAULT_LOOP_ITERATIONS,
+        }
+    }
+
+    /// Check against `limits`, reported under the profile name `profile`.
+    pub fn with_limits(mut self, profile: &str, limits: NetworkLimits) -> Self {
+        self.profile = profile.to_string();
+        self.limits = limits;
+        self
+    }
+
+    /// Assume `iterations` for loops without a constant bound.
+    pub fn with_loop_iterations(mut self, iterations: u64) -> Self {
+        self.loop_iterations = iterations;
+        self
+    }
+}
+
+impl Default for ResourceLimitsRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One limit checked per entry point.
+struct Resource {
+    /// What is counted, phrased to follow a number.
+    noun: &'static str,
+    used: fn(&Usage) -> u64,
+    limit: fn(&NetworkLimits) -> u64,
+    suggestion: &'static str,
+}
+
+const RESOURCES: &[Resource] = &[
+    Resource {
+        noun: "ledger entries in its footprint",
+        used: |u| u.read_entries,
+        limit: |l| l.read_entries,
+        suggestion: "Bound how many distinct storage keys and contracts one call can touch: cap \
+                     the loop with a constant maximum or paginate the batch across several \
+                     transactions.",
+    },
+    Resource {
+        noun: "written ledger entries",
+        used: |u| u.write_entries,
+        limit: |l| l.write_entries,
+        suggestion: "Cap how many distinct entries one call writes, e.g. by paginating batch \
+                     updates across transactions or aggregating per-item records into one entry.",
+    },
+    Resource {
+        noun: "bytes of ledger entries read",
+        used: |u| u.read_bytes,
+        limit: |l| l.read_bytes,
+        suggestion: "Read fewer or smaller entries per call; every footprint entry counts towards \
+                     the read limit in full, not just the fields the call uses.",
+    },
+    Resource {
+        noun: "bytes of ledger entries written",
+        used: |u| u.write_bytes,
+        limit: |l| l.write_bytes,
+        suggestion: "Shrink the values written per call or split large writes across \
+                     transactions.",
+    },
+    Resource {
+        noun: "bytes of events",
+        used: |u| u.events_bytes,
+        limit: |l| l.events_bytes,
+        suggestion: "Emit smaller events — publish identifiers instead of whole structs — or one \
+                     summary event instead of one per loop iteration.",
+    },
+];
+
+// ── Rule impl ──────────────────────────────────────────────────────────────────
+
+impl Rule for ResourceLimitsRule {
+    fn name(&self) -> &str {
+        "resource_limits"
+    }
+
+    fn description(&self) -> &str {
+        "Detects entry points whose storage footprint, written bytes or events can exceed \
+         Soroban's per-transaction resource limits (S033)"
+    }
+
+    fn configure(&mut self, config: &RuleConfig) -> Result<(), String> {
+        config.expect_options(&[
+            "profile",
+            "read_entries",
+            "write_entries",
+            "read_bytes",
+            "write_bytes",
+            "events_bytes",
+            "entry_bytes",
+            "loop_iterations",
+        ])?;
+        if let Some(name) = config.string_option("profile")? {
+            self.limits = NetworkLimits::profile(&name).ok_or_else(|| {
+                format!("unknown profile `{}`; expected mainnet or testnet", name)
+            })?;
+            self.profile = name;
+        }
+        let limits = &mut self.limits;
+        let overrides = [
+            ("read_entries", &mut limits.read_entries),
+            ("write_entries", &mut limits.write_entries),
+            ("read_bytes", &mut limits.read_bytes),
+            ("write_bytes", &mut limits.write_bytes),
+            ("events_bytes", &mut limits.events_bytes),
+        ];
+        for (key, limit) in overrides {
+            if let Some(value) = config.u64_option(key)? {
+                *limit = value;
+                self.profile = "custom".to_string();
+            }
+        }
+        if let Some(bytes) = config.u64_option("entry_bytes")? {
+            self.entry_bytes = bytes;
+        }
+        if let Some(iterations) = config.u64_option("loop_iterations")? {
+            self.loop_iterations = iterations;
+        }
+        Ok(())
+    }
+
+    fn check(&self, source: &str) -> Vec<RuleViolation> {
+        let file = match parse_str::<File>(source) {
+            Ok(f) => f,
+            Err(_) => return vec![],
+        };
+
+        let scope = Scope::new(&file, self.loop_iterations);
+        let mut violations = Vec::new();
+
+        for contract in discover_contracts(&file) {
+            for func in contract.all_public_functions() {
+                let Some((impl_block, f)) = entry_point(&file, &contract.struct_name, &func.name)
+                else {
+                    continue;
+                };
+                let usage = entry_point_usage(&scope, &f.sig, &f.block, self.entry_bytes);
+                let span = f.sig.ident.span();
+                for resource in RESOURCES {
+                    let (used, limit) = ((resource.used)(&usage), (resource.limit)(&self.limits));
+                    if used <= limit {
+                        continue;
+                    }
+                    violations.push(
+                        RuleViolation::new(
+                            self.name(),
+                            Severity::Warning,
+                            format!(
+                                "Entry point '{}' can need {} {} on one path, above the {} \
+                                 limit of {}",
+                                func.name, used, resource.noun, self.profile, limit
+                            ),
+                            format!("{}:{}", func.name, span.start().line),
+                        )
+                        .with_span(
+                            SourceSpan::from_span(span)
+                                .in_impl(impl_block)
+                                .in_function(&func.name),
+                        )
+                        .with_suggestion(resource.suggestion.to_string()),
+                    );
+                }
+            }
+        }
+
+        violations
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Worst resource usage over every path through an entry point.
+fn entry_point_usage(scope: &Scope, sig: &Signature, body: &Block, entry_bytes: u64) -> Usage {
+    let mut walker = Walker::new(scope, sig, HashMap::new());
+    let flow = walker.block(body, Flow::start(Cost::baseline()));
+    flow.open
+        .iter()
+        .chain(&flow.done)
+        .map(|path| path.usage(entry_bytes))
+        .fold(Usage::default(), Usage::max)
+}
+
+// ── Costs ─────────────────────────────────────────────────────────────────────
+
+/// Key of the contract instance entry, shared by all `instance()` storage.
+const INSTANCE_KEY: &str = "instance";
+/// Key of the contract code entry.
+const CODE_KEY: &str = "code";
+
+/// Resources one path through an entry point uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Usage {
+    read_entries: u64,
+    write_entries: u64,
+    read_bytes: u64,
+    write_bytes: u64,
+    events_bytes: u64,
+}
+
+impl Usage {
+    fn max(self, other: Self) -> Self {
+        Self {
+            read_entries: self.read_entries.max(other.read_entries),
+            write_entries: self.write_entries.max(other.write_entries),
+            read_bytes: self.read_bytes.max(other.read_bytes),
+            write_bytes: self.write_bytes.max(other.write_bytes),
+            events_bytes: self.events_bytes.max(other.events_bytes),
+        }
+    }
+}
+
+/// Ledger entries reached through one storage key.
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    /// Distinct entries behind the key — more than one when the key varies
+    /// with a loop variable.
+    count: u64,
+    /// Size of each entry, when its value type is known.  Code entries are
+    /// `Some(0)` so they never count towards read bytes.
+    bytes: Option<u64>,
+    /// Identifiers the key was built from.
+    idents: BTreeSet<String>,
+}
+
+impl Entry {
+    fn new(bytes: Option<u64>, idents: BTreeSet<String>) -> Self {
+        Self {
+            count: 1,
+            bytes,
+            idents,
+        }
+    }
+
+    fn merge(&mut self, other: &Entry) {
+        self.count = self.count.max(other.count);
+        self.bytes = match (self.bytes, other.bytes) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.idents.extend(other.idents.iter().cloned());
+    }
+}
+
+fn merge_entries(into: &mut BTreeMap<String, Entry>, from: &BTreeMap<String, Entry>) {
+    for (key, entry) in from {
+        into.entry(key.clone()).or_default().merge(entry);
+    }
+}
+
+/// Storage keys and event bytes used along one path.
+#[derive(Debug, Clone, Default)]
+struct Cost {
+    reads: BTreeMap<String, Entry>,
+    writes: BTreeMap<String, Entry>,
+    events_bytes: u64,
+}
+
+impl Cost {
+    /// The contract instance and code entries every invocation reads.
+    fn baseline() -> Self {
+        let mut cost = Self::default();
+        cost.read(INSTANCE_KEY.to_string(), Entry::new(None, BTreeSet::new()));
+        cost.read(CODE_KEY.to_string(), Entry::new(Some(0), BTreeSet::new()));
+        cost
+    }
+
+    fn read(&mut self, key: String, entry: Entry) {
+        self.reads.entry(key).or_default().merge(&entry);
+    }
+
+    fn write(&mut self, key: String, entry: Entry) {
+        self.writes.entry(key).or_default().merge(&entry);
+    }
+
+    /// Add the accesses of `other`, made after those of `self`.
+    fn then(&mut self, other: &Cost) {
+        merge_entries(&mut self.reads, &other.reads);
+        merge_entries(&mut self.writes, &other.writes);
+        self.events_bytes = self.events_bytes.saturating_add(other.events_bytes);
+    }
+
+    /// Widen to cover `other` as well, when either may happen.
+    fn widen(&mut self, other: &Cost) {
+        merge_entries(&mut self.reads, &other.reads);
+        merge_entries(&mut self.writes, &other.writes);
+        self.events_bytes = self.events_bytes.max(other.events_bytes);
+    }
+
+    /// Scale to `iterations` runs of a loop whose variables are `varying`:
+    /// keys built from them reach a new entry per iteration.
+    fn repeat(&mut self, iterations: u64, varying: &BTreeSet<String>) {
+        for entry in self.reads.values_mut().chain(self.writes.values_mut()) {
+            if !entry.idents.is_disjoint(varying) {
+                entry.count = entry.count.saturating_mul(iterations);
+            }
+        }
+        self.events_bytes = self.events_bytes.saturating_mul(iterations);
+    }
+
+    fn usage(&self, entry_bytes: u64) -> Usage {
+        let mut footprint = self.reads.clone();
+        merge_entries(&mut footprint, &self.writes);
+        let entries = |map: &BTreeMap<String, Entry>| {
+            map.values().map(|e| e.count).fold(0, u64::saturating_add)
+        };
+        let bytes = |map: &BTreeMap<String, Entry>| {
+            map.values()
+                .map(|e| e.count.saturating_mul(e.bytes.unwrap_or(entry_bytes)))
+                .fold(0, u64::saturating_add)
+        };
+        Usage {
+            read_entries: entries(&footprint),
+            write_entries: entries(&self.writes),
+            read_bytes: bytes(&footprint),
+            write_bytes: bytes(&self.writes),
+            events_bytes: self.events_bytes,
+        }
+    }
+}
+
+/// Paths walked so far: those still running and those that already returned
+/// or panicked.
+#[derive(Debug, Clone)]
+struct Flow {
+    open: Vec<Cost>,
+    done: Vec<Cost>,
+}
+
+impl Flow {
+    fn start(cost: Cost) -> Self {
+        Self {
+            open: vec![cost],
+            done: vec![],
+        }
+    }
+
+    /// The running paths, to walk one branch from.
+    fn fork(&self) -> Self {
+        Self {
+            open: self.open.clone(),
+            done: vec![],
+        }
+    }
+
+    /// Continue with the running paths of every branch.
+    fn join(&mut self, branches: Vec<Flow>) {
+        self.open.clear();
+        for branch in branches {
+            self.open.extend(branch.open);
+            self.done.extend(branch.done);
+        }
+        collapse(&mut self.open);
+        collapse(&mut self.done);
+    }
+
+    fn then(&mut self, cost: &Cost) {
+        for path in &mut self.open {
+            path.then(cost);
+        }
+    }
+
+    /// End every running path.
+    fn exit(&mut self) {
+        self.done.append(&mut self.open);
+    }
+
+    /// One cost covering every path.
+    fn worst(&self) -> Cost {
+        let mut worst = Cost::default();
+        for path in self.open.iter().chain(&self.done) {
+            worst.widen(path);
+        }
+        worst
+    }
+}
+
+/// Replace `paths` by their worst case once there are too many to track.
+fn collapse(paths: &mut Vec<Cost>) {
+    if paths.len() > MAX_PATHS {
+        let mut worst = Cost::default();
+        for path in paths.iter() {
+            worst.widen(path);
+        }
+        *paths = vec![worst];
+    }
+}
+
+// ── Walking function bodies ───────────────────────────────────────────────────
+
+/// File-level items the walk resolves names against.
+struct Scope<'a> {
+    fns: HashMap<String, (&'a Signature, &'a Block)>,
+    types: HashMap<String, &'a Item>,
+    consts: HashMap<String, i128>,
+    loop_iterations: u64,
+}
+
+impl<'a> Scope<'a> {
+    fn new(file: &'a File, loop_iterations: u64) -> Self {
+        let mut fns = HashMap::new();
+        let mut types = HashMap::new();
+        for item in &file.items {
+            match item {
+                Item::Fn(f) => {
+                    fns.entry(f.sig.ident.to_string())
+                        .or_insert((&f.sig, &*f.block));
+                }
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        if let ImplItem::Fn(f) = impl_item {
+                            fns.entry(f.sig.ident.to_string())
+                                .or_insert((&f.sig, &f.block));
+                        }
+                    }
+                }
+                Item::Struct(s) => {
+                    types.insert(s.ident.to_string(), item);
+                }
+                Item::Enum(e) => {
+                    types.insert(e.ident.to_string(), item);
+                }
+                _ => {}
+            }
+        }
+        Self {
+            fns,
+            types,
+            consts: const_values(file),
+            loop_iterations,
+        }
+    }
+
+    /// Estimated size of a value of type `ty`.
+    fn type_bytes(&self, ty: &Type) -> u64 {
+        let sizes = LedgerSizeRule::new();
+        if let Type::Reference(r) = ty {
+            return self.type_bytes(&r.elem);
+        }
+        let named = match ty {
+            Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+            _ => None,
+        };
+        let bytes = match named.and_then(|name| self.types.get(&name)) {
+            Some(Item::Struct(s)) => sizes.estimate_struct_size(s),
+            Some(Item::Enum(e)) => sizes.estimate_enum_size(e),
+            _ => sizes.estimate_type_size(ty),
+        };
+        bytes as u64
+    }
+
+    /// Integer value of a literal or `const` expression.
+    fn int(&self, expr: &Expr) -> Option<i128> {
+        fold_to_i128(expr).or_else(|| match expr {
+            Expr::Path(p) => self
+                .consts
+                .get(&p.path.segments.last()?.ident.to_string())
+                .copied(),
+            _ => None,
+        })
+    }
+
+    /// Iterations of a loop over `iter`.
+    fn iterations(&self, iter: &Expr) -> u64 {
+        let count = |n: i128| u64::try_from(n.max(0)).unwrap_or(u64::MAX);
+        match iter {
+            Expr::Range(range) => {
+                let start = match &range.start {
+                    Some(start) => self.int(start),
+                    None => Some(0),
+                };
+                let end = range.end.as_deref().and_then(|end| self.int(end));
+                match (start, end) {
+                    (Some(start), Some(end)) => {
+                        let inclusive = matches!(range.limits, syn::RangeLimits::Closed(_));
+                        count(end - start + i128::from(inclusive))
+                    }
+                    _ => self.loop_iterations,
+                }
+            }
+            Expr::MethodCall(call) if call.method == "take" => call
+                .args
+                .first()
+                .and_then(|n| self.int(n))
+                .map(count)
+                .unwrap_or_else(|| self.iterations(&call.receiver)),
+            Expr::MethodCall(call)
+                if matches!(
+                    call.method.to_string().as_str(),
+                    "iter" | "into_iter" | "enumerate" | "rev" | "keys" | "values"
+                ) =>
+            {
+                self.iterations(&call.receiver)
+            }
+            Expr::Array(array) => array.elems.len() as u64,
+            Expr::Reference(r) => self.iterations(&r.expr),
+            Expr::Paren(p) => self.iterations(&p.expr),
+            _ => self.loop_iterations,
+        }
+    }
+}
+
+/// Source text of a storage key with local names resolved, and the
+/// identifiers it was built from.
+#[derive(Debug, Clone, Default)]
+struct Key {
+    text: String,
+    idents: BTreeSet<String>,
+}
+
+/// Walks one function body, collecting the cost of each path.
+struct Walker<'s, 'a> {
+    scope: &'s Scope<'a>,
+    /// What a name stands for: the argument bound to an inlined function's
+    /// parameter, or the initializer of a local.
+    bindings: HashMap<String, Key>,
+    /// Declared types of parameters and locals.
+    types: HashMap<String, Type>,
+    /// Variables of the enclosing loops, innermost last.
+    loops: Vec<BTreeSet<String>>,
+    /// Functions being inlined, to stop at recursion.
+    calls: Vec<String>,
+}
+
+impl<'s, 'a> Walker<'s, 'a> {
+    fn new(scope: &'s Scope<'a>, sig: &Signature, bindings: HashMap<String, Key>) -> Self {
+        let types = sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(pt) => match pt.pat.as_ref() {
+                    Pat::Ident(pi) => Some((pi.ident.to_string(), (*pt.ty).clone())),
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect();
+        Self {
+            scope,
+            bindings,
+            types,
+            loops: vec![],
+            calls: vec![sig.ident.to_string()],
+        }
+    }
+
+    fn block(&mut self, block: &Block, mut flow: Flow) -> Flow {
+        for stmt in &block.stmts {
+            if flow.open.is_empty() {
+                break;
+            }
+            flow = self.stmt(stmt, flow);
+        }
+        flow
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, mut flow: Flow) -> Flow {
+        match stmt {
+            Stmt::Local(local) => {
+                let mut init_key = None;
+                if let Some(init) = &local.init {
+                    flow = self.expr(&init.expr, flow);
+                    if let Some((_, diverge)) = &init.diverge {
+                        // The `else` of a `let .. else` always leaves the function.
+                        let alt = self.expr(diverge, flow.fork());
+                        flow.done.extend(alt.open.into_iter().chain(alt.done));
+                    }
+                    init_key = Some(self.key(&init.expr));
+                }
+                self.bind(&local.pat, init_key);
+                flow
+            }
+            Stmt::Expr(expr, _) => self.expr(expr, flow),
+            Stmt::Macro(m) => self.mac(&m.mac, flow),
+            Stmt::Item(_) => flow,
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, mut flow: Flow) -> Flow {
+        match expr {
+            Expr::If(e) => {
+                flow = self.expr(&e.cond, flow);
+                let then = self.block(&e.then_branch, flow.fork());
+                let otherwise = match &e.else_branch {
+                    Some((_, alt)) => self.expr(alt, flow.fork()),
+                    None => flow.fork(),
+                };
+                flow.join(vec![then, otherwise]);
+                flow
+            }
+            Expr::Match(e) => {
+                flow = self.expr(&e.expr, flow);
+                let mut arms = Vec::new();
+                for arm in &e.arms {
+                    self.bind(&arm.pat, None);
+                    arms.push(self.expr(&arm.body, flow.fork()));
+                }
+                flow.join(arms);
+                flow
+            }
+            Expr::ForLoop(e) => {
+                flow = self.expr(&e.expr, flow);
+                let varying = binders(&e.pat);
+                self.unbind(&varying);
+                let iterations = self.scope.iterations(&e.expr);
+                self.repeat(&e.body, varying, iterations, flow)
+            }
+            Expr::While(e) => {
+                flow = self.expr(&e.cond, flow);
+                let varying = match e.cond.as_ref() {
+                    Expr::Let(cond) => binders(&cond.pat),
+                    _ => BTreeSet::new(),
+                };
+                let iterations = self.scope.loop_iterations;
+                self.repeat(&e.body, varying, iterations, flow)
+            }
+            Expr::Loop(e) => {
+                let iterations = self.scope.loop_iterations;
+                self.repeat(&e.body, BTreeSet::new(), iterations, flow)
+            }
+            Expr::Block(e) => self.block(&e.block, flow),
+            Expr::Unsafe(e) => self.block(&e.block, flow),
+            Expr::Return(e) => {
+                if let Some(value) = &e.expr {
+                    flow = self.expr(value, flow);
+                }
+                flow.exit();
+                flow
+            }
+            Expr::Try(e) => {
+                flow = self.expr(&e.expr, flow);
+                flow.done.extend(flow.open.iter().cloned());
+                collapse(&mut flow.done);
+                flow
+            }
+            Expr::Macro(e) => self.mac(&e.mac, flow),
+            Expr::Closure(e) => {
+                for input in &e.inputs {
+                    self.unbind(&binders(input));
+                }
+                // Whatever paths the closure takes, the caller carries on.
+                let body = self.expr(&e.body, Flow::start(Cost::default()));
+                flow.then(&body.worst());
+                flow
+            }
+            Expr::Let(e) => {
+                flow = self.expr(&e.expr, flow);
+                self.bind(&e.pat, None);
+                flow
+            }
+            Expr::MethodCall(e) => {
+                flow = self.expr(&e.receiver, flow);
+                for arg in &e.args {
+                    flow = self.expr(arg, flow);
+                }
+                let cost = self.method_call(e);
+                flow.then(&cost);
+                flow
+            }
+            Expr::Call(e) => {
+                for arg in &e.args {
+                    flow = self.expr(arg, flow);
+                }
+                let cost = self.call(e);
+                flow.then(&cost);
+                flow
+            }
+            Expr::Binary(e) => {
+                flow = self.expr(&e.left, flow);
+                self.expr(&e.right, flow)
+            }
+            Expr::Assign(e) => {
+                flow = self.expr(&e.left, flow);
+                self.expr(&e.right, flow)
+            }
+            Expr::Index(e) => {
+                flow = self.expr(&e.expr, flow);
+                self.expr(&e.index, flow)
+            }
+            Expr::Range(e) => {
+                for bound in e.start.iter().chain(&e.end) {
+                    flow = self.expr(bound, flow);
+                }
+                flow
+            }
+            Expr::Tuple(e) => self.exprs(e.elems.iter(), flow),
+            Expr::Array(e) => self.exprs(e.elems.iter(), flow),
+            Expr::Struct(e) => self.exprs(e.fields.iter().map(|f| &f.expr), flow),
+            Expr::Unary(e) => self.expr(&e.expr, flow),
+            Expr::Reference(e) => self.expr(&e.expr, flow),
+            Expr::Paren(e) => self.expr(&e.expr, flow),
+            Expr::Group(e) => self.expr(&e.expr, flow),
+            Expr::Field(e) => self.expr(&e.base, flow),
+            Expr::Cast(e) => self.expr(&e.expr, flow),
+            Expr::Break(e) => match &e.expr {
+                Some(value) => self.expr(value, flow),
+                None => flow,
+            },
+            _ => flow,
+        }
+    }
+
+    fn exprs<'e>(&mut self, exprs: impl Iterator<Item = &'e Expr>, mut flow: Flow) -> Flow {
+        for expr in exprs {
+            flow = self.expr(expr, flow);
+        }
+        flow
+    }
+
+    fn mac(&mut self, mac: &syn::Macro, mut flow: Flow) -> Flow {
+        let name = mac.path.segments.last().map(|s| s.ident.to_string());
+        if matches!(
+            name.as_deref(),
+            Some("panic" | "panic_with_error" | "unreachable" | "todo" | "unimplemented")
+        ) {
+            flow.open.clear();
+        }
+        flow
+    }
+
+    /// Run a loop body `iterations` times after `flow`.  The body's early
+    /// exits are folded into its worst case rather than ending the path.
+    fn repeat(
+        &mut self,
+        body: &Block,
+        varying: BTreeSet<String>,
+        iterations: u64,
+        mut flow: Flow,
+    ) -> Flow {
+        self.loops.push(varying);
+        let inner = self.block(body, Flow::start(Cost::default()));
+        let varying = self.loops.pop().unwrap_or_default();
+        let mut cost = inner.worst();
+        cost.repeat(iterations, &varying);
+        flow.then(&cost);
+        flow
+    }
+
+    /// Storage, event and cross-contract accesses of a method call.
+    fn method_call(&mut self, call: &syn::ExprMethodCall) -> Cost {
+        let mut cost = Cost::default();
+        let method = call.method.to_string();
+
+        if let Some(tier) = SorobanStorageType::of_receiver(&call.receiver) {
+            let (reads, writes) = match method.as_str() {
+                "get" | "has" | "extend_ttl" | "bump" => (true, false),
+                "set" => (false, true),
+                "update" | "try_update" => (true, true),
+                "remove" => (false, true),
+                _ => return cost,
+            };
+            let (key, idents) = match tier {
+                SorobanStorageType::Instance => (INSTANCE_KEY.to_string(), BTreeSet::new()),
+                _ => {
+                    let Some(arg) = call.args.first() else {
+                        return cost;
+                    };
+                    let key = self.key(arg);
+                    (format!("{}:{}", tier.as_str(), key.text), key.idents)
+                }
+            };
+            let bytes = match method.as_str() {
+                "set" => call.args.iter().nth(1).and_then(|v| self.value_bytes(v)),
+                "remove" => Some(0),
+                _ => None,
+            };
+            if reads {
+                cost.read(key.clone(), Entry::new(None, idents.clone()));
+            }
+            if writes {
+                cost.write(key, Entry::new(bytes, idents));
+            }
+        } else if method == "publish" && is_method_call(&call.receiver, "events") {
+            cost.events_bytes = call.args.iter().fold(EVENT_OVERHEAD_BYTES, |total, arg| {
+                total.saturating_add(self.value_bytes(arg).unwrap_or(UNKNOWN_VALUE_BYTES))
+            });
+        } else if matches!(method.as_str(), "invoke_contract" | "try_invoke_contract") {
+            if let Some(callee) = call.args.first() {
+                self.invoke(&mut cost, callee);
+            }
+        }
+        cost
+    }
+
+    /// Contract-client construction, or an inlined call to a function of
+    /// this file.
+    fn call(&mut self, call: &syn::ExprCall) -> Cost {
+        let mut cost = Cost::default();
+        let Expr::Path(func) = call.func.as_ref() else {
+            return cost;
+        };
+        let segments: Vec<String> = func
+            .path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        match segments.as_slice() {
+            [.., client, new] if new == "new" && client.ends_with("Client") => {
+                if let Some(callee) = call.args.iter().nth(1) {
+                    self.invoke(&mut cost, callee);
+                }
+            }
+            [name] | [_, name] if segments.len() == 1 || segments[0] == "Self" => {
+                if let Some(inlined) = self.inline(name, call) {
+                    cost = inlined;
+                }
+            }
+            _ => {}
+        }
+        cost
+    }
+
+    /// The callee's instance and code entries join the footprint.
+    fn invoke(&self, cost: &mut Cost, callee: &Expr) {
+        let key = self.key(callee);
+        cost.read(
+            format!("{}:{}", key.text, INSTANCE_KEY),
+            Entry::new(None, key.idents.clone()),
+        );
+        cost.read(
+            format!("{}:{}", key.text, CODE_KEY),
+            Entry::new(Some(0), key.idents),
+        );
+    }
+
+    /// Worst cost of the function `name` of this file, called with the
+    /// arguments of `call`.
+    fn inline(&self, name: &str, call: &syn::ExprCall) -> Option<Cost> {
+        let &(sig, body) = self.scope.fns.get(name)?;
+        if self.calls.iter().any(|c| c == name) {
+            return None;
+        }
+        let bindings = sig
+            .inputs
+            .iter()
+            .zip(&call.args)
+            .filter_map(|(param, arg)| match param {
+                syn::FnArg::Typed(pt) => match pt.pat.as_ref() {
+                    Pat::Ident(pi) => Some((pi.ident.to_string(), self.key(arg))),
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let mut callee = Walker::new(self.scope, sig, bindings);
+        callee.loops = self.loops.clone();
+        callee.calls.extend(self.calls.iter().cloned());
+        Some(callee.block(body, Flow::start(Cost::default())).worst())
+    }
+
+    /// Record the names `pat` binds.  A plain name bound to `init` stands for
+    /// it from now on; names bound from a loop variable vary with that loop.
+    fn bind(&mut self, pat: &Pat, init: Option<Key>) {
+        if let Pat::Type(typed) = pat {
+            if let Pat::Ident(pi) = typed.pat.as_ref() {
+                self.types.insert(pi.ident.to_string(), (*typed.ty).clone());
+            }
+            return self.bind(&typed.pat, init);
+        }
+        let names = binders(pat);
+        self.unbind(&names);
+        let Some(init) = init else {
+            return;
+        };
+        for varying in &mut self.loops {
+            if !init.idents.is_disjoint(varying) {
+                varying.extend(names.iter().cloned());
+            }
+        }
+        if let Pat::Ident(pi) = pat {
+            if pi.subpat.is_none() {
+                self.bindings.insert(pi.ident.to_string(), init);
+            }
+        }
+    }
+
+    fn unbind(&mut self, names: &BTreeSet<String>) {
+        for name in names {
+            self.bindings.remove(name);
+            self.types.remove(name);
+        }
+    }
+
+    fn key(&self, expr: &Expr) -> Key {
+        let mut key = Key::default();
+        self.render(expr.to_token_stream(), &mut key);
+        key
+    }
+
+    /// Append `tokens` to `key`, resolving bound names and dropping `&` and
+    /// `.clone()`, which do not change which entry a key names.
+    fn render(&self, tokens: TokenStream, key: &mut Key) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut i = 0;
+        while i < tokens.len() {
+            let after_path_or_field = i > 0
+                && matches!(&tokens[i - 1], TokenTree::Punct(p) if matches!(p.as_char(), '.' | ':'));
+            match &tokens[i] {
+                TokenTree::Punct(p) if p.as_char() == '&' => {}
+                TokenTree::Punct(p) if p.as_char() == '.' && is_clone_call(&tokens[i + 1..]) => {
+                    i += 2;
+                }
+                TokenTree::Ident(ident) => {
+                    let name = ident.to_string();
+                    match self.bindings.get(&name) {
+                        Some(bound) if !after_path_or_field => {
+                            push_word(&mut key.text, &bound.text);
+                            key.idents.extend(bound.idents.iter().cloned());
+                        }
+                        _ => {
+                            push_word(&mut key.text, &name);
+                            key.idents.insert(name);
+                        }
+                    }
+                }
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    key.text.push_str(open);
+                    self.render(group.stream(), key);
+                    key.text.push_str(close);
+                }
+                TokenTree::Literal(lit) => push_word(&mut key.text, &lit.to_string()),
+                TokenTree::Punct(p) => key.text.push(p.as_char()),
+            }
+            i += 1;
+        }
+    }
+
+    /// Estimated size of the value `expr` evaluates to, when its type is
+    /// known.
+    fn value_bytes(&self, expr: &Expr) -> Option<u64> {
+        match expr {
+            Expr::Reference(r) => self.value_bytes(&r.expr),
+            Expr::Paren(p) => self.value_bytes(&p.expr),
+            Expr::Group(g) => self.value_bytes(&g.expr),
+            Expr::MethodCall(call) if call.method == "clone" => self.value_bytes(&call.receiver),
+            Expr::Lit(lit) => match &lit.lit {
+                syn::Lit::Str(s) => Some(s.value().len() as u64 + 8),
+                syn::Lit::Bool(_) => Some(4),
+                syn::Lit::Int(_) => Some(16),
+                _ => None,
+            },
+            Expr::Path(p) => {
+                let name = p.path.get_ident()?.to_string();
+                self.types.get(&name).map(|ty| self.scope.type_bytes(ty))
+            }
+            Expr::Macro(m) if m.mac.path.is_ident("symbol_short") => Some(16),
+            Expr::Tuple(t) => Some(t.elems.iter().fold(0, |total, elem| {
+                total.saturating_add(self.value_bytes(elem).unwrap_or(UNKNOWN_VALUE_BYTES))
+            })),
+            Expr::Struct(s) => {
+                let name = s.path.segments.last()?.ident.to_string();
+                match self.scope.types.get(&name) {
+                    Some(Item::Struct(item)) => {
+                        Some(LedgerSizeRule::new().estimate_struct_size(item) as u64)
+                    }
+                    _ => None,
+                }
+            }
+            Expr::Cast(c) => Some(self.scope.type_bytes(&c.ty)),
+            _ => None,
+        }
+    }
+}
+
+fn is_method_call(expr: &Expr, method: &str) -> bool {
+    matches!(expr, Expr::MethodCall(call) if call.method == method)
+}
+
+/// Whether `tokens` starts with `clone()`.
+fn is_clone_call(tokens: &[TokenTree]) -> bool {
+    matches!(
+        tokens,
+        [TokenTree::Ident(ident), TokenTree::Group(args), ..]
+            if ident == "clone"
+                && args.delimiter() == Delimiter::Parenthesis
+                && args.stream().is_empty()
+    )
+}
+
+/// Append `word`, separated by a space from a preceding word.
+fn push_word(text: &mut String, word: &str) {
+    if text.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+        text.push(' ');
+    }
+    text.push_str(word);
+}
+
+/// Names bound by a pattern.
+fn binders(pat: &Pat) -> BTreeSet<String> {
+    struct Binders(BTreeSet<String>);
+    impl<'ast> Visit<'ast> for Binders {
+        fn visit_pat_ident(&mut self, pi: &'ast syn::PatIdent) {
+            self.0.insert(pi.ident.to_string());
+            syn::visit::visit_pat_ident(self, pi);
+        }
+    }
+    let mut names = Binders(BTreeSet::new());
+    names.visit_pat(pat);
+    names.0
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(body: &str) -> String {
+        format!(
+            r#"
+            #[contracttype]
+            pub enum DataKey {{ Balance(Address), Claimed(u32), Admin }}
+
+            #[contract]
+            pub struct Pool;
+
+            #[contractimpl]
+            impl Pool {{
+                {}
+            }}
+            "#,
+            body
+        )
+    }
+
+    fn messages(rule: &ResourceLimitsRule, source: &str) -> Vec<String> {
+        rule.check(source).into_iter().map(|v| v.message).collect()
+    }
+
+    #[test]
+    fn flags_writes_in_a_loop_over_distinct_keys() {
+        let source = contract(
+            r#"
+            pub fn mark_all(env: Env) {
+                for i in 0..50 {
+                    env.storage().persistent().set(&DataKey::Claimed(i), &true);
+                }
+            }
+            "#,
+        );
+        let v = ResourceLimitsRule::new().check(&source);
+        assert_eq!(v.len(), 2, "{:?}", v);
+        assert!(v[0]
+            .message
+            .contains("'mark_all' can need 52 ledger entries in its footprint"));
+        assert!(v[1].message.contains("50 written ledger entries"));
+        assert!(v[1].message.contains("mainnet limit of 25"));
+        assert_eq!(v[0].code, "S033");
+        assert_eq!(v[0].location, "mark_all:11");
+        assert!(v[0].suggestion.is_some());
+    }
+
+    #[test]
+    fn repeated_access_to_one_key_counts_once() {
+        let source = contract(
+            r#"
+            pub fn spin(env: Env, admin: Address) {
+                for _ in 0..1000 {
+                    let key = DataKey::Balance(admin.clone());
+                    let v: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+                    env.storage().persistent().set(&DataKey::Balance(admin), &(v + 1));
+                }
+            }
+            "#,
+        );
+        assert!(ResourceLimitsRule::new().check(&source).is_empty());
+    }
+
+    #[test]
+    fn branches_are_counted_separately() {
+        let branches = contract(
+            r#"
+            pub fn either(env: Env, flag: bool) {
+                if flag {
+                    for i in 0..20 { env.storage().persistent().set(&DataKey::Claimed(i), &true); }
+                } else {
+                    for j in 0..20 { env.storage().temporary().set(&DataKey::Claimed(j), &true); }
+                }
+            }
+            "#,
+        );
+        assert!(ResourceLimitsRule::new().check(&branches).is_empty());
+
+        let sequence = contract(
+            r#"
+            pub fn both(env: Env) {
+                for i in 0..20 { env.storage().persistent().set(&DataKey::Claimed(i), &true); }
+                for j in 0..20 { env.storage().temporary().set(&DataKey::Claimed(j), &true); }
+            }
+            "#,
+        );
+        let msgs = messages(&ResourceLimitsRule::new(), &sequence);
+        assert!(msgs.iter().any(|m| m.contains("40 written ledger entries")));
+    }
+
+    #[test]
+    fn panicking_paths_are_not_charged() {
+        let source = contract(
+            r#"
+            pub fn guarded(env: Env, n: u32) {
+                if n > 5 {
+                    panic!("too many");
+                }
+                for i in 0..30 {
+                    env.storage().persistent().set(&DataKey::Claimed(i), &true);
+                }
+            }
+            "#,
+        );
+        // The loop still runs on the path that did not panic.
+        assert!(!ResourceLimitsRule::new().check(&source).is_empty());
+
+        let source = contract(
+            r#"
+            pub fn guarded(env: Env, n: u32) {
+                if n > 5 {
+                    for i in 0..30 {
+                        env.storage().persistent().set(&DataKey::Claimed(i), &true);
+                    }
+                    panic!("rolled back");
+                }
+                env.storage().persistent().set(&DataKey::Claimed(n), &true);
+            }
+            "#,
+        );
+        assert!(ResourceLimitsRule::new().check(&source).is_empty());
+    }
+
+    #[test]
+    fn helpers_are_inlined_with_their_arguments() {
+        let source = contract(
+            r#"
+            pub fn reset(env: Env, users: Vec<Address>) {
+                for user in users.iter().take(30) {
+                    write_balance(&env, &user, 0);
+                }
+            }
+            "#,
+        ) + r#"
+            fn write_balance(env: &Env, who: &Address, amount: i128) {
+                let key = DataKey::Balance(who.clone());
+                env.storage().persistent().set(&key, &amount);
+            }
+        "#;
+        let msgs = messages(&ResourceLimitsRule::new(), &source);
+        assert!(
+            msgs.iter().any(|m| m.contains("30 written ledger entries")),
+            "{:?}",
+            msgs
+        );
+    }
+
+    #[test]
+    fn unbounded_loops_use_the_configured_iteration_count() {
+        let source = contract(
+            r#"
+            pub fn pay_all(env: Env, users: Vec<Address>) {
+                for user in users.iter() {
+                    env.storage().persistent().set(&DataKey::Balance(user), &0i128);
+                }
+            }
+            "#,
+        );
+        assert!(ResourceLimitsRule::new().check(&source).is_empty());
+        let rule = ResourceLimitsRule::new().with_loop_iterations(100);
+        assert_eq!(rule.check(&source).len(), 2);
+    }
+
+    #[test]
+    fn constant_bounds_are_resolved() {
+        let source = format!(
+            "const MAX_BATCH: u32 = 64;\n{}",
+            contract(
+                r#"
+                pub fn batch(env: Env) {
+                    for i in 0..MAX_BATCH {
+                        env.storage().temporary().set(&DataKey::Claimed(i), &true);
+                    }
+                }
+                "#,
+            )
+        );
+        let msgs = messages(&ResourceLimitsRule::new(), &source);
+        assert!(msgs.iter().any(|m| m.contains("64 written ledger entries")));
+    }
+
+    #[test]
+    fn flags_events_emitted_per_iteration() {
+        let source = contract(
+            r#"
+            pub fn airdrop(env: Env, to: Address, amount: i128) {
+                for i in 0..100 {
+                    env.events().publish((symbol_short!("drop"), to.clone()), amount);
+                }
+            }
+            "#,
+        );
+        let msgs = messages(&ResourceLimitsRule::new(), &source);
+        assert_eq!(msgs.len(), 1, "{:?}", msgs);
+        assert!(msgs[0].contains("bytes of events"));
+    }
+
+    #[test]
+    fn cross_contract_calls_add_the_callee_entries() {
+        let source = contract(
+            r#"
+            pub fn sweep(env: Env, tokens: Vec<Address>, to: Address) {
+                for token in tokens.iter().take(20) {
+                    TokenClient::new(&env, &token).transfer(&env.current_contract_address(), &to, &1);
+                }
+            }
+            "#,
+        );
+        let msgs = messages(&ResourceLimitsRule::new(), &source);
+        assert!(
+            msgs.iter()
+                .any(|m| m.contains("42 ledger entries in its footprint")),
+            "{:?}",
+            msgs
+        );
+    }
+
+    #[test]
+    fn value_types_size_written_bytes() {
+        let source = contract(
+            r#"
+            pub fn store(env: Env, blob: BytesN<32>) {
+                for i in 0..20 {
+                    env.storage().persistent().set(&DataKey::Claimed(i), &blob);
+                }
+            }
+            "#,
+        );
+        let rule = ResourceLimitsRule::new().with_limits(
+            "tight",
+            NetworkLimits {
+                write_bytes: 1000,
+                ..NetworkLimits::MAINNET
+            },
+        );
+        let msgs = messages(&rule, &source);
+        assert_eq!(msgs.len(), 1, "{:?}", msgs);
+        assert!(msgs[0].contains("1280 bytes of ledger entries written"));
+        assert!(msgs[0].contains("tight limit of 1000"));
+    }
+
+    #[test]
+    fn configure_reads_profile_and_overrides() {
+        let mut rule = ResourceLimitsRule::new();
+        let config: RuleConfig = serde_json::from_value(serde_json::json!({
+            "profile": "testnet",
+            "loop_iterations": 50,
+        }))
+        .unwrap();
+        rule.configure(&config).unwrap();
+        assert_eq!(rule.profile, "testnet");
+        assert_eq!(rule.limits, NetworkLimits::TESTNET);
+        assert_eq!(rule.loop_iterations, 50);
+
+        let config: RuleConfig =
+            serde_json::from_value(serde_json::json!({ "write_entries": 5 })).unwrap();
+        rule.configure(&config).unwrap();
+        assert_eq!(rule.profile, "custom");
+        assert_eq!(rule.limits.write_entries, 5);
+
+        let unknown: RuleConfig =
+            serde_json::from_value(serde_json::json!({ "profile": "futurenet" })).unwrap();
+        assert!(rule.configure(&unknown).is_err());
+        let typo: RuleConfig =
+            serde_json::from_value(serde_json::json!({ "write_entry": 5 })).unwrap();
+        assert!(rule.configure(&typo).is_err());
+    }
+
+    #[test]
+    fn non_contract_code_is_ignored() {
+        let source = r#"
+            impl Helper {
+                pub fn fill(env: Env) {
+                    for i in 0..100 {
+                        env.storage().persistent().set(&i, &i);
+                    }
+                }
+            }
+        "#;
+        assert!(ResourceLimitsRule::new().check(source).is_empty());
+    }
+
+    #[test]
+    fn empty_source_produces_no_findings() {
+        assert!(ResourceLimitsRule::new().check("").is_empty());
+    }
+
+    #[test]
+    fn invalid_source_produces_no_panic() {
+        assert!(ResourceLimitsRule::new()
+            .check("not valid rust {{{{")
+            .is_empty());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
+use syn::spanned::Spanned;
 use syn::{parse_str, Expr, File, Item, Stmt};
 
 /// Rule to detect shadow storage patterns in Soroban contracts.
@@ -73,7 +74,13 @@ impl Rule for ShadowStorageRule {
                                             fn_name
                                         ),
                                         location,
-                                    ).with_suggestion(
+                                    )
+                                    .with_span(
+                                        SourceSpan::from_span(fn_item.sig.span())
+                                            .in_impl(impl_block)
+                                            .in_function(&fn_name),
+                                    )
+                                    .with_suggestion(
                                         "Emit an event after each storage mutation using env.events().publish() to ensure indexers can track state changes. Example: env.events().publish((symbol_short!(\"event_name\"), key), data)".to_string()
                                     )
                                 );
@@ -6,7 +6,8 @@
 //! `Env::register_contract` + the generated client pattern so that the full
 //! Soroban execution stack is covered.
 
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
+use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
 /// Rule that flags `#[test]` functions in a contract crate that never reference
@@ -63,11 +64,7 @@ impl Rule for ShallowTestRule {
 fn source_has_contractimpl(file: &File) -> bool {
     for item in &file.items {
         match item {
-            Item::Impl(i) => {
-                if has_contractimpl_attr(&i.attrs) {
-                    return true;
-                }
-            }
+            Item::Impl(i) if has_contractimpl_attr(&i.attrs) => return true,
             Item::Mod(m) => {
                 if let Some((_, items)) = &m.content {
                     for inner in items {
@@ -117,10 +114,8 @@ fn collect_violations_from_items(items: &[Item], violations: &mut Vec<RuleViolat
     for item in items {
         match item {
             // Top-level `#[test]` free functions
-            Item::Fn(f) if has_test_attr(&f.attrs) => {
-                if !block_references_client(&f.block) {
-                    violations.push(make_violation(f.sig.ident.to_string()));
-                }
+            Item::Fn(f) if has_test_attr(&f.attrs) && !block_references_client(&f.block) => {
+                violations.push(make_violation(&f.sig, None));
             }
             // `#[cfg(test)]` modules — descend into them
             Item::Mod(m) if is_cfg_test(&m.attrs) => {
@@ -139,7 +134,7 @@ fn collect_violations_from_items(items: &[Item], violations: &mut Vec<RuleViolat
                 for impl_item in &i.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
                         if has_test_attr(&f.attrs) && !block_references_client(&f.block) {
-                            violations.push(make_violation(f.sig.ident.to_string()));
+                            violations.push(make_violation(&f.sig, Some(i)));
                         }
                     }
                 }
@@ -149,7 +144,12 @@ fn collect_violations_from_items(items: &[Item], violations: &mut Vec<RuleViolat
     }
 }
 
-fn make_violation(fn_name: String) -> RuleViolation {
+fn make_violation(sig: &syn::Signature, item_impl: Option<&syn::ItemImpl>) -> RuleViolation {
+    let fn_name = sig.ident.to_string();
+    let mut span = SourceSpan::from_span(sig.span()).in_function(&fn_name);
+    if let Some(item_impl) = item_impl {
+        span = span.in_impl(item_impl);
+    }
     RuleViolation::new(
         "shallow_test",
         Severity::Info,
@@ -160,6 +160,7 @@ fn make_violation(fn_name: String) -> RuleViolation {
         ),
         fn_name,
     )
+    .with_span(span)
     .with_suggestion(
         "Use `Env::register_contract` to deploy the contract under test and \
          call it through the generated `<ContractName>Client` to exercise the \
@@ -14,7 +14,7 @@
 //! - **Medium** — `try_invoke_contract` precedes a storage write (recoverable call).
 //! - **Low** — external call is separated from the write by control flow.
 
-use super::{Rule, RuleViolation, Severity};
+use super::{Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
@@ -68,6 +68,7 @@ struct FnCallState {
 struct StaticReentrancyViolation {
     fn_name: String,
     line: usize,
+    span: proc_macro2::Span,
     confidence: Confidence,
     #[allow(dead_code)]
     call_kind: ExternalCallKind,
@@ -136,6 +137,11 @@ impl Rule for StaticReentrancyRule {
                                     ),
                                     format!("{}:{}", v.fn_name, v.line),
                                 )
+                                .with_span(
+                                    SourceSpan::from_span(v.span)
+                                        .in_impl(impl_block)
+                                        .in_function(&v.fn_name),
+                                )
                                 .with_suggestion(
                                     "Follow the checks-effects-interactions pattern: \
                                      update all storage state BEFORE making external calls. \
@@ -215,6 +221,7 @@ fn scan_expr(expr: &syn::Expr, fn_name: &str, state: &mut FnCallState) {
                 state.violations.push(StaticReentrancyViolation {
                     fn_name: fn_name.to_string(),
                     line: span.start().line,
+                    span,
                     confidence: call_kind.confidence(),
                     call_kind,
                 });
@@ -1,5 +1,6 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
 use quote::ToTokens;
+use syn::spanned::Spanned;
 use syn::{parse_str, Expr, File, Item, Stmt};
 
 /// Rule to detect usage of env.storage().instance().update() without a state check.
@@ -49,8 +50,14 @@ impl Rule for StorageUpdateStateCheckRule {
                                         "Function '{}' calls update() without an explicit state check.",
                                         fn_name
                                     ),
-                                    fn_name,
-                                ).with_suggestion(
+                                    fn_name.clone(),
+                                )
+                                .with_span(
+                                    SourceSpan::from_span(fn_item.sig.span())
+                                        .in_impl(impl_block)
+                                        .in_function(fn_name),
+                                )
+                                .with_suggestion(
                                     "Ensure there is a state check (e.g., env.storage().instance().has()) before calling update() to prevent accidental overwrites.".to_string()
                                 ));
                             }
@@ -12,17 +12,39 @@
 //! rule previously missed entirely: a `for`/`while` loop body that taints a
 //! variable used by a later iteration, or a branch that only conditionally
 //! introduces taint before a sink is reached after the branches join.
+//!
+//! Flows through helper functions in the same file, or anywhere in the crate
+//! when run through [`Rule::check_in_crate`], are followed with
+//! [`crate::interprocedural`] summaries: an entry point that passes a
+//! parameter to a helper which writes it to storage is flagged at the call
+//! (`'set via Contract::write'`), and a helper that calls `require_auth`
+//! authorizes the entry point after the call.
+//!
+//! Projects with their own privileged entry points can add sink method names
+//! with the `extra_sinks` option of `[rules.taint_propagation]`.
 
-use super::{Rule, RuleViolation, Severity};
-use crate::taint_engine;
+use super::{impl_type_name, Rule, RuleViolation, Severity};
+use crate::interprocedural::{param_sources, CallSummaries};
+use crate::rule_config::RuleConfig;
+use crate::taint_engine::{self, TaintContext};
 use std::collections::HashSet;
 use syn::{parse_str, File, Item};
 
-pub struct TaintPropagationRule;
+pub struct TaintPropagationRule {
+    extra_sinks: Vec<String>,
+}
 
 impl TaintPropagationRule {
     pub fn new() -> Self {
-        Self
+        Self {
+            extra_sinks: Vec::new(),
+        }
+    }
+
+    /// Also treat calls to these method names as sinks.
+    pub fn with_extra_sinks(mut self, sinks: Vec<String>) -> Self {
+        self.extra_sinks = sinks;
+        self
     }
 }
 
@@ -44,16 +66,35 @@ impl Rule for TaintPropagationRule {
          when tainted values reach storage or external-call sinks without auth"
     }
 
+    fn configure(&mut self, config: &RuleConfig) -> Result<(), String> {
+        config.expect_options(&["extra_sinks"])?;
+        if let Some(sinks) = config.string_list_option("extra_sinks")? {
+            self.extra_sinks = sinks;
+        }
+        Ok(())
+    }
+
     fn check(&self, source: &str) -> Vec<RuleViolation> {
+        self.check_in_crate(source, &[])
+    }
+
+    fn check_in_crate(&self, source: &str, crate_sources: &[String]) -> Vec<RuleViolation> {
         let file = match parse_str::<File>(source) {
             Ok(f) => f,
             Err(_) => return vec![],
         };
 
+        let summaries = CallSummaries::from_sources(&file, crate_sources, &self.extra_sinks);
         let mut violations = Vec::new();
 
         for item in &file.items {
             if let Item::Impl(impl_block) = item {
+                let owner = impl_type_name(impl_block);
+                let ctx = TaintContext {
+                    extra_sinks: &self.extra_sinks,
+                    summaries: Some(&summaries),
+                    owner: owner.as_deref(),
+                };
                 for impl_item in &impl_block.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
                         if !matches!(f.vis, syn::Visibility::Public(_)) {
@@ -63,12 +104,15 @@ impl Rule for TaintPropagationRule {
                         let fn_name = f.sig.ident.to_string();
 
                         // Seed taint from parameters (excluding `env: Env` and `self`)
-                        let sources = collect_param_names(&f.sig);
+                        let sources: HashSet<String> = param_sources(&f.sig)
+                            .into_iter()
+                            .flat_map(|(_, names)| names)
+                            .collect();
                         if sources.is_empty() {
                             continue;
                         }
 
-                        let findings = taint_engine::analyze(&f.block, sources);
+                        let findings = taint_engine::analyze_with(&f.block, sources, ctx);
 
                         for finding in findings {
                             violations.push(
@@ -78,10 +122,13 @@ impl Rule for TaintPropagationRule {
                                     format!(
                                         "Function '{}': tainted variable '{}' reaches '{}' \
                                          sink without prior require_auth",
-                                        fn_name, finding.var, finding.sink
+                                        fn_name,
+                                        finding.var,
+                                        finding.sink_description()
                                     ),
                                     format!("{}:{}", fn_name, finding.line),
                                 )
+                                .with_span(finding.span.in_impl(impl_block).in_function(&fn_name))
                                 .with_suggestion(
                                     "Call require_auth() on any address parameter before using \
                                      user-controlled data in storage or external calls."
@@ -102,23 +149,6 @@ impl Rule for TaintPropagationRule {
     }
 }
 
-// ── Parameter extraction ───────────────────────────────────────────────────────
-
-fn collect_param_names(sig: &syn::Signature) -> HashSet<String> {
-    let mut names = HashSet::new();
-    for arg in &sig.inputs {
-        if let syn::FnArg::Typed(pt) = arg {
-            // Skip Env parameters
-            let ty_str = quote::quote!(#pt.ty).to_string();
-            if ty_str.contains("Env") {
-                continue;
-            }
-            taint_engine::collect_pat_idents(&pt.pat, &mut names);
-        }
-    }
-    names
-}
-
 // ── Unit tests ─────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -261,4 +291,59 @@ mod tests {
             "taint introduced in only one if-branch must still be flagged after the join"
         );
     }
+
+    #[test]
+    fn detects_taint_reaching_sink_in_private_helper() {
+        let source = r#"
+            impl Vault {
+                pub fn deposit(env: Env, amount: i128) {
+                    Self::credit(&env, amount);
+                }
+                fn credit(env: &Env, value: i128) {
+                    env.storage().persistent().set(&DataKey::Balance, &value);
+                }
+            }
+        "#;
+        let v = rule().check(source);
+        assert_eq!(v.len(), 1, "flow into a helper's sink must be flagged once");
+        assert!(v[0]
+            .message
+            .contains("'amount' reaches 'set via Vault::credit' sink"));
+    }
+
+    #[test]
+    fn require_auth_in_helper_suppresses_later_sinks() {
+        let source = r#"
+            fn ensure_owner(owner: &Address) {
+                owner.require_auth();
+            }
+            impl Vault {
+                pub fn deposit(env: Env, owner: Address, amount: i128) {
+                    ensure_owner(&owner);
+                    env.storage().persistent().set(&owner, &amount);
+                }
+            }
+        "#;
+        assert!(rule().check(source).is_empty());
+    }
+
+    #[test]
+    fn extra_sinks_from_config_are_flagged() {
+        let source = r#"
+            impl MyContract {
+                pub fn burn_for(env: Env, amount: i128) {
+                    token_client(&env).burn(&env.current_contract_address(), &amount);
+                }
+            }
+        "#;
+        assert!(rule().check(source).is_empty());
+
+        let mut configured = rule();
+        let config: RuleConfig =
+            serde_json::from_value(serde_json::json!({ "extra_sinks": ["burn"] })).unwrap();
+        configured.configure(&config).unwrap();
+        let v = configured.check(source);
+        assert_eq!(v.len(), 1);
+        assert!(v[0].message.contains("'burn' sink"));
+    }
 }
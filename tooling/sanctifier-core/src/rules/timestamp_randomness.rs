@@ -8,7 +8,7 @@
 //! - a function whose name contains `rand`, `seed`, `pick`, or `winner`, OR
 //! - a variable binding whose name contains `rand`, `seed`, `pick`, or `winner`.
 
-use super::{Rule, RuleViolation, Severity};
+use super::{Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item, Pat};
 
@@ -54,10 +54,10 @@ impl Rule for TimestampRandomnessRule {
                         let fn_is_sensitive =
                             SENSITIVE_NAMES.iter().any(|kw| fn_name_lower.contains(kw));
 
-                        let mut findings: Vec<(String, String)> = Vec::new();
+                        let mut findings: Vec<(String, proc_macro2::Span, String)> = Vec::new();
                         scan_block(&f.block, &fn_name, fn_is_sensitive, &mut findings);
 
-                        for (location, context) in findings {
+                        for (location, span, context) in findings {
                             violations.push(
                                 RuleViolation::new(
                                     self.name(),
@@ -68,6 +68,11 @@ impl Rule for TimestampRandomnessRule {
                                     ),
                                     location,
                                 )
+                                .with_span(
+                                    SourceSpan::from_span(span)
+                                        .in_impl(impl_block)
+                                        .in_function(&fn_name),
+                                )
                                 .with_suggestion(
                                     "Replace timestamp-based entropy with a VRF oracle or a combination \
                                     of unpredictable sources (e.g. transaction hash + sequence number). \
@@ -89,13 +94,13 @@ impl Rule for TimestampRandomnessRule {
     }
 }
 
-/// Walk a block and collect (location, context_label) for every
+/// Walk a block and collect (location, span, context_label) for every
 /// `env.ledger().timestamp()` call that appears in a sensitive context.
 fn scan_block(
     block: &syn::Block,
     fn_name: &str,
     fn_is_sensitive: bool,
-    findings: &mut Vec<(String, String)>,
+    findings: &mut Vec<(String, proc_macro2::Span, String)>,
 ) {
     for stmt in &block.stmts {
         match stmt {
@@ -136,7 +141,7 @@ fn scan_expr(
     expr: &syn::Expr,
     fn_name: &str,
     sensitive: bool,
-    findings: &mut Vec<(String, String)>,
+    findings: &mut Vec<(String, proc_macro2::Span, String)>,
 ) {
     match expr {
         syn::Expr::MethodCall(m) => {
@@ -147,6 +152,7 @@ fn scan_expr(
                     let span = m.span();
                     findings.push((
                         format!("{}:line {}", fn_name, span.start().line),
+                        span,
                         fn_name.to_string(),
                     ));
                 }
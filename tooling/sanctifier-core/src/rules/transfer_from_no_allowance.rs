@@ -1,4 +1,5 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
+use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
 /// Rule S023 — detects transfer_from-style functions that move a 'from' balance
@@ -61,6 +62,11 @@ impl Rule for TransferFromNoAllowanceRule {
                                     ),
                                     fn_name.clone(),
                                 )
+                                .with_span(
+                                    SourceSpan::from_span(method.sig.span())
+                                        .in_impl(impl_block)
+                                        .in_function(&fn_name),
+                                )
                                 .with_suggestion(
                                     "Read the spender's allowance for 'from', assert it is >= amount, decrement it, then move the balance. \
                                      Example: let allowance = get_allowance(&e, from.clone(), spender.clone()); \
@@ -1,4 +1,4 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Rule, RuleViolation, Severity, SourceSpan};
 use crate::TruncationBoundsIssue;
 use std::collections::HashSet;
 use syn::spanned::Spanned;
@@ -39,6 +39,7 @@ impl Rule for TruncationBoundsRule {
         let mut visitor = TruncationBoundsVisitor {
             issues: Vec::new(),
             current_fn: None,
+            current_impl: None,
             seen: HashSet::new(),
             test_mod_depth: 0,
         };
@@ -47,7 +48,7 @@ impl Rule for TruncationBoundsRule {
         visitor
             .issues
             .into_iter()
-            .map(|issue| {
+            .map(|(issue, span)| {
                 RuleViolation::new(
                     self.name(),
                     Severity::Warning,
@@ -62,6 +63,7 @@ impl Rule for TruncationBoundsRule {
                     ),
                     issue.location,
                 )
+                .with_span(span)
                 .with_suggestion(issue.suggestion)
             })
             .collect()
@@ -73,8 +75,9 @@ impl Rule for TruncationBoundsRule {
 }
 
 pub(crate) struct TruncationBoundsVisitor {
-    pub(crate) issues: Vec<TruncationBoundsIssue>,
+    pub(crate) issues: Vec<(TruncationBoundsIssue, SourceSpan)>,
     pub(crate) current_fn: Option<String>,
+    pub(crate) current_impl: Option<String>,
     pub(crate) seen: HashSet<(String, String)>,
     /// When >0 we are inside a #[cfg(test)] module and skip everything.
     pub(crate) test_mod_depth: u32,
@@ -86,6 +89,14 @@ pub(crate) struct TruncationBoundsVisitor {
 /// captures same-width sign changes like `u64 as i64` or `i32 as u32`.
 const NARROWING_TYPES: &[&str] = &["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
 
+impl TruncationBoundsVisitor {
+    fn span_of(&self, span: proc_macro2::Span, fn_name: &str) -> SourceSpan {
+        SourceSpan::from_span(span)
+            .in_function(fn_name)
+            .in_contract(self.current_impl.as_deref())
+    }
+}
+
 impl<'ast> Visit<'ast> for TruncationBoundsVisitor {
     // ── Module-level: skip #[cfg(test)] modules entirely ─────────────────────
     fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
@@ -98,13 +109,16 @@ impl<'ast> Visit<'ast> for TruncationBoundsVisitor {
         }
     }
 
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        syn::visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
         if self.test_mod_depth > 0 || has_test_attr(&node.attrs) {
             return;
         }
-        if has_allow_truncate(&node.attrs) {
-            return;
-        }
         let prev = self.current_fn.take();
         self.current_fn = Some(node.sig.ident.to_string());
         syn::visit::visit_impl_item_fn(self, node);
@@ -115,9 +129,6 @@ impl<'ast> Visit<'ast> for TruncationBoundsVisitor {
         if self.test_mod_depth > 0 || has_test_attr(&node.attrs) {
             return;
         }
-        if has_allow_truncate(&node.attrs) {
-            return;
-        }
         let prev = self.current_fn.take();
         self.current_fn = Some(node.sig.ident.to_string());
         syn::visit::visit_item_fn(self, node);
@@ -136,17 +147,21 @@ impl<'ast> Visit<'ast> for TruncationBoundsVisitor {
                         if !self.seen.contains(&key) {
                             self.seen.insert(key);
                             let line = node.span().start().line;
-                            self.issues.push(TruncationBoundsIssue {
-                                function_name: fn_name.clone(),
-                                kind: "truncation".to_string(),
-                                expression: expr_str,
-                                suggestion: format!(
-                                    "Use `{ty_name}::try_from(val).unwrap_or(...)` or \
-                                     `.try_into()` with proper error handling instead of `as {ty_name}`; \
-                                     suppress with `#[allow(sanctifier::truncate)]` if intentional"
-                                ),
-                                location: format!("{}:{}", fn_name, line),
-                            });
+                            let span = self.span_of(node.span(), &fn_name);
+                            self.issues.push((
+                                TruncationBoundsIssue {
+                                    function_name: fn_name.clone(),
+                                    kind: "truncation".to_string(),
+                                    expression: expr_str,
+                                    suggestion: format!(
+                                        "Use `{ty_name}::try_from(val).unwrap_or(...)` or \
+                                         `.try_into()` with proper error handling instead of `as {ty_name}`; \
+                                         suppress with `#[allow(sanctifier::truncate)]` if intentional"
+                                    ),
+                                    location: format!("{}:{}", fn_name, line),
+                                },
+                                span,
+                            ));
                         }
                     }
                 }
@@ -164,14 +179,18 @@ impl<'ast> Visit<'ast> for TruncationBoundsVisitor {
             if !self.seen.contains(&key) {
                 self.seen.insert(key);
                 let line = node.span().start().line;
-                self.issues.push(TruncationBoundsIssue {
-                    function_name: fn_name.clone(),
-                    kind: "unchecked_index".to_string(),
-                    expression: expr_str,
-                    suggestion: "Use `.get(index)` with bounds checking instead of direct indexing"
-                        .to_string(),
-                    location: format!("{}:{}", fn_name, line),
-                });
+                let span = self.span_of(node.span(), &fn_name);
+                self.issues.push((
+                    TruncationBoundsIssue {
+                        function_name: fn_name.clone(),
+                        kind: "unchecked_index".to_string(),
+                        expression: expr_str,
+                        suggestion: "Use `.get(index)` with bounds checking instead of direct indexing"
+                            .to_string(),
+                        location: format!("{}:{}", fn_name, line),
+                    },
+                    span,
+                ));
             }
         }
         syn::visit::visit_expr_index(self, node);
@@ -190,16 +209,6 @@ fn is_cfg_test(attrs: &[syn::Attribute]) -> bool {
         .any(|a| a.path().is_ident("cfg") && quote::quote!(#a).to_string().contains("test"))
 }
 
-/// Returns true if the item has `#[allow(sanctifier::truncate)]`, allowing the
-/// developer to opt out of truncation warnings for a specific function.
-fn has_allow_truncate(attrs: &[syn::Attribute]) -> bool {
-    attrs.iter().any(|a| {
-        a.path().is_ident("allow")
-            && quote::quote!(#a).to_string().contains("sanctifier")
-            && quote::quote!(#a).to_string().contains("truncate")
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -381,17 +390,25 @@ mod tests {
     }
 
     // ── #[allow(sanctifier::truncate)] opt-out ────────────────────────────────
+    //
+    // The attribute is handled by `RuleRegistry`, so these go through it.
+
+    fn registry() -> crate::rules::RuleRegistry {
+        let mut registry = crate::rules::RuleRegistry::new();
+        registry.register(TruncationBoundsRule::new());
+        registry
+    }
 
     #[test]
     fn test_allow_truncate_suppresses_violation_on_item_fn() {
-        let rule = TruncationBoundsRule::new();
+        let registry = registry();
         let source = r#"
             #[allow(sanctifier::truncate)]
             fn convert(val: u128) -> u64 {
                 val as u64
             }
         "#;
-        let violations = rule.check(source);
+        let violations = registry.run_all(source);
         assert_eq!(
             violations.len(),
             0,
@@ -401,7 +418,7 @@ mod tests {
 
     #[test]
     fn test_allow_truncate_suppresses_violation_on_impl_fn() {
-        let rule = TruncationBoundsRule::new();
+        let registry = registry();
         let source = r#"
             impl Codec {
                 #[allow(sanctifier::truncate)]
@@ -410,7 +427,7 @@ mod tests {
                 }
             }
         "#;
-        let violations = rule.check(source);
+        let violations = registry.run_all(source);
         assert_eq!(
             violations.len(),
             0,
@@ -420,7 +437,7 @@ mod tests {
 
     #[test]
     fn test_allow_truncate_only_suppresses_annotated_function() {
-        let rule = TruncationBoundsRule::new();
+        let registry = registry();
         let source = r#"
             #[allow(sanctifier::truncate)]
             fn allowed(val: u128) -> u64 {
@@ -431,7 +448,7 @@ mod tests {
                 val as u64
             }
         "#;
-        let violations = rule.check(source);
+        let violations = registry.run_all(source);
         assert_eq!(
             violations.len(),
             1,
@@ -1,4 +1,4 @@
-use crate::rules::{Patch, Rule, RuleViolation, Severity};
+use crate::rules::{Patch, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
 
@@ -38,7 +38,12 @@ impl Rule for UncheckedExternalCallRule {
             if let Item::Impl(i) = item {
                 for impl_item in &i.items {
                     if let syn::ImplItem::Fn(f) = impl_item {
+                        let fn_name = f.sig.ident.to_string();
+                        let found = violations.len();
                         check_function_for_unchecked_calls(&f.block, &mut violations);
+                        for v in &mut violations[found..] {
+                            v.span = v.span.take().map(|s| s.in_impl(i).in_function(&fn_name));
+                        }
                     }
                 }
             }
@@ -70,6 +75,7 @@ fn check_function_for_unchecked_calls(block: &syn::Block, violations: &mut Vec<R
                             "External contract call result is not checked or handled".to_string(),
                             format!("line {}", span.start().line),
                         )
+                        .with_span(SourceSpan::from_span(span))
                         .with_suggestion(
                             "Store the result in a variable and handle errors with match, ?, or .unwrap_or()".to_string()
                         ),
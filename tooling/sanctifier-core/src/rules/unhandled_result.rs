@@ -1,4 +1,4 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Rule, RuleViolation, Severity, SourceSpan};
 use syn::spanned::Spanned;
 use syn::visit::Visit;
 use syn::{parse_str, File, Type};
@@ -37,6 +37,7 @@ impl Rule for UnhandledResultRule {
         let mut visitor = ResultVisitor {
             issues: Vec::new(),
             current_fn: None,
+            current_impl: None,
             is_public_fn: false,
         };
         visitor.visit_file(&file);
@@ -51,6 +52,7 @@ impl Rule for UnhandledResultRule {
                     issue.message,
                     issue.location,
                 )
+                .with_span(issue.span)
                 .with_suggestion(
                     "Use ?, match, or .unwrap()/.expect() to handle the Result".to_string(),
                 )
@@ -66,12 +68,14 @@ impl Rule for UnhandledResultRule {
 struct ResultVisitor {
     issues: Vec<UnhandledResultIssue>,
     current_fn: Option<String>,
+    current_impl: Option<String>,
     is_public_fn: bool,
 }
 
 struct UnhandledResultIssue {
     message: String,
     location: String,
+    span: SourceSpan,
 }
 
 impl ResultVisitor {
@@ -180,6 +184,12 @@ impl ResultVisitor {
 }
 
 impl<'ast> Visit<'ast> for ResultVisitor {
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        syn::visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
         let prev_fn = self.current_fn.take();
         let prev_public = self.is_public_fn;
@@ -245,6 +255,9 @@ impl ResultVisitor {
                                 Self::expr_to_string(expr)
                             ),
                             location: format!("{}:{}", fn_name, line),
+                            span: SourceSpan::from_span(expr.span())
+                                .in_function(fn_name)
+                                .in_contract(self.current_impl.as_deref()),
                         });
                     }
                 }
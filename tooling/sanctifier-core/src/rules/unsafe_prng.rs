@@ -1,4 +1,4 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{Rule, RuleViolation, Severity, SourceSpan};
 use crate::soroban_v21::is_prng_function;
 use syn::spanned::Spanned;
 use syn::{parse_str, File, Item};
@@ -61,10 +61,11 @@ impl Rule for UnsafePrngRule {
 
                         // Flag if PRNG is used in a function that mutates storage without reseeding
                         if has_prng_usage && has_storage_mutation && !has_prng_reseed {
-                            let location = if !prng_locations.is_empty() {
-                                format!("{}:{}", fn_name, prng_locations[0])
-                            } else {
-                                fn_name.clone()
+                            let (location, span) = match prng_locations.first() {
+                                Some(span) => {
+                                    (format!("{}:line {}", fn_name, span.start().line), *span)
+                                }
+                                None => (fn_name.clone(), f.sig.span()),
                             };
 
                             violations.push(
@@ -78,6 +79,7 @@ impl Rule for UnsafePrngRule {
                                     ),
                                     location,
                                 )
+                                .with_span(SourceSpan::from_span(span).in_impl(i).in_function(&fn_name))
                                 .with_suggestion(
                                     "Consider using prng.reseed() with unpredictable entropy (e.g., from ledger timestamp, \
                                     transaction hash, or external oracle) before generating random values for state-critical operations. \
@@ -104,7 +106,7 @@ fn analyze_fn_body(
     has_prng_usage: &mut bool,
     has_prng_reseed: &mut bool,
     has_storage_mutation: &mut bool,
-    prng_locations: &mut Vec<String>,
+    prng_locations: &mut Vec<proc_macro2::Span>,
 ) {
     for stmt in &block.stmts {
         match stmt {
@@ -139,7 +141,7 @@ fn analyze_expr(
     has_prng_usage: &mut bool,
     has_prng_reseed: &mut bool,
     has_storage_mutation: &mut bool,
-    prng_locations: &mut Vec<String>,
+    prng_locations: &mut Vec<proc_macro2::Span>,
 ) {
     match expr {
         syn::Expr::MethodCall(m) => {
@@ -149,14 +151,14 @@ fn analyze_expr(
             if method_name == "prng" {
                 *has_prng_usage = true;
                 let span = m.span();
-                prng_locations.push(format!("line {}", span.start().line));
+                prng_locations.push(span);
             }
 
             // Check for PRNG host functions (v21)
             if is_prng_function(&method_name) {
                 *has_prng_usage = true;
                 let span = m.span();
-                prng_locations.push(format!("line {}", span.start().line));
+                prng_locations.push(span);
             }
 
             // Check for reseed calls
@@ -205,7 +207,7 @@ fn analyze_expr(
                     if is_prng_function(&ident) {
                         *has_prng_usage = true;
                         let span = c.span();
-                        prng_locations.push(format!("line {}", span.start().line));
+                        prng_locations.push(span);
                     }
                 }
             }
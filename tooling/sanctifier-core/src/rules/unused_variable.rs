@@ -1,4 +1,4 @@
-use crate::rules::{Patch, Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Patch, Rule, RuleViolation, Severity, SourceSpan};
 use syn::visit::{self, Visit};
 use syn::{parse_str, File, Local, Pat};
 
@@ -37,10 +37,9 @@ impl Rule for UnusedVariableRule {
         visitor.visit_file(&file);
 
         let mut violations = Vec::new();
-        for (ident, span) in visitor.unused_locals {
-            let line = span.start().line;
-            let col = span.start().column;
-            let name = ident.to_string();
+        for (name, span) in visitor.unused_locals {
+            let line = span.start_line;
+            let col = span.start_column;
 
             let patch = Patch {
                 start_line: line,
@@ -58,6 +57,7 @@ impl Rule for UnusedVariableRule {
                     format!("Unused local variable: '{}'", name),
                     format!("{}:{}", line, col),
                 )
+                .with_span(span)
                 .with_suggestion(format!("Prefix with underscore: '_{}'", name))
                 .with_patches(vec![patch]),
             );
@@ -79,8 +79,10 @@ impl Rule for UnusedVariableRule {
 
 struct UnusedVariableVisitor {
     // Current scope locals: (name, span, was_used)
-    locals_stack: Vec<Vec<(String, proc_macro2::Span, bool)>>,
-    unused_locals: Vec<(String, proc_macro2::Span)>,
+    locals_stack: Vec<Vec<(String, SourceSpan, bool)>>,
+    unused_locals: Vec<(String, SourceSpan)>,
+    current_fn: Option<String>,
+    current_impl: Option<String>,
 }
 
 impl UnusedVariableVisitor {
@@ -88,6 +90,8 @@ impl UnusedVariableVisitor {
         Self {
             locals_stack: vec![vec![]],
             unused_locals: Vec::new(),
+            current_fn: None,
+            current_impl: None,
         }
     }
 
@@ -117,6 +121,10 @@ impl UnusedVariableVisitor {
     }
 
     fn add_local(&mut self, name: String, span: proc_macro2::Span) {
+        let mut span = SourceSpan::from_span(span).in_contract(self.current_impl.as_deref());
+        if let Some(fn_name) = &self.current_fn {
+            span = span.in_function(fn_name);
+        }
         if let Some(scope) = self.locals_stack.last_mut() {
             scope.push((name, span, false));
         }
@@ -124,17 +132,27 @@ impl UnusedVariableVisitor {
 }
 
 impl<'ast> Visit<'ast> for UnusedVariableVisitor {
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let prev = self.current_fn.replace(node.sig.ident.to_string());
         self.enter_scope();
         // Skip arguments for now, focus on locals within the body
         visit::visit_item_fn(self, node);
         self.exit_scope();
+        self.current_fn = prev;
     }
 
     fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        let prev = self.current_fn.replace(node.sig.ident.to_string());
         self.enter_scope();
         visit::visit_impl_item_fn(self, node);
         self.exit_scope();
+        self.current_fn = prev;
     }
 
     fn visit_block(&mut self, node: &'ast syn::Block) {
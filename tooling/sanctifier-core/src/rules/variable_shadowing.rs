@@ -1,4 +1,4 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Rule, RuleViolation, Severity, SourceSpan};
 use std::collections::HashMap;
 use syn::visit::{self, Visit};
 use syn::{parse_str, File, Local, Pat};
@@ -45,8 +45,8 @@ impl Rule for VariableShadowingRule {
             .shadowing_violations
             .into_iter()
             .map(|(name, inner_span, outer_span)| {
-                let inner_line = inner_span.start().line;
-                let inner_col = inner_span.start().column;
+                let inner_line = inner_span.start_line;
+                let inner_col = inner_span.start_column;
                 let outer_line = outer_span.start().line;
 
                 RuleViolation::new(
@@ -58,6 +58,7 @@ impl Rule for VariableShadowingRule {
                     ),
                     format!("{}:{}", inner_line, inner_col),
                 )
+                .with_span(inner_span)
                 .with_suggestion(format!(
                     "Consider renaming the inner variable to avoid shadowing (e.g., '{}_inner', '{}_2')",
                     name, name
@@ -77,9 +78,13 @@ struct ShadowingVisitor {
     /// The last element is the current (innermost) scope.
     scope_stack: Vec<HashMap<String, proc_macro2::Span>>,
     /// Detected shadowing violations: (name, inner_span, outer_span)
-    shadowing_violations: Vec<(String, proc_macro2::Span, proc_macro2::Span)>,
+    shadowing_violations: Vec<(String, SourceSpan, proc_macro2::Span)>,
     /// Current scope depth (for debugging)
     depth: usize,
+    /// Enclosing function, recorded on each violation's span.
+    current_fn: Option<String>,
+    /// Self type of the enclosing `impl` block.
+    current_impl: Option<String>,
 }
 
 impl ShadowingVisitor {
@@ -88,6 +93,8 @@ impl ShadowingVisitor {
             scope_stack: vec![HashMap::new()],
             shadowing_violations: Vec::new(),
             depth: 0,
+            current_fn: None,
+            current_impl: None,
         }
     }
 
@@ -121,8 +128,13 @@ impl ShadowingVisitor {
         // Check if this variable shadows an outer variable
         for outer_scope in self.scope_stack.iter().rev().skip(1) {
             if let Some(&outer_span) = outer_scope.get(&name) {
+                let mut inner =
+                    SourceSpan::from_span(span).in_contract(self.current_impl.as_deref());
+                if let Some(fn_name) = &self.current_fn {
+                    inner = inner.in_function(fn_name);
+                }
                 self.shadowing_violations
-                    .push((name.clone(), span, outer_span));
+                    .push((name.clone(), inner, outer_span));
                 break;
             }
         }
@@ -135,7 +147,14 @@ impl ShadowingVisitor {
 }
 
 impl<'ast> Visit<'ast> for ShadowingVisitor {
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
     fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let prev_fn = self.current_fn.replace(node.sig.ident.to_string());
         self.enter_scope();
 
         // Add function parameters to the scope
@@ -152,9 +171,11 @@ impl<'ast> Visit<'ast> for ShadowingVisitor {
 
         visit::visit_item_fn(self, node);
         self.exit_scope();
+        self.current_fn = prev_fn;
     }
 
     fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        let prev_fn = self.current_fn.replace(node.sig.ident.to_string());
         self.enter_scope();
 
         // Add function parameters to the scope
@@ -171,6 +192,7 @@ impl<'ast> Visit<'ast> for ShadowingVisitor {
 
         visit::visit_impl_item_fn(self, node);
         self.exit_scope();
+        self.current_fn = prev_fn;
     }
 
     fn visit_block(&mut self, node: &'ast syn::Block) {
@@ -1,4 +1,4 @@
-use crate::rules::{Rule, RuleViolation, Severity};
+use crate::rules::{impl_type_name, Rule, RuleViolation, Severity, SourceSpan};
 use std::collections::HashSet;
 use syn::spanned::Spanned;
 use syn::visit::{self, Visit};
@@ -41,13 +41,14 @@ impl Rule for XdrRawConstructionRule {
         visitor
             .occurrences
             .into_iter()
-            .map(|(line, column)| {
+            .map(|span| {
                 RuleViolation::new(
                     self.name(),
                     Severity::Info,
                     "Direct xdr::ScVal raw construction is discouraged".to_string(),
-                    format!("{}:{}", line, column),
+                    format!("{}:{}", span.start_line, span.start_column),
                 )
+                .with_span(span)
                 .with_suggestion("Use typed Soroban helpers or ScVal constructors such as `ScVal::from_u64(...)` instead of raw `xdr::ScVal::...` calls.".to_string())
             })
             .collect()
@@ -59,8 +60,10 @@ impl Rule for XdrRawConstructionRule {
 }
 
 struct XdrRawConstructionVisitor {
-    occurrences: Vec<(usize, usize)>,
+    occurrences: Vec<SourceSpan>,
     seen: HashSet<(usize, usize)>,
+    current_fn: Option<String>,
+    current_impl: Option<String>,
 }
 
 impl XdrRawConstructionVisitor {
@@ -68,6 +71,8 @@ impl XdrRawConstructionVisitor {
         Self {
             occurrences: Vec::new(),
             seen: HashSet::new(),
+            current_fn: None,
+            current_impl: None,
         }
     }
 
@@ -75,12 +80,34 @@ impl XdrRawConstructionVisitor {
         let start = span.start();
         let key = (start.line, start.column);
         if self.seen.insert(key) {
-            self.occurrences.push(key);
+            let mut span = SourceSpan::from_span(span).in_contract(self.current_impl.as_deref());
+            if let Some(fn_name) = &self.current_fn {
+                span = span.in_function(fn_name);
+            }
+            self.occurrences.push(span);
         }
     }
 }
 
 impl<'ast> Visit<'ast> for XdrRawConstructionVisitor {
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let prev = std::mem::replace(&mut self.current_impl, impl_type_name(node));
+        visit::visit_item_impl(self, node);
+        self.current_impl = prev;
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let prev = self.current_fn.replace(node.sig.ident.to_string());
+        visit::visit_item_fn(self, node);
+        self.current_fn = prev;
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        let prev = self.current_fn.replace(node.sig.ident.to_string());
+        visit::visit_impl_item_fn(self, node);
+        self.current_fn = prev;
+    }
+
     fn visit_expr_call(&mut self, node: &'ast ExprCall) {
         if let Expr::Path(path) = &*node.func {
             if is_xdr_scval_constructor(path) {
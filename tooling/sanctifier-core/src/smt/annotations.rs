@@ -0,0 +1,82 @@
+//! AST-based extraction of `#[invariant = "..."]` annotations.
+//!
+//! Extraction only needs `syn`, so it is available without the `smt` feature
+//! and feeds both the Z3 verifier and the SMT-LIB2 emitter.
+
+use super::types::InvariantSpec;
+
+// ── Public API ────────────────────────────────────────────────────────────────
+
+/// Parse every `#[invariant = "..."]` attribute in the source file using the
+/// syn AST.  Returns an empty vec on parse errors (no panic).
+pub fn parse_invariants(source: &str) -> Vec<InvariantSpec> {
+    use syn::{parse_str, File, Item};
+
+    let file = match parse_str::<File>(source) {
+        Ok(f) => f,
+        Err(_) => return vec![],
+    };
+
+    let mut specs = Vec::new();
+
+    for item in &file.items {
+        if let Item::Impl(impl_block) = item {
+            for impl_item in &impl_block.items {
+                if let syn::ImplItem::Fn(f) = impl_item {
+                    let fn_name = f.sig.ident.to_string();
+                    for expression in invariant_attrs(&f.attrs) {
+                        specs.push(InvariantSpec {
+                            expression,
+                            location: fn_name.clone(),
+                            params: typed_params(&f.sig),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    specs
+}
+
+// ── Internal ──────────────────────────────────────────────────────────────────
+
+/// The expressions of every `#[invariant = "..."]` in `attrs`.
+pub(super) fn invariant_attrs(attrs: &[syn::Attribute]) -> Vec<String> {
+    use syn::{Expr, Lit, Meta};
+
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("invariant"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(expr_lit) => match &expr_lit.lit {
+                    Lit::Str(lit_str) => Some(lit_str.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// `(name, type)` for every named parameter, looking through references.
+pub(super) fn typed_params(sig: &syn::Signature) -> Vec<(String, String)> {
+    sig.inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pt) => match &*pt.pat {
+                syn::Pat::Ident(id) => {
+                    let mut ty = &*pt.ty;
+                    while let syn::Type::Reference(r) = ty {
+                        ty = &r.elem;
+                    }
+                    Some((id.ident.to_string(), quote::quote!(#ty).to_string()))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
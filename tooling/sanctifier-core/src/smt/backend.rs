@@ -4,14 +4,20 @@
 //! top-level dispatcher [`prove_fixed_point_mul_div_bounds_with_backend`].
 //! All Z3 API imports are confined here so the rest of the crate never touches
 //! the `z3` crate directly.
+//!
+//! The dispatcher itself does not need Z3: every backend other than an
+//! in-process Z3 runs through the SMT-LIB2 process driver in
+//! [`super::process`].
 
+#[cfg(feature = "smt")]
 use z3::ast::{Bool, Int};
+#[cfg(feature = "smt")]
 use z3::{Context, SatResult, Solver};
 
-use super::types::{
-    FixedPointCounterexample, FixedPointMulDivSpec, FixedPointProofError, FixedPointProofReport,
-    SmtBackend, SmtInvariantIssue,
-};
+use super::process::SmtLibSolver;
+#[cfg(feature = "smt")]
+use super::types::{FixedPointCounterexample, SmtInvariantIssue};
+use super::types::{FixedPointMulDivSpec, FixedPointProofError, FixedPointProofReport, SmtBackend};
 
 // ── SmtVerifier ───────────────────────────────────────────────────────────────
 
@@ -19,10 +25,12 @@ use super::types::{
 ///
 /// Bound to a single Z3 [`Context`] for its lifetime.  Create a new
 /// `SmtVerifier` per verification session.
+#[cfg(feature = "smt")]
 pub struct SmtVerifier<'ctx> {
-    ctx: &'ctx Context,
+    pub(super) ctx: &'ctx Context,
 }
 
+#[cfg(feature = "smt")]
 impl<'ctx> SmtVerifier<'ctx> {
     /// Create a verifier bound to a Z3 [`Context`].
     pub fn new(ctx: &'ctx Context) -> Self {
@@ -71,7 +79,8 @@ impl<'ctx> SmtVerifier<'ctx> {
 // ── Fixed-point proof dispatch ────────────────────────────────────────────────
 
 /// Prove that `a * b / d` cannot overflow `u128` within the provided bounds
-/// using the default backend (Z3).
+/// using the default backend (Z3, in-process with the `smt` feature and the
+/// `z3` binary without it).
 pub fn prove_fixed_point_mul_div_bounds(
     spec: &FixedPointMulDivSpec,
 ) -> Result<FixedPointProofReport, FixedPointProofError> {
@@ -80,6 +89,10 @@ pub fn prove_fixed_point_mul_div_bounds(
 
 /// Prove that `a * b / d` cannot overflow `u128` within the provided bounds
 /// using the selected SMT backend.
+///
+/// Backends other than in-process Z3 run their default binary through
+/// [`SmtLibSolver::for_backend`]; [`SmtBackend::External`] has none, so use
+/// [`SmtLibSolver::prove_fixed_point_mul_div_bounds`] for custom binaries.
 pub fn prove_fixed_point_mul_div_bounds_with_backend(
     backend: SmtBackend,
     spec: &FixedPointMulDivSpec,
@@ -87,14 +100,20 @@ pub fn prove_fixed_point_mul_div_bounds_with_backend(
     validate_fixed_point_spec(spec)?;
 
     match backend {
+        #[cfg(feature = "smt")]
         SmtBackend::Z3 => prove_fixed_point_z3(spec),
-        SmtBackend::Cvc5 => Err(FixedPointProofError::UnsupportedBackend(SmtBackend::Cvc5)),
+        other => match SmtLibSolver::for_backend(other) {
+            Some(solver) => solver.prove_fixed_point_mul_div_bounds(spec),
+            None => Err(FixedPointProofError::UnsupportedBackend(other)),
+        },
     }
 }
 
 // ── Internal: spec validation ─────────────────────────────────────────────────
 
-fn validate_fixed_point_spec(spec: &FixedPointMulDivSpec) -> Result<(), FixedPointProofError> {
+pub(super) fn validate_fixed_point_spec(
+    spec: &FixedPointMulDivSpec,
+) -> Result<(), FixedPointProofError> {
     if spec.divisor_min == 0 {
         return Err(FixedPointProofError::InvalidSpec(
             "divisor_min must be greater than zero",
@@ -108,8 +127,21 @@ fn validate_fixed_point_spec(spec: &FixedPointMulDivSpec) -> Result<(), FixedPoi
     Ok(())
 }
 
+/// The properties a fixed-point proof of `spec` establishes.
+pub(super) fn fixed_point_properties(spec: &FixedPointMulDivSpec) -> Vec<String> {
+    let mut properties = vec![match spec.product_max {
+        Some(product_max) => format!("intermediate multiplication <= {}", product_max),
+        None => "intermediate multiplication fits in u128".to_string(),
+    }];
+    if let Some(result_max) = spec.result_max {
+        properties.push(format!("final quotient <= {}", result_max));
+    }
+    properties
+}
+
 // ── Internal: Z3 proof ────────────────────────────────────────────────────────
 
+#[cfg(feature = "smt")]
 fn prove_fixed_point_z3(
     spec: &FixedPointMulDivSpec,
 ) -> Result<FixedPointProofReport, FixedPointProofError> {
@@ -124,7 +156,7 @@ fn prove_fixed_point_z3(
     let divisor = Int::new_const(&ctx, "divisor");
 
     let zero = int_from_u128(&ctx, 0);
-    let max_u128 = int_from_u128(&ctx, u128::MAX);
+    let product_max = int_from_u128(&ctx, spec.product_max.unwrap_or(u128::MAX));
     let multiplicand_max = int_from_u128(&ctx, spec.multiplicand_max);
     let multiplier_max = int_from_u128(&ctx, spec.multiplier_max);
     let divisor_min = int_from_u128(&ctx, spec.divisor_min);
@@ -139,12 +171,11 @@ fn prove_fixed_point_z3(
 
     let product = Int::mul(&ctx, &[&multiplicand, &multiplier]);
     let quotient = product.div(&divisor);
-    let product_overflow = product.gt(&max_u128);
+    let product_overflow = product.gt(&product_max);
 
-    let mut checked_properties = vec!["intermediate multiplication fits in u128".to_string()];
+    let checked_properties = fixed_point_properties(spec);
 
     let violation = if let Some(result_max) = spec.result_max {
-        checked_properties.push(format!("final quotient <= {}", result_max));
         let quotient_overflow = quotient.gt(&int_from_u128(&ctx, result_max));
         Bool::or(&ctx, &[&product_overflow, &quotient_overflow])
     } else {
@@ -218,6 +249,7 @@ fn prove_fixed_point_z3(
     }
 }
 
+#[cfg(feature = "smt")]
 fn int_from_u128<'ctx>(ctx: &'ctx Context, value: u128) -> Int<'ctx> {
     Int::from_str(ctx, &value.to_string()).expect("u128 literal should be a valid Z3 integer")
 }
@@ -228,6 +260,7 @@ fn int_from_u128<'ctx>(ctx: &'ctx Context, value: u128) -> Int<'ctx> {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "smt")]
     #[test]
     fn prove_fixed_point_reports_safe_ranges() {
         let spec = FixedPointMulDivSpec {
@@ -237,12 +270,14 @@ mod tests {
             divisor_min: 1,
             divisor_max: 10_000_000,
             result_max: Some(u128::MAX),
+            product_max: None,
         };
         let report = prove_fixed_point_mul_div_bounds(&spec).unwrap();
         assert!(report.proven_safe);
         assert!(report.counterexample.is_none());
     }
 
+    #[cfg(feature = "smt")]
     #[test]
     fn prove_fixed_point_reports_counterexample_for_unsafe_ranges() {
         let spec = FixedPointMulDivSpec {
@@ -252,6 +287,7 @@ mod tests {
             divisor_min: 1,
             divisor_max: 1,
             result_max: None,
+            product_max: None,
         };
         let report = prove_fixed_point_mul_div_bounds(&spec).unwrap();
         assert!(!report.proven_safe);
@@ -268,6 +304,7 @@ mod tests {
             divisor_min: 0,
             divisor_max: 10,
             result_max: None,
+            product_max: None,
         };
         let error = prove_fixed_point_mul_div_bounds(&spec).unwrap_err();
         assert_eq!(
@@ -285,6 +322,7 @@ mod tests {
             divisor_min: 10,
             divisor_max: 1,
             result_max: None,
+            product_max: None,
         };
         let error = prove_fixed_point_mul_div_bounds(&spec).unwrap_err();
         assert_eq!(
@@ -296,7 +334,7 @@ mod tests {
     }
 
     #[test]
-    fn prove_fixed_point_returns_unsupported_for_cvc5_backend() {
+    fn prove_fixed_point_returns_unsupported_for_external_backend() {
         let spec = FixedPointMulDivSpec {
             function_name: "mul_div_floor".to_string(),
             multiplicand_max: 10,
@@ -304,15 +342,17 @@ mod tests {
             divisor_min: 1,
             divisor_max: 10,
             result_max: None,
+            product_max: None,
         };
         let error =
-            prove_fixed_point_mul_div_bounds_with_backend(SmtBackend::Cvc5, &spec).unwrap_err();
+            prove_fixed_point_mul_div_bounds_with_backend(SmtBackend::External, &spec).unwrap_err();
         assert_eq!(
             error,
-            FixedPointProofError::UnsupportedBackend(SmtBackend::Cvc5)
+            FixedPointProofError::UnsupportedBackend(SmtBackend::External)
         );
     }
 
+    #[cfg(feature = "smt")]
     #[test]
     fn smt_verifier_detects_addition_overflow() {
         let cfg = z3::Config::new();
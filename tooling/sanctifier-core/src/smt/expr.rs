@@ -0,0 +1,736 @@
+//! The `#[invariant = "..."]` expression language.
+//!
+//! Invariants are ordinary Rust expressions, parsed with `syn` and
+//! type-checked against the annotated function's signature before they are
+//! handed to the solver:
+//!
+//! | Construct | Example |
+//! |---|---|
+//! | Function parameters | `amount`, `fee_bps` |
+//! | Storage keys (two or more path segments) | `DataKey::TotalSupply` |
+//! | Sums over a keyed storage map | `sum(DataKey::Balance)` |
+//! | Integer literals, optionally suffixed | `0`, `10_000u32`, `u128::MAX` |
+//! | Arithmetic | `+ - * / %`, unary `-` |
+//! | Comparisons | `== != < <= > >=` |
+//! | Boolean connectives | `&& \|\| !`, `true`, `false` |
+//! | Casts between integer types | `amount as u128` |
+//!
+//! Integer types follow Rust: both operands of a binary operator must have
+//! the same type, and an unsuffixed literal takes the type of the other
+//! operand.  Storage keys and sums are `i128` (the Soroban token amount type)
+//! unless cast, in which case the cast names the stored type:
+//! `DataKey::Fee as u32 <= 10_000`.  A missing storage entry reads as zero.
+//!
+//! This module only parses and type-checks; [`super::invariants`] encodes the
+//! result as Z3 bit-vector terms.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use syn::{BinOp, Expr, Lit, UnOp};
+use thiserror::Error;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// A Rust integer type, encoded as a bit-vector of the same width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntType {
+    U32,
+    I32,
+    U64,
+    I64,
+    U128,
+    I128,
+}
+
+impl IntType {
+    /// Map a Rust type name (`"u64"`) to an [`IntType`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "u32" => Some(Self::U32),
+            "i32" => Some(Self::I32),
+            "u64" => Some(Self::U64),
+            "i64" => Some(Self::I64),
+            "u128" => Some(Self::U128),
+            "i128" => Some(Self::I128),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::U32 => "u32",
+            Self::I32 => "i32",
+            Self::U64 => "u64",
+            Self::I64 => "i64",
+            Self::U128 => "u128",
+            Self::I128 => "i128",
+        }
+    }
+
+    /// Width in bits.
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::U32 | Self::I32 => 32,
+            Self::U64 | Self::I64 => 64,
+            Self::U128 | Self::I128 => 128,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, Self::I32 | Self::I64 | Self::I128)
+    }
+
+    /// `Self::MAX` as a two's-complement bit pattern.
+    pub(super) fn max_bits(self) -> u128 {
+        let mask = self.mask();
+        if self.is_signed() {
+            mask >> 1
+        } else {
+            mask
+        }
+    }
+
+    /// `Self::MIN` as a two's-complement bit pattern.
+    pub(super) fn min_bits(self) -> u128 {
+        if self.is_signed() {
+            1u128 << (self.bits() - 1)
+        } else {
+            0
+        }
+    }
+
+    pub(super) fn mask(self) -> u128 {
+        u128::MAX >> (128 - self.bits())
+    }
+}
+
+/// The type of a parameter an invariant may reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantType {
+    Bool,
+    Int(IntType),
+}
+
+impl InvariantType {
+    /// Map a Rust type name to a supported invariant type.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bool" => Some(Self::Bool),
+            other => IntType::from_name(other).map(Self::Int),
+        }
+    }
+}
+
+/// Where a variable's value comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VarSource {
+    /// A parameter of the annotated function.
+    Param,
+    /// A contract storage entry.
+    Storage,
+    /// The sum of the values stored under every key built from one enum
+    /// variant, e.g. every `DataKey::Balance(_)`.
+    StorageSum,
+}
+
+/// Comparison operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Integer arithmetic operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl ArithOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+        }
+    }
+}
+
+/// A type-checked boolean invariant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoolExpr {
+    Const(bool),
+    Var(String),
+    Not(Box<BoolExpr>),
+    And(Box<BoolExpr>, Box<BoolExpr>),
+    Or(Box<BoolExpr>, Box<BoolExpr>),
+    /// `==` / `!=` between two booleans.
+    Iff {
+        lhs: Box<BoolExpr>,
+        rhs: Box<BoolExpr>,
+        negated: bool,
+    },
+    Compare {
+        op: CmpOp,
+        lhs: IntExpr,
+        rhs: IntExpr,
+    },
+}
+
+/// A type-checked integer term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntExpr {
+    pub ty: IntType,
+    pub kind: IntExprKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntExprKind {
+    /// Two's-complement bit pattern, already truncated to the type's width.
+    Const(u128),
+    Var {
+        name: String,
+        source: VarSource,
+    },
+    Neg(Box<IntExpr>),
+    Arith {
+        op: ArithOp,
+        lhs: Box<IntExpr>,
+        rhs: Box<IntExpr>,
+    },
+    /// Conversion from another integer type with `as` semantics.
+    Cast(Box<IntExpr>),
+}
+
+impl fmt::Display for IntExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            IntExprKind::Const(bits) if self.ty.is_signed() && *bits >= self.ty.min_bits() => {
+                write!(f, "-{}", bits.wrapping_neg() & self.ty.mask())
+            }
+            IntExprKind::Const(bits) => write!(f, "{}", bits),
+            IntExprKind::Var { name, .. } => f.write_str(name),
+            IntExprKind::Neg(inner) => write!(f, "-{}", Operand(inner)),
+            IntExprKind::Arith { op, lhs, rhs } => {
+                write!(f, "{} {} {}", Operand(lhs), op.symbol(), Operand(rhs))
+            }
+            IntExprKind::Cast(inner) => write!(f, "{} as {}", Operand(inner), self.ty.name()),
+        }
+    }
+}
+
+/// Parenthesise compound sub-terms when printing.
+struct Operand<'a>(&'a IntExpr);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.kind {
+            IntExprKind::Arith { .. } | IntExprKind::Cast(_) => write!(f, "({})", self.0),
+            _ => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// An invariant that was parsed and type-checked, ready for encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedInvariant {
+    pub expr: BoolExpr,
+    /// Every parameter and storage key the invariant mentions, with its type.
+    pub variables: BTreeMap<String, (VarSource, InvariantType)>,
+}
+
+/// Why an invariant string is outside the expression language.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvariantError {
+    #[error("not a Rust expression: {0}")]
+    Syntax(String),
+    #[error(
+        "unknown identifier `{0}`; invariants may reference function parameters and storage keys"
+    )]
+    UnknownIdent(String),
+    #[error("parameter `{name}` has unsupported type `{ty}`")]
+    UnsupportedParamType { name: String, ty: String },
+    #[error("mismatched types: `{lhs}` and `{rhs}`")]
+    TypeMismatch { lhs: String, rhs: String },
+    #[error("expected a {expected} expression, found `{found}`")]
+    Expected {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("literal `{literal}` does not fit in {ty}")]
+    LiteralOutOfRange { literal: String, ty: &'static str },
+    #[error("unsupported expression `{0}`")]
+    Unsupported(String),
+}
+
+// ── Parsing ───────────────────────────────────────────────────────────────────
+
+/// Parse and type-check an invariant against the annotated function's
+/// parameters (`(name, type)` pairs as written in the signature).
+pub fn parse_invariant_expr(
+    source: &str,
+    params: &[(String, String)],
+) -> Result<ParsedInvariant, InvariantError> {
+    let expr = syn::parse_str::<Expr>(source).map_err(|e| InvariantError::Syntax(e.to_string()))?;
+    let mut checker = Checker {
+        params,
+        variables: BTreeMap::new(),
+    };
+    let expr = checker.boolean(&expr)?;
+    Ok(ParsedInvariant {
+        expr,
+        variables: checker.variables,
+    })
+}
+
+struct Checker<'a> {
+    params: &'a [(String, String)],
+    variables: BTreeMap<String, (VarSource, InvariantType)>,
+}
+
+impl Checker<'_> {
+    fn boolean(&mut self, expr: &Expr) -> Result<BoolExpr, InvariantError> {
+        match expr {
+            Expr::Paren(p) => self.boolean(&p.expr),
+            Expr::Group(g) => self.boolean(&g.expr),
+            Expr::Lit(l) => match &l.lit {
+                Lit::Bool(b) => Ok(BoolExpr::Const(b.value)),
+                _ => Err(expected("boolean", expr)),
+            },
+            Expr::Unary(u) if matches!(u.op, UnOp::Not(_)) => {
+                Ok(BoolExpr::Not(Box::new(self.boolean(&u.expr)?)))
+            }
+            Expr::Binary(b) => {
+                let op = match b.op {
+                    BinOp::And(_) => {
+                        return Ok(BoolExpr::And(
+                            Box::new(self.boolean(&b.left)?),
+                            Box::new(self.boolean(&b.right)?),
+                        ))
+                    }
+                    BinOp::Or(_) => {
+                        return Ok(BoolExpr::Or(
+                            Box::new(self.boolean(&b.left)?),
+                            Box::new(self.boolean(&b.right)?),
+                        ))
+                    }
+                    BinOp::Eq(_) => CmpOp::Eq,
+                    BinOp::Ne(_) => CmpOp::Ne,
+                    BinOp::Lt(_) => CmpOp::Lt,
+                    BinOp::Le(_) => CmpOp::Le,
+                    BinOp::Gt(_) => CmpOp::Gt,
+                    BinOp::Ge(_) => CmpOp::Ge,
+                    _ => return Err(expected("boolean", expr)),
+                };
+                if matches!(op, CmpOp::Eq | CmpOp::Ne)
+                    && (self.is_boolean(&b.left) || self.is_boolean(&b.right))
+                {
+                    return Ok(BoolExpr::Iff {
+                        lhs: Box::new(self.boolean(&b.left)?),
+                        rhs: Box::new(self.boolean(&b.right)?),
+                        negated: op == CmpOp::Ne,
+                    });
+                }
+                let ty = self
+                    .int_type_of(&b.left)?
+                    .or(self.int_type_of(&b.right)?)
+                    .unwrap_or(IntType::I32);
+                Ok(BoolExpr::Compare {
+                    op,
+                    lhs: self.integer(&b.left, ty)?,
+                    rhs: self.integer(&b.right, ty)?,
+                })
+            }
+            Expr::Path(_) => match self.variable(expr)? {
+                (name, _, InvariantType::Bool) => Ok(BoolExpr::Var(name)),
+                _ => Err(expected("boolean", expr)),
+            },
+            _ => Err(InvariantError::Unsupported(render(expr))),
+        }
+    }
+
+    /// Lower `expr` as an integer term of type `ty`.
+    fn integer(&mut self, expr: &Expr, ty: IntType) -> Result<IntExpr, InvariantError> {
+        let kind = match expr {
+            Expr::Paren(p) => return self.integer(&p.expr, ty),
+            Expr::Group(g) => return self.integer(&g.expr, ty),
+            Expr::Lit(_) => IntExprKind::Const(literal(expr, ty, false)?),
+            Expr::Unary(u) if matches!(u.op, UnOp::Neg(_)) => {
+                if !ty.is_signed() {
+                    return Err(InvariantError::Unsupported(render(expr)));
+                }
+                if matches!(&*u.expr, Expr::Lit(_)) {
+                    IntExprKind::Const(literal(&u.expr, ty, true)?)
+                } else {
+                    IntExprKind::Neg(Box::new(self.integer(&u.expr, ty)?))
+                }
+            }
+            Expr::Binary(b) => {
+                let op = match b.op {
+                    BinOp::Add(_) => ArithOp::Add,
+                    BinOp::Sub(_) => ArithOp::Sub,
+                    BinOp::Mul(_) => ArithOp::Mul,
+                    BinOp::Div(_) => ArithOp::Div,
+                    BinOp::Rem(_) => ArithOp::Rem,
+                    _ => return Err(expected("integer", expr)),
+                };
+                IntExprKind::Arith {
+                    op,
+                    lhs: Box::new(self.integer(&b.left, ty)?),
+                    rhs: Box::new(self.integer(&b.right, ty)?),
+                }
+            }
+            Expr::Cast(c) => {
+                let target = cast_target(c)?;
+                if target != ty {
+                    return Err(mismatch(ty, target));
+                }
+                if let Some((name, source)) = storage_ref(&c.expr) {
+                    self.declare(&name, source, InvariantType::Int(target))?;
+                    IntExprKind::Var { name, source }
+                } else {
+                    let inner_ty = self.int_type_of(&c.expr)?.unwrap_or(IntType::I32);
+                    IntExprKind::Cast(Box::new(self.integer(&c.expr, inner_ty)?))
+                }
+            }
+            Expr::Path(p) if type_bound(p).is_some() => {
+                let (bound_ty, bits) = type_bound(p).expect("checked by the guard");
+                if bound_ty != ty {
+                    return Err(mismatch(ty, bound_ty));
+                }
+                IntExprKind::Const(bits)
+            }
+            Expr::Path(_) | Expr::Call(_) => match self.variable(expr)? {
+                (name, source, InvariantType::Int(var_ty)) if var_ty == ty => {
+                    IntExprKind::Var { name, source }
+                }
+                (_, _, InvariantType::Int(var_ty)) => return Err(mismatch(ty, var_ty)),
+                (_, _, InvariantType::Bool) => return Err(expected("integer", expr)),
+            },
+            _ => return Err(InvariantError::Unsupported(render(expr))),
+        };
+        Ok(IntExpr { ty, kind })
+    }
+
+    /// The type `expr` has on its own, or `None` when it is made up only of
+    /// unsuffixed literals and takes its type from context.
+    fn int_type_of(&self, expr: &Expr) -> Result<Option<IntType>, InvariantError> {
+        Ok(match expr {
+            Expr::Paren(p) => self.int_type_of(&p.expr)?,
+            Expr::Group(g) => self.int_type_of(&g.expr)?,
+            Expr::Lit(l) => match &l.lit {
+                Lit::Int(i) if !i.suffix().is_empty() => Some(
+                    IntType::from_name(i.suffix())
+                        .ok_or_else(|| InvariantError::Unsupported(render(expr)))?,
+                ),
+                _ => None,
+            },
+            Expr::Unary(u) => self.int_type_of(&u.expr)?,
+            Expr::Binary(b) => match self.int_type_of(&b.left)? {
+                Some(ty) => Some(ty),
+                None => self.int_type_of(&b.right)?,
+            },
+            Expr::Cast(c) => Some(cast_target(c)?),
+            Expr::Path(p) => match type_bound(p) {
+                Some((ty, _)) => Some(ty),
+                None => match self.lookup(expr)? {
+                    (_, _, InvariantType::Int(ty)) => Some(ty),
+                    (_, _, InvariantType::Bool) => None,
+                },
+            },
+            Expr::Call(_) => match self.lookup(expr)? {
+                (_, _, InvariantType::Int(ty)) => Some(ty),
+                (_, _, InvariantType::Bool) => None,
+            },
+            _ => None,
+        })
+    }
+
+    fn is_boolean(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Paren(p) => self.is_boolean(&p.expr),
+            Expr::Lit(l) => matches!(l.lit, Lit::Bool(_)),
+            Expr::Unary(u) => matches!(u.op, UnOp::Not(_)) && self.is_boolean(&u.expr),
+            Expr::Binary(b) => matches!(
+                b.op,
+                BinOp::And(_)
+                    | BinOp::Or(_)
+                    | BinOp::Eq(_)
+                    | BinOp::Ne(_)
+                    | BinOp::Lt(_)
+                    | BinOp::Le(_)
+                    | BinOp::Gt(_)
+                    | BinOp::Ge(_)
+            ),
+            Expr::Path(_) => matches!(self.lookup(expr), Ok((_, _, InvariantType::Bool))),
+            _ => false,
+        }
+    }
+
+    /// Resolve a path to a parameter or storage key without recording it.
+    fn lookup(&self, expr: &Expr) -> Result<(String, VarSource, InvariantType), InvariantError> {
+        if let Some((name, source)) = storage_ref(expr) {
+            let ty = match self.variables.get(&name) {
+                Some((_, ty)) => *ty,
+                None => InvariantType::Int(IntType::I128),
+            };
+            return Ok((name, source, ty));
+        }
+        let name = render(expr);
+        let (_, ty) = self
+            .params
+            .iter()
+            .find(|(param, _)| *param == name)
+            .ok_or_else(|| InvariantError::UnknownIdent(name.clone()))?;
+        let resolved =
+            InvariantType::from_name(ty).ok_or_else(|| InvariantError::UnsupportedParamType {
+                name: name.clone(),
+                ty: ty.clone(),
+            })?;
+        Ok((name, VarSource::Param, resolved))
+    }
+
+    fn variable(
+        &mut self,
+        expr: &Expr,
+    ) -> Result<(String, VarSource, InvariantType), InvariantError> {
+        let (name, source, ty) = self.lookup(expr)?;
+        self.declare(&name, source, ty)?;
+        Ok((name, source, ty))
+    }
+
+    fn declare(
+        &mut self,
+        name: &str,
+        source: VarSource,
+        ty: InvariantType,
+    ) -> Result<(), InvariantError> {
+        match self.variables.get(name) {
+            Some((_, existing)) if *existing != ty => Err(InvariantError::TypeMismatch {
+                lhs: type_name(*existing).to_string(),
+                rhs: type_name(ty).to_string(),
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.variables.insert(name.to_string(), (source, ty));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `DataKey::Balance`-style paths name storage entries, and
+/// `sum(DataKey::Balance)` the sum over every key built from that variant.
+fn storage_ref(expr: &Expr) -> Option<(String, VarSource)> {
+    match expr {
+        Expr::Path(p) if p.path.segments.len() >= 2 && type_bound(p).is_none() => {
+            Some((render(expr), VarSource::Storage))
+        }
+        Expr::Call(call) if call.args.len() == 1 && render(&call.func) == "sum" => {
+            match storage_ref(&call.args[0])? {
+                (key, VarSource::Storage) => Some((format!("sum({key})"), VarSource::StorageSum)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `u64::MAX` / `i128::MIN` as `(type, bit pattern)`.
+pub(super) fn type_bound(p: &syn::ExprPath) -> Option<(IntType, u128)> {
+    let segments: Vec<String> = p
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    let [ty, bound] = segments.as_slice() else {
+        return None;
+    };
+    let ty = IntType::from_name(ty)?;
+    match bound.as_str() {
+        "MAX" => Some((ty, ty.max_bits())),
+        "MIN" => Some((ty, ty.min_bits())),
+        _ => None,
+    }
+}
+
+fn cast_target(cast: &syn::ExprCast) -> Result<IntType, InvariantError> {
+    match &*cast.ty {
+        syn::Type::Path(tp) => tp
+            .path
+            .get_ident()
+            .and_then(|ident| IntType::from_name(&ident.to_string())),
+        _ => None,
+    }
+    .ok_or_else(|| InvariantError::Unsupported(render(&Expr::Cast(cast.clone()))))
+}
+
+/// Parse an integer literal as a bit pattern of `ty`, negated when the
+/// literal sits under a unary minus.
+fn literal(expr: &Expr, ty: IntType, negative: bool) -> Result<u128, InvariantError> {
+    let Expr::Lit(syn::ExprLit {
+        lit: Lit::Int(int), ..
+    }) = expr
+    else {
+        return Err(expected("integer", expr));
+    };
+    if !int.suffix().is_empty() && IntType::from_name(int.suffix()) != Some(ty) {
+        return Err(InvariantError::TypeMismatch {
+            lhs: ty.name().to_string(),
+            rhs: int.suffix().to_string(),
+        });
+    }
+    let out_of_range = || InvariantError::LiteralOutOfRange {
+        literal: format!("{}{}", if negative { "-" } else { "" }, int.base10_digits()),
+        ty: ty.name(),
+    };
+    let magnitude: u128 = int.base10_parse().map_err(|_| out_of_range())?;
+    // A signed type reaches one further below zero than above it.
+    let limit = if negative {
+        ty.min_bits()
+    } else {
+        ty.max_bits()
+    };
+    if magnitude > limit {
+        return Err(out_of_range());
+    }
+    Ok(if negative {
+        magnitude.wrapping_neg() & ty.mask()
+    } else {
+        magnitude
+    })
+}
+
+fn type_name(ty: InvariantType) -> &'static str {
+    match ty {
+        InvariantType::Bool => "bool",
+        InvariantType::Int(int) => int.name(),
+    }
+}
+
+fn mismatch(lhs: IntType, rhs: IntType) -> InvariantError {
+    InvariantError::TypeMismatch {
+        lhs: lhs.name().to_string(),
+        rhs: rhs.name().to_string(),
+    }
+}
+
+fn expected(expected: &'static str, expr: &Expr) -> InvariantError {
+    InvariantError::Expected {
+        expected,
+        found: render(expr),
+    }
+}
+
+pub(super) fn render(expr: &Expr) -> String {
+    quote::quote!(#expr).to_string().replace(" :: ", "::")
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(n, t)| (n.to_string(), t.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn literals_take_the_type_of_the_other_operand() {
+        let parsed = parse_invariant_expr("amount + 1 > 0", &params(&[("amount", "u32")])).unwrap();
+        let BoolExpr::Compare { lhs, rhs, .. } = parsed.expr else {
+            panic!("expected a comparison");
+        };
+        assert_eq!(lhs.ty, IntType::U32);
+        assert_eq!(rhs.ty, IntType::U32);
+        assert_eq!(
+            parsed.variables["amount"],
+            (VarSource::Param, InvariantType::Int(IntType::U32))
+        );
+    }
+
+    #[test]
+    fn storage_keys_default_to_i128_and_take_cast_types() {
+        let parsed =
+            parse_invariant_expr("DataKey::Supply >= 0 && DataKey::Fee as u32 <= 10_000", &[])
+                .unwrap();
+        assert_eq!(
+            parsed.variables["DataKey::Supply"],
+            (VarSource::Storage, InvariantType::Int(IntType::I128))
+        );
+        assert_eq!(
+            parsed.variables["DataKey::Fee"],
+            (VarSource::Storage, InvariantType::Int(IntType::U32))
+        );
+
+        let parsed =
+            parse_invariant_expr("DataKey::TotalSupply == sum(DataKey::Balance)", &[]).unwrap();
+        assert_eq!(
+            parsed.variables["sum(DataKey::Balance)"],
+            (VarSource::StorageSum, InvariantType::Int(IntType::I128))
+        );
+    }
+
+    #[test]
+    fn type_bounds_and_negative_literals_are_encoded_as_bit_patterns() {
+        let parsed =
+            parse_invariant_expr("x >= -1 && x <= i32::MAX", &params(&[("x", "i32")])).unwrap();
+        let BoolExpr::And(lower, upper) = parsed.expr else {
+            panic!("expected a conjunction");
+        };
+        let BoolExpr::Compare { rhs, .. } = *lower else {
+            panic!("expected a comparison");
+        };
+        assert_eq!(rhs.kind, IntExprKind::Const(u32::MAX as u128));
+        let BoolExpr::Compare { rhs, .. } = *upper else {
+            panic!("expected a comparison");
+        };
+        assert_eq!(rhs.kind, IntExprKind::Const(i32::MAX as u128));
+        assert!(parse_invariant_expr("x >= -128", &params(&[("x", "i128")])).is_ok());
+        assert!(parse_invariant_expr(
+            "x >= -170141183460469231731687303715884105728",
+            &params(&[("x", "i128")])
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_ill_typed_or_unknown_invariants() {
+        let p = params(&[("a", "u64"), ("b", "u32"), ("who", "Address")]);
+        assert!(matches!(
+            parse_invariant_expr("a + b > 0", &p),
+            Err(InvariantError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            parse_invariant_expr("only_admin", &p),
+            Err(InvariantError::UnknownIdent(_))
+        ));
+        assert!(matches!(
+            parse_invariant_expr("who == who", &p),
+            Err(InvariantError::UnsupportedParamType { .. })
+        ));
+        assert!(matches!(
+            parse_invariant_expr("a + b is safe", &p),
+            Err(InvariantError::Syntax(_))
+        ));
+        assert!(matches!(
+            parse_invariant_expr("b <= 5_000_000_000", &p),
+            Err(InvariantError::LiteralOutOfRange { .. })
+        ));
+    }
+}
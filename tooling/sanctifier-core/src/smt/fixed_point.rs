@@ -0,0 +1,944 @@
+//! Automatic discovery and proof of fixed-point `a * b / d` calculations.
+//!
+//! [`discover_fixed_point_mul_divs`] finds multiply-then-divide calculations
+//! in every function of a source file:
+//!
+//! | Form | Example |
+//! |---|---|
+//! | Operators | `a * b / d` |
+//! | Checked chains | `a.checked_mul(b)?.checked_div(d)`, `a.checked_mul(b).and_then(\|p\| p.checked_div(d))` |
+//! | Through a local | `let n = a.checked_mul(b)?; n.checked_div(d)` |
+//! | Helpers | `mul_div(a, b, d)`, `a.fixed_mul_floor(b, d)`, `a.fixed_div_floor(b, d)` |
+//!
+//! Each operand's range comes from its type, `const` values (folded with
+//! [`crate::constant_folding::fold_to_i128`]), and `assert!` or early-exit
+//! `if` guards such as `if d == 0 { return None; }`, so the inferred
+//! [`FixedPointMulDivSpec`] is only as wide as the code allows.  Signed
+//! operands are modelled by their magnitude, and a divisor that may be zero
+//! is assumed to be at least one: division by zero is a separate panic.
+//!
+//! [`verify_fixed_point_mul_divs`] proves every discovered spec and reports
+//! the calculations that can overflow as S011 findings.
+
+use std::collections::{BTreeMap, HashMap};
+
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr, ImplItem, Item, Pat, Stmt, UnOp};
+
+use super::backend::prove_fixed_point_mul_div_bounds_with_backend;
+use super::expr::{render, IntType};
+use super::types::{
+    FixedPointMulDivSpec, FixedPointProofError, SmtBackend, SmtFinding, SmtFindingKind, SmtLibError,
+};
+use crate::constant_folding::fold_to_i128;
+
+// ── Public API ────────────────────────────────────────────────────────────────
+
+/// How a discovered calculation multiplies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MulDivForm {
+    /// `a * b`: an overflow panics.
+    Unchecked,
+    /// `checked_mul` or an `Option`-returning helper: an overflow makes the
+    /// calculation return `None`.
+    Checked,
+    /// A `mul_div`-style helper whose overflow behaviour is its own.
+    Helper,
+}
+
+/// A multiply-then-divide calculation found in the source, with the bounds
+/// inferred for it.
+#[derive(Debug, Clone)]
+pub struct FixedPointCandidate {
+    /// The calculation as written.
+    pub expression: String,
+    /// The multiplicand, multiplier and divisor as written.
+    pub operands: [String; 3],
+    /// The integer type the multiplication is performed in.
+    pub ty: &'static str,
+    pub form: MulDivForm,
+    /// The inferred proof obligation; `function_name` is the enclosing
+    /// function.
+    pub spec: FixedPointMulDivSpec,
+}
+
+/// Find every fixed-point `a * b / d` calculation in `source` and infer its
+/// operand bounds.  Calculations whose integer type cannot be determined
+/// are skipped.  Returns an empty vec on parse errors.
+pub fn discover_fixed_point_mul_divs(source: &str) -> Vec<FixedPointCandidate> {
+    let Ok(file) = syn::parse_file(source) else {
+        return vec![];
+    };
+
+    let mut consts = HashMap::new();
+    for item in &file.items {
+        match item {
+            Item::Const(c) => record_const(&mut consts, &c.ident, &c.ty, &c.expr),
+            Item::Impl(imp) => {
+                for item in &imp.items {
+                    if let ImplItem::Const(c) = item {
+                        record_const(&mut consts, &c.ident, &c.ty, &c.expr);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut candidates = Vec::new();
+    let mut scan = |sig: &syn::Signature, block: &syn::Block| {
+        let mut finder = Finder::new(&consts, sig);
+        finder.visit_block(block);
+        candidates.extend(finder.found);
+    };
+    for item in &file.items {
+        match item {
+            Item::Fn(f) => scan(&f.sig, &f.block),
+            Item::Impl(imp) => {
+                for item in &imp.items {
+                    if let ImplItem::Fn(f) = item {
+                        scan(&f.sig, &f.block);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    candidates
+}
+
+/// Prove every calculation [`discover_fixed_point_mul_divs`] finds with
+/// `backend` and return one [`SmtFindingKind::FixedPointOverflow`] finding
+/// per calculation that can overflow, with the overflowing operands as the
+/// witness.  A solver timeout becomes an [`SmtFinding::is_timeout`]
+/// finding; any other solver failure aborts the run.  Use
+/// [`FixedPointCandidate::verify`] to carry on past failures.
+pub fn verify_fixed_point_mul_divs(
+    source: &str,
+    backend: SmtBackend,
+) -> Result<Vec<SmtFinding>, FixedPointProofError> {
+    let mut findings = Vec::new();
+    for candidate in discover_fixed_point_mul_divs(source) {
+        findings.extend(candidate.verify(backend)?);
+    }
+    Ok(findings)
+}
+
+impl FixedPointCandidate {
+    /// Prove this calculation with `backend`: `None` when it cannot
+    /// overflow, otherwise a [`SmtFindingKind::FixedPointOverflow`] finding
+    /// with the overflowing operands as the witness, or an
+    /// [`SmtFinding::is_timeout`] one when the solver timed out.
+    pub fn verify(&self, backend: SmtBackend) -> Result<Option<SmtFinding>, FixedPointProofError> {
+        let mut finding = SmtFinding {
+            kind: SmtFindingKind::FixedPointOverflow,
+            invariant_name: self.expression.clone(),
+            location: self.spec.function_name.clone(),
+            counterexample: None,
+            witness: BTreeMap::new(),
+            path: Vec::new(),
+            is_timeout: false,
+        };
+        let report = match prove_fixed_point_mul_div_bounds_with_backend(backend, &self.spec) {
+            Ok(report) => report,
+            Err(FixedPointProofError::SmtLib(SmtLibError::Timeout { .. })) => {
+                finding.is_timeout = true;
+                return Ok(Some(finding));
+            }
+            Err(error) => return Err(error),
+        };
+        let Some(witness) = report.counterexample.filter(|_| !report.proven_safe) else {
+            return Ok(None);
+        };
+
+        let [a, b, d] = &self.operands;
+        let consequence = match self.form {
+            MulDivForm::Unchecked => "and panics",
+            MulDivForm::Checked => "so the calculation returns None",
+            MulDivForm::Helper => "inside the helper",
+        };
+        finding.counterexample = Some(format!(
+            "{a} = {}, {b} = {}, {d} = {} — `{a} * {b}` = {} overflows {} {consequence}",
+            witness.multiplicand,
+            witness.multiplier,
+            witness.divisor,
+            witness.intermediate_product,
+            self.ty,
+        ));
+        finding.witness = BTreeMap::from([
+            (a.clone(), witness.multiplicand),
+            (b.clone(), witness.multiplier),
+            (d.clone(), witness.divisor),
+        ]);
+        Ok(Some(finding))
+    }
+}
+
+// ── Internal: ranges ──────────────────────────────────────────────────────────
+
+/// An inclusive range of magnitudes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Range {
+    min: u128,
+    max: u128,
+}
+
+impl Range {
+    fn exactly(value: u128) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    fn of(ty: IntType) -> Self {
+        Self {
+            min: 0,
+            max: ty.max_bits(),
+        }
+    }
+
+    /// The tighter of the two ranges; `self` when they contradict.
+    fn intersect(self, other: Range) -> Range {
+        let narrowed = Range {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        };
+        if narrowed.min > narrowed.max {
+            self
+        } else {
+            narrowed
+        }
+    }
+}
+
+fn record_const(
+    consts: &mut HashMap<String, (Option<IntType>, u128)>,
+    ident: &syn::Ident,
+    ty: &syn::Type,
+    expr: &Expr,
+) {
+    if let Some(value) = fold_to_i128(expr).and_then(|v| u128::try_from(v).ok()) {
+        consts.insert(ident.to_string(), (int_type_name(ty), value));
+    }
+}
+
+fn int_type_name(ty: &syn::Type) -> Option<IntType> {
+    match ty {
+        syn::Type::Path(tp) => IntType::from_name(&tp.path.get_ident()?.to_string()),
+        syn::Type::Reference(r) => int_type_name(&r.elem),
+        _ => None,
+    }
+}
+
+// ── Internal: the per-function scan ───────────────────────────────────────────
+
+/// `a * b`, found directly or through the local it was bound to.
+#[derive(Clone)]
+struct Product {
+    lhs: Expr,
+    rhs: Expr,
+    form: MulDivForm,
+}
+
+struct Finder<'a> {
+    consts: &'a HashMap<String, (Option<IntType>, u128)>,
+    function_name: String,
+    /// Integer types of parameters and locals.
+    types: HashMap<String, IntType>,
+    /// Ranges of locals computed from their initializers.
+    locals: HashMap<String, Range>,
+    /// Locals bound to a product, e.g. `let n = a.checked_mul(b)?`.
+    products: HashMap<String, Product>,
+    /// Ranges established by guards, innermost scope last.
+    facts: Vec<HashMap<String, Range>>,
+    found: Vec<FixedPointCandidate>,
+}
+
+impl<'a> Finder<'a> {
+    fn new(consts: &'a HashMap<String, (Option<IntType>, u128)>, sig: &syn::Signature) -> Self {
+        let mut types = HashMap::new();
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(pt) = input {
+                if let (Pat::Ident(id), Some(ty)) = (&*pt.pat, int_type_name(&pt.ty)) {
+                    types.insert(id.ident.to_string(), ty);
+                }
+            }
+        }
+        Self {
+            consts,
+            function_name: sig.ident.to_string(),
+            types,
+            locals: HashMap::new(),
+            products: HashMap::new(),
+            facts: vec![HashMap::new()],
+            found: Vec::new(),
+        }
+    }
+
+    /// Record the calculation if `expr` divides a product.
+    fn check(&mut self, expr: &Expr) {
+        let Some((product, divisor)) = self.mul_div(expr) else {
+            return;
+        };
+        let Some(ty) = self
+            .int_type(&product.lhs)
+            .or_else(|| self.int_type(&product.rhs))
+            .or_else(|| self.int_type(&divisor))
+        else {
+            return;
+        };
+        let natural = Range::of(ty);
+        let range = |finder: &Self, e: &Expr| finder.range(e).unwrap_or(natural);
+        let (a, b, d) = (
+            range(self, &product.lhs),
+            range(self, &product.rhs),
+            range(self, &divisor),
+        );
+        let divisor_min = d.min.max(1);
+        self.found.push(FixedPointCandidate {
+            expression: source_text(expr),
+            operands: [
+                source_text(&product.lhs),
+                source_text(&product.rhs),
+                source_text(&divisor),
+            ],
+            ty: ty.name(),
+            form: product.form,
+            spec: FixedPointMulDivSpec {
+                function_name: self.function_name.clone(),
+                multiplicand_max: a.max,
+                multiplier_max: b.max,
+                divisor_min,
+                divisor_max: d.max.max(divisor_min),
+                result_max: None,
+                product_max: (ty != IntType::U128).then(|| ty.max_bits()),
+            },
+        });
+    }
+
+    /// The product and divisor when `expr` is a multiply-then-divide.
+    fn mul_div(&self, expr: &Expr) -> Option<(Product, Expr)> {
+        match expr {
+            Expr::Binary(b) if matches!(b.op, BinOp::Div(_)) => {
+                Some((self.product(&b.left)?, (*b.right).clone()))
+            }
+            Expr::MethodCall(m) => {
+                let method = m.method.to_string();
+                let args: Vec<&Expr> = m.args.iter().collect();
+                match (method.as_str(), args.as_slice()) {
+                    ("checked_div", [d]) => Some((self.product(&m.receiver)?, (*d).clone())),
+                    // a.checked_mul(b).and_then(|p| p.checked_div(d))
+                    ("and_then", [Expr::Closure(c)]) => {
+                        let product = self.product(&m.receiver)?;
+                        let [Pat::Ident(param)] = c.inputs.iter().collect::<Vec<_>>()[..] else {
+                            return None;
+                        };
+                        let Expr::MethodCall(div) = strip(&c.body) else {
+                            return None;
+                        };
+                        let [d] = div.args.iter().collect::<Vec<_>>()[..] else {
+                            return None;
+                        };
+                        (div.method == "checked_div" && is_ident(&div.receiver, &param.ident))
+                            .then(|| (product, d.clone()))
+                    }
+                    // soroban-fixed-point-math: `Option`-returning without an
+                    // env, panicking with one.
+                    (name, [.., y, d]) if name.starts_with("fixed_mul_") && args.len() <= 3 => {
+                        let product = Product {
+                            lhs: (*m.receiver).clone(),
+                            rhs: strip_ref(y).clone(),
+                            form: fixed_point_form(args.len()),
+                        };
+                        Some((product, strip_ref(d).clone()))
+                    }
+                    (name, [.., y, d]) if name.starts_with("fixed_div_") && args.len() <= 3 => {
+                        let product = Product {
+                            lhs: (*m.receiver).clone(),
+                            rhs: strip_ref(d).clone(),
+                            form: fixed_point_form(args.len()),
+                        };
+                        Some((product, strip_ref(y).clone()))
+                    }
+                    (name, [b, d]) if is_mul_div_helper(name) => Some((
+                        Product {
+                            lhs: (*m.receiver).clone(),
+                            rhs: strip_ref(b).clone(),
+                            form: MulDivForm::Helper,
+                        },
+                        strip_ref(d).clone(),
+                    )),
+                    _ => None,
+                }
+            }
+            Expr::Call(call) => {
+                let Expr::Path(p) = &*call.func else {
+                    return None;
+                };
+                let name = p.path.segments.last()?.ident.to_string();
+                let [a, b, d] = call.args.iter().collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+                is_mul_div_helper(&name).then(|| {
+                    let product = Product {
+                        lhs: strip_ref(a).clone(),
+                        rhs: strip_ref(b).clone(),
+                        form: MulDivForm::Helper,
+                    };
+                    (product, strip_ref(d).clone())
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// `expr` as a product, looking through `?`, `.unwrap()` and locals.
+    fn product(&self, expr: &Expr) -> Option<Product> {
+        match strip(expr) {
+            Expr::Binary(b) if matches!(b.op, BinOp::Mul(_)) => Some(Product {
+                lhs: (*b.left).clone(),
+                rhs: (*b.right).clone(),
+                form: MulDivForm::Unchecked,
+            }),
+            Expr::MethodCall(m) if m.method == "checked_mul" && m.args.len() == 1 => {
+                Some(Product {
+                    lhs: (*m.receiver).clone(),
+                    rhs: m.args[0].clone(),
+                    form: MulDivForm::Checked,
+                })
+            }
+            Expr::Path(p) => self.products.get(&p.path.get_ident()?.to_string()).cloned(),
+            _ => None,
+        }
+    }
+
+    /// The magnitude range of `expr`, when anything narrower than "unknown"
+    /// is known.
+    fn range(&self, expr: &Expr) -> Option<Range> {
+        let expr = strip(expr);
+        if let Some(value) = self.constant(expr) {
+            return Some(Range::exactly(value));
+        }
+        let ty = self.int_type(expr);
+        let natural = ty.map(Range::of);
+        let computed = match expr {
+            Expr::Path(p) => {
+                let name = p.path.get_ident()?.to_string();
+                let fact = self.facts.iter().rev().find_map(|scope| scope.get(&name));
+                match (fact, self.locals.get(&name)) {
+                    (Some(fact), Some(local)) => Some(local.intersect(*fact)),
+                    (fact, local) => fact.or(local).copied(),
+                }
+            }
+            Expr::Cast(c) => self.range(&c.expr),
+            // Magnitudes of signed operands do not follow interval arithmetic.
+            _ if ty.is_some_and(IntType::is_signed) => None,
+            _ => self.arith_range(expr),
+        };
+        match (computed, natural) {
+            (Some(computed), Some(natural)) => Some(computed.intersect(natural)),
+            (computed, natural) => computed.or(natural),
+        }
+    }
+
+    /// The range of unsigned arithmetic, when both operands' ranges are known.
+    fn arith_range(&self, expr: &Expr) -> Option<Range> {
+        match expr {
+            Expr::Binary(b) => arith(&b.op, self.range(&b.left)?, self.range(&b.right)?),
+            Expr::MethodCall(m) if m.args.len() == 1 => {
+                let op: BinOp = match m.method.to_string().as_str() {
+                    "checked_add" | "saturating_add" => syn::parse_quote!(+),
+                    "checked_sub" | "saturating_sub" => syn::parse_quote!(-),
+                    "checked_mul" | "saturating_mul" => syn::parse_quote!(*),
+                    "checked_div" => syn::parse_quote!(/),
+                    _ => return None,
+                };
+                arith(&op, self.range(&m.receiver)?, self.range(&m.args[0])?)
+            }
+            _ => None,
+        }
+    }
+
+    /// The value of a literal, a constant expression or a `const`.
+    fn constant(&self, expr: &Expr) -> Option<u128> {
+        if let Some(value) = fold_to_i128(expr) {
+            return u128::try_from(value).ok();
+        }
+        let Expr::Path(p) = strip(expr) else {
+            return None;
+        };
+        let name = p.path.segments.last()?.ident.to_string();
+        self.consts.get(&name).map(|(_, value)| *value)
+    }
+
+    /// The integer type `expr` evaluates to, when it can be told locally.
+    fn int_type(&self, expr: &Expr) -> Option<IntType> {
+        match strip(expr) {
+            Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(int),
+                ..
+            }) => IntType::from_name(int.suffix()),
+            Expr::Path(p) => {
+                let name = p.path.segments.last()?.ident.to_string();
+                p.path
+                    .get_ident()
+                    .and_then(|ident| self.types.get(&ident.to_string()).copied())
+                    .or_else(|| self.consts.get(&name).and_then(|(ty, _)| *ty))
+            }
+            Expr::Cast(c) => int_type_name(&c.ty),
+            Expr::Binary(b) if is_arith(&b.op) => {
+                self.int_type(&b.left).or_else(|| self.int_type(&b.right))
+            }
+            Expr::Unary(u) if matches!(u.op, UnOp::Neg(_)) => self.int_type(&u.expr),
+            Expr::MethodCall(m)
+                if ["checked_", "saturating_", "wrapping_"]
+                    .iter()
+                    .any(|prefix| m.method.to_string().starts_with(prefix))
+                    || ["min", "max", "pow", "abs"].contains(&m.method.to_string().as_str()) =>
+            {
+                self.int_type(&m.receiver)
+                    .or_else(|| m.args.first().and_then(|a| self.int_type(a)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Record what `cond` (negated when `negated`) says about variables.
+    fn assume(&mut self, cond: &Expr, negated: bool) {
+        for (name, range) in self.facts_of(cond, negated) {
+            let scope = self
+                .facts
+                .last_mut()
+                .expect("the function scope is never popped");
+            let range = match scope.get(&name) {
+                Some(existing) => existing.intersect(range),
+                None => range,
+            };
+            scope.insert(name, range);
+        }
+    }
+
+    fn facts_of(&self, cond: &Expr, negated: bool) -> Vec<(String, Range)> {
+        match cond {
+            Expr::Paren(p) => self.facts_of(&p.expr, negated),
+            Expr::Unary(u) if matches!(u.op, UnOp::Not(_)) => self.facts_of(&u.expr, !negated),
+            // `a && b` holds, or `a || b` fails: both halves are known.
+            Expr::Binary(b)
+                if (matches!(b.op, BinOp::And(_)) && !negated)
+                    || (matches!(b.op, BinOp::Or(_)) && negated) =>
+            {
+                let mut facts = self.facts_of(&b.left, negated);
+                facts.extend(self.facts_of(&b.right, negated));
+                facts
+            }
+            Expr::Binary(b) => {
+                let (name, op, value) = match (self.constant(&b.left), self.constant(&b.right)) {
+                    (None, Some(value)) => (variable(&b.left), b.op, value),
+                    (Some(value), None) => (variable(&b.right), flip(b.op), value),
+                    _ => return vec![],
+                };
+                let Some(name) = name else {
+                    return vec![];
+                };
+                let op = if negated { negate(op) } else { Some(op) };
+                let range = match op {
+                    Some(BinOp::Lt(_)) if value > 0 => Range {
+                        min: 0,
+                        max: value - 1,
+                    },
+                    Some(BinOp::Le(_)) => Range { min: 0, max: value },
+                    Some(BinOp::Gt(_)) if value < u128::MAX => Range {
+                        min: value + 1,
+                        max: u128::MAX,
+                    },
+                    Some(BinOp::Ge(_)) => Range {
+                        min: value,
+                        max: u128::MAX,
+                    },
+                    Some(BinOp::Eq(_)) => Range::exactly(value),
+                    Some(BinOp::Ne(_)) if value == 0 => Range {
+                        min: 1,
+                        max: u128::MAX,
+                    },
+                    _ => return vec![],
+                };
+                vec![(name, range)]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Finder<'_> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.check(expr);
+        visit::visit_expr(self, expr);
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.facts.push(HashMap::new());
+        for stmt in &block.stmts {
+            self.visit_stmt(stmt);
+            match stmt {
+                // `if x == 0 { return ...; }` — the rest of the block runs
+                // only when the condition is false.
+                Stmt::Expr(Expr::If(i), _)
+                    if i.else_branch.is_none() && diverges(&i.then_branch) =>
+                {
+                    self.assume(&i.cond, true);
+                }
+                Stmt::Macro(m) if m.mac.path.is_ident("assert") => {
+                    if let Ok(cond) = m.mac.parse_body_with(first_macro_arg) {
+                        self.assume(&cond, false);
+                    }
+                }
+                Stmt::Expr(Expr::Macro(m), _) if m.mac.path.is_ident("assert") => {
+                    if let Ok(cond) = m.mac.parse_body_with(first_macro_arg) {
+                        self.assume(&cond, false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.facts.pop();
+    }
+
+    fn visit_expr_if(&mut self, i: &'ast syn::ExprIf) {
+        self.visit_expr(&i.cond);
+        self.facts.push(HashMap::new());
+        self.assume(&i.cond, false);
+        self.visit_block(&i.then_branch);
+        self.facts.pop();
+        if let Some((_, else_branch)) = &i.else_branch {
+            self.facts.push(HashMap::new());
+            self.assume(&i.cond, true);
+            self.visit_expr(else_branch);
+            self.facts.pop();
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        visit::visit_local(self, local);
+        let (name, declared) = match &local.pat {
+            Pat::Ident(id) => (id.ident.to_string(), None),
+            Pat::Type(pt) => match &*pt.pat {
+                Pat::Ident(id) => (id.ident.to_string(), int_type_name(&pt.ty)),
+                _ => return,
+            },
+            _ => return,
+        };
+        let init = local.init.as_ref().map(|init| &*init.expr);
+        let ty = declared.or_else(|| init.and_then(|e| self.int_type(e)));
+        let product = init.and_then(|e| self.product(e));
+        let range = init.and_then(|e| self.range(e));
+
+        // A shadowing `let` replaces everything known about the old binding.
+        for scope in &mut self.facts {
+            scope.remove(&name);
+        }
+        match ty {
+            Some(ty) => self.types.insert(name.clone(), ty),
+            None => self.types.remove(&name),
+        };
+        match product {
+            Some(product) => self.products.insert(name.clone(), product),
+            None => self.products.remove(&name),
+        };
+        match range {
+            Some(range) => self.locals.insert(name, range),
+            None => self.locals.remove(&name),
+        };
+    }
+
+    // Nested items are not part of the enclosing function.
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
+
+// ── Internal: syntax helpers ──────────────────────────────────────────────────
+
+/// `expr` without parentheses, `?`, `.unwrap()` or `.expect(..)`.
+fn strip(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(p) => strip(&p.expr),
+        Expr::Group(g) => strip(&g.expr),
+        Expr::Try(t) => strip(&t.expr),
+        Expr::MethodCall(m) if m.method == "unwrap" || m.method == "expect" => strip(&m.receiver),
+        _ => expr,
+    }
+}
+
+/// `expr` without a leading `&`.
+fn strip_ref(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Reference(r) => strip_ref(&r.expr),
+        _ => strip(expr),
+    }
+}
+
+fn is_ident(expr: &Expr, ident: &syn::Ident) -> bool {
+    matches!(strip(expr), Expr::Path(p) if p.path.is_ident(ident))
+}
+
+/// The variable `expr` names, looking through casts.
+fn variable(expr: &Expr) -> Option<String> {
+    match strip(expr) {
+        Expr::Path(p) => Some(p.path.get_ident()?.to_string()),
+        Expr::Cast(c) => variable(&c.expr),
+        _ => None,
+    }
+}
+
+fn is_mul_div_helper(name: &str) -> bool {
+    matches!(
+        name,
+        "mul_div" | "mul_div_floor" | "mul_div_ceil" | "muldiv"
+    )
+}
+
+/// `fixed_mul_floor(y, d)` returns an `Option`; the `Env`-taking variant
+/// `fixed_mul_floor(&env, &y, &d)` panics.
+fn fixed_point_form(args: usize) -> MulDivForm {
+    if args == 2 {
+        MulDivForm::Checked
+    } else {
+        MulDivForm::Unchecked
+    }
+}
+
+fn is_arith(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_)
+    )
+}
+
+/// Interval arithmetic on magnitudes, saturating at `u128::MAX`.
+fn arith(op: &BinOp, l: Range, r: Range) -> Option<Range> {
+    Some(match op {
+        BinOp::Add(_) => Range {
+            min: l.min.saturating_add(r.min),
+            max: l.max.saturating_add(r.max),
+        },
+        BinOp::Sub(_) => Range {
+            min: l.min.saturating_sub(r.max),
+            max: l.max.saturating_sub(r.min),
+        },
+        BinOp::Mul(_) => Range {
+            min: l.min.saturating_mul(r.min),
+            max: l.max.saturating_mul(r.max),
+        },
+        BinOp::Div(_) => Range {
+            min: l.min / r.max.max(1),
+            max: l.max / r.min.max(1),
+        },
+        _ => return None,
+    })
+}
+
+/// `a op b` rewritten as `b op' a`.
+fn flip(op: BinOp) -> BinOp {
+    match op {
+        BinOp::Lt(t) => BinOp::Gt(syn::Token![>](t.span)),
+        BinOp::Le(t) => BinOp::Ge(syn::Token![>=](t.spans)),
+        BinOp::Gt(t) => BinOp::Lt(syn::Token![<](t.span)),
+        BinOp::Ge(t) => BinOp::Le(syn::Token![<=](t.spans)),
+        other => other,
+    }
+}
+
+/// The comparison that holds exactly when `op` does not.
+fn negate(op: BinOp) -> Option<BinOp> {
+    Some(match op {
+        BinOp::Lt(t) => BinOp::Ge(syn::Token![>=](t.span)),
+        BinOp::Le(t) => BinOp::Gt(syn::Token![>](t.spans[0])),
+        BinOp::Gt(t) => BinOp::Le(syn::Token![<=](t.span)),
+        BinOp::Ge(t) => BinOp::Lt(syn::Token![<](t.spans[0])),
+        BinOp::Eq(t) => BinOp::Ne(syn::Token![!=](t.spans)),
+        BinOp::Ne(t) => BinOp::Eq(syn::Token![==](t.spans)),
+        _ => return None,
+    })
+}
+
+/// Whether `block` never falls through: it returns, panics or jumps.
+fn diverges(block: &syn::Block) -> bool {
+    block.stmts.iter().any(|stmt| {
+        let expr = match stmt {
+            Stmt::Expr(expr, _) => expr,
+            Stmt::Macro(m) => {
+                return is_panic_macro(&m.mac);
+            }
+            _ => return false,
+        };
+        match expr {
+            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) => true,
+            Expr::Macro(m) => is_panic_macro(&m.mac),
+            Expr::MethodCall(m) => m.method == "panic_with_error",
+            _ => false,
+        }
+    })
+}
+
+fn is_panic_macro(mac: &syn::Macro) -> bool {
+    ["panic", "unreachable", "unimplemented", "todo"]
+        .iter()
+        .any(|name| mac.path.is_ident(name))
+}
+
+/// The condition of `assert!(cond, "message", ...)`.
+fn first_macro_arg(input: syn::parse::ParseStream) -> syn::Result<Expr> {
+    let cond: Expr = input.parse()?;
+    let _: proc_macro2::TokenStream = input.parse()?;
+    Ok(cond)
+}
+
+/// `expr` as it appears in the source, falling back to the token rendering.
+fn source_text(expr: &Expr) -> String {
+    expr.span().source_text().unwrap_or_else(|| render(expr))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn discover(source: &str) -> Vec<FixedPointCandidate> {
+        discover_fixed_point_mul_divs(source)
+    }
+
+    #[test]
+    fn finds_operator_checked_and_helper_forms() {
+        let source = r#"
+            fn ops(a: u64, b: u64, d: u64) -> u64 { a * b / d }
+            fn chain(a: u128, b: u128, d: u128) -> Option<u128> { a.checked_mul(b)?.checked_div(d) }
+            fn and_then(a: u128, b: u128, d: u128) -> Option<u128> {
+                a.checked_mul(b).and_then(|p| p.checked_div(d))
+            }
+            fn local(a: u128, b: u128, d: u128) -> Option<u128> {
+                let n = a.checked_mul(b)?;
+                n.checked_div(d)
+            }
+            fn helper(a: i128, b: i128, d: i128) -> i128 { mul_div(a, b, d) }
+            fn fixed(a: i128, b: i128, d: i128) -> Option<i128> { a.fixed_div_floor(b, d) }
+        "#;
+        let found = discover(source);
+        let summary: Vec<(&str, [&str; 3], MulDivForm)> = found
+            .iter()
+            .map(|c| {
+                let [a, b, d] = &c.operands;
+                (
+                    c.spec.function_name.as_str(),
+                    [a.as_str(), b.as_str(), d.as_str()],
+                    c.form,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("ops", ["a", "b", "d"], MulDivForm::Unchecked),
+                ("chain", ["a", "b", "d"], MulDivForm::Checked),
+                ("and_then", ["a", "b", "d"], MulDivForm::Checked),
+                ("local", ["a", "b", "d"], MulDivForm::Checked),
+                ("helper", ["a", "b", "d"], MulDivForm::Helper),
+                ("fixed", ["a", "d", "b"], MulDivForm::Checked),
+            ]
+        );
+        assert_eq!(found[0].expression, "a * b / d");
+        assert_eq!(found[0].spec.product_max, Some(u64::MAX as u128));
+        assert_eq!(found[1].spec.product_max, None);
+        assert_eq!(found[4].spec.product_max, Some(i128::MAX as u128));
+    }
+
+    #[test]
+    fn bounds_come_from_types_consts_and_guards() {
+        let source = r#"
+            const SCALE: u128 = 1_000_000;
+            fn price(amount: u64, reserve: u128, d: u128) -> u128 {
+                assert!(reserve <= 1_000_000_000, "reserve too large");
+                if d == 0 || d > SCALE {
+                    return 0;
+                }
+                (amount as u128) * reserve / d
+            }
+            fn scaled(x: u32, d: u32) -> u32 {
+                if x < 100 {
+                    x * SCALE as u32 / d
+                } else {
+                    0
+                }
+            }
+        "#;
+        let found = discover(source);
+        assert_eq!(found.len(), 2);
+
+        let spec = &found[0].spec;
+        assert_eq!(spec.multiplicand_max, u64::MAX as u128);
+        assert_eq!(spec.multiplier_max, 1_000_000_000);
+        assert_eq!((spec.divisor_min, spec.divisor_max), (1, 1_000_000));
+
+        let spec = &found[1].spec;
+        assert_eq!(spec.multiplicand_max, 99);
+        assert_eq!(spec.multiplier_max, 1_000_000);
+        assert_eq!((spec.divisor_min, spec.divisor_max), (1, u32::MAX as u128));
+    }
+
+    #[test]
+    fn guards_do_not_leak_out_of_their_branch_and_shadowing_forgets_them() {
+        let source = r#"
+            fn f(x: u32, d: u32) -> u32 {
+                if x > 10 {
+                    return 0;
+                }
+                let x = x * 1000;
+                x * 7 / d
+            }
+        "#;
+        let found = discover(source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].spec.multiplicand_max, 10_000);
+    }
+
+    #[test]
+    fn skips_calculations_of_unknown_type_and_plain_divisions() {
+        let source = r#"
+            fn f(env: Env, d: u64) -> u64 {
+                let a = read(&env);
+                let b = a / d;
+                a * read(&env) / 2
+            }
+        "#;
+        assert!(discover(source).is_empty());
+    }
+
+    #[cfg(feature = "smt")]
+    #[test]
+    fn verification_reports_only_calculations_that_can_overflow() {
+        let source = r#"
+            fn safe(a: u32, b: u32, d: u32) -> u32 {
+                assert!(a <= 1000 && b <= 1000);
+                a * b / d
+            }
+            fn risky(a: u64, b: u64, d: u64) -> Option<u64> {
+                a.checked_mul(b)?.checked_div(d)
+            }
+        "#;
+        let findings = verify_fixed_point_mul_divs(source, SmtBackend::Z3).unwrap();
+        assert_eq!(findings.len(), 1);
+        let finding = &findings[0];
+        assert_eq!(finding.kind, SmtFindingKind::FixedPointOverflow);
+        assert_eq!(finding.location, "risky");
+        assert_eq!(finding.invariant_name, "a.checked_mul(b)?.checked_div(d)");
+        assert_eq!(finding.witness.len(), 3);
+        let counterexample = finding.counterexample.as_deref().unwrap();
+        assert!(
+            counterexample.ends_with("overflows u64 so the calculation returns None"),
+            "{counterexample}"
+        );
+        let a: u128 = finding.witness["a"].parse().unwrap();
+        let b: u128 = finding.witness["b"].parse().unwrap();
+        assert!(a * b > u64::MAX as u128);
+    }
+}
@@ -1,61 +1,35 @@
-//! AST-based `#[invariant = "..."]` parsing and Z3 verification.
+//! Z3 verification of `#[invariant = "..."]` annotations.
 //!
-//! This module owns the two public entry-points for S011:
-//! - [`parse_invariants`] — extract annotations from Rust source via `syn`
-//! - [`verify_invariants`] — check each annotation with Z3 under a timeout
-
-use z3::ast::Int;
-use z3::{Config, Context, SatResult, Solver};
-
-use super::types::{InvariantSpec, SmtConfig, SmtFinding};
-
-// ── Public API ────────────────────────────────────────────────────────────────
+//! This module owns the S011 entry-point [`verify_invariants`], which checks
+//! every annotation found by [`parse_invariants`] with Z3 under a timeout.
+//!
+//! Each invariant is parsed into the expression language of [`super::expr`]
+//! and encoded as Z3 bit-vector terms of the same width and signedness as the
+//! Rust types involved.  Arithmetic follows checked Rust semantics: an
+//! operation that overflows, underflows or divides by zero would panic, so it
+//! violates the invariant just like the expression evaluating to `false`.
 
-/// Parse every `#[invariant = "..."]` attribute in the source file using the
-/// syn AST.  Returns an empty vec on parse errors (no panic).
-pub fn parse_invariants(source: &str) -> Vec<InvariantSpec> {
-    use syn::{parse_str, Expr, File, Item, Lit, Meta};
+use std::collections::BTreeMap;
 
-    let file = match parse_str::<File>(source) {
-        Ok(f) => f,
-        Err(_) => return vec![],
-    };
+use z3::ast::{Ast, Bool, BV};
+use z3::{Config, Context, Model, SatResult, Solver};
 
-    let mut specs = Vec::new();
-
-    for item in &file.items {
-        if let Item::Impl(impl_block) = item {
-            for impl_item in &impl_block.items {
-                if let syn::ImplItem::Fn(f) = impl_item {
-                    let fn_name = f.sig.ident.to_string();
-                    for attr in &f.attrs {
-                        if attr.path().is_ident("invariant") {
-                            if let Meta::NameValue(nv) = &attr.meta {
-                                if let Expr::Lit(expr_lit) = &nv.value {
-                                    if let Lit::Str(lit_str) = &expr_lit.lit {
-                                        specs.push(InvariantSpec {
-                                            expression: lit_str.value(),
-                                            location: fn_name.clone(),
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+use super::annotations::parse_invariants;
+use super::expr::{
+    parse_invariant_expr, ArithOp, BoolExpr, CmpOp, IntExpr, IntExprKind, IntType, InvariantType,
+    ParsedInvariant,
+};
+use super::types::{InvariantSpec, SmtConfig, SmtFinding, SmtFindingKind};
 
-    specs
-}
+// ── Public API ────────────────────────────────────────────────────────────────
 
 /// Verify `#[invariant = "..."]` annotations with Z3 under a configurable
 /// timeout.
 ///
 /// Returns one [`SmtFinding`] for every invariant that could not be proved
-/// safe, or for which the solver timed out.  Safe invariants produce no
-/// finding.
+/// to hold for all values of the parameters and storage keys it mentions, or
+/// for which the solver timed out.  Invariants that hold produce no finding,
+/// and neither do invariants outside the expression language.
 pub fn verify_invariants(source: &str, config: &SmtConfig) -> Vec<SmtFinding> {
     let specs = parse_invariants(source);
     if specs.is_empty() {
@@ -75,21 +49,12 @@ pub fn verify_invariants(source: &str, config: &SmtConfig) -> Vec<SmtFinding> {
 
 /// Check one [`InvariantSpec`] with Z3.
 ///
-/// * Expressions that mention addition (`+` / `add`) are verified against
-///   u64 overflow.
-/// * Expressions that mention subtraction (`-` / `sub`) are verified against
-///   u64 underflow.
-/// * All other expressions are skipped (no finding — they require manual
-///   modelling).
+/// The solver searches for an assignment of the referenced parameters and
+/// storage keys under which the expression is `false` or one of its
+/// operations panics.  Expressions outside the invariant language are
+/// skipped (no finding — they require manual modelling).
 fn check_invariant_spec(spec: &InvariantSpec, config: &SmtConfig) -> Option<SmtFinding> {
-    let expr_lower = spec.expression.to_lowercase();
-
-    let overflow_check = expr_lower.contains('+') || expr_lower.contains("add");
-    let underflow_check = expr_lower.contains('-') || expr_lower.contains("sub");
-
-    if !overflow_check && !underflow_check {
-        return None;
-    }
+    let parsed = parse_invariant_expr(&spec.expression, &spec.params).ok()?;
 
     let mut cfg = Config::new();
     // Pass the timeout as milliseconds; Z3 returns Unknown when it expires.
@@ -97,60 +62,306 @@ fn check_invariant_spec(spec: &InvariantSpec, config: &SmtConfig) -> Option<SmtF
     let ctx = Context::new(&cfg);
     let solver = Solver::new(&ctx);
 
-    let a = Int::new_const(&ctx, "a");
-    let b = Int::new_const(&ctx, "b");
-    let zero = Int::from_u64(&ctx, 0);
-    let max_u64 = Int::from_u64(&ctx, u64::MAX);
-
-    solver.assert(&a.ge(&zero));
-    solver.assert(&a.le(&max_u64));
-    solver.assert(&b.ge(&zero));
-    solver.assert(&b.le(&max_u64));
-
-    let violation = if overflow_check {
-        let sum = Int::add(&ctx, &[&a, &b]);
-        sum.gt(&max_u64)
-    } else {
-        let diff = Int::sub(&ctx, &[&a, &b]);
-        diff.lt(&zero)
-    };
-
-    solver.assert(&violation);
+    let mut encoder = Encoder::fresh(&ctx, &parsed);
+    let holds = encoder.encode(&parsed.expr);
+    solver.assert(&Bool::and(&ctx, &[&holds, &encoder.safe()]).not());
 
     match solver.check() {
         SatResult::Sat => {
-            let counterexample = solver.get_model().map(|m| {
-                let a_val = m
-                    .eval(&a, true)
-                    .map(|v| v.to_string())
-                    .unwrap_or_else(|| "?".to_string());
-                let b_val = m
-                    .eval(&b, true)
-                    .map(|v| v.to_string())
-                    .unwrap_or_else(|| "?".to_string());
-                if overflow_check {
-                    format!("a={a_val}, b={b_val} — a+b overflows u64")
-                } else {
-                    format!("a={a_val}, b={b_val} — a-b underflows u64")
+            let (counterexample, witness) = match solver.get_model() {
+                Some(model) => {
+                    let witness = encoder.witness(&model);
+                    let reason = encoder.reason(&model);
+                    let assignment = witness
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    (Some(format!("{assignment} — {reason}")), witness)
                 }
-            });
+                None => (None, BTreeMap::new()),
+            };
             Some(SmtFinding {
+                kind: SmtFindingKind::InvariantViolation,
                 invariant_name: spec.expression.clone(),
                 location: spec.location.clone(),
                 counterexample,
+                witness,
+                path: Vec::new(),
                 is_timeout: false,
             })
         }
         SatResult::Unsat => None,
         SatResult::Unknown => Some(SmtFinding {
+            kind: SmtFindingKind::InvariantViolation,
             invariant_name: spec.expression.clone(),
             location: spec.location.clone(),
             counterexample: None,
+            witness: BTreeMap::new(),
+            path: Vec::new(),
             is_timeout: true,
         }),
     }
 }
 
+/// The Z3 term an invariant variable stands for.
+pub(super) enum Var<'ctx> {
+    Bool(Bool<'ctx>),
+    Int(BV<'ctx>, IntType),
+}
+
+impl<'ctx> Var<'ctx> {
+    /// The variable's value in `model`, in decimal.
+    pub(super) fn value_in(&self, model: &Model<'ctx>) -> String {
+        match self {
+            Var::Bool(b) => model.eval(b, true).map(|v| v.to_string()),
+            // Z3 prints negative integers as `(- 5)`.
+            Var::Int(bv, ty) => model
+                .eval(&bv.to_int(ty.is_signed()), true)
+                .map(|v| v.to_string().replace("(- ", "-").replace(')', "")),
+        }
+        .unwrap_or_else(|| "?".to_string())
+    }
+}
+
+/// Translates a [`ParsedInvariant`] into Z3 terms.
+///
+/// Every arithmetic operation records a guard — the condition under which it
+/// does not panic — conditioned on the operation actually being evaluated,
+/// so `b != 0 && a / b > 1` does not report a division by zero.
+pub(super) struct Encoder<'ctx> {
+    ctx: &'ctx Context,
+    vars: BTreeMap<String, Var<'ctx>>,
+    guards: Vec<(Bool<'ctx>, String)>,
+}
+
+impl<'ctx> Encoder<'ctx> {
+    /// Unconstrained constants for every variable of `parsed`.
+    pub(super) fn fresh(ctx: &'ctx Context, parsed: &ParsedInvariant) -> Self {
+        let vars = parsed
+            .variables
+            .iter()
+            .map(|(name, (_, ty))| {
+                let var = match ty {
+                    InvariantType::Bool => Var::Bool(Bool::new_const(ctx, name.as_str())),
+                    InvariantType::Int(int) => {
+                        Var::Int(BV::new_const(ctx, name.as_str(), int.bits()), *int)
+                    }
+                };
+                (name.clone(), var)
+            })
+            .collect();
+        Self::with_vars(ctx, vars)
+    }
+
+    /// Encode against caller-supplied terms, e.g. the storage state at some
+    /// point of a symbolic execution.
+    pub(super) fn with_vars(ctx: &'ctx Context, vars: BTreeMap<String, Var<'ctx>>) -> Self {
+        Self {
+            ctx,
+            vars,
+            guards: Vec::new(),
+        }
+    }
+
+    /// Encode the invariant's value.
+    pub(super) fn encode(&mut self, expr: &BoolExpr) -> Bool<'ctx> {
+        self.boolean(expr, &Bool::from_bool(self.ctx, true))
+    }
+
+    /// Whether no operation encoded so far panics.
+    pub(super) fn safe(&self) -> Bool<'ctx> {
+        let guards: Vec<&Bool> = self.guards.iter().map(|(safe, _)| safe).collect();
+        Bool::and(self.ctx, &guards)
+    }
+
+    /// Encode `expr`, evaluated only when `reached` holds.
+    fn boolean(&mut self, expr: &BoolExpr, reached: &Bool<'ctx>) -> Bool<'ctx> {
+        match expr {
+            BoolExpr::Const(b) => Bool::from_bool(self.ctx, *b),
+            BoolExpr::Var(name) => match &self.vars[name] {
+                Var::Bool(b) => b.clone(),
+                Var::Int(..) => unreachable!("type-checked as bool"),
+            },
+            BoolExpr::Not(inner) => self.boolean(inner, reached).not(),
+            BoolExpr::And(lhs, rhs) => {
+                let lhs = self.boolean(lhs, reached);
+                let rhs = self.boolean(rhs, &Bool::and(self.ctx, &[reached, &lhs]));
+                Bool::and(self.ctx, &[&lhs, &rhs])
+            }
+            BoolExpr::Or(lhs, rhs) => {
+                let lhs = self.boolean(lhs, reached);
+                let rhs = self.boolean(rhs, &Bool::and(self.ctx, &[reached, &lhs.not()]));
+                Bool::or(self.ctx, &[&lhs, &rhs])
+            }
+            BoolExpr::Iff { lhs, rhs, negated } => {
+                let lhs = self.boolean(lhs, reached);
+                let rhs = self.boolean(rhs, reached);
+                if *negated {
+                    lhs.xor(&rhs)
+                } else {
+                    lhs.iff(&rhs)
+                }
+            }
+            BoolExpr::Compare { op, lhs, rhs } => {
+                let signed = lhs.ty.is_signed();
+                let l = self.integer(lhs, reached);
+                let r = self.integer(rhs, reached);
+                match (op, signed) {
+                    (CmpOp::Eq, _) => l._eq(&r),
+                    (CmpOp::Ne, _) => l._eq(&r).not(),
+                    (CmpOp::Lt, false) => l.bvult(&r),
+                    (CmpOp::Lt, true) => l.bvslt(&r),
+                    (CmpOp::Le, false) => l.bvule(&r),
+                    (CmpOp::Le, true) => l.bvsle(&r),
+                    (CmpOp::Gt, false) => l.bvugt(&r),
+                    (CmpOp::Gt, true) => l.bvsgt(&r),
+                    (CmpOp::Ge, false) => l.bvuge(&r),
+                    (CmpOp::Ge, true) => l.bvsge(&r),
+                }
+            }
+        }
+    }
+
+    fn integer(&mut self, expr: &IntExpr, reached: &Bool<'ctx>) -> BV<'ctx> {
+        let ty = expr.ty;
+        match &expr.kind {
+            IntExprKind::Const(bits) => BV::from_str(self.ctx, ty.bits(), &bits.to_string())
+                .expect("decimal literal should be a valid Z3 bit-vector"),
+            IntExprKind::Var { name, .. } => match &self.vars[name] {
+                Var::Int(bv, _) => bv.clone(),
+                Var::Bool(_) => unreachable!("type-checked as integer"),
+            },
+            IntExprKind::Neg(inner) => {
+                let v = self.integer(inner, reached);
+                self.guard(
+                    reached,
+                    v.bvneg_no_overflow(),
+                    format!("`{expr}` overflows {}", ty.name()),
+                );
+                v.bvneg()
+            }
+            IntExprKind::Cast(inner) => {
+                let from = inner.ty;
+                let v = self.integer(inner, reached);
+                cast_bv(v, from, ty)
+            }
+            IntExprKind::Arith { op, lhs, rhs } => {
+                let l = self.integer(lhs, reached);
+                let r = self.integer(rhs, reached);
+                let (value, guards) = checked_arith(self.ctx, *op, &l, &r, ty);
+                for (safe, panic) in guards {
+                    let description = if panic == DIVIDES_BY_ZERO {
+                        format!("`{expr}` {panic}")
+                    } else {
+                        format!("`{expr}` {panic} {}", ty.name())
+                    };
+                    self.guard(reached, safe, description);
+                }
+                value
+            }
+        }
+    }
+
+    fn guard(&mut self, reached: &Bool<'ctx>, safe: Bool<'ctx>, description: String) {
+        self.guards.push((reached.implies(&safe), description));
+    }
+
+    /// Values of every referenced variable in `model`, in decimal.
+    pub(super) fn witness(&self, model: &Model<'ctx>) -> BTreeMap<String, String> {
+        self.vars
+            .iter()
+            .map(|(name, var)| (name.clone(), var.value_in(model)))
+            .collect()
+    }
+
+    /// Why the invariant fails under `model`: the first operation that
+    /// panics, or the expression evaluating to `false`.
+    pub(super) fn reason(&self, model: &Model<'ctx>) -> String {
+        self.guards
+            .iter()
+            .find(|(safe, _)| model.eval(safe, true).and_then(|v| v.as_bool()) == Some(false))
+            .map(|(_, description)| description.clone())
+            .unwrap_or_else(|| "the invariant evaluates to false".to_string())
+    }
+}
+
+/// How [`checked_arith`] describes a division by zero.
+pub(super) const DIVIDES_BY_ZERO: &str = "divides by zero";
+
+/// `l <op> r` in `ty`, together with each condition under which Rust's
+/// checked arithmetic does not panic and how it panics otherwise
+/// (`"overflows"`, `"underflows"` or [`DIVIDES_BY_ZERO`]).
+pub(super) fn checked_arith<'ctx>(
+    ctx: &'ctx Context,
+    op: ArithOp,
+    l: &BV<'ctx>,
+    r: &BV<'ctx>,
+    ty: IntType,
+) -> (BV<'ctx>, Vec<(Bool<'ctx>, &'static str)>) {
+    let signed = ty.is_signed();
+    let (value, safe, panic) = match op {
+        ArithOp::Add => {
+            let safe = if signed {
+                Bool::and(
+                    ctx,
+                    &[&l.bvadd_no_overflow(r, true), &l.bvadd_no_underflow(r)],
+                )
+            } else {
+                l.bvadd_no_overflow(r, false)
+            };
+            (l.bvadd(r), safe, "overflows")
+        }
+        ArithOp::Sub if signed => {
+            let safe = Bool::and(
+                ctx,
+                &[&l.bvsub_no_overflow(r), &l.bvsub_no_underflow(r, true)],
+            );
+            (l.bvsub(r), safe, "overflows")
+        }
+        ArithOp::Sub => (l.bvsub(r), l.bvsub_no_underflow(r, false), "underflows"),
+        ArithOp::Mul => {
+            let safe = if signed {
+                Bool::and(
+                    ctx,
+                    &[&l.bvmul_no_overflow(r, true), &l.bvmul_no_underflow(r)],
+                )
+            } else {
+                l.bvmul_no_overflow(r, false)
+            };
+            (l.bvmul(r), safe, "overflows")
+        }
+        ArithOp::Div | ArithOp::Rem => {
+            let zero = BV::from_u64(ctx, 0, ty.bits());
+            let mut guards = vec![(r._eq(&zero).not(), DIVIDES_BY_ZERO)];
+            if signed {
+                guards.push((l.bvsdiv_no_overflow(r), "overflows"));
+            }
+            let value = match (op, signed) {
+                (ArithOp::Div, false) => l.bvudiv(r),
+                (ArithOp::Div, true) => l.bvsdiv(r),
+                (_, false) => l.bvurem(r),
+                (_, true) => l.bvsrem(r),
+            };
+            return (value, guards);
+        }
+    };
+    (value, vec![(safe, panic)])
+}
+
+/// `v as to` for a value of type `from`.
+pub(super) fn cast_bv(v: BV<'_>, from: IntType, to: IntType) -> BV<'_> {
+    if to.bits() > from.bits() && from.is_signed() {
+        v.sign_ext(to.bits() - from.bits())
+    } else if to.bits() > from.bits() {
+        v.zero_ext(to.bits() - from.bits())
+    } else if to.bits() < from.bits() {
+        v.extract(to.bits() - 1, 0)
+    } else {
+        v
+    }
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -186,11 +397,30 @@ mod tests {
         assert_eq!(specs[0].location, "deposit");
     }
 
+    #[test]
+    fn parse_invariants_records_parameter_types() {
+        let source = r#"
+            impl Vault {
+                #[invariant = "amount > 0"]
+                pub fn deposit(env: Env, from: &Address, amount: &i128) {}
+            }
+        "#;
+        let specs = parse_invariants(source);
+        assert_eq!(
+            specs[0].params,
+            vec![
+                ("env".to_string(), "Env".to_string()),
+                ("from".to_string(), "Address".to_string()),
+                ("amount".to_string(), "i128".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn verify_invariants_addition_overflow_flagged() {
         let source = r#"
             impl Vault {
-                #[invariant = "a + b is safe"]
+                #[invariant = "a + b >= a"]
                 pub fn credit(&self, a: u64, b: u64) {}
             }
         "#;
@@ -200,18 +430,88 @@ mod tests {
             !findings[0].is_timeout,
             "should be a SAT result, not timeout"
         );
+        let counterexample = findings[0]
+            .counterexample
+            .as_ref()
+            .expect("counterexample must be populated for an unsafe invariant");
         assert!(
-            findings[0].counterexample.is_some(),
-            "counterexample must be populated for an unsafe invariant"
-        );
-        assert!(
-            findings[0]
-                .counterexample
-                .as_ref()
-                .unwrap()
-                .contains("overflow"),
-            "counterexample should mention overflow"
+            counterexample.contains("`a + b` overflows u64"),
+            "counterexample should name the overflowing operation: {counterexample}"
         );
+        let a: u128 = findings[0].witness["a"].parse().unwrap();
+        let b: u128 = findings[0].witness["b"].parse().unwrap();
+        assert!(a + b > u64::MAX as u128, "witness must actually overflow");
+    }
+
+    #[test]
+    fn verify_invariants_widened_addition_is_proved() {
+        let source = r#"
+            impl Vault {
+                #[invariant = "a as u128 + b as u128 >= a as u128"]
+                pub fn credit(&self, a: u64, b: u64) {}
+            }
+        "#;
+        assert!(verify_invariants(source, &SmtConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn verify_invariants_reports_false_property_over_storage() {
+        let source = r#"
+            impl Token {
+                #[invariant = "amount <= DataKey::TotalSupply"]
+                pub fn burn(env: Env, amount: i128) {}
+            }
+        "#;
+        let findings = verify_invariants(source, &SmtConfig::default());
+        assert_eq!(findings.len(), 1);
+        let witness = &findings[0].witness;
+        let amount: i128 = witness["amount"].parse().unwrap();
+        let supply: i128 = witness["DataKey::TotalSupply"].parse().unwrap();
+        assert!(amount > supply);
+        assert!(findings[0]
+            .counterexample
+            .as_ref()
+            .unwrap()
+            .ends_with("the invariant evaluates to false"));
+    }
+
+    #[test]
+    fn verify_invariants_respects_short_circuiting() {
+        let guarded = r#"
+            impl Pool {
+                #[invariant = "shares == 0 || amount / shares <= amount"]
+                pub fn price(&self, amount: u32, shares: u32) {}
+            }
+        "#;
+        assert!(verify_invariants(guarded, &SmtConfig::default()).is_empty());
+
+        let unguarded = r#"
+            impl Pool {
+                #[invariant = "amount / shares <= amount"]
+                pub fn price(&self, amount: u32, shares: u32) {}
+            }
+        "#;
+        let findings = verify_invariants(unguarded, &SmtConfig::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].witness["shares"], "0");
+        assert!(findings[0]
+            .counterexample
+            .as_ref()
+            .unwrap()
+            .contains("divides by zero"));
+    }
+
+    #[test]
+    fn verify_invariants_uses_signed_i128_semantics() {
+        let source = r#"
+            impl Vault {
+                #[invariant = "balance - 1 < balance"]
+                pub fn withdraw(&self, balance: i128) {}
+            }
+        "#;
+        let findings = verify_invariants(source, &SmtConfig::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].witness["balance"], i128::MIN.to_string());
     }
 
     #[test]
@@ -233,7 +533,7 @@ mod tests {
     fn verify_invariants_timeout_produces_is_timeout_true() {
         let source = r#"
             impl Vault {
-                #[invariant = "a + b is safe"]
+                #[invariant = "a * b / 3 <= a"]
                 pub fn credit(&self, a: u64, b: u64) {}
             }
         "#;
@@ -1,12 +1,18 @@
-//! Z3-based formal-verification primitives (S011).
+//! SMT-based formal-verification primitives (S011).
 //!
 //! # Module layout
 //!
 //! | Sub-module | Responsibility |
 //! |---|---|
 //! | [`types`] | All shared data types and error enums |
-//! | [`invariants`] | `#[invariant = "..."]` AST parsing and Z3 verification |
+//! | [`expr`] | The invariant expression language: parsing and type-checking |
+//! | [`annotations`] | `#[invariant = "..."]` AST extraction |
+//! | [`invariants`] | Z3 verification of invariant annotations |
+//! | [`symbolic`] | Symbolic execution proving contract functions preserve invariants |
+//! | [`fixed_point`] | Discovery and proof of `a * b / d` calculations in contract source |
 //! | [`backend`] | `SmtVerifier`, Z3 context wrapper, fixed-point proof dispatch |
+//! | [`smtlib`] | SMT-LIB2 emission of every proof, for external solvers or `.smt2` dumps |
+//! | [`process`] | `SmtLibSolver`: runs an SMT-LIB2 solver binary with a timeout |
 //! | [`benchmark`] | Latency micro-benchmark for CI artifact generation |
 //!
 //! All items from every sub-module are re-exported at this level so that
@@ -15,13 +21,25 @@
 //!
 //! # Feature flag
 //!
-//! This entire module is gated behind `#[cfg(feature = "smt")]`.  Disable it
-//! with `default-features = false` when targeting `wasm32-unknown-unknown`,
-//! which has no native Z3 library.
+//! Everything that links Z3 — `SmtVerifier`, the in-process Z3 proofs,
+//! [`invariants`], [`symbolic`] and [`benchmark`] — is gated behind
+//! `#[cfg(feature = "smt")]`.  Disable it with `default-features = false`
+//! when targeting `wasm32-unknown-unknown`, which has no native Z3 library.
+//! The SMT-LIB2 layer ([`smtlib`], [`process`]) is always available, so
+//! builds without libz3 can still run cvc5, Bitwuzla or the `z3` binary.
 
+mod annotations;
 mod backend;
+#[cfg(feature = "smt")]
 mod benchmark;
+mod expr;
+mod fixed_point;
+#[cfg(feature = "smt")]
 mod invariants;
+mod process;
+mod smtlib;
+#[cfg(feature = "smt")]
+mod symbolic;
 mod types;
 
 // ── Public re-exports (zero-breaking-change surface) ─────────────────────────
@@ -29,17 +47,40 @@ mod types;
 // Types
 pub use types::{
     FixedPointCounterexample, FixedPointMulDivSpec, FixedPointProofError, FixedPointProofReport,
-    InvariantSpec, SmtBackend, SmtConfig, SmtFinding, SmtInvariantIssue, SmtLatencyBenchmarkReport,
-    SmtProofStrategy, SmtStrategyLatency,
+    InvariantSpec, SmtBackend, SmtConfig, SmtFinding, SmtFindingKind, SmtInvariantIssue,
+    SmtLatencyBenchmarkReport, SmtLibError, SmtProofStrategy, SmtStrategyLatency,
+};
+
+// Invariant expression language
+pub use expr::{
+    parse_invariant_expr, ArithOp, BoolExpr, CmpOp, IntExpr, IntExprKind, IntType,
+    InvariantError, InvariantType, ParsedInvariant, VarSource,
 };
 
 // Invariant verification (S011 entry-points)
-pub use invariants::{parse_invariants, verify_invariants};
+pub use annotations::parse_invariants;
+#[cfg(feature = "smt")]
+pub use invariants::verify_invariants;
+#[cfg(feature = "smt")]
+pub use symbolic::verify_invariant_preservation;
 
 // Backend: SmtVerifier + fixed-point proofs
-pub use backend::{
-    prove_fixed_point_mul_div_bounds, prove_fixed_point_mul_div_bounds_with_backend, SmtVerifier,
+pub use backend::{prove_fixed_point_mul_div_bounds, prove_fixed_point_mul_div_bounds_with_backend};
+#[cfg(feature = "smt")]
+pub use backend::SmtVerifier;
+
+// Fixed-point calculation discovery
+pub use fixed_point::{
+    discover_fixed_point_mul_divs, verify_fixed_point_mul_divs, FixedPointCandidate, MulDivForm,
+};
+
+// SMT-LIB2 emission and external solvers
+pub use process::{SmtLibOutcome, SmtLibSolver};
+pub use smtlib::{
+    addition_overflow_query, fixed_point_query, invariant_queries, invariant_query, SmtLibQuery,
+    SmtLibSort,
 };
 
 // Benchmark
+#[cfg(feature = "smt")]
 pub use benchmark::run_smt_latency_benchmark;
@@ -0,0 +1,726 @@
+//! Process-based driver for SMT-LIB2 solver binaries.
+//!
+//! [`SmtLibSolver`] pipes an [`SmtLibQuery`] into any locally installed
+//! solver that reads SMT-LIB2 on stdin — cvc5, Bitwuzla, the `z3` binary or
+//! anything else — kills it when the timeout expires, and parses the
+//! `check-sat` answer and the `(get-value ...)` model from its stdout.
+//! Nothing here links libz3, so these proofs also run in builds without the
+//! `smt` feature.
+//!
+//! Scripts are sent whole and stdin is closed, so the solver runs in batch
+//! mode.  After `unsat` the trailing `(get-value ...)` makes most solvers
+//! print an `(error ...)`; anything after the answer is ignored unless the
+//! answer was `sat`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::annotations::parse_invariants;
+use super::backend::fixed_point_properties;
+use super::smtlib::{
+    addition_overflow_query, fixed_point_query, invariant_query, SmtLibQuery, SmtLibSort,
+};
+use super::types::{
+    FixedPointCounterexample, FixedPointMulDivSpec, FixedPointProofError, FixedPointProofReport,
+    SmtBackend, SmtFinding, SmtFindingKind, SmtInvariantIssue, SmtLibError,
+};
+
+/// Matches the default of [`super::SmtConfig::timeout_ms`].
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a running solver is polled for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+// ── SmtLibSolver ──────────────────────────────────────────────────────────────
+
+/// An SMT-LIB2 solver binary, run once per query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtLibSolver {
+    backend: SmtBackend,
+    program: PathBuf,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+/// A solver's answer to an [`SmtLibQuery`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmtLibOutcome {
+    /// The violation is reachable.  Holds every requested value, keyed by
+    /// symbol: integers in decimal, booleans as `true` / `false`.
+    Sat(BTreeMap<String, String>),
+    /// The property holds.
+    Unsat,
+    /// The solver gave up.
+    Unknown,
+}
+
+impl SmtLibSolver {
+    /// Run `program` with no arguments, reported as [`SmtBackend::External`].
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            backend: SmtBackend::External,
+            program: program.into(),
+            args: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// The binary on `PATH` for `backend`, set up to read SMT-LIB2 on stdin.
+    /// `None` for [`SmtBackend::External`], which has no default binary.
+    pub fn for_backend(backend: SmtBackend) -> Option<Self> {
+        let (program, args): (&str, &[&str]) = match backend {
+            SmtBackend::Z3 => ("z3", &["-in", "-smt2"]),
+            SmtBackend::Cvc5 => ("cvc5", &["--lang=smt2"]),
+            SmtBackend::Bitwuzla => ("bitwuzla", &[]),
+            SmtBackend::External => return None,
+        };
+        Some(Self {
+            backend,
+            ..Self::new(program).with_args(args.iter().copied())
+        })
+    }
+
+    /// Replace the command-line arguments.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Kill the solver and fail with [`SmtLibError::Timeout`] after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The backend reported in proof results.
+    pub fn backend(&self) -> SmtBackend {
+        self.backend
+    }
+
+    /// The binary that is run.
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Run `query` and parse the answer.
+    pub fn check(&self, query: &SmtLibQuery) -> Result<SmtLibOutcome, SmtLibError> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| SmtLibError::Spawn {
+                program: self.display_name(),
+                message: e.to_string(),
+            })?;
+
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        if let Some(mut stdin) = child.stdin.take() {
+            let script = query.script.clone();
+            // A solver that exits early closes the pipe; its output says why.
+            thread::spawn(move || stdin.write_all(script.as_bytes()));
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+                Ok(None) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(SmtLibError::Timeout {
+                        program: self.display_name(),
+                        timeout_ms: self.timeout.as_millis() as u64,
+                    });
+                }
+                Err(e) => {
+                    return Err(SmtLibError::Spawn {
+                        program: self.display_name(),
+                        message: e.to_string(),
+                    })
+                }
+            }
+        }
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        parse_response(&stdout, query).map_err(|response| match response {
+            Response::SolverError(message) => SmtLibError::Solver {
+                program: self.display_name(),
+                message,
+            },
+            Response::Malformed(message) => SmtLibError::Output {
+                program: self.display_name(),
+                message: match stderr.trim() {
+                    "" => message,
+                    stderr => format!("{message}; stderr: {stderr}"),
+                },
+            },
+        })
+    }
+
+    /// Whether `a + b` can overflow a 64-bit unsigned integer under
+    /// unconstrained inputs, like `SmtVerifier::verify_addition_overflow`.
+    pub fn verify_addition_overflow(
+        &self,
+        fn_name: &str,
+        location: &str,
+    ) -> Result<Option<SmtInvariantIssue>, SmtLibError> {
+        match self.check(&addition_overflow_query())? {
+            SmtLibOutcome::Sat(_) => Ok(Some(SmtInvariantIssue {
+                function_name: fn_name.to_string(),
+                description: format!(
+                    "SMT Solver ({}) proved that this addition can overflow u64 bounds.",
+                    self.display_name()
+                ),
+                location: location.to_string(),
+            })),
+            SmtLibOutcome::Unsat | SmtLibOutcome::Unknown => Ok(None),
+        }
+    }
+
+    /// Check every `#[invariant = "..."]` in `source`, with the same
+    /// findings as `verify_invariants`.  A timeout becomes an
+    /// [`SmtFinding::is_timeout`] finding; failing to run the solver at all
+    /// is an error.
+    pub fn verify_invariants(&self, source: &str) -> Result<Vec<SmtFinding>, SmtLibError> {
+        let mut findings = Vec::new();
+        for spec in parse_invariants(source) {
+            let Ok(query) = invariant_query(&spec) else {
+                continue;
+            };
+            let outcome = match self.check(&query) {
+                Err(SmtLibError::Timeout { .. }) => SmtLibOutcome::Unknown,
+                outcome => outcome?,
+            };
+            let (counterexample, witness, is_timeout) = match outcome {
+                SmtLibOutcome::Unsat => continue,
+                SmtLibOutcome::Unknown => (None, BTreeMap::new(), true),
+                SmtLibOutcome::Sat(mut model) => {
+                    let reason = query
+                        .guards
+                        .iter()
+                        .find(|(name, _)| model.get(name).map(String::as_str) == Some("false"))
+                        .map(|(_, description)| description.clone())
+                        .unwrap_or_else(|| "the invariant evaluates to false".to_string());
+                    model.retain(|name, _| !query.guards.iter().any(|(guard, _)| guard == name));
+                    let assignment = model
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    (Some(format!("{assignment} — {reason}")), model, false)
+                }
+            };
+            findings.push(SmtFinding {
+                kind: SmtFindingKind::InvariantViolation,
+                invariant_name: spec.expression.clone(),
+                location: spec.location.clone(),
+                counterexample,
+                witness,
+                path: Vec::new(),
+                is_timeout,
+            });
+        }
+        Ok(findings)
+    }
+
+    /// Prove that `a * b / d` cannot overflow `u128` within the provided
+    /// bounds with this solver.
+    pub fn prove_fixed_point_mul_div_bounds(
+        &self,
+        spec: &FixedPointMulDivSpec,
+    ) -> Result<FixedPointProofReport, FixedPointProofError> {
+        let query = fixed_point_query(spec)?;
+        let name = self.display_name();
+        let mut report = FixedPointProofReport {
+            function_name: spec.function_name.clone(),
+            backend: self.backend,
+            proven_safe: true,
+            checked_properties: fixed_point_properties(spec),
+            message: format!(
+                "{name} proved the fixed-point calculation stays within the configured bounds."
+            ),
+            counterexample: None,
+        };
+
+        match self.check(&query)? {
+            SmtLibOutcome::Unsat => Ok(report),
+            SmtLibOutcome::Sat(model) => {
+                let value = |symbol: &str| {
+                    model
+                        .get(symbol)
+                        .cloned()
+                        .ok_or(FixedPointProofError::SolverFailure(
+                            "missing fixed-point witness",
+                        ))
+                };
+                report.proven_safe = false;
+                report.message =
+                    format!("{name} found a counterexample within the configured input ranges.");
+                report.counterexample = Some(FixedPointCounterexample {
+                    multiplicand: value("multiplicand")?,
+                    multiplier: value("multiplier")?,
+                    divisor: value("divisor")?,
+                    intermediate_product: value("product")?,
+                    quotient: value("quotient")?,
+                });
+                Ok(report)
+            }
+            SmtLibOutcome::Unknown => Err(FixedPointProofError::SolverFailure(
+                "the solver returned unknown for the requested fixed-point proof",
+            )),
+        }
+    }
+
+    /// The binary's file name, e.g. `cvc5`.
+    fn display_name(&self) -> String {
+        self.program
+            .file_name()
+            .unwrap_or(self.program.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Read `pipe` to the end on a separate thread, so a chatty solver never
+/// blocks on a full pipe while it is being polled.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut output);
+        }
+        output
+    })
+}
+
+// ── Internal: output parsing ──────────────────────────────────────────────────
+
+/// Why stdout did not contain a usable answer.
+#[derive(Debug, PartialEq, Eq)]
+enum Response {
+    /// The solver printed `(error "...")` before answering.
+    SolverError(String),
+    Malformed(String),
+}
+
+/// The `check-sat` answer in `stdout` and, after `sat`, the model.
+fn parse_response(stdout: &str, query: &SmtLibQuery) -> Result<SmtLibOutcome, Response> {
+    let mut items = parse_sexps(stdout)
+        .map_err(Response::Malformed)?
+        .into_iter();
+    loop {
+        match items.next() {
+            Some(Sexp::Atom(answer)) if answer == "unsat" => return Ok(SmtLibOutcome::Unsat),
+            Some(Sexp::Atom(answer)) if answer == "unknown" => return Ok(SmtLibOutcome::Unknown),
+            Some(Sexp::Atom(answer)) if answer == "sat" => break,
+            Some(item) => {
+                if let Some(message) = item.error_message() {
+                    return Err(Response::SolverError(message));
+                }
+            }
+            None => return Err(Response::Malformed("no `check-sat` answer".to_string())),
+        }
+    }
+
+    if query.values.is_empty() {
+        return Ok(SmtLibOutcome::Sat(BTreeMap::new()));
+    }
+    let pairs = match items.next() {
+        Some(item) if item.error_message().is_some() => {
+            return Err(Response::SolverError(
+                item.error_message().unwrap_or_default(),
+            ))
+        }
+        Some(Sexp::List(pairs)) => pairs,
+        _ => {
+            return Err(Response::Malformed(
+                "no `get-value` response after `sat`".to_string(),
+            ))
+        }
+    };
+
+    let mut model = BTreeMap::new();
+    for pair in pairs {
+        let (symbol, value) = match &pair {
+            Sexp::List(entry) => match entry.as_slice() {
+                [Sexp::Atom(symbol), value] => (symbol, value),
+                _ => return Err(Response::Malformed(format!("unexpected entry `{pair}`"))),
+            },
+            Sexp::Atom(_) => return Err(Response::Malformed(format!("unexpected entry `{pair}`"))),
+        };
+        let Some((_, sort)) = query.values.iter().find(|(name, _)| name == symbol) else {
+            continue;
+        };
+        let decimal = decimal_value(value, *sort)
+            .ok_or_else(|| Response::Malformed(format!("cannot read `{value}` for `{symbol}`")))?;
+        model.insert(symbol.clone(), decimal);
+    }
+    Ok(SmtLibOutcome::Sat(model))
+}
+
+/// `value` in decimal, or `true` / `false` for booleans.
+fn decimal_value(value: &Sexp, sort: SmtLibSort) -> Option<String> {
+    match (value, sort) {
+        (Sexp::Atom(b), SmtLibSort::Bool) if b == "true" || b == "false" => Some(b.clone()),
+        (Sexp::Atom(atom), SmtLibSort::BitVec { bits, signed }) => {
+            let (digits, radix) = if let Some(binary) = atom.strip_prefix("#b") {
+                (binary, 2)
+            } else {
+                (atom.strip_prefix("#x")?, 16)
+            };
+            bitvec_to_decimal(digits, radix, bits, signed)
+        }
+        // `(_ bv42 64)`
+        (Sexp::List(items), SmtLibSort::BitVec { bits, signed }) => match items.as_slice() {
+            [Sexp::Atom(underscore), Sexp::Atom(literal), Sexp::Atom(_)] if underscore == "_" => {
+                bitvec_to_decimal(literal.strip_prefix("bv")?, 10, bits, signed)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The unsigned bit pattern `digits` (in `radix`) of a `bits`-wide
+/// bit-vector, as a decimal integer.
+fn bitvec_to_decimal(digits: &str, radix: u32, bits: u32, signed: bool) -> Option<String> {
+    if bits <= 128 {
+        let value = u128::from_str_radix(digits, radix).ok()?;
+        let mask = u128::MAX >> (128 - bits);
+        if signed && value >> (bits - 1) & 1 == 1 {
+            return Some(format!("-{}", value.wrapping_neg() & mask));
+        }
+        return Some(value.to_string());
+    }
+
+    // Wider than u128 (the fixed-point product): base-10⁹ limbs,
+    // least significant first.
+    const BASE: u64 = 1_000_000_000;
+    let mut limbs = vec![0u64];
+    for c in digits.chars() {
+        let mut carry = u64::from(c.to_digit(radix)?);
+        for limb in &mut limbs {
+            let v = *limb * u64::from(radix) + carry;
+            *limb = v % BASE;
+            carry = v / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut decimal = limbs.last()?.to_string();
+    for limb in limbs.iter().rev().skip(1) {
+        let _ = write!(decimal, "{limb:09}");
+    }
+    Some(decimal)
+}
+
+/// An s-expression as printed by a solver.  `|quoted|` symbols and string
+/// literals are atoms without their delimiters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    /// The message of an `(error "...")` response.
+    fn error_message(&self) -> Option<String> {
+        match self {
+            Sexp::List(items) => match items.as_slice() {
+                [Sexp::Atom(head), Sexp::Atom(message)] if head == "error" => Some(message.clone()),
+                _ => None,
+            },
+            Sexp::Atom(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Sexp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sexp::Atom(atom) => f.write_str(atom),
+            Sexp::List(items) => {
+                f.write_str("(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Every top-level s-expression in `text`.
+fn parse_sexps(text: &str) -> Result<Vec<Sexp>, String> {
+    let mut stack: Vec<Vec<Sexp>> = vec![Vec::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let atom = match c {
+            c if c.is_whitespace() => continue,
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                continue;
+            }
+            '(' => {
+                stack.push(Vec::new());
+                continue;
+            }
+            ')' => {
+                let list = stack.pop().filter(|_| !stack.is_empty());
+                let (Some(list), Some(parent)) = (list, stack.last_mut()) else {
+                    return Err("unbalanced `)`".to_string());
+                };
+                parent.push(Sexp::List(list));
+                continue;
+            }
+            '|' => {
+                let mut atom = String::new();
+                loop {
+                    match chars.next() {
+                        Some('|') => break atom,
+                        Some(c) => atom.push(c),
+                        None => return Err("unterminated `|` symbol".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let mut atom = String::new();
+                loop {
+                    match chars.next() {
+                        // `""` is an escaped quote.
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            atom.push('"');
+                        }
+                        Some('"') => break atom,
+                        Some(c) => atom.push(c),
+                        None => return Err("unterminated string literal".to_string()),
+                    }
+                }
+            }
+            c => {
+                let mut atom = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()|\";".contains(c) {
+                        break;
+                    }
+                    atom.push(c);
+                    chars.next();
+                }
+                atom
+            }
+        };
+        stack
+            .last_mut()
+            .expect("the top level is never popped")
+            .push(Sexp::Atom(atom));
+    }
+    match <[Vec<Sexp>; 1]>::try_from(stack) {
+        Ok([items]) => Ok(items),
+        Err(_) => Err("unbalanced `(`".to_string()),
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smt::InvariantSpec;
+
+    fn deposit_query() -> SmtLibQuery {
+        invariant_query(&InvariantSpec {
+            expression: "DataKey::Total + amount >= amount".to_string(),
+            location: "deposit".to_string(),
+            params: vec![("amount".to_string(), "i128".to_string())],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_binary_hex_and_decimal_bit_vectors() {
+        let query = deposit_query();
+        let stdout = "sat\n((|DataKey::Total| #b1111111111111111111111111111111111111111111111111111111111111111\
+                      1111111111111111111111111111111111111111111111111111111111111110)\n \
+                      (amount (_ bv5 128))\n ($safe0 false))\n(error \"ignored\")\n";
+        let SmtLibOutcome::Sat(model) = parse_response(stdout, &query).unwrap() else {
+            panic!("expected sat");
+        };
+        assert_eq!(model["DataKey::Total"], "-2");
+        assert_eq!(model["amount"], "5");
+        assert_eq!(model["$safe0"], "false");
+    }
+
+    #[test]
+    fn reads_bit_vectors_wider_than_u128() {
+        let all_ones = "f".repeat(64);
+        assert_eq!(
+            bitvec_to_decimal(&all_ones, 16, 256, false).unwrap(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(
+            bitvec_to_decimal("1000000000", 16, 256, false).unwrap(),
+            (1u128 << 36).to_string()
+        );
+    }
+
+    #[test]
+    fn reports_errors_before_the_answer_and_ignores_them_after() {
+        let query = deposit_query();
+        assert_eq!(
+            parse_response("(error \"line 3: unknown logic\")\n", &query),
+            Err(Response::SolverError("line 3: unknown logic".to_string()))
+        );
+        assert_eq!(
+            parse_response("unsat\n(error \"model is not available\")\n", &query),
+            Ok(SmtLibOutcome::Unsat)
+        );
+        assert_eq!(
+            parse_response("", &query),
+            Err(Response::Malformed("no `check-sat` answer".to_string()))
+        );
+    }
+
+    #[test]
+    fn for_backend_knows_every_binary_but_external() {
+        let cvc5 = SmtLibSolver::for_backend(SmtBackend::Cvc5).unwrap();
+        assert_eq!(cvc5.program(), Path::new("cvc5"));
+        assert_eq!(cvc5.backend(), SmtBackend::Cvc5);
+        assert!(SmtLibSolver::for_backend(SmtBackend::Bitwuzla).is_some());
+        assert!(SmtLibSolver::for_backend(SmtBackend::Z3).is_some());
+        assert!(SmtLibSolver::for_backend(SmtBackend::External).is_none());
+    }
+
+    #[test]
+    fn missing_binary_is_a_spawn_error() {
+        let solver = SmtLibSolver::new("sanctifier-no-such-solver");
+        let error = solver.check(&addition_overflow_query()).unwrap_err();
+        assert!(
+            matches!(error, SmtLibError::Spawn { ref program, .. } if program == "sanctifier-no-such-solver")
+        );
+    }
+
+    /// A stand-in solver: discards the script and prints `output`.
+    #[cfg(unix)]
+    fn scripted(output: &str) -> SmtLibSolver {
+        SmtLibSolver::new("sh").with_args([
+            "-c".to_string(),
+            format!("cat > /dev/null; printf '%s' '{output}'"),
+        ])
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fixed_point_counterexample_comes_from_the_solver_model() {
+        let spec = FixedPointMulDivSpec {
+            function_name: "mul_div_floor".to_string(),
+            multiplicand_max: u128::MAX,
+            multiplier_max: 2,
+            divisor_min: 1,
+            divisor_max: 1,
+            result_max: None,
+            product_max: None,
+        };
+        let solver = scripted(
+            "sat\n((multiplicand (_ bv340282366920938463463374607431768211455 256)) \
+             (multiplier (_ bv2 256)) (divisor (_ bv1 256)) \
+             (product (_ bv680564733841876926926749214863536422910 256)) \
+             (quotient (_ bv680564733841876926926749214863536422910 256)))\n",
+        );
+        let report = solver.prove_fixed_point_mul_div_bounds(&spec).unwrap();
+        assert!(!report.proven_safe);
+        assert_eq!(report.backend, SmtBackend::External);
+        assert!(report.message.starts_with("sh found a counterexample"));
+        let witness = report.counterexample.unwrap();
+        assert_eq!(witness.multiplier, "2");
+        assert_eq!(
+            witness.intermediate_product,
+            "680564733841876926926749214863536422910"
+        );
+
+        let report = scripted("unsat\n")
+            .prove_fixed_point_mul_div_bounds(&spec)
+            .unwrap();
+        assert!(report.proven_safe);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn invariant_findings_name_the_panicking_operation() {
+        let source = r#"
+            impl Vault {
+                #[invariant = "DataKey::Total + amount >= amount"]
+                pub fn deposit(amount: i128) {}
+            }
+        "#;
+        let solver = scripted(&format!(
+            "sat\n((|DataKey::Total| (_ bv{} 128)) (amount (_ bv1 128)) ($safe0 false))\n",
+            i128::MAX
+        ));
+        let findings = solver.verify_invariants(source).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location, "deposit");
+        assert_eq!(findings[0].witness.len(), 2);
+        assert_eq!(
+            findings[0].counterexample.as_deref(),
+            Some(
+                "DataKey::Total = 170141183460469231731687303715884105727, amount = 1 \
+                 — `DataKey::Total + amount` overflows i128"
+            )
+        );
+
+        assert!(scripted("unsat\n")
+            .verify_invariants(source)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_solvers_are_killed_at_the_timeout() {
+        let solver = SmtLibSolver::new("sleep")
+            .with_args(["5"])
+            .with_timeout(Duration::from_millis(50));
+        let started = Instant::now();
+        let error = solver.check(&addition_overflow_query()).unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            error,
+            SmtLibError::Timeout {
+                program: "sleep".to_string(),
+                timeout_ms: 50
+            }
+        );
+
+        let source = r#"
+            impl Vault {
+                #[invariant = "amount > 0"]
+                pub fn deposit(amount: u64) {}
+            }
+        "#;
+        let findings = solver.verify_invariants(source).unwrap();
+        assert!(findings[0].is_timeout);
+    }
+}
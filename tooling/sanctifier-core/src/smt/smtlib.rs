@@ -0,0 +1,694 @@
+//! SMT-LIB2 emission for the proofs of the `smt` module.
+//!
+//! Every proof — fixed-point `a * b / d` bounds, `u64` addition overflow and
+//! `#[invariant = "..."]` annotations — can be rendered as a self-contained
+//! [`SmtLibQuery`] in the quantifier-free bit-vector logic `QF_BV`, which Z3,
+//! cvc5 and Bitwuzla all accept.  Like the in-process Z3 encodings, each
+//! query asserts the *violation*: `sat` means the property fails and the
+//! requested values form a counterexample, `unsat` means it holds.
+//!
+//! Queries can be written to `.smt2` files for offline review or run with
+//! [`super::SmtLibSolver`]; neither links libz3.  Checked arithmetic is
+//! encoded with plain SMT-LIB 2.6 operators (widen, compute, check the result
+//! fits) rather than the newer overflow predicates, so older solvers work too.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::annotations::parse_invariants;
+use super::backend::validate_fixed_point_spec;
+use super::expr::{
+    parse_invariant_expr, ArithOp, BoolExpr, CmpOp, IntExpr, IntExprKind, IntType, InvariantError,
+    InvariantType,
+};
+use super::types::{FixedPointMulDivSpec, FixedPointProofError, InvariantSpec};
+
+/// Width of the fixed-point encoding: wide enough that `u128 * u128` cannot
+/// wrap, so the intermediate product is exact.
+const FIXED_POINT_BITS: u32 = 256;
+
+// ── Queries ───────────────────────────────────────────────────────────────────
+
+/// How a value requested with `(get-value ...)` is read back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtLibSort {
+    Bool,
+    /// A bit-vector read as a signed or unsigned integer.
+    BitVec {
+        bits: u32,
+        signed: bool,
+    },
+}
+
+impl SmtLibSort {
+    fn of(ty: InvariantType) -> Self {
+        match ty {
+            InvariantType::Bool => Self::Bool,
+            InvariantType::Int(int) => Self::BitVec {
+                bits: int.bits(),
+                signed: int.is_signed(),
+            },
+        }
+    }
+
+    fn render(self) -> String {
+        match self {
+            Self::Bool => "Bool".to_string(),
+            Self::BitVec { bits, .. } => format!("(_ BitVec {bits})"),
+        }
+    }
+}
+
+/// A self-contained SMT-LIB2 script asserting that a property is violated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtLibQuery {
+    /// File-name-safe identifier, e.g. `deposit_invariant_0`.
+    pub name: String,
+    /// The script, ending in `(check-sat)`, `(get-value ...)` and `(exit)`.
+    pub script: String,
+    /// Symbols requested with `(get-value ...)`, in request order.
+    pub values: Vec<(String, SmtLibSort)>,
+    /// Symbols among [`Self::values`] that are `false` exactly when an
+    /// operation panics, with a description of the panic, in evaluation
+    /// order.
+    pub guards: Vec<(String, String)>,
+}
+
+impl SmtLibQuery {
+    /// Write the script to `<dir>/<name>.smt2`, creating `dir` if needed.
+    pub fn write_to(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.smt2", self.name));
+        fs::write(&path, &self.script)?;
+        Ok(path)
+    }
+}
+
+// ── Public API ────────────────────────────────────────────────────────────────
+
+/// The query for `a * b` exceeding `spec.product_max` (`u128::MAX` by
+/// default), or `a * b / d` exceeding `spec.result_max`, within the bounds
+/// of `spec`.
+pub fn fixed_point_query(spec: &FixedPointMulDivSpec) -> Result<SmtLibQuery, FixedPointProofError> {
+    validate_fixed_point_spec(spec)?;
+
+    let wide = SmtLibSort::BitVec {
+        bits: FIXED_POINT_BITS,
+        signed: false,
+    };
+    let bound = |value: u128| literal(value, FIXED_POINT_BITS);
+
+    let mut script = Script::new(&format!(
+        "fixed-point mul/div bounds of `{}`",
+        spec.function_name
+    ));
+    for name in ["multiplicand", "multiplier", "divisor"] {
+        script.declare(name, wide);
+    }
+    script.assert(format!(
+        "(bvule multiplicand {})",
+        bound(spec.multiplicand_max)
+    ));
+    script.assert(format!("(bvule multiplier {})", bound(spec.multiplier_max)));
+    script.assert(format!("(bvuge divisor {})", bound(spec.divisor_min)));
+    script.assert(format!("(bvule divisor {})", bound(spec.divisor_max)));
+    script.define("product", wide, "(bvmul multiplicand multiplier)", true);
+    script.define("quotient", wide, "(bvudiv product divisor)", true);
+
+    let product_max = spec.product_max.unwrap_or(u128::MAX);
+    let product_overflow = format!("(bvugt product {})", bound(product_max));
+    match spec.result_max {
+        Some(result_max) => script.assert(format!(
+            "(or {product_overflow} (bvugt quotient {}))",
+            bound(result_max)
+        )),
+        None => script.assert(product_overflow),
+    }
+
+    Ok(script.finish(
+        format!("{}_mul_div", file_stem(&spec.function_name)),
+        Vec::new(),
+    ))
+}
+
+/// The query for `a + b` overflowing `u64` under unconstrained inputs.
+pub fn addition_overflow_query() -> SmtLibQuery {
+    let u64_sort = SmtLibSort::BitVec {
+        bits: 64,
+        signed: false,
+    };
+    let mut script = Script::new("u64 addition overflow");
+    script.declare("a", u64_sort);
+    script.declare("b", u64_sort);
+    let sum = format!(
+        "(bvadd {} {})",
+        extend("a", 1, false),
+        extend("b", 1, false)
+    );
+    script.assert(format!("(= ((_ extract 64 64) {sum}) #b1)"));
+    script.finish("addition_overflow".to_string(), Vec::new())
+}
+
+/// The query for one invariant evaluating to `false` or panicking.
+///
+/// Fails with the same [`InvariantError`] as [`super::parse_invariant_expr`]
+/// when the expression is outside the invariant language.
+pub fn invariant_query(spec: &InvariantSpec) -> Result<SmtLibQuery, InvariantError> {
+    let parsed = parse_invariant_expr(&spec.expression, &spec.params)?;
+
+    let mut script = Script::new(&format!(
+        "invariant `{}` on `{}`",
+        spec.expression, spec.location
+    ));
+    for (name, (_, ty)) in &parsed.variables {
+        script.declare(name, SmtLibSort::of(*ty));
+    }
+
+    let mut emitter = Emitter {
+        script,
+        guards: Vec::new(),
+    };
+    let holds = emitter.boolean(&parsed.expr, "true");
+    let Emitter { mut script, guards } = emitter;
+    script.define("$holds", SmtLibSort::Bool, &holds, false);
+
+    let conditions = std::iter::once("$holds")
+        .chain(guards.iter().map(|(name, _)| name.as_str()))
+        .collect::<Vec<_>>();
+    script.assert(format!("(not {})", conjunction(&conditions)));
+
+    Ok(script.finish(format!("{}_invariant", file_stem(&spec.location)), guards))
+}
+
+/// One query per `#[invariant = "..."]` in `source` that is inside the
+/// invariant language, numbered in source order so the names are unique.
+pub fn invariant_queries(source: &str) -> Vec<SmtLibQuery> {
+    parse_invariants(source)
+        .iter()
+        .enumerate()
+        .filter_map(|(index, spec)| {
+            let mut query = invariant_query(spec).ok()?;
+            query.name = format!("{}_{index}", query.name);
+            Some(query)
+        })
+        .collect()
+}
+
+// ── Internal: script assembly ─────────────────────────────────────────────────
+
+/// Accumulates declarations and assertions, then renders the script.
+struct Script {
+    header: String,
+    body: String,
+    values: Vec<(String, SmtLibSort)>,
+}
+
+impl Script {
+    fn new(description: &str) -> Self {
+        Self {
+            header: format!("; {}\n", description.replace('\n', " ")),
+            body: String::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Declare an unconstrained constant and request its value.
+    fn declare(&mut self, name: &str, sort: SmtLibSort) {
+        let _ = writeln!(
+            self.body,
+            "(declare-const {} {})",
+            symbol(name),
+            sort.render()
+        );
+        self.values.push((name.to_string(), sort));
+    }
+
+    /// Name `term`, optionally requesting its value.
+    fn define(&mut self, name: &str, sort: SmtLibSort, term: &str, requested: bool) {
+        let _ = writeln!(
+            self.body,
+            "(define-fun {} () {} {term})",
+            symbol(name),
+            sort.render()
+        );
+        if requested {
+            self.values.push((name.to_string(), sort));
+        }
+    }
+
+    fn assert(&mut self, term: String) {
+        let _ = writeln!(self.body, "(assert {term})");
+    }
+
+    fn finish(self, name: String, guards: Vec<(String, String)>) -> SmtLibQuery {
+        let mut script = self.header;
+        script.push_str("(set-option :produce-models true)\n(set-logic QF_BV)\n");
+        script.push_str(&self.body);
+        script.push_str("(check-sat)\n");
+        if !self.values.is_empty() {
+            let symbols = self
+                .values
+                .iter()
+                .map(|(name, _)| symbol(name))
+                .collect::<Vec<_>>();
+            let _ = writeln!(script, "(get-value ({}))", symbols.join(" "));
+        }
+        script.push_str("(exit)\n");
+        SmtLibQuery {
+            name,
+            script,
+            values: self.values,
+            guards,
+        }
+    }
+}
+
+// ── Internal: invariant encoding ──────────────────────────────────────────────
+
+/// Translates a [`super::ParsedInvariant`] into SMT-LIB terms, mirroring the
+/// Z3 encoder: every arithmetic operation defines a guard that holds unless
+/// the operation is evaluated and panics.
+struct Emitter {
+    script: Script,
+    guards: Vec<(String, String)>,
+}
+
+impl Emitter {
+    /// Encode `expr`, evaluated only when `reached` holds.
+    fn boolean(&mut self, expr: &BoolExpr, reached: &str) -> String {
+        match expr {
+            BoolExpr::Const(b) => b.to_string(),
+            BoolExpr::Var(name) => symbol(name),
+            BoolExpr::Not(inner) => format!("(not {})", self.boolean(inner, reached)),
+            BoolExpr::And(lhs, rhs) => {
+                let lhs = self.boolean(lhs, reached);
+                let rhs = self.boolean(rhs, &conjunction(&[reached, &lhs]));
+                format!("(and {lhs} {rhs})")
+            }
+            BoolExpr::Or(lhs, rhs) => {
+                let lhs = self.boolean(lhs, reached);
+                let rhs = self.boolean(rhs, &conjunction(&[reached, &format!("(not {lhs})")]));
+                format!("(or {lhs} {rhs})")
+            }
+            BoolExpr::Iff { lhs, rhs, negated } => {
+                let lhs = self.boolean(lhs, reached);
+                let rhs = self.boolean(rhs, reached);
+                let op = if *negated { "distinct" } else { "=" };
+                format!("({op} {lhs} {rhs})")
+            }
+            BoolExpr::Compare { op, lhs, rhs } => {
+                let signed = lhs.ty.is_signed();
+                let l = self.integer(lhs, reached);
+                let r = self.integer(rhs, reached);
+                let op = match (op, signed) {
+                    (CmpOp::Eq, _) => "=",
+                    (CmpOp::Ne, _) => "distinct",
+                    (CmpOp::Lt, false) => "bvult",
+                    (CmpOp::Lt, true) => "bvslt",
+                    (CmpOp::Le, false) => "bvule",
+                    (CmpOp::Le, true) => "bvsle",
+                    (CmpOp::Gt, false) => "bvugt",
+                    (CmpOp::Gt, true) => "bvsgt",
+                    (CmpOp::Ge, false) => "bvuge",
+                    (CmpOp::Ge, true) => "bvsge",
+                };
+                format!("({op} {l} {r})")
+            }
+        }
+    }
+
+    fn integer(&mut self, expr: &IntExpr, reached: &str) -> String {
+        let ty = expr.ty;
+        match &expr.kind {
+            IntExprKind::Const(bits) => literal(*bits, ty.bits()),
+            IntExprKind::Var { name, .. } => symbol(name),
+            IntExprKind::Neg(inner) => {
+                let v = self.integer(inner, reached);
+                let min = literal(ty.min_bits(), ty.bits());
+                self.guard(
+                    reached,
+                    format!("(distinct {v} {min})"),
+                    format!("`{expr}` overflows {}", ty.name()),
+                );
+                format!("(bvneg {v})")
+            }
+            IntExprKind::Cast(inner) => {
+                let v = self.integer(inner, reached);
+                cast(&v, inner.ty, ty)
+            }
+            IntExprKind::Arith { op, lhs, rhs } => {
+                let l = self.integer(lhs, reached);
+                let r = self.integer(rhs, reached);
+                let (value, guards) = checked_arith(*op, &l, &r, ty);
+                for (safe, panic) in guards {
+                    let description = if panic == DIVIDES_BY_ZERO {
+                        format!("`{expr}` {panic}")
+                    } else {
+                        format!("`{expr}` {panic} {}", ty.name())
+                    };
+                    self.guard(reached, safe, description);
+                }
+                value
+            }
+        }
+    }
+
+    fn guard(&mut self, reached: &str, safe: String, description: String) {
+        let name = format!("$safe{}", self.guards.len());
+        let term = if reached == "true" {
+            safe
+        } else {
+            format!("(=> {reached} {safe})")
+        };
+        self.script.define(&name, SmtLibSort::Bool, &term, true);
+        self.guards.push((name, description));
+    }
+}
+
+/// How [`checked_arith`] describes a division by zero.
+const DIVIDES_BY_ZERO: &str = "divides by zero";
+
+/// `l <op> r` in `ty`, together with each condition under which Rust's
+/// checked arithmetic does not panic and how it panics otherwise.
+fn checked_arith(
+    op: ArithOp,
+    l: &str,
+    r: &str,
+    ty: IntType,
+) -> (String, Vec<(String, &'static str)>) {
+    let bits = ty.bits();
+    let signed = ty.is_signed();
+    // Compute in a wider bit-vector and check the result survives truncation.
+    let widened = |bv_op: &str, extra: u32| {
+        let wide = format!(
+            "({bv_op} {} {})",
+            extend(l, extra, signed),
+            extend(r, extra, signed)
+        );
+        let truncated = format!("((_ extract {} 0) {wide})", bits - 1);
+        format!("(= {wide} {})", extend(&truncated, extra, signed))
+    };
+    match op {
+        ArithOp::Add => (
+            format!("(bvadd {l} {r})"),
+            vec![(widened("bvadd", 1), "overflows")],
+        ),
+        ArithOp::Sub if signed => (
+            format!("(bvsub {l} {r})"),
+            vec![(widened("bvsub", 1), "overflows")],
+        ),
+        ArithOp::Sub => (
+            format!("(bvsub {l} {r})"),
+            vec![(format!("(bvuge {l} {r})"), "underflows")],
+        ),
+        ArithOp::Mul => (
+            format!("(bvmul {l} {r})"),
+            vec![(widened("bvmul", bits), "overflows")],
+        ),
+        ArithOp::Div | ArithOp::Rem => {
+            let mut guards = vec![(
+                format!("(distinct {r} {})", literal(0, bits)),
+                DIVIDES_BY_ZERO,
+            )];
+            if signed {
+                let min = literal(ty.min_bits(), bits);
+                let minus_one = literal(ty.mask(), bits);
+                guards.push((
+                    format!("(not (and (= {l} {min}) (= {r} {minus_one})))"),
+                    "overflows",
+                ));
+            }
+            let bv_op = match (op, signed) {
+                (ArithOp::Div, false) => "bvudiv",
+                (ArithOp::Div, true) => "bvsdiv",
+                (_, false) => "bvurem",
+                (_, true) => "bvsrem",
+            };
+            (format!("({bv_op} {l} {r})"), guards)
+        }
+    }
+}
+
+/// `v as to` for a value of type `from`.
+fn cast(v: &str, from: IntType, to: IntType) -> String {
+    if to.bits() > from.bits() {
+        extend(v, to.bits() - from.bits(), from.is_signed())
+    } else if to.bits() < from.bits() {
+        format!("((_ extract {} 0) {v})", to.bits() - 1)
+    } else {
+        v.to_string()
+    }
+}
+
+// ── Internal: term helpers ────────────────────────────────────────────────────
+
+/// `v` widened by `extra` bits.
+fn extend(v: &str, extra: u32, signed: bool) -> String {
+    let op = if signed { "sign_extend" } else { "zero_extend" };
+    format!("((_ {op} {extra}) {v})")
+}
+
+/// A bit-vector literal of the given width.
+fn literal(value: u128, bits: u32) -> String {
+    format!("(_ bv{value} {bits})")
+}
+
+/// `(and ...)` of `terms`, dropping `true` and avoiding unary `and`.
+fn conjunction(terms: &[&str]) -> String {
+    let terms = terms
+        .iter()
+        .filter(|term| **term != "true")
+        .copied()
+        .collect::<Vec<_>>();
+    match terms.as_slice() {
+        [] => "true".to_string(),
+        [term] => term.to_string(),
+        _ => format!("(and {})", terms.join(" ")),
+    }
+}
+
+/// `name` as an SMT-LIB symbol, quoted when it is not a simple symbol (e.g.
+/// the storage key `DataKey::TotalSupply`).
+fn symbol(name: &str) -> String {
+    let simple = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+    if simple {
+        name.to_string()
+    } else {
+        format!("|{name}|")
+    }
+}
+
+/// `name` with everything but ASCII letters, digits and `_` replaced.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(expression: &str, params: &[(&str, &str)]) -> InvariantSpec {
+        InvariantSpec {
+            expression: expression.to_string(),
+            location: "deposit".to_string(),
+            params: params
+                .iter()
+                .map(|(name, ty)| (name.to_string(), ty.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn invariant_query_declares_variables_and_guards_arithmetic() {
+        let query = invariant_query(&spec(
+            "amount + fee <= DataKey::Cap as u64",
+            &[("amount", "u64"), ("fee", "u64")],
+        ))
+        .unwrap();
+
+        assert_eq!(query.name, "deposit_invariant");
+        assert!(query.script.starts_with(
+            "; invariant `amount + fee <= DataKey::Cap as u64` on `deposit`\n\
+             (set-option :produce-models true)\n(set-logic QF_BV)\n"
+        ));
+        assert!(query
+            .script
+            .contains("(declare-const |DataKey::Cap| (_ BitVec 64))"));
+        assert!(query
+            .script
+            .contains("(declare-const amount (_ BitVec 64))"));
+        assert!(query.script.contains("(assert (not (and $holds $safe0)))"));
+        assert!(query
+            .script
+            .ends_with("(check-sat)\n(get-value (|DataKey::Cap| amount fee $safe0))\n(exit)\n"));
+        assert_eq!(
+            query.guards,
+            vec![(
+                "$safe0".to_string(),
+                "`amount + fee` overflows u64".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn invariant_query_conditions_guards_on_short_circuiting() {
+        let query = invariant_query(&spec(
+            "divisor != 0 && total / divisor > 1",
+            &[("divisor", "i32"), ("total", "i32")],
+        ))
+        .unwrap();
+
+        assert!(query.script.contains(
+            "(define-fun $safe0 () Bool (=> (distinct divisor (_ bv0 32)) \
+             (distinct divisor (_ bv0 32))))"
+        ));
+        let descriptions = query
+            .guards
+            .iter()
+            .map(|(_, description)| description.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            descriptions,
+            [
+                "`total / divisor` divides by zero",
+                "`total / divisor` overflows i32"
+            ]
+        );
+    }
+
+    #[test]
+    fn invariant_query_rejects_expressions_outside_the_language() {
+        let error = invariant_query(&spec("self.balance >= 0", &[])).unwrap_err();
+        assert!(matches!(
+            error,
+            InvariantError::Unsupported(_) | InvariantError::Expected { .. }
+        ));
+    }
+
+    #[test]
+    fn invariant_queries_number_every_supported_annotation() {
+        let source = r#"
+            impl Token {
+                #[invariant = "amount > 0"]
+                #[invariant = "self.ok()"]
+                #[invariant = "amount < 100"]
+                pub fn mint(amount: u64) {}
+            }
+        "#;
+        let names = invariant_queries(source)
+            .into_iter()
+            .map(|query| query.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["mint_invariant_0", "mint_invariant_2"]);
+    }
+
+    #[test]
+    fn fixed_point_query_widens_the_product_and_bounds_the_quotient() {
+        let query = fixed_point_query(&FixedPointMulDivSpec {
+            function_name: "mul_div::floor".to_string(),
+            multiplicand_max: 10,
+            multiplier_max: 20,
+            divisor_min: 1,
+            divisor_max: 5,
+            result_max: Some(7),
+            product_max: None,
+        })
+        .unwrap();
+
+        assert_eq!(query.name, "mul_div__floor_mul_div");
+        assert!(query
+            .script
+            .contains("(define-fun product () (_ BitVec 256) (bvmul multiplicand multiplier))"));
+        assert!(query.script.contains(&format!(
+            "(assert (or (bvugt product (_ bv{} 256)) (bvugt quotient (_ bv7 256))))",
+            u128::MAX
+        )));
+        assert!(query
+            .script
+            .contains("(get-value (multiplicand multiplier divisor product quotient))"));
+    }
+
+    #[test]
+    fn fixed_point_query_validates_the_spec() {
+        let error = fixed_point_query(&FixedPointMulDivSpec {
+            function_name: "invalid".to_string(),
+            multiplicand_max: 10,
+            multiplier_max: 10,
+            divisor_min: 0,
+            divisor_max: 10,
+            result_max: None,
+            product_max: None,
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            FixedPointProofError::InvalidSpec("divisor_min must be greater than zero")
+        );
+    }
+
+    #[test]
+    fn write_to_creates_an_smt2_file() {
+        let dir = std::env::temp_dir().join(format!("sanctifier-smtlib-{}", std::process::id()));
+        let query = addition_overflow_query();
+        let path = query.write_to(&dir).unwrap();
+        assert_eq!(path, dir.join("addition_overflow.smt2"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), query.script);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Every emitted query must mean the same thing as the in-process Z3
+    /// encoding: load it into Z3 and compare the verdicts.
+    #[cfg(feature = "smt")]
+    #[test]
+    fn emitted_invariant_queries_agree_with_the_z3_verifier() {
+        use crate::smt::{verify_invariants, SmtConfig};
+        use z3::{Config, Context, SatResult, Solver};
+
+        let source = r#"
+            impl Vault {
+                #[invariant = "amount + fee <= 1_000u64 || amount > 1_000"]
+                pub fn a(amount: u64, fee: u64) {}
+                #[invariant = "divisor == 0 || total / divisor <= total"]
+                pub fn b(divisor: u32, total: u32) {}
+                #[invariant = "x - y < x"]
+                pub fn c(x: i64, y: i64) {}
+                #[invariant = "(small as i128) * 2 >= DataKey::Floor as i128 || DataKey::Floor > 5"]
+                pub fn d(small: i32) {}
+                #[invariant = "-n != n || n == 0"]
+                pub fn e(n: i128) {}
+            }
+        "#;
+        let violated = verify_invariants(source, &SmtConfig::default())
+            .into_iter()
+            .map(|finding| finding.location)
+            .collect::<Vec<_>>();
+
+        assert!(!violated.contains(&"b".to_string()));
+
+        let queries = invariant_queries(source);
+        assert_eq!(queries.len(), 5);
+        for query in queries {
+            let ctx = Context::new(&Config::new());
+            let solver = Solver::new(&ctx);
+            solver.from_string(query.script.as_str());
+            let location = query.name.split('_').next().unwrap().to_string();
+            assert_eq!(
+                solver.check() == SatResult::Sat,
+                violated.contains(&location),
+                "verdicts differ for {}:\n{}",
+                query.name,
+                query.script
+            );
+        }
+    }
+}
@@ -0,0 +1,1723 @@
+//! Symbolic execution of contract functions against `#[invariant]`s (S011).
+//!
+//! [`verify_invariant_preservation`] proves that an invariant which holds
+//! before a call still holds after it.  An invariant on an `impl` block is a
+//! contract invariant and applies to every public function in the block; an
+//! invariant on a single function applies to that function when it mentions
+//! storage:
+//!
+//! ```ignore
+//! #[contractimpl]
+//! #[invariant = "DataKey::TotalSupply == sum(DataKey::Balance)"]
+//! impl Token { /* mint, burn, transfer, ... */ }
+//! ```
+//!
+//! Each function body is lowered to a [`Cfg`] and executed path by path:
+//!
+//! * integer parameters become bit-vectors of their Rust width, `Address`es
+//!   values of an uninterpreted sort;
+//! * branch and `assert!` conditions are collected into a path condition;
+//! * arithmetic follows checked Rust semantics.  A path that overflows,
+//!   panics, fails an `assert!`, unwraps a missing value or returns an `Err`
+//!   rolls the invocation back and is not checked;
+//! * storage is an uninterpreted map per key: `DataKey::Balance(id)` reads and
+//!   writes an array indexed by `id`, and `sum(DataKey::Balance)` is a ghost
+//!   total kept up to date on every write;
+//! * calls to other functions of the same file are inlined.
+//!
+//! A committed path violates the invariant when the invariant can hold before
+//! the call and fail after it.  The finding
+//! ([`SmtFindingKind::InvariantNotPreserved`]) carries the statements and
+//! branch decisions of the path together with concrete inputs and storage
+//! values.
+//!
+//! The search is bounded: each block runs at most twice per path (loops are
+//! unrolled once), at most [`MAX_PATHS`] paths are checked per function, and
+//! calls nested deeper than [`MAX_CALL_DEPTH`] forget everything known about
+//! storage.  Storage tiers (instance, persistent, temporary) share one
+//! namespace, and calls to functions outside the file are assumed to leave
+//! the contract's storage alone.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use syn::{BinOp, Expr, File, FnArg, ImplItem, Item, Lit, Pat, Signature, Type, UnOp};
+use z3::ast::{Array, Ast, Bool, Dynamic, BV};
+use z3::{Config, Context, FuncDecl, Model, SatResult, Solver, Sort, Symbol};
+
+use super::annotations::{invariant_attrs, typed_params};
+use super::backend::SmtVerifier;
+use super::expr::{
+    parse_invariant_expr, render, type_bound, ArithOp, IntType, InvariantType, ParsedInvariant,
+    VarSource,
+};
+use super::invariants::{cast_bv, checked_arith, Encoder, Var};
+use super::types::{SmtConfig, SmtFinding, SmtFindingKind};
+use crate::cfg::{assert_condition, BlockStmt, Cfg};
+use crate::interprocedural::{qualified_name, resolve_call_path};
+use crate::rules::impl_type_name;
+use crate::taint_engine::collect_pat_idents;
+
+/// Times a block may run on one path.
+const MAX_BLOCK_VISITS: usize = 2;
+/// Paths checked per function.
+const MAX_PATHS: usize = 256;
+/// Nested same-file calls that are inlined.
+const MAX_CALL_DEPTH: usize = 4;
+/// Blocks executed per function, over every path and inlined call.
+const MAX_STEPS: usize = 20_000;
+
+// ── Public API ────────────────────────────────────────────────────────────────
+
+/// Check that every contract function preserves the invariants that apply to
+/// it (see the [module docs](self)).
+///
+/// Returns one [`SmtFinding`] per function and invariant for which a path
+/// breaking the invariant was found, or for which the solver timed out.
+pub fn verify_invariant_preservation(source: &str, config: &SmtConfig) -> Vec<SmtFinding> {
+    let file = match syn::parse_str::<File>(source) {
+        Ok(f) => f,
+        Err(_) => return vec![],
+    };
+
+    let mut cfg = Config::new();
+    cfg.set_param_value("timeout", &config.timeout_ms.to_string());
+    let ctx = Context::new(&cfg);
+    SmtVerifier::new(&ctx).verify_invariant_preservation(&file)
+}
+
+impl<'ctx> SmtVerifier<'ctx> {
+    /// Symbolically execute the public functions of every `impl` block in
+    /// `file` against the invariants that apply to them.
+    pub fn verify_invariant_preservation(&self, file: &File) -> Vec<SmtFinding> {
+        let fns = index_functions(file);
+        let mut findings = Vec::new();
+
+        for item in &file.items {
+            let Item::Impl(impl_block) = item else {
+                continue;
+            };
+            let owner = impl_type_name(impl_block);
+            let contract_wide = invariant_attrs(&impl_block.attrs);
+
+            for impl_item in &impl_block.items {
+                let ImplItem::Fn(f) = impl_item else {
+                    continue;
+                };
+                if !matches!(f.vis, syn::Visibility::Public(_)) {
+                    continue;
+                }
+
+                let params = typed_params(&f.sig);
+                let wide = contract_wide.iter().filter_map(|expression| {
+                    let parsed = parse_invariant_expr(expression, &[]).ok()?;
+                    Some((expression.clone(), parsed))
+                });
+                let own = invariant_attrs(&f.attrs)
+                    .into_iter()
+                    .filter_map(|expression| {
+                        let parsed = parse_invariant_expr(&expression, &params).ok()?;
+                        let mentions_storage = parsed
+                            .variables
+                            .values()
+                            .any(|(source, _)| *source != VarSource::Param);
+                        mentions_storage.then_some((expression, parsed))
+                    });
+
+                let function = Function {
+                    owner: owner.clone(),
+                    sig: &f.sig,
+                    block: &f.block,
+                };
+                for (expression, parsed) in wide.chain(own) {
+                    findings.extend(check_preserved(
+                        self.ctx,
+                        &fns,
+                        &function,
+                        &expression,
+                        &parsed,
+                    ));
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+// ── Internal ──────────────────────────────────────────────────────────────────
+
+/// A function body that can be executed or inlined.
+struct Function<'f> {
+    owner: Option<String>,
+    sig: &'f Signature,
+    block: &'f syn::Block,
+}
+
+/// Every function of the file by [`qualified_name`].
+fn index_functions(file: &File) -> HashMap<String, Function<'_>> {
+    let mut fns = HashMap::new();
+    for item in &file.items {
+        match item {
+            Item::Fn(f) => {
+                fns.entry(f.sig.ident.to_string()).or_insert(Function {
+                    owner: None,
+                    sig: &f.sig,
+                    block: &f.block,
+                });
+            }
+            Item::Impl(impl_block) => {
+                let owner = impl_type_name(impl_block);
+                for impl_item in &impl_block.items {
+                    if let ImplItem::Fn(f) = impl_item {
+                        let name = qualified_name(owner.as_deref(), &f.sig.ident.to_string());
+                        fns.entry(name).or_insert(Function {
+                            owner: owner.clone(),
+                            sig: &f.sig,
+                            block: &f.block,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    fns
+}
+
+/// Look for a committed path of `function` that takes a state satisfying
+/// `parsed` to one that does not.
+fn check_preserved(
+    ctx: &Context,
+    fns: &HashMap<String, Function<'_>>,
+    function: &Function<'_>,
+    expression: &str,
+    parsed: &ParsedInvariant,
+) -> Option<SmtFinding> {
+    let mut exec = Executor::new(ctx, fns, parsed, function.owner.clone());
+    let (mut entry, inputs) = exec.entry_state(function.sig);
+
+    let mut before = Encoder::with_vars(ctx, exec.invariant_vars(&mut entry, parsed, &inputs));
+    let held = before.encode(&parsed.expr);
+    let solver = Solver::new(ctx);
+    solver.assert(&held);
+    solver.assert(&before.safe());
+
+    let name = function.sig.ident.to_string();
+    for mut exit in exec.run(&Cfg::build(function.block), entry) {
+        let mut after = Encoder::with_vars(ctx, exec.invariant_vars(&mut exit, parsed, &inputs));
+        let holds = after.encode(&parsed.expr);
+
+        solver.push();
+        for condition in &exit.path {
+            solver.assert(condition);
+        }
+        solver.assert(&Bool::and(ctx, &[&holds, &after.safe()]).not());
+        let result = solver.check();
+        let model = match result {
+            SatResult::Sat => solver.get_model(),
+            _ => None,
+        };
+        solver.pop(1);
+
+        match result {
+            SatResult::Unsat => continue,
+            SatResult::Unknown => {
+                return Some(SmtFinding {
+                    kind: SmtFindingKind::InvariantNotPreserved,
+                    invariant_name: expression.to_string(),
+                    location: name,
+                    counterexample: None,
+                    witness: BTreeMap::new(),
+                    path: exit.trace,
+                    is_timeout: true,
+                })
+            }
+            SatResult::Sat => {}
+        }
+
+        let mut witness = BTreeMap::new();
+        let mut counterexample = None;
+        if let Some(model) = model {
+            for (param, value) in &inputs {
+                if let Some(value) = value_in(value, &model) {
+                    witness.insert(param.clone(), value);
+                }
+            }
+            let call = format!("{name}({})", assignment(&witness));
+            let is_storage = |var: &String| parsed.variables[var].0 != VarSource::Param;
+            let storage_before: BTreeMap<_, _> = before
+                .witness(&model)
+                .into_iter()
+                .filter(|(var, _)| is_storage(var))
+                .collect();
+            let storage_after: BTreeMap<_, _> = after
+                .witness(&model)
+                .into_iter()
+                .filter(|(var, _)| is_storage(var))
+                .collect();
+            counterexample = Some(format!(
+                "{call} takes {} to {} — {}",
+                assignment(&storage_before),
+                assignment(&storage_after),
+                after.reason(&model)
+            ));
+            for (var, value) in storage_before {
+                witness.insert(format!("{var} (before)"), value);
+            }
+            for (var, value) in storage_after {
+                witness.insert(format!("{var} (after)"), value);
+            }
+        }
+
+        return Some(SmtFinding {
+            kind: SmtFindingKind::InvariantNotPreserved,
+            invariant_name: expression.to_string(),
+            location: name,
+            counterexample,
+            witness,
+            path: exit.trace,
+            is_timeout: false,
+        });
+    }
+
+    None
+}
+
+fn assignment(values: &BTreeMap<String, String>) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("{name} = {value}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn value_in<'ctx>(value: &Value<'ctx>, model: &Model<'ctx>) -> Option<String> {
+    match value {
+        Value::Int(bv, ty) => Some(Var::Int(bv.clone(), *ty).value_in(model)),
+        Value::Bool(b) => Some(Var::Bool(b.clone()).value_in(model)),
+        // Uninterpreted values print as `Address!val!0`.
+        Value::Addr(a) => model
+            .eval(a, true)
+            .map(|v| v.to_string().replace("!val!", "#")),
+        _ => None,
+    }
+}
+
+/// The types the executor models precisely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Int(IntType),
+    Bool,
+    Address,
+}
+
+fn kind_of(ty: &Type) -> Option<Kind> {
+    match ty {
+        Type::Reference(r) => kind_of(&r.elem),
+        Type::Path(p) => match p.path.segments.last()?.ident.to_string().as_str() {
+            "bool" => Some(Kind::Bool),
+            "Address" => Some(Kind::Address),
+            name => IntType::from_name(name).map(Kind::Int),
+        },
+        _ => None,
+    }
+}
+
+/// A symbolic value.
+#[derive(Clone)]
+enum Value<'ctx> {
+    Int(BV<'ctx>, IntType),
+    /// An unsuffixed integer literal, typed by the operand it meets.
+    Lit(i128),
+    Bool(Bool<'ctx>),
+    Addr(Dynamic<'ctx>),
+    /// A storage key: an enum variant, with its argument if it has one.
+    Key {
+        family: String,
+        arg: Option<Box<Value<'ctx>>>,
+    },
+    /// An `Option` (or a storage read) that holds `value` when `present`.
+    Opt {
+        present: Bool<'ctx>,
+        value: Box<Value<'ctx>>,
+    },
+    Unit,
+    Unknown,
+}
+
+/// The entries of storage built from one key.
+#[derive(Clone)]
+enum Family<'ctx> {
+    /// A key without an argument, e.g. `DataKey::TotalSupply`.
+    Scalar {
+        ty: IntType,
+        value: BV<'ctx>,
+        present: Bool<'ctx>,
+    },
+    /// Keys built from one variant, e.g. `DataKey::Balance(id)`, and the
+    /// total of their values.
+    Map {
+        ty: IntType,
+        key: Kind,
+        values: Array<'ctx>,
+        present: Array<'ctx>,
+        sum: BV<'ctx>,
+    },
+}
+
+/// One path through a function.
+#[derive(Clone)]
+struct State<'ctx> {
+    locals: HashMap<String, Value<'ctx>>,
+    storage: BTreeMap<String, Family<'ctx>>,
+    /// Families an unmodelled write may have changed.
+    lost: BTreeSet<String>,
+    /// Set when an unmodelled write may have changed any family.
+    havocked: bool,
+    /// Branch decisions and the conditions under which nothing panicked.
+    path: Vec<Bool<'ctx>>,
+    /// Statements and branch decisions, for the finding.
+    trace: Vec<String>,
+    /// The value of the last statement: a branch condition, the scrutinee of
+    /// a `match` or the function's result.
+    last: Value<'ctx>,
+}
+
+struct Executor<'a, 'ctx> {
+    ctx: &'ctx Context,
+    fns: &'a HashMap<String, Function<'a>>,
+    /// Value type of each storage family the invariant mentions.
+    types: HashMap<String, IntType>,
+    address: Sort<'ctx>,
+    owner: Option<String>,
+    depth: usize,
+    steps: usize,
+    fresh: usize,
+}
+
+impl<'a, 'ctx> Executor<'a, 'ctx> {
+    fn new(
+        ctx: &'ctx Context,
+        fns: &'a HashMap<String, Function<'a>>,
+        parsed: &ParsedInvariant,
+        owner: Option<String>,
+    ) -> Self {
+        let mut types = HashMap::new();
+        for (name, (source, ty)) in &parsed.variables {
+            let InvariantType::Int(ty) = ty else {
+                continue;
+            };
+            match source {
+                VarSource::Storage => types.insert(name.clone(), *ty),
+                VarSource::StorageSum => types.insert(summed_family(name).to_string(), *ty),
+                VarSource::Param => None,
+            };
+        }
+        Self {
+            ctx,
+            fns,
+            types,
+            address: Sort::uninterpreted(ctx, Symbol::String("Address".to_string())),
+            owner,
+            depth: 0,
+            steps: 0,
+            fresh: 0,
+        }
+    }
+
+    /// The state on entry, with a named constant for every parameter the
+    /// executor models.
+    fn entry_state(&mut self, sig: &Signature) -> (State<'ctx>, BTreeMap<String, Value<'ctx>>) {
+        let mut locals = HashMap::new();
+        let mut inputs = BTreeMap::new();
+        for arg in &sig.inputs {
+            let FnArg::Typed(pt) = arg else {
+                continue;
+            };
+            let Pat::Ident(id) = &*pt.pat else {
+                continue;
+            };
+            let name = id.ident.to_string();
+            let value = match kind_of(&pt.ty) {
+                Some(Kind::Int(ty)) => {
+                    Value::Int(BV::new_const(self.ctx, name.as_str(), ty.bits()), ty)
+                }
+                Some(Kind::Bool) => Value::Bool(Bool::new_const(self.ctx, name.as_str())),
+                Some(Kind::Address) => Value::Addr(self.address_const(&name)),
+                None => {
+                    locals.insert(name, Value::Unknown);
+                    continue;
+                }
+            };
+            locals.insert(name.clone(), value.clone());
+            inputs.insert(name, value);
+        }
+        let state = State {
+            locals,
+            storage: BTreeMap::new(),
+            lost: BTreeSet::new(),
+            havocked: false,
+            path: Vec::new(),
+            trace: Vec::new(),
+            last: Value::Unit,
+        };
+        (state, inputs)
+    }
+
+    /// The terms the invariant's variables stand for in `state`.
+    fn invariant_vars(
+        &mut self,
+        state: &mut State<'ctx>,
+        parsed: &ParsedInvariant,
+        inputs: &BTreeMap<String, Value<'ctx>>,
+    ) -> BTreeMap<String, Var<'ctx>> {
+        let mut vars = BTreeMap::new();
+        for (name, (source, ty)) in &parsed.variables {
+            let known = match (source, ty, inputs.get(name)) {
+                (VarSource::Param, InvariantType::Int(ty), Some(Value::Int(bv, _))) => {
+                    Some(Var::Int(bv.clone(), *ty))
+                }
+                (VarSource::Param, InvariantType::Bool, Some(Value::Bool(b))) => {
+                    Some(Var::Bool(b.clone()))
+                }
+                (VarSource::Storage, InvariantType::Int(ty), _) => {
+                    match self.family(state, name, None, Some(*ty)) {
+                        Some(Family::Scalar { value, present, .. }) => {
+                            let zero = BV::from_u64(self.ctx, 0, ty.bits());
+                            Some(Var::Int(present.ite(value, &zero), *ty))
+                        }
+                        _ => None,
+                    }
+                }
+                (VarSource::StorageSum, InvariantType::Int(ty), _) => {
+                    let family = summed_family(name);
+                    match state.storage.get(family) {
+                        Some(Family::Map { sum, .. }) => Some(Var::Int(sum.clone(), *ty)),
+                        None if !is_lost(state, family) => Some(Var::Int(
+                            BV::new_const(self.ctx, name.as_str(), ty.bits()),
+                            *ty,
+                        )),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            let var = known.unwrap_or_else(|| match ty {
+                InvariantType::Bool => Var::Bool(Bool::fresh_const(self.ctx, name)),
+                InvariantType::Int(ty) => Var::Int(BV::fresh_const(self.ctx, name, ty.bits()), *ty),
+            });
+            vars.insert(name.clone(), var);
+        }
+        vars
+    }
+
+    // ── Paths ─────────────────────────────────────────────────────────────────
+
+    /// Every state in which `cfg` returns normally, starting from `state`.
+    fn run(&mut self, cfg: &Cfg, state: State<'ctx>) -> Vec<State<'ctx>> {
+        let mut exits = Vec::new();
+        let mut pending = vec![(cfg.entry, state, vec![0; cfg.block_count()])];
+
+        while let Some((block, state, mut visits)) = pending.pop() {
+            if exits.len() >= MAX_PATHS || self.steps >= MAX_STEPS {
+                break;
+            }
+            visits[block] += 1;
+            if visits[block] > MAX_BLOCK_VISITS {
+                continue;
+            }
+            self.steps += 1;
+
+            let stmts = &cfg.blocks[block].stmts;
+            let branches = cfg.successors[block]
+                .iter()
+                .any(|next| cfg.conditions.contains_key(&(block, *next)));
+            let mut states = vec![state];
+            for (i, stmt) in stmts.iter().enumerate() {
+                // A branch condition shows up in the trace as the decision.
+                let text = (!branches || i + 1 < stmts.len())
+                    .then(|| stmt.to_source().replace(" :: ", "::"));
+                states = states
+                    .into_iter()
+                    .flat_map(|mut s| {
+                        s.trace.extend(text.clone());
+                        self.step(s, stmt)
+                    })
+                    .collect();
+            }
+
+            for state in states {
+                for &next in &cfg.successors[block] {
+                    let mut branch = state.clone();
+                    if let Some(cond) = cfg.conditions.get(&(block, next)) {
+                        if let Value::Bool(b) = &state.last {
+                            branch
+                                .path
+                                .push(if cond.holds { b.clone() } else { b.not() });
+                        }
+                        let expr = render(&cond.expr);
+                        branch.trace.push(if cond.holds {
+                            format!("assume {expr}")
+                        } else {
+                            format!("assume !({expr})")
+                        });
+                    }
+                    if next != cfg.exit {
+                        pending.push((next, branch, visits.clone()));
+                    } else if !aborts(cfg, block) {
+                        exits.push(branch);
+                    }
+                }
+            }
+        }
+
+        exits
+    }
+
+    fn step(&mut self, state: State<'ctx>, stmt: &BlockStmt) -> Vec<State<'ctx>> {
+        match stmt {
+            BlockStmt::Local(local) => match &local.init {
+                Some(init) => self
+                    .eval(state, &init.expr)
+                    .into_iter()
+                    .map(|(mut s, value)| {
+                        self.bind(&mut s, &local.pat, value);
+                        s.last = Value::Unit;
+                        s
+                    })
+                    .collect(),
+                None => {
+                    let mut s = state;
+                    self.bind(&mut s, &local.pat, Value::Unknown);
+                    s.last = Value::Unit;
+                    vec![s]
+                }
+            },
+            BlockStmt::Expr(expr) => self
+                .eval(state, expr)
+                .into_iter()
+                .map(|(mut s, value)| {
+                    s.last = value;
+                    s
+                })
+                .collect(),
+            BlockStmt::ForBinding { pat, .. } => {
+                let mut s = state;
+                self.bind(&mut s, pat, Value::Unknown);
+                s.last = Value::Unit;
+                vec![s]
+            }
+            BlockStmt::MatchBinding { pat, .. } => {
+                // The scrutinee was the last statement before the branch.
+                let mut s = state;
+                let scrutinee = std::mem::replace(&mut s.last, Value::Unit);
+                self.bind(&mut s, pat, scrutinee);
+                vec![s]
+            }
+        }
+    }
+
+    /// Bind the names in `pat` to the parts of `value`, assuming the pattern
+    /// matches.
+    fn bind(&mut self, state: &mut State<'ctx>, pat: &Pat, value: Value<'ctx>) {
+        match pat {
+            Pat::Ident(id) if id.ident == "None" && id.subpat.is_none() => {
+                if let Value::Opt { present, .. } = value {
+                    state.path.push(present.not());
+                }
+            }
+            Pat::Ident(id) => {
+                state.locals.insert(id.ident.to_string(), value);
+            }
+            Pat::Type(pt) => {
+                let value = self.coerce(value, kind_of(&pt.ty));
+                self.bind(state, &pt.pat, value);
+            }
+            Pat::Reference(r) => self.bind(state, &r.pat, value),
+            Pat::Paren(p) => self.bind(state, &p.pat, value),
+            Pat::TupleStruct(ts) if ts.elems.len() == 1 && last_segment(&ts.path) == "Some" => {
+                let inner = match value {
+                    Value::Opt { present, value } => {
+                        state.path.push(present);
+                        *value
+                    }
+                    _ => Value::Unknown,
+                };
+                self.bind(state, &ts.elems[0], inner);
+            }
+            Pat::TupleStruct(ts) if ts.elems.len() == 1 && last_segment(&ts.path) == "Ok" => {
+                self.bind(state, &ts.elems[0], value);
+            }
+            Pat::Lit(lit) => {
+                let lit = self.literal(&lit.lit);
+                if let Value::Bool(eq) = self.compare(&BinOp::Eq(Default::default()), value, lit) {
+                    state.path.push(eq);
+                }
+            }
+            Pat::Wild(_) => {}
+            _ => {
+                let mut names = HashSet::new();
+                collect_pat_idents(pat, &mut names);
+                for name in names {
+                    state.locals.insert(name, Value::Unknown);
+                }
+            }
+        }
+    }
+
+    // ── Expressions ───────────────────────────────────────────────────────────
+
+    fn eval(&mut self, state: State<'ctx>, expr: &Expr) -> Vec<(State<'ctx>, Value<'ctx>)> {
+        match expr {
+            Expr::Lit(lit) => {
+                let value = self.literal(&lit.lit);
+                vec![(state, value)]
+            }
+            Expr::Paren(e) => self.eval(state, &e.expr),
+            Expr::Group(e) => self.eval(state, &e.expr),
+            Expr::Reference(e) => self.eval(state, &e.expr),
+            Expr::Path(p) => {
+                let value = self.path(&state, p);
+                vec![(state, value)]
+            }
+            Expr::Unary(u) => self
+                .eval(state, &u.expr)
+                .into_iter()
+                .map(|(mut s, value)| {
+                    let value = self.unary(&mut s, &u.op, value);
+                    (s, value)
+                })
+                .collect(),
+            Expr::Binary(b) if matches!(b.op, BinOp::And(_) | BinOp::Or(_)) => {
+                self.short_circuit(state, b)
+            }
+            Expr::Binary(b) => self
+                .eval_all(state, &[&b.left, &b.right])
+                .into_iter()
+                .map(|(mut s, mut values)| {
+                    let r = values.pop().unwrap_or(Value::Unknown);
+                    let l = values.pop().unwrap_or(Value::Unknown);
+                    if let Some(op) = compound_op(&b.op) {
+                        let value = self.arith(&mut s, op, l, r);
+                        self.assign(&mut s, &b.left, value);
+                        return (s, Value::Unit);
+                    }
+                    let value = match arith_op(&b.op) {
+                        Some(op) => self.arith(&mut s, op, l, r),
+                        None => self.compare(&b.op, l, r),
+                    };
+                    (s, value)
+                })
+                .collect(),
+            Expr::Assign(a) => self
+                .eval(state, &a.right)
+                .into_iter()
+                .map(|(mut s, value)| {
+                    self.assign(&mut s, &a.left, value);
+                    (s, Value::Unit)
+                })
+                .collect(),
+            Expr::Cast(c) => {
+                let to = kind_of(&c.ty);
+                self.eval(state, &c.expr)
+                    .into_iter()
+                    .map(|(s, value)| {
+                        let value = match (value, to) {
+                            (Value::Int(bv, from), Some(Kind::Int(to))) => {
+                                Value::Int(cast_bv(bv, from, to), to)
+                            }
+                            (Value::Lit(n), Some(Kind::Int(to))) => {
+                                Value::Int(self.int_const(n, to), to)
+                            }
+                            _ => Value::Unknown,
+                        };
+                        (s, value)
+                    })
+                    .collect()
+            }
+            Expr::MethodCall(m) => self.method_call(state, m),
+            Expr::Call(c) => self.call(state, c),
+            Expr::Macro(m) => match assert_condition(&m.mac) {
+                Some(cond) => self.eval(state, &cond),
+                None => vec![(state, Value::Unknown)],
+            },
+            Expr::Return(r) => match &r.expr {
+                Some(e) => self.eval(state, e),
+                None => vec![(state, Value::Unit)],
+            },
+            Expr::Try(t) => self
+                .eval(state, &t.expr)
+                .into_iter()
+                .map(|(mut s, value)| match value {
+                    // The `None` path returns early and is rolled back.
+                    Value::Opt { present, value } => {
+                        s.path.push(present);
+                        (s, *value)
+                    }
+                    value => (s, value),
+                })
+                .collect(),
+            Expr::Block(b) => match b.block.stmts.as_slice() {
+                [syn::Stmt::Expr(e, None)] => self.eval(state, e),
+                _ => vec![(state, Value::Unknown)],
+            },
+            _ => vec![(state, Value::Unknown)],
+        }
+    }
+
+    /// Evaluate `exprs` left to right.
+    fn eval_all(
+        &mut self,
+        state: State<'ctx>,
+        exprs: &[&Expr],
+    ) -> Vec<(State<'ctx>, Vec<Value<'ctx>>)> {
+        let mut results = vec![(state, Vec::new())];
+        for expr in exprs {
+            let mut next = Vec::new();
+            for (s, values) in results {
+                for (s, value) in self.eval(s, expr) {
+                    let mut values = values.clone();
+                    values.push(value);
+                    next.push((s, values));
+                }
+            }
+            results = next;
+        }
+        results
+    }
+
+    /// `&&` / `||`: a panic on the right-hand side only matters when the
+    /// right-hand side is evaluated.
+    fn short_circuit(
+        &mut self,
+        state: State<'ctx>,
+        b: &syn::ExprBinary,
+    ) -> Vec<(State<'ctx>, Value<'ctx>)> {
+        let and = matches!(b.op, BinOp::And(_));
+        let mut results = Vec::new();
+        for (s, l) in self.eval(state, &b.left) {
+            let l = self.boolean(l);
+            let reached = if and { l.clone() } else { l.not() };
+            let depth = s.path.len();
+            for (mut s, r) in self.eval(s, &b.right) {
+                let r = self.boolean(r);
+                for safe in s.path.split_off(depth) {
+                    s.path.push(reached.implies(&safe));
+                }
+                let value = if and {
+                    Bool::and(self.ctx, &[&l, &r])
+                } else {
+                    Bool::or(self.ctx, &[&l, &r])
+                };
+                results.push((s, Value::Bool(value)));
+            }
+        }
+        results
+    }
+
+    fn path(&mut self, state: &State<'ctx>, p: &syn::ExprPath) -> Value<'ctx> {
+        if let Some((ty, bits)) = type_bound(p) {
+            let bound = BV::from_str(self.ctx, ty.bits(), &bits.to_string())
+                .expect("type bound should be a valid Z3 bit-vector");
+            return Value::Int(bound, ty);
+        }
+        match p.path.segments.len() {
+            1 => {
+                let name = p.path.segments[0].ident.to_string();
+                if name == "None" {
+                    return Value::Opt {
+                        present: Bool::from_bool(self.ctx, false),
+                        value: Box::new(Value::Unknown),
+                    };
+                }
+                state.locals.get(&name).cloned().unwrap_or(Value::Unknown)
+            }
+            _ => Value::Key {
+                family: render(&Expr::Path(p.clone())),
+                arg: None,
+            },
+        }
+    }
+
+    fn call(&mut self, state: State<'ctx>, c: &syn::ExprCall) -> Vec<(State<'ctx>, Value<'ctx>)> {
+        let args: Vec<&Expr> = c.args.iter().collect();
+        let Expr::Path(func) = &*c.func else {
+            return vec![(state, Value::Unknown)];
+        };
+        let name = last_segment(&func.path);
+        let fns = self.fns;
+        let callee = resolve_call_path(self.owner.as_deref(), &c.func).and_then(|n| fns.get(&n));
+
+        let mut results = Vec::new();
+        for (s, mut values) in self.eval_all(state, &args) {
+            let single = (values.len() == 1).then(|| values.remove(0));
+            match (name.as_str(), single, callee) {
+                ("Some", Some(value), _) => results.push((
+                    s,
+                    Value::Opt {
+                        present: Bool::from_bool(self.ctx, true),
+                        value: Box::new(value),
+                    },
+                )),
+                ("Ok", Some(value), _) => results.push((s, value)),
+                (_, single, Some(callee)) => results.extend(self.inline(
+                    s,
+                    callee,
+                    single.into_iter().chain(values).collect(),
+                )),
+                (_, arg, None) if func.path.segments.len() >= 2 && args.len() <= 1 => {
+                    results.push((
+                        s,
+                        Value::Key {
+                            family: render(&c.func),
+                            arg: arg.map(Box::new),
+                        },
+                    ))
+                }
+                _ => results.push((s, Value::Unknown)),
+            }
+        }
+        results
+    }
+
+    /// Execute `callee` with `args` in the caller's storage.
+    fn inline(
+        &mut self,
+        state: State<'ctx>,
+        callee: &Function<'a>,
+        args: Vec<Value<'ctx>>,
+    ) -> Vec<(State<'ctx>, Value<'ctx>)> {
+        if self.depth >= MAX_CALL_DEPTH {
+            let mut s = state;
+            self.havoc_all(&mut s);
+            return vec![(s, Value::Unknown)];
+        }
+
+        let mut body = state.clone();
+        body.locals = HashMap::new();
+        let mut args = args.into_iter();
+        for input in &callee.sig.inputs {
+            if let FnArg::Typed(pt) = input {
+                let value = args.next().unwrap_or(Value::Unknown);
+                let value = self.coerce(value, kind_of(&pt.ty));
+                self.bind(&mut body, &pt.pat, value);
+            }
+        }
+
+        let owner = std::mem::replace(&mut self.owner, callee.owner.clone());
+        self.depth += 1;
+        let exits = self.run(&Cfg::build(callee.block), body);
+        self.depth -= 1;
+        self.owner = owner;
+
+        exits
+            .into_iter()
+            .map(|mut s| {
+                let value = std::mem::replace(&mut s.last, Value::Unit);
+                s.locals = state.locals.clone();
+                (s, value)
+            })
+            .collect()
+    }
+
+    fn method_call(
+        &mut self,
+        state: State<'ctx>,
+        m: &syn::ExprMethodCall,
+    ) -> Vec<(State<'ctx>, Value<'ctx>)> {
+        let method = m.method.to_string();
+        if matches!(method.as_str(), "get" | "set" | "has" | "remove")
+            && render(&m.receiver).contains("storage ()")
+        {
+            return self.storage_op(state, m, &method);
+        }
+
+        let mut exprs: Vec<&Expr> = vec![&m.receiver];
+        exprs.extend(m.args.iter());
+        self.eval_all(state, &exprs)
+            .into_iter()
+            .map(|(mut s, mut values)| {
+                let receiver = values.remove(0);
+                let value = self.method(&mut s, &method, receiver, values);
+                (s, value)
+            })
+            .collect()
+    }
+
+    fn method(
+        &mut self,
+        state: &mut State<'ctx>,
+        method: &str,
+        receiver: Value<'ctx>,
+        args: Vec<Value<'ctx>>,
+    ) -> Value<'ctx> {
+        let arg = args.into_iter().next().unwrap_or(Value::Unknown);
+        match (method, receiver) {
+            ("clone" | "to_owned" | "borrow", value) => value,
+            ("unwrap" | "expect", Value::Opt { present, value }) => {
+                state.path.push(present);
+                *value
+            }
+            // `Ok(..)` results are plain values.
+            ("unwrap" | "expect", value @ (Value::Int(..) | Value::Bool(_) | Value::Addr(_))) => {
+                value
+            }
+            ("unwrap_or", Value::Opt { present, value }) => self.select(&present, *value, arg),
+            ("unwrap_or_default", Value::Opt { present, value }) => {
+                self.select(&present, *value, Value::Lit(0))
+            }
+            ("is_some", Value::Opt { present, .. }) => Value::Bool(present),
+            ("is_none", Value::Opt { present, .. }) => Value::Bool(present.not()),
+            (method, receiver) => {
+                let (wrapping, op) = match method.split_once('_') {
+                    Some(("checked", op)) => (false, op),
+                    Some(("wrapping", op @ ("add" | "sub" | "mul"))) => (true, op),
+                    _ => return Value::Unknown,
+                };
+                let op = match op {
+                    "add" => ArithOp::Add,
+                    "sub" => ArithOp::Sub,
+                    "mul" => ArithOp::Mul,
+                    "div" => ArithOp::Div,
+                    "rem" => ArithOp::Rem,
+                    _ => return Value::Unknown,
+                };
+                let (Value::Int(l, ty), Value::Int(r, rty)) = self.unify(receiver, arg) else {
+                    return Value::Unknown;
+                };
+                if ty != rty {
+                    return Value::Unknown;
+                }
+                let (value, guards) = checked_arith(self.ctx, op, &l, &r, ty);
+                if wrapping {
+                    return Value::Int(value, ty);
+                }
+                let safe: Vec<Bool> = guards.into_iter().map(|(safe, _)| safe).collect();
+                let safe: Vec<&Bool> = safe.iter().collect();
+                Value::Opt {
+                    present: Bool::and(self.ctx, &safe),
+                    value: Box::new(Value::Int(value, ty)),
+                }
+            }
+        }
+    }
+
+    fn unary(&mut self, state: &mut State<'ctx>, op: &UnOp, value: Value<'ctx>) -> Value<'ctx> {
+        match (op, value) {
+            (UnOp::Not(_), Value::Bool(b)) => Value::Bool(b.not()),
+            (UnOp::Neg(_), Value::Lit(n)) => n.checked_neg().map_or(Value::Unknown, Value::Lit),
+            (UnOp::Neg(_), Value::Int(v, ty)) if ty.is_signed() => {
+                state.path.push(v.bvneg_no_overflow());
+                Value::Int(v.bvneg(), ty)
+            }
+            (UnOp::Deref(_), value) => value,
+            _ => Value::Unknown,
+        }
+    }
+
+    /// `l <op> r` under checked semantics: the path continues only where the
+    /// operation does not panic.
+    fn arith(
+        &mut self,
+        state: &mut State<'ctx>,
+        op: ArithOp,
+        l: Value<'ctx>,
+        r: Value<'ctx>,
+    ) -> Value<'ctx> {
+        match self.unify(l, r) {
+            (Value::Lit(a), Value::Lit(b)) => match op {
+                ArithOp::Add => a.checked_add(b),
+                ArithOp::Sub => a.checked_sub(b),
+                ArithOp::Mul => a.checked_mul(b),
+                ArithOp::Div => a.checked_div(b),
+                ArithOp::Rem => a.checked_rem(b),
+            }
+            .map_or(Value::Unknown, Value::Lit),
+            (Value::Int(a, ty), Value::Int(b, rty)) if ty == rty => {
+                let (value, guards) = checked_arith(self.ctx, op, &a, &b, ty);
+                state.path.extend(guards.into_iter().map(|(safe, _)| safe));
+                Value::Int(value, ty)
+            }
+            _ => Value::Unknown,
+        }
+    }
+
+    fn compare(&mut self, op: &BinOp, l: Value<'ctx>, r: Value<'ctx>) -> Value<'ctx> {
+        let eq = match (op, self.unify(l, r)) {
+            (_, (Value::Int(a, ty), Value::Int(b, rty))) if ty == rty => {
+                match (op, ty.is_signed()) {
+                    (BinOp::Eq(_) | BinOp::Ne(_), _) => a._eq(&b),
+                    (BinOp::Lt(_), false) => a.bvult(&b),
+                    (BinOp::Lt(_), true) => a.bvslt(&b),
+                    (BinOp::Le(_), false) => a.bvule(&b),
+                    (BinOp::Le(_), true) => a.bvsle(&b),
+                    (BinOp::Gt(_), false) => a.bvugt(&b),
+                    (BinOp::Gt(_), true) => a.bvsgt(&b),
+                    (BinOp::Ge(_), false) => a.bvuge(&b),
+                    (BinOp::Ge(_), true) => a.bvsge(&b),
+                    _ => return Value::Unknown,
+                }
+            }
+            (_, (Value::Lit(a), Value::Lit(b))) => {
+                let holds = match op {
+                    BinOp::Eq(_) | BinOp::Ne(_) => a == b,
+                    BinOp::Lt(_) => a < b,
+                    BinOp::Le(_) => a <= b,
+                    BinOp::Gt(_) => a > b,
+                    BinOp::Ge(_) => a >= b,
+                    _ => return Value::Unknown,
+                };
+                Bool::from_bool(self.ctx, holds)
+            }
+            (BinOp::Eq(_) | BinOp::Ne(_), (Value::Addr(a), Value::Addr(b))) => a._eq(&b),
+            (BinOp::Eq(_) | BinOp::Ne(_), (Value::Bool(a), Value::Bool(b))) => a._eq(&b),
+            _ => return Value::Unknown,
+        };
+        Value::Bool(if matches!(op, BinOp::Ne(_)) {
+            eq.not()
+        } else {
+            eq
+        })
+    }
+
+    fn assign(&mut self, state: &mut State<'ctx>, place: &Expr, value: Value<'ctx>) {
+        match place {
+            Expr::Path(p) if p.path.segments.len() == 1 => {
+                let name = p.path.segments[0].ident.to_string();
+                // `x = 0` keeps the type `x` was declared with.
+                let value = match (state.locals.get(&name), value) {
+                    (Some(Value::Int(_, ty)), Value::Lit(n)) => {
+                        Value::Int(self.int_const(n, *ty), *ty)
+                    }
+                    (_, value) => value,
+                };
+                state.locals.insert(name, value);
+            }
+            Expr::Paren(p) => self.assign(state, &p.expr, value),
+            Expr::Unary(u) if matches!(u.op, UnOp::Deref(_)) => self.assign(state, &u.expr, value),
+            _ => {}
+        }
+    }
+
+    // ── Storage ───────────────────────────────────────────────────────────────
+
+    fn storage_op(
+        &mut self,
+        state: State<'ctx>,
+        m: &syn::ExprMethodCall,
+        method: &str,
+    ) -> Vec<(State<'ctx>, Value<'ctx>)> {
+        let args: Vec<&Expr> = m.args.iter().collect();
+        let Some(&key_expr) = args.first() else {
+            return vec![(state, Value::Unknown)];
+        };
+
+        let mut results = Vec::new();
+        for (mut s, mut values) in self.eval_all(state, &args) {
+            let key = self.key(&s, key_expr, values.remove(0));
+            let value = match (method, key) {
+                ("get", Some((family, arg))) => match self.read(&mut s, &family, arg.as_ref()) {
+                    Some((present, value, ty)) => Value::Opt {
+                        present,
+                        value: Box::new(Value::Int(value, ty)),
+                    },
+                    None => Value::Unknown,
+                },
+                ("has", Some((family, arg))) => match self.read(&mut s, &family, arg.as_ref()) {
+                    Some((present, ..)) => Value::Bool(present),
+                    None => Value::Unknown,
+                },
+                ("set", Some((family, arg))) => {
+                    let value = values.into_iter().next().unwrap_or(Value::Unknown);
+                    self.write(&mut s, &family, arg.as_ref(), Some(value));
+                    Value::Unit
+                }
+                ("remove", Some((family, arg))) => {
+                    self.write(&mut s, &family, arg.as_ref(), None);
+                    Value::Unit
+                }
+                ("set" | "remove", None) => {
+                    self.havoc_all(&mut s);
+                    Value::Unit
+                }
+                _ => Value::Unknown,
+            };
+            results.push((s, value));
+        }
+        results
+    }
+
+    /// The family and argument of a storage key.
+    fn key(
+        &self,
+        state: &State<'ctx>,
+        expr: &Expr,
+        value: Value<'ctx>,
+    ) -> Option<(String, Option<Value<'ctx>>)> {
+        match (value, strip(expr)) {
+            (Value::Key { family, arg }, _) => Some((family, arg.map(|arg| *arg))),
+            // `symbol_short!("admin")`, or a constant.
+            (_, e @ Expr::Macro(_)) => Some((render(e), None)),
+            (_, Expr::Path(p))
+                if p.path.segments.len() == 1
+                    && !state
+                        .locals
+                        .contains_key(&p.path.segments[0].ident.to_string()) =>
+            {
+                Some((render(strip(expr)), None))
+            }
+            _ => None,
+        }
+    }
+
+    /// The array index of a key argument: `None` for a key without one.
+    fn index(&self, arg: Option<&Value<'ctx>>) -> Option<(Option<Kind>, Option<Dynamic<'ctx>>)> {
+        let (kind, index) = match arg {
+            None => return Some((None, None)),
+            Some(Value::Int(bv, ty)) => (Kind::Int(*ty), Dynamic::from_ast(bv)),
+            Some(Value::Lit(n)) => (
+                Kind::Int(IntType::I128),
+                Dynamic::from_ast(&self.int_const(*n, IntType::I128)),
+            ),
+            Some(Value::Bool(b)) => (Kind::Bool, Dynamic::from_ast(b)),
+            Some(Value::Addr(a)) => (Kind::Address, a.clone()),
+            Some(_) => return None,
+        };
+        Some((Some(kind), Some(index)))
+    }
+
+    /// `(present, value, type)` of the entry at `arg` in `family`.
+    fn read(
+        &mut self,
+        state: &mut State<'ctx>,
+        family: &str,
+        arg: Option<&Value<'ctx>>,
+    ) -> Option<(Bool<'ctx>, BV<'ctx>, IntType)> {
+        let (key, index) = self.index(arg)?;
+        match (self.family(state, family, key, None)?, index) {
+            (Family::Scalar { ty, value, present }, None) => {
+                Some((present.clone(), value.clone(), *ty))
+            }
+            (
+                Family::Map {
+                    ty,
+                    values,
+                    present,
+                    ..
+                },
+                Some(index),
+            ) => Some((
+                present.select(&index).as_bool()?,
+                values.select(&index).as_bv()?,
+                *ty,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Set (`Some`) or remove (`None`) the entry at `arg` in `family`.
+    fn write(
+        &mut self,
+        state: &mut State<'ctx>,
+        family: &str,
+        arg: Option<&Value<'ctx>>,
+        value: Option<Value<'ctx>>,
+    ) {
+        let Some((key, index)) = self.index(arg) else {
+            self.havoc_family(state, family);
+            return;
+        };
+        let hint = match &value {
+            Some(Value::Int(_, ty)) => Some(*ty),
+            _ => None,
+        };
+        let Some(ty) = self.family(state, family, key, hint).map(|f| f.ty()) else {
+            self.havoc_family(state, family);
+            return;
+        };
+        let new = value.map(|value| self.int(value, ty));
+
+        let ctx = self.ctx;
+        let set = Bool::from_bool(ctx, new.is_some());
+        let zero = BV::from_u64(ctx, 0, ty.bits());
+        match (state.storage.get_mut(family), index) {
+            (Some(Family::Scalar { value, present, .. }), None) => {
+                if let Some(new) = new {
+                    *value = new;
+                }
+                *present = set;
+            }
+            (
+                Some(Family::Map {
+                    values,
+                    present,
+                    sum,
+                    ..
+                }),
+                Some(index),
+            ) => {
+                let (Some(was_set), Some(old)) = (
+                    present.select(&index).as_bool(),
+                    values.select(&index).as_bv(),
+                ) else {
+                    return;
+                };
+                let mut total = sum.bvsub(&was_set.ite(&old, &zero));
+                if let Some(new) = new {
+                    total = total.bvadd(&new);
+                    *values = values.store(&index, &new);
+                }
+                *sum = total;
+                *present = present.store(&index, &set);
+            }
+            _ => {}
+        }
+    }
+
+    /// The family named `name` in `state`, created on first use.  `None`
+    /// when it was used with a different shape of key before.
+    fn family<'s>(
+        &mut self,
+        state: &'s mut State<'ctx>,
+        name: &str,
+        key: Option<Kind>,
+        hint: Option<IntType>,
+    ) -> Option<&'s mut Family<'ctx>> {
+        if !state.storage.contains_key(name) {
+            let ty = self
+                .types
+                .get(name)
+                .copied()
+                .or(hint)
+                .unwrap_or(IntType::I128);
+            let family = self.initial_family(name, key, ty, is_lost(state, name));
+            state.storage.insert(name.to_string(), family);
+        }
+        let family = state.storage.get_mut(name)?;
+        let same_shape = match &*family {
+            Family::Scalar { .. } => key.is_none(),
+            Family::Map { key: k, .. } => key == Some(*k),
+        };
+        same_shape.then_some(family)
+    }
+
+    /// A family's contents on entry (named constants shared by every path),
+    /// or arbitrary contents when `fresh`.
+    fn initial_family(
+        &self,
+        name: &str,
+        key: Option<Kind>,
+        ty: IntType,
+        fresh: bool,
+    ) -> Family<'ctx> {
+        let ctx = self.ctx;
+        let bits = ty.bits();
+        let is_set = format!("{name} is set");
+        let sum = format!("sum({name})");
+        match key {
+            None if fresh => Family::Scalar {
+                ty,
+                value: BV::fresh_const(ctx, name, bits),
+                present: Bool::fresh_const(ctx, &is_set),
+            },
+            None => Family::Scalar {
+                ty,
+                value: BV::new_const(ctx, name, bits),
+                present: Bool::new_const(ctx, is_set),
+            },
+            Some(key) => {
+                let domain = self.sort(key);
+                let range = Sort::bitvector(ctx, bits);
+                let flag = Sort::bool(ctx);
+                if fresh {
+                    Family::Map {
+                        ty,
+                        key,
+                        values: Array::fresh_const(ctx, name, &domain, &range),
+                        present: Array::fresh_const(ctx, &is_set, &domain, &flag),
+                        sum: BV::fresh_const(ctx, &sum, bits),
+                    }
+                } else {
+                    Family::Map {
+                        ty,
+                        key,
+                        values: Array::new_const(ctx, name, &domain, &range),
+                        present: Array::new_const(ctx, is_set, &domain, &flag),
+                        sum: BV::new_const(ctx, sum, bits),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forget everything known about one family.
+    fn havoc_family(&mut self, state: &mut State<'ctx>, name: &str) {
+        state.lost.insert(name.to_string());
+        if let Some(family) = state.storage.get(name) {
+            let (key, ty) = (family.key(), family.ty());
+            let fresh = self.initial_family(name, key, ty, true);
+            state.storage.insert(name.to_string(), fresh);
+        }
+    }
+
+    /// Forget everything known about storage.
+    fn havoc_all(&mut self, state: &mut State<'ctx>) {
+        state.havocked = true;
+        let names: Vec<String> = state.storage.keys().cloned().collect();
+        for name in names {
+            self.havoc_family(state, &name);
+        }
+    }
+
+    // ── Values ────────────────────────────────────────────────────────────────
+
+    fn literal(&self, lit: &Lit) -> Value<'ctx> {
+        match lit {
+            Lit::Bool(b) => Value::Bool(Bool::from_bool(self.ctx, b.value)),
+            Lit::Int(i) => match (IntType::from_name(i.suffix()), i.base10_parse::<i128>()) {
+                (Some(ty), Ok(n)) => Value::Int(self.int_const(n, ty), ty),
+                (None, Ok(n)) if i.suffix().is_empty() => Value::Lit(n),
+                _ => Value::Unknown,
+            },
+            _ => Value::Unknown,
+        }
+    }
+
+    fn int_const(&self, n: i128, ty: IntType) -> BV<'ctx> {
+        let pattern = (n as u128) & (u128::MAX >> (128 - ty.bits()));
+        BV::from_str(self.ctx, ty.bits(), &pattern.to_string())
+            .expect("decimal literal should be a valid Z3 bit-vector")
+    }
+
+    /// `value` as a `ty`, or an arbitrary `ty` if it is not one.
+    fn int(&mut self, value: Value<'ctx>, ty: IntType) -> BV<'ctx> {
+        match self.coerce(value, Some(Kind::Int(ty))) {
+            Value::Int(bv, t) if t == ty => bv,
+            _ => BV::fresh_const(self.ctx, "value", ty.bits()),
+        }
+    }
+
+    /// Give literals and unknown values the type they are bound as.
+    fn coerce(&mut self, value: Value<'ctx>, kind: Option<Kind>) -> Value<'ctx> {
+        match (value, kind) {
+            (Value::Lit(n), Some(Kind::Int(ty))) => Value::Int(self.int_const(n, ty), ty),
+            (Value::Unknown, Some(Kind::Int(ty))) => {
+                Value::Int(BV::fresh_const(self.ctx, "value", ty.bits()), ty)
+            }
+            (Value::Unknown, Some(Kind::Bool)) => Value::Bool(Bool::fresh_const(self.ctx, "value")),
+            (Value::Unknown, Some(Kind::Address)) => {
+                self.fresh += 1;
+                Value::Addr(self.address_const(&format!("address!{}", self.fresh)))
+            }
+            (value, _) => value,
+        }
+    }
+
+    /// Literals take the type of the integer they meet.
+    fn unify(&self, l: Value<'ctx>, r: Value<'ctx>) -> (Value<'ctx>, Value<'ctx>) {
+        match (l, r) {
+            (Value::Lit(n), Value::Int(r, ty)) => {
+                (Value::Int(self.int_const(n, ty), ty), Value::Int(r, ty))
+            }
+            (Value::Int(l, ty), Value::Lit(n)) => {
+                (Value::Int(l, ty), Value::Int(self.int_const(n, ty), ty))
+            }
+            pair => pair,
+        }
+    }
+
+    /// `if cond { a } else { b }`.
+    fn select(&self, cond: &Bool<'ctx>, a: Value<'ctx>, b: Value<'ctx>) -> Value<'ctx> {
+        match self.unify(a, b) {
+            (Value::Int(a, ty), Value::Int(b, rty)) if ty == rty => {
+                Value::Int(cond.ite(&a, &b), ty)
+            }
+            (Value::Bool(a), Value::Bool(b)) => Value::Bool(cond.ite(&a, &b)),
+            (Value::Addr(a), Value::Addr(b)) => Value::Addr(cond.ite(&a, &b)),
+            (Value::Lit(a), Value::Lit(b)) if a == b => Value::Lit(a),
+            _ => Value::Unknown,
+        }
+    }
+
+    fn boolean(&self, value: Value<'ctx>) -> Bool<'ctx> {
+        match value {
+            Value::Bool(b) => b,
+            _ => Bool::fresh_const(self.ctx, "cond"),
+        }
+    }
+
+    fn address_const(&self, name: &str) -> Dynamic<'ctx> {
+        FuncDecl::new(self.ctx, name, &[], &self.address).apply(&[])
+    }
+
+    fn sort(&self, kind: Kind) -> Sort<'ctx> {
+        match kind {
+            Kind::Int(ty) => Sort::bitvector(self.ctx, ty.bits()),
+            Kind::Bool => Sort::bool(self.ctx),
+            Kind::Address => self.address.clone(),
+        }
+    }
+}
+
+impl<'ctx> Family<'ctx> {
+    fn ty(&self) -> IntType {
+        match self {
+            Family::Scalar { ty, .. } | Family::Map { ty, .. } => *ty,
+        }
+    }
+
+    fn key(&self) -> Option<Kind> {
+        match self {
+            Family::Scalar { .. } => None,
+            Family::Map { key, .. } => Some(*key),
+        }
+    }
+}
+
+fn is_lost(state: &State<'_>, family: &str) -> bool {
+    state.havocked || state.lost.contains(family)
+}
+
+/// Whether leaving `block` for the exit rolls the invocation back: a failed
+/// `assert!`, a panic, a `?` on an error, or returning an `Err`.
+fn aborts(cfg: &Cfg, block: usize) -> bool {
+    if let Some(cond) = cfg.conditions.get(&(block, cfg.exit)) {
+        return !cond.holds;
+    }
+    if cfg.successors[block].len() > 1 {
+        return true;
+    }
+    match cfg.blocks[block].stmts.last() {
+        Some(stmt) if stmt.panics() => true,
+        Some(BlockStmt::Expr(Expr::Return(r))) => r.expr.as_deref().is_some_and(is_err),
+        Some(BlockStmt::Expr(e)) => is_err(e),
+        _ => false,
+    }
+}
+
+fn is_err(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(c) => matches!(&*c.func, Expr::Path(p) if last_segment(&p.path) == "Err"),
+        _ => false,
+    }
+}
+
+fn last_segment(path: &syn::Path) -> String {
+    path.segments
+        .last()
+        .map(|s| s.ident.to_string())
+        .unwrap_or_default()
+}
+
+fn strip(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Reference(r) => strip(&r.expr),
+        Expr::Paren(p) => strip(&p.expr),
+        Expr::Group(g) => strip(&g.expr),
+        _ => expr,
+    }
+}
+
+/// `DataKey::Balance` for `sum(DataKey::Balance)`.
+fn summed_family(name: &str) -> &str {
+    name.strip_prefix("sum(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or(name)
+}
+
+fn arith_op(op: &BinOp) -> Option<ArithOp> {
+    match op {
+        BinOp::Add(_) => Some(ArithOp::Add),
+        BinOp::Sub(_) => Some(ArithOp::Sub),
+        BinOp::Mul(_) => Some(ArithOp::Mul),
+        BinOp::Div(_) => Some(ArithOp::Div),
+        BinOp::Rem(_) => Some(ArithOp::Rem),
+        _ => None,
+    }
+}
+
+fn compound_op(op: &BinOp) -> Option<ArithOp> {
+    match op {
+        BinOp::AddAssign(_) => Some(ArithOp::Add),
+        BinOp::SubAssign(_) => Some(ArithOp::Sub),
+        BinOp::MulAssign(_) => Some(ArithOp::Mul),
+        BinOp::DivAssign(_) => Some(ArithOp::Div),
+        BinOp::RemAssign(_) => Some(ArithOp::Rem),
+        _ => None,
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = r#"
+        #[contractimpl]
+        #[invariant = "DataKey::TotalSupply == sum(DataKey::Balance)"]
+        impl Token {
+            pub fn mint(env: Env, to: Address, amount: i128) {
+                assert!(amount > 0, "amount must be positive");
+                Self::credit(&env, &to, amount);
+                let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
+                env.storage().instance().set(&DataKey::TotalSupply, &(supply + amount));
+            }
+
+            pub fn burn(env: Env, from: Address, amount: i128) {
+                from.require_auth();
+                Self::debit(&env, &from, amount);
+                let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
+                env.storage().instance().set(&DataKey::TotalSupply, &(supply - amount));
+            }
+
+            pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+                from.require_auth();
+                Self::debit(&env, &from, amount);
+                Self::credit(&env, &to, amount);
+            }
+
+            pub fn balance(env: Env, id: Address) -> i128 {
+                env.storage().persistent().get(&DataKey::Balance(id)).unwrap_or(0)
+            }
+
+            fn credit(env: &Env, to: &Address, amount: i128) {
+                let balance = Self::balance(env.clone(), to.clone());
+                env.storage().persistent().set(&DataKey::Balance(to.clone()), &(balance + amount));
+            }
+
+            fn debit(env: &Env, from: &Address, amount: i128) {
+                let balance = Self::balance(env.clone(), from.clone());
+                if balance < amount {
+                    panic!("insufficient balance");
+                }
+                env.storage().persistent().set(&DataKey::Balance(from.clone()), &(balance - amount));
+            }
+        }
+    "#;
+
+    fn verify(source: &str) -> Vec<SmtFinding> {
+        verify_invariant_preservation(source, &SmtConfig::default())
+    }
+
+    #[test]
+    fn total_supply_is_preserved_by_mint_burn_and_transfer() {
+        let findings = verify(TOKEN);
+        assert!(findings.is_empty(), "unexpected findings: {findings:?}");
+    }
+
+    #[test]
+    fn mint_that_skips_the_supply_update_is_reported() {
+        let source = TOKEN.replace(
+            "env.storage().instance().set(&DataKey::TotalSupply, &(supply + amount));",
+            "",
+        );
+        let findings = verify(&source);
+        assert_eq!(findings.len(), 1, "{findings:?}");
+        let finding = &findings[0];
+        assert_eq!(finding.kind, SmtFindingKind::InvariantNotPreserved);
+        assert_eq!(finding.location, "mint");
+        assert!(!finding.is_timeout);
+        assert!(finding.path.iter().any(|step| step == "assume amount > 0"));
+
+        let amount: i128 = finding.witness["amount"].parse().unwrap();
+        assert!(amount > 0, "the witness must pass the assert: {amount}");
+        let before: i128 = finding.witness["sum(DataKey::Balance) (before)"]
+            .parse()
+            .unwrap();
+        let after: i128 = finding.witness["sum(DataKey::Balance) (after)"]
+            .parse()
+            .unwrap();
+        assert_eq!(after.wrapping_sub(before), amount);
+        assert_eq!(
+            finding.witness["DataKey::TotalSupply (before)"],
+            finding.witness["DataKey::TotalSupply (after)"]
+        );
+        let counterexample = finding.counterexample.as_deref().unwrap();
+        assert!(
+            counterexample.starts_with("mint(amount = ") && counterexample.contains(" takes "),
+            "{counterexample}"
+        );
+    }
+
+    #[test]
+    fn transfer_that_reads_both_balances_first_mints_on_self_transfer() {
+        let source = r#"
+            #[invariant = "DataKey::TotalSupply == sum(DataKey::Balance)"]
+            impl Token {
+                pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+                    let from_balance: i128 = env.storage().persistent().get(&DataKey::Balance(from.clone())).unwrap_or(0);
+                    let to_balance: i128 = env.storage().persistent().get(&DataKey::Balance(to.clone())).unwrap_or(0);
+                    if from_balance < amount {
+                        return Err(Error::InsufficientBalance);
+                    }
+                    env.storage().persistent().set(&DataKey::Balance(from), &(from_balance - amount));
+                    env.storage().persistent().set(&DataKey::Balance(to), &(to_balance + amount));
+                    Ok(())
+                }
+            }
+        "#;
+        let findings = verify(source);
+        assert_eq!(findings.len(), 1, "{findings:?}");
+        let witness = &findings[0].witness;
+        assert_eq!(witness["from"], witness["to"], "only a self-transfer mints");
+        assert_ne!(witness["amount"], "0");
+        assert!(findings[0]
+            .path
+            .iter()
+            .any(|step| step == "assume !(from_balance < amount)"));
+    }
+
+    #[test]
+    fn function_invariant_over_storage_follows_branches_and_asserts() {
+        let source = r#"
+            impl Counter {
+                #[invariant = "DataKey::Count as u32 <= 100"]
+                pub fn bump(env: Env, by: u32) {
+                    let count: u32 = env.storage().instance().get(&DataKey::Count).unwrap_or(0);
+                    if count + by > 100 {
+                        panic!("over the cap");
+                    }
+                    env.storage().instance().set(&DataKey::Count, &(count + by));
+                }
+
+                #[invariant = "DataKey::Count as u32 <= 100"]
+                pub fn bump_unchecked(env: Env, by: u32) {
+                    let count: u32 = env.storage().instance().get(&DataKey::Count).unwrap_or(0);
+                    assert!(count + by <= 101);
+                    env.storage().instance().set(&DataKey::Count, &(count + by));
+                }
+            }
+        "#;
+        let findings = verify(source);
+        assert_eq!(findings.len(), 1, "{findings:?}");
+        assert_eq!(findings[0].location, "bump_unchecked");
+        assert_eq!(findings[0].witness["DataKey::Count (after)"], "101");
+    }
+
+    #[test]
+    fn unmodelled_storage_writes_forget_the_family() {
+        let source = r#"
+            #[invariant = "DataKey::TotalSupply >= 0"]
+            impl Token {
+                pub fn reset(env: Env, key: DataKey) {
+                    env.storage().instance().set(&key, &-1i128);
+                }
+            }
+        "#;
+        let findings = verify(source);
+        assert_eq!(findings.len(), 1, "{findings:?}");
+    }
+
+    #[test]
+    fn invalid_source_and_unsupported_invariants_produce_no_findings() {
+        assert!(verify("this is not valid rust }{{{").is_empty());
+        let source = r#"
+            #[invariant = "env.ledger().sequence() > 0"]
+            impl Token {
+                pub fn noop(env: Env) {}
+            }
+        "#;
+        assert!(verify(source).is_empty());
+    }
+}
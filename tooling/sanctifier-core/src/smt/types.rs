@@ -1,10 +1,11 @@
 //! Shared types for the SMT formal-verification module.
 //!
 //! All public data types are defined here so that the other sub-modules
-//! (`invariants`, `fixed_point`, `benchmark`) can import them without
+//! (`invariants`, `backend`, `smtlib`, `benchmark`) can import them without
 //! creating circular dependencies.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 // ── Core finding type ─────────────────────────────────────────────────────────
@@ -35,15 +36,40 @@ impl Default for SmtConfig {
     }
 }
 
+/// What an [`SmtFinding`] proved (S011 sub-kinds).
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SmtFindingKind {
+    /// The invariant formula itself can be false or panic.
+    #[default]
+    InvariantViolation,
+    /// The invariant holds before a call to the function but not after it.
+    InvariantNotPreserved,
+    /// A fixed-point `a * b / d` calculation found in the source can overflow.
+    FixedPointOverflow,
+}
+
 /// Structured finding returned by the SMT invariant verifier.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SmtFinding {
+    /// Which S011 check produced the finding.
+    #[serde(default)]
+    pub kind: SmtFindingKind,
     /// Name / expression of the invariant that was checked.
     pub invariant_name: String,
     /// Source location (enclosing function name or file:line).
     pub location: String,
-    /// Concrete counterexample values returned by Z3, when available.
+    /// Concrete counterexample returned by Z3, when available: the violating
+    /// assignment followed by why the invariant fails under it.
     pub counterexample: Option<String>,
+    /// The violating assignment, keyed by parameter or storage key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub witness: BTreeMap<String, String>,
+    /// The statements and branch decisions along the violating path, for
+    /// [`SmtFindingKind::InvariantNotPreserved`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<String>,
     /// `true` when the solver timed out instead of producing sat/unsat.
     pub is_timeout: bool,
 }
@@ -56,19 +82,29 @@ pub struct InvariantSpec {
     pub expression: String,
     /// Enclosing function name used as the location hint.
     pub location: String,
+    /// Parameters of the enclosing function as `(name, type)`, which the
+    /// expression may reference.
+    pub params: Vec<(String, String)>,
 }
 
 // ── Backend selector ──────────────────────────────────────────────────────────
 
 /// Supported SMT backends for fixed-point proofs.
+///
+/// Z3 runs in-process when the `smt` feature links libz3; every other
+/// backend is an SMT-LIB2 solver binary driven by [`super::SmtLibSolver`].
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum SmtBackend {
     /// Z3 backend.
     Z3,
-    /// Placeholder for future CVC5 support.
+    /// The `cvc5` binary.
     Cvc5,
+    /// The `bitwuzla` binary.
+    Bitwuzla,
+    /// Any other SMT-LIB2 solver binary.
+    External,
 }
 
 // ── Fixed-point proof types ───────────────────────────────────────────────────
@@ -89,6 +125,10 @@ pub struct FixedPointMulDivSpec {
     /// Optional bound for the final quotient.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result_max: Option<u128>,
+    /// Bound the intermediate product must stay within, e.g. `i128::MAX`
+    /// for `i128` arithmetic.  `None` means `u128::MAX`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_max: Option<u128>,
 }
 
 /// Concrete witness returned when a fixed-point proof fails.
@@ -130,12 +170,32 @@ pub enum FixedPointProofError {
     /// Invalid input bounds.
     #[error("invalid fixed-point proof specification: {0}")]
     InvalidSpec(&'static str),
-    /// The requested backend is not implemented yet.
+    /// The requested backend has no default solver to dispatch to.
     #[error("unsupported SMT backend: {0:?}")]
     UnsupportedBackend(SmtBackend),
     /// The backend did not produce a usable answer.
     #[error("solver did not produce a usable result: {0}")]
     SolverFailure(&'static str),
+    /// An SMT-LIB2 solver binary could not be run or answered badly.
+    #[error(transparent)]
+    SmtLib(#[from] SmtLibError),
+}
+
+/// Errors raised when driving an SMT-LIB2 solver binary.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SmtLibError {
+    /// The binary could not be started or fed the script.
+    #[error("could not run SMT solver `{program}`: {message}")]
+    Spawn { program: String, message: String },
+    /// The binary did not exit within the configured timeout.
+    #[error("SMT solver `{program}` did not answer within {timeout_ms} ms")]
+    Timeout { program: String, timeout_ms: u64 },
+    /// The solver reported an `(error ...)` before answering.
+    #[error("SMT solver `{program}` reported an error: {message}")]
+    Solver { program: String, message: String },
+    /// The solver's output was not a `check-sat` answer.
+    #[error("could not read the output of SMT solver `{program}`: {message}")]
+    Output { program: String, message: String },
 }
 
 // ── Benchmark types ───────────────────────────────────────────────────────────
@@ -185,7 +245,7 @@ impl SmtLatencyBenchmarkReport {
     /// Return strategies ordered by descending average latency.
     pub fn most_expensive_first(&self) -> Vec<SmtStrategyLatency> {
         let mut sorted = self.strategies.clone();
-        sorted.sort_by(|a, b| b.avg_micros.cmp(&a.avg_micros));
+        sorted.sort_by_key(|s| std::cmp::Reverse(s.avg_micros));
         sorted
     }
 }
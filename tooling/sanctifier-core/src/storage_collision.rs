@@ -1,11 +1,16 @@
 use quote::quote;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::visit::Visit;
-use syn::{Expr, ExprCall, ExprMacro, ExprMethodCall, ItemConst, Lit};
+use syn::visit_mut::VisitMut;
+use syn::{
+    Expr, ExprCall, ExprMacro, ExprMethodCall, FnArg, ItemConst, Lit, Pat, ReturnType, Signature,
+    Stmt, Token,
+};
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StorageCollisionIssue {
     pub key_value: String,
     pub key_type: String,
@@ -22,7 +27,7 @@ pub enum SorobanStorageType {
 }
 
 impl SorobanStorageType {
-    fn as_str(self) -> &'static str {
+    pub(crate) fn as_str(self) -> &'static str {
         match self {
             Self::Instance => "instance",
             Self::Persistent => "persistent",
@@ -30,29 +35,201 @@ impl SorobanStorageType {
             Self::Unknown => "unknown",
         }
     }
+
+    /// Tier of `env.storage().<tier>()`, when `receiver` is such a call.
+    pub(crate) fn of_receiver(receiver: &Expr) -> Option<Self> {
+        let Expr::MethodCall(call) = receiver else {
+            return None;
+        };
+        if !matches!(call.receiver.as_ref(), Expr::MethodCall(inner) if inner.method == "storage") {
+            return None;
+        }
+        match call.method.to_string().as_str() {
+            "instance" => Some(Self::Instance),
+            "persistent" => Some(Self::Persistent),
+            "temporary" => Some(Self::Temporary),
+            _ => None,
+        }
+    }
 }
 
 pub struct StorageVisitor {
     pub collisions: Vec<StorageCollisionIssue>,
     keys: HashMap<(SorobanStorageType, String), Vec<KeyInfo>>,
+    definitions: KeyDefinitions,
+    file: Option<String>,
 }
 
 #[derive(Clone)]
 struct KeyInfo {
     key_type: String,
     location: String,
+    file: Option<String>,
     line: usize,
 }
 
+impl KeyInfo {
+    fn position(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}:{}", file, self.line),
+            None => format!("{}:{}", self.location, self.line),
+        }
+    }
+}
+
+/// Key constants and key-constructor functions by name, gathered from every
+/// file of a crate or workspace.  `None` marks a name defined with different
+/// values in different places, which is left unresolved.
+#[derive(Default)]
+struct KeyDefinitions {
+    consts: HashMap<String, Option<Expr>>,
+    constructors: HashMap<String, Option<KeyConstructor>>,
+}
+
+/// A function whose body is nothing but a key expression, such as
+/// `fn balance_key(user: Address) -> DataKey { DataKey::Balance(user) }`.
+#[derive(Clone, PartialEq)]
+struct KeyConstructor {
+    /// Parameter names in order; empty for patterns other than an identifier.
+    params: Vec<String>,
+    key: Expr,
+}
+
+impl KeyConstructor {
+    fn of(sig: &Signature, block: &syn::Block) -> Option<Self> {
+        if matches!(sig.output, ReturnType::Default) {
+            return None;
+        }
+        let key = match block.stmts.as_slice() {
+            [Stmt::Expr(expr, None)] if is_key_expr(expr) => expr.clone(),
+            _ => return None,
+        };
+        let params = sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Receiver(_) => "self".to_string(),
+                FnArg::Typed(typed) => match typed.pat.as_ref() {
+                    Pat::Ident(ident) => ident.ident.to_string(),
+                    _ => String::new(),
+                },
+            })
+            .collect();
+        Some(Self { params, key })
+    }
+
+    /// The key a call with `args` builds: the returned expression with every
+    /// parameter replaced by the argument passed for it.
+    fn bind(&self, args: &Punctuated<Expr, Token![,]>) -> Expr {
+        let mut key = self.key.clone();
+        BindParams {
+            bindings: self.params.iter().zip(args).collect(),
+        }
+        .visit_expr_mut(&mut key);
+        key
+    }
+}
+
+/// Replaces parameter paths by the arguments bound to them.
+struct BindParams<'a> {
+    bindings: HashMap<&'a String, &'a Expr>,
+}
+
+impl VisitMut for BindParams<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Path(path) = expr {
+            let arg = path
+                .path
+                .get_ident()
+                .and_then(|ident| self.bindings.get(&ident.to_string()));
+            if let Some(arg) = arg {
+                *expr = (*arg).clone();
+                return;
+            }
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+impl KeyDefinitions {
+    fn insert<T: Clone + PartialEq>(map: &mut HashMap<String, Option<T>>, name: String, value: T) {
+        match map.get(&name) {
+            Some(Some(existing)) if *existing != value => {
+                map.insert(name, None);
+            }
+            Some(_) => {}
+            None => {
+                map.insert(name, Some(value));
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for KeyDefinitions {
+    fn visit_item_const(&mut self, i: &'ast ItemConst) {
+        if is_key_expr(&i.expr) {
+            Self::insert(&mut self.consts, i.ident.to_string(), (*i.expr).clone());
+        }
+    }
+
+    fn visit_item_fn(&mut self, i: &'ast syn::ItemFn) {
+        if let Some(constructor) = KeyConstructor::of(&i.sig, &i.block) {
+            Self::insert(&mut self.constructors, i.sig.ident.to_string(), constructor);
+        }
+        syn::visit::visit_item_fn(self, i);
+    }
+
+    fn visit_impl_item_fn(&mut self, i: &'ast syn::ImplItemFn) {
+        if let Some(constructor) = KeyConstructor::of(&i.sig, &i.block) {
+            Self::insert(&mut self.constructors, i.sig.ident.to_string(), constructor);
+        }
+        syn::visit::visit_impl_item_fn(self, i);
+    }
+}
+
+fn is_key_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Macro(_) | Expr::Path(_) => true,
+        Expr::Reference(reference) => is_key_expr(&reference.expr),
+        Expr::Paren(paren) => is_key_expr(&paren.expr),
+        // Enum variants and `Symbol::new`, not calls to other functions.
+        Expr::Call(call) => match call.func.as_ref() {
+            Expr::Path(path) => {
+                path.path.segments.len() >= 2
+                    && path.path.segments[0]
+                        .ident
+                        .to_string()
+                        .starts_with(|c: char| c.is_ascii_uppercase())
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 #[allow(clippy::new_without_default)]
 impl StorageVisitor {
     pub fn new() -> Self {
         Self {
             collisions: Vec::new(),
             keys: HashMap::new(),
+            definitions: KeyDefinitions::default(),
+            file: None,
         }
     }
 
+    /// Record the key constants and key-constructor functions of `file`, so
+    /// keys built through them resolve to the same value in every file.
+    /// Call this for all files before visiting any of them.
+    pub fn collect_definitions(&mut self, file: &syn::File) {
+        self.definitions.visit_file(file);
+    }
+
+    /// Attribute keys visited from now on to `path`.
+    pub fn set_file(&mut self, path: &str) {
+        self.file = Some(path.to_string());
+    }
+
     fn add_key(
         &mut self,
         value: String,
@@ -64,6 +241,7 @@ impl StorageVisitor {
         let info = KeyInfo {
             key_type,
             location,
+            file: self.file.clone(),
             line,
         };
         self.keys
@@ -81,13 +259,16 @@ impl StorageVisitor {
                         .iter()
                         .enumerate()
                         .filter(|(idx, _)| *idx != i)
-                        .map(|(_, info)| format!("{} (line {})", info.location, info.line))
+                        .map(|(_, info)| match info.file {
+                            Some(_) => info.position(),
+                            None => format!("{} (line {})", info.location, info.line),
+                        })
                         .collect();
 
                     self.collisions.push(StorageCollisionIssue {
                         key_value: value.clone(),
                         key_type: format!("{} ({})", current.key_type, storage_type.as_str()),
-                        location: format!("{}:{}", current.location, current.line),
+                        location: current.position(),
                         message: format!(
                             "Potential {} storage key collision: value '{}' is also used in: {}",
                             storage_type.as_str(),
@@ -120,7 +301,39 @@ impl StorageVisitor {
         }
     }
 
-    fn extract_key_value_expr(expr: &Expr) -> Option<String> {
+    fn extract_key_value_expr(&self, expr: &Expr) -> Option<String> {
+        self.resolve_key(expr, 0)
+    }
+
+    fn resolve_key(&self, expr: &Expr, depth: usize) -> Option<String> {
+        // Keys named through a constant or built by a key-constructor
+        // function take the value of its definition, with the call's
+        // arguments in place of the constructor's parameters.
+        let definition = match expr {
+            Expr::Path(expr_path) => expr_path
+                .path
+                .segments
+                .last()
+                .and_then(|s| self.definitions.consts.get(&s.ident.to_string()))
+                .and_then(|definition| definition.clone()),
+            Expr::Call(call) => match call.func.as_ref() {
+                Expr::Path(func) => func
+                    .path
+                    .segments
+                    .last()
+                    .and_then(|s| self.definitions.constructors.get(&s.ident.to_string()))
+                    .and_then(|constructor| constructor.as_ref())
+                    .map(|constructor| constructor.bind(&call.args)),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(definition) = definition {
+            if depth < 4 {
+                return self.resolve_key(&definition, depth + 1);
+            }
+        }
+
         match expr {
             Expr::Lit(expr_lit) => match &expr_lit.lit {
                 Lit::Str(lit_str) => Some(lit_str.value()),
@@ -129,8 +342,8 @@ impl StorageVisitor {
                 _ => None,
             },
             Expr::Path(expr_path) => Some(quote!(#expr_path).to_string()),
-            Expr::Reference(reference) => Self::extract_key_value_expr(&reference.expr),
-            Expr::Paren(paren) => Self::extract_key_value_expr(&paren.expr),
+            Expr::Reference(reference) => self.resolve_key(&reference.expr, depth),
+            Expr::Paren(paren) => self.resolve_key(&paren.expr, depth),
             Expr::Call(call) => Some(quote!(#call).to_string()),
             Expr::MethodCall(method_call) => Some(quote!(#method_call).to_string()),
             Expr::Macro(expr_macro) => Some(quote!(#expr_macro).to_string()),
@@ -188,7 +401,7 @@ impl<'ast> Visit<'ast> for StorageVisitor {
         if i.method == "set" && i.args.len() >= 2 {
             let storage_type = Self::parse_storage_type_from_expr(&i.receiver);
             if storage_type != SorobanStorageType::Unknown {
-                if let Some(key) = Self::extract_key_value_expr(&i.args[0]) {
+                if let Some(key) = self.extract_key_value_expr(&i.args[0]) {
                     self.add_key(
                         key,
                         "storage::set".to_string(),
@@ -0,0 +1,1133 @@
+//! Storage layout extraction and upgrade-compatibility diffing.
+//!
+//! Upgrading a Soroban contract swaps its wasm but keeps every ledger entry
+//! the old code wrote, so the new code has to find those entries under the
+//! same keys, in the same storage tier, and decode them as the same types.
+//! [`extract_storage_layout`] records what a source file relies on:
+//!
+//! - every `#[contracttype]` enum and struct with its variants or fields, in
+//!   declaration order;
+//! - every storage key passed to `env.storage().<tier>()`, with its tier and
+//!   the value types it is written or read as.
+//!
+//! [`diff_storage_layouts`] compares the layouts of two versions and reports
+//! changes that strand or corrupt existing entries as an [`UpgradeRiskReport`]
+//! under [`UPGRADE_RISK`].  Additions — new variants appended to an enum, new
+//! keys, new types — are compatible and not reported.
+//!
+//! Keys are named by their path: `DataKey::Balance(user)` is recorded as
+//! `DataKey::Balance`, and a local bound to a key expression is resolved to
+//! it.  Value types come from the declared type of the value written, a
+//! `get::<K, V>` turbofish, or the annotated `let` or return type a read ends
+//! up in.
+
+use crate::contract_discovery::has_attr_named;
+use crate::finding_codes::{FindingSeverity, UPGRADE_RISK};
+use crate::storage_collision::SorobanStorageType;
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::{parse_str, Expr, Fields, File, Item, Pat, Type};
+
+/// Storage schema a contract version relies on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLayout {
+    /// `#[contracttype]` types, in declaration order.
+    pub types: Vec<LayoutType>,
+    /// Storage keys with the tier they live in, ordered by key.
+    pub keys: Vec<KeyUsage>,
+}
+
+/// A `#[contracttype]` enum or struct.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutType {
+    pub name: String,
+    pub kind: LayoutTypeKind,
+    /// Variants of an enum or fields of a struct, in declaration order.
+    pub members: Vec<LayoutMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutTypeKind {
+    Enum,
+    Struct,
+}
+
+/// An enum variant or struct field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutMember {
+    /// Variant or field name; the index for tuple-struct fields.
+    pub name: String,
+    /// Field types of a variant, or the single type of a field.
+    pub types: Vec<String>,
+    /// Explicit discriminant of an integer enum variant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discriminant: Option<i64>,
+}
+
+/// A storage key as used in one tier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyUsage {
+    /// Key path, e.g. `DataKey::Balance` or `symbol_short!("ADMIN")`.
+    pub key: String,
+    /// `instance`, `persistent` or `temporary`.
+    pub tier: String,
+    /// Types the value is written or read as, where known.
+    pub value_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// First line the key is used on.
+    pub line: usize,
+}
+
+impl StorageLayout {
+    /// Record `file` as the source of every type and key.
+    pub fn in_file(mut self, file: &str) -> Self {
+        for ty in &mut self.types {
+            ty.file = Some(file.to_string());
+        }
+        for key in &mut self.keys {
+            key.file = Some(file.to_string());
+        }
+        self
+    }
+
+    /// Add the layout of another source file of the same contract.  A type
+    /// declared twice keeps its first declaration.
+    pub fn merge(&mut self, other: StorageLayout) {
+        for ty in other.types {
+            if !self.types.iter().any(|t| t.name == ty.name) {
+                self.types.push(ty);
+            }
+        }
+        for usage in other.keys {
+            match self
+                .keys
+                .iter_mut()
+                .find(|k| k.key == usage.key && k.tier == usage.tier)
+            {
+                Some(existing) => {
+                    for ty in usage.value_types {
+                        if !existing.value_types.contains(&ty) {
+                            existing.value_types.push(ty);
+                        }
+                    }
+                    existing.value_types.sort();
+                }
+                None => self.keys.push(usage),
+            }
+        }
+        self.keys
+            .sort_by(|a, b| a.key.cmp(&b.key).then_with(|| a.tier.cmp(&b.tier)));
+    }
+
+    fn find_type(&self, name: &str) -> Option<&LayoutType> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    /// Tiers and value types per key.
+    fn keys_by_name(&self) -> BTreeMap<&str, Vec<&KeyUsage>> {
+        let mut keys: BTreeMap<&str, Vec<&KeyUsage>> = BTreeMap::new();
+        for usage in &self.keys {
+            keys.entry(usage.key.as_str()).or_default().push(usage);
+        }
+        keys
+    }
+}
+
+// ── Extraction ────────────────────────────────────────────────────────────────
+
+/// Extract the storage layout of one source file.
+pub fn extract_storage_layout(source: &str) -> Result<StorageLayout, syn::Error> {
+    let file = parse_str::<File>(source)?;
+    let types = file
+        .items
+        .iter()
+        .filter_map(|item| layout_type(item, "contracttype"))
+        .collect();
+
+    let mut collector = KeyCollector::default();
+    collector.visit_file(&file);
+    let keys = collector
+        .keys
+        .into_iter()
+        .map(|((key, tier), (value_types, line))| KeyUsage {
+            key,
+            tier: tier.to_string(),
+            value_types: value_types.into_iter().collect(),
+            file: None,
+            line,
+        })
+        .collect();
+    Ok(StorageLayout { types, keys })
+}
+
+/// Shape of an enum or struct carrying the `attr` attribute.
+pub(crate) fn layout_type(item: &Item, attr: &str) -> Option<LayoutType> {
+    match item {
+        Item::Enum(e) if has_attr_named(&e.attrs, attr) => {
+            let members = e
+                .variants
+                .iter()
+                .map(|v| LayoutMember {
+                    name: v.ident.to_string(),
+                    types: field_types(&v.fields),
+                    discriminant: v.discriminant.as_ref().and_then(|(_, expr)| {
+                        crate::constant_folding::fold_to_i128(expr)
+                            .and_then(|d| i64::try_from(d).ok())
+                    }),
+                })
+                .collect();
+            Some(LayoutType {
+                name: e.ident.to_string(),
+                kind: LayoutTypeKind::Enum,
+                members,
+                file: None,
+                line: e.ident.span().start().line,
+            })
+        }
+        Item::Struct(s) if has_attr_named(&s.attrs, attr) => {
+            let members = s
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| LayoutMember {
+                    name: f
+                        .ident
+                        .as_ref()
+                        .map_or_else(|| i.to_string(), |id| id.to_string()),
+                    types: vec![type_name(&f.ty)],
+                    discriminant: None,
+                })
+                .collect();
+            Some(LayoutType {
+                name: s.ident.to_string(),
+                kind: LayoutTypeKind::Struct,
+                members,
+                file: None,
+                line: s.ident.span().start().line,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn field_types(fields: &Fields) -> Vec<String> {
+    fields.iter().map(|f| type_name(&f.ty)).collect()
+}
+
+/// `Vec<Address>` for `Vec < Address >`, without references.
+pub(crate) fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Reference(r) => type_name(&r.elem),
+        _ => ty
+            .to_token_stream()
+            .to_string()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect(),
+    }
+}
+
+/// Locals and parameters of the function being visited.
+#[derive(Default)]
+struct FnScope {
+    /// Declared types of parameters and annotated locals.
+    types: HashMap<String, String>,
+    /// Initializers of locals, to resolve `let key = DataKey::X(..)`.
+    inits: HashMap<String, Expr>,
+}
+
+#[derive(Default)]
+struct KeyCollector {
+    scope: FnScope,
+    /// Value types and first line per key and tier.
+    keys: BTreeMap<(String, &'static str), (BTreeSet<String>, usize)>,
+}
+
+impl KeyCollector {
+    fn enter_fn(&mut self, sig: &syn::Signature, block: &syn::Block) {
+        let mut scope = FnScope::default();
+        for arg in &sig.inputs {
+            if let syn::FnArg::Typed(pt) = arg {
+                if let Pat::Ident(pi) = pt.pat.as_ref() {
+                    scope.types.insert(pi.ident.to_string(), type_name(&pt.ty));
+                }
+            }
+        }
+        let mut locals = LocalCollector(&mut scope);
+        locals.visit_block(block);
+        self.scope = scope;
+
+        // A read in tail position has the function's return type.
+        if let (syn::ReturnType::Type(_, ty), Some(syn::Stmt::Expr(tail, None))) =
+            (&sig.output, block.stmts.last())
+        {
+            self.record_read_as(tail, ty);
+        }
+    }
+
+    fn record(
+        &mut self,
+        key: String,
+        tier: SorobanStorageType,
+        value_type: Option<String>,
+        line: usize,
+    ) {
+        let entry = self
+            .keys
+            .entry((key, tier.as_str()))
+            .or_insert_with(|| (BTreeSet::new(), line));
+        entry.0.extend(value_type);
+        entry.1 = entry.1.min(line);
+    }
+
+    /// Record the type a storage read in `expr` is decoded as, when `expr`
+    /// ends up in a place of type `ty`.
+    fn record_read_as(&mut self, expr: &Expr, ty: &Type) {
+        let mut unwrapped = false;
+        let mut expr = expr;
+        loop {
+            match expr {
+                Expr::MethodCall(call)
+                    if matches!(
+                        call.method.to_string().as_str(),
+                        "unwrap" | "expect" | "unwrap_or" | "unwrap_or_default" | "unwrap_or_else"
+                    ) =>
+                {
+                    unwrapped = true;
+                    expr = &call.receiver;
+                }
+                Expr::Try(t) => {
+                    unwrapped = true;
+                    expr = &t.expr;
+                }
+                Expr::Paren(p) => expr = &p.expr,
+                _ => break,
+            }
+        }
+        let Expr::MethodCall(call) = expr else {
+            return;
+        };
+        if call.method != "get" {
+            return;
+        }
+        let Some(tier) = SorobanStorageType::of_receiver(&call.receiver) else {
+            return;
+        };
+        let Some(key) = call.args.first().and_then(|k| self.key_name(k, 0)) else {
+            return;
+        };
+        let value_type = if unwrapped {
+            type_name(ty)
+        } else {
+            match option_inner(ty) {
+                Some(inner) => type_name(inner),
+                None => return,
+            }
+        };
+        self.record(key, tier, Some(value_type), call.span().start().line);
+    }
+
+    /// Path naming the key `expr` builds.
+    fn key_name(&self, expr: &Expr, depth: usize) -> Option<String> {
+        match expr {
+            Expr::Reference(r) => self.key_name(&r.expr, depth),
+            Expr::Paren(p) => self.key_name(&p.expr, depth),
+            Expr::MethodCall(call) if call.method == "clone" => {
+                self.key_name(&call.receiver, depth)
+            }
+            Expr::Path(p) => {
+                if let Some(ident) = p.path.get_ident() {
+                    if let Some(init) = self.scope.inits.get(&ident.to_string()) {
+                        return if depth < 4 {
+                            self.key_name(init, depth + 1)
+                        } else {
+                            None
+                        };
+                    }
+                }
+                Some(path_name(&p.path))
+            }
+            Expr::Call(call) => match call.func.as_ref() {
+                Expr::Path(p) => Some(path_name(&p.path)),
+                _ => None,
+            },
+            Expr::Macro(m) => Some(format!(
+                "{}!({})",
+                path_name(&m.mac.path),
+                m.mac.tokens.to_string().replace(' ', "")
+            )),
+            Expr::Lit(lit) => Some(lit.to_token_stream().to_string()),
+            _ => None,
+        }
+    }
+
+    /// Declared type of the value `expr` evaluates to, when known.
+    fn value_type(&self, expr: &Expr, depth: usize) -> Option<String> {
+        match expr {
+            Expr::Reference(r) => self.value_type(&r.expr, depth),
+            Expr::Paren(p) => self.value_type(&p.expr, depth),
+            Expr::MethodCall(call) if call.method == "clone" => {
+                self.value_type(&call.receiver, depth)
+            }
+            Expr::Path(p) => {
+                let name = p.path.get_ident()?.to_string();
+                if let Some(ty) = self.scope.types.get(&name) {
+                    return Some(ty.clone());
+                }
+                let init = self.scope.inits.get(&name)?;
+                (depth < 4).then(|| self.value_type(init, depth + 1))?
+            }
+            Expr::Lit(lit) => match &lit.lit {
+                syn::Lit::Int(int) if !int.suffix().is_empty() => Some(int.suffix().to_string()),
+                syn::Lit::Bool(_) => Some("bool".to_string()),
+                _ => None,
+            },
+            Expr::Struct(s) => Some(path_name(&s.path)),
+            Expr::Cast(c) => Some(type_name(&c.ty)),
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for KeyCollector {
+    fn visit_item_fn(&mut self, f: &'ast syn::ItemFn) {
+        self.enter_fn(&f.sig, &f.block);
+        syn::visit::visit_item_fn(self, f);
+    }
+
+    fn visit_impl_item_fn(&mut self, f: &'ast syn::ImplItemFn) {
+        self.enter_fn(&f.sig, &f.block);
+        syn::visit::visit_impl_item_fn(self, f);
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if let (Pat::Type(typed), Some(init)) = (&local.pat, &local.init) {
+            self.record_read_as(&init.expr, &typed.ty);
+        }
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if let Some(tier) = SorobanStorageType::of_receiver(&call.receiver) {
+            let method = call.method.to_string();
+            let keyed = matches!(
+                method.as_str(),
+                "get" | "set" | "has" | "update" | "try_update" | "remove"
+            ) || (method == "extend_ttl" && tier != SorobanStorageType::Instance);
+            if let Some(key) = call
+                .args
+                .first()
+                .filter(|_| keyed)
+                .and_then(|k| self.key_name(k, 0))
+            {
+                let value_type = match method.as_str() {
+                    "set" => call.args.iter().nth(1).and_then(|v| self.value_type(v, 0)),
+                    "get" => turbofish_value_type(call),
+                    _ => None,
+                };
+                self.record(key, tier, value_type, call.span().start().line);
+            }
+        }
+        syn::visit::visit_expr_method_call(self, call);
+    }
+}
+
+/// Records the type and initializer of every local of a function body.
+struct LocalCollector<'a>(&'a mut FnScope);
+
+impl<'ast> Visit<'ast> for LocalCollector<'_> {
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        let (pat, ty) = match &local.pat {
+            Pat::Type(typed) => (typed.pat.as_ref(), Some(typed.ty.as_ref())),
+            pat => (pat, None),
+        };
+        if let Pat::Ident(pi) = pat {
+            let name = pi.ident.to_string();
+            if let Some(ty) = ty {
+                self.0.types.insert(name.clone(), type_name(ty));
+            }
+            if let Some(init) = &local.init {
+                self.0.inits.insert(name, (*init.expr).clone());
+            }
+        }
+        syn::visit::visit_local(self, local);
+    }
+
+    // Nested items have their own locals.
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
+
+/// `V` of `get::<K, V>(..)`.
+fn turbofish_value_type(call: &syn::ExprMethodCall) -> Option<String> {
+    let turbofish = call.turbofish.as_ref()?;
+    match turbofish.args.iter().nth(1)? {
+        syn::GenericArgument::Type(ty) => Some(type_name(ty)),
+        _ => None,
+    }
+}
+
+/// `T` of `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(p) = ty else {
+        return None;
+    };
+    let segment = p.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn path_name(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+// ── Diffing ───────────────────────────────────────────────────────────────────
+
+/// Kind of an upgrade-breaking layout change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutChangeKind {
+    TypeRemoved,
+    VariantRemoved,
+    VariantRenamed,
+    VariantReordered,
+    VariantFieldsChanged,
+    DiscriminantChanged,
+    FieldRemoved,
+    FieldAdded,
+    FieldRenamed,
+    FieldTypeChanged,
+    KeyRemoved,
+    KeyTierChanged,
+    ValueTypeChanged,
+}
+
+/// One change between two storage layouts that affects existing entries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutChange {
+    pub kind: LayoutChangeKind,
+    /// Type, `Type::Member` or key the change applies to.
+    pub item: String,
+    /// The old shape, when it can be stated in a few words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    /// The new shape, when it can be stated in a few words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+    pub severity: FindingSeverity,
+    pub message: String,
+    /// `file:line` in the new version, or in the old one for removals.
+    pub location: String,
+}
+
+/// Result of comparing the storage layouts of two contract versions.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeRiskReport {
+    /// Finding code every change is reported under.
+    pub code: &'static str,
+    /// No change affects existing entries.
+    pub compatible: bool,
+    pub changes: Vec<LayoutChange>,
+    pub old_layout: StorageLayout,
+    pub new_layout: StorageLayout,
+}
+
+/// Compare the layout an upgrade starts from with the one it installs.
+pub fn diff_storage_layouts(old: &StorageLayout, new: &StorageLayout) -> UpgradeRiskReport {
+    let mut changes = Vec::new();
+    // `Type::Variant` renames, so keys built from a renamed variant are
+    // compared under their new name.
+    let mut renamed: HashMap<String, String> = HashMap::new();
+
+    for old_ty in &old.types {
+        let Some(new_ty) = new.find_type(&old_ty.name) else {
+            changes.push(LayoutChange {
+                kind: LayoutChangeKind::TypeRemoved,
+                item: old_ty.name.clone(),
+                old: None,
+                new: None,
+                severity: FindingSeverity::Medium,
+                message: format!(
+                    "#[contracttype] {} was removed; entries that store it can no longer be \
+                     decoded",
+                    old_ty.name
+                ),
+                location: location(old_ty.file.as_deref(), old_ty.line),
+            });
+            continue;
+        };
+        match (old_ty.kind, new_ty.kind) {
+            (LayoutTypeKind::Enum, LayoutTypeKind::Enum) => {
+                diff_enum(old_ty, new_ty, &mut changes, &mut renamed)
+            }
+            _ => diff_struct(old_ty, new_ty, &mut changes),
+        }
+    }
+
+    diff_keys(old, new, &renamed, &mut changes);
+
+    UpgradeRiskReport {
+        code: UPGRADE_RISK,
+        compatible: changes.is_empty(),
+        changes,
+        old_layout: old.clone(),
+        new_layout: new.clone(),
+    }
+}
+
+fn location(file: Option<&str>, line: usize) -> String {
+    format!("{}:{}", file.unwrap_or("<source>"), line)
+}
+
+fn diff_enum(
+    old: &LayoutType,
+    new: &LayoutType,
+    changes: &mut Vec<LayoutChange>,
+    renamed: &mut HashMap<String, String>,
+) {
+    let at = location(new.file.as_deref(), new.line);
+    let change =
+        |kind, member: &str, old_shape, new_shape, severity, message: String| LayoutChange {
+            kind,
+            item: format!("{}::{}", old.name, member),
+            old: old_shape,
+            new: new_shape,
+            severity,
+            message,
+            location: at.clone(),
+        };
+    let new_names: BTreeSet<&str> = new.members.iter().map(|m| m.name.as_str()).collect();
+    let old_names: BTreeSet<&str> = old.members.iter().map(|m| m.name.as_str()).collect();
+
+    for (index, variant) in old.members.iter().enumerate() {
+        if let Some(current) = new.members.iter().find(|m| m.name == variant.name) {
+            if current.types != variant.types {
+                changes.push(change(
+                    LayoutChangeKind::VariantFieldsChanged,
+                    &variant.name,
+                    Some(format!("({})", variant.types.join(", "))),
+                    Some(format!("({})", current.types.join(", "))),
+                    FindingSeverity::High,
+                    format!(
+                        "{}::{} changed its fields from ({}) to ({}); stored keys and values \
+                         built from the old fields no longer decode",
+                        old.name,
+                        variant.name,
+                        variant.types.join(", "),
+                        current.types.join(", ")
+                    ),
+                ));
+            }
+            if let (Some(before), Some(after)) = (variant.discriminant, current.discriminant) {
+                if before != after {
+                    changes.push(change(
+                        LayoutChangeKind::DiscriminantChanged,
+                        &variant.name,
+                        Some(before.to_string()),
+                        Some(after.to_string()),
+                        FindingSeverity::High,
+                        format!(
+                            "{}::{} changed its value from {} to {}; stored values decode as a \
+                             different variant",
+                            old.name, variant.name, before, after
+                        ),
+                    ));
+                }
+            }
+            continue;
+        }
+        // Same position, same fields, and a name the old version did not
+        // have: the variant was renamed.
+        match new.members.get(index) {
+            Some(candidate)
+                if candidate.types == variant.types
+                    && !old_names.contains(candidate.name.as_str()) =>
+            {
+                renamed.insert(
+                    format!("{}::{}", old.name, variant.name),
+                    format!("{}::{}", old.name, candidate.name),
+                );
+                changes.push(change(
+                    LayoutChangeKind::VariantRenamed,
+                    &variant.name,
+                    Some(variant.name.clone()),
+                    Some(candidate.name.clone()),
+                    FindingSeverity::High,
+                    format!(
+                        "{}::{} was renamed to {}::{}; variants are encoded by name, so \
+                         entries stored under the old name are no longer found",
+                        old.name, variant.name, old.name, candidate.name
+                    ),
+                ));
+            }
+            _ => changes.push(change(
+                LayoutChangeKind::VariantRemoved,
+                &variant.name,
+                None,
+                None,
+                FindingSeverity::High,
+                format!(
+                    "{}::{} was removed; entries stored under it are orphaned and values \
+                     holding it no longer decode",
+                    old.name, variant.name
+                ),
+            )),
+        }
+    }
+
+    // Integer enums are encoded by value, so only their discriminants matter.
+    if old.members.iter().all(|m| m.discriminant.is_some())
+        && new.members.iter().all(|m| m.discriminant.is_some())
+    {
+        return;
+    }
+    let kept_old: Vec<&str> = old
+        .members
+        .iter()
+        .map(|m| m.name.as_str())
+        .filter(|name| new_names.contains(name))
+        .collect();
+    let kept_new: Vec<&str> = new
+        .members
+        .iter()
+        .map(|m| m.name.as_str())
+        .filter(|name| old_names.contains(name))
+        .collect();
+    for (position, name) in kept_old.iter().enumerate() {
+        let Some(moved_to) = kept_new.iter().position(|n| n == name) else {
+            continue;
+        };
+        if moved_to != position {
+            changes.push(change(
+                LayoutChangeKind::VariantReordered,
+                name,
+                Some(format!("position {}", position)),
+                Some(format!("position {}", moved_to)),
+                FindingSeverity::Medium,
+                format!(
+                    "{}::{} moved from position {} to {}; clients and tooling that rely on \
+                     the variant order of the contract spec decode it differently",
+                    old.name, name, position, moved_to
+                ),
+            ));
+        }
+    }
+}
+
+fn diff_struct(old: &LayoutType, new: &LayoutType, changes: &mut Vec<LayoutChange>) {
+    let at = location(new.file.as_deref(), new.line);
+    if old.kind != new.kind {
+        changes.push(LayoutChange {
+            kind: LayoutChangeKind::TypeRemoved,
+            item: old.name.clone(),
+            old: Some(format!("{:?}", old.kind).to_lowercase()),
+            new: Some(format!("{:?}", new.kind).to_lowercase()),
+            severity: FindingSeverity::High,
+            message: format!(
+                "{} changed from {:?} to {:?}; stored values no longer decode",
+                old.name, old.kind, new.kind
+            )
+            .to_lowercase(),
+            location: at,
+        });
+        return;
+    }
+    let change = |kind, field: &str, old_shape, new_shape, message: String| LayoutChange {
+        kind,
+        item: format!("{}::{}", old.name, field),
+        old: old_shape,
+        new: new_shape,
+        severity: FindingSeverity::High,
+        message,
+        location: at.clone(),
+    };
+    let old_names: BTreeSet<&str> = old.members.iter().map(|m| m.name.as_str()).collect();
+    let mut renamed_to = BTreeSet::new();
+
+    for (index, field) in old.members.iter().enumerate() {
+        let ty = field.types.join(", ");
+        if let Some(current) = new.members.iter().find(|m| m.name == field.name) {
+            if current.types != field.types {
+                let new_ty = current.types.join(", ");
+                changes.push(change(
+                    LayoutChangeKind::FieldTypeChanged,
+                    &field.name,
+                    Some(ty.clone()),
+                    Some(new_ty.clone()),
+                    format!(
+                        "{}.{} changed type from {} to {}; stored values no longer decode",
+                        old.name, field.name, ty, new_ty
+                    ),
+                ));
+            }
+            continue;
+        }
+        match new.members.get(index) {
+            Some(candidate)
+                if candidate.types == field.types
+                    && !old_names.contains(candidate.name.as_str()) =>
+            {
+                renamed_to.insert(candidate.name.as_str());
+                changes.push(change(
+                    LayoutChangeKind::FieldRenamed,
+                    &field.name,
+                    Some(field.name.clone()),
+                    Some(candidate.name.clone()),
+                    format!(
+                        "{}.{} was renamed to {}; struct fields are encoded by name, so stored \
+                         values no longer decode",
+                        old.name, field.name, candidate.name
+                    ),
+                ));
+            }
+            _ => changes.push(change(
+                LayoutChangeKind::FieldRemoved,
+                &field.name,
+                Some(ty),
+                None,
+                format!(
+                    "{}.{} was removed; stored values still carry it and no longer decode",
+                    old.name, field.name
+                ),
+            )),
+        }
+    }
+
+    for field in &new.members {
+        if old_names.contains(field.name.as_str()) || renamed_to.contains(field.name.as_str()) {
+            continue;
+        }
+        changes.push(change(
+            LayoutChangeKind::FieldAdded,
+            &field.name,
+            None,
+            Some(field.types.join(", ")),
+            format!(
+                "{}.{} was added; values stored by the old version lack it and no longer \
+                 decode",
+                old.name, field.name
+            ),
+        ));
+    }
+}
+
+fn diff_keys(
+    old: &StorageLayout,
+    new: &StorageLayout,
+    renamed: &HashMap<String, String>,
+    changes: &mut Vec<LayoutChange>,
+) {
+    let new_keys = new.keys_by_name();
+    for (key, old_uses) in old.keys_by_name() {
+        let current = renamed.get(key).map(String::as_str).unwrap_or(key);
+        let old_at = location(old_uses[0].file.as_deref(), old_uses[0].line);
+        let old_tiers: Vec<&str> = old_uses.iter().map(|u| u.tier.as_str()).collect();
+        let Some(new_uses) = new_keys.get(current) else {
+            changes.push(LayoutChange {
+                kind: LayoutChangeKind::KeyRemoved,
+                item: key.to_string(),
+                old: Some(old_tiers.join(", ")),
+                new: None,
+                severity: FindingSeverity::Medium,
+                message: format!(
+                    "Storage key {} ({}) is no longer used; its existing entries are orphaned",
+                    key,
+                    old_tiers.join(", ")
+                ),
+                location: old_at,
+            });
+            continue;
+        };
+        let new_at = location(new_uses[0].file.as_deref(), new_uses[0].line);
+        let new_tiers: Vec<&str> = new_uses.iter().map(|u| u.tier.as_str()).collect();
+        if !old_tiers.iter().any(|tier| new_tiers.contains(tier)) {
+            changes.push(LayoutChange {
+                kind: LayoutChangeKind::KeyTierChanged,
+                item: key.to_string(),
+                old: Some(old_tiers.join(", ")),
+                new: Some(new_tiers.join(", ")),
+                severity: FindingSeverity::High,
+                message: format!(
+                    "Storage key {} moved from {} to {} storage; entries written by the old \
+                     version are not found in the new tier",
+                    key,
+                    old_tiers.join(", "),
+                    new_tiers.join(", ")
+                ),
+                location: new_at,
+            });
+            continue;
+        }
+        for old_use in &old_uses {
+            let Some(new_use) = new_uses.iter().find(|u| u.tier == old_use.tier) else {
+                continue;
+            };
+            let disjoint = !old_use.value_types.is_empty()
+                && !new_use.value_types.is_empty()
+                && !old_use
+                    .value_types
+                    .iter()
+                    .any(|ty| new_use.value_types.contains(ty));
+            if disjoint {
+                let (before, after) = (
+                    old_use.value_types.join(" | "),
+                    new_use.value_types.join(" | "),
+                );
+                changes.push(LayoutChange {
+                    kind: LayoutChangeKind::ValueTypeChanged,
+                    item: key.to_string(),
+                    old: Some(before.clone()),
+                    new: Some(after.clone()),
+                    severity: FindingSeverity::High,
+                    message: format!(
+                        "Value of storage key {} ({}) changed type from {} to {}; entries \
+                         written by the old version no longer decode",
+                        key, old_use.tier, before, after
+                    ),
+                    location: location(new_use.file.as_deref(), new_use.line),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = r#"
+        #[contracttype]
+        pub enum DataKey {
+            Admin,
+            Balance(Address),
+            Allowance(Address, Address),
+        }
+
+        #[contracttype]
+        pub struct Config {
+            pub fee: u32,
+            pub owner: Address,
+        }
+
+        #[contractimpl]
+        impl Token {
+            pub fn init(env: Env, admin: Address, config: Config) {
+                env.storage().instance().set(&DataKey::Admin, &admin);
+                env.storage().instance().set(&symbol_short!("CFG"), &config);
+            }
+
+            pub fn balance(env: Env, id: Address) -> i128 {
+                let key = DataKey::Balance(id);
+                env.storage().persistent().get(&key).unwrap_or(0)
+            }
+
+            pub fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+                let amount: Option<i128> = env
+                    .storage()
+                    .temporary()
+                    .get(&DataKey::Allowance(from, spender));
+                amount.unwrap_or(0)
+            }
+        }
+    "#;
+
+    fn layout(source: &str) -> StorageLayout {
+        extract_storage_layout(source).unwrap()
+    }
+
+    fn kinds(report: &UpgradeRiskReport) -> Vec<(LayoutChangeKind, &str)> {
+        report
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.item.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_types_keys_tiers_and_value_types() {
+        let layout = layout(V1);
+        let data_key = layout.find_type("DataKey").unwrap();
+        assert_eq!(data_key.kind, LayoutTypeKind::Enum);
+        let variants: Vec<_> = data_key.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(variants, ["Admin", "Balance", "Allowance"]);
+        assert_eq!(data_key.members[2].types, ["Address", "Address"]);
+        assert_eq!(
+            layout.find_type("Config").unwrap().members[0].types,
+            ["u32"]
+        );
+
+        let keys: Vec<_> = layout
+            .keys
+            .iter()
+            .map(|k| (k.key.as_str(), k.tier.as_str(), k.value_types.join("|")))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("DataKey::Admin", "instance", "Address".to_string()),
+                ("DataKey::Allowance", "temporary", "i128".to_string()),
+                ("DataKey::Balance", "persistent", "i128".to_string()),
+                ("symbol_short!(\"CFG\")", "instance", "Config".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_layouts_are_compatible() {
+        let report = diff_storage_layouts(&layout(V1), &layout(V1));
+        assert!(report.compatible);
+        assert!(report.changes.is_empty());
+        assert_eq!(report.code, "S010");
+    }
+
+    #[test]
+    fn appending_variants_and_keys_is_compatible() {
+        let v2 = V1
+            .replace(
+                "Allowance(Address, Address),",
+                "Allowance(Address, Address),\n            Paused,",
+            )
+            .replace(
+                "pub fn init(env: Env, admin: Address, config: Config) {",
+                "pub fn init(env: Env, admin: Address, config: Config) {\n                env.storage().instance().set(&DataKey::Paused, &false);",
+            );
+        let report = diff_storage_layouts(&layout(V1), &layout(&v2));
+        assert!(report.compatible, "{:?}", report.changes);
+    }
+
+    #[test]
+    fn flags_renamed_and_reordered_variants() {
+        let renamed = V1.replace("Balance(", "Balances(");
+        let report = diff_storage_layouts(&layout(V1), &layout(&renamed));
+        // The key follows the rename instead of being reported as removed.
+        assert_eq!(
+            kinds(&report),
+            [(LayoutChangeKind::VariantRenamed, "DataKey::Balance")]
+        );
+        assert_eq!(report.changes[0].new.as_deref(), Some("Balances"));
+        assert_eq!(report.changes[0].severity, FindingSeverity::High);
+
+        let reordered = V1.replace(
+            "Admin,\n            Balance(Address),",
+            "Balance(Address),\n            Admin,",
+        );
+        let report = diff_storage_layouts(&layout(V1), &layout(&reordered));
+        assert_eq!(
+            kinds(&report),
+            [
+                (LayoutChangeKind::VariantReordered, "DataKey::Admin"),
+                (LayoutChangeKind::VariantReordered, "DataKey::Balance"),
+            ]
+        );
+        assert_eq!(report.changes[0].severity, FindingSeverity::Medium);
+    }
+
+    #[test]
+    fn flags_removed_variants_and_orphaned_keys() {
+        let v2 = V1
+            .replace("Allowance(Address, Address),", "")
+            .replace(".get(&DataKey::Allowance(from, spender));", ".get(&from);");
+        let report = diff_storage_layouts(&layout(V1), &layout(&v2));
+        let kinds = kinds(&report);
+        assert!(kinds.contains(&(LayoutChangeKind::VariantRemoved, "DataKey::Allowance")));
+        assert!(kinds.contains(&(LayoutChangeKind::KeyRemoved, "DataKey::Allowance")));
+    }
+
+    #[test]
+    fn flags_value_type_and_tier_changes() {
+        let v2 = V1
+            .replace(
+                "pub fn balance(env: Env, id: Address) -> i128 {",
+                "pub fn balance(env: Env, id: Address) -> u64 {",
+            )
+            .replace(".temporary()", ".persistent()");
+        let report = diff_storage_layouts(&layout(V1), &layout(&v2));
+        assert_eq!(
+            kinds(&report),
+            [
+                (LayoutChangeKind::KeyTierChanged, "DataKey::Allowance"),
+                (LayoutChangeKind::ValueTypeChanged, "DataKey::Balance"),
+            ]
+        );
+        assert_eq!(report.changes[1].old.as_deref(), Some("i128"));
+        assert_eq!(report.changes[1].new.as_deref(), Some("u64"));
+    }
+
+    #[test]
+    fn flags_struct_field_changes() {
+        let v2 = V1.replace(
+            "pub fee: u32,\n            pub owner: Address,",
+            "pub fee: u64,\n            pub admin: Address,\n            pub paused: bool,",
+        );
+        let report = diff_storage_layouts(&layout(V1), &layout(&v2));
+        assert_eq!(
+            kinds(&report),
+            [
+                (LayoutChangeKind::FieldTypeChanged, "Config::fee"),
+                (LayoutChangeKind::FieldRenamed, "Config::owner"),
+                (LayoutChangeKind::FieldAdded, "Config::paused"),
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_enums_compare_discriminants_not_order() {
+        let v1 = "#[contracttype] pub enum Status { Active = 1, Frozen = 2 }";
+        let swapped = "#[contracttype] pub enum Status { Frozen = 2, Active = 1 }";
+        assert!(diff_storage_layouts(&layout(v1), &layout(swapped)).compatible);
+
+        let renumbered = "#[contracttype] pub enum Status { Active = 1, Frozen = 3 }";
+        let report = diff_storage_layouts(&layout(v1), &layout(renumbered));
+        assert_eq!(
+            kinds(&report),
+            [(LayoutChangeKind::DiscriminantChanged, "Status::Frozen")]
+        );
+    }
+
+    #[test]
+    fn merge_combines_files_and_records_their_names() {
+        let mut merged = layout(V1).in_file("src/lib.rs");
+        merged.merge(
+            layout(
+                r#"
+                pub fn bump(env: &Env) {
+                    env.storage().persistent().set(&DataKey::Counter, &1u32);
+                }
+                "#,
+            )
+            .in_file("src/storage.rs"),
+        );
+        let counter = merged
+            .keys
+            .iter()
+            .find(|k| k.key == "DataKey::Counter")
+            .unwrap();
+        assert_eq!(counter.value_types, ["u32"]);
+        assert_eq!(counter.file.as_deref(), Some("src/storage.rs"));
+        assert_eq!(merged.types[0].file.as_deref(), Some("src/lib.rs"));
+    }
+
+    #[test]
+    fn invalid_source_is_an_error() {
+        assert!(extract_storage_layout("not valid rust {{{{").is_err());
+    }
+}
@@ -0,0 +1,545 @@
+//! Config-driven finding suppressions.
+//!
+//! Suppressions are declared in `.sanctify.toml` under a `[suppressions]`
+//! table keyed by finding code (this is the shape `sanctifier suppress`
+//! writes):
+//!
+//! ```toml
+//! [suppressions]
+//! S003 = [{ file = "src/lib.rs", line = 42, reason = "amount is bounded by caller" }]
+//! ```
+//!
+//! A finding is suppressed when its code matches, its file path ends with the
+//! configured `file`, and its line is within [`LINE_DRIFT_TOLERANCE`] lines of
+//! the configured `line`.  The tolerance keeps suppressions stable across
+//! small edits above the finding (an added import, a reworded doc comment).
+//!
+//! Suppressions can also live next to the code.  [`InlineSuppressions`]
+//! understands two forms, each naming rules (`auth_gap`) or codes (`S003`):
+//!
+//! ```rust,ignore
+//! #[allow(sanctifier::auth_gap)]
+//! pub fn set_admin(env: Env, admin: Address) { /* ... */ }
+//!
+//! // sanctifier-ignore-next-line S003: supply is capped at MAX_SUPPLY
+//! let total = supply + amount;
+//! ```
+//!
+//! [`RuleRegistry::run_all`](crate::rules::RuleRegistry::run_all) applies them
+//! to every registered rule and reports stale ones as `unused_suppression`.
+
+use crate::finding_codes;
+use crate::rules::{RuleViolation, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// Maximum distance (in lines) between a suppression and the finding it covers.
+pub const LINE_DRIFT_TOLERANCE: usize = 3;
+
+/// A single `[suppressions]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    /// Path of the file the finding lives in, relative to the project root.
+    pub file: String,
+    /// 1-based line the finding was reported on.
+    pub line: usize,
+    /// Why the finding is acceptable. Surfaced in JSON and SARIF output.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Suppressions grouped by finding code (`S001`, `S003`, …).
+pub type SuppressionMap = BTreeMap<String, Vec<Suppression>>;
+
+/// A rule violation removed from the output by a matching suppression.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressedViolation {
+    /// File the violation was reported in.
+    pub file: String,
+    /// Finding code the suppression matched on.
+    pub code: String,
+    /// Justification copied from the matching suppression.
+    pub reason: String,
+    /// The original violation.
+    pub violation: RuleViolation,
+}
+
+/// Return the suppression covering `code` at `file:line`, if any.
+///
+/// Findings without a known line are never suppressed: a file-wide match
+/// would silently hide unrelated findings of the same code.
+pub fn find_suppression<'a>(
+    suppressions: &'a SuppressionMap,
+    code: &str,
+    file: &str,
+    line: Option<usize>,
+) -> Option<&'a Suppression> {
+    let line = line?;
+    suppressions
+        .get(code)?
+        .iter()
+        .find(|s| paths_match(file, &s.file) && s.line.abs_diff(line) <= LINE_DRIFT_TOLERANCE)
+}
+
+/// Split `violations` for `file` into those that remain and those that are
+/// covered by an entry in `suppressions`.
+pub fn partition_violations(
+    suppressions: &SuppressionMap,
+    file: &str,
+    violations: Vec<RuleViolation>,
+) -> (Vec<RuleViolation>, Vec<SuppressedViolation>) {
+    if suppressions.is_empty() {
+        return (violations, vec![]);
+    }
+    let mut kept = Vec::with_capacity(violations.len());
+    let mut suppressed = Vec::new();
+    for v in violations {
+        match find_suppression(suppressions, &v.code, file, v.line()) {
+            Some(s) => suppressed.push(SuppressedViolation {
+                file: file.to_string(),
+                code: v.code.clone(),
+                reason: s.reason.clone(),
+                violation: v,
+            }),
+            None => kept.push(v),
+        }
+    }
+    (kept, suppressed)
+}
+
+/// Extract the trailing line number from a location string such as
+/// `"transfer:42"`, `"src/lib.rs:transfer:42"` or `"src/lib.rs:line 42"`.
+pub fn line_from_location(location: &str) -> Option<usize> {
+    let last = location.rsplit(':').next()?.trim();
+    last.strip_prefix("line ").unwrap_or(last).parse().ok()
+}
+
+/// Compare two paths, ignoring separator style and a leading `./`.  The
+/// configured path may be a suffix of the reported one (reported paths are
+/// often absolute or prefixed with the scan root).
+fn paths_match(reported: &str, configured: &str) -> bool {
+    let reported = normalize(reported);
+    let configured = normalize(configured);
+    if configured.is_empty() {
+        return false;
+    }
+    reported == configured
+        || reported
+            .strip_suffix(configured.as_str())
+            .is_some_and(|prefix| prefix.ends_with('/'))
+}
+
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+// ── Inline suppressions ───────────────────────────────────────────────────────
+
+/// Rule name carried by warnings about inline suppressions that matched nothing.
+pub const UNUSED_SUPPRESSION_RULE: &str = "unused_suppression";
+
+const IGNORE_NEXT_LINE: &str = "sanctifier-ignore-next-line";
+
+/// Older attribute spellings that map onto a built-in rule name.
+const RULE_ALIASES: &[(&str, &str)] = &[("truncate", "truncation_bounds")];
+
+/// One `#[allow(sanctifier::…)]` attribute or `// sanctifier-ignore-next-line`
+/// comment found in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineSuppression {
+    /// Rule names or finding codes covered. Empty means every rule.
+    pub targets: Vec<String>,
+    /// 1-based line the directive is written on.
+    pub line: usize,
+    /// First and last line (inclusive) the directive applies to.
+    pub covers: (usize, usize),
+    /// Function the attribute is attached to. Lets findings that only report
+    /// a function name (no line) be matched.
+    pub item: Option<String>,
+    /// Text after the `:` in a comment directive.
+    pub reason: Option<String>,
+}
+
+impl InlineSuppression {
+    fn matches(&self, v: &RuleViolation) -> bool {
+        self.covers_rule(&v.rule_name) && self.covers_location(v)
+    }
+
+    fn covers_rule(&self, rule_name: &str) -> bool {
+        if self.targets.is_empty() {
+            return true;
+        }
+        let code = finding_codes::code_for_rule(rule_name);
+        self.targets
+            .iter()
+            .any(|t| t == rule_name || Some(t.as_str()) == code)
+    }
+
+    fn covers_location(&self, v: &RuleViolation) -> bool {
+        match v.line() {
+            Some(line) => (self.covers.0..=self.covers.1).contains(&line),
+            None => self.item.as_deref() == Some(v.location.as_str()),
+        }
+    }
+}
+
+/// All inline suppressions declared in one source file.
+#[derive(Debug, Clone, Default)]
+pub struct InlineSuppressions {
+    directives: Vec<InlineSuppression>,
+}
+
+impl InlineSuppressions {
+    /// Collect every inline suppression in `source`.  Comment directives are
+    /// found even when the file does not parse; attributes need a valid AST.
+    pub fn parse(source: &str) -> Self {
+        if !source.contains("sanctifier") {
+            return Self::default();
+        }
+        let mut directives = parse_ignore_comments(source);
+        if let Ok(file) = syn::parse_file(source) {
+            let mut collector = AllowCollector::default();
+            collector.visit_file(&file);
+            directives.extend(collector.directives);
+        }
+        directives.sort_by_key(|d| d.line);
+        Self { directives }
+    }
+
+    /// `true` when the file declares no inline suppressions.
+    pub fn is_empty(&self) -> bool {
+        self.directives.is_empty()
+    }
+
+    /// The parsed directives, ordered by line.
+    pub fn directives(&self) -> &[InlineSuppression] {
+        &self.directives
+    }
+
+    /// Drop every violation covered by a directive.  Also returns, for each
+    /// directive, whether it suppressed at least one violation.
+    pub fn filter(&self, violations: Vec<RuleViolation>) -> (Vec<RuleViolation>, Vec<bool>) {
+        let mut used = vec![false; self.directives.len()];
+        let kept = violations
+            .into_iter()
+            .filter(|v| {
+                let mut suppressed = false;
+                for (i, d) in self.directives.iter().enumerate() {
+                    if d.matches(v) {
+                        used[i] = true;
+                        suppressed = true;
+                    }
+                }
+                !suppressed
+            })
+            .collect();
+        (kept, used)
+    }
+
+    /// Warnings for directives that suppressed nothing.  Only directives that
+    /// target a rule in `ran` (or every rule) are reported, so a suppression
+    /// for a rule that is disabled or loaded elsewhere is not flagged.
+    pub fn unused(&self, used: &[bool], ran: &[&str]) -> Vec<RuleViolation> {
+        self.directives
+            .iter()
+            .zip(used)
+            .filter(|(d, used)| !**used && targets_ran(d, ran))
+            .map(|(d, _)| {
+                let what = if d.targets.is_empty() {
+                    String::new()
+                } else {
+                    format!(" for `{}`", d.targets.join(", "))
+                };
+                RuleViolation::new(
+                    UNUSED_SUPPRESSION_RULE,
+                    Severity::Warning,
+                    format!("Inline suppression{} does not match any finding", what),
+                    format!("line {}", d.line),
+                )
+                .with_suggestion("Remove the stale suppression".to_string())
+            })
+            .collect()
+    }
+}
+
+fn targets_ran(d: &InlineSuppression, ran: &[&str]) -> bool {
+    d.targets.is_empty()
+        || d.targets.iter().any(|t| {
+            ran.iter()
+                .any(|r| r == t || finding_codes::code_for_rule(r) == Some(t.as_str()))
+        })
+}
+
+fn resolve_alias(target: &str) -> String {
+    RULE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == target)
+        .map_or(target, |(_, rule)| rule)
+        .to_string()
+}
+
+/// Find `// sanctifier-ignore-next-line [targets][: reason]` comments.  The
+/// directive covers the next line that is neither blank nor a comment, so
+/// several directives can be stacked above one statement.
+fn parse_ignore_comments(source: &str) -> Vec<InlineSuppression> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut out = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(comment) = line.trim_start().strip_prefix("//") else {
+            continue;
+        };
+        let Some(rest) = comment.trim_start().strip_prefix(IGNORE_NEXT_LINE) else {
+            continue;
+        };
+        if !(rest.is_empty() || rest.starts_with(char::is_whitespace) || rest.starts_with(':')) {
+            continue;
+        }
+        let (targets, reason) = match rest.split_once(':') {
+            Some((t, r)) => (t, Some(r.trim().to_string()).filter(|r| !r.is_empty())),
+            None => (rest, None),
+        };
+        let target = lines
+            .iter()
+            .enumerate()
+            .skip(idx + 1)
+            .find(|(_, l)| {
+                let l = l.trim();
+                !l.is_empty() && !l.starts_with("//")
+            })
+            .map_or(idx + 2, |(i, _)| i + 1);
+        out.push(InlineSuppression {
+            targets: targets
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|t| !t.is_empty())
+                .map(resolve_alias)
+                .collect(),
+            line: idx + 1,
+            covers: (target, target),
+            item: None,
+            reason,
+        });
+    }
+    out
+}
+
+/// Collects `#[allow(sanctifier::…)]` on items and `#![allow(sanctifier::…)]`
+/// at the top of the file.
+#[derive(Default)]
+struct AllowCollector {
+    directives: Vec<InlineSuppression>,
+}
+
+impl AllowCollector {
+    fn record(&mut self, attrs: &[syn::Attribute], covers: (usize, usize), item: Option<String>) {
+        for attr in attrs {
+            if !attr.path().is_ident("allow") {
+                continue;
+            }
+            let Ok(paths) =
+                attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)
+            else {
+                continue;
+            };
+            let targets: Vec<String> = paths
+                .iter()
+                .filter(|p| p.segments.len() == 2 && p.segments[0].ident == "sanctifier")
+                .map(|p| resolve_alias(&p.segments[1].ident.to_string()))
+                .collect();
+            if !targets.is_empty() {
+                self.directives.push(InlineSuppression {
+                    targets,
+                    line: attr.span().start().line,
+                    covers,
+                    item: item.clone(),
+                    reason: None,
+                });
+            }
+        }
+    }
+}
+
+fn line_range(span: proc_macro2::Span) -> (usize, usize) {
+    (span.start().line, span.end().line)
+}
+
+impl<'ast> Visit<'ast> for AllowCollector {
+    fn visit_file(&mut self, node: &'ast syn::File) {
+        self.record(&node.attrs, (1, usize::MAX), None);
+        syn::visit::visit_file(self, node);
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let item = Some(node.sig.ident.to_string());
+        self.record(&node.attrs, line_range(node.span()), item);
+        syn::visit::visit_item_fn(self, node);
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        let item = Some(node.sig.ident.to_string());
+        self.record(&node.attrs, line_range(node.span()), item);
+        syn::visit::visit_impl_item_fn(self, node);
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        self.record(&node.attrs, line_range(node.span()), None);
+        syn::visit::visit_item_impl(self, node);
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        self.record(&node.attrs, line_range(node.span()), None);
+        syn::visit::visit_item_mod(self, node);
+    }
+
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        let item = Some(node.ident.to_string());
+        self.record(&node.attrs, line_range(node.span()), item);
+        syn::visit::visit_item_struct(self, node);
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        let item = Some(node.ident.to_string());
+        self.record(&node.attrs, line_range(node.span()), item);
+        syn::visit::visit_item_enum(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(code: &str, file: &str, line: usize) -> SuppressionMap {
+        let mut m = SuppressionMap::new();
+        m.insert(
+            code.to_string(),
+            vec![Suppression {
+                file: file.to_string(),
+                line,
+                reason: "reviewed".to_string(),
+            }],
+        );
+        m
+    }
+
+    #[test]
+    fn matches_with_small_line_drift() {
+        let m = map("S003", "src/lib.rs", 10);
+        assert!(find_suppression(&m, "S003", "src/lib.rs", Some(12)).is_some());
+        assert!(find_suppression(&m, "S003", "src/lib.rs", Some(14)).is_none());
+        assert!(find_suppression(&m, "S001", "src/lib.rs", Some(10)).is_none());
+        assert!(find_suppression(&m, "S003", "src/lib.rs", None).is_none());
+    }
+
+    #[test]
+    fn matches_path_suffix_on_component_boundary() {
+        let m = map("S003", "./src/lib.rs", 10);
+        assert!(find_suppression(&m, "S003", "/work/proj/src/lib.rs", Some(10)).is_some());
+        assert!(find_suppression(&m, "S003", "C:\\proj\\src\\lib.rs", Some(10)).is_some());
+        assert!(find_suppression(&m, "S003", "/work/proj/xsrc/lib.rs", Some(10)).is_none());
+    }
+
+    #[test]
+    fn extracts_line_from_location_strings() {
+        assert_eq!(line_from_location("transfer:42"), Some(42));
+        assert_eq!(line_from_location("src/lib.rs:transfer:7"), Some(7));
+        assert_eq!(line_from_location("src/lib.rs:line 9"), Some(9));
+        assert_eq!(line_from_location("src/lib.rs:transfer"), None);
+    }
+
+    #[test]
+    fn partition_moves_matching_violations() {
+        let m = map("S003", "src/lib.rs", 7);
+        let violations = vec![
+            RuleViolation::new(
+                "arithmetic_overflow",
+                Severity::Warning,
+                "overflow".to_string(),
+                "add:8".to_string(),
+            ),
+            RuleViolation::new(
+                "arithmetic_overflow",
+                Severity::Warning,
+                "overflow".to_string(),
+                "add:30".to_string(),
+            ),
+        ];
+        let (kept, suppressed) = partition_violations(&m, "src/lib.rs", violations);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].code, "S003");
+        assert_eq!(suppressed[0].reason, "reviewed");
+    }
+
+    fn violation(rule: &str, location: &str) -> RuleViolation {
+        RuleViolation::new(
+            rule,
+            Severity::Warning,
+            "finding".to_string(),
+            location.to_string(),
+        )
+    }
+
+    #[test]
+    fn ignore_comment_covers_next_code_line() {
+        let source = "fn f() {\n    // sanctifier-ignore-next-line S003: bounded\n\n    let x = a + b;\n    let y = a + b;\n}\n";
+        let inline = InlineSuppressions::parse(source);
+        assert_eq!(inline.directives().len(), 1);
+        let d = &inline.directives()[0];
+        assert_eq!(d.targets, vec!["S003"]);
+        assert_eq!(d.covers, (4, 4));
+        assert_eq!(d.reason.as_deref(), Some("bounded"));
+
+        let (kept, used) = inline.filter(vec![
+            violation("arithmetic_overflow", "f:4"),
+            violation("arithmetic_overflow", "f:5"),
+            violation("panic_detection", "f:4"),
+        ]);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(used, vec![true]);
+    }
+
+    #[test]
+    fn allow_attribute_covers_item_by_line_and_name() {
+        let source = r#"
+#[allow(sanctifier::panic_detection, sanctifier::truncate)]
+fn risky() {
+    panic!("boom");
+}
+"#;
+        let inline = InlineSuppressions::parse(source);
+        assert_eq!(inline.directives().len(), 1);
+        assert_eq!(
+            inline.directives()[0].targets,
+            vec!["panic_detection", "truncation_bounds"]
+        );
+        let (kept, _) = inline.filter(vec![
+            violation("panic_detection", "risky"),
+            violation("truncation_bounds", "risky:4"),
+            violation("panic_detection", "other"),
+        ]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].location, "other");
+    }
+
+    #[test]
+    fn unused_directives_are_reported_for_rules_that_ran() {
+        let source = "// sanctifier-ignore-next-line auth_gap\nfn a() {}\n// sanctifier-ignore-next-line my_yaml_rule\nfn b() {}\n";
+        let inline = InlineSuppressions::parse(source);
+        let (_, used) = inline.filter(vec![]);
+        let warnings = inline.unused(&used, &["auth_gap"]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].rule_name, UNUSED_SUPPRESSION_RULE);
+        assert_eq!(warnings[0].location, "line 1");
+    }
+
+    #[test]
+    fn reason_defaults_to_empty() {
+        let m: SuppressionMap =
+            serde_json::from_str(r#"{"S004":[{"file":"src/lib.rs","line":3}]}"#).unwrap();
+        assert_eq!(m["S004"][0].line, 3);
+        assert!(m["S004"][0].reason.is_empty());
+    }
+}
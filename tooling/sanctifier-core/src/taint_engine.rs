@@ -1,4 +1,4 @@
-//! Intra-procedural taint analysis on top of [`crate::cfg`].
+//! Taint analysis on top of [`crate::cfg`].
 //!
 //! Identifies **sources** (untrusted parameters passed into a contract entry
 //! point) and **sinks** (privileged operations: storage writes and external
@@ -15,6 +15,12 @@
 //!   affecting `y`, since each variable's taint is tracked independently and
 //!   blocks are processed in their own statement order.
 //!
+//! The dataflow itself runs over one function body.  Given a
+//! [`CallSummaries`] in the [`TaintContext`], a call to another summarised
+//! function is treated through that function's summary: a tainted
+//! argument bound to a parameter that reaches a sink is reported at the call,
+//! and a callee that calls `require_auth` authorizes the rest of the path.
+//!
 //! # Soundness note
 //!
 //! This is a best-effort static analysis, not a soundness-certified one. The
@@ -24,6 +30,8 @@
 //! This matches the precision level of the rest of the rule suite.
 
 use crate::cfg::{BasicBlock, BlockStmt, Cfg};
+use crate::interprocedural::CallSummaries;
+use crate::rules::SourceSpan;
 use std::collections::{HashSet, VecDeque};
 use syn::spanned::Spanned;
 use syn::{Expr, Pat};
@@ -39,8 +47,36 @@ pub struct TaintFinding {
     pub var: String,
     /// The sink method name (e.g. `"set"`, `"invoke_contract"`).
     pub sink: String,
+    /// The summarised function the value was passed to when the sink is
+    /// reached inside a callee rather than in the analysed body.
+    pub via: Option<String>,
     /// Source line of the sink call, if available.
     pub line: usize,
+    /// Span of the sink call.
+    pub span: SourceSpan,
+}
+
+impl TaintFinding {
+    /// The sink, qualified with the callee it was reached through.
+    pub fn sink_description(&self) -> String {
+        match &self.via {
+            Some(callee) => format!("{} via {}", self.sink, callee),
+            None => self.sink.clone(),
+        }
+    }
+}
+
+/// What the analysis knows beyond the body being analysed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaintContext<'a> {
+    /// Method names treated as sinks alongside storage writes and external
+    /// calls.
+    pub extra_sinks: &'a [String],
+    /// Summaries of the functions the body may call.
+    pub summaries: Option<&'a CallSummaries>,
+    /// Self type of the `impl` the body belongs to, used to resolve
+    /// `Self::helper(..)` calls.
+    pub owner: Option<&'a str>,
 }
 
 /// Runs taint analysis over `body`, seeding the analysis with `sources` (the
@@ -50,6 +86,15 @@ pub struct TaintFinding {
 /// that point in the CFG (i.e. some `require_auth*` call precedes it on every
 /// path the dataflow fixed point considers reachable).
 pub fn analyze(body: &syn::Block, sources: HashSet<String>) -> Vec<TaintFinding> {
+    analyze_with(body, sources, TaintContext::default())
+}
+
+/// Like [`analyze`], with extra sinks and callee summaries from `ctx`.
+pub fn analyze_with(
+    body: &syn::Block,
+    sources: HashSet<String>,
+    ctx: TaintContext<'_>,
+) -> Vec<TaintFinding> {
     let cfg = Cfg::build(body);
     let n = cfg.block_count();
     if n == 0 {
@@ -61,7 +106,7 @@ pub fn analyze(body: &syn::Block, sources: HashSet<String>) -> Vec<TaintFinding>
 
     let mut worklist: VecDeque<usize> = (0..n).collect();
     while let Some(b) = worklist.pop_front() {
-        let (out, _) = transfer(&cfg.blocks[b], &block_in[b]);
+        let (out, _) = transfer(&cfg.blocks[b], &block_in[b], ctx);
         for &succ in &cfg.successors[b] {
             let before = block_in[succ].len();
             block_in[succ].extend(out.iter().cloned());
@@ -75,17 +120,31 @@ pub fn analyze(body: &syn::Block, sources: HashSet<String>) -> Vec<TaintFinding>
     // duplication from intermediate iterations.
     let mut findings = Vec::new();
     for block in &cfg.blocks {
-        let (_, block_findings) = transfer(block, &block_in[block.id]);
+        let (_, block_findings) = transfer(block, &block_in[block.id], ctx);
         findings.extend(block_findings);
     }
     findings
 }
 
+/// Sink findings collected while scanning a block, together with the
+/// context they are judged in.
+struct Findings<'a> {
+    found: Vec<TaintFinding>,
+    ctx: TaintContext<'a>,
+}
+
 /// Applies one basic block's statements to an incoming fact set, returning
 /// the outgoing fact set and any sink findings observed along the way.
-fn transfer(block: &BasicBlock, in_facts: &HashSet<String>) -> (HashSet<String>, Vec<TaintFinding>) {
+fn transfer(
+    block: &BasicBlock,
+    in_facts: &HashSet<String>,
+    ctx: TaintContext<'_>,
+) -> (HashSet<String>, Vec<TaintFinding>) {
     let mut facts = in_facts.clone();
-    let mut findings = Vec::new();
+    let mut findings = Findings {
+        found: Vec::new(),
+        ctx,
+    };
 
     for stmt in &block.stmts {
         match stmt {
@@ -112,13 +171,22 @@ fn transfer(block: &BasicBlock, in_facts: &HashSet<String>) -> (HashSet<String>,
                     facts.extend(names);
                 }
             }
+            // The scrutinee itself was already scanned where it is evaluated,
+            // before the branch.
+            BlockStmt::MatchBinding { pat, scrutinee } => {
+                if expr_is_tainted(scrutinee, &facts) {
+                    let mut names = HashSet::new();
+                    collect_pat_idents(pat, &mut names);
+                    facts.extend(names);
+                }
+            }
         }
     }
 
-    (facts, findings)
+    (facts, findings.found)
 }
 
-fn process_top_level_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Vec<TaintFinding>) {
+fn process_top_level_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Findings<'_>) {
     if let Expr::Assign(a) = expr {
         scan_expr(&a.right, facts, findings);
         let tainted = expr_is_tainted(&a.right, facts);
@@ -137,7 +205,7 @@ fn process_top_level_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &m
 /// Recursively walks an expression, recording sink findings and updating the
 /// auth marker, without itself binding any new taint (that only happens for
 /// `let` bindings and plain assignments — see [`transfer`]).
-fn scan_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Vec<TaintFinding>) {
+fn scan_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Findings<'_>) {
     match expr {
         Expr::MethodCall(mc) => {
             let method = mc.method.to_string();
@@ -146,15 +214,18 @@ fn scan_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Vec<TaintF
                 facts.insert(AUTH_MARKER.to_string());
             }
 
-            if (is_storage_write(&method, &mc.receiver) || is_external_call(&method))
-                && !facts.contains(AUTH_MARKER)
-            {
+            let is_sink = is_storage_write(&method, &mc.receiver)
+                || is_external_call(&method)
+                || findings.ctx.extra_sinks.contains(&method);
+            if is_sink && !facts.contains(AUTH_MARKER) {
                 for arg in &mc.args {
                     if let Some(var) = first_tainted_ident(arg, facts) {
-                        findings.push(TaintFinding {
+                        findings.found.push(TaintFinding {
                             var,
                             sink: method.clone(),
+                            via: None,
                             line: mc.span().start().line,
+                            span: SourceSpan::from_span(mc.span()),
                         });
                     }
                 }
@@ -170,6 +241,35 @@ fn scan_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Vec<TaintF
             for arg in &c.args {
                 scan_expr(arg, facts, findings);
             }
+
+            let ctx = findings.ctx;
+            let Some((callee, summary)) = ctx
+                .summaries
+                .and_then(|summaries| summaries.resolve(ctx.owner, &c.func))
+            else {
+                return;
+            };
+            if !facts.contains(AUTH_MARKER) {
+                for (idx, arg) in c.args.iter().enumerate() {
+                    let Some(sinks) = summary.param_sinks.get(&idx) else {
+                        continue;
+                    };
+                    if let Some(var) = first_tainted_ident(arg, facts) {
+                        for sink in sinks {
+                            findings.found.push(TaintFinding {
+                                var: var.clone(),
+                                sink: sink.clone(),
+                                via: Some(callee.to_string()),
+                                line: c.span().start().line,
+                                span: SourceSpan::from_span(c.span()),
+                            });
+                        }
+                    }
+                }
+            }
+            if summary.requires_auth {
+                facts.insert(AUTH_MARKER.to_string());
+            }
         }
         Expr::If(i) => {
             scan_expr(&i.cond, facts, findings);
@@ -224,7 +324,7 @@ fn scan_expr(expr: &Expr, facts: &mut HashSet<String>, findings: &mut Vec<TaintF
     }
 }
 
-fn scan_stmt(stmt: &syn::Stmt, facts: &mut HashSet<String>, findings: &mut Vec<TaintFinding>) {
+fn scan_stmt(stmt: &syn::Stmt, facts: &mut HashSet<String>, findings: &mut Findings<'_>) {
     match stmt {
         syn::Stmt::Local(local) => {
             if let Some(init) = &local.init {
@@ -414,6 +514,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn taint_carried_out_of_a_loop_by_break_is_caught() {
+        // Before `break` had a target, the loop exit only saw the header's
+        // facts, and the header never saw the body's assignment.
+        let findings = analyze_src(
+            "let mut key = clean_key; loop { key = input; break; } env.storage().persistent().set(&key, &1);",
+            &["input"],
+        );
+        assert!(!findings.is_empty());
+    }
+
+    #[test]
+    fn taint_through_branching_let_initializer_is_caught() {
+        let findings = analyze_src(
+            "let key = if flag { input } else { DEFAULT }; env.storage().persistent().set(&key, &1);",
+            &["input"],
+        );
+        assert!(
+            !findings.is_empty(),
+            "`let x = if ..` must be lowered per branch"
+        );
+
+        let findings = analyze_src(
+            "let key = match input { Some(k) => k, None => DEFAULT }; env.storage().persistent().set(&key, &1);",
+            &["input"],
+        );
+        assert!(
+            !findings.is_empty(),
+            "match arm bindings must inherit the scrutinee's taint"
+        );
+    }
+
+    #[test]
+    fn sinks_after_a_return_are_not_reachable() {
+        let findings = analyze_src(
+            "return; env.storage().persistent().set(&key, &val);",
+            &["key"],
+        );
+        assert!(findings.is_empty());
+    }
+
     #[test]
     fn no_finding_when_no_source_reaches_sink() {
         let findings = analyze_src(
@@ -432,4 +573,50 @@ mod tests {
         assert!(!findings.is_empty());
         assert_eq!(findings[0].sink, "invoke_contract");
     }
+
+    #[test]
+    fn extra_sinks_are_flagged() {
+        let block: syn::Block = parse_str("{ token.burn(&from, amount); }").unwrap();
+        let sources: HashSet<String> = ["amount".to_string()].into();
+        assert!(analyze(&block, sources.clone()).is_empty());
+
+        let extra_sinks = ["burn".to_string()];
+        let ctx = TaintContext {
+            extra_sinks: &extra_sinks,
+            ..Default::default()
+        };
+        let findings = analyze_with(&block, sources, ctx);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].sink, "burn");
+    }
+
+    #[test]
+    fn sinks_in_summarised_callees_are_reported_at_the_call() {
+        let file: syn::File = parse_str(
+            r#"
+            fn store(env: &Env, value: i128) {
+                env.storage().persistent().set(&KEY, &value);
+            }
+            fn ensure_admin(env: &Env) {
+                admin(env).require_auth();
+            }
+            "#,
+        )
+        .unwrap();
+        let summaries = CallSummaries::from_file(&file, &[]);
+        let ctx = TaintContext {
+            summaries: Some(&summaries),
+            ..Default::default()
+        };
+        let sources: HashSet<String> = ["amount".to_string()].into();
+
+        let block: syn::Block = parse_str("{ store(&env, amount); }").unwrap();
+        let findings = analyze_with(&block, sources.clone(), ctx);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].var, "amount");
+        assert_eq!(findings[0].sink_description(), "set via store");
+
+        let block: syn::Block = parse_str("{ ensure_admin(&env); store(&env, amount); }").unwrap();
+        assert!(analyze_with(&block, sources, ctx).is_empty());
+    }
 }
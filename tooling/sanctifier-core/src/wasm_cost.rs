@@ -0,0 +1,682 @@
+//! Resource estimation from compiled Soroban contract bytecode.
+//!
+//! [`estimate_wasm`] parses a contract `.wasm` and, for every exported
+//! function, counts the instructions of the function and everything it can
+//! call, and the call sites of each Soroban host function it imports.  Host
+//! imports are resolved offline against the protocol 21 environment
+//! interface (`soroban-env-common`'s `env.json`) and priced per cost
+//! category, so the estimate needs nothing but the module itself.
+//!
+//! The counts are static: a loop body is counted once and an indirect call
+//! reaches nothing.  Treat the figures as a lower bound for relative
+//! comparison between exports, not as the fee the network will charge.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::{Add, Mul};
+
+use serde::Serialize;
+use thiserror::Error;
+use wasmparser::{ExternalKind, Operator, Parser, Payload, TypeRef};
+
+/// CPU instructions the host charges per executed wasm instruction.
+const CPU_PER_WASM_INSN: u64 = 4;
+/// CPU instructions charged per byte of module to parse and instantiate it.
+const CPU_PER_MODULE_BYTE: u64 = 60;
+/// Size of one linear-memory page.
+const WASM_PAGE_BYTES: u64 = 64 * 1024;
+/// Assumed size of a contract data ledger entry.
+const LEDGER_ENTRY_BYTES: u64 = 256;
+
+/// Resources one invocation is estimated to consume.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ResourceEstimate {
+    /// Host CPU instructions.
+    pub cpu_instructions: u64,
+    /// Host memory allocated, in bytes.
+    pub memory_bytes: u64,
+    /// Ledger entry bytes read.
+    pub ledger_read_bytes: u64,
+    /// Ledger entry bytes written.
+    pub ledger_write_bytes: u64,
+}
+
+impl Add for ResourceEstimate {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            cpu_instructions: self.cpu_instructions.saturating_add(other.cpu_instructions),
+            memory_bytes: self.memory_bytes.saturating_add(other.memory_bytes),
+            ledger_read_bytes: self
+                .ledger_read_bytes
+                .saturating_add(other.ledger_read_bytes),
+            ledger_write_bytes: self
+                .ledger_write_bytes
+                .saturating_add(other.ledger_write_bytes),
+        }
+    }
+}
+
+impl Mul<u64> for ResourceEstimate {
+    type Output = Self;
+
+    fn mul(self, times: u64) -> Self {
+        Self {
+            cpu_instructions: self.cpu_instructions.saturating_mul(times),
+            memory_bytes: self.memory_bytes.saturating_mul(times),
+            ledger_read_bytes: self.ledger_read_bytes.saturating_mul(times),
+            ledger_write_bytes: self.ledger_write_bytes.saturating_mul(times),
+        }
+    }
+}
+
+/// Soroban cost-model category a host function is priced by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostCostClass {
+    /// Ledger and invocation context lookups.
+    Context,
+    /// Events and diagnostic logs.
+    Event,
+    /// Host object conversions and `Vec`/`Map`/`Bytes` operations.
+    Object,
+    /// `has_contract_data` / `get_contract_data`.
+    StorageRead,
+    /// `put_contract_data` / `del_contract_data`.
+    StorageWrite,
+    /// TTL extensions.
+    StorageTtl,
+    /// Contract creation and wasm upload or upgrade.
+    Deploy,
+    /// Cross-contract calls, which instantiate the callee.
+    Call,
+    /// `require_auth` and friends.
+    Auth,
+    /// SHA-256 / Keccak-256.
+    Hash,
+    /// Ed25519 signature verification.
+    Ed25519,
+    /// secp256k1 / secp256r1 ECDSA.
+    Ecdsa,
+    /// The host PRNG.
+    Prng,
+}
+
+impl HostCostClass {
+    /// Estimated cost of one call.  The figures are rough protocol 21
+    /// calibrations for small arguments.
+    pub fn cost(self) -> ResourceEstimate {
+        let (cpu_instructions, memory_bytes, ledger_read_bytes, ledger_write_bytes) = match self {
+            Self::Context => (1_000, 0, 0, 0),
+            Self::Event => (10_000, 1_024, 0, 0),
+            Self::Object => (2_000, 128, 0, 0),
+            Self::StorageRead => (15_000, 512, LEDGER_ENTRY_BYTES, 0),
+            Self::StorageWrite => (20_000, 512, 0, LEDGER_ENTRY_BYTES),
+            Self::StorageTtl => (10_000, 256, LEDGER_ENTRY_BYTES, 0),
+            Self::Deploy => (
+                1_000_000,
+                100_000,
+                LEDGER_ENTRY_BYTES,
+                4 * LEDGER_ENTRY_BYTES,
+            ),
+            Self::Call => (500_000, 100_000, 2 * LEDGER_ENTRY_BYTES, 0),
+            Self::Auth => (50_000, 2_000, 0, 0),
+            Self::Hash => (15_000, 256, 0, 0),
+            Self::Ed25519 => (400_000, 1_024, 0, 0),
+            Self::Ecdsa => (2_500_000, 2_048, 0, 0),
+            Self::Prng => (5_000, 256, 0, 0),
+        };
+        ResourceEstimate {
+            cpu_instructions,
+            memory_bytes,
+            ledger_read_bytes,
+            ledger_write_bytes,
+        }
+    }
+}
+
+/// A Soroban host function a contract can import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostFunction {
+    /// Import module name, e.g. `"l"`.
+    pub module: &'static str,
+    /// Import field name, e.g. `"1"`.
+    pub field: &'static str,
+    /// Environment interface name, e.g. `"get_contract_data"`.
+    pub name: &'static str,
+    /// Cost category the call is priced by.
+    pub class: HostCostClass,
+}
+
+/// Resolve a wasm import to the host function it binds to.
+pub fn host_function(module: &str, field: &str) -> Option<HostFunction> {
+    HOST_FUNCTIONS
+        .iter()
+        .find(|(m, f, _, _)| *m == module && *f == field)
+        .map(|&(module, field, name, class)| HostFunction {
+            module,
+            field,
+            name,
+            class,
+        })
+}
+
+/// Static cost profile of one exported function.
+#[derive(Debug, Clone, Serialize)]
+pub struct WasmExportCost {
+    /// Export name, i.e. the contract function name.
+    pub name: String,
+    /// Instructions in the function and every function it can reach.
+    pub instructions: u64,
+    /// Number of functions reachable from the export, itself included.
+    pub reachable_functions: usize,
+    /// `loop` blocks among the reachable instructions.
+    pub loops: usize,
+    /// `call_indirect` sites, whose targets are not followed.
+    pub indirect_calls: usize,
+    /// Call sites per host function, keyed by environment interface name.
+    /// Unrecognised imports are keyed `module.field`.
+    pub host_calls: BTreeMap<String, usize>,
+    /// Estimated cost of one invocation.
+    pub estimate: ResourceEstimate,
+}
+
+/// Cost profiles of every exported function in a module.
+#[derive(Debug, Clone, Serialize)]
+pub struct WasmCostReport {
+    /// Size of the module.
+    pub module_bytes: usize,
+    /// Initial linear memory.
+    pub memory_bytes: u64,
+    /// Function imports that are not Soroban host functions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unknown_imports: Vec<String>,
+    /// One entry per exported function, in export order.
+    pub exports: Vec<WasmExportCost>,
+}
+
+impl WasmCostReport {
+    /// The profile of the export called `name`.
+    pub fn export(&self, name: &str) -> Option<&WasmExportCost> {
+        self.exports.iter().find(|export| export.name == name)
+    }
+}
+
+/// A module that could not be profiled.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum WasmCostError {
+    /// The bytes are not a well-formed wasm module.
+    #[error("invalid wasm module: {0}")]
+    Parse(String),
+    /// A component, rather than a core module.
+    #[error("wasm components are not supported; expected a core module")]
+    Component,
+}
+
+impl From<wasmparser::BinaryReaderError> for WasmCostError {
+    fn from(error: wasmparser::BinaryReaderError) -> Self {
+        Self::Parse(error.to_string())
+    }
+}
+
+/// Profile every exported function of the wasm module in `bytes`.
+pub fn estimate_wasm(bytes: &[u8]) -> Result<WasmCostReport, WasmCostError> {
+    let module = Module::parse(bytes)?;
+
+    let instantiation = ResourceEstimate {
+        cpu_instructions: CPU_PER_MODULE_BYTE.saturating_mul(bytes.len() as u64),
+        memory_bytes: module.memory_bytes,
+        ..ResourceEstimate::default()
+    };
+    let exports = module
+        .exports
+        .iter()
+        .map(|(name, index)| module.export_cost(name, *index, instantiation))
+        .collect();
+
+    Ok(WasmCostReport {
+        module_bytes: bytes.len(),
+        memory_bytes: module.memory_bytes,
+        unknown_imports: module
+            .imports
+            .iter()
+            .filter(|import| import.host.is_none())
+            .map(|import| import.key.clone())
+            .collect(),
+        exports,
+    })
+}
+
+// ── Internal: module model ────────────────────────────────────────────────────
+
+struct Import {
+    /// `host_calls` key: the host function name, or `module.field`.
+    key: String,
+    host: Option<HostFunction>,
+}
+
+/// What one defined function does, before following its calls.
+#[derive(Default)]
+struct Body {
+    instructions: u64,
+    loops: usize,
+    indirect_calls: usize,
+    /// Call sites by function index.
+    calls: HashMap<u32, usize>,
+}
+
+struct Module {
+    /// Function imports, which occupy the first function indices.
+    imports: Vec<Import>,
+    /// Defined function bodies, indexed from `imports.len()`.
+    bodies: Vec<Body>,
+    exports: Vec<(String, u32)>,
+    memory_bytes: u64,
+}
+
+impl Module {
+    fn parse(bytes: &[u8]) -> Result<Self, WasmCostError> {
+        let mut module = Module {
+            imports: Vec::new(),
+            bodies: Vec::new(),
+            exports: Vec::new(),
+            memory_bytes: 0,
+        };
+        for payload in Parser::new(0).parse_all(bytes) {
+            match payload? {
+                Payload::Version {
+                    encoding: wasmparser::Encoding::Component,
+                    ..
+                } => return Err(WasmCostError::Component),
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        if let TypeRef::Func(_) = import.ty {
+                            let host = host_function(import.module, import.name);
+                            module.imports.push(Import {
+                                key: match host {
+                                    Some(host) => host.name.to_string(),
+                                    None => format!("{}.{}", import.module, import.name),
+                                },
+                                host,
+                            });
+                        }
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        module.memory_bytes = module
+                            .memory_bytes
+                            .saturating_add(memory?.initial.saturating_mul(WASM_PAGE_BYTES));
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export?;
+                        if export.kind == ExternalKind::Func {
+                            module.exports.push((export.name.to_string(), export.index));
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut profile = Body::default();
+                    let mut reader = body.get_operators_reader()?;
+                    while !reader.eof() {
+                        match reader.read()? {
+                            // The `end` closing the body is not executed code.
+                            Operator::End if reader.eof() => continue,
+                            Operator::Loop { .. } => profile.loops += 1,
+                            Operator::Call { function_index }
+                            | Operator::ReturnCall { function_index } => {
+                                *profile.calls.entry(function_index).or_default() += 1;
+                            }
+                            Operator::CallIndirect { .. } | Operator::ReturnCallIndirect { .. } => {
+                                profile.indirect_calls += 1;
+                            }
+                            _ => {}
+                        }
+                        profile.instructions += 1;
+                    }
+                    module.bodies.push(profile);
+                }
+                _ => {}
+            }
+        }
+        Ok(module)
+    }
+
+    /// The body of function `index`, or `None` for an import.
+    fn body(&self, index: u32) -> Option<&Body> {
+        (index as usize)
+            .checked_sub(self.imports.len())
+            .and_then(|defined| self.bodies.get(defined))
+    }
+
+    fn export_cost(&self, name: &str, index: u32, base: ResourceEstimate) -> WasmExportCost {
+        let mut cost = WasmExportCost {
+            name: name.to_string(),
+            instructions: 0,
+            reachable_functions: 0,
+            loops: 0,
+            indirect_calls: 0,
+            host_calls: BTreeMap::new(),
+            estimate: base,
+        };
+
+        let mut seen = HashSet::from([index]);
+        let mut stack = vec![index];
+        while let Some(function) = stack.pop() {
+            let Some(body) = self.body(function) else {
+                // An exported import: the export is the host call itself.
+                if let Some(import) = self.imports.get(function as usize) {
+                    *cost.host_calls.entry(import.key.clone()).or_default() += 1;
+                }
+                continue;
+            };
+            cost.reachable_functions += 1;
+            cost.instructions += body.instructions;
+            cost.loops += body.loops;
+            cost.indirect_calls += body.indirect_calls;
+            for (&callee, &sites) in &body.calls {
+                match self.imports.get(callee as usize) {
+                    Some(import) => {
+                        *cost.host_calls.entry(import.key.clone()).or_default() += sites
+                    }
+                    None if seen.insert(callee) => stack.push(callee),
+                    None => {}
+                }
+            }
+        }
+
+        cost.estimate = cost.estimate
+            + ResourceEstimate {
+                cpu_instructions: cost.instructions.saturating_mul(CPU_PER_WASM_INSN),
+                ..ResourceEstimate::default()
+            };
+        for (key, &sites) in &cost.host_calls {
+            let host = self
+                .imports
+                .iter()
+                .find(|import| &import.key == key)
+                .and_then(|import| import.host);
+            if let Some(host) = host {
+                cost.estimate = cost.estimate + host.class.cost() * sites as u64;
+            }
+        }
+        cost
+    }
+}
+
+/// The protocol 21 host interface: `(module, field, name, class)`.
+#[rustfmt::skip]
+const HOST_FUNCTIONS: &[(&str, &str, &str, HostCostClass)] = &[
+    // context
+    ("x", "_", "log_from_linear_memory", HostCostClass::Event),
+    ("x", "0", "obj_cmp", HostCostClass::Context),
+    ("x", "1", "contract_event", HostCostClass::Event),
+    ("x", "2", "get_ledger_version", HostCostClass::Context),
+    ("x", "3", "get_ledger_sequence", HostCostClass::Context),
+    ("x", "4", "get_ledger_timestamp", HostCostClass::Context),
+    ("x", "5", "fail_with_error", HostCostClass::Context),
+    ("x", "6", "get_ledger_network_id", HostCostClass::Context),
+    ("x", "7", "get_current_contract_address", HostCostClass::Context),
+    ("x", "8", "get_max_live_until_ledger", HostCostClass::Context),
+    // int
+    ("i", "_", "obj_from_u64", HostCostClass::Object),
+    ("i", "0", "obj_to_u64", HostCostClass::Object),
+    ("i", "1", "obj_from_i64", HostCostClass::Object),
+    ("i", "2", "obj_to_i64", HostCostClass::Object),
+    ("i", "3", "obj_from_u128_pieces", HostCostClass::Object),
+    ("i", "4", "obj_to_u128_lo64", HostCostClass::Object),
+    ("i", "5", "obj_to_u128_hi64", HostCostClass::Object),
+    ("i", "6", "obj_from_i128_pieces", HostCostClass::Object),
+    ("i", "7", "obj_to_i128_lo64", HostCostClass::Object),
+    ("i", "8", "obj_to_i128_hi64", HostCostClass::Object),
+    ("i", "9", "obj_from_u256_pieces", HostCostClass::Object),
+    ("i", "a", "u256_val_from_be_bytes", HostCostClass::Object),
+    ("i", "b", "u256_val_to_be_bytes", HostCostClass::Object),
+    ("i", "c", "obj_to_u256_hi_hi", HostCostClass::Object),
+    ("i", "d", "obj_to_u256_hi_lo", HostCostClass::Object),
+    ("i", "e", "obj_to_u256_lo_hi", HostCostClass::Object),
+    ("i", "f", "obj_to_u256_lo_lo", HostCostClass::Object),
+    ("i", "g", "obj_from_i256_pieces", HostCostClass::Object),
+    ("i", "h", "i256_val_from_be_bytes", HostCostClass::Object),
+    ("i", "i", "i256_val_to_be_bytes", HostCostClass::Object),
+    ("i", "j", "obj_to_i256_hi_hi", HostCostClass::Object),
+    ("i", "k", "obj_to_i256_hi_lo", HostCostClass::Object),
+    ("i", "l", "obj_to_i256_lo_hi", HostCostClass::Object),
+    ("i", "m", "obj_to_i256_lo_lo", HostCostClass::Object),
+    ("i", "n", "u256_add", HostCostClass::Object),
+    ("i", "o", "u256_sub", HostCostClass::Object),
+    ("i", "p", "u256_mul", HostCostClass::Object),
+    ("i", "q", "u256_div", HostCostClass::Object),
+    ("i", "r", "u256_rem_euclid", HostCostClass::Object),
+    ("i", "s", "u256_pow", HostCostClass::Object),
+    ("i", "t", "u256_shl", HostCostClass::Object),
+    ("i", "u", "u256_shr", HostCostClass::Object),
+    ("i", "v", "i256_add", HostCostClass::Object),
+    ("i", "w", "i256_sub", HostCostClass::Object),
+    ("i", "x", "i256_mul", HostCostClass::Object),
+    ("i", "y", "i256_div", HostCostClass::Object),
+    ("i", "z", "i256_rem_euclid", HostCostClass::Object),
+    ("i", "A", "i256_pow", HostCostClass::Object),
+    ("i", "B", "i256_shl", HostCostClass::Object),
+    ("i", "C", "i256_shr", HostCostClass::Object),
+    ("i", "D", "timepoint_obj_from_u64", HostCostClass::Object),
+    ("i", "E", "timepoint_obj_to_u64", HostCostClass::Object),
+    ("i", "F", "duration_obj_from_u64", HostCostClass::Object),
+    ("i", "G", "duration_obj_to_u64", HostCostClass::Object),
+    // map
+    ("m", "_", "map_new", HostCostClass::Object),
+    ("m", "0", "map_put", HostCostClass::Object),
+    ("m", "1", "map_get", HostCostClass::Object),
+    ("m", "2", "map_del", HostCostClass::Object),
+    ("m", "3", "map_len", HostCostClass::Object),
+    ("m", "4", "map_has", HostCostClass::Object),
+    ("m", "5", "map_key_by_pos", HostCostClass::Object),
+    ("m", "6", "map_val_by_pos", HostCostClass::Object),
+    ("m", "7", "map_keys", HostCostClass::Object),
+    ("m", "8", "map_values", HostCostClass::Object),
+    ("m", "9", "map_new_from_linear_memory", HostCostClass::Object),
+    ("m", "a", "map_unpack_to_linear_memory", HostCostClass::Object),
+    // vec
+    ("v", "_", "vec_new", HostCostClass::Object),
+    ("v", "0", "vec_put", HostCostClass::Object),
+    ("v", "1", "vec_get", HostCostClass::Object),
+    ("v", "2", "vec_del", HostCostClass::Object),
+    ("v", "3", "vec_len", HostCostClass::Object),
+    ("v", "4", "vec_push_front", HostCostClass::Object),
+    ("v", "5", "vec_pop_front", HostCostClass::Object),
+    ("v", "6", "vec_push_back", HostCostClass::Object),
+    ("v", "7", "vec_pop_back", HostCostClass::Object),
+    ("v", "8", "vec_front", HostCostClass::Object),
+    ("v", "9", "vec_back", HostCostClass::Object),
+    ("v", "a", "vec_insert", HostCostClass::Object),
+    ("v", "b", "vec_append", HostCostClass::Object),
+    ("v", "c", "vec_slice", HostCostClass::Object),
+    ("v", "d", "vec_first_index_of", HostCostClass::Object),
+    ("v", "e", "vec_last_index_of", HostCostClass::Object),
+    ("v", "f", "vec_binary_search", HostCostClass::Object),
+    ("v", "g", "vec_new_from_linear_memory", HostCostClass::Object),
+    ("v", "h", "vec_unpack_to_linear_memory", HostCostClass::Object),
+    // ledger
+    ("l", "_", "put_contract_data", HostCostClass::StorageWrite),
+    ("l", "0", "has_contract_data", HostCostClass::StorageRead),
+    ("l", "1", "get_contract_data", HostCostClass::StorageRead),
+    ("l", "2", "del_contract_data", HostCostClass::StorageWrite),
+    ("l", "3", "create_contract", HostCostClass::Deploy),
+    ("l", "4", "create_asset_contract", HostCostClass::Deploy),
+    ("l", "5", "upload_wasm", HostCostClass::Deploy),
+    ("l", "6", "update_current_contract_wasm", HostCostClass::Deploy),
+    ("l", "7", "extend_contract_data_ttl", HostCostClass::StorageTtl),
+    ("l", "8", "extend_current_contract_instance_and_code_ttl", HostCostClass::StorageTtl),
+    ("l", "9", "extend_contract_instance_and_code_ttl", HostCostClass::StorageTtl),
+    ("l", "a", "get_contract_id", HostCostClass::Context),
+    ("l", "b", "get_asset_contract_id", HostCostClass::Context),
+    ("l", "c", "extend_contract_instance_ttl", HostCostClass::StorageTtl),
+    ("l", "d", "extend_contract_code_ttl", HostCostClass::StorageTtl),
+    // call
+    ("d", "_", "call", HostCostClass::Call),
+    ("d", "0", "try_call", HostCostClass::Call),
+    // buf
+    ("b", "_", "serialize_to_bytes", HostCostClass::Object),
+    ("b", "0", "deserialize_from_bytes", HostCostClass::Object),
+    ("b", "1", "bytes_copy_to_linear_memory", HostCostClass::Object),
+    ("b", "2", "bytes_copy_from_linear_memory", HostCostClass::Object),
+    ("b", "3", "bytes_new_from_linear_memory", HostCostClass::Object),
+    ("b", "4", "bytes_new", HostCostClass::Object),
+    ("b", "5", "bytes_put", HostCostClass::Object),
+    ("b", "6", "bytes_get", HostCostClass::Object),
+    ("b", "7", "bytes_del", HostCostClass::Object),
+    ("b", "8", "bytes_len", HostCostClass::Object),
+    ("b", "9", "bytes_push", HostCostClass::Object),
+    ("b", "a", "bytes_pop", HostCostClass::Object),
+    ("b", "b", "bytes_front", HostCostClass::Object),
+    ("b", "c", "bytes_back", HostCostClass::Object),
+    ("b", "d", "bytes_insert", HostCostClass::Object),
+    ("b", "e", "bytes_append", HostCostClass::Object),
+    ("b", "f", "bytes_slice", HostCostClass::Object),
+    ("b", "g", "string_copy_to_linear_memory", HostCostClass::Object),
+    ("b", "h", "symbol_copy_to_linear_memory", HostCostClass::Object),
+    ("b", "i", "string_new_from_linear_memory", HostCostClass::Object),
+    ("b", "j", "symbol_new_from_linear_memory", HostCostClass::Object),
+    ("b", "k", "string_len", HostCostClass::Object),
+    ("b", "l", "symbol_len", HostCostClass::Object),
+    ("b", "m", "symbol_index_in_linear_memory", HostCostClass::Object),
+    // crypto
+    ("c", "_", "compute_hash_sha256", HostCostClass::Hash),
+    ("c", "0", "verify_sig_ed25519", HostCostClass::Ed25519),
+    ("c", "1", "compute_hash_keccak256", HostCostClass::Hash),
+    ("c", "2", "recover_key_ecdsa_secp256k1", HostCostClass::Ecdsa),
+    ("c", "3", "verify_sig_ecdsa_secp256r1", HostCostClass::Ecdsa),
+    // address
+    ("a", "_", "require_auth_for_args", HostCostClass::Auth),
+    ("a", "0", "require_auth", HostCostClass::Auth),
+    ("a", "1", "strkey_to_address", HostCostClass::Object),
+    ("a", "2", "address_to_strkey", HostCostClass::Object),
+    ("a", "3", "authorize_as_curr_contract", HostCostClass::Auth),
+    // test
+    ("t", "_", "dummy0", HostCostClass::Context),
+    ("t", "0", "protocol_gated_dummy", HostCostClass::Context),
+    // prng
+    ("p", "_", "prng_reseed", HostCostClass::Prng),
+    ("p", "0", "prng_bytes_new", HostCostClass::Prng),
+    ("p", "1", "prng_u64_in_inclusive_range", HostCostClass::Prng),
+    ("p", "2", "prng_vec_shuffle", HostCostClass::Prng),
+];
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Assemble a module whose functions all have type `() -> ()`.
+    /// `bodies` are raw instruction bytes, without the trailing `end`.
+    fn module(imports: &[(&str, &str)], bodies: &[&[u8]], exports: &[(&str, u32)]) -> Vec<u8> {
+        fn name(out: &mut Vec<u8>, name: &str) {
+            out.push(name.len() as u8);
+            out.extend_from_slice(name.as_bytes());
+        }
+        fn section(out: &mut Vec<u8>, id: u8, count: usize, content: Vec<u8>) {
+            out.push(id);
+            out.push((content.len() + 1) as u8);
+            out.push(count as u8);
+            out.extend(content);
+        }
+
+        let mut out = b"\0asm\x01\0\0\0".to_vec();
+        section(&mut out, 1, 1, vec![0x60, 0, 0]);
+        let mut content = Vec::new();
+        for (module, field) in imports {
+            name(&mut content, module);
+            name(&mut content, field);
+            content.extend([0x00, 0x00]);
+        }
+        section(&mut out, 2, imports.len(), content);
+        section(&mut out, 3, bodies.len(), vec![0; bodies.len()]);
+        section(&mut out, 5, 1, vec![0x00, 0x02]);
+        let mut content = Vec::new();
+        for (export, index) in exports {
+            name(&mut content, export);
+            content.extend([0x00, *index as u8]);
+        }
+        section(&mut out, 7, exports.len(), content);
+        let mut content = Vec::new();
+        for body in bodies {
+            content.push((body.len() + 2) as u8);
+            content.push(0);
+            content.extend_from_slice(body);
+            content.push(0x0b);
+        }
+        section(&mut out, 10, bodies.len(), content);
+        out
+    }
+
+    const NOP: u8 = 0x01;
+    const CALL: u8 = 0x10;
+
+    #[test]
+    fn resolves_host_imports_offline() {
+        let host = host_function("l", "1").unwrap();
+        assert_eq!(host.name, "get_contract_data");
+        assert_eq!(host.class, HostCostClass::StorageRead);
+        assert_eq!(host_function("a", "0").unwrap().name, "require_auth");
+        assert_eq!(host_function("d", "_").unwrap().class, HostCostClass::Call);
+        assert!(host_function("env", "memcpy").is_none());
+    }
+
+    #[test]
+    fn profiles_each_export_through_its_callees() {
+        // 0: l._ put_contract_data, 1: l.1 get_contract_data, 2: env.abort
+        // 3: helper  (reads storage)
+        // 4: deposit (calls helper, writes storage, loops)
+        // 5: balance (calls helper)
+        let bytes = module(
+            &[("l", "_"), ("l", "1"), ("env", "abort")],
+            &[
+                &[CALL, 1, NOP],
+                &[CALL, 3, CALL, 0, CALL, 0, 0x03, 0x40, NOP, 0x0b],
+                &[CALL, 3],
+            ],
+            &[("deposit", 4), ("balance", 5)],
+        );
+        let report = estimate_wasm(&bytes).unwrap();
+        assert_eq!(report.memory_bytes, 2 * WASM_PAGE_BYTES);
+        assert_eq!(report.unknown_imports, ["env.abort"]);
+
+        let deposit = report.export("deposit").unwrap();
+        assert_eq!(deposit.reachable_functions, 2);
+        assert_eq!(deposit.instructions, 6 + 2);
+        assert_eq!(deposit.loops, 1);
+        assert_eq!(deposit.host_calls["put_contract_data"], 2);
+        assert_eq!(deposit.host_calls["get_contract_data"], 1);
+        assert_eq!(deposit.estimate.ledger_write_bytes, 2 * LEDGER_ENTRY_BYTES);
+        assert_eq!(deposit.estimate.ledger_read_bytes, LEDGER_ENTRY_BYTES);
+
+        let balance = report.export("balance").unwrap();
+        assert_eq!(balance.instructions, 1 + 2);
+        assert_eq!(balance.estimate.ledger_write_bytes, 0);
+        assert!(balance.estimate.cpu_instructions < deposit.estimate.cpu_instructions);
+        let expected_cpu = CPU_PER_MODULE_BYTE * bytes.len() as u64
+            + CPU_PER_WASM_INSN * 3
+            + HostCostClass::StorageRead.cost().cpu_instructions;
+        assert_eq!(balance.estimate.cpu_instructions, expected_cpu);
+    }
+
+    #[test]
+    fn recursion_counts_each_function_once() {
+        let bytes = module(&[], &[&[CALL, 1], &[CALL, 0]], &[("ping", 0)]);
+        let ping = &estimate_wasm(&bytes).unwrap().exports[0];
+        assert_eq!(ping.reachable_functions, 2);
+        assert_eq!(ping.instructions, 2);
+    }
+
+    #[test]
+    fn rejects_malformed_modules() {
+        assert!(matches!(
+            estimate_wasm(b"not wasm"),
+            Err(WasmCostError::Parse(_))
+        ));
+    }
+}
@@ -16,18 +16,22 @@ fn analyzer() -> Analyzer {
     Analyzer::new(SanctifyConfig::default())
 }
 
+fn with_rules(rules: Vec<CustomRule>) -> Analyzer {
+    Analyzer::new(SanctifyConfig {
+        custom_rules: rules,
+        ..SanctifyConfig::default()
+    })
+}
+
 fn rule(name: &str, pattern: &str) -> CustomRule {
-    CustomRule {
-        name: name.to_string(),
-        pattern: pattern.to_string(),
-        severity: RuleSeverity::Low,
-    }
+    rule_with_severity(name, pattern, RuleSeverity::Low)
 }
 
 fn rule_with_severity(name: &str, pattern: &str, severity: RuleSeverity) -> CustomRule {
     CustomRule {
         name: name.to_string(),
         pattern: pattern.to_string(),
+        description: String::new(),
         severity,
     }
 }
@@ -113,7 +117,7 @@ fn validation_error_display_includes_rule_name_and_message() {
 fn rule_matches_at_correct_line_number() {
     let rules = vec![rule("find_todo", r"TODO")];
     let source = "fn a() {}\n// TODO: fix this\nfn b() {}";
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     assert_eq!(matches.len(), 1);
     assert_eq!(matches[0].line, 2, "TODO is on line 2");
     assert_eq!(matches[0].rule_name, "find_todo");
@@ -123,7 +127,7 @@ fn rule_matches_at_correct_line_number() {
 fn multiple_matches_for_one_rule_all_returned() {
     let rules = vec![rule("find_unwrap", r"\.unwrap\(\)")];
     let source = "let x = foo().unwrap();\nlet y = bar().unwrap();\nlet z = baz();";
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     assert_eq!(matches.len(), 2, "both unwrap lines should match");
 }
 
@@ -134,7 +138,7 @@ fn overlapping_rules_report_independently() {
         rule("unsafe_fn", r"unsafe fn"),
     ];
     let source = "pub unsafe fn danger() {}";
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     // Both rules match; they are independent findings.
     assert_eq!(matches.len(), 2);
     let names: Vec<&str> = matches.iter().map(|m| m.rule_name.as_str()).collect();
@@ -146,7 +150,7 @@ fn overlapping_rules_report_independently() {
 fn no_match_returns_empty_vec() {
     let rules = vec![rule("find_never", r"THIS_NEVER_APPEARS_XYZ_123")];
     let source = "fn clean() { let x = 1; }";
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     assert!(matches.is_empty());
 }
 
@@ -154,7 +158,7 @@ fn no_match_returns_empty_vec() {
 fn severity_is_propagated_to_match() {
     let rules = vec![rule_with_severity("critical_rule", r"panic!", RuleSeverity::Critical)];
     let source = "panic!(\"oh no\");";
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     assert_eq!(matches.len(), 1);
     assert_eq!(matches[0].severity, RuleSeverity::Critical);
 }
@@ -163,7 +167,7 @@ fn severity_is_propagated_to_match() {
 fn snippet_is_trimmed_in_match() {
     let rules = vec![rule("find_set", r"\.set\(")];
     let source = "    env.storage().instance().set(&key, &val);";
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     assert_eq!(matches.len(), 1);
     // snippet should not start/end with whitespace
     assert_eq!(
@@ -181,7 +185,7 @@ fn invalid_regex_in_rule_does_not_panic_or_affect_valid_rules() {
     ];
     let source = "fn hello() {}";
     // Should not panic; valid rule still matches.
-    let matches = analyzer().analyze_custom_rules(source, &rules);
+    let matches = with_rules(rules).analyze_custom_rules(source);
     assert_eq!(matches.len(), 1);
     assert_eq!(matches[0].rule_name, "good");
 }
@@ -189,13 +193,13 @@ fn invalid_regex_in_rule_does_not_panic_or_affect_valid_rules() {
 #[test]
 fn empty_source_produces_no_matches() {
     let rules = vec![rule("any", r".+")];
-    let matches = analyzer().analyze_custom_rules("", &rules);
+    let matches = with_rules(rules).analyze_custom_rules("");
     assert!(matches.is_empty());
 }
 
 #[test]
 fn empty_rules_slice_produces_no_matches() {
     let source = "fn hello() { panic!(\"test\"); }";
-    let matches = analyzer().analyze_custom_rules(source, &[]);
+    let matches = with_rules(vec![]).analyze_custom_rules(source);
     assert!(matches.is_empty());
 }
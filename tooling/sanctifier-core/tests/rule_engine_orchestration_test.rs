@@ -14,6 +14,7 @@
 //! cargo test --test rule_engine_orchestration_test -p sanctifier-core
 //! ```
 
+use sanctifier_core::fix_context::contract_errors;
 use sanctifier_core::rules::{RuleRegistry, RuleViolation, Severity};
 use sanctifier_core::{Analyzer, SanctifyConfig};
 
@@ -338,7 +339,7 @@ fn analyzer_run_rule_returns_empty_for_unknown_rule_name() {
 #[test]
 fn custom_regex_rule_fires_on_matching_pattern() {
     let config = SanctifyConfig {
-        rules: vec![sanctifier_core::CustomRule {
+        custom_rules: vec![sanctifier_core::CustomRule {
             name: "no_unsafe_test".to_string(),
             pattern: "unsafe\\s*\\{".to_string(),
             description: "No unsafe blocks".to_string(),
@@ -358,7 +359,7 @@ fn custom_regex_rule_fires_on_matching_pattern() {
 #[test]
 fn custom_regex_rule_does_not_fire_on_non_matching_source() {
     let config = SanctifyConfig {
-        rules: vec![sanctifier_core::CustomRule {
+        custom_rules: vec![sanctifier_core::CustomRule {
             name: "no_unsafe_test".to_string(),
             pattern: "unsafe\\s*\\{".to_string(),
             description: "No unsafe blocks".to_string(),
@@ -490,3 +491,348 @@ fn rule_violation_serialises_with_required_fields() {
         "serialised violation missing 'location'"
     );
 }
+
+// ── 13. Inline suppressions ───────────────────────────────────────────────────
+
+#[test]
+fn allow_attribute_suppresses_named_rule_only() {
+    let source = r#"
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+    #[contract] pub struct Vault;
+    #[contractimpl] impl Vault {
+        #[allow(sanctifier::auth_gap)]
+        pub fn withdraw(env: Env, recipient: Address, amount: i128) {
+            env.storage().persistent().set(&recipient, &amount);
+        }
+    }
+"#;
+    let violations = registry().run_all(source);
+    let names = rule_names_for(&violations);
+    assert!(!names.contains(&"auth_gap"), "auth_gap must be suppressed");
+    assert!(
+        !names.contains(&"unused_suppression"),
+        "a used suppression must not be reported"
+    );
+}
+
+#[test]
+fn ignore_next_line_comment_suppresses_by_finding_code() {
+    let source = r#"
+    use soroban_sdk::{contract, contractimpl, Env};
+    #[contract] pub struct Calc;
+    #[contractimpl] impl Calc {
+        pub fn add(_env: Env, a: u64, b: u64) -> u64 {
+            // sanctifier-ignore-next-line S003: inputs are u32-bounded upstream
+            a + b
+        }
+    }
+"#;
+    let violations = registry().run_all(source);
+    assert!(!rule_names_for(&violations).contains(&"arithmetic_overflow"));
+}
+
+#[test]
+fn stale_inline_suppression_is_reported_as_warning() {
+    let source = r#"
+    use soroban_sdk::{contract, contractimpl, Env};
+    #[contract] pub struct Calc;
+    #[contractimpl] impl Calc {
+        pub fn add(_env: Env, a: u64, b: u64) -> u64 {
+            // sanctifier-ignore-next-line arithmetic_overflow
+            a.saturating_add(b)
+        }
+    }
+"#;
+    let violations = registry().run_all(source);
+    let unused: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_name == "unused_suppression")
+        .collect();
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0].severity, Severity::Warning);
+    assert_eq!(unused[0].location, "line 6");
+}
+
+#[test]
+fn inline_suppression_applies_to_yaml_custom_rules() {
+    use sanctifier_core::custom_yaml_rules::{
+        AstMatcher, YamlCustomRule, YamlRuleWrapper, YamlSeverity,
+    };
+
+    let mut reg = RuleRegistry::new();
+    reg.register(YamlRuleWrapper::new(YamlCustomRule {
+        id: "no_todo".to_string(),
+        name: "No TODO".to_string(),
+        description: "Unresolved TODO".to_string(),
+        severity: YamlSeverity::Warning,
+        matcher: AstMatcher::Regex {
+            pattern: "TODO".to_string(),
+        },
+    }));
+    let source = "fn a() {}\n// sanctifier-ignore-next-line no_todo: tracked in #42\nconst NOTE: &str = \"TODO\";\nconst OTHER: &str = \"TODO\";\n";
+
+    let violations = reg.run_all(source);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].location, "line 4");
+}
+
+// ── 14. Finding codes and severity taxonomy ───────────────────────────────────
+
+#[test]
+fn registry_tags_violations_with_code_and_catalogue_severity() {
+    use sanctifier_core::finding_codes::{lookup_finding_code, AUTH_GAP};
+
+    let violations = registry().run_by_name(AUTH_GAP_CONTRACT, "auth_gap");
+    assert!(!violations.is_empty());
+    let expected = lookup_finding_code(AUTH_GAP).unwrap().severity;
+    for v in &violations {
+        assert_eq!(v.code, AUTH_GAP);
+        assert_eq!(v.finding_severity, expected);
+    }
+}
+
+#[test]
+fn unmapped_rule_reports_as_custom_rule_with_its_own_severity() {
+    use sanctifier_core::finding_codes::CUSTOM_RULE_MATCH;
+    use sanctifier_core::rules::Rule;
+    use sanctifier_core::FindingSeverity;
+
+    struct LoudRule;
+    impl Rule for LoudRule {
+        fn name(&self) -> &str {
+            "loud_test_rule"
+        }
+        fn description(&self) -> &str {
+            "Flags every source"
+        }
+        fn default_severity(&self) -> FindingSeverity {
+            FindingSeverity::Critical
+        }
+        fn check(&self, _source: &str) -> Vec<RuleViolation> {
+            vec![RuleViolation::new(
+                self.name(),
+                Severity::Info,
+                "always".to_string(),
+                "line 1".to_string(),
+            )]
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    let mut reg = RuleRegistry::new();
+    reg.register(LoudRule);
+    let violations = reg.run_all("fn a() {}");
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].code, CUSTOM_RULE_MATCH);
+    assert_eq!(violations[0].finding_severity, FindingSeverity::Critical);
+}
+
+// ── 15. Per-rule configuration ([rules.<name>]) ──────────────────────────────
+
+fn config_with_rules(rules: serde_json::Value) -> SanctifyConfig {
+    serde_json::from_value(serde_json::json!({ "rules": rules })).unwrap()
+}
+
+#[test]
+fn configured_registry_drops_disabled_rules() {
+    let config = config_with_rules(serde_json::json!({ "auth_gap": { "enabled": false } }));
+    let reg = RuleRegistry::from_config(&config).unwrap();
+    assert!(!reg.available_rules().contains(&"auth_gap"));
+    assert!(reg.run_by_name(AUTH_GAP_CONTRACT, "auth_gap").is_empty());
+    assert!(config.rule_enabled("panic_detection"));
+}
+
+#[test]
+fn configured_registry_applies_severity_override() {
+    use sanctifier_core::FindingSeverity;
+
+    let config = config_with_rules(serde_json::json!({ "auth_gap": { "severity": "low" } }));
+    let violations = RuleRegistry::from_config(&config)
+        .unwrap()
+        .run_by_name(AUTH_GAP_CONTRACT, "auth_gap");
+    assert!(!violations.is_empty());
+    assert!(violations
+        .iter()
+        .all(|v| v.finding_severity == FindingSeverity::Low));
+}
+
+#[test]
+fn strict_mode_ignores_disable() {
+    let mut config = config_with_rules(serde_json::json!({ "auth_gap": { "enabled": false } }));
+    config.enabled_rules = vec!["panics".to_string()];
+    config.strict_mode = true;
+    let reg = RuleRegistry::from_config(&config).unwrap();
+    assert!(!reg.run_by_name(AUTH_GAP_CONTRACT, "auth_gap").is_empty());
+    assert!(config.rule_enabled("arithmetic_overflow"));
+}
+
+#[test]
+fn strict_mode_rejects_downgrade() {
+    let mut config = config_with_rules(serde_json::json!({ "auth_gap": { "severity": "info" } }));
+    config.strict_mode = true;
+    let err = RuleRegistry::from_config(&config).err().unwrap();
+    assert_eq!(err.rule, "auth_gap");
+    assert!(err.to_string().contains("strict_mode"), "{err}");
+
+    let mut config =
+        config_with_rules(serde_json::json!({ "panic_detection": { "severity": "critical" } }));
+    config.strict_mode = true;
+    assert!(RuleRegistry::from_config(&config).is_ok());
+}
+
+#[test]
+fn legacy_enabled_rules_switch_their_groups() {
+    let config: SanctifyConfig = serde_json::from_value(serde_json::json!({
+        "enabled_rules": ["auth_gaps", "arithmetic"],
+        "rules": { "ledger_size": { "enabled": true } }
+    }))
+    .unwrap();
+    let reg = RuleRegistry::from_config(&config).unwrap();
+    let available = reg.available_rules();
+    assert!(available.contains(&"auth_gap"));
+    assert!(available.contains(&"arithmetic_overflow"));
+    assert!(!available.contains(&"panic_detection"));
+    // An explicit table wins over the legacy list; ungrouped rules always run.
+    assert!(available.contains(&"ledger_size"));
+    assert!(available.contains(&"reentrancy"));
+
+    let err = serde_json::from_value::<SanctifyConfig>(serde_json::json!({
+        "enabled_rules": ["auth_gap"]
+    }))
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown rule group `auth_gap`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn unknown_rule_or_option_is_a_config_error() {
+    let config = config_with_rules(serde_json::json!({ "auth_gaps": { "enabled": false } }));
+    let err = RuleRegistry::from_config(&config).err().unwrap();
+    assert_eq!(err.rule, "auth_gaps");
+
+    let config = config_with_rules(serde_json::json!({ "auth_gap": { "threshold": 3 } }));
+    let err = RuleRegistry::from_config(&config).err().unwrap();
+    assert!(err.to_string().contains("threshold"), "{err}");
+}
+
+#[test]
+fn analyzer_passes_respect_disabled_rules() {
+    let config = config_with_rules(serde_json::json!({ "auth_gap": { "enabled": false } }));
+    let a = Analyzer::new(config);
+    assert!(a.scan_auth_gaps(AUTH_GAP_CONTRACT).is_empty());
+    assert!(!Analyzer::new(SanctifyConfig::default())
+        .scan_auth_gaps(AUTH_GAP_CONTRACT)
+        .is_empty());
+}
+
+// ── 16. Inter-procedural analysis ─────────────────────────────────────────────
+
+/// Entry points that delegate storage writes and auth checks to helpers.
+const HELPER_CONTRACT: &str = r#"
+    use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
+    #[contract] pub struct Vault;
+    fn ensure_admin(env: &Env) {
+        let admin: Address = env.storage().instance().get(&Symbol::new(env, "admin")).unwrap();
+        admin.require_auth();
+    }
+    #[contractimpl] impl Vault {
+        pub fn set_limit(env: Env, limit: i128) {
+            Self::write_limit(&env, limit);
+        }
+        pub fn set_limit_as_admin(env: Env, limit: i128) {
+            ensure_admin(&env);
+            Self::write_limit(&env, limit);
+        }
+        fn write_limit(env: &Env, limit: i128) {
+            env.storage().instance().set(&Symbol::new(env, "limit"), &limit);
+        }
+    }
+"#;
+
+#[test]
+fn helper_flows_are_followed_by_taint_and_auth_rules() {
+    let reg = RuleRegistry::default();
+    for rule in ["auth_gap", "taint_propagation"] {
+        let violations = reg.run_by_name(HELPER_CONTRACT, rule);
+        let functions: Vec<_> = violations
+            .iter()
+            .filter_map(|v| v.span.as_ref()?.function.as_deref())
+            .collect();
+        assert_eq!(functions, vec!["set_limit"], "{rule}: {violations:?}");
+    }
+}
+
+// ── 17. Auto-fix patches ──────────────────────────────────────────────────────
+
+#[test]
+fn fix_all_tags_patches_with_their_rule() {
+    let fixes = registry().fix_all(AUTH_GAP_CONTRACT);
+    let (rule, patch) = fixes
+        .iter()
+        .find(|(rule, _)| rule == "auth_gap")
+        .expect("auth_gap offers a require_auth patch");
+    assert_eq!(rule, "auth_gap");
+    assert!(patch.replacement.contains("require_auth"), "{patch:?}");
+    assert!(registry().fix_all(CLEAN_CONTRACT).is_empty());
+}
+
+#[test]
+fn fix_all_in_crate_reports_error_enums_of_other_files() {
+    let errors =
+        contract_errors("#[contracterror] #[derive(Copy, Clone)] pub enum Error { Overflow = 1 }");
+    let source = r#"
+    use crate::errors::Error;
+    #[contractimpl] impl Calc {
+        pub fn add(_env: Env, a: u64, b: u64) -> Result<u64, Error> { Ok(a + b) }
+    }
+"#;
+    assert!(!registry()
+        .fix_all(source)
+        .iter()
+        .any(|(rule, _)| rule == "arithmetic_overflow"));
+    let fixes = registry().fix_all_in_crate(source, &errors);
+    let (_, patch) = fixes
+        .iter()
+        .find(|(rule, _)| rule == "arithmetic_overflow")
+        .expect("arithmetic_overflow fixes through the other file's enum");
+    assert!(
+        patch.replacement.contains("ok_or(Error::Overflow)?"),
+        "{patch:?}"
+    );
+}
+
+// ── 18. Crate-wide call summaries ─────────────────────────────────────────────
+
+#[test]
+fn run_all_in_crate_follows_helpers_in_other_files() {
+    let entry = r#"
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+    #[contract] pub struct Vault;
+    #[contractimpl] impl Vault {
+        pub fn deposit(env: Env, from: Address, amount: i128) {
+            storage::write(&env, amount);
+        }
+    }
+"#;
+    let helpers = r#"
+    pub fn write(env: &Env, value: i128) {
+        env.storage().persistent().set(&KEY, &value);
+    }
+"#
+    .to_string();
+    let rules = |violations: &[RuleViolation]| {
+        rule_names_for(violations)
+            .into_iter()
+            .filter(|rule| *rule == "auth_gap" || *rule == "taint_propagation")
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(rules(&registry().run_all(entry)).is_empty());
+    let violations = registry().run_all_in_crate(entry, &[entry.to_string(), helpers]);
+    assert_eq!(rules(&violations), ["auth_gap", "taint_propagation"]);
+}
@@ -14,9 +14,10 @@
 #![cfg(feature = "smt")]
 
 use sanctifier_core::smt::{
-    parse_invariants, prove_fixed_point_mul_div_bounds,
-    prove_fixed_point_mul_div_bounds_with_backend, run_smt_latency_benchmark, verify_invariants,
-    FixedPointMulDivSpec, FixedPointProofError, SmtBackend, SmtConfig, SmtVerifier,
+    fixed_point_query, invariant_queries, parse_invariants, prove_fixed_point_mul_div_bounds,
+    prove_fixed_point_mul_div_bounds_with_backend, run_smt_latency_benchmark,
+    verify_invariant_preservation, verify_invariants, FixedPointMulDivSpec, FixedPointProofError,
+    SmtBackend, SmtConfig, SmtFindingKind, SmtLibError, SmtLibSolver, SmtVerifier,
 };
 
 // ── 1. types module: data types are constructable and serialisable ─────────────
@@ -41,6 +42,7 @@ fn fixed_point_mul_div_spec_is_constructable() {
         divisor_min: 1,
         divisor_max: 10,
         result_max: None,
+        product_max: None,
     };
     assert_eq!(spec.function_name, "test");
     assert_eq!(spec.divisor_min, 1);
@@ -96,7 +98,7 @@ fn parse_invariants_is_resilient_to_invalid_rust_source() {
 fn verify_invariants_returns_finding_for_addition_overflow() {
     let source = r#"
         impl Calc {
-            #[invariant = "a + b is safe"]
+            #[invariant = "a + b >= a"]
             pub fn add(&self, a: u64, b: u64) {}
         }
     "#;
@@ -131,7 +133,7 @@ fn verify_invariants_returns_no_finding_for_non_arithmetic_invariant() {
 fn verify_invariants_respects_timeout_config() {
     let source = r#"
         impl Vault {
-            #[invariant = "a + b is safe"]
+            #[invariant = "a + b >= a"]
             pub fn credit(&self, a: u64, b: u64) {}
         }
     "#;
@@ -152,14 +154,17 @@ fn verify_invariants_respects_timeout_config() {
 fn verify_invariants_detects_subtraction_underflow() {
     let source = r#"
         impl Vault {
-            #[invariant = "a - b does not underflow"]
+            #[invariant = "a - b <= a"]
             pub fn withdraw(&self, a: u64, b: u64) {}
         }
     "#;
     let findings = verify_invariants(source, &SmtConfig::default());
     assert_eq!(findings.len(), 1);
     assert!(
-        findings[0].counterexample.is_some(),
+        findings[0]
+            .counterexample
+            .as_ref()
+            .is_some_and(|c| c.contains("`a - b` underflows u64")),
         "underflow counterexample must be present"
     );
 }
@@ -197,6 +202,7 @@ fn fixed_point_proof_proves_safe_bounds() {
         divisor_min: 1,
         divisor_max: 1_000_000,
         result_max: Some(u128::MAX),
+        product_max: None,
     };
     let report = prove_fixed_point_mul_div_bounds(&spec).unwrap();
     assert!(report.proven_safe);
@@ -214,6 +220,7 @@ fn fixed_point_proof_finds_unsafe_bounds() {
         divisor_min: 1,
         divisor_max: 1,
         result_max: None,
+        product_max: None,
     };
     let report = prove_fixed_point_mul_div_bounds(&spec).unwrap();
     assert!(!report.proven_safe);
@@ -231,6 +238,7 @@ fn fixed_point_proof_rejects_zero_divisor_min() {
         divisor_min: 0,
         divisor_max: 10,
         result_max: None,
+        product_max: None,
     };
     assert_eq!(
         prove_fixed_point_mul_div_bounds(&spec).unwrap_err(),
@@ -239,7 +247,7 @@ fn fixed_point_proof_rejects_zero_divisor_min() {
 }
 
 #[test]
-fn fixed_point_proof_with_backend_rejects_cvc5() {
+fn fixed_point_proof_with_backend_runs_cvc5_as_a_process() {
     let spec = FixedPointMulDivSpec {
         function_name: "test".to_string(),
         multiplicand_max: 10,
@@ -247,10 +255,26 @@ fn fixed_point_proof_with_backend_rejects_cvc5() {
         divisor_min: 1,
         divisor_max: 10,
         result_max: None,
+        product_max: None,
     };
+    // Whether or not cvc5 is installed, the proof must go through the
+    // SMT-LIB2 driver instead of being rejected.
+    match prove_fixed_point_mul_div_bounds_with_backend(SmtBackend::Cvc5, &spec) {
+        Ok(report) => {
+            assert_eq!(report.backend, SmtBackend::Cvc5);
+            assert!(report.proven_safe);
+        }
+        Err(error) => assert!(
+            matches!(
+                error,
+                FixedPointProofError::SmtLib(SmtLibError::Spawn { .. })
+            ),
+            "unexpected error: {error}"
+        ),
+    }
     assert_eq!(
-        prove_fixed_point_mul_div_bounds_with_backend(SmtBackend::Cvc5, &spec).unwrap_err(),
-        FixedPointProofError::UnsupportedBackend(SmtBackend::Cvc5)
+        prove_fixed_point_mul_div_bounds_with_backend(SmtBackend::External, &spec).unwrap_err(),
+        FixedPointProofError::UnsupportedBackend(SmtBackend::External)
     );
 }
 
@@ -335,14 +359,99 @@ fn invariant_spec_location_matches_smt_finding_location() {
 fn smt_finding_invariant_name_matches_original_expression() {
     let source = r#"
         impl Token {
-            #[invariant = "balance + amount <= u64::MAX"]
+            #[invariant = "DataKey::Balance as u64 + amount <= u64::MAX"]
             pub fn mint(&self, amount: u64) {}
         }
     "#;
     let findings = verify_invariants(source, &SmtConfig::default());
     assert_eq!(findings.len(), 1);
     assert_eq!(
-        findings[0].invariant_name, "balance + amount <= u64::MAX",
+        findings[0].invariant_name, "DataKey::Balance as u64 + amount <= u64::MAX",
         "SmtFinding.invariant_name must preserve the original expression verbatim"
     );
 }
+
+// ── 8. symbolic module: verify_invariant_preservation boundary ───────────────
+
+#[test]
+fn verify_invariant_preservation_reports_path_and_serialises_sub_kind() {
+    let source = r#"
+        #[invariant = "DataKey::Reserve == sum(DataKey::Deposit)"]
+        impl Pool {
+            pub fn deposit(env: Env, from: Address, amount: i128) {
+                let current: i128 = env.storage().persistent().get(&DataKey::Deposit(from.clone())).unwrap_or(0);
+                env.storage().persistent().set(&DataKey::Deposit(from), &(current + amount));
+                if amount > 1000 {
+                    let reserve: i128 = env.storage().instance().get(&DataKey::Reserve).unwrap_or(0);
+                    env.storage().instance().set(&DataKey::Reserve, &(reserve + amount));
+                }
+            }
+        }
+    "#;
+    let findings = verify_invariant_preservation(source, &SmtConfig::default());
+    assert_eq!(findings.len(), 1);
+    let finding = &findings[0];
+    assert_eq!(finding.kind, SmtFindingKind::InvariantNotPreserved);
+    assert_eq!(finding.location, "deposit");
+    assert!(
+        finding
+            .path
+            .iter()
+            .any(|step| step == "assume !(amount > 1000)"),
+        "the violating path must record the branch taken: {:?}",
+        finding.path
+    );
+    assert!(finding.witness.contains_key("DataKey::Reserve (after)"));
+
+    let json = serde_json::to_value(finding).expect("SmtFinding must serialise");
+    assert_eq!(json["kind"], "invariant_not_preserved");
+    assert!(json["path"].is_array());
+
+    // Plain invariant checks keep the original sub-kind.
+    let plain = verify_invariants(
+        "impl V { #[invariant = \"a + b >= a\"] pub fn f(a: u64, b: u64) {} }",
+        &SmtConfig::default(),
+    );
+    assert_eq!(plain[0].kind, SmtFindingKind::InvariantViolation);
+}
+
+// ── 9. smtlib + process modules: solver-agnostic SMT-LIB2 boundary ───────────
+
+#[test]
+fn smtlib_queries_cover_invariants_and_fixed_point_proofs() {
+    let source = r#"
+        impl Token {
+            #[invariant = "DataKey::Supply >= amount"]
+            pub fn burn(amount: i128) {}
+        }
+    "#;
+    let queries = invariant_queries(source);
+    assert_eq!(queries.len(), parse_invariants(source).len());
+    assert_eq!(queries[0].name, "burn_invariant_0");
+    assert!(queries[0]
+        .script
+        .contains("(declare-const |DataKey::Supply| (_ BitVec 128))"));
+
+    let spec = FixedPointMulDivSpec {
+        function_name: "price_calc".to_string(),
+        multiplicand_max: 10,
+        multiplier_max: 10,
+        divisor_min: 1,
+        divisor_max: 10,
+        result_max: None,
+        product_max: None,
+    };
+    let query = fixed_point_query(&spec).unwrap();
+    assert!(query.script.contains("(set-logic QF_BV)"));
+    assert!(query.script.contains("(check-sat)"));
+}
+
+#[test]
+fn smtlib_solver_reports_a_missing_binary_as_an_error() {
+    let solver = SmtLibSolver::new("sanctifier-no-such-solver");
+    let error = solver
+        .verify_invariants("impl V { #[invariant = \"a > 0\"] pub fn f(a: u64) {} }")
+        .unwrap_err();
+    assert!(matches!(error, SmtLibError::Spawn { .. }));
+    assert!(error.to_string().contains("sanctifier-no-such-solver"));
+}
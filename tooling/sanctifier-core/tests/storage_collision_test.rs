@@ -16,8 +16,9 @@
 //! * **Enum-variant aliasing** — two `DataKey` variants that serialise to the
 //!   same byte string collide invisibly.
 //!
-//! The scanner is *intra-file* and *intra-storage-type*.  Cross-file collisions
-//! and cross-contract collisions are outside scope for this pass.
+//! The scanner is *intra-storage-type*.  `scan_storage_collisions` checks one
+//! file; `scan_storage_collisions_in_files` checks a crate or workspace as a
+//! whole, resolving key constants and key-constructor functions across files.
 //!
 //! ## Non-goals (by design)
 //! * The same key reused across *different* storage types (instance vs
@@ -188,3 +189,164 @@ fn temporary_storage_collision_detected() {
         "temporary storage collision should be detected"
     );
 }
+
+// ── Cross-file tests ─────────────────────────────────────────────────────────
+
+fn sources(files: &[(&str, &str)]) -> Vec<(String, String)> {
+    files
+        .iter()
+        .map(|(path, source)| (path.to_string(), source.to_string()))
+        .collect()
+}
+
+#[test]
+fn collision_across_files_reports_both_locations() {
+    let analyzer = Analyzer::new(SanctifyConfig::default());
+    let files = sources(&[
+        (
+            "src/staking.rs",
+            r#"
+            pub fn stake(env: &Env, amount: i128) {
+                env.storage().persistent().set(&"TOTAL", &amount);
+            }
+            "#,
+        ),
+        (
+            "src/rewards.rs",
+            r#"
+            pub fn reward(env: &Env, amount: i128) {
+                env.storage().persistent().set(&"TOTAL", &amount);
+            }
+            "#,
+        ),
+    ]);
+
+    // Neither file collides on its own.
+    for (_, source) in &files {
+        assert!(analyzer.scan_storage_collisions(source).is_empty());
+    }
+
+    let collisions = analyzer.scan_storage_collisions_in_files(&files);
+    assert_eq!(collisions.len(), 2, "got: {collisions:?}");
+    let staking = collisions
+        .iter()
+        .find(|c| c.location == "src/staking.rs:3")
+        .expect("finding located in staking.rs");
+    assert!(
+        staking.message.contains("src/rewards.rs:3"),
+        "message should name the other file: {}",
+        staking.message
+    );
+}
+
+#[test]
+fn key_constructors_and_constants_resolve_across_files() {
+    let analyzer = Analyzer::new(SanctifyConfig::default());
+    let files = sources(&[
+        (
+            "src/storage.rs",
+            r#"
+            pub const ADMIN: Symbol = symbol_short!("ADMIN");
+
+            pub fn balance_key(user: Address) -> DataKey {
+                DataKey::Balance(user)
+            }
+
+            // Copy-pasted helper that builds the same key as `balance_key`.
+            pub fn reward_key(user: Address) -> DataKey {
+                DataKey::Balance(user)
+            }
+            "#,
+        ),
+        (
+            "src/lib.rs",
+            r#"
+            #[contractimpl]
+            impl Token {
+                pub fn set_balance(env: Env, id: Address, amount: i128) {
+                    env.storage().persistent().set(&storage::balance_key(id), &amount);
+                }
+
+                pub fn set_reward(env: Env, id: Address, amount: i128) {
+                    env.storage().persistent().set(&storage::reward_key(id), &amount);
+                }
+
+                pub fn init(env: Env, admin: Address) {
+                    env.storage().instance().set(&storage::ADMIN, &admin);
+                }
+            }
+            "#,
+        ),
+    ]);
+
+    let collisions = analyzer.scan_storage_collisions_in_files(&files);
+    let persistent: Vec<_> = collisions
+        .iter()
+        .filter(|c| c.key_type.contains("persistent"))
+        .collect();
+    assert_eq!(persistent.len(), 2, "got: {collisions:?}");
+    assert!(persistent
+        .iter()
+        .all(|c| c.key_value.contains("DataKey :: Balance")));
+    // The single instance write through `ADMIN` does not collide.
+    assert!(!collisions.iter().any(|c| c.key_type.contains("instance")));
+}
+
+#[test]
+fn key_constructor_calls_with_different_arguments_do_not_collide() {
+    // `balance_key(from)` and `balance_key(to)` build different keys.
+    let analyzer = Analyzer::new(SanctifyConfig::default());
+    let files = sources(&[
+        (
+            "src/storage.rs",
+            r#"
+            pub fn balance_key(user: Address) -> DataKey {
+                DataKey::Balance(user)
+            }
+            "#,
+        ),
+        (
+            "src/lib.rs",
+            r#"
+            #[contractimpl]
+            impl Token {
+                pub fn transfer(env: Env, from: Address, to: Address, from_balance: i128, to_balance: i128) {
+                    env.storage().persistent().set(&storage::balance_key(from), &from_balance);
+                    env.storage().persistent().set(&storage::balance_key(to), &to_balance);
+                }
+            }
+            "#,
+        ),
+    ]);
+
+    let collisions = analyzer.scan_storage_collisions_in_files(&files);
+    assert!(collisions.is_empty(), "got: {collisions:?}");
+}
+
+#[test]
+fn ambiguous_key_constructors_are_left_unresolved() {
+    // Two crates of a workspace define `config_key` differently; calls to it
+    // cannot be attributed to either definition.
+    let analyzer = Analyzer::new(SanctifyConfig::default());
+    let files = sources(&[
+        (
+            "vault/src/keys.rs",
+            "pub fn config_key() -> Symbol { symbol_short!(\"VCFG\") }",
+        ),
+        (
+            "pool/src/keys.rs",
+            "pub fn config_key() -> Symbol { symbol_short!(\"PCFG\") }",
+        ),
+        (
+            "pool/src/lib.rs",
+            r#"
+            pub fn configure(env: Env, fee: u32) {
+                env.storage().instance().set(&config_key(), &fee);
+            }
+            "#,
+        ),
+    ]);
+
+    let collisions = analyzer.scan_storage_collisions_in_files(&files);
+    assert!(collisions.is_empty(), "got: {collisions:?}");
+}
@@ -42,7 +42,8 @@
 //!   format strings over trusted internal data; user-supplied source bytes
 //!   are never interpolated into a shell command or eval'd by the engine.
 
-use sanctifier_core::{finding_codes, Analyzer, SanctifyConfig};
+use sanctifier_core::finding_codes::FindingSeverity;
+use sanctifier_core::{finding_codes, Analyzer, RuleRegistry, SanctifyConfig};
 
 use crate::constants::{CACHE_NAMESPACE, SCHEMA_VERSION};
 use crate::converters;
@@ -60,7 +61,16 @@ const PROGRESS_PHASES: [(&str, u8); 5] = [
 
 // ── Internal helpers ───────────────────────────────────────────────────────────
 
-fn run_analysis(analyzer: &Analyzer, source: &str) -> AnalysisResult {
+/// Give `finding` the severity `registry` reports `rule` with, so a
+/// `[rules.<name>]` severity override applies to the pass backed by `rule`.
+fn rated(mut finding: Finding, registry: &RuleRegistry, rule: &str) -> Finding {
+    if let Some(severity) = registry.severity_of(rule) {
+        finding.severity = severity;
+    }
+    finding
+}
+
+fn run_analysis(analyzer: &Analyzer, registry: &RuleRegistry, source: &str) -> AnalysisResult {
     let auth_gaps = analyzer.scan_auth_gaps(source);
     let panic_issues = analyzer.scan_panics(source);
     let arithmetic_issues = analyzer.scan_arithmetic_overflow(source);
@@ -75,16 +85,28 @@ fn run_analysis(analyzer: &Analyzer, source: &str) -> AnalysisResult {
     let mut findings: Vec<Finding> = Vec::new();
 
     for g in &auth_gaps {
-        findings.push(converters::auth_gap(g.as_str()));
+        findings.push(rated(
+            converters::auth_gap(g.as_str()),
+            registry,
+            "auth_gap",
+        ));
     }
     for p in &panic_issues {
-        findings.push(converters::panic_issue(p));
+        findings.push(rated(
+            converters::panic_issue(p),
+            registry,
+            "panic_detection",
+        ));
     }
     for a in &arithmetic_issues {
-        findings.push(converters::arithmetic(a));
+        findings.push(rated(
+            converters::arithmetic(a),
+            registry,
+            "arithmetic_overflow",
+        ));
     }
     for w in &size_warnings {
-        findings.push(converters::size_warning(w));
+        findings.push(rated(converters::size_warning(w), registry, "ledger_size"));
     }
     for p in &unsafe_patterns {
         findings.push(converters::unsafe_pattern(p));
@@ -96,12 +118,17 @@ fn run_analysis(analyzer: &Analyzer, source: &str) -> AnalysisResult {
         findings.push(converters::event_issue(e));
     }
     for r in &unhandled_results {
-        findings.push(converters::unhandled_result(r));
+        findings.push(rated(
+            converters::unhandled_result(r),
+            registry,
+            "unhandled_result",
+        ));
     }
     for f in &upgrade_report.findings {
         findings.push(Finding {
             code: finding_codes::UPGRADE_RISK,
             category: "upgrades",
+            severity: converters::catalogue_severity(finding_codes::UPGRADE_RISK),
             message: f.message.clone(),
             location: Some(f.location.clone()),
         });
@@ -110,6 +137,7 @@ fn run_analysis(analyzer: &Analyzer, source: &str) -> AnalysisResult {
         findings.push(Finding {
             code: finding_codes::SEP41_INTERFACE_DEVIATION,
             category: "token_interface",
+            severity: converters::catalogue_severity(finding_codes::SEP41_INTERFACE_DEVIATION),
             message: issue.message.clone(),
             location: Some(issue.location.clone()),
         });
@@ -137,8 +165,12 @@ fn run_analysis(analyzer: &Analyzer, source: &str) -> AnalysisResult {
         unhandled_results: unhandled_results.len(),
         upgrade_risks: upgrade_report.findings.len(),
         sep41_issues: sep41_report.issues.len(),
-        has_critical: false,
-        has_high: !auth_gaps.is_empty() || !upgrade_report.findings.is_empty(),
+        has_critical: findings
+            .iter()
+            .any(|f| f.severity == FindingSeverity::Critical),
+        has_high: findings
+            .iter()
+            .any(|f| f.severity.rank() >= FindingSeverity::High.rank()),
     };
 
     AnalysisResult {
@@ -165,31 +197,40 @@ fn build_progress_events(total_findings: usize) -> Vec<ProgressEvent> {
 /// Run all analysis passes with `SanctifyConfig::default()`.
 pub fn run_analysis_default(source: &str) -> AnalysisResult {
     let analyzer = Analyzer::new(SanctifyConfig::default());
-    run_analysis(&analyzer, source)
+    run_analysis(&analyzer, &RuleRegistry::with_default_rules(), source)
 }
 
 /// Run all analysis passes, deserialising config from JSON (falls back to
-/// `SanctifyConfig::default()` if parsing fails).
+/// `SanctifyConfig::default()` if parsing fails).  The config's `rules`
+/// tables apply as in the CLI: passes backed by a disabled rule are skipped
+/// and severity overrides replace the catalogue severity.  Invalid tables
+/// fall back to the defaults; [`crate::validation::validate_config_json`]
+/// reports them.
 pub fn run_analysis_with_config(config_json: &str, source: &str) -> AnalysisResult {
     let config: SanctifyConfig = serde_json::from_str(config_json).unwrap_or_default();
+    let registry = RuleRegistry::from_config(&config).unwrap_or_default();
     let analyzer = Analyzer::new(config);
-    run_analysis(&analyzer, source)
+    run_analysis(&analyzer, &registry, source)
 }
 
 /// Run all passes and bundle the result with deterministic progress events.
 pub fn run_analysis_with_progress(source: &str) -> ProgressiveAnalysisResult {
     let analyzer = Analyzer::new(SanctifyConfig::default());
-    let result = run_analysis(&analyzer, source);
+    let result = run_analysis(&analyzer, &RuleRegistry::with_default_rules(), source);
     let events = build_progress_events(result.summary.total);
     ProgressiveAnalysisResult { events, result }
 }
 
-/// Return a deterministic cache-bust key (`namespace:pkg_version:schema_version`).
+/// Return a deterministic cache-bust key
+/// (`namespace:pkg_version:engine_fingerprint:schema_version`).  The engine
+/// fingerprint is the one the CLI's result cache keys on, so a rule change
+/// busts both even when no version number moves.
 pub fn build_cache_key() -> String {
     format!(
-        "{}:{}:{}",
+        "{}:{}:{}:{}",
         CACHE_NAMESPACE,
         env!("CARGO_PKG_VERSION"),
+        &sanctifier_core::ENGINE_FINGERPRINT[..16],
         SCHEMA_VERSION
     )
 }
@@ -209,9 +250,10 @@ mod tests {
     }
 
     #[test]
-    fn cache_key_has_three_colon_delimited_segments() {
+    fn cache_key_has_four_colon_delimited_segments() {
         let key = build_cache_key();
-        assert_eq!(key.splitn(3, ':').count(), 3);
+        assert_eq!(key.split(':').count(), 4);
+        assert!(sanctifier_core::ENGINE_FINGERPRINT.starts_with(key.split(':').nth(2).unwrap()));
     }
 
     #[test]
@@ -245,7 +287,7 @@ mod tests {
         let source_code = "fn buggy_func() { panic!(\"error\"); }";
         let result = run_analysis_default(source_code);
         // We assert that the findings include some location info that could be mapped via source-maps
-        assert!(result.summary.total >= 0); // Just a sanity check for the fixture
+        assert_eq!(result.summary.total, result.findings.len()); // Just a sanity check for the fixture
     }
 
     // ── Determinism tests (Issue #544) ────────────────────────────────────────
@@ -283,6 +325,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn run_analysis_with_config_skips_disabled_rules() {
+        let source = r#"
+            use soroban_sdk::{contract, contractimpl, Env};
+            #[contract] pub struct C;
+            #[contractimpl] impl C {
+                pub fn transfer(env: Env, x: i128) -> i128 { x + 1 }
+            }
+        "#;
+        let enabled = run_analysis_with_config("{}", source);
+        assert!(enabled.summary.arithmetic_issues > 0);
+
+        let config = r#"{ "rules": { "arithmetic_overflow": { "enabled": false } } }"#;
+        let disabled = run_analysis_with_config(config, source);
+        assert_eq!(disabled.summary.arithmetic_issues, 0);
+        assert!(disabled
+            .findings
+            .iter()
+            .all(|f| f.code != finding_codes::ARITHMETIC_OVERFLOW));
+    }
+
+    #[test]
+    fn run_analysis_with_config_applies_severity_overrides() {
+        let source = r#"
+            use soroban_sdk::{contract, contractimpl, Env};
+            #[contract] pub struct C;
+            #[contractimpl] impl C {
+                pub fn transfer(env: Env, x: i128) -> i128 { x + 1 }
+            }
+        "#;
+        let arithmetic = |result: &AnalysisResult| {
+            result
+                .findings
+                .iter()
+                .filter(|f| f.code == finding_codes::ARITHMETIC_OVERFLOW)
+                .map(|f| f.severity)
+                .collect::<Vec<_>>()
+        };
+
+        let default = run_analysis_with_config("{}", source);
+        let catalogue = converters::catalogue_severity(finding_codes::ARITHMETIC_OVERFLOW);
+        assert!(!arithmetic(&default).is_empty());
+        assert!(arithmetic(&default).iter().all(|s| *s == catalogue));
+
+        let config = r#"{ "rules": { "arithmetic_overflow": { "severity": "critical" } } }"#;
+        let raised = run_analysis_with_config(config, source);
+        assert!(arithmetic(&raised)
+            .iter()
+            .all(|s| *s == FindingSeverity::Critical));
+        assert!(raised.summary.has_critical);
+
+        let config = r#"{ "rules": { "arithmetic_overflow": { "severity": "info" } } }"#;
+        let lowered = run_analysis_with_config(config, source);
+        assert!(arithmetic(&lowered)
+            .iter()
+            .all(|s| *s == FindingSeverity::Info));
+    }
+
     #[test]
     fn findings_are_sorted_by_code_then_message() {
         let source = r#"
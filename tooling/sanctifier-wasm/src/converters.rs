@@ -5,6 +5,7 @@
 //! here rather than inlining them into the analysis module makes it easy to
 //! audit the mapping between internal codes and output messages in one place.
 
+use sanctifier_core::finding_codes::FindingSeverity;
 use sanctifier_core::{
     finding_codes, ArithmeticIssue, EventIssue, PanicIssue, SizeWarning, StorageCollisionIssue,
     UnhandledResultIssue, UnsafePattern,
@@ -12,10 +13,18 @@ use sanctifier_core::{
 
 use crate::types::Finding;
 
+/// Severity the finding-code catalogue gives `code`.
+pub fn catalogue_severity(code: &str) -> FindingSeverity {
+    finding_codes::lookup_finding_code(code)
+        .map(|entry| entry.severity)
+        .unwrap_or_default()
+}
+
 pub fn auth_gap(function_name: &str) -> Finding {
     Finding {
         code: finding_codes::AUTH_GAP,
         category: "authentication",
+        severity: catalogue_severity(finding_codes::AUTH_GAP),
         message: format!("Missing authentication guard in `{}`", function_name),
         location: Some(function_name.to_string()),
     }
@@ -25,6 +34,7 @@ pub fn panic_issue(p: &PanicIssue) -> Finding {
     Finding {
         code: finding_codes::PANIC_USAGE,
         category: "panic_handling",
+        severity: catalogue_severity(finding_codes::PANIC_USAGE),
         message: format!("`{}` usage in `{}`", p.issue_type, p.function_name),
         location: Some(p.location.clone()),
     }
@@ -34,6 +44,7 @@ pub fn arithmetic(a: &ArithmeticIssue) -> Finding {
     Finding {
         code: finding_codes::ARITHMETIC_OVERFLOW,
         category: "arithmetic",
+        severity: catalogue_severity(finding_codes::ARITHMETIC_OVERFLOW),
         message: format!(
             "Unchecked `{}` in `{}` — {}",
             a.operation, a.function_name, a.suggestion
@@ -46,6 +57,7 @@ pub fn size_warning(w: &SizeWarning) -> Finding {
     Finding {
         code: finding_codes::LEDGER_SIZE_RISK,
         category: "storage_limits",
+        severity: catalogue_severity(finding_codes::LEDGER_SIZE_RISK),
         message: format!(
             "`{}` estimated size {}B approaches/exceeds ledger limit {}B",
             w.struct_name, w.estimated_size, w.limit
@@ -58,6 +70,7 @@ pub fn unsafe_pattern(p: &UnsafePattern) -> Finding {
     Finding {
         code: finding_codes::UNSAFE_PATTERN,
         category: "unsafe_patterns",
+        severity: catalogue_severity(finding_codes::UNSAFE_PATTERN),
         message: format!("{:?} at line {}: {}", p.pattern_type, p.line, p.snippet),
         location: Some(format!("line:{}", p.line)),
     }
@@ -67,6 +80,7 @@ pub fn storage_collision(c: &StorageCollisionIssue) -> Finding {
     Finding {
         code: finding_codes::STORAGE_COLLISION,
         category: "storage_keys",
+        severity: catalogue_severity(finding_codes::STORAGE_COLLISION),
         message: c.message.clone(),
         location: Some(c.location.clone()),
     }
@@ -76,6 +90,7 @@ pub fn event_issue(e: &EventIssue) -> Finding {
     Finding {
         code: finding_codes::EVENT_INCONSISTENCY,
         category: "events",
+        severity: catalogue_severity(finding_codes::EVENT_INCONSISTENCY),
         message: e.message.clone(),
         location: Some(e.location.clone()),
     }
@@ -85,6 +100,7 @@ pub fn unhandled_result(r: &UnhandledResultIssue) -> Finding {
     Finding {
         code: finding_codes::UNHANDLED_RESULT,
         category: "logic",
+        severity: catalogue_severity(finding_codes::UNHANDLED_RESULT),
         message: r.message.clone(),
         location: Some(r.location.clone()),
     }
@@ -64,7 +64,7 @@ fn make_error(error_code: &str, message: String) -> JsValue {
 /// Returns a JS object shaped as [`AnalysisResult`]:
 /// ```json
 /// {
-///   "findings": [{ "code": "S001", "category": "...", "message": "...", "location": "..." }],
+///   "findings": [{ "code": "S001", "category": "...", "severity": "Critical", "message": "...", "location": "..." }],
 ///   "summary":  { "total": 3, "has_critical": false, "has_high": true, ... },
 ///   "schema_version": "1.0.0"
 /// }
@@ -94,7 +94,8 @@ pub fn analyze(source: &str) -> JsValue {
 /// Falls back to `SanctifyConfig::default()` if `config_json` cannot be parsed.
 ///
 /// # Errors
-/// Returns an [`ErrorResponse`] object if input validation fails.
+/// Returns an [`ErrorResponse`] object if input validation fails, including
+/// an `INVALID_CONFIG` error for a `rules` table the CLI would reject.
 #[wasm_bindgen]
 pub fn analyze_with_config(config_json: &str, source: &str) -> JsValue {
     set_panic_hook();
@@ -164,7 +165,8 @@ pub fn default_config_json() -> String {
 /// Return a deterministic cache key for wasm module assets.
 ///
 /// Frontend loaders use this to bust stale service-worker and CacheStorage
-/// entries whenever the package or schema version changes.
+/// entries whenever the package or schema version, or the analysis engine
+/// itself, changes.
 #[wasm_bindgen]
 pub fn asset_cache_key() -> String {
     analysis::build_cache_key()
@@ -189,7 +191,6 @@ mod tests {
     use super::*;
     use crate::constants::{MAX_SOURCE_SIZE, MEMORY_BUDGET_BYTES, MEMORY_OVERHEAD_FACTOR};
     use crate::validation::{check_memory_budget, validate_source};
-    use serde_json;
 
     // ── validate_source ───────────────────────────────────────────────────────
 
@@ -5,6 +5,7 @@
 //! the `sanctifier-core` types so the WASM public API surface can evolve
 //! independently of internal analyser structures.
 
+use sanctifier_core::finding_codes::FindingSeverity;
 use serde::Serialize;
 
 /// Error response for validation or processing failures.
@@ -29,6 +30,9 @@ pub struct Finding {
     pub code: &'static str,
     /// Broad category string (matches the finding-code catalogue).
     pub category: &'static str,
+    /// Catalogue severity of `code`, or the `[rules.<name>]` override of
+    /// the rule behind the pass that reported it.
+    pub severity: FindingSeverity,
     /// Human-readable description of the issue.
     pub message: String,
     /// Source location string when available (e.g. `"function_name:line"`).
@@ -10,6 +10,7 @@ use crate::constants::{
     MEMORY_BUDGET_BYTES, MEMORY_BUDGET_BYTES_BROWSER, MEMORY_BUDGET_BYTES_NODE,
     MEMORY_OVERHEAD_FACTOR, MIN_SOURCE_SIZE,
 };
+use sanctifier_core::{RuleRegistry, SanctifyConfig};
 
 /// Deployment target for target-aware validation.
 ///
@@ -66,13 +67,17 @@ pub fn check_memory_budget(source_len: usize) -> Result<(), String> {
     Ok(())
 }
 
-/// Validate configuration JSON against size limits.
+/// Validate configuration JSON against size limits and check its
+/// `[rules.<name>]` tables the way the CLI does.
 ///
-/// An empty or whitespace-only string is accepted (caller falls back to defaults).
+/// An empty or whitespace-only string is accepted (caller falls back to defaults),
+/// and so is JSON that does not parse as a config.
 ///
 /// # Errors
 /// - `"Configuration JSON exceeds maximum size of 1 MB"` — when
 ///   `config_json.len() > MAX_CONFIG_SIZE`.
+/// - The rule configuration error — when a `rules` table names an unknown
+///   rule or sets an option the rule does not understand.
 pub fn validate_config_json(config_json: &str) -> Result<(), String> {
     if config_json.trim().is_empty() {
         return Ok(());
@@ -85,6 +90,10 @@ pub fn validate_config_json(config_json: &str) -> Result<(), String> {
         ));
     }
 
+    if let Ok(config) = serde_json::from_str::<SanctifyConfig>(config_json) {
+        RuleRegistry::from_config(&config).map_err(|err| err.to_string())?;
+    }
+
     Ok(())
 }
 
@@ -212,6 +221,15 @@ mod tests {
         assert!(validate_config_json(&"x".repeat(MAX_CONFIG_SIZE)).is_ok());
     }
 
+    #[test]
+    fn validate_config_rejects_invalid_rule_tables() {
+        assert!(validate_config_json(r#"{ "rules": { "auth_gap": { "severity": "low" } } }"#).is_ok());
+        let unknown = validate_config_json(r#"{ "rules": { "no_such_rule": {} } }"#);
+        assert!(unknown.unwrap_err().contains("no_such_rule"));
+        let option = validate_config_json(r#"{ "rules": { "auth_gap": { "threshold": 3 } } }"#);
+        assert!(option.unwrap_err().contains("auth_gap"));
+    }
+
     // ── validate_for_target (node vs browser parity) ──────────────────────────
 
     #[test]